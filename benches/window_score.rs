@@ -0,0 +1,18 @@
+#![feature(test)]
+
+extern crate test;
+
+use test::Bencher;
+
+use bio::alignment::pairwise::MatchParams;
+use bio::alignment::window_score::{QueryProfile, WindowScorer};
+
+static QUERY: &[u8] = b"GACGCATAGCCTAGCGATCG";
+
+#[bench]
+fn bench_score_windows(b: &mut Bencher) {
+    let text = b"ATCTAACTATTCCCTGTGCCTTATGGGGGCCTGCGCTATCTGCCTGT".repeat(1000);
+    let profile = QueryProfile::new(QUERY, &MatchParams::new(1, -1));
+    let mut scorer = WindowScorer::new();
+    b.iter(|| scorer.score_windows(&profile, &text).len());
+}
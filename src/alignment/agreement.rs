@@ -0,0 +1,227 @@
+//! Agreement between two alignments of the same pair of sequences, e.g. for benchmarking
+//! heuristic (banded, SIMD, ...) aligners against the exact dynamic programming solution.
+//!
+//! Alignments are compared by their columns: a matched or substituted pair of positions
+//! `(i, j)` in x and y, an insertion of `x[i]` or a deletion of `y[j]`. Clip operations do not
+//! form columns. The positions of aligned pairs are independent of how gaps are placed in
+//! between, which makes the aligned pairs the usual basis of alignment accuracy measures
+//! (e.g. the sum-of-pairs score, Thompson et al., 1999).
+//!
+//! # Example
+//!
+//! ```
+//! use bio::alignment::agreement::agreement;
+//! use bio::alignment::pairwise::{banded, Aligner};
+//!
+//! let x = b"ACCGTGGATGGGCGCGATAGCGAGAGC";
+//! let y = b"AAAAACCGTTGATGGGCGCGATTAGCGAGAGC";
+//! let score = |a: u8, b: u8| if a == b { 1i32 } else { -1i32 };
+//! let exact = Aligner::new(-5, -1, score).global(x, y);
+//! let banded = banded::Aligner::new(-5, -1, score, 4, 6)
+//!     .global(x, y)
+//!     .unwrap();
+//!
+//! let agreement = agreement(&banded, &exact);
+//! assert!(agreement.pair_jaccard() > 0.9);
+//! assert!(agreement.sum_of_pairs() > 0.9);
+//! ```
+
+use std::collections::HashSet;
+
+use crate::alignment::{Alignment, AlignmentOperation};
+
+/// A column of an alignment, given by the aligned positions in x and y (`None` for a gap).
+type Column = (Option<usize>, Option<usize>);
+
+/// The columns of an alignment.
+fn columns(alignment: &Alignment) -> Vec<Column> {
+    let (mut i, mut j) = (alignment.xstart, alignment.ystart);
+    let mut columns = Vec::with_capacity(alignment.operations.len());
+    for op in &alignment.operations {
+        match op {
+            AlignmentOperation::Match | AlignmentOperation::Subst => {
+                columns.push((Some(i), Some(j)));
+                i += 1;
+                j += 1;
+            }
+            AlignmentOperation::Ins => {
+                columns.push((Some(i), None));
+                i += 1;
+            }
+            AlignmentOperation::Del => {
+                columns.push((None, Some(j)));
+                j += 1;
+            }
+            AlignmentOperation::Xclip(_) | AlignmentOperation::Yclip(_) => (),
+        }
+    }
+    columns
+}
+
+/// The pairs of positions in x and y aligned (as match or substitution) by the given
+/// alignment, in increasing order.
+///
+/// # Example
+///
+/// ```
+/// use bio::alignment::agreement::aligned_pairs;
+/// use bio::alignment::{Alignment, AlignmentOperation::*};
+///
+/// let alignment = Alignment {
+///     xstart: 1,
+///     xend: 4,
+///     yend: 3,
+///     xlen: 4,
+///     ylen: 3,
+///     operations: vec![Match, Ins, Subst, Del],
+///     ..Default::default()
+/// };
+/// assert_eq!(aligned_pairs(&alignment), [(1, 0), (3, 1)]);
+/// ```
+pub fn aligned_pairs(alignment: &Alignment) -> Vec<(usize, usize)> {
+    columns(alignment)
+        .into_iter()
+        .filter_map(|column| match column {
+            (Some(i), Some(j)) => Some((i, j)),
+            _ => None,
+        })
+        .collect()
+}
+
+/// Counts of shared and total columns and aligned pairs of two alignments `a` and `b`
+/// (see `agreement`). Ratios of empty sets are defined as 1.
+#[derive(
+    Default, Copy, Clone, Eq, PartialEq, Ord, PartialOrd, Hash, Debug, Serialize, Deserialize,
+)]
+pub struct Agreement {
+    /// Number of aligned pairs in both alignments.
+    pub shared_pairs: usize,
+    pub pairs_a: usize,
+    pub pairs_b: usize,
+    /// Number of columns (including gaps) in both alignments.
+    pub shared_columns: usize,
+    pub columns_a: usize,
+    pub columns_b: usize,
+}
+
+fn ratio(numerator: usize, denominator: usize) -> f64 {
+    if denominator == 0 {
+        1.0
+    } else {
+        numerator as f64 / denominator as f64
+    }
+}
+
+impl Agreement {
+    /// Jaccard index of the aligned pairs, i.e. shared pairs over pairs aligned by any of both.
+    pub fn pair_jaccard(&self) -> f64 {
+        ratio(
+            self.shared_pairs,
+            self.pairs_a + self.pairs_b - self.shared_pairs,
+        )
+    }
+
+    /// Fraction of the columns of the longer alignment that occur identically in the other.
+    pub fn column_identity(&self) -> f64 {
+        ratio(self.shared_columns, self.columns_a.max(self.columns_b))
+    }
+
+    /// Fraction of the aligned pairs of `b` that are recovered by `a` (the sum-of-pairs
+    /// score, or sensitivity, if `b` is the reference alignment).
+    pub fn sum_of_pairs(&self) -> f64 {
+        ratio(self.shared_pairs, self.pairs_b)
+    }
+
+    /// Fraction of the aligned pairs of `a` that also occur in `b` (the precision, if `b` is
+    /// the reference alignment).
+    pub fn precision(&self) -> f64 {
+        ratio(self.shared_pairs, self.pairs_a)
+    }
+}
+
+/// Compare two alignments of the same pair of sequences x and y.
+/// Complexity: O(n), where n is the number of alignment operations.
+///
+/// # Panics
+///
+/// Panics if the alignments are not of sequences with the same lengths.
+pub fn agreement(a: &Alignment, b: &Alignment) -> Agreement {
+    assert!(
+        a.xlen == b.xlen && a.ylen == b.ylen,
+        "Expecting alignments of the same pair of sequences."
+    );
+    let (columns_a, columns_b) = (columns(a), columns(b));
+    let shared = columns_a.iter().collect::<HashSet<_>>();
+    let shared = columns_b
+        .iter()
+        .filter(|column| shared.contains(column))
+        .collect::<Vec<_>>();
+    let is_pair = |column: &&Column| column.0.is_some() && column.1.is_some();
+
+    Agreement {
+        shared_pairs: shared.iter().filter(|column| is_pair(column)).count(),
+        pairs_a: columns_a.iter().filter(is_pair).count(),
+        pairs_b: columns_b.iter().filter(is_pair).count(),
+        shared_columns: shared.len(),
+        columns_a: columns_a.len(),
+        columns_b: columns_b.len(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::alignment::AlignmentMode;
+    use crate::alignment::AlignmentOperation::*;
+
+    fn alignment(operations: Vec<AlignmentOperation>) -> Alignment {
+        Alignment {
+            score: 0,
+            xstart: 0,
+            ystart: 0,
+            xend: 4,
+            yend: 4,
+            xlen: 4,
+            ylen: 4,
+            operations,
+            mode: AlignmentMode::Global,
+        }
+    }
+
+    #[test]
+    fn test_shifted_gap() {
+        // x = ACGT, y = AGGT with the gaps placed differently
+        let a = alignment(vec![Match, Ins, Del, Match, Match]);
+        let b = alignment(vec![Match, Del, Ins, Match, Match]);
+        let agreement = agreement(&a, &b);
+        assert_eq!(agreement.shared_pairs, 3);
+        assert_eq!(agreement.pair_jaccard(), 1.0);
+        assert_eq!(agreement.shared_columns, 5);
+
+        let c = alignment(vec![Match, Subst, Match, Match]);
+        let agreement = super::agreement(&a, &c);
+        assert_eq!((agreement.pairs_a, agreement.pairs_b), (3, 4));
+        assert_eq!(agreement.pair_jaccard(), 0.75);
+        assert_eq!(agreement.column_identity(), 0.6);
+        assert_eq!(agreement.sum_of_pairs(), 0.75);
+        assert_eq!(agreement.precision(), 1.0);
+    }
+
+    #[test]
+    fn test_disjoint() {
+        let a = alignment(vec![Ins, Ins, Ins, Ins, Del, Del, Del, Del]);
+        let b = alignment(vec![Match, Match, Match, Match]);
+        let agreement = agreement(&a, &b);
+        assert_eq!(agreement.pair_jaccard(), 0.0);
+        assert_eq!(agreement.column_identity(), 0.0);
+        assert_eq!(agreement.precision(), 1.0);
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_different_sequences() {
+        let mut b = alignment(vec![]);
+        b.xlen = 5;
+        agreement(&alignment(vec![]), &b);
+    }
+}
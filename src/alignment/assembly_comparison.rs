@@ -0,0 +1,403 @@
+//! Comparison of two genome assemblies, summarizing the aligned fraction of each assembly,
+//! the SNPs and indels within aligned regions, and the unaligned regions, similar to the
+//! report of MUMmer's `dnadiff`.
+//!
+//! Anchors are found as maximal runs of k-mer matches on the same diagonal (on both strands),
+//! using only k-mers that are unique in the second assembly, which avoids spurious anchors
+//! in repeats. The anchors are chained into synteny blocks (see `alignment::synteny`), and each
+//! block is aligned globally with the banded aligner to count the differences. Blocks whose
+//! band would exceed the cell limit (see `AssemblyComparison::set_max_cells`) are not aligned,
+//! and reported as unaligned instead.
+//!
+//! # Example
+//!
+//! ```
+//! use bio::alignment::assembly_comparison::AssemblyComparison;
+//!
+//! let x = b"ACGTTGCAAGCTGACCTAGGCATCGATCGGATCGATTACAGGCTTACGATCGATGCATGCAAGTC";
+//! // a SNP and a deleted base
+//! let y = b"ACGTTGCAAGCTGACCTAGGCATCGTTCGGATCGATTACAGGCTACGATCGATGCATGCAAGTC";
+//! let report = AssemblyComparison::new(8, 20, 30).compare(&[x], &[y]);
+//! assert_eq!(report.snps, 1);
+//! assert_eq!(report.insertions, 1);
+//! assert_eq!(report.inserted_bases, 1);
+//! assert_eq!(report.x_aligned, x.len());
+//! assert!(report.x_unaligned.is_empty());
+//! ```
+
+use std::collections::HashMap;
+use std::ops::Range;
+
+use crate::alignment::pairwise::banded;
+use crate::alignment::sparse::HashMapFx;
+use crate::alignment::synteny::{Anchor, SyntenyBlock, SyntenyChainer};
+use crate::alignment::AlignmentOperation;
+use crate::alphabets::dna;
+use bio_types::strand::ReqStrand;
+
+/// Summary of the comparison of two assemblies `x` and `y`.
+#[derive(Default, Clone, Eq, PartialEq, Hash, Debug, Serialize, Deserialize)]
+pub struct ComparisonReport {
+    /// The aligned blocks (see `SyntenyChainer::blocks`).
+    pub blocks: Vec<SyntenyBlock>,
+    /// The blocks that were not aligned because their band exceeded the cell limit. Their
+    /// regions are not counted as aligned.
+    pub unaligned_blocks: Vec<SyntenyBlock>,
+    /// Total length of `x`.
+    pub x_len: usize,
+    /// Number of bases of `x` covered by aligned blocks.
+    pub x_aligned: usize,
+    /// Total length of `y`.
+    pub y_len: usize,
+    /// Number of bases of `y` covered by aligned blocks.
+    pub y_aligned: usize,
+    /// Number of substitutions in the aligned blocks.
+    pub snps: usize,
+    /// Number of insertions in `x` relative to `y` in the aligned blocks.
+    pub insertions: usize,
+    /// Number of bases inserted in `x` relative to `y`.
+    pub inserted_bases: usize,
+    /// Number of deletions from `x` relative to `y` in the aligned blocks.
+    pub deletions: usize,
+    /// Number of bases deleted from `x` relative to `y`.
+    pub deleted_bases: usize,
+    /// Regions of `x` not covered by any block, as pairs of sequence index and interval.
+    pub x_unaligned: Vec<(usize, Range<usize>)>,
+    /// Regions of `y` not covered by any block, as pairs of sequence index and interval.
+    pub y_unaligned: Vec<(usize, Range<usize>)>,
+}
+
+impl ComparisonReport {
+    /// Fraction of `x` covered by aligned blocks.
+    pub fn x_aligned_fraction(&self) -> f64 {
+        self.x_aligned as f64 / self.x_len.max(1) as f64
+    }
+
+    /// Fraction of `y` covered by aligned blocks.
+    pub fn y_aligned_fraction(&self) -> f64 {
+        self.y_aligned as f64 / self.y_len.max(1) as f64
+    }
+}
+
+/// Comparison of assemblies, given as slices of sequences (e.g. contigs or chromosomes).
+#[derive(Copy, Clone, Eq, PartialEq, Ord, PartialOrd, Hash, Debug, Serialize, Deserialize)]
+pub struct AssemblyComparison {
+    k: usize,
+    max_gap: usize,
+    min_block_len: usize,
+    max_cells: Option<usize>,
+    match_score: i32,
+    mismatch_score: i32,
+    gap_open: i32,
+    gap_extend: i32,
+}
+
+impl AssemblyComparison {
+    /// Create a new comparison, with anchors built from k-mers of length `k`, and blocks of at
+    /// least `min_block_len` bases in `x`, with gaps of at most `max_gap` between anchors.
+    /// Blocks are aligned with match score 1, mismatch score -1, gap open penalty -5 and gap
+    /// extend penalty -1 (see `set_scoring`), and at most `banded::MAX_CELLS` cells per block
+    /// (see `set_max_cells`).
+    ///
+    /// # Panics
+    ///
+    /// Panics if `k` is zero.
+    pub fn new(k: usize, max_gap: usize, min_block_len: usize) -> Self {
+        assert!(k > 0, "Expecting k > 0.");
+        AssemblyComparison {
+            k,
+            max_gap,
+            min_block_len,
+            max_cells: Some(banded::MAX_CELLS),
+            match_score: 1,
+            mismatch_score: -1,
+            gap_open: -5,
+            gap_extend: -1,
+        }
+    }
+
+    /// Set the scores used for aligning the blocks, with `gap_open` and `gap_extend` being
+    /// negative.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use bio::alignment::assembly_comparison::AssemblyComparison;
+    ///
+    /// let mut comparison = AssemblyComparison::new(8, 20, 30);
+    /// comparison.set_scoring(2, -3, -8, -2);
+    /// ```
+    pub fn set_scoring(
+        &mut self,
+        match_score: i32,
+        mismatch_score: i32,
+        gap_open: i32,
+        gap_extend: i32,
+    ) {
+        self.match_score = match_score;
+        self.mismatch_score = mismatch_score;
+        self.gap_open = gap_open;
+        self.gap_extend = gap_extend;
+    }
+
+    /// Set the maximum number of cells in the band of a block, or `None` for no limit (see
+    /// `banded::Aligner::set_max_cells`). Blocks exceeding it are not aligned and listed in
+    /// `ComparisonReport::unaligned_blocks`, their regions being reported as unaligned.
+    pub fn set_max_cells(&mut self, max_cells: Option<usize>) {
+        self.max_cells = max_cells;
+    }
+
+    /// Compare the assemblies `x` and `y`.
+    pub fn compare<T, U>(&self, x: &[T], y: &[U]) -> ComparisonReport
+    where
+        T: AsRef<[u8]>,
+        U: AsRef<[u8]>,
+    {
+        let anchors = self.anchors(x, y);
+        let mut blocks = SyntenyChainer::new(self.max_gap, 1).blocks(&anchors);
+        blocks.retain(|block| block.x.len() >= self.min_block_len);
+
+        let mut report = ComparisonReport {
+            x_len: x.iter().map(|seq| seq.as_ref().len()).sum(),
+            y_len: y.iter().map(|seq| seq.as_ref().len()).sum(),
+            ..Default::default()
+        };
+        let (match_score, mismatch_score) = (self.match_score, self.mismatch_score);
+        let score = |a: u8, b: u8| if a == b { match_score } else { mismatch_score };
+        let mut aligner = banded::Aligner::new(
+            self.gap_open,
+            self.gap_extend,
+            score,
+            self.k,
+            self.max_gap.max(1),
+        );
+        aligner.set_max_cells(self.max_cells);
+        let mut aligned_blocks = Vec::with_capacity(blocks.len());
+        for block in blocks {
+            let x_block = &x[block.x_seq].as_ref()[block.x.clone()];
+            let y_block = &y[block.y_seq].as_ref()[block.y.clone()];
+            let result = if block.strand == ReqStrand::Reverse {
+                aligner.global(x_block, &dna::revcomp(y_block))
+            } else {
+                aligner.global(x_block, y_block)
+            };
+            let alignment = match result {
+                Ok(alignment) => alignment,
+                Err(banded::Error::BandTooLarge { .. }) => {
+                    report.unaligned_blocks.push(block);
+                    continue;
+                }
+            };
+            aligned_blocks.push(block);
+
+            let mut prev = None;
+            for &op in &alignment.operations {
+                match op {
+                    AlignmentOperation::Subst => report.snps += 1,
+                    AlignmentOperation::Ins => {
+                        report.insertions += (prev != Some(op)) as usize;
+                        report.inserted_bases += 1;
+                    }
+                    AlignmentOperation::Del => {
+                        report.deletions += (prev != Some(op)) as usize;
+                        report.deleted_bases += 1;
+                    }
+                    _ => (),
+                }
+                prev = Some(op);
+            }
+        }
+
+        let blocks = aligned_blocks;
+        let (x_covered, y_covered) = (
+            covered(x, blocks.iter().map(|block| (block.x_seq, block.x.clone()))),
+            covered(y, blocks.iter().map(|block| (block.y_seq, block.y.clone()))),
+        );
+        report.x_aligned = x_covered.iter().map(|(_, range)| range.len()).sum();
+        report.y_aligned = y_covered.iter().map(|(_, range)| range.len()).sum();
+        report.x_unaligned = complement(x, &x_covered);
+        report.y_unaligned = complement(y, &y_covered);
+        report.blocks = blocks;
+        report
+    }
+
+    /// Find the maximal runs of matching k-mers on both strands, using k-mers that are unique
+    /// in `y`.
+    fn anchors<T, U>(&self, x: &[T], y: &[U]) -> Vec<Anchor>
+    where
+        T: AsRef<[u8]>,
+        U: AsRef<[u8]>,
+    {
+        let k = self.k;
+        // position of each k-mer of y, or None if it occurs multiple times
+        let mut kmers: HashMapFx<&[u8], Option<(usize, usize)>> = HashMapFx::default();
+        for (y_seq, seq) in y.iter().enumerate() {
+            for (pos, kmer) in seq.as_ref().windows(k).enumerate() {
+                kmers
+                    .entry(kmer)
+                    .and_modify(|hit| *hit = None)
+                    .or_insert(Some((y_seq, pos)));
+            }
+        }
+
+        let mut anchors: Vec<Anchor> = Vec::new();
+        for (x_seq, seq) in x.iter().enumerate() {
+            let seq = seq.as_ref();
+            let revcomp = dna::revcomp(seq);
+            // index of the anchor that can be extended on each diagonal
+            let mut open: HashMap<(usize, isize, bool), usize> = HashMap::new();
+            for pos in 0..(seq.len() + 1).saturating_sub(k) {
+                let rc_pos = seq.len() - pos - k;
+                let hits = [
+                    (kmers.get(&seq[pos..pos + k]), ReqStrand::Forward),
+                    (kmers.get(&revcomp[rc_pos..rc_pos + k]), ReqStrand::Reverse),
+                ];
+                for (hit, strand) in hits.iter() {
+                    let (y_seq, y_pos) = match hit {
+                        Some(Some(hit)) => *hit,
+                        _ => continue,
+                    };
+                    let reverse = *strand == ReqStrand::Reverse;
+                    let diagonal = if reverse {
+                        (pos + y_pos) as isize
+                    } else {
+                        y_pos as isize - pos as isize
+                    };
+                    let key = (y_seq, diagonal, reverse);
+                    match open.get(&key).map(|&i| &mut anchors[i]) {
+                        Some(anchor) if anchor.x + anchor.len - k + 1 == pos => {
+                            anchor.len += 1;
+                            if reverse {
+                                anchor.y -= 1;
+                            }
+                        }
+                        _ => {
+                            open.insert(key, anchors.len());
+                            anchors.push(Anchor::new(x_seq, pos, y_seq, y_pos, k, *strand));
+                        }
+                    }
+                }
+            }
+        }
+        anchors
+    }
+}
+
+/// The union of the given intervals, sorted.
+fn covered<T, I>(seqs: &[T], intervals: I) -> Vec<(usize, Range<usize>)>
+where
+    I: Iterator<Item = (usize, Range<usize>)>,
+{
+    let mut intervals = intervals
+        .filter(|(_, range)| !range.is_empty())
+        .collect::<Vec<_>>();
+    intervals.sort_unstable_by_key(|(seq, range)| (*seq, range.start));
+    let mut union: Vec<(usize, Range<usize>)> = Vec::with_capacity(seqs.len());
+    for (seq, range) in intervals {
+        match union.last_mut() {
+            Some((last_seq, last)) if *last_seq == seq && range.start <= last.end => {
+                last.end = last.end.max(range.end);
+            }
+            _ => union.push((seq, range)),
+        }
+    }
+    union
+}
+
+/// The regions of the sequences not covered by the given sorted disjoint intervals.
+fn complement<T: AsRef<[u8]>>(
+    seqs: &[T],
+    covered: &[(usize, Range<usize>)],
+) -> Vec<(usize, Range<usize>)> {
+    let mut uncovered = Vec::new();
+    let mut covered = covered.iter().peekable();
+    for (i, seq) in seqs.iter().enumerate() {
+        let mut pos = 0;
+        while let Some((_, range)) = covered.next_if(|(seq, _)| *seq == i) {
+            if range.start > pos {
+                uncovered.push((i, pos..range.start));
+            }
+            pos = range.end;
+        }
+        if seq.as_ref().len() > pos {
+            uncovered.push((i, pos..seq.as_ref().len()));
+        }
+    }
+    uncovered
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::prelude::*;
+    use rand::rngs::StdRng;
+
+    fn random(rng: &mut StdRng, len: usize) -> Vec<u8> {
+        (0..len).map(|_| *b"ACGT".choose(rng).unwrap()).collect()
+    }
+
+    #[test]
+    fn test_compare() {
+        let mut rng = StdRng::seed_from_u64(42);
+        let (a, b, c) = (
+            random(&mut rng, 2000),
+            random(&mut rng, 1000),
+            random(&mut rng, 500),
+        );
+        // x: a b, y: a' with 3 SNPs and a 4 bp deletion, revcomp(b), and c on another contig
+        let x = [[&a[..], &b[..]].concat()];
+        let mut a2 = a.clone();
+        for &pos in &[300, 900, 1500] {
+            a2[pos] = if a2[pos] == b'A' { b'C' } else { b'A' };
+        }
+        a2.drain(1200..1204);
+        let y = [[&a2[..], &dna::revcomp(&b)[..]].concat(), c.clone()];
+
+        let report = AssemblyComparison::new(15, 50, 100).compare(&x, &y);
+        assert_eq!(report.blocks.len(), 2);
+        assert_eq!(report.blocks[1].strand, ReqStrand::Reverse);
+        assert_eq!(report.snps, 3);
+        assert_eq!((report.insertions, report.inserted_bases), (1, 4));
+        assert_eq!((report.deletions, report.deleted_bases), (0, 0));
+        assert_eq!((report.x_len, report.y_len), (3000, 3496));
+        assert_eq!(report.x_aligned, 3000);
+        assert_eq!(report.y_aligned, 2996);
+        assert!(report.x_unaligned.is_empty());
+        assert_eq!(report.y_unaligned, [(1, 0..500)]);
+        assert_eq!(report.x_aligned_fraction(), 1.0);
+    }
+
+    #[test]
+    fn test_max_cells() {
+        let mut rng = StdRng::seed_from_u64(42);
+        let x = random(&mut rng, 1000);
+        let mut comparison = AssemblyComparison::new(15, 50, 100);
+        comparison.set_scoring(2, -3, -8, -2);
+        let report = comparison.compare(&[&x], &[&x]);
+        assert_eq!(report.x_aligned, 1000);
+        assert!(report.unaligned_blocks.is_empty());
+
+        // the band of a block has more than 1000 cells
+        comparison.set_max_cells(Some(1000));
+        let report = comparison.compare(&[&x], &[&x]);
+        assert!(report.blocks.is_empty());
+        assert_eq!(report.unaligned_blocks.len(), 1);
+        assert_eq!(report.x_aligned, 0);
+        assert_eq!(report.x_unaligned, [(0, 0..1000)]);
+        assert_eq!(report.y_unaligned, [(0, 0..1000)]);
+    }
+
+    #[test]
+    fn test_intervals() {
+        let seqs = [vec![0; 10], vec![0; 5], vec![0; 3]];
+        let union = covered(
+            &seqs,
+            vec![(0, 2..4), (0, 3..6), (0, 8..9), (2, 0..3)].into_iter(),
+        );
+        assert_eq!(union, [(0, 2..6), (0, 8..9), (2, 0..3)]);
+        assert_eq!(
+            complement(&seqs, &union),
+            [(0, 0..2), (0, 6..8), (0, 9..10), (1, 0..5)]
+        );
+    }
+}
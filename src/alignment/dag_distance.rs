@@ -0,0 +1,322 @@
+//! Edit distance of a pattern (e.g. a read) against a sequence graph, i.e. the smallest edit
+//! distance between the pattern and the sequence spelled by any path in a directed acyclic
+//! graph with one symbol per node (like the `POAGraph` of the partial order aligner). In
+//! contrast to the full graph alignment, no traceback is kept, which makes this suitable for
+//! quickly filtering candidate regions of a graph before aligning to them.
+//!
+//! The DP columns of the nodes are computed in topological order with Myers' bit-parallel
+//! algorithm (with 64 pattern positions per word), as described for graphs by Rautiainen et
+//! al. (GraphAligner, 2019). The column of a node with multiple predecessors is derived from
+//! the cell-wise minimum of the predecessor columns, which is computed on the decoded columns.
+//! Complexity: O(V * ceil(m / 64) + M * m) for a graph with V nodes, M of which have multiple
+//! predecessors, and a pattern of length m.
+//!
+//! # Example
+//!
+//! ```
+//! use bio::alignment::dag_distance::DagDistance;
+//! use bio::alignment::poa::POAGraph;
+//!
+//! // a graph spelling ACGTTA and ACCTTA (a SNP)
+//! let mut graph = POAGraph::default();
+//! let nodes = b"ACGTTA"
+//!     .iter()
+//!     .map(|&a| graph.add_node(a))
+//!     .collect::<Vec<_>>();
+//! let alt = graph.add_node(b'C');
+//! for w in nodes.windows(2) {
+//!     graph.add_edge(w[0], w[1], 1);
+//! }
+//! graph.add_edge(nodes[1], alt, 1);
+//! graph.add_edge(alt, nodes[3], 1);
+//!
+//! let dag_distance = DagDistance::new(b"CCTT");
+//! assert_eq!(dag_distance.distance(&graph, 1), Some((0, nodes[4])));
+//! assert_eq!(DagDistance::new(b"GGTT").distance(&graph, 1), Some((1, nodes[4])));
+//! assert_eq!(DagDistance::new(b"AAAA").distance(&graph, 1), None);
+//! ```
+
+use std::cmp::min;
+
+use petgraph::graph::{IndexType, NodeIndex};
+use petgraph::visit::Topo;
+use petgraph::{Directed, Graph, Incoming};
+
+use crate::utils::TextSlice;
+
+/// Bit-parallel representation of a DP column, storing the vertical deltas between
+/// consecutive rows and the value of the last row.
+#[derive(Clone, Debug)]
+struct Column {
+    pv: Vec<u64>,
+    mv: Vec<u64>,
+    dist: usize,
+}
+
+/// Edit distance computation of a pattern against sequence graphs.
+#[derive(Default, Clone, Eq, PartialEq, Hash, Debug, Serialize, Deserialize)]
+pub struct DagDistance {
+    /// Match vectors of all symbols, with one entry per block.
+    peq: Vec<u64>,
+    m: usize,
+    blocks: usize,
+}
+
+impl DagDistance {
+    /// Create a new instance for the given pattern.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the pattern is empty.
+    pub fn new(pattern: TextSlice<'_>) -> Self {
+        assert!(!pattern.is_empty(), "Expecting non-empty pattern.");
+        let m = pattern.len();
+        let blocks = (m + 63) / 64;
+        let mut peq = vec![0; 256 * blocks];
+        for (i, &a) in pattern.iter().enumerate() {
+            peq[a as usize * blocks + i / 64] |= 1 << (i % 64);
+        }
+        DagDistance { peq, m, blocks }
+    }
+
+    /// The smallest edit distance between the pattern and a path of the given acyclic graph,
+    /// together with the node where the path ends, or `None` if the distance exceeds
+    /// `max_dist`. If multiple end nodes have the smallest distance, the first one in
+    /// topological order is returned. The computation stops at the first exact occurrence.
+    pub fn distance<E, Ix: IndexType>(
+        &self,
+        graph: &Graph<u8, E, Directed, Ix>,
+        max_dist: usize,
+    ) -> Option<(usize, NodeIndex<Ix>)> {
+        let mut best: Option<(usize, NodeIndex<Ix>)> = None;
+        self.visit(graph, |node, dist| {
+            if dist <= max_dist && best.map_or(true, |(d, _)| dist < d) {
+                best = Some((dist, node));
+            }
+            dist > 0
+        });
+        best
+    }
+
+    /// The smallest edit distance between the pattern and a path of the given acyclic graph
+    /// ending in each node (indexed by the node index).
+    pub fn end_distances<E, Ix: IndexType>(
+        &self,
+        graph: &Graph<u8, E, Directed, Ix>,
+    ) -> Vec<usize> {
+        let mut dists = vec![self.m; graph.node_count()];
+        self.visit(graph, |node, dist| {
+            dists[node.index()] = dist;
+            true
+        });
+        dists
+    }
+
+    /// Compute the columns of all nodes in topological order, passing the distance of each
+    /// node to the callback until it returns `false`.
+    fn visit<E, Ix, F>(&self, graph: &Graph<u8, E, Directed, Ix>, mut callback: F)
+    where
+        Ix: IndexType,
+        F: FnMut(NodeIndex<Ix>, usize) -> bool,
+    {
+        let initial = Column {
+            pv: vec![u64::MAX; self.blocks],
+            mv: vec![0; self.blocks],
+            dist: self.m,
+        };
+        let mut columns: Vec<Option<Column>> = vec![None; graph.node_count()];
+        let mut topo = Topo::new(graph);
+        while let Some(node) = topo.next(graph) {
+            let mut preds = graph
+                .neighbors_directed(node, Incoming)
+                .map(|pred| columns[pred.index()].as_ref().unwrap());
+            let column = match (preds.next(), preds.next()) {
+                (None, _) => self.step(&initial, graph[node]),
+                (Some(pred), None) => self.step(pred, graph[node]),
+                (Some(first), Some(second)) => {
+                    let mut values = self.decode(first);
+                    for pred in std::iter::once(second).chain(preds) {
+                        for (v, p) in values.iter_mut().zip(self.decode(pred)) {
+                            *v = min(*v, p);
+                        }
+                    }
+                    self.step(&self.encode(&values), graph[node])
+                }
+            };
+            let dist = column.dist;
+            columns[node.index()] = Some(column);
+            if !callback(node, dist) {
+                return;
+            }
+        }
+    }
+
+    /// Compute the column of a node with symbol `a` from the column of its predecessor.
+    fn step(&self, pred: &Column, a: u8) -> Column {
+        let peq = &self.peq[a as usize * self.blocks..(a as usize + 1) * self.blocks];
+        let mut column = Column {
+            pv: Vec::with_capacity(self.blocks),
+            mv: Vec::with_capacity(self.blocks),
+            dist: pred.dist,
+        };
+        // the first row is always zero (the pattern may start anywhere)
+        let mut hin = 0;
+        for (b, (&peq, (&pv, &mv))) in peq.iter().zip(pred.pv.iter().zip(&pred.mv)).enumerate() {
+            let eq = peq | (hin < 0) as u64;
+            let xv = peq | mv;
+            let xh = ((eq & pv).wrapping_add(pv) ^ pv) | eq;
+            let mut ph = mv | !(xh | pv);
+            let mut mh = pv & xh;
+            // the last row of the block
+            let high = if b == self.blocks - 1 {
+                1 << ((self.m - 1) % 64)
+            } else {
+                1 << 63
+            };
+            let hout = if ph & high != 0 {
+                1
+            } else if mh & high != 0 {
+                -1
+            } else {
+                0
+            };
+            ph = ph << 1 | (hin > 0) as u64;
+            mh = mh << 1 | (hin < 0) as u64;
+            column.pv.push(mh | !(xv | ph));
+            column.mv.push(ph & xv);
+            hin = hout;
+        }
+        column.dist = (column.dist as isize + hin) as usize;
+        column
+    }
+
+    /// The values of all rows of a column.
+    fn decode(&self, column: &Column) -> Vec<usize> {
+        let mut values = Vec::with_capacity(self.m + 1);
+        let mut value = 0;
+        values.push(value);
+        for i in 0..self.m {
+            let bit = 1 << (i % 64);
+            if column.pv[i / 64] & bit != 0 {
+                value += 1;
+            } else if column.mv[i / 64] & bit != 0 {
+                value -= 1;
+            }
+            values.push(value);
+        }
+        values
+    }
+
+    /// The bit-parallel representation of a column given by the values of all rows.
+    fn encode(&self, values: &[usize]) -> Column {
+        let mut column = Column {
+            pv: vec![0; self.blocks],
+            mv: vec![0; self.blocks],
+            dist: values[self.m],
+        };
+        for (i, w) in values.windows(2).enumerate() {
+            if w[1] > w[0] {
+                column.pv[i / 64] |= 1 << (i % 64);
+            } else if w[1] < w[0] {
+                column.mv[i / 64] |= 1 << (i % 64);
+            }
+        }
+        column
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::alignment::poa::POAGraph;
+    use crate::pattern_matching::myers::Myers;
+    use rand::prelude::*;
+    use rand::rngs::StdRng;
+
+    /// Plain DP over the graph.
+    fn naive(pattern: &[u8], graph: &POAGraph) -> Vec<usize> {
+        let m = pattern.len();
+        let mut columns: Vec<Vec<usize>> = vec![Vec::new(); graph.node_count()];
+        let initial = (0..=m).collect::<Vec<_>>();
+        let mut topo = Topo::new(graph);
+        while let Some(node) = topo.next(graph) {
+            let mut preds = graph
+                .neighbors_directed(node, Incoming)
+                .map(|p| &columns[p.index()])
+                .collect::<Vec<_>>();
+            if preds.is_empty() {
+                preds.push(&initial);
+            }
+            let mut column = vec![0; m + 1];
+            for j in 1..=m {
+                let cost = (pattern[j - 1] != graph[node]) as usize;
+                column[j] = preds
+                    .iter()
+                    .map(|p| min(p[j - 1] + cost, p[j] + 1))
+                    .min()
+                    .unwrap()
+                    .min(column[j - 1] + 1);
+            }
+            columns[node.index()] = column;
+        }
+        columns.iter().map(|c| c[m]).collect()
+    }
+
+    fn random_dag(rng: &mut StdRng, n: usize) -> POAGraph {
+        let mut graph = POAGraph::default();
+        for i in 0..n {
+            let node = graph.add_node(*b"ACGT".choose(rng).unwrap());
+            // edges only from earlier nodes keep the graph acyclic
+            for _ in 0..rng.gen_range(0..3).min(i) {
+                let pred = NodeIndex::new(rng.gen_range(i.saturating_sub(10)..i));
+                graph.update_edge(pred, node, 1);
+            }
+        }
+        graph
+    }
+
+    #[test]
+    fn test_naive() {
+        let mut rng = StdRng::seed_from_u64(42);
+        for &m in &[1, 5, 63, 64, 65, 150] {
+            let graph = random_dag(&mut rng, 300);
+            let pattern = (0..m)
+                .map(|_| *b"ACGT".choose(&mut rng).unwrap())
+                .collect::<Vec<_>>();
+            let dag_distance = DagDistance::new(&pattern);
+            let expected = naive(&pattern, &graph);
+            assert_eq!(dag_distance.end_distances(&graph), expected);
+            let best = *expected.iter().min().unwrap();
+            assert_eq!(dag_distance.distance(&graph, best).unwrap().0, best);
+            if best > 0 {
+                assert_eq!(dag_distance.distance(&graph, best - 1), None);
+            }
+        }
+    }
+
+    #[test]
+    fn test_linear() {
+        // on a linear graph, the distances equal those of Myers' algorithm
+        let text = b"ACCGTGGATGAGCGCCATAGACCGTGGATGAGCGCCATAG";
+        let mut graph = POAGraph::default();
+        let mut prev = None;
+        for &a in text.iter() {
+            let node = graph.add_node(a);
+            if let Some(prev) = prev {
+                graph.add_edge(prev, node, 1);
+            }
+            prev = Some(node);
+        }
+        let pattern = b"TGAGCGT";
+        let myers = Myers::<u64>::new(pattern);
+        let expected = myers
+            .find_all_end(text, u8::MAX)
+            .map(|(_, dist)| dist)
+            .collect::<Vec<_>>();
+        let dists = DagDistance::new(pattern).end_distances(&graph);
+        assert_eq!(
+            dists,
+            expected.iter().map(|&d| d as usize).collect::<Vec<_>>()
+        );
+    }
+}
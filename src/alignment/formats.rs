@@ -0,0 +1,300 @@
+//! Output of pairwise alignments in standard text formats: MAF blocks (as produced by
+//! LASTZ or UCSC tools) and the EMBOSS "pair" format (as produced by needle and water).
+//! For SAM records, see `sam::sam_record`.
+//!
+//! Both formats only show the aligned parts of the sequences, i.e. clipped or unaligned
+//! prefixes and suffixes are omitted. `y` is considered the reference and `x` the query.
+//!
+//! # Example
+//!
+//! ```
+//! use bio::alignment::formats::{emboss_pair, maf_block};
+//! use bio::alignment::pairwise::Aligner;
+//!
+//! let x = b"ACCGTGGATGGGCG";
+//! let y = b"TTACCGTCGGATGGGCGTT";
+//! let score = |a: u8, b: u8| if a == b { 1i32 } else { -1i32 };
+//! let mut aligner = Aligner::new(-5, -1, score);
+//! let alignment = aligner.semiglobal(x, y);
+//!
+//! assert_eq!(
+//!     maf_block(&alignment, x, y, "read", "chr1"),
+//!     "a score=8\n\
+//!      s chr1 2 15 + 19 ACCGTCGGATGGGCG\n\
+//!      s read 0 14 + 14 ACCGT-GGATGGGCG\n\n"
+//! );
+//!
+//! let pair = emboss_pair(&alignment, x, y, "read", "chr1", score);
+//! assert!(pair.contains("# Identity:      14/15 (93.3%)\n"));
+//! assert!(pair.contains("\nread               1 ACCGT-GGATGGGCG     14\n"));
+//! assert!(pair.contains("\n                     ||||| |||||||||\n"));
+//! assert!(pair.contains("\nchr1               3 ACCGTCGGATGGGCG     17\n"));
+//! ```
+
+use std::fmt::Write;
+
+use crate::alignment::pairwise::MatchFunc;
+use crate::alignment::{Alignment, AlignmentOperation, AlignmentStats};
+use crate::utils::TextSlice;
+
+/// Number of alignment columns per block of the EMBOSS pair format.
+pub const EMBOSS_WIDTH: usize = 50;
+
+/// One column of the aligned part of an alignment.
+#[derive(Copy, Clone, Eq, PartialEq, Debug)]
+struct Column {
+    /// Symbol and position of `x` in this column, `None` for a gap.
+    x: Option<(u8, usize)>,
+    /// Symbol and position of `y` in this column, `None` for a gap.
+    y: Option<(u8, usize)>,
+}
+
+/// The columns of the aligned part of the alignment, without clips.
+fn aligned_columns(alignment: &Alignment, x: TextSlice<'_>, y: TextSlice<'_>) -> Vec<Column> {
+    let (mut i, mut j) = (alignment.xstart, alignment.ystart);
+    let mut cols = Vec::with_capacity(alignment.operations.len());
+    for &op in &alignment.operations {
+        let col = match op {
+            AlignmentOperation::Match | AlignmentOperation::Subst => Column {
+                x: Some((x[i], i)),
+                y: Some((y[j], j)),
+            },
+            AlignmentOperation::Ins => Column {
+                x: Some((x[i], i)),
+                y: None,
+            },
+            AlignmentOperation::Del => Column {
+                x: None,
+                y: Some((y[j], j)),
+            },
+            AlignmentOperation::Xclip(_) | AlignmentOperation::Yclip(_) => continue,
+        };
+        i += col.x.is_some() as usize;
+        j += col.y.is_some() as usize;
+        cols.push(col);
+    }
+    cols
+}
+
+/// Accessor of the symbol and position of one sequence in a column.
+type Symbol = fn(&Column) -> Option<(u8, usize)>;
+
+fn gapped(cols: &[Column], get: Symbol) -> String {
+    cols.iter()
+        .map(|col| get(col).map_or('-', |(a, _)| char::from(a)))
+        .collect()
+}
+
+/// Format the alignment of the query `x` named `x_name` against the reference `y` named
+/// `y_name` as a MAF block, with the reference line first and both sequences on the forward
+/// strand. Start positions are 0-based. The `##maf version=1` header of MAF files is not
+/// included.
+pub fn maf_block(
+    alignment: &Alignment,
+    x: TextSlice<'_>,
+    y: TextSlice<'_>,
+    x_name: &str,
+    y_name: &str,
+) -> String {
+    let cols = aligned_columns(alignment, x, y);
+    let lines = [
+        (
+            y_name,
+            alignment.ystart,
+            alignment.yend - alignment.ystart,
+            alignment.ylen,
+            gapped(&cols, |col| col.y),
+        ),
+        (
+            x_name,
+            alignment.xstart,
+            alignment.xend - alignment.xstart,
+            alignment.xlen,
+            gapped(&cols, |col| col.x),
+        ),
+    ];
+    // align the fields like MAF writers usually do
+    let width = |f: fn(&(&str, usize, usize, usize, String)) -> usize| {
+        lines.iter().map(f).max().unwrap_or(0)
+    };
+    let name_width = width(|l| l.0.len());
+    let start_width = width(|l| l.1.to_string().len());
+    let size_width = width(|l| l.2.to_string().len());
+    let len_width = width(|l| l.3.to_string().len());
+
+    let mut maf = format!("a score={}\n", alignment.score);
+    for (name, start, size, len, text) in &lines {
+        writeln!(
+            maf,
+            "s {:name_width$} {:>start_width$} {:>size_width$} + {:>len_width$} {}",
+            name, start, size, len, text
+        )
+        .unwrap();
+    }
+    maf.push('\n');
+    maf
+}
+
+/// Format the alignment of `x` named `x_name` and `y` named `y_name` in the EMBOSS pair
+/// format, with a header of alignment statistics followed by blocks of `EMBOSS_WIDTH`
+/// columns with 1-based coordinates. The markup line shows `|` for matches, `:` for
+/// substitutions with a positive score according to `match_fn`, `.` for other substitutions
+/// and blanks for gaps. Names are truncated to 13 characters, as by EMBOSS.
+pub fn emboss_pair<F: MatchFunc>(
+    alignment: &Alignment,
+    x: TextSlice<'_>,
+    y: TextSlice<'_>,
+    x_name: &str,
+    y_name: &str,
+    match_fn: F,
+) -> String {
+    let cols = aligned_columns(alignment, x, y);
+    let counts = alignment.operation_counts();
+    let len = counts.columns();
+    let similar = cols
+        .iter()
+        .filter(|col| match (col.x, col.y) {
+            (Some((a, _)), Some((b, _))) => match_fn.score(a, b) > 0,
+            _ => false,
+        })
+        .count();
+    let percent = |count: usize| {
+        if len == 0 {
+            0.0
+        } else {
+            count as f64 / len as f64 * 100.0
+        }
+    };
+
+    let mut pair = String::new();
+    let rule = "#=======================================";
+    writeln!(pair, "{}\n#\n# Aligned_sequences: 2", rule).unwrap();
+    writeln!(pair, "# 1: {}\n# 2: {}", x_name, y_name).unwrap();
+    writeln!(pair, "# Length: {}", len).unwrap();
+    for (label, count) in [
+        ("# Identity:", counts.matches),
+        ("# Similarity:", similar),
+        ("# Gaps:", counts.inserted + counts.deleted),
+    ] {
+        let stat = format!("{}/{}", count, len);
+        writeln!(pair, "{:15}{:>7} ({:4.1}%)", label, stat, percent(count)).unwrap();
+    }
+    writeln!(pair, "# Score: {}\n#\n#\n{}\n", alignment.score, rule).unwrap();
+
+    let (mut xi, mut yi) = (alignment.xstart, alignment.ystart);
+    for block in cols.chunks(EMBOSS_WIDTH) {
+        let sequence_line = |name: &str, consumed: &mut usize, get: Symbol| {
+            let start = block.iter().find_map(get).map_or(*consumed, |(_, p)| p + 1);
+            if let Some((_, last)) = block.iter().rev().find_map(get) {
+                *consumed = last + 1;
+            }
+            format!(
+                "{:<13.13} {:>6} {} {:>6}\n",
+                name,
+                start,
+                gapped(block, get),
+                consumed
+            )
+        };
+        let x_line = sequence_line(x_name, &mut xi, |col| col.x);
+        let y_line = sequence_line(y_name, &mut yi, |col| col.y);
+        let markup: String = block
+            .iter()
+            .map(|col| match (col.x, col.y) {
+                (Some((a, _)), Some((b, _))) if a == b => '|',
+                (Some((a, _)), Some((b, _))) if match_fn.score(a, b) > 0 => ':',
+                (Some(_), Some(_)) => '.',
+                _ => ' ',
+            })
+            .collect();
+        pair.push_str(&x_line);
+        writeln!(pair, "{:21}{}", "", markup.trim_end()).unwrap();
+        pair.push_str(&y_line);
+        pair.push('\n');
+    }
+    pair.push_str("\n#---------------------------------------\n");
+    pair.push_str("#---------------------------------------\n");
+    pair
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::alignment::pairwise::Aligner;
+    use crate::alignment::AlignmentMode;
+    use crate::alignment::AlignmentOperation::*;
+    use crate::scores::blosum62;
+
+    #[test]
+    fn test_clips_omitted() {
+        let alignment = Alignment {
+            score: 3,
+            xstart: 2,
+            xend: 6,
+            xlen: 7,
+            ystart: 1,
+            yend: 5,
+            ylen: 5,
+            operations: vec![Xclip(2), Yclip(1), Match, Ins, Match, Del, Subst, Xclip(1)],
+            mode: AlignmentMode::Custom,
+        };
+        let (x, y) = (b"GGATCAT", b"TACGG");
+        let cols = aligned_columns(&alignment, x, y);
+        assert_eq!(gapped(&cols, |col| col.x), "ATC-A");
+        assert_eq!(gapped(&cols, |col| col.y), "A-CGG");
+        assert_eq!(
+            maf_block(&alignment, x, y, "query", "ref"),
+            "a score=3\ns ref   1 4 + 5 A-CGG\ns query 2 4 + 7 ATC-A\n\n"
+        );
+    }
+
+    #[test]
+    fn test_emboss_pair() {
+        let x = b"MKVLAAGIVKHEEHEEHEEHEEHEEHEEHEEHEEHEEHEEHEEHEEHEEHEEAAW";
+        let y = b"MRVIAAGLVKHEEHEEHEEHEEHEEHEEHEEHEEHEEHEEHEEHEEHEEHEEHAAW";
+        let mut aligner = Aligner::new(-10, -1, blosum62);
+        let alignment = aligner.global(x, y);
+        let pair = emboss_pair(
+            &alignment,
+            x,
+            y,
+            "a_very_long_sequence_name",
+            "ref",
+            blosum62,
+        );
+        let lines: Vec<&str> = pair.lines().collect();
+        assert_eq!(lines[3], "# 1: a_very_long_sequence_name");
+        assert_eq!(lines[5], "# Length: 56");
+        assert_eq!(lines[6], "# Identity:      52/56 (92.9%)");
+        assert_eq!(lines[7], "# Similarity:    55/56 (98.2%)");
+        assert_eq!(lines[8], "# Gaps:           1/56 ( 1.8%)");
+        assert_eq!(
+            lines[14],
+            "a_very_long_s      1 MKVLAAGIVKHEEHEEHEEHEEHEEHEEHEEHEEHEEHEEHEEHEEHEEH     50"
+        );
+        assert_eq!(
+            lines[15],
+            "                     |:|:|||:||||||||||||||||||||||||||||||||||||||||||"
+        );
+        assert_eq!(
+            lines[16],
+            "ref                1 MRVIAAGLVKHEEHEEHEEHEEHEEHEEHEEHEEHEEHEEHEEHEEHEEH     50"
+        );
+        assert_eq!(lines[18], "a_very_long_s     51 EE-AAW     55");
+        assert_eq!(
+            lines.last(),
+            Some(&"#---------------------------------------")
+        );
+    }
+
+    #[test]
+    fn test_empty() {
+        let alignment = Alignment::default();
+        assert_eq!(
+            maf_block(&alignment, b"", b"", "x", "y"),
+            "a score=0\ns y 0 0 + 0 \ns x 0 0 + 0 \n\n"
+        );
+        let pair = emboss_pair(&alignment, b"", b"", "x", "y", blosum62);
+        assert!(pair.contains("# Identity:        0/0 ( 0.0%)\n"));
+    }
+}
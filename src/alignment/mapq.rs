@@ -0,0 +1,183 @@
+//! Mapping qualities of reads from the scores of their candidate alignments, such that mappers
+//! built on the aligners of this crate report consistent values.
+//!
+//! Alignment scores are interpreted as log-likelihoods up to a constant factor `lambda` (e.g.
+//! `ln(2)` for scores in bits). Assuming a uniform prior over the candidate locations, the
+//! posterior probability of the best alignment being the correct one is
+//! `exp(lambda * s_1) / sum_i exp(lambda * s_i)`, and the mapping quality is the
+//! PHRED-scaled probability of it being wrong, capped at `MAX_MAPQ`.
+//! In particular, a read with two equally good alignments has a mapping quality of 3, and the
+//! mapping quality grows asymptotically linear with the score difference between the best and
+//! the second best alignment (by `10 * lambda / ln(10)` per score unit).
+//!
+//! # Example
+//!
+//! ```
+//! use bio::alignment::mapq::{mapq, ScoreDistribution};
+//!
+//! let lambda = 2f64.ln();
+//! // a unique hit
+//! assert_eq!(mapq(100, None, lambda), 60);
+//! // a second hit with 5 bits less
+//! assert_eq!(mapq(100, Some(95), lambda), 15);
+//!
+//! let mut scores = ScoreDistribution::new();
+//! scores.extend(vec![95, 100, 80, 95]);
+//! assert_eq!(scores.best(), Some(100));
+//! assert_eq!(scores.suboptimal(), Some(95));
+//! assert_eq!(scores.mapq(lambda), 12);
+//! ```
+
+use std::iter::FromIterator;
+
+use crate::stats::{LogProb, PHREDProb};
+
+/// Maximum reported mapping quality.
+pub const MAX_MAPQ: u8 = 60;
+
+/// The scores of all candidate alignments of a read.
+#[derive(Default, Clone, Eq, PartialEq, Hash, Debug, Serialize, Deserialize)]
+pub struct ScoreDistribution {
+    scores: Vec<i32>,
+}
+
+impl ScoreDistribution {
+    /// Create a new, empty distribution.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add the score of a candidate alignment.
+    pub fn add(&mut self, score: i32) {
+        self.scores.push(score);
+    }
+
+    /// The scores of all candidate alignments.
+    pub fn scores(&self) -> &[i32] {
+        &self.scores
+    }
+
+    /// Whether no alignment has been added.
+    pub fn is_empty(&self) -> bool {
+        self.scores.is_empty()
+    }
+
+    /// Score of the best alignment.
+    pub fn best(&self) -> Option<i32> {
+        self.scores.iter().max().cloned()
+    }
+
+    /// Score of the second best alignment, which equals the best score if there are multiple
+    /// best alignments.
+    pub fn suboptimal(&self) -> Option<i32> {
+        let best = self.scores.iter().enumerate().max_by_key(|&(_, s)| s)?.0;
+        self.scores
+            .iter()
+            .enumerate()
+            .filter(|&(i, _)| i != best)
+            .map(|(_, &s)| s)
+            .max()
+    }
+
+    /// Posterior probability that the best alignment is the correct one, for scores in units
+    /// of `1 / lambda` natural logarithms. Returns zero if there is no alignment.
+    pub fn prob_correct(&self, lambda: f64) -> LogProb {
+        self.prob_wrong(lambda).ln_one_minus_exp()
+    }
+
+    /// Posterior probability that the best alignment is not the correct one.
+    fn prob_wrong(&self, lambda: f64) -> LogProb {
+        let best = match self.best() {
+            Some(best) => best,
+            None => return LogProb::ln_one(),
+        };
+        let mut seen_best = false;
+        let mut others = Vec::with_capacity(self.scores.len());
+        let mut all = Vec::with_capacity(self.scores.len());
+        for &s in &self.scores {
+            let p = LogProb(lambda * (s - best) as f64);
+            all.push(p);
+            if s == best && !seen_best {
+                seen_best = true;
+            } else {
+                others.push(p);
+            }
+        }
+        // computed directly instead of 1 - P(correct), avoiding cancellation
+        LogProb(*LogProb::ln_sum_exp(&others) - *LogProb::ln_sum_exp(&all))
+    }
+
+    /// Mapping quality of the best alignment, i.e. the PHRED-scaled probability of it not
+    /// being the correct one, rounded down and capped at `MAX_MAPQ`. Returns zero if there is
+    /// no alignment.
+    pub fn mapq(&self, lambda: f64) -> u8 {
+        let phred = *PHREDProb::from(self.prob_wrong(lambda));
+        if phred.is_nan() {
+            0
+        } else {
+            phred.min(MAX_MAPQ as f64) as u8
+        }
+    }
+}
+
+impl Extend<i32> for ScoreDistribution {
+    fn extend<I: IntoIterator<Item = i32>>(&mut self, scores: I) {
+        self.scores.extend(scores);
+    }
+}
+
+impl FromIterator<i32> for ScoreDistribution {
+    fn from_iter<I: IntoIterator<Item = i32>>(scores: I) -> Self {
+        ScoreDistribution {
+            scores: scores.into_iter().collect(),
+        }
+    }
+}
+
+/// Mapping quality of a read from the score of its best alignment and, if present, the score
+/// of its second best alignment (see `ScoreDistribution::mapq`).
+pub fn mapq(best: i32, suboptimal: Option<i32>, lambda: f64) -> u8 {
+    let mut scores = ScoreDistribution::new();
+    scores.add(best);
+    scores.extend(suboptimal);
+    scores.mapq(lambda)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use approx::assert_relative_eq;
+
+    #[test]
+    fn test_mapq() {
+        let lambda = 1.0;
+        assert_eq!(mapq(10, Some(10), lambda), 3);
+        assert_eq!(mapq(10, None, lambda), MAX_MAPQ);
+        // suboptimal scores exceeding the best one are treated as the best
+        assert_eq!(mapq(10, Some(12), lambda), mapq(12, Some(10), lambda));
+        // -10 * log10(exp(-2) / (1 + exp(-2))) = 9.2
+        assert_eq!(mapq(10, Some(8), lambda), 9);
+        assert_eq!(mapq(100, Some(-100), lambda), MAX_MAPQ);
+        // monotone in the score difference
+        for diff in 0..20 {
+            assert!(mapq(50, Some(50 - diff), lambda) <= mapq(50, Some(49 - diff), lambda));
+        }
+    }
+
+    #[test]
+    fn test_distribution() {
+        let empty = ScoreDistribution::new();
+        assert!(empty.is_empty());
+        assert_eq!(empty.mapq(1.0), 0);
+        assert_eq!(*empty.prob_correct(1.0), f64::NEG_INFINITY);
+
+        let scores = vec![5, 5, 5, 5].into_iter().collect::<ScoreDistribution>();
+        assert_eq!(scores.suboptimal(), Some(5));
+        assert_relative_eq!(scores.prob_correct(1.0).exp(), 0.25, epsilon = 1e-5);
+        assert_eq!(scores.mapq(1.0), 1);
+
+        let scores = vec![1, 2].into_iter().collect::<ScoreDistribution>();
+        let p = 1.0 / (1.0 + (-1.0f64).exp());
+        assert_relative_eq!(scores.prob_correct(1.0).exp(), p, epsilon = 1e-5);
+    }
+}
@@ -5,10 +5,27 @@
 
 //! Various alignment and distance computing algorithms.
 
+pub mod agreement;
+pub mod assembly_comparison;
+pub mod dag_distance;
 pub mod distance;
+pub mod formats;
+pub mod mapq;
+pub mod normalize;
+pub mod overhang;
 pub mod pairwise;
+pub mod pileup;
 pub mod poa;
+pub mod prelude;
+pub mod render;
+pub mod sam;
 pub mod sparse;
+pub mod stats;
+pub mod synteny;
+pub mod window_score;
 
 // Re-export the alignment types.
 pub use bio_types::alignment::*;
+
+pub use self::render::PrettyAlignment;
+pub use self::stats::AlignmentStats;
@@ -0,0 +1,200 @@
+//! Normalization of alignments.
+//!
+//! Within repetitive sequence, an indel can be placed at several equivalent positions, all
+//! yielding the same alignment score. Aligners choose one of them arbitrarily, which makes it
+//! hard to compare alignments or to call variants consistently. Following the convention of
+//! variant normalization (e.g. in VCF), indels are shifted to their leftmost equivalent
+//! position.
+//!
+//! # Example
+//!
+//! ```
+//! use bio::alignment::normalize::left_align_indels;
+//! use bio::alignment::{Alignment, AlignmentMode, AlignmentOperation::*};
+//!
+//! let x = b"ACAAT";
+//! let y = b"ACAAAT";
+//! // the deletion of one A can be placed anywhere in the homopolymer
+//! let mut alignment = Alignment {
+//!     score: 0,
+//!     xstart: 0,
+//!     ystart: 0,
+//!     xend: 5,
+//!     yend: 6,
+//!     xlen: 5,
+//!     ylen: 6,
+//!     operations: vec![Match, Match, Match, Match, Del, Match],
+//!     mode: AlignmentMode::Global,
+//! };
+//! left_align_indels(&mut alignment, x, y);
+//! assert_eq!(alignment.operations, [Match, Match, Del, Match, Match, Match]);
+//! ```
+
+use crate::alignment::{Alignment, AlignmentOperation};
+use crate::utils::TextSlice;
+
+/// Shift all insertions and deletions of the alignment of `x` against `y` to their leftmost
+/// equivalent position, i.e. as long as the shifted gap yields the same aligned sequences.
+/// Gaps are only shifted across matches, such that the score of the alignment is unchanged.
+/// Adjacent gaps of the same kind are merged as a consequence. Clip operations are retained.
+pub fn left_align_indels(alignment: &mut Alignment, x: TextSlice<'_>, y: TextSlice<'_>) {
+    let ops = &mut alignment.operations;
+    let (mut i, mut j) = (alignment.xstart, alignment.ystart);
+    let mut k = 0;
+    while k < ops.len() {
+        let op = ops[k];
+        let (gapped, pos) = match op {
+            AlignmentOperation::Ins => (x, i),
+            AlignmentOperation::Del => (y, j),
+            AlignmentOperation::Match | AlignmentOperation::Subst => {
+                i += 1;
+                j += 1;
+                k += 1;
+                continue;
+            }
+            AlignmentOperation::Xclip(_) | AlignmentOperation::Yclip(_) => {
+                k += 1;
+                continue;
+            }
+        };
+        let len = ops[k..].iter().take_while(|&&o| o == op).count();
+
+        // Shift the gap gapped[start..start + len] one to the left as long as the preceding
+        // matched base equals the last gapped base.
+        let (mut start, mut first) = (pos, k);
+        while first > 0
+            && ops[first - 1] == AlignmentOperation::Match
+            && gapped[start - 1] == gapped[start + len - 1]
+        {
+            ops[first - 1..first + len].rotate_left(1);
+            start -= 1;
+            first -= 1;
+        }
+
+        // continue after the (original) gap, which is followed by the shifted matches
+        let shift = k - first;
+        match op {
+            AlignmentOperation::Ins => i += len,
+            _ => j += len,
+        }
+        i -= shift;
+        j -= shift;
+        k = first + len;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::alignment::pairwise::Aligner;
+    use crate::alignment::AlignmentMode;
+    use crate::alignment::AlignmentOperation::*;
+
+    fn aligned(alignment: &Alignment, x: &[u8], y: &[u8]) -> (Vec<u8>, Vec<u8>) {
+        let (mut i, mut j) = (alignment.xstart, alignment.ystart);
+        let (mut ax, mut ay) = (Vec::new(), Vec::new());
+        for op in &alignment.operations {
+            match op {
+                Match | Subst => {
+                    ax.push(x[i]);
+                    ay.push(y[j]);
+                    i += 1;
+                    j += 1;
+                }
+                Ins => {
+                    ax.push(x[i]);
+                    ay.push(b'-');
+                    i += 1;
+                }
+                Del => {
+                    ax.push(b'-');
+                    ay.push(y[j]);
+                    j += 1;
+                }
+                _ => (),
+            }
+        }
+        (ax, ay)
+    }
+
+    #[test]
+    fn test_left_align_repeat() {
+        // deletion of a CA unit from a CA repeat
+        let x = b"GGTCACACATT";
+        let y = b"GGTCACACACATT";
+        let score = |a: u8, b: u8| if a == b { 1i32 } else { -1i32 };
+        let mut aligner = Aligner::new(-5, -1, score);
+        let mut alignment = aligner.global(x, y);
+        let score = alignment.score;
+        left_align_indels(&mut alignment, x, y);
+        assert_eq!(alignment.score, score);
+        assert_eq!(
+            alignment.operations,
+            [
+                Match, Match, Match, Del, Del, Match, Match, Match, Match, Match, Match, Match,
+                Match
+            ]
+        );
+        assert_eq!(aligned(&alignment, x, y).0, b"GGT--CACACATT");
+    }
+
+    #[test]
+    fn test_left_align_insertion_with_clips() {
+        let x = b"TTAGGGC";
+        let y = b"CCAGGC";
+        let mut alignment = Alignment {
+            score: 0,
+            xstart: 2,
+            ystart: 2,
+            xend: 7,
+            yend: 6,
+            xlen: 7,
+            ylen: 6,
+            operations: vec![Yclip(2), Xclip(2), Match, Match, Match, Ins, Match],
+            mode: AlignmentMode::Custom,
+        };
+        let before = aligned(&alignment, x, y);
+        left_align_indels(&mut alignment, x, y);
+        assert_eq!(
+            alignment.operations,
+            [Yclip(2), Xclip(2), Match, Ins, Match, Match, Match]
+        );
+        let after = aligned(&alignment, x, y);
+        assert_eq!(after.0, before.0);
+        assert_eq!(after.1, b"A-GGC");
+    }
+
+    #[test]
+    fn test_left_align_merges_and_stops_at_mismatch() {
+        let x = b"ACGTT";
+        let y = b"ACGTTTT";
+        let mut alignment = Alignment {
+            score: 0,
+            xstart: 0,
+            ystart: 0,
+            xend: 5,
+            yend: 7,
+            xlen: 5,
+            ylen: 7,
+            operations: vec![Match, Match, Match, Match, Del, Match, Del],
+            mode: AlignmentMode::Global,
+        };
+        left_align_indels(&mut alignment, x, y);
+        assert_eq!(
+            alignment.operations,
+            [Match, Match, Match, Del, Del, Match, Match]
+        );
+
+        // gaps are not shifted across substitutions
+        let mut alignment = Alignment {
+            operations: vec![Match, Subst, Del, Match],
+            xend: 3,
+            yend: 4,
+            xlen: 3,
+            ylen: 4,
+            ..alignment
+        };
+        left_align_indels(&mut alignment, b"AGA", b"ACAA");
+        assert_eq!(alignment.operations, [Match, Subst, Del, Match]);
+    }
+}
@@ -0,0 +1,168 @@
+//! Terminal gaps and clips (overhangs) of alignments, and classification of overlaps.
+//!
+//! An overhang is the number of bases at the start or end of a sequence that are not aligned
+//! to the other sequence, either because they are clipped or because they are aligned to
+//! gaps. Overhangs decide whether an overlap between two reads is a dovetail (the reads
+//! extend each other) or a containment, as needed for building assembly graphs
+//! (see e.g. Li, 2016, Minimap and miniasm).
+//!
+//! # Example
+//!
+//! ```
+//! use bio::alignment::overhang::{overhangs, OverlapKind};
+//! use bio::alignment::pairwise::Aligner;
+//!
+//! let x = b"GGATCCATGCAAGTGGCATTACCAG";
+//! let y = b"CAAGTGGCATTACCAGATCAAGGTC";
+//! let score = |a: u8, b: u8| if a == b { 1i32 } else { -1i32 };
+//! let mut aligner = Aligner::new(-5, -1, score);
+//! // suffix of x overlapping prefix of y
+//! let alignment = aligner.semiglobal(x, y);
+//! let overhangs = overhangs(&alignment);
+//! assert_eq!((overhangs.x_prefix, overhangs.x_suffix), (9, 0));
+//! assert_eq!((overhangs.y_prefix, overhangs.y_suffix), (0, 9));
+//! assert_eq!(overhangs.overlap_kind(0), OverlapKind::DovetailXY);
+//! ```
+
+use crate::alignment::{Alignment, AlignmentOperation};
+
+/// The kind of overlap between two aligned sequences.
+#[derive(Copy, Clone, Eq, PartialEq, Ord, PartialOrd, Hash, Debug, Serialize, Deserialize)]
+pub enum OverlapKind {
+    /// `x` is contained in `y`.
+    XContained,
+    /// `y` is contained in `x`.
+    YContained,
+    /// The suffix of `x` overlaps the prefix of `y`.
+    DovetailXY,
+    /// The suffix of `y` overlaps the prefix of `x`.
+    DovetailYX,
+    /// Both sequences have overhangs on the same side, e.g. because of a repeat.
+    Internal,
+}
+
+/// Lengths of the unaligned prefixes and suffixes of both sequences of an alignment.
+#[derive(
+    Default, Copy, Clone, Eq, PartialEq, Ord, PartialOrd, Hash, Debug, Serialize, Deserialize,
+)]
+pub struct Overhangs {
+    pub x_prefix: usize,
+    pub x_suffix: usize,
+    pub y_prefix: usize,
+    pub y_suffix: usize,
+}
+
+impl Overhangs {
+    /// Classify the overlap, tolerating overhangs of up to `max_overhang` bases at the
+    /// overlapping ends. If both sequences are contained in each other (i.e. they overlap
+    /// entirely), `XContained` is reported.
+    pub fn overlap_kind(&self, max_overhang: usize) -> OverlapKind {
+        let short = |len: usize| len <= max_overhang;
+        if short(self.x_prefix) && short(self.x_suffix) {
+            OverlapKind::XContained
+        } else if short(self.y_prefix) && short(self.y_suffix) {
+            OverlapKind::YContained
+        } else if short(self.x_suffix) && short(self.y_prefix) {
+            OverlapKind::DovetailXY
+        } else if short(self.y_suffix) && short(self.x_prefix) {
+            OverlapKind::DovetailYX
+        } else {
+            OverlapKind::Internal
+        }
+    }
+}
+
+/// Return the overhangs of both sequences of the alignment, counting clipped bases and bases
+/// aligned to terminal gaps. If the alignment contains no aligned pair of bases, both
+/// sequences are considered entirely unaligned, and reported as prefix.
+pub fn overhangs(alignment: &Alignment) -> Overhangs {
+    let is_aligned = |op: &&AlignmentOperation| {
+        matches!(op, AlignmentOperation::Match | AlignmentOperation::Subst)
+    };
+    if !alignment.operations.iter().any(|op| is_aligned(&op)) {
+        return Overhangs {
+            x_prefix: alignment.xlen,
+            y_prefix: alignment.ylen,
+            ..Default::default()
+        };
+    }
+
+    // count the gaps before the first (or after the last) aligned pair of bases
+    let terminal_gaps = |ops: &mut dyn Iterator<Item = &AlignmentOperation>| {
+        let (mut ins, mut del) = (0, 0);
+        for op in ops.take_while(|op| !is_aligned(op)) {
+            match op {
+                AlignmentOperation::Ins => ins += 1,
+                AlignmentOperation::Del => del += 1,
+                _ => (),
+            }
+        }
+        (ins, del)
+    };
+    let (leading_ins, leading_del) = terminal_gaps(&mut alignment.operations.iter());
+    let (trailing_ins, trailing_del) = terminal_gaps(&mut alignment.operations.iter().rev());
+
+    Overhangs {
+        x_prefix: alignment.xstart + leading_ins,
+        x_suffix: alignment.xlen - alignment.xend + trailing_ins,
+        y_prefix: alignment.ystart + leading_del,
+        y_suffix: alignment.ylen - alignment.yend + trailing_del,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::alignment::pairwise::Aligner;
+
+    #[test]
+    fn test_overhangs_global() {
+        let x = b"AAAACCGTTGACGG";
+        let y = b"CCGTTGACGGTTTTT";
+        let score = |a: u8, b: u8| if a == b { 1i32 } else { -1i32 };
+        let mut aligner = Aligner::new(-1, -1, score);
+        let alignment = aligner.global(x, y);
+        let overhangs = overhangs(&alignment);
+        assert_eq!(
+            overhangs,
+            Overhangs {
+                x_prefix: 4,
+                x_suffix: 0,
+                y_prefix: 0,
+                y_suffix: 5
+            }
+        );
+        assert_eq!(overhangs.overlap_kind(0), OverlapKind::DovetailXY);
+        assert_eq!(overhangs.overlap_kind(4), OverlapKind::XContained);
+        assert_eq!(overhangs.overlap_kind(5), OverlapKind::XContained);
+    }
+
+    #[test]
+    fn test_overhangs_containment_and_local() {
+        let x = b"TGACGG";
+        let y = b"CCGTTGACGGTTTTT";
+        let score = |a: u8, b: u8| if a == b { 1i32 } else { -1i32 };
+        let mut aligner = Aligner::new(-5, -1, score);
+        let overhangs_semiglobal = overhangs(&aligner.semiglobal(x, y));
+        assert_eq!(
+            overhangs_semiglobal.overlap_kind(0),
+            OverlapKind::XContained
+        );
+        assert_eq!(
+            (overhangs_semiglobal.y_prefix, overhangs_semiglobal.y_suffix),
+            (4, 5)
+        );
+        assert_eq!(
+            overhangs(&aligner.semiglobal(y, x)).overlap_kind(0),
+            OverlapKind::YContained
+        );
+
+        let x = b"AAAAATGACGGAAAAA";
+        let overhangs_local = overhangs(&aligner.local(x, y));
+        assert_eq!(overhangs_local.x_prefix, 5);
+        assert_eq!(overhangs_local.overlap_kind(1), OverlapKind::Internal);
+
+        let unaligned = overhangs(&aligner.local(b"AAAA", b"CCC"));
+        assert_eq!((unaligned.x_prefix, unaligned.y_prefix), (4, 3));
+    }
+}
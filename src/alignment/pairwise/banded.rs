@@ -30,7 +30,7 @@
 //! let k = 8; // kmer match length
 //! let w = 6; // Window size for creating the band
 //! let mut aligner = Aligner::new(-5, -1, score, k, w);
-//! let alignment = aligner.local(x, y);
+//! let alignment = aligner.local(x, y).unwrap();
 //! // aligner.global(x, y), aligner.semiglobal(x, y) are also supported
 //! assert_eq!(alignment.ystart, 0);
 //! assert_eq!(alignment.xstart, 0);
@@ -41,7 +41,7 @@
 //! let y = b"CAGTTGTACTAGCATGACCAGTTGTACTAGCATGACAGCACACGTGTGCGCTATACAGTAAGTAGTAGTACACGTGTCA\
 //!     CAGTTGTACTAGCATGACCAGTTGTACTAGCATGAC";
 //! let y_kmers_hash = hash_kmers(y, k);
-//! let alignment = aligner.semiglobal_with_prehash(x, y, &y_kmers_hash);
+//! let alignment = aligner.semiglobal_with_prehash(x, y, &y_kmers_hash).unwrap();
 //! assert_eq!(alignment.score, 37);
 //!
 //! // In addition to the standard modes (Global, Semiglobal and Local), a custom alignment
@@ -63,11 +63,12 @@
 //!     xclip_suffix: MIN_SCORE,
 //!     yclip_prefix: 0,
 //!     yclip_suffix: 0,
+//!     long_gap: None,
 //! };
 //! let x = b"GGGGGGACGTACGTACGTGTGCATCATCATGTGCGTATCATAGATAGATGTAGATGATCCACAGT";
 //! let y = b"AAAAACGTACGTACGTGTGCATCATCATGTGCGTATCATAGATAGATGTAGATGATCCACAGTAAAA";
 //! let mut aligner = Aligner::with_capacity_and_scoring(x.len(), y.len(), scoring, k, w);
-//! let alignment = aligner.custom(x, y);
+//! let alignment = aligner.custom(x, y).unwrap();
 //! println!("{}", alignment.pretty(x, y, 80));
 //! assert_eq!(alignment.score, 49);
 //! let mut correct_ops = Vec::new();
@@ -82,7 +83,8 @@
 
 use crate::alignment::{Alignment, AlignmentOperation};
 use crate::utils::TextSlice;
-use std::cmp::{max, min, Ordering};
+use std::cmp::{max, min, Ordering, Reverse};
+use std::fmt;
 use std::i32;
 use std::ops::Range;
 
@@ -90,10 +92,131 @@ use super::*;
 use crate::alignment::pairwise::Scoring;
 use crate::alignment::sparse;
 use crate::alignment::sparse::HashMapFx;
+use crate::alphabets::protein::ReducedAlphabet;
+use thiserror::Error;
 
-const MAX_CELLS: usize = 5_000_000;
+/// Default maximum number of cells in the band (see `Aligner::set_max_cells`).
+pub const MAX_CELLS: usize = 5_000_000;
 const DEFAULT_MATCH_SCORE: i32 = 2;
 
+#[derive(Error, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Error {
+    #[error("band of {cells} cells exceeds the maximum of {max_cells} cells")]
+    BandTooLarge { cells: usize, max_cells: usize },
+}
+
+pub type Result<T, E = Error> = std::result::Result<T, E>;
+
+/// Recommend the k-mer length `k` and band width `w` for the banded aligner, given the
+/// lengths of the sequences and their expected divergence (fraction of differing positions,
+/// e.g. 0.01 for 99% identity).
+///
+/// The k-mer length is chosen long enough to avoid spurious matches between sequences of the
+/// given lengths, but short enough that an exact k-mer match still occurs with reasonable
+/// probability under the given divergence. The band width is chosen to cover the drift of
+/// the alignment path caused by indels between consecutive k-mer matches.
+/// These are heuristics; for critical applications, candidates can be compared on a
+/// representative subsample of the data with `probe_kw`.
+///
+/// # Example
+///
+/// ```
+/// use bio::alignment::pairwise::banded::recommend_kw;
+///
+/// let (k, w) = recommend_kw(10_000, 10_000, 0.01);
+/// assert_eq!((k, w), (10, 11));
+/// // more divergent sequences require shorter k-mers and wider bands
+/// let (k2, w2) = recommend_kw(10_000, 10_000, 0.25);
+/// assert!(k2 < k && w2 > w);
+/// ```
+pub fn recommend_kw(xlen: usize, ylen: usize, divergence: f64) -> (usize, usize) {
+    assert!(
+        (0.0..1.0).contains(&divergence),
+        "Expecting divergence in [0, 1)"
+    );
+    const MIN_K: usize = 4;
+    const MAX_K: usize = 32;
+    const MIN_W: usize = 10;
+    // extra bases beyond log4(len): each one divides the expected number of random matches
+    // of a k-mer by 4, so 2 keep it at about 1/16
+    const K_SPECIFICITY_MARGIN: usize = 2;
+    // the band is centred on the path through the k-mer matches and hence has to absorb drift
+    // to either side (factor 2), doubled again as slack for the variance of the indel count
+    const DRIFT_PER_ANCHOR_DIST: f64 = 4.0;
+    // the sparse alignment may chain matches that lie further apart than the expected anchor
+    // distance, so drift is always accounted over at least this many positions
+    const MIN_DRIFT_SPAN: f64 = 50.0;
+
+    // specificity: the expected number of random matches of a k-mer should be below one
+    let len = (xlen + ylen).max(1) as f64;
+    let k_specific = (len.ln() / 4f64.ln()).ceil() as usize + K_SPECIFICITY_MARGIN;
+    // sensitivity: a k-mer should be free of differences with probability >= 10%
+    let k_sensitive = if divergence > 0.0 {
+        (0.1f64.ln() / (1.0 - divergence).ln()).floor() as usize
+    } else {
+        MAX_K
+    };
+    let k = k_specific.min(k_sensitive).clamp(MIN_K, MAX_K);
+
+    // expected distance between error free k-mers, and the indel drift accumulated over it
+    let anchor_dist = 1.0 / (1.0 - divergence).powi(k as i32);
+    let drift = divergence * (DRIFT_PER_ANCHOR_DIST * anchor_dist + MIN_DRIFT_SPAN);
+    let w = (MIN_W + drift.ceil() as usize).min(xlen.max(ylen).max(MIN_W));
+
+    (k, w)
+}
+
+/// Compare candidate values of `k` and `w` by aligning `x` and `y` in custom mode with
+/// the given scoring, returning the candidate with the best alignment score, along with the
+/// alignment. On ties, the candidate with the smaller band is chosen. Candidates whose band
+/// exceeds `MAX_CELLS` are skipped. Returns `None` if no candidate remains.
+///
+/// Since each candidate requires a full banded alignment, `x` and `y` should be a
+/// representative subsample of the sequences to align.
+///
+/// # Example
+///
+/// ```
+/// use bio::alignment::pairwise::banded::probe_kw;
+/// use bio::alignment::pairwise::Scoring;
+///
+/// let x = b"AGCACACGTGTGCGCTATACAGTAAGTAGTAGTACACGTGTCACAGTTGTACTAGCATGAC";
+/// let y = b"AGCACACGTGTGCGCTATACAGTACACGTGTCACAGTTGTACTAGCATGAC";
+/// let scoring = Scoring::from_scores(-5, -1, 1, -1).xclip(0).yclip(0);
+/// let ((k, w), alignment) = probe_kw(x, y, &scoring, &[(12, 20), (8, 6)]).unwrap();
+/// assert_eq!(alignment.score, 36);
+/// // both candidates yield the same score, but (8, 6) requires a smaller band
+/// assert_eq!((k, w), (8, 6));
+/// ```
+pub fn probe_kw<F: MatchFunc + Clone>(
+    x: TextSlice<'_>,
+    y: TextSlice<'_>,
+    scoring: &Scoring<F>,
+    candidates: &[(usize, usize)],
+) -> Option<((usize, usize), Alignment)> {
+    let mut best: Option<((usize, usize), Alignment, usize)> = None;
+    for &(k, w) in candidates {
+        let mut aligner =
+            Aligner::with_capacity_and_scoring(x.len(), y.len(), scoring.clone(), k, w);
+        let alignment = match aligner.custom(x, y) {
+            Ok(alignment) => alignment,
+            Err(Error::BandTooLarge { .. }) => continue,
+        };
+        let cells = aligner.band.num_cells();
+        let better = match &best {
+            Some((_, best_alignment, best_cells)) => {
+                (alignment.score, Reverse(cells)) > (best_alignment.score, Reverse(*best_cells))
+            }
+            None => true,
+        };
+        if better {
+            best = Some(((k, w), alignment, cells));
+        }
+    }
+
+    best.map(|(kw, alignment, _)| (kw, alignment))
+}
+
 /// A banded implementation of Smith-Waterman aligner (SWA).
 /// Unlike the full SWA, this implementation computes the alignment between a pair of sequences
 /// only inside a 'band' withing the dynamic programming matrix. The band is constructed using the
@@ -105,27 +228,249 @@ const DEFAULT_MATCH_SCORE: i32 = 2;
 /// by setting the band to contain the full matrix.
 ///
 /// Banded aligner will proceed to compute the alignment only when the total number of cells
-/// in the band is less than MAX_CELLS (currently set to 10 million), otherwise it returns an
-/// empty alignment
+/// in the band does not exceed `MAX_CELLS` (currently set to 5 million), otherwise it returns
+/// `Error::BandTooLarge`. The limit can be changed with `set_max_cells`.
 #[allow(non_snake_case)]
-#[derive(Default, Clone, Eq, PartialEq, Hash, Debug, Serialize, Deserialize)]
+#[derive(Clone, Eq, PartialEq, Hash, Debug, Serialize, Deserialize)]
 pub struct Aligner<F: MatchFunc> {
     S: [Vec<i32>; 2],
     I: [Vec<i32>; 2],
     D: [Vec<i32>; 2],
+    #[serde(default)]
+    I2: [Vec<i32>; 2],
+    #[serde(default)]
+    D2: [Vec<i32>; 2],
     Lx: Vec<usize>,
     Ly: Vec<usize>,
     Sn: Vec<i32>,
     traceback: Traceback,
+    #[serde(default)]
+    traceback2: Traceback,
     scoring: Scoring<F>,
 
     band: Band,
     k: usize,
     w: usize,
+    repeat_filter: Option<RepeatFilter>,
+    seed_alphabet: Option<ReducedAlphabet>,
+    #[serde(default = "default_max_cells")]
+    max_cells: Option<usize>,
+}
+
+fn default_max_cells() -> Option<usize> {
+    Some(MAX_CELLS)
+}
+
+impl<F: MatchFunc + Default> Default for Aligner<F> {
+    fn default() -> Self {
+        Aligner {
+            S: Default::default(),
+            I: Default::default(),
+            D: Default::default(),
+            I2: Default::default(),
+            D2: Default::default(),
+            Lx: Vec::new(),
+            Ly: Vec::new(),
+            Sn: Vec::new(),
+            traceback: Traceback::default(),
+            traceback2: Traceback::default(),
+            scoring: Scoring::default(),
+            band: Band::default(),
+            k: 0,
+            w: 0,
+            repeat_filter: None,
+            seed_alphabet: None,
+            max_cells: default_max_cells(),
+        }
+    }
 }
 
 const DEFAULT_ALIGNER_CAPACITY: usize = 200;
 
+/// Parameters for keeping band construction fast on repetitive sequences.
+///
+/// K-mers occurring more than `max_kmer_occurrences` times in y are not used as anchors.
+/// If the remaining anchors still exceed `max_matches` (i.e. the sequences are dominated by
+/// repeats), only k-mers that are unique in y are used, and if needed, these are thinned out
+/// evenly along x until at most `max_matches` anchors remain.
+///
+/// # Example
+///
+/// ```
+/// use bio::alignment::pairwise::banded::{Aligner, RepeatFilter};
+///
+/// let x = b"ACGTTGCAAGCTAGCTAGCTAGCTAGCTAGCTAGCTAGCTAGCTTTGACCATGA";
+/// let y = b"ACGTTGCAAGCTAGCTAGCTAGCTAGCTAGCTAGCTAGCTTTGACCATGA";
+/// let score = |a: u8, b: u8| if a == b { 1i32 } else { -1i32 };
+/// let mut aligner = Aligner::new(-5, -1, score, 4, 6);
+/// aligner.set_repeat_filter(Some(RepeatFilter::new(2, 100)));
+/// let alignment = aligner.global(x, y).unwrap();
+/// assert_eq!(alignment.score, 41);
+/// ```
+#[derive(Copy, Clone, Eq, PartialEq, Ord, PartialOrd, Hash, Debug, Serialize, Deserialize)]
+pub struct RepeatFilter {
+    pub max_kmer_occurrences: usize,
+    pub max_matches: usize,
+}
+
+impl RepeatFilter {
+    /// Create a new repeat filter.
+    ///
+    /// # Arguments
+    ///
+    /// * `max_kmer_occurrences` - ignore k-mers occurring more often than this in y
+    /// * `max_matches` - maximum number of k-mer matches used for constructing the band
+    pub fn new(max_kmer_occurrences: usize, max_matches: usize) -> Self {
+        RepeatFilter {
+            max_kmer_occurrences,
+            max_matches,
+        }
+    }
+
+    /// Find the k-mer matches between x and the pre-hashed y, applying the filter.
+    fn find_matches(
+        &self,
+        x: TextSlice<'_>,
+        y_kmer_hash: &HashMapFx<&[u8], Vec<u32>>,
+        k: usize,
+    ) -> Vec<(u32, u32)> {
+        let matches = sparse::find_kmer_matches_seq2_hashed_max_occ(
+            x,
+            y_kmer_hash,
+            k,
+            self.max_kmer_occurrences,
+        );
+        if matches.len() <= self.max_matches {
+            return matches;
+        }
+        // repeat dense sequences: fall back to k-mers that are unique in y
+        let matches = sparse::find_kmer_matches_seq2_hashed_max_occ(x, y_kmer_hash, k, 1);
+        if matches.len() <= self.max_matches || self.max_matches == 0 {
+            return matches;
+        }
+        let step = (matches.len() + self.max_matches - 1) / self.max_matches;
+        matches.into_iter().step_by(step).collect()
+    }
+}
+
+/// A builder for banded aligners, validating the parameters. Without further configuration,
+/// it uses the scoring of `pairwise::AlignerBuilder`, a k-mer length of 8, a band width of 6,
+/// no repeat filter and seed alphabet and at most `MAX_CELLS` cells. Missing fields are set to
+/// these defaults when deserializing.
+///
+/// # Example
+///
+/// ```
+/// use bio::alignment::pairwise::banded::AlignerBuilder;
+/// use bio::alignment::pairwise::{self, Error};
+///
+/// let x = b"AGCACACGTGTGCGCTATACAGTAAGTAGTAGTACACGTGTCACAGTTGTACTAGCATGAC";
+/// let y = b"AGCACACGTGTGCGCTATACAGTACACGTGTCACAGTTGTACTAGCATGAC";
+/// let mut aligner = AlignerBuilder::new().k(8).w(6).build().unwrap();
+/// let alignment = aligner.local(x, y).unwrap();
+/// assert_eq!((alignment.xstart, alignment.ystart), (0, 0));
+///
+/// let builder = AlignerBuilder::new().scoring(pairwise::AlignerBuilder::new().gap_open(5));
+/// assert_eq!(builder.build().unwrap_err(), Error::PositiveGapScore { score: 5 });
+/// assert_eq!(AlignerBuilder::new().k(0).build().unwrap_err(), Error::ZeroKmerLength);
+/// ```
+#[derive(Clone, Eq, PartialEq, Hash, Debug, Serialize, Deserialize)]
+#[serde(default)]
+pub struct AlignerBuilder {
+    scoring: super::AlignerBuilder,
+    k: usize,
+    w: usize,
+    repeat_filter: Option<RepeatFilter>,
+    seed_alphabet: Option<ReducedAlphabet>,
+    max_cells: Option<usize>,
+}
+
+impl Default for AlignerBuilder {
+    fn default() -> Self {
+        AlignerBuilder {
+            scoring: super::AlignerBuilder::default(),
+            k: 8,
+            w: 6,
+            repeat_filter: None,
+            seed_alphabet: None,
+            max_cells: default_max_cells(),
+        }
+    }
+}
+
+impl AlignerBuilder {
+    /// Create a new builder with the default configuration.
+    pub fn new() -> Self {
+        AlignerBuilder::default()
+    }
+
+    /// Use the scores and capacity of the given builder (see `pairwise::AlignerBuilder`).
+    pub fn scoring(mut self, scoring: super::AlignerBuilder) -> Self {
+        self.scoring = scoring;
+        self
+    }
+
+    /// Use the given k-mer length for constructing the band (default: 8,
+    /// see also `recommend_kw`).
+    pub fn k(mut self, k: usize) -> Self {
+        self.k = k;
+        self
+    }
+
+    /// Use the given band width (default: 6, see also `recommend_kw`).
+    pub fn w(mut self, w: usize) -> Self {
+        self.w = w;
+        self
+    }
+
+    /// Use the given repeat filter, see `Aligner::set_repeat_filter` (default: none).
+    pub fn repeat_filter(mut self, repeat_filter: Option<RepeatFilter>) -> Self {
+        self.repeat_filter = repeat_filter;
+        self
+    }
+
+    /// Use the given seed alphabet, see `Aligner::set_seed_alphabet` (default: none).
+    pub fn seed_alphabet(mut self, seed_alphabet: Option<ReducedAlphabet>) -> Self {
+        self.seed_alphabet = seed_alphabet;
+        self
+    }
+
+    /// Use the given maximum number of cells in the band, see `Aligner::set_max_cells`
+    /// (default: `MAX_CELLS`).
+    pub fn max_cells(mut self, max_cells: Option<usize>) -> Self {
+        self.max_cells = max_cells;
+        self
+    }
+
+    fn configure<F: MatchFunc>(&self, scoring: Scoring<F>) -> super::Result<Aligner<F>> {
+        if self.k == 0 {
+            return Err(super::Error::ZeroKmerLength);
+        }
+        let (m, n) = self.scoring.capacity;
+        let mut aligner = Aligner::with_capacity_and_scoring(m, n, scoring, self.k, self.w);
+        aligner.set_repeat_filter(self.repeat_filter);
+        aligner.set_seed_alphabet(self.seed_alphabet.clone());
+        aligner.set_max_cells(self.max_cells);
+        Ok(aligner)
+    }
+
+    /// Build the aligner with the given score function instead of the match and mismatch
+    /// scores (e.g. `scores::blosum62`), see `pairwise::AlignerBuilder::scoring_with`.
+    ///
+    /// # Errors
+    /// * `pairwise::Error::ZeroKmerLength` - the k-mer length is zero
+    /// * see `pairwise::AlignerBuilder::scoring_with`
+    pub fn build_with<F: MatchFunc>(&self, match_fn: F) -> super::Result<Aligner<F>> {
+        self.configure(self.scoring.scoring_with(match_fn)?)
+    }
+
+    /// Build the aligner, see `pairwise::AlignerBuilder::scoring` and
+    /// `AlignerBuilder::build_with`.
+    pub fn build(&self) -> super::Result<Aligner<MatchParams>> {
+        self.configure(self.scoring.scoring()?)
+    }
+}
+
 impl<F: MatchFunc> Aligner<F> {
     /// Create new aligner instance with given gap open and gap extend penalties
     /// and the score function.
@@ -175,13 +520,19 @@ impl<F: MatchFunc> Aligner<F> {
             S: [Vec::with_capacity(m + 1), Vec::with_capacity(m + 1)],
             I: [Vec::with_capacity(m + 1), Vec::with_capacity(m + 1)],
             D: [Vec::with_capacity(m + 1), Vec::with_capacity(m + 1)],
+            I2: [Vec::new(), Vec::new()],
+            D2: [Vec::new(), Vec::new()],
             Lx: Vec::with_capacity(n + 1),
             Ly: Vec::with_capacity(m + 1),
             Sn: Vec::with_capacity(m + 1),
             traceback: Traceback::with_capacity(m, n),
+            traceback2: Traceback::with_capacity(0, 0),
             scoring: Scoring::new(gap_open, gap_extend, match_fn),
             k,
             w,
+            repeat_filter: None,
+            seed_alphabet: None,
+            max_cells: default_max_cells(),
         }
     }
 
@@ -226,13 +577,19 @@ impl<F: MatchFunc> Aligner<F> {
             S: [Vec::with_capacity(m + 1), Vec::with_capacity(m + 1)],
             I: [Vec::with_capacity(m + 1), Vec::with_capacity(m + 1)],
             D: [Vec::with_capacity(m + 1), Vec::with_capacity(m + 1)],
+            I2: [Vec::new(), Vec::new()],
+            D2: [Vec::new(), Vec::new()],
             Lx: Vec::with_capacity(n + 1),
             Ly: Vec::with_capacity(m + 1),
             Sn: Vec::with_capacity(m + 1),
             traceback: Traceback::with_capacity(m, n),
+            traceback2: Traceback::with_capacity(0, 0),
             scoring,
             k,
             w,
+            repeat_filter: None,
+            seed_alphabet: None,
+            max_cells: default_max_cells(),
         }
     }
 
@@ -263,19 +620,107 @@ impl<F: MatchFunc> Aligner<F> {
         &mut self.scoring
     }
 
+    /// Set the repeat filter used when constructing the band from k-mer matches
+    /// (see `RepeatFilter`). By default, all k-mer matches are used.
+    /// The filter does not apply to the methods taking pre-computed matches.
+    pub fn set_repeat_filter(&mut self, repeat_filter: Option<RepeatFilter>) {
+        self.repeat_filter = repeat_filter;
+    }
+
+    /// Set a reduced alphabet used for finding the k-mer matches the band is constructed
+    /// from (see `alphabets::protein::ReducedAlphabet`). By default, k-mers have to match
+    /// exactly. For divergent protein sequences, exact k-mer matches are rare and the band
+    /// falls back to the full matrix. Seeding on k-mers over a reduced alphabet tolerates
+    /// conservative substitutions. The alignment itself is always computed on the original
+    /// sequences with the given scoring (e.g. `scores::blosum62`).
+    /// As with the repeat filter, this does not apply to the methods taking pre-computed matches.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use bio::alignment::pairwise::banded::Aligner;
+    /// use bio::alphabets::protein::ReducedAlphabet;
+    /// use bio::scores::blosum62;
+    ///
+    /// let x = b"MKVLITGAGSGIGLEIARRFAEEGAHVVLVDINEEGLKAVAEEL";
+    /// let y = b"MRILVTGASSGIGKAIALELAKRGHQVIAVDINEEGLRALVEEI";
+    /// let mut aligner = Aligner::new(-11, -1, blosum62, 4, 6);
+    /// aligner.set_seed_alphabet(Some(ReducedAlphabet::murphy10()));
+    /// let alignment = aligner.global(x, y).unwrap();
+    /// assert_eq!(alignment.x_aln_len(), x.len());
+    /// ```
+    pub fn set_seed_alphabet(&mut self, seed_alphabet: Option<ReducedAlphabet>) {
+        self.seed_alphabet = seed_alphabet;
+    }
+
+    /// Set the maximum number of cells in the band, or `None` for no limit. If the band
+    /// exceeds it, no alignment is computed and `Error::BandTooLarge` is returned instead.
+    /// By default, the limit is `MAX_CELLS`.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use bio::alignment::pairwise::banded::{Aligner, Error};
+    ///
+    /// let x = b"ACCGTGGATGGGCGCGATCG";
+    /// let y = b"TTTTTTTTTTTTTTTTTTTT";
+    /// let score = |a: u8, b: u8| if a == b { 1i32 } else { -1i32 };
+    /// let mut aligner = Aligner::new(-5, -1, score, 8, 6);
+    /// // without k-mer matches, the band covers the full matrix
+    /// aligner.set_max_cells(Some(100));
+    /// assert_eq!(
+    ///     aligner.global(x, y),
+    ///     Err(Error::BandTooLarge {
+    ///         cells: 441,
+    ///         max_cells: 100
+    ///     })
+    /// );
+    /// aligner.set_max_cells(None);
+    /// assert!(aligner.global(x, y).is_ok());
+    /// ```
+    pub fn set_max_cells(&mut self, max_cells: Option<usize>) {
+        self.max_cells = max_cells;
+    }
+
+    /// Find the k-mer matches between x and the pre-hashed y, applying the repeat filter.
+    fn find_matches(
+        &self,
+        x: TextSlice<'_>,
+        y_kmer_hash: &HashMapFx<&[u8], Vec<u32>>,
+    ) -> Vec<(u32, u32)> {
+        match self.repeat_filter {
+            Some(filter) => filter.find_matches(x, y_kmer_hash, self.k),
+            None => sparse::find_kmer_matches_seq2_hashed(x, y_kmer_hash, self.k),
+        }
+    }
+
     /// Compute the alignment with custom clip penalties
     ///
     /// # Arguments
     ///
     /// * `x` - Textslice
     /// * `y` - Textslice
-    pub fn custom(&mut self, x: TextSlice<'_>, y: TextSlice<'_>) -> Alignment {
-        self.band = Band::create(x, y, self.k, self.w, &self.scoring);
+    pub fn custom(&mut self, x: TextSlice<'_>, y: TextSlice<'_>) -> Result<Alignment> {
+        self.band = match (&self.seed_alphabet, self.repeat_filter) {
+            (None, None) => Band::create(x, y, self.k, self.w, &self.scoring),
+            (Some(seed_alphabet), _) => {
+                let (x_seed, y_seed) = (seed_alphabet.reduce(x), seed_alphabet.reduce(y));
+                let y_kmer_hash = sparse::hash_kmers(&y_seed, self.k);
+                let matches = self.find_matches(&x_seed, &y_kmer_hash);
+                Band::create_with_matches(x, y, self.k, self.w, &self.scoring, &matches)
+            }
+            (None, Some(_)) => {
+                let y_kmer_hash = sparse::hash_kmers(y, self.k);
+                let matches = self.find_matches(x, &y_kmer_hash);
+                Band::create_with_matches(x, y, self.k, self.w, &self.scoring, &matches)
+            }
+        };
         self.compute_alignment(x, y)
     }
 
     /// Compute the alignment with custom clip penalties with 'y' being pre-hashed
-    /// (see sparse::hash_kmers)
+    /// (see sparse::hash_kmers). If a seed alphabet is set, 'y' has to be hashed after
+    /// reducing it to the seed alphabet.
     ///
     /// # Arguments
     ///
@@ -286,8 +731,49 @@ impl<F: MatchFunc> Aligner<F> {
         x: TextSlice<'_>,
         y: TextSlice<'_>,
         y_kmer_hash: &HashMapFx<&[u8], Vec<u32>>,
-    ) -> Alignment {
-        self.band = Band::create_with_prehash(x, y, self.k, self.w, &self.scoring, y_kmer_hash);
+    ) -> Result<Alignment> {
+        self.band = match (&self.seed_alphabet, self.repeat_filter) {
+            (None, None) => {
+                Band::create_with_prehash(x, y, self.k, self.w, &self.scoring, y_kmer_hash)
+            }
+            (Some(seed_alphabet), _) => {
+                let matches = self.find_matches(&seed_alphabet.reduce(x), y_kmer_hash);
+                Band::create_with_matches(x, y, self.k, self.w, &self.scoring, &matches)
+            }
+            (None, Some(_)) => {
+                let matches = self.find_matches(x, y_kmer_hash);
+                Band::create_with_matches(x, y, self.k, self.w, &self.scoring, &matches)
+            }
+        };
+        self.compute_alignment(x, y)
+    }
+
+    /// Compute the alignment with custom clip penalties with 'x' being pre-hashed
+    /// (see sparse::hash_kmers). This is the symmetric case of `custom_with_prehash`, useful
+    /// for aligning the same query against many targets. If a seed alphabet is set, 'x' has
+    /// to be hashed after reducing it to the seed alphabet. If a repeat filter is set,
+    /// its k-mer occurrence limit applies to 'x' instead of 'y'.
+    ///
+    /// # Arguments
+    ///
+    /// * `x` - Textslice
+    /// * `y` - Textslice
+    /// * `x_kmer_hash` - the k-mer hash of 'x'
+    pub fn custom_with_query_prehash(
+        &mut self,
+        x: TextSlice<'_>,
+        y: TextSlice<'_>,
+        x_kmer_hash: &HashMapFx<&[u8], Vec<u32>>,
+    ) -> Result<Alignment> {
+        let mut matches = match &self.seed_alphabet {
+            Some(seed_alphabet) => self.find_matches(&seed_alphabet.reduce(y), x_kmer_hash),
+            None => self.find_matches(y, x_kmer_hash),
+        };
+        for m in matches.iter_mut() {
+            *m = (m.1, m.0);
+        }
+        matches.sort_unstable();
+        self.band = Band::create_with_matches(x, y, self.k, self.w, &self.scoring, &matches);
         self.compute_alignment(x, y)
     }
 
@@ -305,7 +791,7 @@ impl<F: MatchFunc> Aligner<F> {
         x: TextSlice<'_>,
         y: TextSlice<'_>,
         matches: &[(u32, u32)],
-    ) -> Alignment {
+    ) -> Result<Alignment> {
         self.band = Band::create_with_matches(x, y, self.k, self.w, &self.scoring, matches);
         self.compute_alignment(x, y)
     }
@@ -332,7 +818,7 @@ impl<F: MatchFunc> Aligner<F> {
         matches: Vec<(u32, u32)>,
         allowed_mismatches: Option<usize>,
         use_lcskpp_union: bool,
-    ) -> Alignment {
+    ) -> Result<Alignment> {
         let expanded_matches = match allowed_mismatches {
             Some(m) => sparse::expand_kmer_matches(x, y, self.k, &matches, m),
             None => matches,
@@ -384,7 +870,7 @@ impl<F: MatchFunc> Aligner<F> {
         y: TextSlice,
         matches: &[(u32, u32)],
         path: &[usize],
-    ) -> Alignment {
+    ) -> Result<Alignment> {
         self.band =
             Band::create_from_match_path(x, y, self.k, self.w, &self.scoring, path, matches);
         self.compute_alignment(x, y)
@@ -393,31 +879,28 @@ impl<F: MatchFunc> Aligner<F> {
     // Computes the alignment. The band needs to be populated prior
     // to calling this function
     #[inline(never)]
-    fn compute_alignment(&mut self, x: TextSlice<'_>, y: TextSlice<'_>) -> Alignment {
-        if self.band.num_cells() > MAX_CELLS {
-            // Too many cells in the band. Return an empty alignment
-            return Alignment {
-                score: MIN_SCORE,
-                ystart: 0,
-                xstart: 0,
-                yend: 0,
-                xend: 0,
-                ylen: 0,
-                xlen: 0,
-                operations: Vec::new(),
-                mode: AlignmentMode::Custom,
-            };
+    fn compute_alignment(&mut self, x: TextSlice<'_>, y: TextSlice<'_>) -> Result<Alignment> {
+        let cells = self.band.num_cells();
+        if let Some(max_cells) = self.max_cells.filter(|&max_cells| cells > max_cells) {
+            return Err(Error::BandTooLarge { cells, max_cells });
         }
 
         let (m, n) = (x.len(), y.len());
         self.traceback.init(m, n);
+        if self.scoring.long_gap.is_some() {
+            self.traceback2.init(m, n);
+        }
 
         for k in 0..2 {
             self.I[k].clear();
             self.D[k].clear();
+            self.I2[k].clear();
+            self.D2[k].clear();
             self.S[k].clear();
             self.D[k].extend(repeat(MIN_SCORE).take(m + 1));
             self.I[k].extend(repeat(MIN_SCORE).take(m + 1));
+            self.D2[k].extend(repeat(MIN_SCORE).take(m + 1));
+            self.I2[k].extend(repeat(MIN_SCORE).take(m + 1));
             self.S[k].extend(repeat(MIN_SCORE).take(m + 1));
         }
         self.Lx.clear();
@@ -438,6 +921,7 @@ impl<F: MatchFunc> Aligner<F> {
 
             for i in max(1, i_start)..i_end {
                 let mut tb = TracebackCell::new();
+                let mut tb2 = TracebackCell::new();
                 tb.set_all(TB_START);
                 if i == 1 {
                     self.I[curr][i] = self.scoring.gap_open + self.scoring.gap_extend;
@@ -455,6 +939,22 @@ impl<F: MatchFunc> Aligner<F> {
                         tb.set_i_bits(TB_XCLIP_PREFIX);
                     }
                 }
+                if let Some((gap_open2, gap_extend2)) = self.scoring.long_gap {
+                    if i == 1 {
+                        self.I2[curr][i] = gap_open2 + gap_extend2;
+                        tb2.set_i_bits(TB_START);
+                    } else {
+                        let i_score = gap_open2 + gap_extend2 * (i as i32);
+                        let c_score = self.scoring.xclip_prefix + gap_open2 + gap_extend2;
+                        if i_score > c_score {
+                            self.I2[curr][i] = i_score;
+                            tb2.set_i_bits(TB_INS2);
+                        } else {
+                            self.I2[curr][i] = c_score;
+                            tb2.set_i_bits(TB_XCLIP_PREFIX);
+                        }
+                    }
+                }
 
                 if i == m {
                     tb.set_s_bits(TB_XCLIP_SUFFIX);
@@ -465,6 +965,11 @@ impl<F: MatchFunc> Aligner<F> {
                     tb.set_s_bits(TB_INS);
                 }
 
+                if self.I2[curr][i] > self.S[curr][i] {
+                    self.S[curr][i] = self.I2[curr][i];
+                    tb.set_s_bits(TB_INS2);
+                }
+
                 if self.scoring.xclip_prefix > self.S[curr][i] {
                     self.S[curr][i] = self.scoring.xclip_prefix;
                     tb.set_s_bits(TB_XCLIP_PREFIX);
@@ -478,11 +983,15 @@ impl<F: MatchFunc> Aligner<F> {
                 }
 
                 self.traceback.set(i, 0, tb);
+                if self.scoring.long_gap.is_some() {
+                    self.traceback2.set(i, 0, tb2);
+                }
             }
 
             for i in i_end..min(m + 1, self.band.ranges[min(n, 1)].end) {
                 self.S[curr][i] = MIN_SCORE;
                 self.I[curr][i] = MIN_SCORE;
+                self.I2[curr][i] = MIN_SCORE;
             }
 
             if i_end < (m + 1) {
@@ -509,7 +1018,9 @@ impl<F: MatchFunc> Aligner<F> {
             if i_start == 0 {
                 // Handle i = 0
                 let mut tb = TracebackCell::new();
+                let mut tb2 = TracebackCell::new();
                 self.I[curr][0] = MIN_SCORE;
+                self.I2[curr][0] = MIN_SCORE;
 
                 if j == 1 {
                     self.D[curr][0] = self.scoring.gap_open + self.scoring.gap_extend;
@@ -527,6 +1038,22 @@ impl<F: MatchFunc> Aligner<F> {
                         tb.set_d_bits(TB_YCLIP_PREFIX);
                     }
                 }
+                if let Some((gap_open2, gap_extend2)) = self.scoring.long_gap {
+                    if j == 1 {
+                        self.D2[curr][0] = gap_open2 + gap_extend2;
+                        tb2.set_d_bits(TB_START);
+                    } else {
+                        let d_score = gap_open2 + gap_extend2 * (j as i32);
+                        let c_score = self.scoring.yclip_prefix + gap_open2 + gap_extend2;
+                        if d_score > c_score {
+                            self.D2[curr][0] = d_score;
+                            tb2.set_d_bits(TB_DEL2);
+                        } else {
+                            self.D2[curr][0] = c_score;
+                            tb2.set_d_bits(TB_YCLIP_PREFIX);
+                        }
+                    }
+                }
 
                 if self.D[curr][0] > self.scoring.yclip_prefix {
                     self.S[curr][0] = self.D[curr][0];
@@ -535,6 +1062,10 @@ impl<F: MatchFunc> Aligner<F> {
                     self.S[curr][0] = self.scoring.yclip_prefix;
                     tb.set_s_bits(TB_YCLIP_PREFIX);
                 }
+                if self.D2[curr][0] > self.S[curr][0] {
+                    self.S[curr][0] = self.D2[curr][0];
+                    tb.set_s_bits(TB_DEL2);
+                }
 
                 // Track the score if we do suffix clip (y) from here
                 if self.S[curr][0] + self.scoring.yclip_suffix > self.Sn[0] {
@@ -543,12 +1074,17 @@ impl<F: MatchFunc> Aligner<F> {
                     self.traceback.get_mut(0, n).set_s_bits(TB_YCLIP_SUFFIX);
                 }
                 self.traceback.set(0, j, tb);
+                if self.scoring.long_gap.is_some() {
+                    self.traceback2.set(0, j, tb2);
+                }
             }
 
             for i in i_start.saturating_sub(1)..i_start {
                 self.S[curr][i] = MIN_SCORE;
                 self.I[curr][i] = MIN_SCORE;
                 self.D[curr][i] = MIN_SCORE;
+                self.I2[curr][i] = MIN_SCORE;
+                self.D2[curr][i] = MIN_SCORE;
             }
             self.S[curr][m] = MIN_SCORE;
 
@@ -560,12 +1096,13 @@ impl<F: MatchFunc> Aligner<F> {
                     } else {
                         self.scoring.yclip_prefix
                     },
-                    self.scoring.gap_open + self.scoring.gap_extend * (j as i32),
+                    self.scoring.gap_score(j),
                 );
 
             for i in max(1, i_start)..i_end {
                 let p = x[i - 1];
                 let mut tb = TracebackCell::new();
+                let mut tb2 = TracebackCell::new();
 
                 let m_score = self.S[prev][i - 1] + self.scoring.match_fn.score(p, q);
 
@@ -599,6 +1136,38 @@ impl<F: MatchFunc> Aligner<F> {
                     tb.set_d_bits(self.traceback.get(i, j - 1).get_s_bits());
                 }
 
+                let (best_i2_score, best_d2_score) = match self.scoring.long_gap {
+                    Some((gap_open2, gap_extend2)) => {
+                        let i_score = self.I2[curr][i - 1] + gap_extend2;
+                        let s_score = self.S[curr][i - 1] + gap_open2 + gap_extend2;
+                        let mut best_i2_score = if i_score > s_score {
+                            tb2.set_i_bits(TB_INS2);
+                            i_score
+                        } else {
+                            tb2.set_i_bits(self.traceback.get(i - 1, j).get_s_bits());
+                            s_score
+                        };
+                        if j == n {
+                            let clip_score = self.Sn[i - 1] + gap_open2 + gap_extend2;
+                            if clip_score > best_i2_score {
+                                best_i2_score = clip_score;
+                                tb2.set_i_bits(TB_YCLIP_SUFFIX);
+                            }
+                        }
+                        let d_score = self.D2[prev][i] + gap_extend2;
+                        let s_score = self.S[prev][i] + gap_open2 + gap_extend2;
+                        let best_d2_score = if d_score > s_score {
+                            tb2.set_d_bits(TB_DEL2);
+                            d_score
+                        } else {
+                            tb2.set_d_bits(self.traceback.get(i, j - 1).get_s_bits());
+                            s_score
+                        };
+                        (best_i2_score, best_d2_score)
+                    }
+                    None => (MIN_SCORE, MIN_SCORE),
+                };
+
                 if i == m {
                     tb.set_s_bits(TB_XCLIP_SUFFIX);
                 } else {
@@ -616,19 +1185,27 @@ impl<F: MatchFunc> Aligner<F> {
                     tb.set_s_bits(TB_INS);
                 }
 
+                if best_i2_score > best_s_score {
+                    best_s_score = best_i2_score;
+                    tb.set_s_bits(TB_INS2);
+                }
+
                 if best_d_score > best_s_score {
                     best_s_score = best_d_score;
                     tb.set_s_bits(TB_DEL);
                 }
 
+                if best_d2_score > best_s_score {
+                    best_s_score = best_d2_score;
+                    tb.set_s_bits(TB_DEL2);
+                }
+
                 if xclip_score > best_s_score {
                     best_s_score = xclip_score;
                     tb.set_s_bits(TB_XCLIP_PREFIX);
                 }
 
-                let yclip_score = self.scoring.yclip_prefix
-                    + self.scoring.gap_open
-                    + self.scoring.gap_extend * (i as i32);
+                let yclip_score = self.scoring.yclip_prefix + self.scoring.gap_score(i);
                 if yclip_score > best_s_score {
                     best_s_score = yclip_score;
                     tb.set_s_bits(TB_YCLIP_PREFIX);
@@ -637,6 +1214,8 @@ impl<F: MatchFunc> Aligner<F> {
                 self.S[curr][i] = best_s_score;
                 self.I[curr][i] = best_i_score;
                 self.D[curr][i] = best_d_score;
+                self.I2[curr][i] = best_i2_score;
+                self.D2[curr][i] = best_d2_score;
 
                 // Track the score if we do suffix clip (x) from here
                 if self.S[curr][i] + self.scoring.xclip_suffix > self.S[curr][m] {
@@ -653,6 +1232,9 @@ impl<F: MatchFunc> Aligner<F> {
                 }
 
                 self.traceback.set(i, j, tb);
+                if self.scoring.long_gap.is_some() {
+                    self.traceback2.set(i, j, tb2);
+                }
             }
 
             // Suffix clip (y) from i = m and reset Sn[m] if required
@@ -670,6 +1252,8 @@ impl<F: MatchFunc> Aligner<F> {
                 self.S[curr][i] = MIN_SCORE;
                 self.I[curr][i] = MIN_SCORE;
                 self.D[curr][i] = MIN_SCORE;
+                self.I2[curr][i] = MIN_SCORE;
+                self.D2[curr][i] = MIN_SCORE;
             }
         }
 
@@ -713,10 +1297,27 @@ impl<F: MatchFunc> Aligner<F> {
                     self.traceback.get_mut(m, j).set_s_bits(TB_XCLIP_SUFFIX);
                 }
             }
+            if let Some((gap_open2, gap_extend2)) = self.scoring.long_gap {
+                let s_score = self.S[curr][i - 1] + gap_open2 + gap_extend2;
+                if s_score > self.I2[curr][i] {
+                    self.I2[curr][i] = s_score;
+                    let s_bit = self.traceback.get(i - 1, j).get_s_bits();
+                    self.traceback2.get_mut(i, j).set_i_bits(s_bit);
+                }
+                if s_score > self.S[curr][i] {
+                    self.S[curr][i] = s_score;
+                    self.traceback.get_mut(i, j).set_s_bits(TB_INS2);
+                    if self.S[curr][i] + self.scoring.xclip_suffix > self.S[curr][m] {
+                        self.S[curr][m] = self.S[curr][i] + self.scoring.xclip_suffix;
+                        self.Lx[j] = m - i;
+                        self.traceback.get_mut(m, j).set_s_bits(TB_XCLIP_SUFFIX);
+                    }
+                }
+            }
         }
 
         for j in 1..=n {
-            let d_score = self.scoring.gap_open + self.scoring.gap_extend * (j as i32);
+            let d_score = self.scoring.gap_score(j);
             if d_score > self.scoring.yclip_prefix {
                 self.traceback.get_mut(0, j).set_s_bits(TB_DEL);
             } else {
@@ -737,7 +1338,7 @@ impl<F: MatchFunc> Aligner<F> {
         }
 
         for i in 1..=m {
-            let c_score = self.scoring.gap_open + self.scoring.gap_extend * (i as i32);
+            let c_score = self.scoring.gap_score(i);
             if c_score > self.scoring.xclip_prefix {
                 self.traceback.get_mut(i, 0).set_s_bits(TB_INS);
             } else {
@@ -781,6 +1382,16 @@ impl<F: MatchFunc> Aligner<F> {
                     next_layer = self.traceback.get(i, j).get_d_bits();
                     j -= 1;
                 }
+                TB_INS2 => {
+                    operations.push(AlignmentOperation::Ins);
+                    next_layer = self.traceback2.get(i, j).get_i_bits();
+                    i -= 1;
+                }
+                TB_DEL2 => {
+                    operations.push(AlignmentOperation::Del);
+                    next_layer = self.traceback2.get(i, j).get_d_bits();
+                    j -= 1;
+                }
                 TB_MATCH => {
                     operations.push(AlignmentOperation::Match);
                     next_layer = self.traceback.get(i - 1, j - 1).get_s_bits();
@@ -826,7 +1437,7 @@ impl<F: MatchFunc> Aligner<F> {
         // Handle the case when the traceback ends outside the band other than at (0, 0)
         if i != 0 {
             // Insert all i characters
-            let i_score = self.scoring.gap_open + self.scoring.gap_extend * (i as i32);
+            let i_score = self.scoring.gap_score(i);
             if i_score > self.scoring.xclip_prefix {
                 operations.resize(operations.len() + i, AlignmentOperation::Ins);
                 xstart = 0;
@@ -837,7 +1448,7 @@ impl<F: MatchFunc> Aligner<F> {
         }
         if j != 0 {
             // Delete all j characters
-            let d_score = self.scoring.gap_open + self.scoring.gap_extend * (j as i32);
+            let d_score = self.scoring.gap_score(j);
             if d_score > self.scoring.yclip_prefix {
                 operations.resize(operations.len() + j, AlignmentOperation::Del);
                 ystart = 0;
@@ -848,7 +1459,7 @@ impl<F: MatchFunc> Aligner<F> {
         }
 
         operations.reverse();
-        Alignment {
+        Ok(Alignment {
             score: self.S[n % 2][m],
             ystart,
             xstart,
@@ -858,11 +1469,11 @@ impl<F: MatchFunc> Aligner<F> {
             xlen: m,
             operations,
             mode: AlignmentMode::Custom,
-        }
+        })
     }
 
     /// Calculate global alignment of x against y.
-    pub fn global(&mut self, x: TextSlice<'_>, y: TextSlice<'_>) -> Alignment {
+    pub fn global(&mut self, x: TextSlice<'_>, y: TextSlice<'_>) -> Result<Alignment> {
         // Store the current clip penalties
         let clip_penalties = [
             self.scoring.xclip_prefix,
@@ -878,8 +1489,10 @@ impl<F: MatchFunc> Aligner<F> {
         self.scoring.yclip_suffix = MIN_SCORE;
 
         // Compute the alignment
-        let mut alignment = self.custom(x, y);
-        alignment.mode = AlignmentMode::Global;
+        let alignment = self.custom(x, y).map(|mut alignment| {
+            alignment.mode = AlignmentMode::Global;
+            alignment
+        });
 
         // Set the clip penalties to the original values
         self.scoring.xclip_prefix = clip_penalties[0];
@@ -891,7 +1504,7 @@ impl<F: MatchFunc> Aligner<F> {
     }
 
     /// Calculate semiglobal alignment of x against y (x is global, y is local).
-    pub fn semiglobal(&mut self, x: TextSlice<'_>, y: TextSlice<'_>) -> Alignment {
+    pub fn semiglobal(&mut self, x: TextSlice<'_>, y: TextSlice<'_>) -> Result<Alignment> {
         // Store the current clip penalties
         let clip_penalties = [
             self.scoring.xclip_prefix,
@@ -907,11 +1520,12 @@ impl<F: MatchFunc> Aligner<F> {
         self.scoring.yclip_suffix = 0;
 
         // Compute the alignment
-        let mut alignment = self.custom(x, y);
-        alignment.mode = AlignmentMode::Semiglobal;
-
-        // Filter out Xclip and Yclip from alignment.operations
-        alignment.filter_clip_operations();
+        let alignment = self.custom(x, y).map(|mut alignment| {
+            alignment.mode = AlignmentMode::Semiglobal;
+            // Filter out Xclip and Yclip from alignment.operations
+            alignment.filter_clip_operations();
+            alignment
+        });
 
         // Set the clip penalties to the original values
         self.scoring.xclip_prefix = clip_penalties[0];
@@ -933,7 +1547,7 @@ impl<F: MatchFunc> Aligner<F> {
         x: TextSlice<'_>,
         y: TextSlice<'_>,
         y_kmer_hash: &HashMapFx<&[u8], Vec<u32>>,
-    ) -> Alignment {
+    ) -> Result<Alignment> {
         // Store the current clip penalties
         let clip_penalties = [
             self.scoring.xclip_prefix,
@@ -949,11 +1563,57 @@ impl<F: MatchFunc> Aligner<F> {
         self.scoring.yclip_suffix = 0;
 
         // Compute the alignment
-        let mut alignment = self.custom_with_prehash(x, y, y_kmer_hash);
-        alignment.mode = AlignmentMode::Semiglobal;
+        let alignment = self
+            .custom_with_prehash(x, y, y_kmer_hash)
+            .map(|mut alignment| {
+                alignment.mode = AlignmentMode::Semiglobal;
+                // Filter out Xclip and Yclip from alignment.operations
+                alignment.filter_clip_operations();
+                alignment
+            });
 
-        // Filter out Xclip and Yclip from alignment.operations
-        alignment.filter_clip_operations();
+        // Set the clip penalties to the original values
+        self.scoring.xclip_prefix = clip_penalties[0];
+        self.scoring.xclip_suffix = clip_penalties[1];
+        self.scoring.yclip_prefix = clip_penalties[2];
+        self.scoring.yclip_suffix = clip_penalties[3];
+
+        alignment
+    }
+
+    /// Calculate semiglobal alignment of x against y (x is global, y is local),
+    /// with 'x' being pre-hashed (see sparse::hash_kmers and `custom_with_query_prehash`).
+    /// This is useful for aligning a query against many targets, e.g. candidate windows
+    /// obtained from seeding (also see `semiglobal_many`).
+    pub fn semiglobal_with_query_prehash(
+        &mut self,
+        x: TextSlice<'_>,
+        y: TextSlice<'_>,
+        x_kmer_hash: &HashMapFx<&[u8], Vec<u32>>,
+    ) -> Result<Alignment> {
+        // Store the current clip penalties
+        let clip_penalties = [
+            self.scoring.xclip_prefix,
+            self.scoring.xclip_suffix,
+            self.scoring.yclip_prefix,
+            self.scoring.yclip_suffix,
+        ];
+
+        // Temporarily Over-write the clip penalties
+        self.scoring.xclip_prefix = MIN_SCORE;
+        self.scoring.xclip_suffix = MIN_SCORE;
+        self.scoring.yclip_prefix = 0;
+        self.scoring.yclip_suffix = 0;
+
+        // Compute the alignment
+        let alignment = self
+            .custom_with_query_prehash(x, y, x_kmer_hash)
+            .map(|mut alignment| {
+                alignment.mode = AlignmentMode::Semiglobal;
+                // Filter out Xclip and Yclip from alignment.operations
+                alignment.filter_clip_operations();
+                alignment
+            });
 
         // Set the clip penalties to the original values
         self.scoring.xclip_prefix = clip_penalties[0];
@@ -964,8 +1624,39 @@ impl<F: MatchFunc> Aligner<F> {
         alignment
     }
 
+    /// Calculate semiglobal alignments of x against each of the given targets
+    /// (x is global, the targets are local). The k-mers of x are hashed only once,
+    /// and the DP buffers of the aligner are reused across the targets. An error is
+    /// returned for each target whose band exceeds the maximum number of cells.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use bio::alignment::pairwise::banded::Aligner;
+    ///
+    /// let x = b"ACCGTGGATGGGCGCG";
+    /// let reference = b"TTTACCGTGGATGGGCGCGAAAAAAAAAACCGTTGATGGGCGCGTT";
+    /// let windows = [&reference[..22], &reference[22..]];
+    /// let score = |a: u8, b: u8| if a == b { 1i32 } else { -1i32 };
+    /// let mut aligner = Aligner::new(-5, -1, score, 6, 4);
+    /// let alignments = aligner.semiglobal_many(x, windows.iter().copied());
+    /// assert_eq!(alignments[0].as_ref().unwrap().score, 16);
+    /// assert_eq!(alignments[1].as_ref().unwrap().score, 14);
+    /// ```
+    pub fn semiglobal_many<'b, I>(&mut self, x: TextSlice<'_>, targets: I) -> Vec<Result<Alignment>>
+    where
+        I: IntoIterator<Item = TextSlice<'b>>,
+    {
+        let x_seed = self.seed_alphabet.as_ref().map(|a| a.reduce(x));
+        let x_kmer_hash = sparse::hash_kmers(x_seed.as_deref().unwrap_or(x), self.k);
+        targets
+            .into_iter()
+            .map(|y| self.semiglobal_with_query_prehash(x, y, &x_kmer_hash))
+            .collect()
+    }
+
     /// Calculate local alignment of x against y.
-    pub fn local(&mut self, x: TextSlice<'_>, y: TextSlice<'_>) -> Alignment {
+    pub fn local(&mut self, x: TextSlice<'_>, y: TextSlice<'_>) -> Result<Alignment> {
         // Store the current clip penalties
         let clip_penalties = [
             self.scoring.xclip_prefix,
@@ -981,11 +1672,12 @@ impl<F: MatchFunc> Aligner<F> {
         self.scoring.yclip_suffix = 0;
 
         // Compute the alignment
-        let mut alignment = self.custom(x, y);
-        alignment.mode = AlignmentMode::Local;
-
-        // Filter out Xclip and Yclip from alignment.operations
-        alignment.filter_clip_operations();
+        let alignment = self.custom(x, y).map(|mut alignment| {
+            alignment.mode = AlignmentMode::Local;
+            // Filter out Xclip and Yclip from alignment.operations
+            alignment.filter_clip_operations();
+            alignment
+        });
 
         // Set the clip penalties to the original values
         self.scoring.xclip_prefix = clip_penalties[0];
@@ -996,30 +1688,90 @@ impl<F: MatchFunc> Aligner<F> {
         alignment
     }
 
-    #[allow(dead_code)]
-    pub fn visualize(&self, alignment: &Alignment) {
-        // First populate the band
-        let mut view = vec!['.'; self.band.rows * self.band.cols];
-        let index = |i, j| i * self.band.cols + j;
-        for j in 0..self.band.ranges.len() {
-            let range = &self.band.ranges[j];
-            for i in range.start..range.end {
-                view[index(i, j)] = 'x';
-            }
+    /// The band of the last computed alignment, together with the path of the given
+    /// alignment (which should be the last computed one), e.g. for logging or rendering it.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use bio::alignment::pairwise::banded::Aligner;
+    ///
+    /// let x = b"ACGTACGTTTAGCCATGCA";
+    /// let y = b"ACGTACGTAGCCATGCA";
+    /// let score = |a: u8, b: u8| if a == b { 1i32 } else { -1i32 };
+    /// let mut aligner = Aligner::new(-5, -1, score, 4, 2);
+    /// let alignment = aligner.global(x, y).unwrap();
+    /// let view = aligner.band_view(&alignment);
+    /// assert_eq!((view.rows, view.cols), (x.len() + 1, y.len() + 1));
+    /// assert!(view.num_cells() < view.rows * view.cols);
+    /// assert!(view.path.iter().all(|&(i, j)| view.contains(i, j)));
+    /// let rendered = view.to_string();
+    /// assert_eq!(rendered.lines().count(), view.rows);
+    /// ```
+    pub fn band_view(&self, alignment: &Alignment) -> BandView {
+        BandView {
+            rows: self.band.rows,
+            cols: self.band.cols,
+            ranges: self.band.ranges.clone(),
+            path: alignment
+                .path()
+                .into_iter()
+                .map(|(i, j, _)| (i, j))
+                .collect(),
         }
+    }
 
-        // Populate the path
-        let path = alignment.path();
-        for p in path {
-            view[index(p.0, p.1)] = '\\';
-        }
+    /// Print the band of the last computed alignment and the path of the given alignment
+    /// to stdout (see `band_view`).
+    pub fn visualize(&self, alignment: &Alignment) {
+        print!("{}", self.band_view(alignment));
+    }
+}
+
+/// The cells of the dynamic programming matrix that are computed by the banded aligner
+/// (with the rows corresponding to `x` and the columns to `y`), and an alignment path.
+///
+/// When displayed, each row of the matrix is rendered as one line, with `.` for cells
+/// outside the band, `x` for cells in the band and `\` for cells on the path.
+#[derive(Default, Clone, Eq, PartialEq, Hash, Debug, Serialize, Deserialize)]
+pub struct BandView {
+    /// Number of rows, i.e. the length of `x` plus one.
+    pub rows: usize,
+    /// Number of columns, i.e. the length of `y` plus one.
+    pub cols: usize,
+    /// The rows in the band, for each column.
+    pub ranges: Vec<Range<usize>>,
+    /// The cells visited by the alignment path.
+    pub path: Vec<(usize, usize)>,
+}
+
+impl BandView {
+    /// Whether the given cell is in the band.
+    pub fn contains(&self, i: usize, j: usize) -> bool {
+        self.ranges.get(j).map_or(false, |range| range.contains(&i))
+    }
 
-        for i in 0..self.band.rows {
-            for j in 0..self.band.cols {
-                print!("{}", view[index(i, j)]);
+    /// Number of cells in the band.
+    pub fn num_cells(&self) -> usize {
+        self.ranges.iter().map(|range| range.len()).sum()
+    }
+}
+
+impl fmt::Display for BandView {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut view = vec![b'.'; self.rows * self.cols];
+        for (j, range) in self.ranges.iter().enumerate() {
+            for i in range.clone() {
+                view[i * self.cols + j] = b'x';
             }
-            println!();
         }
+        for &(i, j) in &self.path {
+            view[i * self.cols + j] = b'\\';
+        }
+        for row in view.chunks(self.cols.max(1)) {
+            writeln!(f, "{}", String::from_utf8_lossy(row))?;
+        }
+        Ok(())
     }
 }
 
@@ -1372,25 +2124,6 @@ impl Band {
         banded_cells
     }
 
-    #[allow(dead_code)]
-    fn visualize(&self) {
-        let mut view = vec!['.'; self.rows * self.cols];
-        let index = |i, j| i * self.cols + j;
-        for j in 0..self.ranges.len() {
-            let range = &self.ranges[j];
-            for i in range.start..range.end {
-                view[index(i, j)] = 'x';
-            }
-        }
-
-        for i in 0..self.rows {
-            for j in 0..self.cols {
-                print!("{}", view[index(i, j)]);
-            }
-            println!();
-        }
-    }
-
     #[allow(dead_code)]
     fn stat(&self) {
         let total_cells = self.rows * self.cols;
@@ -1417,7 +2150,7 @@ mod banded {
 
         let mut banded_aligner =
             banded::Aligner::with_capacity(x.len(), y.len(), -5, -1, &score, 10, 10);
-        let banded_alignment = banded_aligner.local(x, y);
+        let banded_alignment = banded_aligner.local(x, y).unwrap();
         // banded_aligner.visualize(&banded_alignment);
 
         let mut full_aligner = pairwise::Aligner::with_capacity(x.len(), y.len(), -5, -1, &score);
@@ -1431,7 +2164,7 @@ mod banded {
 
         let mut banded_aligner =
             banded::Aligner::with_capacity(x.len(), y.len(), -5, -1, &score, 10, 10);
-        let banded_alignment = banded_aligner.global(x, y);
+        let banded_alignment = banded_aligner.global(x, y).unwrap();
         banded_aligner.visualize(&banded_alignment);
 
         let mut full_aligner = pairwise::Aligner::with_capacity(x.len(), y.len(), -5, -1, &score);
@@ -1445,7 +2178,7 @@ mod banded {
 
         let mut banded_aligner =
             banded::Aligner::with_capacity(x.len(), y.len(), -5, -1, &score, 10, 10);
-        let banded_alignment = banded_aligner.semiglobal(x, y);
+        let banded_alignment = banded_aligner.semiglobal(x, y).unwrap();
         banded_aligner.visualize(&banded_alignment);
 
         let mut full_aligner = pairwise::Aligner::with_capacity(x.len(), y.len(), -5, -1, &score);
@@ -1455,7 +2188,7 @@ mod banded {
         assert_eq!(banded_alignment, full_alignment);
 
         let set = hash_kmers(y, 10);
-        let banded_alignment = banded_aligner.semiglobal_with_prehash(x, y, &set);
+        let banded_alignment = banded_aligner.semiglobal_with_prehash(x, y, &set).unwrap();
         assert_eq!(banded_alignment, full_alignment);
     }
 
@@ -1753,6 +2486,7 @@ mod banded {
     // }
 
     use crate::alignment::AlignmentOperation::*;
+    use crate::alphabets::protein::ReducedAlphabet;
     use crate::scores::blosum62;
     use std::iter::repeat;
 
@@ -1762,7 +2496,7 @@ mod banded {
         let y = b"AAAAACCGTTGAT";
         let score = |a: u8, b: u8| if a == b { 1i32 } else { -1i32 };
         let mut aligner = banded::Aligner::with_capacity(x.len(), y.len(), -5, -1, &score, 10, 10);
-        let alignment = aligner.semiglobal(x, y);
+        let alignment = aligner.semiglobal(x, y).unwrap();
         assert_eq!(alignment.ystart, 4);
         assert_eq!(alignment.xstart, 0);
         assert_eq!(
@@ -1778,7 +2512,7 @@ mod banded {
         let y = b"AAAAACCGTTGAT";
         let score = |a: u8, b: u8| if a == b { 1i32 } else { -5i32 };
         let mut aligner = banded::Aligner::with_capacity(x.len(), y.len(), -1, -1, &score, 10, 10);
-        let alignment = aligner.semiglobal(x, y);
+        let alignment = aligner.semiglobal(x, y).unwrap();
         assert_eq!(alignment.ystart, 4);
         assert_eq!(alignment.xstart, 0);
         assert_eq!(
@@ -1793,7 +2527,7 @@ mod banded {
         let y = b"ACGACA";
         let score = |a: u8, b: u8| if a == b { 1i32 } else { -3i32 };
         let mut aligner = banded::Aligner::with_capacity(x.len(), y.len(), -5, -1, &score, 10, 10);
-        let alignment = aligner.global(x, y);
+        let alignment = aligner.global(x, y).unwrap();
 
         println!("aln:\n{}", alignment.pretty(x, y, 80));
         assert_eq!(
@@ -1808,7 +2542,7 @@ mod banded {
         let y = b"AACGTACGATACGTGGGTTGTCACACGTGTCGCGCGGCAACACATCAGACTCTAACAGCATCATCAGCACGTGACA";
         let score = |a: u8, b: u8| if a == b { 1i32 } else { -3i32 };
         let mut aligner = banded::Aligner::with_capacity(x.len(), y.len(), -5, -1, &score, 10, 10);
-        let alignment = aligner.local(x, y);
+        let alignment = aligner.local(x, y).unwrap();
 
         println!("aln:\n{}", alignment.pretty(x, y, 80));
         assert_eq!(alignment.x_aln_len(), 0);
@@ -1821,7 +2555,7 @@ mod banded {
         let y = b"AGATAGATAGATGTAGATGATCCACAGT";
         let score = |a: u8, b: u8| if a == b { 1i32 } else { -1i32 };
         let mut aligner = banded::Aligner::with_capacity(x.len(), y.len(), -5, -1, &score, 10, 10);
-        let alignment = aligner.global(x, y);
+        let alignment = aligner.global(x, y).unwrap();
 
         println!("aln:\n{}", alignment.pretty(x, y, 80));
 
@@ -1839,7 +2573,7 @@ mod banded {
         let y = b"CGTATCATAGATAGATGTAGATGATCCACAGT";
         let score = |a: u8, b: u8| if a == b { 1i32 } else { -1i32 };
         let mut aligner = banded::Aligner::with_capacity(x.len(), y.len(), -5, -1, &score, 10, 10);
-        let alignment = aligner.local(x, y);
+        let alignment = aligner.local(x, y).unwrap();
         assert_eq!(alignment.xstart, 1);
         assert_eq!(alignment.ystart, 0);
     }
@@ -1850,7 +2584,7 @@ mod banded {
         let y = b"AAAAACCGTTGAT";
         let score = |a: u8, b: u8| if a == b { 1i32 } else { -1i32 };
         let mut aligner = banded::Aligner::with_capacity(x.len(), y.len(), -5, -1, &score, 10, 10);
-        let alignment = aligner.local(x, y);
+        let alignment = aligner.local(x, y).unwrap();
         assert_eq!(alignment.ystart, 4);
         assert_eq!(alignment.xstart, 0);
         assert_eq!(
@@ -1865,7 +2599,7 @@ mod banded {
         let y = b"AAAAACCGTTGAT";
         let score = |a: u8, b: u8| if a == b { 1i32 } else { -1i32 };
         let mut aligner = banded::Aligner::with_capacity(x.len(), y.len(), -5, -1, &score, 10, 10);
-        let alignment = aligner.global(x, y);
+        let alignment = aligner.global(x, y).unwrap();
 
         println!("\naln:\n{}", alignment.pretty(x, y, 80));
         assert_eq!(alignment.ystart, 0);
@@ -1876,13 +2610,209 @@ mod banded {
         );
     }
 
+    #[test]
+    fn test_repeat_filter() {
+        let unique = b"TTGACCATGAGCATCGGATCCATGCAAGT";
+        let repeat = b"AGCT".repeat(50);
+        let mut x = unique.to_vec();
+        x.extend_from_slice(&repeat);
+        x.extend_from_slice(b"GGCATTACCAGATCAAGGTC");
+        let mut y = unique.to_vec();
+        y.extend_from_slice(&repeat[..160]);
+        y.extend_from_slice(b"GGCATTACCAGATCAAGGTC");
+
+        let score = |a: u8, b: u8| if a == b { 1i32 } else { -1i32 };
+        let mut full_aligner = pairwise::Aligner::with_capacity(x.len(), y.len(), -5, -1, &score);
+        let full_alignment = full_aligner.global(&x, &y);
+
+        let mut aligner = banded::Aligner::with_capacity(x.len(), y.len(), -5, -1, &score, 8, 50);
+        aligner.set_repeat_filter(Some(banded::RepeatFilter::new(4, 1000)));
+        let alignment = aligner.global(&x, &y).unwrap();
+        assert_eq!(alignment.score, full_alignment.score);
+
+        // thinning out the unique anchors still yields the optimal alignment here
+        aligner.set_repeat_filter(Some(banded::RepeatFilter::new(1, 4)));
+        let alignment = aligner.global(&x, &y).unwrap();
+        assert_eq!(alignment.score, full_alignment.score);
+
+        let y_kmers = hash_kmers(&y, 8);
+        let matches = banded::RepeatFilter::new(4, 1000).find_matches(&x, &y_kmers, 8);
+        assert!(matches
+            .iter()
+            .all(|&(_, j)| y_kmers[&y[j as usize..j as usize + 8]].len() <= 4));
+        let matches = banded::RepeatFilter::new(4, 10).find_matches(&x, &y_kmers, 8);
+        assert!(matches.len() <= 10);
+    }
+
+    #[test]
+    fn test_seed_alphabet() {
+        // y differs from x by conservative substitutions at every other position,
+        // such that there are no exact 4-mer matches
+        let x = b"MTSLETRFTVTIQRDSMMYRSQSRKFQLMFLQSKYIFNVLNTIDNIQQDRTYKYNKKYNT";
+        let y = b"LTTLDTKFSVSINRESLMFRTQTRRFNLLFMQTKFIYNILQTVDQINQERSYRYQKRYQT";
+        let mut full_aligner = pairwise::Aligner::new(-11, -1, blosum62);
+        let full_alignment = full_aligner.global(x, y);
+
+        let mut aligner = banded::Aligner::new(-11, -1, blosum62, 4, 6);
+        let alignment = aligner.global(x, y).unwrap();
+        assert_eq!(aligner.band.num_cells(), (x.len() + 1) * (y.len() + 1));
+        assert_eq!(alignment.score, full_alignment.score);
+
+        aligner.set_seed_alphabet(Some(ReducedAlphabet::murphy10()));
+        let alignment = aligner.global(x, y).unwrap();
+        assert!(aligner.band.num_cells() < x.len() * y.len() / 2);
+        assert_eq!(alignment.score, full_alignment.score);
+
+        let y_seed = ReducedAlphabet::murphy10().reduce(&y[..]);
+        let y_kmers = hash_kmers(&y_seed, 4);
+        let alignment = aligner.custom_with_prehash(x, y, &y_kmers).unwrap();
+        assert_eq!(alignment.score, full_alignment.score);
+    }
+
+    #[test]
+    fn test_semiglobal_many() {
+        let x = b"ACCGTGGATGGGCGCGTAGCTAGCTGGATCG";
+        let mut reference = b"TTGACCATGAGCATCGGATC".to_vec();
+        reference.extend_from_slice(b"ACCGTGGATGGGCGAGTAGCTAGCTGGATCG");
+        reference.extend_from_slice(b"GGCATTACCAGATCAAGGTC");
+        reference.extend_from_slice(b"ACCGTCGATGGGCGCGTAGCTTGCTGGATCGAAT");
+        let windows = [&reference[..60], &reference[50..], &reference[..20]];
+
+        let score = |a: u8, b: u8| if a == b { 1i32 } else { -1i32 };
+        let mut aligner = banded::Aligner::new(-5, -1, score, 6, 6);
+        let alignments = aligner
+            .semiglobal_many(x, windows.iter().copied())
+            .into_iter()
+            .collect::<Result<Vec<_>, _>>()
+            .unwrap();
+        assert_eq!(alignments.len(), 3);
+
+        let mut full_aligner = pairwise::Aligner::new(-5, -1, score);
+        for (alignment, y) in alignments.iter().zip(windows.iter()) {
+            assert_eq!(alignment.score, full_aligner.semiglobal(x, y).score);
+            assert_eq!(alignment.score, aligner.semiglobal(x, y).unwrap().score);
+        }
+    }
+
+    #[test]
+    fn test_band_view() {
+        let view = super::BandView {
+            rows: 3,
+            cols: 4,
+            ranges: vec![0..2, 0..3, 1..3, 2..3],
+            path: vec![(2, 3), (1, 2), (1, 1), (0, 0)],
+        };
+        assert_eq!(view.num_cells(), 8);
+        assert!(view.contains(2, 2) && !view.contains(0, 3) && !view.contains(0, 4));
+        assert_eq!(view.to_string(), "\\x..\nx\\\\.\n.xx\\\n");
+    }
+
+    #[test]
+    fn test_max_cells() {
+        let x = b"ACCGTGGATGGGCGCGTAGCTAGCTGGATCG";
+        let y = b"TTTTTTTTTTACCGTGGATGGGCGAGTAGCTAGCTGGATCGTTTTTTTTTT";
+        let score = |a: u8, b: u8| if a == b { 1i32 } else { -1i32 };
+        let mut aligner = banded::Aligner::new(-5, -1, score, 6, 2);
+        let alignment = aligner.semiglobal(x, y).unwrap();
+        let cells = aligner.band.num_cells();
+        assert!(cells < x.len() * y.len());
+
+        aligner.set_max_cells(Some(cells));
+        assert_eq!(aligner.semiglobal(x, y), Ok(alignment.clone()));
+        aligner.set_max_cells(Some(cells - 1));
+        assert_eq!(
+            aligner.semiglobal(x, y),
+            Err(banded::Error::BandTooLarge {
+                cells,
+                max_cells: cells - 1
+            })
+        );
+        // the clip penalties are restored after a failed alignment
+        assert_eq!(aligner.get_mut_scoring().yclip_prefix, pairwise::MIN_SCORE);
+        aligner.set_max_cells(None);
+        assert_eq!(aligner.semiglobal(x, y), Ok(alignment));
+
+        // the limit also applies to default aligners
+        let aligner = banded::Aligner::<pairwise::MatchParams>::default();
+        assert_eq!(aligner.max_cells, Some(banded::MAX_CELLS));
+    }
+
+    #[test]
+    fn test_aligner_builder() {
+        let builder = banded::AlignerBuilder::new()
+            .scoring(pairwise::AlignerBuilder::new().gap_open(-11))
+            .k(4)
+            .repeat_filter(Some(banded::RepeatFilter::new(2, 100)))
+            .max_cells(None);
+        let aligner = builder.build_with(blosum62).unwrap();
+        assert_eq!((aligner.k, aligner.w), (4, 6));
+        assert_eq!(aligner.scoring.gap_open, -11);
+        assert_eq!(
+            aligner.repeat_filter,
+            Some(banded::RepeatFilter::new(2, 100))
+        );
+        assert_eq!(aligner.max_cells, None);
+
+        let aligner = banded::AlignerBuilder::new().build().unwrap();
+        assert_eq!(aligner.max_cells, Some(banded::MAX_CELLS));
+        assert_eq!(aligner.scoring.match_scores, Some((1, -1)));
+    }
+
+    #[test]
+    fn test_long_gap() {
+        let x = b"ACCGTGGATGGGCGCGTAGCGGCATTACCAGATCAAGG";
+        let y = b"ACCGTGGATGGGCGCGTAGCTTGACCATGAGCATCGGATCCATGCAAGTGGGCATTACCAGATCAAGG";
+        let scoring = Scoring::from_scores(-4, -2, 1, -2).long_gap(-24, -1);
+
+        let mut aligner = banded::Aligner::with_scoring(scoring.clone(), 6, 10);
+        let mut full_aligner = pairwise::Aligner::with_scoring(scoring);
+        for (x, y) in [(&x[..], &y[..]), (&y[..], &x[..])] {
+            let alignment = aligner.global(x, y).unwrap();
+            assert_eq!(alignment, full_aligner.global(x, y));
+            assert_eq!(alignment.score, -16);
+            assert_eq!(
+                aligner.semiglobal(x, y).unwrap().score,
+                full_aligner.semiglobal(x, y).score
+            );
+            assert_eq!(
+                aligner.local(x, y).unwrap().score,
+                full_aligner.local(x, y).score
+            );
+        }
+    }
+
+    #[test]
+    fn test_recommend_kw() {
+        assert_eq!(banded::recommend_kw(100, 100, 0.0), (6, 10));
+        // k is bounded by the sensitivity for divergent sequences
+        let (k, w) = banded::recommend_kw(1_000_000, 1_000_000, 0.2);
+        assert_eq!(k, 10);
+        assert!(w > 10);
+        // w never exceeds the sequence lengths by much
+        let (_, w) = banded::recommend_kw(20, 20, 0.5);
+        assert_eq!(w, 20);
+    }
+
+    #[test]
+    fn test_probe_kw() {
+        let x = b"ACCGTGGATGGGCGCGTAGCTAGCTGGATCGATTGTAGGCAT";
+        let y = b"AAAAACCGTTGATGGGCGCGTAGCTAGCTGGATCGATTGTAGGCATAAA";
+        let scoring = Scoring::from_scores(-5, -1, 1, -1).yclip(0);
+        assert!(banded::probe_kw(x, y, &scoring, &[]).is_none());
+        let (kw, alignment) = banded::probe_kw(x, y, &scoring, &[(6, 10), (8, 2)]).unwrap();
+
+        let mut full_aligner = pairwise::Aligner::with_scoring(scoring);
+        assert_eq!(alignment.score, full_aligner.custom(x, y).score);
+        assert_eq!(kw, (8, 2));
+    }
+
     #[test]
     fn test_blosum62() {
         let x = b"AAAA";
         let y = b"AAAA";
         let score = &blosum62;
         let mut aligner = banded::Aligner::with_capacity(x.len(), y.len(), -5, -1, score, 10, 10);
-        let alignment = aligner.global(x, y);
+        let alignment = aligner.global(x, y).unwrap();
         assert_eq!(alignment.ystart, 0);
         assert_eq!(alignment.xstart, 0);
         assert_eq!(alignment.score, 16);
@@ -1895,7 +2825,7 @@ mod banded {
         let x = b"AAAAACC"; //GTTGACGCAA";
         let score = |a: u8, b: u8| if a == b { 1i32 } else { -1i32 };
         let mut aligner = banded::Aligner::with_capacity(x.len(), y.len(), -5, -1, &score, 10, 10);
-        let alignment = aligner.global(x, y);
+        let alignment = aligner.global(x, y).unwrap();
         assert_eq!(alignment.ystart, 0);
         assert_eq!(alignment.xstart, 0);
         assert_eq!(
@@ -1910,7 +2840,7 @@ mod banded {
         let y = b"ACCGTTGACGC";
         let score = |a: u8, b: u8| if a == b { 1i32 } else { -1i32 };
         let mut aligner = banded::Aligner::with_capacity(x.len(), y.len(), -5, -1, &score, 10, 10);
-        let alignment = aligner.semiglobal(x, y);
+        let alignment = aligner.semiglobal(x, y).unwrap();
         assert_eq!(alignment.xstart, 0);
         assert_eq!(alignment.ystart, 1);
         assert_eq!(
@@ -1925,7 +2855,7 @@ mod banded {
         let x = b"ACCGTTGACGC";
         let score = |a: u8, b: u8| if a == b { 1i32 } else { -1i32 };
         let mut aligner = banded::Aligner::with_capacity(x.len(), y.len(), -5, -1, &score, 10, 10);
-        let alignment = aligner.semiglobal(x, y);
+        let alignment = aligner.semiglobal(x, y).unwrap();
         assert_eq!(alignment.xstart, 0);
         assert_eq!(alignment.ystart, 0);
 
@@ -1941,7 +2871,7 @@ mod banded {
         let x = b"AAAAACCGTTGACGCAA";
         let score = |a: u8, b: u8| if a == b { 1i32 } else { -1i32 };
         let mut aligner = banded::Aligner::with_capacity(x.len(), y.len(), -5, -1, &score, 10, 10);
-        let alignment = aligner.semiglobal(x, y);
+        let alignment = aligner.semiglobal(x, y).unwrap();
 
         assert_eq!(alignment.xstart, 0);
         assert_eq!(
@@ -1953,7 +2883,7 @@ mod banded {
         );
 
         let mut aligner = banded::Aligner::with_capacity(y.len(), x.len(), -5, -1, &score, 10, 10);
-        let alignment = aligner.semiglobal(y, x);
+        let alignment = aligner.semiglobal(y, x).unwrap();
 
         assert_eq!(alignment.xstart, 0);
         assert_eq!(
@@ -1968,7 +2898,7 @@ mod banded {
         let y = b"GTGCATCATCATGTG";
         let score = |a: u8, b: u8| if a == b { 1i32 } else { -1i32 };
         let mut aligner = banded::Aligner::with_capacity(x.len(), y.len(), -5, -1, &score, 10, 10);
-        let alignment = aligner.global(x, y);
+        let alignment = aligner.global(x, y).unwrap();
         println!("\naln:\n{}", alignment.pretty(x, y, 80));
 
         assert_eq!(alignment.ystart, 0);
@@ -1991,7 +2921,7 @@ mod banded {
 
         let score = |a: u8, b: u8| if a == b { 1i32 } else { -1i32 };
         let mut aligner = banded::Aligner::with_capacity(x.len(), y.len(), -5, -1, &score, 10, 10);
-        let alignment = aligner.global(x, y);
+        let alignment = aligner.global(x, y).unwrap();
 
         println!("\naln:\n{}", alignment.pretty(x, y, 80));
 
@@ -2014,7 +2944,7 @@ mod banded {
         let y = b"GTGCATCATGTG";
         let score = |a: u8, b: u8| if a == b { 1i32 } else { -1i32 };
         let mut aligner = banded::Aligner::with_capacity(x.len(), y.len(), -5, -1, &score, 10, 10);
-        let alignment = aligner.global(x, y);
+        let alignment = aligner.global(x, y).unwrap();
         println!("\naln:\n{}", alignment.pretty(x, y, 80));
 
         assert_eq!(alignment.ystart, 0);
@@ -2035,7 +2965,7 @@ mod banded {
         let score = |a: u8, b: u8| if a == b { 1i32 } else { -1i32 };
         let mut aligner = banded::Aligner::new(-5, -1, &score, 10, 10);
 
-        let alignment = aligner.semiglobal(x, y);
+        let alignment = aligner.semiglobal(x, y).unwrap();
         assert_eq!(alignment.ystart, 4);
         assert_eq!(alignment.xstart, 0);
         assert_eq!(
@@ -2043,7 +2973,7 @@ mod banded {
             [Match, Match, Match, Match, Match, Subst, Match, Match, Match,]
         );
 
-        let alignment = aligner.local(x, y);
+        let alignment = aligner.local(x, y).unwrap();
         assert_eq!(alignment.ystart, 4);
         assert_eq!(alignment.xstart, 0);
         assert_eq!(
@@ -2051,7 +2981,7 @@ mod banded {
             [Match, Match, Match, Match, Match, Subst, Match, Match, Match,]
         );
 
-        let alignment = aligner.global(x, y);
+        let alignment = aligner.global(x, y).unwrap();
         assert_eq!(alignment.ystart, 0);
         assert_eq!(alignment.xstart, 0);
         assert_eq!(
@@ -2067,7 +2997,7 @@ mod banded {
 
         let score = |a: u8, b: u8| if a == b { 1i32 } else { -1i32 };
         let mut aligner = banded::Aligner::new(-5, -1, &score, 10, 10);
-        let alignment = aligner.semiglobal(x, y);
+        let alignment = aligner.semiglobal(x, y).unwrap();
 
         assert_eq!(
             alignment.operations,
@@ -2082,7 +3012,7 @@ mod banded {
 
         let score = |a: u8, b: u8| if a == b { 1i32 } else { -3i32 };
         let mut aligner = banded::Aligner::new(-5, -1, &score, 10, 10);
-        let alignment = aligner.semiglobal(x, y);
+        let alignment = aligner.semiglobal(x, y).unwrap();
 
         assert_eq!(alignment.operations, [Ins, Ins, Ins, Ins]);
     }
@@ -2094,7 +3024,7 @@ mod banded {
 
         let score = |a: u8, b: u8| if a == b { 1i32 } else { -3i32 };
         let mut aligner = banded::Aligner::new(-5, -1, &score, 10, 10);
-        let alignment = aligner.semiglobal(x, y);
+        let alignment = aligner.semiglobal(x, y).unwrap();
 
         assert_eq!(
             alignment.operations,
@@ -2111,7 +3041,7 @@ mod banded {
         let scoring = Scoring::new(-5, -1, &score).xclip(-5);
 
         let mut aligner = banded::Aligner::with_scoring(scoring, 10, 10);
-        let alignment = aligner.custom(x, y);
+        let alignment = aligner.custom(x, y).unwrap();
 
         assert_eq!(alignment.operations, [Xclip(6), Match, Match, Match]);
     }
@@ -2125,7 +3055,7 @@ mod banded {
         let scoring = Scoring::new(-5, -1, &score).yclip(-5);
 
         let mut aligner = banded::Aligner::with_scoring(scoring, 10, 10);
-        let alignment = aligner.custom(x, y);
+        let alignment = aligner.custom(x, y).unwrap();
 
         assert_eq!(alignment.operations, [Yclip(6), Match, Match, Match]);
     }
@@ -2139,7 +3069,7 @@ mod banded {
         let scoring = Scoring::new(-5, -1, &score).xclip(-5).yclip(0);
 
         let mut aligner = banded::Aligner::with_scoring(scoring, 10, 10);
-        let alignment = aligner.custom(x, y);
+        let alignment = aligner.custom(x, y).unwrap();
 
         assert_eq!(alignment.operations, [Yclip(1), Match, Xclip(4)]);
     }
@@ -2153,7 +3083,7 @@ mod banded {
         let scoring = Scoring::new(-5, -1, &score).yclip(-5).xclip(0);
 
         let mut aligner = banded::Aligner::with_scoring(scoring, 10, 10);
-        let alignment = aligner.custom(x, y);
+        let alignment = aligner.custom(x, y).unwrap();
 
         assert_eq!(alignment.operations, [Xclip(1), Match, Yclip(4)]);
     }
@@ -2167,7 +3097,7 @@ mod banded {
         let scoring = Scoring::new(-5, -1, &score).xclip(-5).yclip(0);
 
         let mut aligner = banded::Aligner::with_scoring(scoring, 10, 10);
-        let alignment = aligner.custom(x, y);
+        let alignment = aligner.custom(x, y).unwrap();
 
         println!("{}", alignment.pretty(x, y, 80));
         assert_eq!(alignment.score, 7);
@@ -2185,7 +3115,7 @@ mod banded {
             ..base_score
         };
         let mut aligner = banded::Aligner::with_scoring(scoring, 6, 5);
-        let alignment = aligner.custom(x, y);
+        let alignment = aligner.custom(x, y).unwrap();
         assert_eq!(alignment.ystart, 0);
     }
 
@@ -2201,7 +3131,7 @@ mod banded {
             ..base_score
         };
         let mut aligner = banded::Aligner::with_scoring(scoring, 6, 5);
-        let alignment = aligner.custom(x, y);
+        let alignment = aligner.custom(x, y).unwrap();
         assert_eq!(alignment.yend, alignment.ylen);
     }
 
@@ -2219,7 +3149,7 @@ mod banded {
         let kmer_len = 5;
         let window_len = 5;
         let mut al = pairwise::banded::Aligner::with_scoring(scoring, kmer_len, window_len);
-        let alignment = al.custom(x, y);
+        let alignment = al.custom(x, y).unwrap();
         assert_eq!(alignment.ystart, 0);
     }
 
@@ -2238,7 +3168,7 @@ mod banded {
         let kmer_len = 5;
         let window_len = 8;
         let mut al = pairwise::banded::Aligner::with_scoring(scoring, kmer_len, window_len);
-        let alignment = al.custom(x, y);
+        let alignment = al.custom(x, y).unwrap();
         assert_eq!(alignment.score, -13);
         assert_eq!(
             alignment.operations,
@@ -2283,7 +3213,7 @@ mod banded {
         let kmer_len = 5;
         let window_len = 7;
         let mut al = pairwise::banded::Aligner::with_scoring(scoring, kmer_len, window_len);
-        let alignment = al.custom(x, y);
+        let alignment = al.custom(x, y).unwrap();
         assert_eq!(alignment.score, -15);
         assert_eq!(
             alignment.operations,
@@ -2331,7 +3261,7 @@ mod banded {
         let kmer_len = 5;
         let window_len = 7;
         let mut al = pairwise::banded::Aligner::with_scoring(scoring, kmer_len, window_len);
-        let alignment = al.custom(x, y);
+        let alignment = al.custom(x, y).unwrap();
         assert_eq!(alignment.score, 24);
     }
 
@@ -2350,7 +3280,7 @@ mod banded {
         let kmer_len = 10;
         let window_len = 10;
         let mut al = pairwise::banded::Aligner::with_scoring(scoring, kmer_len, window_len);
-        let alignment = al.custom(x, y);
+        let alignment = al.custom(x, y).unwrap();
         assert_eq!(alignment.score, -72);
     }
 
@@ -2369,7 +3299,7 @@ mod banded {
                 ..base_score.clone()
             };
             let mut al = pairwise::banded::Aligner::with_scoring(scoring, kmer_len, window_len);
-            let alignment = al.custom(x, y);
+            let alignment = al.custom(x, y).unwrap();
             assert_eq!(alignment.score, 0);
         }
 
@@ -2380,7 +3310,7 @@ mod banded {
                 ..base_score.clone()
             };
             let mut al = pairwise::banded::Aligner::with_scoring(scoring, kmer_len, window_len);
-            let alignment = al.custom(x, y);
+            let alignment = al.custom(x, y).unwrap();
             assert_eq!(alignment.score, 0);
         }
 
@@ -2391,7 +3321,7 @@ mod banded {
                 ..base_score.clone()
             };
             let mut al = pairwise::banded::Aligner::with_scoring(scoring, kmer_len, window_len);
-            let alignment = al.custom(x, y);
+            let alignment = al.custom(x, y).unwrap();
             assert_eq!(alignment.score, 0);
         }
 
@@ -2402,7 +3332,7 @@ mod banded {
                 ..base_score
             };
             let mut al = pairwise::banded::Aligner::with_scoring(scoring, kmer_len, window_len);
-            let alignment = al.custom(x, y);
+            let alignment = al.custom(x, y).unwrap();
             assert_eq!(alignment.score, 0);
         }
     }
@@ -0,0 +1,293 @@
+//! Global alignment in linear space by divide and conquer (Hirschberg, 1975), with affine gap
+//! scores as described by Myers and Miller (1988), generalized to the two-piece affine gap
+//! model of `Scoring::long_gap`.
+//!
+//! The rows of `x` are split in the middle, and the scores of aligning the upper half with all
+//! prefixes of `y` and the lower half with all suffixes of `y` are computed in linear space.
+//! The best combination gives a column through which an optimal alignment passes, either in a
+//! match state or within an insertion spanning both halves. Both halves are then aligned
+//! recursively, with the constraint of starting or ending with that insertion. This doubles
+//! the running time compared to the full dynamic programming matrix.
+
+use std::cmp::max;
+
+use crate::alignment::AlignmentOperation;
+use crate::utils::TextSlice;
+
+use super::{MatchFunc, MIN_SCORE};
+
+/// Subproblems with at most this many rows are aligned with the full matrix.
+const BASE_ROWS: usize = 1;
+
+/// Scoring parameters, with the gap models given as pairs of open and extend scores.
+pub(super) struct Params<S: MatchFunc> {
+    pub(super) match_fn: S,
+    pub(super) gaps: Vec<(i32, i32)>,
+}
+
+/// The state of an alignment cell.
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+enum State {
+    Match,
+    Ins(usize),
+    Del(usize),
+}
+
+/// The last row of the dynamic programming matrix: the best scores ending in any state, and
+/// ending with an insertion in each gap model.
+struct Row {
+    h: Vec<i32>,
+    ins: Vec<Vec<i32>>,
+}
+
+impl<S: MatchFunc> Params<S> {
+    /// Compute the scores of the full matrix, calling `visit` with each row. If `start` is
+    /// given, the alignment has to start with an insertion in that gap model, which
+    /// continues a gap that has been opened before (i.e., the gap open score is not counted).
+    fn rows<F: FnMut(&Row, &[Vec<i32>])>(
+        &self,
+        x: TextSlice<'_>,
+        y: TextSlice<'_>,
+        start: Option<usize>,
+        mut visit: F,
+    ) -> Row {
+        let n = y.len();
+        let mut row = Row {
+            h: vec![MIN_SCORE; n + 1],
+            ins: vec![vec![MIN_SCORE; n + 1]; self.gaps.len()],
+        };
+        let mut del = vec![vec![MIN_SCORE; n + 1]; self.gaps.len()];
+        match start {
+            Some(k) => row.ins[k][0] = 0,
+            None => row.h[0] = 0,
+        }
+        for j in 1..=n {
+            for (k, &(open, extend)) in self.gaps.iter().enumerate() {
+                del[k][j] = (max(row.h[j - 1] + open, del[k][j - 1]) + extend).max(MIN_SCORE);
+                row.h[j] = max(row.h[j], del[k][j]);
+            }
+        }
+        visit(&row, &del);
+
+        for &a in x {
+            let mut diag = row.h[0];
+            for (k, &(open, extend)) in self.gaps.iter().enumerate() {
+                row.ins[k][0] = (max(row.h[0] + open, row.ins[k][0]) + extend).max(MIN_SCORE);
+            }
+            row.h[0] = row.ins.iter().map(|ins| ins[0]).max().unwrap_or(MIN_SCORE);
+            for j in 1..=n {
+                let mut h = diag + self.match_fn.score(a, y[j - 1]);
+                for (k, &(open, extend)) in self.gaps.iter().enumerate() {
+                    row.ins[k][j] = (max(row.h[j] + open, row.ins[k][j]) + extend).max(MIN_SCORE);
+                    del[k][j] = (max(row.h[j - 1] + open, del[k][j - 1]) + extend).max(MIN_SCORE);
+                    h = max(h, max(row.ins[k][j], del[k][j]));
+                }
+                diag = row.h[j];
+                row.h[j] = max(h, MIN_SCORE);
+            }
+            visit(&row, &del);
+        }
+        row
+    }
+
+    /// Align `x` and `y`, appending the operations. If `end` is given, the alignment has to end
+    /// with an insertion in that gap model. Returns the score of the alignment.
+    pub(super) fn align(
+        &self,
+        x: TextSlice<'_>,
+        y: TextSlice<'_>,
+        start: Option<usize>,
+        end: Option<usize>,
+        operations: &mut Vec<AlignmentOperation>,
+    ) -> i32 {
+        if x.len() <= BASE_ROWS {
+            return self.align_full(x, y, start, end, operations);
+        }
+        let (m, n) = (x.len(), y.len());
+        let mid = m / 2;
+        let upper = self.rows(&x[..mid], y, start, |_, _| ());
+        let (x_rev, y_rev) = (
+            x[mid..].iter().rev().cloned().collect::<Vec<_>>(),
+            y.iter().rev().cloned().collect::<Vec<_>>(),
+        );
+        let lower = self.rows(&x_rev, &y_rev, end, |_, _| ());
+
+        // best column and state in which to cross the middle
+        let mut best = (MIN_SCORE, 0, None);
+        for j in 0..=n {
+            let score = upper.h[j] + lower.h[n - j];
+            if score > best.0 {
+                best = (score, j, None);
+            }
+            for (k, &(open, _)) in self.gaps.iter().enumerate() {
+                // the gap spanning both halves is only opened once
+                let score = upper.ins[k][j] + lower.ins[k][n - j] - open;
+                if score > best.0 {
+                    best = (score, j, Some(k));
+                }
+            }
+        }
+        let (score, j, gap) = best;
+        self.align(&x[..mid], &y[..j], start, gap, operations);
+        self.align(&x[mid..], &y[j..], gap, end, operations);
+        score
+    }
+
+    /// Align `x` and `y` with the full dynamic programming matrix.
+    fn align_full(
+        &self,
+        x: TextSlice<'_>,
+        y: TextSlice<'_>,
+        start: Option<usize>,
+        end: Option<usize>,
+        operations: &mut Vec<AlignmentOperation>,
+    ) -> i32 {
+        let (mut h, mut ins, mut del) = (Vec::new(), Vec::new(), Vec::new());
+        self.rows(x, y, start, |row, row_del| {
+            h.push(row.h.clone());
+            ins.push(row.ins.clone());
+            del.push(row_del.to_vec());
+        });
+
+        let (mut i, mut j) = (x.len(), y.len());
+        let mut state = end.map_or(State::Match, State::Ins);
+        let score = match state {
+            State::Ins(k) => ins[i][k][j],
+            _ => h[i][j],
+        };
+        let first = start.map_or(State::Match, State::Ins);
+        let tail = operations.len();
+        while i > 0 || j > 0 || state != first {
+            state = match state {
+                State::Match => {
+                    if i > 0
+                        && j > 0
+                        && h[i][j] == h[i - 1][j - 1] + self.match_fn.score(x[i - 1], y[j - 1])
+                    {
+                        operations.push(if x[i - 1] == y[j - 1] {
+                            AlignmentOperation::Match
+                        } else {
+                            AlignmentOperation::Subst
+                        });
+                        i -= 1;
+                        j -= 1;
+                        State::Match
+                    } else if let Some(k) = (0..self.gaps.len()).find(|&k| ins[i][k][j] == h[i][j])
+                    {
+                        State::Ins(k)
+                    } else {
+                        State::Del(
+                            (0..self.gaps.len())
+                                .find(|&k| del[i][k][j] == h[i][j])
+                                .unwrap(),
+                        )
+                    }
+                }
+                State::Ins(k) => {
+                    let (open, extend) = self.gaps[k];
+                    operations.push(AlignmentOperation::Ins);
+                    i -= 1;
+                    if ins[i + 1][k][j] == h[i][j] + open + extend {
+                        State::Match
+                    } else {
+                        State::Ins(k)
+                    }
+                }
+                State::Del(k) => {
+                    let (open, extend) = self.gaps[k];
+                    operations.push(AlignmentOperation::Del);
+                    j -= 1;
+                    if del[i][k][j + 1] == h[i][j] + open + extend {
+                        State::Match
+                    } else {
+                        State::Del(k)
+                    }
+                }
+            };
+        }
+        operations[tail..].reverse();
+        score
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::alignment::pairwise::{Aligner, MatchFunc, Scoring};
+    use crate::alignment::{Alignment, AlignmentOperation};
+    use rand::prelude::*;
+    use rand::rngs::StdRng;
+
+    /// Recompute the score of a global alignment from its operations.
+    fn rescore<F: MatchFunc>(scoring: &Scoring<F>, x: &[u8], y: &[u8], aln: &Alignment) -> i32 {
+        let (mut i, mut j, mut score) = (0, 0, 0);
+        let mut gap = None;
+        for &op in aln.operations.iter().chain(&[AlignmentOperation::Match]) {
+            match (gap, op) {
+                (Some((gap_op, len)), _) if gap_op == op => gap = Some((gap_op, len + 1)),
+                _ => {
+                    if let Some((_, len)) = gap.take() {
+                        score += scoring.gap_score(len);
+                    }
+                    match op {
+                        AlignmentOperation::Ins | AlignmentOperation::Del => gap = Some((op, 1)),
+                        _ if i < x.len() => score += scoring.match_fn.score(x[i], y[j]),
+                        _ => (),
+                    }
+                }
+            }
+            match op {
+                AlignmentOperation::Match | AlignmentOperation::Subst => {
+                    i += 1;
+                    j += 1;
+                }
+                AlignmentOperation::Ins => i += 1,
+                AlignmentOperation::Del => j += 1,
+                _ => panic!("unexpected operation"),
+            }
+        }
+        assert_eq!((i - 1, j - 1), (x.len(), y.len()));
+        score
+    }
+
+    #[test]
+    fn test_global_hirschberg() {
+        let mut rng = StdRng::seed_from_u64(42);
+        let scorings = [
+            Scoring::from_scores(-5, -1, 1, -1),
+            Scoring::from_scores(-3, -2, 2, -3),
+            Scoring::from_scores(0, -1, 1, -1),
+            Scoring::from_scores(-4, -2, 2, -4).long_gap(-24, -1),
+        ];
+        for scoring in scorings.iter() {
+            let mut aligner = Aligner::with_scoring(scoring.clone());
+            for &(m, n) in &[
+                (0, 0),
+                (0, 5),
+                (5, 0),
+                (1, 1),
+                (1, 7),
+                (7, 1),
+                (30, 45),
+                (100, 60),
+            ] {
+                let mut random = |len| {
+                    (0..len)
+                        .map(|_| *b"ACGT".choose(&mut rng).unwrap())
+                        .collect::<Vec<_>>()
+                };
+                let x = random(m);
+                let mut y = random(n / 2);
+                // make the sequences similar, with a long insertion
+                y.extend_from_slice(&x[m / 3..]);
+                let expected = aligner.global(&x, &y);
+                let alignment = aligner.global_hirschberg(&x, &y);
+                assert_eq!(alignment.score, expected.score);
+                assert_eq!(rescore(scoring, &x, &y, &alignment), alignment.score);
+                assert_eq!(
+                    (alignment.xend, alignment.yend, alignment.mode),
+                    (expected.xend, expected.yend, expected.mode)
+                );
+            }
+        }
+    }
+}
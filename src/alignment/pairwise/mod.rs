@@ -121,6 +121,7 @@
 //!     xclip_suffix: MIN_SCORE,
 //!     yclip_prefix: 0,
 //!     yclip_suffix: 0,
+//!     long_gap: None,
 //! };
 //! let x = b"GGGGGGACGTACGTACGT";
 //! let y = b"AAAAACGTACGTACGTAAAA";
@@ -149,15 +150,41 @@
 //!     ]
 //! );
 //! ```
+//!
+//! Aligners can also be configured with an `AlignerBuilder`, which validates the scores and can
+//! be (de)serialized, e.g. for loading the parameters of a tool from a configuration file.
 
 use std::cmp::max;
 use std::i32;
 use std::iter::repeat;
 
+use thiserror::Error;
+
 use crate::alignment::{Alignment, AlignmentMode, AlignmentOperation};
 use crate::utils::TextSlice;
 
 pub mod banded;
+mod hirschberg;
+
+/// Errors of invalid aligner configurations (see `AlignerBuilder` and
+/// `banded::AlignerBuilder`).
+#[derive(
+    Error, Copy, Clone, Eq, PartialEq, Ord, PartialOrd, Hash, Debug, Serialize, Deserialize,
+)]
+pub enum Error {
+    #[error("match score {} can't be negative", score)]
+    NegativeMatchScore { score: i32 },
+    #[error("mismatch score {} can't be positive", score)]
+    PositiveMismatchScore { score: i32 },
+    #[error("gap score {} can't be positive", score)]
+    PositiveGapScore { score: i32 },
+    #[error("clipping penalty {} can't be positive", score)]
+    PositiveClipPenalty { score: i32 },
+    #[error("k-mer length has to be positive")]
+    ZeroKmerLength,
+}
+
+pub type Result<T, E = Error> = std::result::Result<T, E>;
 
 /// Value to use as a 'negative infinity' score. Should be close to `i32::MIN`,
 /// but avoid underflow when used with reasonable scoring parameters or even
@@ -223,6 +250,15 @@ where
 /// An [affine gap score model](https://en.wikipedia.org/wiki/Gap_penalty#Affine)
 /// is used so that the gap score for a length `k` is:
 /// `GapScore(k) = gap_open + gap_extend * k`
+///
+/// Optionally, a second affine gap model `long_gap = Some((gap_open2, gap_extend2))` can be
+/// given (two-piece or dual affine gap model, as in minimap2), such that
+/// `GapScore(k) = max(gap_open + gap_extend * k, gap_open2 + gap_extend2 * k)`.
+/// With a higher opening and lower extension penalty for the second model,
+/// long gaps are penalized less steeply (see `Scoring::with_long_gap`).
+///
+/// Note that struct literals of `Scoring` have to set `long_gap` (usually to `None`) since
+/// it was added. Serialized instances without the field deserialize to `None`.
 #[derive(
     Default, Copy, Clone, Eq, PartialEq, Ord, PartialOrd, Hash, Debug, Serialize, Deserialize,
 )]
@@ -235,6 +271,8 @@ pub struct Scoring<F: MatchFunc> {
     pub xclip_suffix: i32,
     pub yclip_prefix: i32,
     pub yclip_suffix: i32,
+    #[serde(default)]
+    pub long_gap: Option<(i32, i32)>,
 }
 
 impl Scoring<MatchParams> {
@@ -265,6 +303,7 @@ impl Scoring<MatchParams> {
             xclip_suffix: MIN_SCORE,
             yclip_prefix: MIN_SCORE,
             yclip_suffix: MIN_SCORE,
+            long_gap: None,
         }
     }
 }
@@ -292,6 +331,7 @@ impl<F: MatchFunc> Scoring<F> {
             xclip_suffix: MIN_SCORE,
             yclip_prefix: MIN_SCORE,
             yclip_suffix: MIN_SCORE,
+            long_gap: None,
         }
     }
 
@@ -417,6 +457,269 @@ impl<F: MatchFunc> Scoring<F> {
         self.yclip_suffix = penalty;
         self
     }
+
+    /// Sets a second affine gap model, turning the gap score into a two-piece (dual)
+    /// affine one: `GapScore(k) = max(gap_open + gap_extend * k, gap_open2 + gap_extend2 * k)`.
+    ///
+    /// # Arguments
+    ///
+    /// * `gap_open2` - the score for opening a long gap (should not be positive)
+    /// * `gap_extend2` - the score for extending a long gap (should not be positive)
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use bio::alignment::pairwise::Scoring;
+    /// let scoring = Scoring::from_scores(-4, -2, 1, -2).long_gap(-24, -1);
+    /// assert_eq!(scoring.gap_score(5), -14);
+    /// assert_eq!(scoring.gap_score(30), -54);
+    /// ```
+    pub fn long_gap(mut self, gap_open2: i32, gap_extend2: i32) -> Self {
+        assert!(gap_open2 <= 0, "gap_open2 can't be positive");
+        assert!(gap_extend2 <= 0, "gap_extend2 can't be positive");
+        self.long_gap = Some((gap_open2, gap_extend2));
+        self
+    }
+
+    /// Create new Scoring instance with a two-piece (dual) affine gap model (see
+    /// `Scoring::long_gap`) and the score function. The clip penalties are set to
+    /// [`MIN_SCORE`](constant.MIN_SCORE.html) by default.
+    ///
+    /// # Arguments
+    ///
+    /// * `gap_open` - the score for opening a short gap (should not be positive)
+    /// * `gap_extend` - the score for extending a short gap (should not be positive)
+    /// * `gap_open2` - the score for opening a long gap (should not be positive)
+    /// * `gap_extend2` - the score for extending a long gap (should not be positive)
+    /// * `match_fn` - function that returns the score for substitutions
+    pub fn with_long_gap(
+        gap_open: i32,
+        gap_extend: i32,
+        gap_open2: i32,
+        gap_extend2: i32,
+        match_fn: F,
+    ) -> Self {
+        Scoring::new(gap_open, gap_extend, match_fn).long_gap(gap_open2, gap_extend2)
+    }
+
+    /// Return the score of a gap of the given length (> 0).
+    pub fn gap_score(&self, len: usize) -> i32 {
+        let score = self.gap_open + self.gap_extend * len as i32;
+        match self.long_gap {
+            Some((gap_open2, gap_extend2)) => max(score, gap_open2 + gap_extend2 * len as i32),
+            None => score,
+        }
+    }
+}
+
+/// A builder for aligners with constant match and mismatch scores, validating the scores.
+/// Without further configuration, it uses a match score of 1, a mismatch score of -1, a gap
+/// open score of -5 and a gap extend score of -1, no clipping and a capacity for sequences of
+/// length 200. Missing fields are set to these defaults when deserializing.
+///
+/// # Example
+///
+/// ```
+/// use bio::alignment::pairwise::{AlignerBuilder, Error};
+///
+/// let mut aligner = AlignerBuilder::new()
+///     .mismatch_score(-3)
+///     .gap_open(-6)
+///     .capacity(9, 13)
+///     .build()
+///     .unwrap();
+/// let alignment = aligner.semiglobal(b"ACCGTGGAT", b"AAAAACCGTTGAT");
+/// assert_eq!(alignment.score, 5);
+///
+/// let builder = AlignerBuilder::new().gap_extend(1);
+/// assert_eq!(builder.build().unwrap_err(), Error::PositiveGapScore { score: 1 });
+/// ```
+#[derive(Copy, Clone, Eq, PartialEq, Ord, PartialOrd, Hash, Debug, Serialize, Deserialize)]
+#[serde(default)]
+pub struct AlignerBuilder {
+    match_score: i32,
+    mismatch_score: i32,
+    gap_open: i32,
+    gap_extend: i32,
+    long_gap: Option<(i32, i32)>,
+    xclip_prefix: i32,
+    xclip_suffix: i32,
+    yclip_prefix: i32,
+    yclip_suffix: i32,
+    capacity: (usize, usize),
+}
+
+impl Default for AlignerBuilder {
+    fn default() -> Self {
+        AlignerBuilder {
+            match_score: 1,
+            mismatch_score: -1,
+            gap_open: -5,
+            gap_extend: -1,
+            long_gap: None,
+            xclip_prefix: MIN_SCORE,
+            xclip_suffix: MIN_SCORE,
+            yclip_prefix: MIN_SCORE,
+            yclip_suffix: MIN_SCORE,
+            capacity: (DEFAULT_ALIGNER_CAPACITY, DEFAULT_ALIGNER_CAPACITY),
+        }
+    }
+}
+
+impl AlignerBuilder {
+    /// Create a new builder with the default configuration.
+    pub fn new() -> Self {
+        AlignerBuilder::default()
+    }
+
+    /// Use the given score for matches (default: 1, should not be negative).
+    pub fn match_score(mut self, score: i32) -> Self {
+        self.match_score = score;
+        self
+    }
+
+    /// Use the given score for mismatches (default: -1, should not be positive).
+    pub fn mismatch_score(mut self, score: i32) -> Self {
+        self.mismatch_score = score;
+        self
+    }
+
+    /// Use the given score for opening a gap (default: -5, should not be positive).
+    pub fn gap_open(mut self, score: i32) -> Self {
+        self.gap_open = score;
+        self
+    }
+
+    /// Use the given score for extending a gap (default: -1, should not be positive).
+    pub fn gap_extend(mut self, score: i32) -> Self {
+        self.gap_extend = score;
+        self
+    }
+
+    /// Use a two-piece affine gap model with the given scores for opening and extending long
+    /// gaps (see `Scoring::long_gap`, default: none).
+    pub fn long_gap(mut self, gap_open2: i32, gap_extend2: i32) -> Self {
+        self.long_gap = Some((gap_open2, gap_extend2));
+        self
+    }
+
+    /// Set the prefix and suffix clipping penalties for x (see `Scoring::xclip`,
+    /// default: `MIN_SCORE`, i.e. no clipping).
+    pub fn xclip(self, penalty: i32) -> Self {
+        self.xclip_prefix(penalty).xclip_suffix(penalty)
+    }
+
+    /// Set the prefix clipping penalty for x (default: `MIN_SCORE`).
+    pub fn xclip_prefix(mut self, penalty: i32) -> Self {
+        self.xclip_prefix = penalty;
+        self
+    }
+
+    /// Set the suffix clipping penalty for x (default: `MIN_SCORE`).
+    pub fn xclip_suffix(mut self, penalty: i32) -> Self {
+        self.xclip_suffix = penalty;
+        self
+    }
+
+    /// Set the prefix and suffix clipping penalties for y (see `Scoring::yclip`,
+    /// default: `MIN_SCORE`, i.e. no clipping).
+    pub fn yclip(self, penalty: i32) -> Self {
+        self.yclip_prefix(penalty).yclip_suffix(penalty)
+    }
+
+    /// Set the prefix clipping penalty for y (default: `MIN_SCORE`).
+    pub fn yclip_prefix(mut self, penalty: i32) -> Self {
+        self.yclip_prefix = penalty;
+        self
+    }
+
+    /// Set the suffix clipping penalty for y (default: `MIN_SCORE`).
+    pub fn yclip_suffix(mut self, penalty: i32) -> Self {
+        self.yclip_suffix = penalty;
+        self
+    }
+
+    /// Reserve memory for aligning sequences of the given lengths m (x) and n (y)
+    /// (default: 200 each).
+    pub fn capacity(mut self, m: usize, n: usize) -> Self {
+        self.capacity = (m, n);
+        self
+    }
+
+    /// Build the scoring with the given score function instead of the match and mismatch
+    /// scores (e.g. `scores::blosum62`).
+    ///
+    /// # Errors
+    /// * `Error::PositiveGapScore` - a gap open or extend score is positive
+    /// * `Error::PositiveClipPenalty` - a clipping penalty is positive
+    pub fn scoring_with<F: MatchFunc>(&self, match_fn: F) -> Result<Scoring<F>> {
+        let (gap_open2, gap_extend2) = self.long_gap.unwrap_or((0, 0));
+        for &score in &[self.gap_open, self.gap_extend, gap_open2, gap_extend2] {
+            if score > 0 {
+                return Err(Error::PositiveGapScore { score });
+            }
+        }
+        let clip_penalties = [
+            self.xclip_prefix,
+            self.xclip_suffix,
+            self.yclip_prefix,
+            self.yclip_suffix,
+        ];
+        if let Some(&score) = clip_penalties.iter().find(|&&score| score > 0) {
+            return Err(Error::PositiveClipPenalty { score });
+        }
+        Ok(Scoring {
+            gap_open: self.gap_open,
+            gap_extend: self.gap_extend,
+            match_fn,
+            match_scores: None,
+            xclip_prefix: self.xclip_prefix,
+            xclip_suffix: self.xclip_suffix,
+            yclip_prefix: self.yclip_prefix,
+            yclip_suffix: self.yclip_suffix,
+            long_gap: self.long_gap,
+        })
+    }
+
+    /// Build the scoring.
+    ///
+    /// # Errors
+    /// * `Error::NegativeMatchScore` and `Error::PositiveMismatchScore` - if the match score is
+    ///   negative or the mismatch score positive
+    /// * see `AlignerBuilder::scoring_with`
+    pub fn scoring(&self) -> Result<Scoring<MatchParams>> {
+        if self.match_score < 0 {
+            return Err(Error::NegativeMatchScore {
+                score: self.match_score,
+            });
+        }
+        if self.mismatch_score > 0 {
+            return Err(Error::PositiveMismatchScore {
+                score: self.mismatch_score,
+            });
+        }
+        let mut scoring =
+            self.scoring_with(MatchParams::new(self.match_score, self.mismatch_score))?;
+        scoring.match_scores = Some((self.match_score, self.mismatch_score));
+        Ok(scoring)
+    }
+
+    /// Build the aligner with the given score function instead of the match and mismatch
+    /// scores, see `AlignerBuilder::scoring_with`.
+    pub fn build_with<F: MatchFunc>(&self, match_fn: F) -> Result<Aligner<F>> {
+        let (m, n) = self.capacity;
+        Ok(Aligner::with_capacity_and_scoring(
+            m,
+            n,
+            self.scoring_with(match_fn)?,
+        ))
+    }
+
+    /// Build the aligner, see `AlignerBuilder::scoring`.
+    pub fn build(&self) -> Result<Aligner<MatchParams>> {
+        let (m, n) = self.capacity;
+        Ok(Aligner::with_capacity_and_scoring(m, n, self.scoring()?))
+    }
 }
 
 /// A generalized Smith-Waterman aligner.
@@ -440,6 +743,9 @@ impl<F: MatchFunc> Scoring<F> {
 /// ```
 /// This is interpreted as a deletion from `x` w.r.t reference `y`
 ///
+/// `I2(i,j)` and `D2(i,j)` are the analogous scores under the second gap model
+/// (only used with a two-piece affine gap model, see `Scoring::long_gap`)
+///
 /// `S(i,j)` is the best score for prefixes `x[0..i]`, `y[0..j]`
 ///
 /// To save space, only two columns of these matrices are stored at
@@ -463,11 +769,17 @@ impl<F: MatchFunc> Scoring<F> {
 pub struct Aligner<F: MatchFunc> {
     I: [Vec<i32>; 2],
     D: [Vec<i32>; 2],
+    #[serde(default)]
+    I2: [Vec<i32>; 2],
+    #[serde(default)]
+    D2: [Vec<i32>; 2],
     S: [Vec<i32>; 2],
     Lx: Vec<usize>,
     Ly: Vec<usize>,
     Sn: Vec<i32>,
     traceback: Traceback,
+    #[serde(default)]
+    traceback2: Traceback,
     scoring: Scoring<F>,
 }
 
@@ -511,11 +823,14 @@ impl<F: MatchFunc> Aligner<F> {
         Aligner {
             I: [Vec::with_capacity(m + 1), Vec::with_capacity(m + 1)],
             D: [Vec::with_capacity(m + 1), Vec::with_capacity(m + 1)],
+            I2: [Vec::new(), Vec::new()],
+            D2: [Vec::new(), Vec::new()],
             S: [Vec::with_capacity(m + 1), Vec::with_capacity(m + 1)],
             Lx: Vec::with_capacity(n + 1),
             Ly: Vec::with_capacity(m + 1),
             Sn: Vec::with_capacity(m + 1),
             traceback: Traceback::with_capacity(m, n),
+            traceback2: Traceback::with_capacity(0, 0),
             scoring: Scoring::new(gap_open, gap_extend, match_fn),
         }
     }
@@ -564,11 +879,14 @@ impl<F: MatchFunc> Aligner<F> {
         Aligner {
             I: [Vec::with_capacity(m + 1), Vec::with_capacity(m + 1)],
             D: [Vec::with_capacity(m + 1), Vec::with_capacity(m + 1)],
+            I2: [Vec::new(), Vec::new()],
+            D2: [Vec::new(), Vec::new()],
             S: [Vec::with_capacity(m + 1), Vec::with_capacity(m + 1)],
             Lx: Vec::with_capacity(n + 1),
             Ly: Vec::with_capacity(m + 1),
             Sn: Vec::with_capacity(m + 1),
             traceback: Traceback::with_capacity(m, n),
+            traceback2: Traceback::with_capacity(0, 0),
             scoring,
         }
     }
@@ -582,16 +900,25 @@ impl<F: MatchFunc> Aligner<F> {
     pub fn custom(&mut self, x: TextSlice<'_>, y: TextSlice<'_>) -> Alignment {
         let (m, n) = (x.len(), y.len());
         self.traceback.init(m, n);
+        if self.scoring.long_gap.is_some() {
+            // I2 and D2 traceback bits are kept in the I and D slots of a separate
+            // matrix, such that the common case does not pay for them
+            self.traceback2.init(m, n);
+        }
 
         // Set the initial conditions
         // We are repeating some work, but that's okay!
         for k in 0..2 {
             self.I[k].clear();
             self.D[k].clear();
+            self.I2[k].clear();
+            self.D2[k].clear();
             self.S[k].clear();
 
             self.D[k].extend(repeat(MIN_SCORE).take(m + 1));
             self.I[k].extend(repeat(MIN_SCORE).take(m + 1));
+            self.D2[k].extend(repeat(MIN_SCORE).take(m + 1));
+            self.I2[k].extend(repeat(MIN_SCORE).take(m + 1));
             self.S[k].extend(repeat(MIN_SCORE).take(m + 1));
 
             self.S[k][0] = 0;
@@ -612,6 +939,7 @@ impl<F: MatchFunc> Aligner<F> {
 
             for i in 1..=m {
                 let mut tb = TracebackCell::new();
+                let mut tb2 = TracebackCell::new();
                 tb.set_all(TB_START);
                 if i == 1 {
                     self.I[k][i] = self.scoring.gap_open + self.scoring.gap_extend;
@@ -629,6 +957,22 @@ impl<F: MatchFunc> Aligner<F> {
                         tb.set_i_bits(TB_XCLIP_PREFIX);
                     }
                 }
+                if let Some((gap_open2, gap_extend2)) = self.scoring.long_gap {
+                    if i == 1 {
+                        self.I2[k][i] = gap_open2 + gap_extend2;
+                        tb2.set_i_bits(TB_START);
+                    } else {
+                        let i_score = gap_open2 + gap_extend2 * (i as i32);
+                        let c_score = self.scoring.xclip_prefix + gap_open2 + gap_extend2;
+                        if i_score > c_score {
+                            self.I2[k][i] = i_score;
+                            tb2.set_i_bits(TB_INS2);
+                        } else {
+                            self.I2[k][i] = c_score;
+                            tb2.set_i_bits(TB_XCLIP_PREFIX);
+                        }
+                    }
+                }
 
                 if i == m {
                     tb.set_s_bits(TB_XCLIP_SUFFIX);
@@ -641,6 +985,11 @@ impl<F: MatchFunc> Aligner<F> {
                     tb.set_s_bits(TB_INS);
                 }
 
+                if self.I2[k][i] > self.S[k][i] {
+                    self.S[k][i] = self.I2[k][i];
+                    tb.set_s_bits(TB_INS2);
+                }
+
                 if self.scoring.xclip_prefix > self.S[k][i] {
                     self.S[k][i] = self.scoring.xclip_prefix;
                     tb.set_s_bits(TB_XCLIP_PREFIX);
@@ -654,6 +1003,9 @@ impl<F: MatchFunc> Aligner<F> {
 
                 if k == 0 {
                     self.traceback.set(i, 0, tb);
+                    if self.scoring.long_gap.is_some() {
+                        self.traceback2.set(i, 0, tb2);
+                    }
                 }
                 // Track the score if we do suffix clip (y) from here
                 if self.S[k][i] + self.scoring.yclip_suffix > self.Sn[i] {
@@ -670,7 +1022,9 @@ impl<F: MatchFunc> Aligner<F> {
             {
                 // Handle i = 0 case
                 let mut tb = TracebackCell::new();
+                let mut tb2 = TracebackCell::new();
                 self.I[curr][0] = MIN_SCORE;
+                self.I2[curr][0] = MIN_SCORE;
 
                 if j == 1 {
                     self.D[curr][0] = self.scoring.gap_open + self.scoring.gap_extend;
@@ -688,6 +1042,22 @@ impl<F: MatchFunc> Aligner<F> {
                         tb.set_d_bits(TB_YCLIP_PREFIX);
                     }
                 }
+                if let Some((gap_open2, gap_extend2)) = self.scoring.long_gap {
+                    if j == 1 {
+                        self.D2[curr][0] = gap_open2 + gap_extend2;
+                        tb2.set_d_bits(TB_START);
+                    } else {
+                        let d_score = gap_open2 + gap_extend2 * (j as i32);
+                        let c_score = self.scoring.yclip_prefix + gap_open2 + gap_extend2;
+                        if d_score > c_score {
+                            self.D2[curr][0] = d_score;
+                            tb2.set_d_bits(TB_DEL2);
+                        } else {
+                            self.D2[curr][0] = c_score;
+                            tb2.set_d_bits(TB_YCLIP_PREFIX);
+                        }
+                    }
+                }
                 if self.D[curr][0] > self.scoring.yclip_prefix {
                     self.S[curr][0] = self.D[curr][0];
                     tb.set_s_bits(TB_DEL);
@@ -695,6 +1065,10 @@ impl<F: MatchFunc> Aligner<F> {
                     self.S[curr][0] = self.scoring.yclip_prefix;
                     tb.set_s_bits(TB_YCLIP_PREFIX);
                 }
+                if self.D2[curr][0] > self.S[curr][0] {
+                    self.S[curr][0] = self.D2[curr][0];
+                    tb.set_s_bits(TB_DEL2);
+                }
 
                 if j == n && self.Sn[0] > self.S[curr][0] {
                     // Check if the suffix clip score is better
@@ -707,6 +1081,9 @@ impl<F: MatchFunc> Aligner<F> {
                 }
 
                 self.traceback.set(0, j, tb);
+                if self.scoring.long_gap.is_some() {
+                    self.traceback2.set(0, j, tb2);
+                }
             }
 
             for i in 1..=m {
@@ -715,13 +1092,11 @@ impl<F: MatchFunc> Aligner<F> {
 
             let q = y[j - 1];
             let xclip_score = self.scoring.xclip_prefix
-                + max(
-                    self.scoring.yclip_prefix,
-                    self.scoring.gap_open + self.scoring.gap_extend * (j as i32),
-                );
+                + max(self.scoring.yclip_prefix, self.scoring.gap_score(j));
             for i in 1..m + 1 {
                 let p = x[i - 1];
                 let mut tb = TracebackCell::new();
+                let mut tb2 = TracebackCell::new();
 
                 let m_score = self.S[prev][i - 1] + self.scoring.match_fn.score(p, q);
 
@@ -747,6 +1122,31 @@ impl<F: MatchFunc> Aligner<F> {
                     tb.set_d_bits(self.traceback.get(i, j - 1).get_s_bits());
                 }
 
+                let (best_i2_score, best_d2_score) = match self.scoring.long_gap {
+                    Some((gap_open2, gap_extend2)) => {
+                        let i_score = self.I2[curr][i - 1] + gap_extend2;
+                        let s_score = self.S[curr][i - 1] + gap_open2 + gap_extend2;
+                        let best_i2_score = if i_score > s_score {
+                            tb2.set_i_bits(TB_INS2);
+                            i_score
+                        } else {
+                            tb2.set_i_bits(self.traceback.get(i - 1, j).get_s_bits());
+                            s_score
+                        };
+                        let d_score = self.D2[prev][i] + gap_extend2;
+                        let s_score = self.S[prev][i] + gap_open2 + gap_extend2;
+                        let best_d2_score = if d_score > s_score {
+                            tb2.set_d_bits(TB_DEL2);
+                            d_score
+                        } else {
+                            tb2.set_d_bits(self.traceback.get(i, j - 1).get_s_bits());
+                            s_score
+                        };
+                        (best_i2_score, best_d2_score)
+                    }
+                    None => (MIN_SCORE, MIN_SCORE),
+                };
+
                 tb.set_s_bits(TB_XCLIP_SUFFIX);
                 let mut best_s_score = self.S[curr][i];
 
@@ -760,19 +1160,27 @@ impl<F: MatchFunc> Aligner<F> {
                     tb.set_s_bits(TB_INS);
                 }
 
+                if best_i2_score > best_s_score {
+                    best_s_score = best_i2_score;
+                    tb.set_s_bits(TB_INS2);
+                }
+
                 if best_d_score > best_s_score {
                     best_s_score = best_d_score;
                     tb.set_s_bits(TB_DEL);
                 }
 
+                if best_d2_score > best_s_score {
+                    best_s_score = best_d2_score;
+                    tb.set_s_bits(TB_DEL2);
+                }
+
                 if xclip_score > best_s_score {
                     best_s_score = xclip_score;
                     tb.set_s_bits(TB_XCLIP_PREFIX);
                 }
 
-                let yclip_score = self.scoring.yclip_prefix
-                    + self.scoring.gap_open
-                    + self.scoring.gap_extend * (i as i32);
+                let yclip_score = self.scoring.yclip_prefix + self.scoring.gap_score(i);
                 if yclip_score > best_s_score {
                     best_s_score = yclip_score;
                     tb.set_s_bits(TB_YCLIP_PREFIX);
@@ -781,6 +1189,8 @@ impl<F: MatchFunc> Aligner<F> {
                 self.S[curr][i] = best_s_score;
                 self.I[curr][i] = best_i_score;
                 self.D[curr][i] = best_d_score;
+                self.I2[curr][i] = best_i2_score;
+                self.D2[curr][i] = best_d2_score;
 
                 // Track the score if we do suffix clip (x) from here
                 if self.S[curr][i] + self.scoring.xclip_suffix > self.S[curr][m] {
@@ -795,6 +1205,9 @@ impl<F: MatchFunc> Aligner<F> {
                 }
 
                 self.traceback.set(i, j, tb);
+                if self.scoring.long_gap.is_some() {
+                    self.traceback2.set(i, j, tb2);
+                }
             }
         }
 
@@ -833,6 +1246,23 @@ impl<F: MatchFunc> Aligner<F> {
                     self.traceback.get_mut(m, j).set_s_bits(TB_XCLIP_SUFFIX);
                 }
             }
+            if let Some((gap_open2, gap_extend2)) = self.scoring.long_gap {
+                let s_score = self.S[curr][i - 1] + gap_open2 + gap_extend2;
+                if s_score > self.I2[curr][i] {
+                    self.I2[curr][i] = s_score;
+                    let s_bit = self.traceback.get(i - 1, j).get_s_bits();
+                    self.traceback2.get_mut(i, j).set_i_bits(s_bit);
+                }
+                if s_score > self.S[curr][i] {
+                    self.S[curr][i] = s_score;
+                    self.traceback.get_mut(i, j).set_s_bits(TB_INS2);
+                    if self.S[curr][i] + self.scoring.xclip_suffix > self.S[curr][m] {
+                        self.S[curr][m] = self.S[curr][i] + self.scoring.xclip_suffix;
+                        self.Lx[j] = m - i;
+                        self.traceback.get_mut(m, j).set_s_bits(TB_XCLIP_SUFFIX);
+                    }
+                }
+            }
         }
 
         let mut i = m;
@@ -859,6 +1289,16 @@ impl<F: MatchFunc> Aligner<F> {
                     next_layer = self.traceback.get(i, j).get_d_bits();
                     j -= 1;
                 }
+                TB_INS2 => {
+                    operations.push(AlignmentOperation::Ins);
+                    next_layer = self.traceback2.get(i, j).get_i_bits();
+                    i -= 1;
+                }
+                TB_DEL2 => {
+                    operations.push(AlignmentOperation::Del);
+                    next_layer = self.traceback2.get(i, j).get_d_bits();
+                    j -= 1;
+                }
                 TB_MATCH => {
                     operations.push(AlignmentOperation::Match);
                     next_layer = self.traceback.get(i - 1, j - 1).get_s_bits();
@@ -943,6 +1383,65 @@ impl<F: MatchFunc> Aligner<F> {
         alignment
     }
 
+    /// Calculate global alignment of x against y in linear space, using the divide and conquer
+    /// approach of Hirschberg (1975) and Myers and Miller (1988). The alignment has the same
+    /// score as the one returned by `global` (though it may be a different one of multiple
+    /// optimal alignments), but only O(min(m, n)) memory is used instead of the O(mn)
+    /// traceback matrix, at the cost of about twice the running time. This is useful for
+    /// aligning long sequences. Clip penalties are ignored.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use bio::alignment::pairwise::Aligner;
+    ///
+    /// let x = b"ACCGTGGAT";
+    /// let y = b"AAAAACCGTTGGAT";
+    /// let score = |a: u8, b: u8| if a == b { 1i32 } else { -1i32 };
+    /// let mut aligner = Aligner::new(-5, -1, score);
+    /// let alignment = aligner.global_hirschberg(x, y);
+    /// assert_eq!(alignment.score, aligner.global(x, y).score);
+    /// assert_eq!(alignment.score, -5);
+    /// ```
+    pub fn global_hirschberg(&self, x: TextSlice<'_>, y: TextSlice<'_>) -> Alignment {
+        let mut gaps = vec![(self.scoring.gap_open, self.scoring.gap_extend)];
+        gaps.extend(self.scoring.long_gap);
+        let mut operations = Vec::with_capacity(x.len() + y.len());
+        // the memory is linear in the length of the second sequence
+        let score = if y.len() > x.len() {
+            let params = hirschberg::Params {
+                match_fn: |a, b| self.scoring.match_fn.score(b, a),
+                gaps,
+            };
+            let score = params.align(y, x, None, None, &mut operations);
+            for op in &mut operations {
+                *op = match *op {
+                    AlignmentOperation::Ins => AlignmentOperation::Del,
+                    AlignmentOperation::Del => AlignmentOperation::Ins,
+                    op => op,
+                };
+            }
+            score
+        } else {
+            let params = hirschberg::Params {
+                match_fn: |a, b| self.scoring.match_fn.score(a, b),
+                gaps,
+            };
+            params.align(x, y, None, None, &mut operations)
+        };
+        Alignment {
+            score,
+            ystart: 0,
+            xstart: 0,
+            yend: y.len(),
+            xend: x.len(),
+            ylen: y.len(),
+            xlen: x.len(),
+            operations,
+            mode: AlignmentMode::Global,
+        }
+    }
+
     /// Calculate semiglobal alignment of x against y (x is global, y is local).
     pub fn semiglobal(&mut self, x: TextSlice<'_>, y: TextSlice<'_>) -> Alignment {
         // Store the current clip penalties
@@ -1011,8 +1510,10 @@ impl<F: MatchFunc> Aligner<F> {
 /// Packed representation of one cell of a Smith-Waterman traceback matrix.
 /// Stores the I, D and S traceback matrix values in two bytes.
 /// Possible traceback moves include : start, insert, delete, match, substitute,
-/// prefix clip and suffix clip for x & y. So we need 4 bits each for matrices I, D, S
-/// to keep track of these 9 moves.
+/// prefix clip and suffix clip for x & y, and insert and delete under the second gap model.
+/// So we need 4 bits each for matrices I, D, S to keep track of these 11 moves.
+/// With a two-piece affine gap model, the I2 and D2 values are stored in the I and D
+/// bits of a second traceback matrix.
 #[derive(
     Default, Copy, Clone, Eq, PartialEq, Ord, PartialOrd, Hash, Debug, Serialize, Deserialize,
 )]
@@ -1037,7 +1538,10 @@ const TB_XCLIP_SUFFIX: u16 = 0b0110; // suffix clip of x
 const TB_YCLIP_PREFIX: u16 = 0b0111; // prefix clip of y
 const TB_YCLIP_SUFFIX: u16 = 0b1000; // suffix clip of y
 
-const TB_MAX: u16 = 0b1000; // Useful in checking that the
+const TB_INS2: u16 = 0b1001; // insert under the second gap model
+const TB_DEL2: u16 = 0b1010; // delete under the second gap model
+
+const TB_MAX: u16 = 0b1010; // Useful in checking that the
                             // TB value we got is a valid one
 
 impl TracebackCell {
@@ -1650,6 +2154,34 @@ mod tests {
         assert_eq!(alignment.operations, [Yclip(6), Match, Match, Match]);
     }
 
+    #[test]
+    fn test_long_gap() {
+        let x = b"ACCGTGGATGGGCGCGTAGCGGCATTACCAGATCAAGG";
+        let y = b"ACCGTGGATGGGCGCGTAGCTTGACCATGAGCATCGGATCCATGCAAGTGGGCATTACCAGATCAAGG";
+
+        let scoring = Scoring::from_scores(-4, -2, 1, -2);
+        let mut aligner = Aligner::with_scoring(scoring.clone());
+        let affine = aligner.global(x, y);
+
+        let mut aligner = Aligner::with_scoring(scoring.long_gap(-24, -1));
+        let alignment = aligner.global(x, y);
+        // the 30bp deletion is cheaper under the second gap model
+        assert_eq!(
+            alignment.score,
+            38 + scoring.long_gap(-24, -1).gap_score(30)
+        );
+        assert_eq!(alignment.score, -16);
+        assert!(alignment.score > affine.score);
+        assert_eq!(
+            alignment.operations.iter().filter(|op| **op == Del).count(),
+            30
+        );
+
+        // short gaps are still scored by the first model
+        let alignment = aligner.global(b"ACGTACGTAACGTACGT", b"ACGTACGTACGTACGT");
+        assert_eq!(alignment.score, 16 - 6);
+    }
+
     #[test]
     fn test_only_clips() {
         let x = b"GGAAAAAAAAAAAAA";
@@ -1666,6 +2198,39 @@ mod tests {
         assert_eq!(alignment.score, 0);
     }
 
+    #[test]
+    fn test_aligner_builder() {
+        let builder = AlignerBuilder::new().long_gap(-24, -1).xclip(-5);
+        assert_eq!(
+            builder.build().unwrap(),
+            Aligner::with_scoring(
+                Scoring::from_scores(-5, -1, 1, -1)
+                    .long_gap(-24, -1)
+                    .xclip(-5)
+            )
+        );
+        let scoring = builder.scoring_with(blosum62).unwrap();
+        assert_eq!((scoring.gap_open, scoring.xclip_suffix), (-5, -5));
+        assert_eq!(scoring.match_scores, None);
+
+        assert_eq!(
+            AlignerBuilder::new().match_score(-1).build(),
+            Err(Error::NegativeMatchScore { score: -1 })
+        );
+        assert_eq!(
+            AlignerBuilder::new().mismatch_score(1).build(),
+            Err(Error::PositiveMismatchScore { score: 1 })
+        );
+        assert_eq!(
+            AlignerBuilder::new().long_gap(2, -1).build(),
+            Err(Error::PositiveGapScore { score: 2 })
+        );
+        assert_eq!(
+            AlignerBuilder::new().yclip_suffix(3).build(),
+            Err(Error::PositiveClipPenalty { score: 3 })
+        );
+    }
+
     #[test]
     fn test_zero_score_clips() {
         let x = b"AA";
@@ -0,0 +1,363 @@
+//! Pileup of alignment records, i.e. the bases of all reads aligned to each reference position.
+//!
+//! The pileup consumes records sorted by reference id and position (e.g. from
+//! `bio::io::bam::Reader`) and yields one column per covered reference position, in order.
+//! Only the columns overlapping the current record are buffered. Reads that are
+//! unmapped, secondary, failed quality checks or are duplicates are skipped by default.
+//!
+//! # Example
+//!
+//! ```
+//! use bio::alignment::pileup::Pileup;
+//! use bio::io::bam::{CigarOp, Record};
+//!
+//! let read = |pos, seq: &[u8], reverse| Record {
+//!     ref_id: Some(0),
+//!     pos: Some(pos),
+//!     flag: if reverse { 0x10 } else { 0 },
+//!     mapq: 60,
+//!     cigar: vec![CigarOp {
+//!         len: seq.len() as u32,
+//!         op: b'M',
+//!     }],
+//!     seq: seq.to_vec(),
+//!     qual: vec![30; seq.len()],
+//!     ..Default::default()
+//! };
+//! let records = vec![read(10, b"ACGT", false), read(12, b"TTA", true)];
+//!
+//! let columns: Vec<_> = Pileup::new(records.into_iter().map(Ok))
+//!     .map(|column| column.unwrap())
+//!     .collect();
+//! assert_eq!(columns.len(), 5);
+//! assert_eq!(columns[2].pos, 12);
+//! assert_eq!(columns[2].depth(), 2);
+//! assert_eq!(columns[2].count(b'G'), 1);
+//! assert_eq!(columns[2].count(b'T'), 1);
+//! assert_eq!(columns[2].strand_depths(), (1, 1));
+//! ```
+
+use std::collections::BTreeMap;
+use std::iter::Peekable;
+
+use crate::io::bam::{Error, Record, Result};
+use bio_types::strand::ReqStrand;
+
+/// A read at a pileup column.
+#[derive(Copy, Clone, Eq, PartialEq, Ord, PartialOrd, Hash, Debug, Serialize, Deserialize)]
+pub struct PileupEntry {
+    /// The read base aligned to the column, `None` if the read has a deletion here.
+    pub base: Option<u8>,
+    /// PHRED quality of the base, 0 for deletions and 255 if the read has no qualities.
+    pub qual: u8,
+    /// Position in the read of the base (or the base following a deletion).
+    pub read_pos: usize,
+    pub strand: ReqStrand,
+    pub mapq: u8,
+}
+
+impl PileupEntry {
+    /// Whether the read has a deletion at the column.
+    pub fn is_deletion(&self) -> bool {
+        self.base.is_none()
+    }
+}
+
+/// All reads aligned to a reference position.
+#[derive(Default, Clone, Eq, PartialEq, Ord, PartialOrd, Hash, Debug, Serialize, Deserialize)]
+pub struct PileupColumn {
+    pub ref_id: usize,
+    /// 0-based reference position.
+    pub pos: usize,
+    /// The reads, in the order of their records.
+    pub entries: Vec<PileupEntry>,
+}
+
+impl PileupColumn {
+    /// Number of reads at the column, including deletions.
+    pub fn depth(&self) -> usize {
+        self.entries.len()
+    }
+
+    /// Number of reads with the given base (case-insensitive) at the column.
+    pub fn count(&self, base: u8) -> usize {
+        let (fwd, rev) = self.strand_counts(base);
+        fwd + rev
+    }
+
+    /// Number of reads on the forward and reverse strand with the given base
+    /// (case-insensitive) at the column.
+    pub fn strand_counts(&self, base: u8) -> (usize, usize) {
+        self.strand_depths_where(|e| e.base.map_or(false, |b| b.eq_ignore_ascii_case(&base)))
+    }
+
+    /// Number of reads with a deletion at the column.
+    pub fn deletions(&self) -> usize {
+        self.entries.iter().filter(|e| e.is_deletion()).count()
+    }
+
+    /// Number of reads on the forward and reverse strand at the column.
+    pub fn strand_depths(&self) -> (usize, usize) {
+        self.strand_depths_where(|_| true)
+    }
+
+    fn strand_depths_where<F: Fn(&PileupEntry) -> bool>(&self, f: F) -> (usize, usize) {
+        self.entries
+            .iter()
+            .filter(|e| f(e))
+            .fold((0, 0), |(fwd, rev), e| match e.strand {
+                ReqStrand::Reverse => (fwd, rev + 1),
+                _ => (fwd + 1, rev),
+            })
+    }
+}
+
+/// Iterator over the pileup columns of sorted alignment records.
+#[derive(Debug)]
+pub struct Pileup<I: Iterator<Item = Result<Record>>> {
+    records: Peekable<I>,
+    min_mapq: u8,
+    exclude_flags: u16,
+    ref_id: Option<usize>,
+    // start of the last added record, for checking the sort order
+    last_pos: usize,
+    // columns of the current reference that are covered by buffered reads
+    columns: BTreeMap<usize, Vec<PileupEntry>>,
+}
+
+impl<I: Iterator<Item = Result<Record>>> Pileup<I> {
+    /// Create a new pileup over the given records, which have to be sorted by reference id
+    /// and position.
+    pub fn new<R: IntoIterator<IntoIter = I>>(records: R) -> Self {
+        Pileup {
+            records: records.into_iter().peekable(),
+            min_mapq: 0,
+            // unmapped, secondary, quality check failed, duplicate
+            exclude_flags: 0x4 | 0x100 | 0x200 | 0x400,
+            ref_id: None,
+            last_pos: 0,
+            columns: BTreeMap::new(),
+        }
+    }
+
+    /// Skip records with a mapping quality below the given one.
+    pub fn min_mapq(mut self, min_mapq: u8) -> Self {
+        self.min_mapq = min_mapq;
+        self
+    }
+
+    /// Skip records with any of the given flags set (by default unmapped, secondary,
+    /// quality check failed and duplicate reads, i.e. `0x704`).
+    pub fn exclude_flags(mut self, flags: u16) -> Self {
+        self.exclude_flags = flags;
+        self
+    }
+
+    /// Add the bases of a record to the buffered columns.
+    fn add(&mut self, record: &Record) {
+        let strand = if record.is_reverse() {
+            ReqStrand::Reverse
+        } else {
+            ReqStrand::Forward
+        };
+        let mut pos = record.pos.unwrap();
+        let mut read_pos = 0;
+        let entry = |base: Option<u8>, read_pos: usize| PileupEntry {
+            base,
+            qual: match base {
+                Some(_) => record.qual.get(read_pos).copied().unwrap_or(255),
+                None => 0,
+            },
+            read_pos,
+            strand,
+            mapq: record.mapq,
+        };
+        for op in &record.cigar {
+            let len = op.len as usize;
+            match op.op {
+                b'M' | b'=' | b'X' => {
+                    for i in 0..len {
+                        let base = record.seq.get(read_pos + i).copied();
+                        self.columns
+                            .entry(pos + i)
+                            .or_default()
+                            .push(entry(base.or(Some(b'N')), read_pos + i));
+                    }
+                    pos += len;
+                    read_pos += len;
+                }
+                b'D' => {
+                    for i in 0..len {
+                        self.columns
+                            .entry(pos + i)
+                            .or_default()
+                            .push(entry(None, read_pos));
+                    }
+                    pos += len;
+                }
+                b'N' => pos += len,
+                b'I' | b'S' => read_pos += len,
+                _ => (),
+            }
+        }
+    }
+}
+
+impl<I: Iterator<Item = Result<Record>>> Iterator for Pileup<I> {
+    type Item = Result<PileupColumn>;
+
+    fn next(&mut self) -> Option<Result<PileupColumn>> {
+        loop {
+            // the first buffered column is complete unless the next record starts before it
+            let first = self.columns.keys().next().copied();
+            let (min_mapq, exclude_flags) = (self.min_mapq, self.exclude_flags);
+            let is_excluded = |record: &Record| {
+                record.flag & exclude_flags != 0
+                    || record.mapq < min_mapq
+                    || record.ref_id.is_none()
+                    || record.pos.is_none()
+            };
+            let next = match self.records.peek() {
+                Some(Ok(record)) if is_excluded(record) => {
+                    self.records.next();
+                    continue;
+                }
+                Some(Ok(record)) => Some((record.ref_id.unwrap(), record.pos.unwrap())),
+                Some(Err(_)) => {
+                    let err = self.records.next().unwrap().unwrap_err();
+                    return Some(Err(err));
+                }
+                None => None,
+            };
+            if let Some((ref_id, pos)) = next {
+                let same_ref = self.ref_id == Some(ref_id);
+                if self.ref_id.map_or(false, |r| r > ref_id) || (same_ref && pos < self.last_pos) {
+                    return Some(Err(Error::InvalidRecord {
+                        reason: "records are not sorted by position".to_owned(),
+                    }));
+                }
+                if first.is_none() || (same_ref && first.map_or(false, |first| pos <= first)) {
+                    let record = self.records.next().unwrap().unwrap();
+                    self.ref_id = Some(ref_id);
+                    self.last_pos = pos;
+                    self.add(&record);
+                    continue;
+                }
+            }
+
+            let pos = *self.columns.keys().next()?;
+            let entries = self.columns.remove(&pos).unwrap();
+            return Some(Ok(PileupColumn {
+                ref_id: self.ref_id.unwrap(),
+                pos,
+                entries,
+            }));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::io::bam::CigarOp;
+
+    fn record(ref_id: usize, pos: usize, cigar: &[(u32, u8)], seq: &[u8], flag: u16) -> Record {
+        Record {
+            ref_id: Some(ref_id),
+            pos: Some(pos),
+            flag,
+            mapq: 60,
+            cigar: cigar.iter().map(|&(len, op)| CigarOp { len, op }).collect(),
+            seq: seq.to_vec(),
+            qual: (0..seq.len() as u8).collect(),
+            ..Default::default()
+        }
+    }
+
+    fn pileup(records: Vec<Record>) -> Vec<PileupColumn> {
+        Pileup::new(records.into_iter().map(Ok))
+            .map(|column| column.unwrap())
+            .collect()
+    }
+
+    #[test]
+    fn test_indels_and_clips() {
+        let records = vec![
+            // soft clip, insertion of A, deletion of 2, reference skip of 3
+            record(
+                0,
+                5,
+                &[
+                    (2, b'S'),
+                    (2, b'M'),
+                    (1, b'I'),
+                    (1, b'M'),
+                    (2, b'D'),
+                    (1, b'M'),
+                    (3, b'N'),
+                    (1, b'M'),
+                ],
+                b"GGCAATCG",
+                0x10,
+            ),
+            record(0, 6, &[(4, b'M')], b"ATTC", 0),
+        ];
+        let columns = pileup(records);
+        let positions: Vec<_> = columns.iter().map(|c| c.pos).collect();
+        assert_eq!(positions, [5, 6, 7, 8, 9, 10, 14]);
+
+        let bases = |c: &PileupColumn| c.entries.iter().map(|e| e.base).collect::<Vec<_>>();
+        assert_eq!(bases(&columns[0]), [Some(b'C')]);
+        assert_eq!(bases(&columns[1]), [Some(b'A'), Some(b'A')]);
+        assert_eq!(bases(&columns[2]), [Some(b'T'), Some(b'T')]);
+        assert_eq!(bases(&columns[3]), [None, Some(b'T')]);
+        assert_eq!(bases(&columns[4]), [None, Some(b'C')]);
+        assert_eq!(bases(&columns[5]), [Some(b'C')]);
+        assert_eq!(bases(&columns[6]), [Some(b'G')]);
+
+        assert_eq!(columns[2].entries[0].read_pos, 5);
+        assert_eq!(columns[2].entries[0].qual, 5);
+        assert_eq!(columns[3].deletions(), 1);
+        assert_eq!(columns[3].entries[0].qual, 0);
+        assert_eq!(columns[2].strand_counts(b't'), (1, 1));
+    }
+
+    #[test]
+    fn test_references_and_filters() {
+        let mut low_mapq = record(0, 0, &[(2, b'M')], b"AC", 0);
+        low_mapq.mapq = 5;
+        let records = vec![
+            low_mapq,
+            record(0, 0, &[(2, b'M')], b"AC", 0x400),
+            record(0, 1, &[(2, b'M')], b"CG", 0),
+            record(1, 0, &[(1, b'M')], b"T", 0),
+        ];
+        let columns: Vec<_> = Pileup::new(records.clone().into_iter().map(Ok))
+            .min_mapq(10)
+            .map(|c| c.unwrap())
+            .collect();
+        let coords: Vec<_> = columns
+            .iter()
+            .map(|c| (c.ref_id, c.pos, c.depth()))
+            .collect();
+        assert_eq!(coords, [(0, 1, 1), (0, 2, 1), (1, 0, 1)]);
+
+        let columns = pileup(records.clone());
+        assert_eq!(columns[0].depth(), 1);
+        let columns: Vec<_> = Pileup::new(records.into_iter().map(Ok))
+            .exclude_flags(0)
+            .map(|c| c.unwrap())
+            .collect();
+        assert_eq!(columns[0].depth(), 2);
+    }
+
+    #[test]
+    fn test_unsorted() {
+        let records = vec![
+            record(0, 5, &[(1, b'M')], b"A", 0),
+            record(0, 3, &[(1, b'M')], b"A", 0),
+        ];
+        let mut columns = Pileup::new(records.into_iter().map(Ok));
+        assert!(columns.next().unwrap().is_err());
+    }
+}
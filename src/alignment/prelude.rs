@@ -0,0 +1,23 @@
+//! The alignment prelude, re-exporting the pairwise aligners, their configuration and the
+//! alignment types, such that they can be imported at once.
+//!
+//! # Example
+//!
+//! ```
+//! use bio::alignment::prelude::*;
+//!
+//! let x = b"ACCGTGGAT";
+//! let y = b"AAAAACCGTTGAT";
+//! let mut aligner = AlignerBuilder::new().build().unwrap();
+//! let alignment: Alignment = aligner.semiglobal(x, y);
+//! assert_eq!(alignment.mode, AlignmentMode::Semiglobal);
+//!
+//! let mut banded = banded::AlignerBuilder::new().k(4).build().unwrap();
+//! assert_eq!(banded.semiglobal(x, y).unwrap(), alignment);
+//! ```
+
+pub use crate::alignment::pairwise::banded;
+pub use crate::alignment::pairwise::{
+    Aligner, AlignerBuilder, MatchFunc, MatchParams, Scoring, MIN_SCORE,
+};
+pub use crate::alignment::{Alignment, AlignmentMode, AlignmentOperation, PrettyAlignment};
@@ -0,0 +1,620 @@
+//! Monospaced text rendering of pairwise alignments with annotation tracks.
+//!
+//! An `AlignmentView` lays out the aligned sequences like `Alignment::pretty`, wrapped into
+//! blocks of a given width, and draws any number of annotation tracks below each block.
+//! A track consists of labelled intervals in the coordinates of one of the two sequences.
+//! Every column of a feature is drawn as `=`, with the label written over the first columns
+//! of the feature in each block. Gap columns inside a feature are drawn as part of it.
+//!
+//! # Example
+//!
+//! ```
+//! use bio::alignment::pairwise::Aligner;
+//! use bio::alignment::render::{AlignmentView, Sequence, Track};
+//!
+//! let x = b"ACCGTGGAT";
+//! let y = b"AAAAACCGTTGAT";
+//! let score = |a: u8, b: u8| if a == b { 1i32 } else { -1i32 };
+//! let mut aligner = Aligner::with_capacity(x.len(), y.len(), -5, -1, &score);
+//! let alignment = aligner.semiglobal(x, y);
+//!
+//! let genes = Track::new("gene", Sequence::Y).feature(5..9, "ccgt");
+//! let view = AlignmentView::new(&alignment, x, y).width(20).track(genes);
+//! let rendered = view.render();
+//! let lines: Vec<&str> = rendered.lines().collect();
+//! assert_eq!(lines[0], "x        ACCGTGGAT");
+//! assert_eq!(lines[2], "y    AAAAACCGTTGAT");
+//! assert_eq!(lines[3], "gene      ccgt");
+//! ```
+//!
+//! Without annotation tracks, `PrettyAlignment::pretty_with` renders the alignment with a
+//! configurable width, 1-based coordinates, ANSI colors or as FASTA records of the aligned
+//! sequences:
+//!
+//! ```
+//! use bio::alignment::pairwise::Aligner;
+//! use bio::alignment::render::{PrettyAlignment, PrettyOptions};
+//!
+//! let x = b"ACCGTGGAT";
+//! let y = b"ACCGTCGGAT";
+//! let score = |a: u8, b: u8| if a == b { 1i32 } else { -1i32 };
+//! let mut aligner = Aligner::new(-5, -1, score);
+//! let alignment = aligner.global(x, y);
+//!
+//! let options = PrettyOptions::default().width(6).coordinates(true);
+//! assert_eq!(
+//!     alignment.pretty_with(x, y, &options),
+//!     "x 1  ACCGT- 5\n     |||||x\ny 1  ACCGTC 6\n\nx 6  GGAT 9\n     ||||\ny 7  GGAT 10\n\n"
+//! );
+//!
+//! let options = PrettyOptions::default().width(6).fasta(true).names("read", "ref");
+//! assert_eq!(
+//!     alignment.pretty_with(x, y, &options),
+//!     ">read\nACCGT-\nGGAT\n>ref\nACCGTC\nGGAT\n"
+//! );
+//! ```
+
+use std::fmt;
+use std::ops::Range;
+
+use crate::alignment::{Alignment, AlignmentMode, AlignmentOperation};
+use crate::utils::TextSlice;
+
+/// The aligned sequence an annotation track refers to.
+#[derive(Copy, Clone, Eq, PartialEq, Hash, Debug, Serialize, Deserialize)]
+pub enum Sequence {
+    /// The query sequence `x`.
+    X,
+    /// The reference sequence `y`.
+    Y,
+}
+
+/// An annotation track, i.e. labelled intervals over one of the aligned sequences.
+#[derive(Clone, Eq, PartialEq, Debug, Serialize, Deserialize)]
+pub struct Track {
+    name: String,
+    sequence: Sequence,
+    features: Vec<(Range<usize>, String)>,
+}
+
+impl Track {
+    /// Create a new, empty track with the given name over the given sequence.
+    pub fn new<N: Into<String>>(name: N, sequence: Sequence) -> Self {
+        Track {
+            name: name.into(),
+            sequence,
+            features: Vec::new(),
+        }
+    }
+
+    /// Add a feature spanning the given (0-based, half-open) interval of the sequence.
+    /// Later features are drawn over earlier ones where they overlap.
+    pub fn feature<L: Into<String>>(mut self, interval: Range<usize>, label: L) -> Self {
+        self.features.push((interval, label.into()));
+        self
+    }
+
+    /// Name of the track.
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Sequence the features of this track refer to.
+    pub fn sequence(&self) -> Sequence {
+        self.sequence
+    }
+
+    /// Features of this track.
+    pub fn features(&self) -> &[(Range<usize>, String)] {
+        &self.features
+    }
+}
+
+/// One column of a rendered alignment.
+#[derive(Copy, Clone, Eq, PartialEq, Debug)]
+pub(crate) struct Column {
+    /// Symbol and position of `x` in this column, `None` for a gap.
+    pub x: Option<(u8, usize)>,
+    /// Symbol and position of `y` in this column, `None` for a gap.
+    pub y: Option<(u8, usize)>,
+    /// Symbol drawn between the sequences.
+    pub op: u8,
+}
+
+impl Column {
+    fn new(x: Option<(u8, usize)>, y: Option<(u8, usize)>, op: u8) -> Self {
+        Column { x, y, op }
+    }
+}
+
+/// Lay out the alignment as a sequence of columns, using the same conventions as
+/// `Alignment::pretty`: `|` for a match, `\` for a mismatch, `+` for an insertion,
+/// `x` for a deletion and ` ` for clipped or unaligned sequence.
+pub(crate) fn columns(alignment: &Alignment, x: TextSlice<'_>, y: TextSlice<'_>) -> Vec<Column> {
+    let mut cols = Vec::new();
+    if alignment.operations.is_empty() {
+        return cols;
+    }
+    let (mut xi, mut yi) = (0, 0);
+    if alignment.mode != AlignmentMode::Custom {
+        for (i, &a) in x[..alignment.xstart].iter().enumerate() {
+            cols.push(Column::new(Some((a, i)), None, b' '));
+        }
+        for (i, &b) in y[..alignment.ystart].iter().enumerate() {
+            cols.push(Column::new(None, Some((b, i)), b' '));
+        }
+        xi = alignment.xstart;
+        yi = alignment.ystart;
+    }
+    for &op in &alignment.operations {
+        match op {
+            AlignmentOperation::Match | AlignmentOperation::Subst => {
+                let symbol = if op == AlignmentOperation::Match {
+                    b'|'
+                } else {
+                    b'\\'
+                };
+                cols.push(Column::new(Some((x[xi], xi)), Some((y[yi], yi)), symbol));
+                xi += 1;
+                yi += 1;
+            }
+            AlignmentOperation::Del => {
+                cols.push(Column::new(None, Some((y[yi], yi)), b'x'));
+                yi += 1;
+            }
+            AlignmentOperation::Ins => {
+                cols.push(Column::new(Some((x[xi], xi)), None, b'+'));
+                xi += 1;
+            }
+            AlignmentOperation::Xclip(len) => {
+                for _ in 0..len {
+                    cols.push(Column::new(Some((x[xi], xi)), None, b' '));
+                    xi += 1;
+                }
+            }
+            AlignmentOperation::Yclip(len) => {
+                for _ in 0..len {
+                    cols.push(Column::new(None, Some((y[yi], yi)), b' '));
+                    yi += 1;
+                }
+            }
+        }
+    }
+    if alignment.mode != AlignmentMode::Custom {
+        for (i, &a) in x.iter().enumerate().take(alignment.xlen).skip(xi) {
+            cols.push(Column::new(Some((a, i)), None, b' '));
+        }
+        for (i, &b) in y.iter().enumerate().take(alignment.ylen).skip(yi) {
+            cols.push(Column::new(None, Some((b, i)), b' '));
+        }
+    }
+    cols
+}
+
+/// Renders an alignment together with annotation tracks as monospaced text.
+#[derive(Clone, Debug)]
+pub struct AlignmentView<'a> {
+    alignment: &'a Alignment,
+    x: TextSlice<'a>,
+    y: TextSlice<'a>,
+    width: usize,
+    tracks: Vec<Track>,
+}
+
+impl<'a> AlignmentView<'a> {
+    /// Create a new view of the given alignment between `x` and `y`, with a default
+    /// block width of 100 columns.
+    pub fn new(alignment: &'a Alignment, x: TextSlice<'a>, y: TextSlice<'a>) -> Self {
+        AlignmentView {
+            alignment,
+            x,
+            y,
+            width: 100,
+            tracks: Vec::new(),
+        }
+    }
+
+    /// Set the number of alignment columns per block.
+    pub fn width(mut self, width: usize) -> Self {
+        assert!(width > 0, "width has to be positive");
+        self.width = width;
+        self
+    }
+
+    /// Add an annotation track, drawn below the sequences in the order of addition.
+    pub fn track(mut self, track: Track) -> Self {
+        self.tracks.push(track);
+        self
+    }
+
+    /// Render the view into a string. Each block consists of the `x` row, the operation
+    /// row, the `y` row and one row per track, prefixed by a gutter with the row names,
+    /// and is followed by an empty line. Trailing whitespace is removed from each row.
+    pub fn render(&self) -> String {
+        let cols = columns(self.alignment, self.x, self.y);
+        let gutter = self
+            .tracks
+            .iter()
+            .map(|track| track.name.chars().count())
+            .max()
+            .unwrap_or(0)
+            .max(1)
+            + 1;
+        let track_rows: Vec<Vec<u8>> = self
+            .tracks
+            .iter()
+            .map(|track| draw_track(track, &cols))
+            .collect();
+
+        let mut s = String::new();
+        let push_row = |s: &mut String, name: &str, row: &[u8]| {
+            let line = format!("{:gutter$}{}", name, String::from_utf8_lossy(row));
+            s.push_str(line.trim_end());
+            s.push('\n');
+        };
+        for start in (0..cols.len()).step_by(self.width) {
+            let block = &cols[start..(start + self.width).min(cols.len())];
+            let x_row: Vec<u8> = block.iter().map(|c| c.x.map_or(b'-', |x| x.0)).collect();
+            let op_row: Vec<u8> = block.iter().map(|c| c.op).collect();
+            let y_row: Vec<u8> = block.iter().map(|c| c.y.map_or(b'-', |y| y.0)).collect();
+            push_row(
+                &mut s,
+                "x",
+                &mask_unaligned(&x_row, block, |c| c.x.is_none()),
+            );
+            push_row(&mut s, "", &op_row);
+            push_row(
+                &mut s,
+                "y",
+                &mask_unaligned(&y_row, block, |c| c.y.is_none()),
+            );
+            for (track, row) in self.tracks.iter().zip(&track_rows) {
+                push_row(&mut s, &track.name, &row[start..start + block.len()]);
+            }
+            s.push('\n');
+        }
+        s
+    }
+}
+
+impl fmt::Display for AlignmentView<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.render())
+    }
+}
+
+/// Accessor of the symbol and position of one sequence in a column.
+type Symbol = fn(&Column) -> Option<(u8, usize)>;
+
+const RED: &str = "\x1b[31m";
+const YELLOW: &str = "\x1b[33m";
+const RESET: &str = "\x1b[0m";
+
+/// Options of `PrettyAlignment::pretty_with`.
+#[derive(Clone, Eq, PartialEq, Hash, Debug, Serialize, Deserialize)]
+pub struct PrettyOptions {
+    width: usize,
+    coordinates: bool,
+    color: bool,
+    fasta: bool,
+    names: (String, String),
+}
+
+impl Default for PrettyOptions {
+    fn default() -> Self {
+        PrettyOptions {
+            width: 100,
+            coordinates: false,
+            color: false,
+            fasta: false,
+            names: ("x".to_owned(), "y".to_owned()),
+        }
+    }
+}
+
+impl PrettyOptions {
+    /// Set the number of alignment columns per block or FASTA line (default: 100).
+    pub fn width(mut self, width: usize) -> Self {
+        assert!(width > 0, "width has to be positive");
+        self.width = width;
+        self
+    }
+
+    /// Show the 1-based positions of the first and last symbol of each sequence in each
+    /// block (default: false).
+    pub fn coordinates(mut self, coordinates: bool) -> Self {
+        self.coordinates = coordinates;
+        self
+    }
+
+    /// Highlight substitutions in red and gaps in yellow with ANSI escape codes
+    /// (default: false).
+    pub fn color(mut self, color: bool) -> Self {
+        self.color = color;
+        self
+    }
+
+    /// Output the aligned sequences (with `-` for gaps) as wrapped FASTA records instead of
+    /// blocks (default: false).
+    pub fn fasta(mut self, fasta: bool) -> Self {
+        self.fasta = fasta;
+        self
+    }
+
+    /// Set the names of `x` and `y`, used as row names and FASTA headers
+    /// (default: `x` and `y`).
+    pub fn names<X: Into<String>, Y: Into<String>>(mut self, x: X, y: Y) -> Self {
+        self.names = (x.into(), y.into());
+        self
+    }
+}
+
+/// Configurable pretty printing of alignments.
+pub trait PrettyAlignment {
+    /// Render the alignment between `x` and `y` with the given options. Each block consists
+    /// of the `x` row, the operation row and the `y` row (using the same symbols as
+    /// `Alignment::pretty`), prefixed by a gutter with the row names and the optional start
+    /// coordinates, and is followed by an empty line. Trailing whitespace is removed from
+    /// each row.
+    fn pretty_with(&self, x: TextSlice<'_>, y: TextSlice<'_>, options: &PrettyOptions) -> String;
+}
+
+impl PrettyAlignment for Alignment {
+    fn pretty_with(&self, x: TextSlice<'_>, y: TextSlice<'_>, options: &PrettyOptions) -> String {
+        let cols = columns(self, x, y);
+        if options.fasta {
+            let mut s = String::new();
+            for (name, row) in [
+                (&options.names.0, gapped(&cols, |c| c.x)),
+                (&options.names.1, gapped(&cols, |c| c.y)),
+            ] {
+                s.push('>');
+                s.push_str(name);
+                s.push('\n');
+                for line in row.chunks(options.width) {
+                    s.push_str(&String::from_utf8_lossy(line));
+                    s.push('\n');
+                }
+            }
+            return s;
+        }
+
+        let names = [&options.names.0, &options.names.1];
+        let gutter = names.iter().map(|name| name.chars().count()).max().unwrap() + 1;
+        let digits = if options.coordinates {
+            x.len().max(y.len()).to_string().len() + 1
+        } else {
+            0
+        };
+        let (mut xi, mut yi) = (0, 0);
+        let mut s = String::new();
+        let mut push_line = |line: String| {
+            s.push_str(line.trim_end());
+            s.push('\n');
+        };
+        for block in cols.chunks(options.width) {
+            let sequence_line = |name: &str, consumed: &mut usize, get: Symbol| {
+                let mut line = format!("{:gutter$}", name);
+                if options.coordinates {
+                    let start = block.iter().find_map(get).map_or(*consumed, |a| a.1 + 1);
+                    line.push_str(&format!("{:<digits$}", start));
+                }
+                let row = mask_unaligned(&gapped(block, get), block, |c| get(c).is_none());
+                for (&symbol, col) in row.iter().zip(block) {
+                    match col.op {
+                        b'\\' if options.color => {
+                            line.push_str(&format!("{}{}{}", RED, char::from(symbol), RESET))
+                        }
+                        b'+' | b'x' if options.color => {
+                            line.push_str(&format!("{}{}{}", YELLOW, char::from(symbol), RESET))
+                        }
+                        _ => line.push(char::from(symbol)),
+                    }
+                }
+                if options.coordinates {
+                    if let Some(last) = block.iter().rev().find_map(get) {
+                        *consumed = last.1 + 1;
+                    }
+                    line.push_str(&format!(" {}", consumed));
+                }
+                line
+            };
+            let x_line = sequence_line(names[0], &mut xi, |c| c.x);
+            let y_line = sequence_line(names[1], &mut yi, |c| c.y);
+            let op_row: Vec<u8> = block.iter().map(|c| c.op).collect();
+            push_line(x_line);
+            push_line(format!(
+                "{:w$}{}",
+                "",
+                String::from_utf8_lossy(&op_row),
+                w = gutter + digits
+            ));
+            push_line(y_line);
+            push_line(String::new());
+        }
+        s
+    }
+}
+
+/// The symbols of one sequence in all columns, with `-` for gaps.
+fn gapped(cols: &[Column], get: Symbol) -> Vec<u8> {
+    cols.iter().map(|c| get(c).map_or(b'-', |a| a.0)).collect()
+}
+
+/// Gap symbols facing clipped or unaligned sequence are drawn as blanks.
+fn mask_unaligned<F: Fn(&Column) -> bool>(row: &[u8], block: &[Column], is_gap: F) -> Vec<u8> {
+    row.iter()
+        .zip(block)
+        .map(|(&symbol, col)| {
+            if is_gap(col) && col.op == b' ' {
+                b' '
+            } else {
+                symbol
+            }
+        })
+        .collect()
+}
+
+/// Draw the features of a track over all columns of the alignment.
+fn draw_track(track: &Track, cols: &[Column]) -> Vec<u8> {
+    // number of residues of the tracked sequence before each column, and the
+    // residue position at the column (if any)
+    let mut consumed = 0;
+    let positions: Vec<(usize, Option<usize>)> = cols
+        .iter()
+        .map(|col| {
+            let pos = match track.sequence {
+                Sequence::X => col.x.map(|x| x.1),
+                Sequence::Y => col.y.map(|y| y.1),
+            };
+            let before = consumed;
+            if let Some(p) = pos {
+                consumed = p + 1;
+            }
+            (before, pos)
+        })
+        .collect();
+
+    let mut row = vec![b' '; cols.len()];
+    for (interval, label) in &track.features {
+        let inside: Vec<bool> = positions
+            .iter()
+            .map(|&(before, pos)| match pos {
+                Some(p) => interval.contains(&p),
+                None => interval.start < before && before < interval.end,
+            })
+            .collect();
+        let mut i = 0;
+        while i < inside.len() {
+            if inside[i] {
+                let run_start = i;
+                while i < inside.len() && inside[i] {
+                    row[i] = b'=';
+                    i += 1;
+                }
+                row[run_start..i]
+                    .iter_mut()
+                    .zip(label.bytes())
+                    .for_each(|(cell, c)| *cell = c);
+            } else {
+                i += 1;
+            }
+        }
+    }
+    row
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::alignment::AlignmentOperation::*;
+
+    fn alignment() -> Alignment {
+        Alignment {
+            score: 0,
+            xstart: 0,
+            ystart: 0,
+            xend: 6,
+            yend: 6,
+            ylen: 6,
+            xlen: 6,
+            operations: vec![Match, Match, Ins, Match, Del, Subst, Match],
+            mode: AlignmentMode::Global,
+        }
+    }
+
+    #[test]
+    fn test_columns() {
+        let cols = columns(&alignment(), b"ACGTAC", b"ACTGGC");
+        assert_eq!(cols.len(), 7);
+        assert_eq!(cols[2], Column::new(Some((b'G', 2)), None, b'+'));
+        assert_eq!(cols[4], Column::new(None, Some((b'G', 3)), b'x'));
+    }
+
+    #[test]
+    fn test_render_tracks() {
+        let aln = alignment();
+        let view = AlignmentView::new(&aln, b"ACGTAC", b"ACTGGC")
+            .track(Track::new("x-feat", Sequence::X).feature(1..4, "ab"))
+            .track(Track::new("y", Sequence::Y).feature(2..5, "long-label"));
+        assert_eq!(
+            view.render(),
+            "x      ACGT-AC\n       ||+|x\\|\n\
+             y      AC-TGGC\n\
+             x-feat  ab=\n\
+             y         lon\n\n"
+        );
+    }
+
+    #[test]
+    fn test_render_blocks() {
+        let aln = alignment();
+        let view = AlignmentView::new(&aln, b"ACGTAC", b"ACTGGC")
+            .width(4)
+            .track(Track::new("t", Sequence::Y).feature(1..3, "f"));
+        assert_eq!(
+            view.render(),
+            "x ACGT\n  ||+|\ny AC-T\nt  f==\n\nx -AC\n  x\\|\ny GGC\nt\n\n"
+        );
+        assert_eq!(format!("{}", view), view.render());
+    }
+
+    #[test]
+    fn test_pretty_with() {
+        let aln = alignment();
+        let (x, y) = (b"ACGTAC", b"ACTGGC");
+        let options = PrettyOptions::default()
+            .width(4)
+            .coordinates(true)
+            .names("read", "r");
+        assert_eq!(
+            aln.pretty_with(x, y, &options),
+            "read 1 ACGT 4\n       ||+|\nr    1 AC-T 3\n\n\
+             read 5 -AC 6\n       x\\|\nr    4 GGC 6\n\n"
+        );
+
+        let options = PrettyOptions::default().color(true);
+        assert_eq!(
+            aln.pretty_with(x, y, &options),
+            "x AC\x1b[33mG\x1b[0mT\x1b[33m-\x1b[0m\x1b[31mA\x1b[0mC\n  ||+|x\\|\n\
+             y AC\x1b[33m-\x1b[0mT\x1b[33mG\x1b[0m\x1b[31mG\x1b[0mC\n\n"
+        );
+
+        let options = PrettyOptions::default().width(4).fasta(true);
+        assert_eq!(
+            aln.pretty_with(x, y, &options),
+            ">x\nACGT\n-AC\n>y\nAC-T\nGGC\n"
+        );
+        assert_eq!(
+            Alignment::default().pretty_with(b"", b"", &options),
+            ">x\n>y\n"
+        );
+    }
+
+    #[test]
+    fn test_pretty_with_unaligned() {
+        // unaligned flanks of a local alignment are shown without gaps, but counted
+        let aln = Alignment {
+            score: 0,
+            xstart: 2,
+            ystart: 0,
+            xend: 4,
+            yend: 2,
+            xlen: 4,
+            ylen: 4,
+            operations: vec![Match, Match],
+            mode: AlignmentMode::Local,
+        };
+        let options = PrettyOptions::default().width(3).coordinates(true);
+        assert_eq!(
+            aln.pretty_with(b"TTAC", b"ACGG", &options),
+            "x 1 TTA 3\n      |\ny 1   A 1\n\nx 4 C   4\n    |\ny 2 CGG 4\n\n"
+        );
+    }
+
+    #[test]
+    fn test_render_empty() {
+        let aln = Alignment::default();
+        assert_eq!(AlignmentView::new(&aln, b"", b"").render(), "");
+    }
+}
@@ -0,0 +1,653 @@
+//! Conversion between `Alignment` and SAM record fields.
+//!
+//! This allows to reuse the methods of `Alignment` on externally produced alignments, and to
+//! write alignments computed with this crate as SAM records.
+//! Following the SAM conventions, `x` is the query (read) and `y` is the reference. Positions
+//! are 1-based as in the SAM POS field.
+//!
+//! # Example
+//!
+//! ```
+//! use bio::alignment::sam::{alignment_from_sam, alignment_to_sam};
+//! use bio::alignment::AlignmentOperation::*;
+//!
+//! // read aligned at position 3 of a reference of length 20, with a mismatch recorded in MD
+//! let alignment = alignment_from_sam(3, "2S4M1I2M", Some("2T3"), 20).unwrap();
+//! assert_eq!(alignment.ystart, 2);
+//! assert_eq!(alignment.xstart, 2);
+//! assert_eq!(alignment.xlen, 9);
+//! assert_eq!(
+//!     alignment.operations,
+//!     [Yclip(2), Xclip(2), Match, Match, Subst, Match, Ins, Match, Match, Yclip(12)]
+//! );
+//!
+//! let fields = alignment_to_sam(&alignment);
+//! assert_eq!(fields.pos, 3);
+//! assert_eq!(fields.cigar, "2S2=1X1=1I2=");
+//! ```
+
+use thiserror::Error;
+
+use crate::alignment::{Alignment, AlignmentMode, AlignmentOperation};
+
+#[derive(Error, Debug, PartialEq, Eq)]
+pub enum Error {
+    #[error("invalid CIGAR string: {cigar}")]
+    InvalidCigar { cigar: String },
+    #[error("invalid MD tag: {md}")]
+    InvalidMd { md: String },
+    #[error("MD tag does not match CIGAR string: {reason}")]
+    MdMismatch { reason: &'static str },
+    #[error("alignment exceeds reference of length {ylen}")]
+    ReferenceTooShort { ylen: usize },
+    #[error("SAM positions are 1-based, got 0")]
+    ZeroPosition,
+}
+
+pub type Result<T, E = Error> = std::result::Result<T, E>;
+
+/// The alignment specific fields of a SAM record.
+#[derive(Default, Clone, Eq, PartialEq, Ord, PartialOrd, Hash, Debug, Serialize, Deserialize)]
+pub struct SamFields {
+    /// 1-based leftmost reference position of the first aligned query base (POS).
+    pub pos: usize,
+    /// The CIGAR string, using `=` and `X` for matches and mismatches and `S` for clipped
+    /// query bases.
+    pub cigar: String,
+}
+
+/// State of a reference position covered by an MD tag.
+#[derive(Copy, Clone, Eq, PartialEq, Debug)]
+enum MdState {
+    Match,
+    Mismatch,
+    Deleted,
+}
+
+/// Expand an MD tag into the states of the covered reference positions.
+fn parse_md(md: &str) -> Result<Vec<MdState>> {
+    let invalid = || Error::InvalidMd { md: md.to_owned() };
+    let mut states = Vec::new();
+    let mut num = None;
+    let mut deletion = false;
+    for c in md.bytes() {
+        match c {
+            b'0'..=b'9' => {
+                deletion = false;
+                num = Some(num.unwrap_or(0) * 10 + (c - b'0') as usize);
+            }
+            b'^' => {
+                let n = num.take().ok_or_else(invalid)?;
+                states.extend(std::iter::repeat(MdState::Match).take(n));
+                deletion = true;
+            }
+            c if c.is_ascii_alphabetic() => {
+                if let Some(n) = num.take() {
+                    states.extend(std::iter::repeat(MdState::Match).take(n));
+                } else if !deletion {
+                    return Err(invalid());
+                }
+                states.push(if deletion {
+                    MdState::Deleted
+                } else {
+                    MdState::Mismatch
+                });
+            }
+            _ => return Err(invalid()),
+        }
+    }
+    let n = num.ok_or_else(invalid)?;
+    states.extend(std::iter::repeat(MdState::Match).take(n));
+
+    Ok(states)
+}
+
+/// Parse a CIGAR string into pairs of length and operation symbol.
+fn parse_cigar(cigar: &str) -> Result<Vec<(usize, u8)>> {
+    let invalid = || Error::InvalidCigar {
+        cigar: cigar.to_owned(),
+    };
+    let mut ops = Vec::new();
+    let mut num: Option<usize> = None;
+    for c in cigar.bytes() {
+        match c {
+            b'0'..=b'9' => num = Some(num.unwrap_or(0) * 10 + (c - b'0') as usize),
+            b'M' | b'I' | b'D' | b'N' | b'S' | b'H' | b'P' | b'=' | b'X' => {
+                ops.push((num.take().ok_or_else(invalid)?, c))
+            }
+            _ => return Err(invalid()),
+        }
+    }
+    if num.is_some() {
+        return Err(invalid());
+    }
+
+    Ok(ops)
+}
+
+/// Build an `Alignment` from the POS and CIGAR fields of a SAM record.
+/// The resulting alignment is in `AlignmentMode::Custom`, with soft clipped query bases and
+/// unaligned reference prefix and suffix represented as `Xclip` and `Yclip` operations.
+/// Hard clips and padding are ignored, and skipped reference regions (`N`) are represented
+/// as deletions.
+///
+/// Since `M` does not distinguish matches from mismatches, the optional MD tag is used to
+/// recover mismatches. Without MD tag, `M` is interpreted as a match.
+///
+/// # Arguments
+///
+/// * `pos` - the 1-based POS field
+/// * `cigar` - the CIGAR string
+/// * `md` - the optional MD tag
+/// * `ylen` - the length of the reference sequence
+pub fn alignment_from_sam(
+    pos: usize,
+    cigar: &str,
+    md: Option<&str>,
+    ylen: usize,
+) -> Result<Alignment> {
+    if pos == 0 {
+        return Err(Error::ZeroPosition);
+    }
+    build_alignment(cigar, md, pos - 1, ylen, false)
+}
+
+/// Build an `Alignment` from a CIGAR string, e.g. as obtained from `cigar`.
+/// In contrast to `alignment_from_sam`, the reference start is 0-based and hard clips are
+/// treated like soft clips, i.e. they are part of the query. Otherwise, the same conventions
+/// apply: the resulting alignment is in `AlignmentMode::Custom`, and `M` is interpreted as a
+/// match.
+///
+/// # Arguments
+///
+/// * `cigar` - the CIGAR string
+/// * `ystart` - the 0-based reference position of the first aligned base
+/// * `ylen` - the length of the reference sequence
+///
+/// # Example
+///
+/// ```
+/// use bio::alignment::sam::{alignment_from_cigar, cigar};
+/// use bio::alignment::AlignmentOperation::*;
+///
+/// let alignment = alignment_from_cigar("2H3=1X2D2=", 1, 10).unwrap();
+/// assert_eq!(alignment.xstart, 2);
+/// assert_eq!((alignment.xend, alignment.yend), (8, 9));
+/// assert_eq!(
+///     alignment.operations,
+///     [Yclip(1), Xclip(2), Match, Match, Match, Subst, Del, Del, Match, Match, Yclip(1)]
+/// );
+/// assert_eq!(cigar(&alignment, true), "2H3=1X2D2=");
+/// ```
+pub fn alignment_from_cigar(cigar: &str, ystart: usize, ylen: usize) -> Result<Alignment> {
+    build_alignment(cigar, None, ystart, ylen, true)
+}
+
+/// Build an `Alignment` from a CIGAR string and optional MD tag, starting at the 0-based
+/// reference position `ystart`. If `hard_clips` is true, hard clipped bases are considered
+/// part of the query.
+fn build_alignment(
+    cigar: &str,
+    md: Option<&str>,
+    ystart: usize,
+    ylen: usize,
+    hard_clips: bool,
+) -> Result<Alignment> {
+    let cigar = parse_cigar(cigar)?;
+    let md = md.map(parse_md).transpose()?;
+    let mut md_states = md.as_ref().map(|states| states.iter());
+    let mut next_md = || -> Result<Option<MdState>> {
+        match md_states.as_mut() {
+            Some(states) => states.next().copied().map(Some).ok_or(Error::MdMismatch {
+                reason: "MD tag is shorter than the aligned reference",
+            }),
+            None => Ok(None),
+        }
+    };
+
+    let (mut x, mut y) = (0, ystart);
+    let mut xstart = None;
+    let mut operations = Vec::new();
+    let mut clip_suffix = 0;
+    for (len, op) in cigar {
+        match op {
+            b'S' | b'H' if op == b'S' || hard_clips => {
+                if xstart.is_none() {
+                    operations.push(AlignmentOperation::Xclip(len));
+                } else {
+                    clip_suffix += len;
+                }
+                x += len;
+                continue;
+            }
+            b'H' | b'P' => continue,
+            _ => {}
+        }
+        if xstart.is_none() {
+            xstart = Some(x);
+        }
+        for _ in 0..len {
+            let operation = match op {
+                b'M' | b'=' | b'X' => {
+                    let operation = match next_md()? {
+                        Some(MdState::Deleted) => {
+                            return Err(Error::MdMismatch {
+                                reason: "MD tag reports a deletion at an aligned base",
+                            })
+                        }
+                        Some(MdState::Mismatch) => AlignmentOperation::Subst,
+                        Some(MdState::Match) if op != b'X' => AlignmentOperation::Match,
+                        None if op != b'X' => AlignmentOperation::Match,
+                        _ => AlignmentOperation::Subst,
+                    };
+                    x += 1;
+                    y += 1;
+                    operation
+                }
+                b'I' => {
+                    x += 1;
+                    AlignmentOperation::Ins
+                }
+                b'D' => {
+                    if let Some(state) = next_md()? {
+                        if state != MdState::Deleted {
+                            return Err(Error::MdMismatch {
+                                reason: "deletion is not reported in MD tag",
+                            });
+                        }
+                    }
+                    y += 1;
+                    AlignmentOperation::Del
+                }
+                // skipped region
+                _ => {
+                    y += 1;
+                    AlignmentOperation::Del
+                }
+            };
+            operations.push(operation);
+        }
+    }
+    if md_states.map_or(false, |mut states| states.next().is_some()) {
+        return Err(Error::MdMismatch {
+            reason: "MD tag is longer than the aligned reference",
+        });
+    }
+    if y > ylen {
+        return Err(Error::ReferenceTooShort { ylen });
+    }
+
+    let xstart = xstart.unwrap_or(x);
+    if ystart > 0 {
+        operations.insert(0, AlignmentOperation::Yclip(ystart));
+    }
+    if clip_suffix > 0 {
+        operations.push(AlignmentOperation::Xclip(clip_suffix));
+    }
+    if ylen > y {
+        operations.push(AlignmentOperation::Yclip(ylen - y));
+    }
+
+    Ok(Alignment {
+        score: 0,
+        xstart,
+        ystart,
+        xend: x - clip_suffix,
+        yend: y,
+        xlen: x,
+        ylen,
+        operations,
+        mode: AlignmentMode::Custom,
+    })
+}
+
+/// Obtain the POS and CIGAR fields of a SAM record from an `Alignment`.
+/// Unaligned query prefix and suffix are reported as soft clips. Leading and trailing
+/// deletions are not representable in SAM and are hence omitted, shifting POS accordingly.
+pub fn alignment_to_sam(alignment: &Alignment) -> SamFields {
+    let ops: Vec<AlignmentOperation> = alignment
+        .operations
+        .iter()
+        .copied()
+        .filter(|op| {
+            !matches!(
+                op,
+                AlignmentOperation::Xclip(_) | AlignmentOperation::Yclip(_)
+            )
+        })
+        .collect();
+    let leading_dels = ops
+        .iter()
+        .take_while(|&&op| op == AlignmentOperation::Del)
+        .count();
+    let trailing_dels = ops[leading_dels..]
+        .iter()
+        .rev()
+        .take_while(|&&op| op == AlignmentOperation::Del)
+        .count();
+    let ops = &ops[leading_dels..ops.len() - trailing_dels];
+
+    SamFields {
+        pos: alignment.ystart + leading_dels + 1,
+        cigar: cigar_string(alignment, ops, 'S'),
+    }
+}
+
+/// Generate a CIGAR string from an `Alignment`, using `=` and `X` for matches and mismatches.
+/// Unaligned query prefix and suffix are reported as soft clips (`S`), or as hard clips
+/// (`H`) if `hard_clip` is true.
+/// Unlike `Alignment::cigar`, this supports alignments of any mode, and retains leading and
+/// trailing deletions (see `alignment_to_sam` for a SAM compatible CIGAR string).
+///
+/// # Example
+///
+/// ```
+/// use bio::alignment::pairwise::Aligner;
+/// use bio::alignment::sam::cigar;
+///
+/// let x = b"ACCGTGGATGGGTC";
+/// let y = b"AAAAACCGTTGATCGGGTC";
+/// let score = |a: u8, b: u8| if a == b { 1i32 } else { -1i32 };
+/// let mut aligner = Aligner::with_capacity(x.len(), y.len(), -5, -1, &score);
+/// let alignment = aligner.global(x, y);
+/// assert_eq!(cigar(&alignment, false), "4D5=1X3=1D5=");
+/// let alignment = aligner.local(x, y);
+/// assert_eq!(cigar(&alignment, true), "5=1X3=1X2=2H");
+/// ```
+pub fn cigar(alignment: &Alignment, hard_clip: bool) -> String {
+    let ops: Vec<AlignmentOperation> = alignment
+        .operations
+        .iter()
+        .copied()
+        .filter(|op| {
+            !matches!(
+                op,
+                AlignmentOperation::Xclip(_) | AlignmentOperation::Yclip(_)
+            )
+        })
+        .collect();
+
+    cigar_string(alignment, &ops, if hard_clip { 'H' } else { 'S' })
+}
+
+/// Generate a CIGAR string from the given non-clip operations, clipping the unaligned query
+/// prefix and suffix of the alignment with `clip_symbol`.
+fn cigar_string(alignment: &Alignment, ops: &[AlignmentOperation], clip_symbol: char) -> String {
+    let mut cigar = String::new();
+    if alignment.xstart > 0 {
+        cigar.push_str(&format!("{}{}", alignment.xstart, clip_symbol));
+    }
+    let mut i = 0;
+    while i < ops.len() {
+        let len = ops[i..].iter().take_while(|&&op| op == ops[i]).count();
+        let symbol = match ops[i] {
+            AlignmentOperation::Match => '=',
+            AlignmentOperation::Subst => 'X',
+            AlignmentOperation::Ins => 'I',
+            AlignmentOperation::Del => 'D',
+            AlignmentOperation::Xclip(_) | AlignmentOperation::Yclip(_) => unreachable!(),
+        };
+        cigar.push_str(&format!("{}{}", len, symbol));
+        i += len;
+    }
+    if alignment.xlen > alignment.xend {
+        cigar.push_str(&format!(
+            "{}{}",
+            alignment.xlen - alignment.xend,
+            clip_symbol
+        ));
+    }
+
+    cigar
+}
+
+/// Generate the SAM MD tag and NM value (edit distance) of an `Alignment` against the
+/// reference `y` it was computed with. The MD tag reports mismatching and deleted reference
+/// bases, while NM counts substitutions, inserted and deleted bases. Clip operations are
+/// ignored.
+///
+/// # Example
+///
+/// ```
+/// use bio::alignment::pairwise::Aligner;
+/// use bio::alignment::sam::md_tag;
+///
+/// let x = b"ACCGTGGATGGGTC";
+/// let y = b"AAAAACCGTTGATCGGGTC";
+/// let score = |a: u8, b: u8| if a == b { 1i32 } else { -1i32 };
+/// let mut aligner = Aligner::with_capacity(x.len(), y.len(), -5, -1, &score);
+/// let alignment = aligner.semiglobal(x, y);
+/// assert_eq!(alignment.cigar(false), "5=1X3=1D5=");
+/// assert_eq!(md_tag(&alignment, y), ("5T3^C5".to_owned(), 2));
+/// ```
+pub fn md_tag(alignment: &Alignment, y: &[u8]) -> (String, u32) {
+    let mut md = String::new();
+    let mut nm = 0;
+    let mut matches = 0;
+    let mut in_deletion = false;
+    let mut j = alignment.ystart;
+    for &op in &alignment.operations {
+        match op {
+            AlignmentOperation::Match => {
+                matches += 1;
+                in_deletion = false;
+                j += 1;
+            }
+            AlignmentOperation::Subst => {
+                md.push_str(&matches.to_string());
+                md.push(y[j].to_ascii_uppercase() as char);
+                matches = 0;
+                in_deletion = false;
+                nm += 1;
+                j += 1;
+            }
+            AlignmentOperation::Del => {
+                if !in_deletion {
+                    md.push_str(&matches.to_string());
+                    md.push('^');
+                    matches = 0;
+                    in_deletion = true;
+                }
+                md.push(y[j].to_ascii_uppercase() as char);
+                nm += 1;
+                j += 1;
+            }
+            AlignmentOperation::Ins => nm += 1,
+            AlignmentOperation::Xclip(_) | AlignmentOperation::Yclip(_) => {}
+        }
+    }
+    md.push_str(&matches.to_string());
+
+    (md, nm)
+}
+
+/// Format a minimal SAM record (tab-separated, without trailing newline) of the alignment of
+/// the query `x` named `qname` against the reference named `rname`. POS and CIGAR are
+/// obtained with `alignment_to_sam`, SEQ is the whole query. MAPQ is reported as unavailable
+/// (255), the mate fields as absent and QUAL as `*`. Alignments without any aligned query
+/// base are reported as unmapped (FLAG 4).
+///
+/// # Example
+///
+/// ```
+/// use bio::alignment::pairwise::Aligner;
+/// use bio::alignment::sam::sam_record;
+///
+/// let x = b"TTACCGTGGA";
+/// let y = b"AAAAACCGTTGATCG";
+/// let score = |a: u8, b: u8| if a == b { 1i32 } else { -1i32 };
+/// let mut aligner = Aligner::new(-5, -1, score);
+/// let alignment = aligner.local(x, y);
+/// assert_eq!(
+///     sam_record(&alignment, "read1", "chr1", x),
+///     "read1\t0\tchr1\t5\t255\t2S5=1X2=\t*\t0\t0\tTTACCGTGGA\t*"
+/// );
+/// ```
+pub fn sam_record(alignment: &Alignment, qname: &str, rname: &str, x: &[u8]) -> String {
+    let mapped = alignment.operations.iter().any(|op| {
+        matches!(
+            op,
+            AlignmentOperation::Match | AlignmentOperation::Subst | AlignmentOperation::Ins
+        )
+    });
+    let (flag, rname, fields) = if mapped {
+        (0, rname, alignment_to_sam(alignment))
+    } else {
+        (
+            4,
+            "*",
+            SamFields {
+                pos: 0,
+                cigar: "*".to_owned(),
+            },
+        )
+    };
+    let seq = if x.is_empty() {
+        "*".into()
+    } else {
+        String::from_utf8_lossy(x)
+    };
+    format!(
+        "{}\t{}\t{}\t{}\t255\t{}\t*\t0\t0\t{}\t*",
+        qname, flag, rname, fields.pos, fields.cigar, seq
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::alignment::pairwise::Aligner;
+    use crate::alignment::AlignmentOperation::*;
+
+    #[test]
+    fn test_parse_md() {
+        use MdState::*;
+        assert_eq!(
+            parse_md("2T0^AC1").unwrap(),
+            vec![Match, Match, Mismatch, Deleted, Deleted, Match]
+        );
+        assert_eq!(parse_md("0A0").unwrap(), vec![Mismatch]);
+        assert!(parse_md("2T").is_err());
+        assert!(parse_md("AT3").is_err());
+        assert!(parse_md("3+").is_err());
+    }
+
+    #[test]
+    fn test_from_sam_deletion() {
+        let alignment = alignment_from_sam(1, "3M2D2M", Some("1G1^CA2"), 7).unwrap();
+        assert_eq!(
+            alignment.operations,
+            [Match, Subst, Match, Del, Del, Match, Match]
+        );
+        assert_eq!((alignment.xend, alignment.yend), (5, 7));
+        assert_eq!(alignment_to_sam(&alignment).cigar, "1=1X1=2D2=");
+    }
+
+    #[test]
+    fn test_from_sam_errors() {
+        assert_eq!(
+            alignment_from_sam(1, "3Q", None, 10),
+            Err(Error::InvalidCigar {
+                cigar: "3Q".to_owned()
+            })
+        );
+        assert!(matches!(
+            alignment_from_sam(1, "3M", Some("2"), 10),
+            Err(Error::MdMismatch { .. })
+        ));
+        assert!(matches!(
+            alignment_from_sam(1, "3M", Some("4"), 10),
+            Err(Error::MdMismatch { .. })
+        ));
+        assert!(matches!(
+            alignment_from_sam(1, "2M1D", Some("3"), 10),
+            Err(Error::MdMismatch { .. })
+        ));
+        assert_eq!(
+            alignment_from_sam(9, "3M", None, 10),
+            Err(Error::ReferenceTooShort { ylen: 10 })
+        );
+        assert_eq!(
+            alignment_from_sam(0, "3M", None, 10),
+            Err(Error::ZeroPosition)
+        );
+    }
+
+    #[test]
+    fn test_md_tag_roundtrip() {
+        let y = b"GGACGTTACGATTACAGG";
+        for (pos, cigar, md, nm) in [
+            (3, "3M2D2M", "1C1^TT2", 3),
+            (1, "2S4M1I2M", "0G0G2G1", 4),
+            (5, "2M1D1X1M", "2^T0A1", 2),
+        ] {
+            let alignment = alignment_from_sam(pos, cigar, Some(md), y.len()).unwrap();
+            assert_eq!(md_tag(&alignment, y), (md.to_owned(), nm));
+        }
+    }
+
+    #[test]
+    fn test_roundtrip_semiglobal() {
+        let x = b"ACCGTGGATGGG";
+        let y = b"AAAAACCGTTGATGGGAAA";
+        let score = |a: u8, b: u8| if a == b { 1i32 } else { -1i32 };
+        let mut aligner = Aligner::with_capacity(x.len(), y.len(), -5, -1, &score);
+        let alignment = aligner.semiglobal(x, y);
+
+        let fields = alignment_to_sam(&alignment);
+        assert_eq!(fields.pos, 5);
+        assert_eq!(fields.cigar, alignment.cigar(false));
+
+        let parsed = alignment_from_sam(fields.pos, &fields.cigar, None, y.len()).unwrap();
+        assert_eq!(parsed.ystart, alignment.ystart);
+        assert_eq!(parsed.yend, alignment.yend);
+        assert_eq!(parsed.xend, alignment.xend);
+        let aligned = |aln: &Alignment| {
+            aln.path()
+                .into_iter()
+                .filter(|&(_, _, op)| !matches!(op, Xclip(_) | Yclip(_)))
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(aligned(&parsed), aligned(&alignment));
+    }
+
+    #[test]
+    fn test_cigar_roundtrip_all_modes() {
+        let x = b"GGACCGTGGATGGGTCAA";
+        let y = b"AAAAACCGTTGATCGGGTC";
+        let score = |a: u8, b: u8| if a == b { 1i32 } else { -1i32 };
+        let mut aligner = Aligner::with_capacity(x.len(), y.len(), -5, -1, &score);
+        for alignment in [
+            aligner.global(x, y),
+            aligner.semiglobal(x, y),
+            aligner.local(x, y),
+        ] {
+            for hard_clip in [false, true] {
+                let cigar = cigar(&alignment, hard_clip);
+                let parsed = alignment_from_cigar(&cigar, alignment.ystart, y.len()).unwrap();
+                assert_eq!(parsed.xstart, alignment.xstart);
+                assert_eq!(parsed.xend, alignment.xend);
+                assert_eq!(parsed.xlen, alignment.xlen);
+                assert_eq!(parsed.yend, alignment.yend);
+                assert_eq!(super::cigar(&parsed, hard_clip), cigar);
+            }
+        }
+    }
+
+    #[test]
+    fn test_from_cigar_clips() {
+        let alignment = alignment_from_cigar("2D3M2S", 0, 5).unwrap();
+        assert_eq!(
+            alignment.operations,
+            [Del, Del, Match, Match, Match, Xclip(2)]
+        );
+        assert_eq!(cigar(&alignment, false), "2D3=2S");
+        // alignment_from_sam ignores hard clips, alignment_from_cigar does not
+        assert_eq!(alignment_from_sam(1, "3H2M", None, 5).unwrap().xlen, 2);
+        assert_eq!(alignment_from_cigar("3H2M", 0, 5).unwrap().xlen, 5);
+        assert_eq!(
+            alignment_from_cigar("2M", 4, 5),
+            Err(Error::ReferenceTooShort { ylen: 5 })
+        );
+    }
+}
@@ -46,6 +46,93 @@ pub struct SparseAlignmentResult {
     pub dp_vector: Vec<(u32, i32)>,
 }
 
+/// A chain of k-mer matches, as extracted by `SparseAlignmentResult::chains`.
+#[derive(Default, Clone, Eq, PartialEq, Ord, PartialOrd, Hash, Debug, Serialize, Deserialize)]
+pub struct SparseChain {
+    /// Indices into the input matches vector, in chaining order.
+    pub path: Vec<usize>,
+    /// Score of the chain.
+    pub score: u32,
+}
+
+impl SparseAlignmentResult {
+    /// Number of matches the DP was computed for.
+    pub fn num_matches(&self) -> usize {
+        self.dp_vector.len() / 2
+    }
+
+    /// Best score of any chain ending in the given match.
+    pub fn chain_score(&self, idx: usize) -> u32 {
+        self.dp_vector[idx].0
+    }
+
+    /// Predecessor of the given match in the best chain ending in it, if any.
+    pub fn predecessor(&self, idx: usize) -> Option<usize> {
+        let pred = self.dp_vector[idx].1;
+        if pred >= 0 {
+            Some(pred as usize)
+        } else {
+            None
+        }
+    }
+
+    /// Extract non-overlapping chains (e.g. a primary and secondary mappings) from the DP
+    /// without recomputation. Chains are extracted greedily in the order of their end scores:
+    /// each chain is traced back until it reaches a match that is already used by a
+    /// higher-scoring chain. The score of such a truncated chain is reduced by the score of
+    /// the shared prefix. Only chains with a score of at least `min_score` are reported,
+    /// sorted by decreasing score.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use bio::alignment::sparse::*;
+    ///
+    /// // y contains two copies of x, the second one with a mismatch
+    /// let x = b"ACGTACGATAGGTACCA";
+    /// let y = b"ACGTACGATAGGTACCATTTTTTTTTTTTTTTTTTACGTACGATTGGTACCA";
+    /// let k = 5;
+    /// let matches = find_kmer_matches(x, y, k);
+    /// let res = sdpkpp(&matches, k, 1, -1, -1);
+    /// let chains = res.chains(10);
+    /// assert_eq!(chains.len(), 2);
+    /// assert_eq!(chains[0].path, res.path);
+    /// assert_eq!(matches[chains[1].path[0]], (0, 35));
+    /// ```
+    pub fn chains(&self, min_score: u32) -> Vec<SparseChain> {
+        let n = self.num_matches();
+        let mut order: Vec<usize> = (0..n).collect();
+        order.sort_unstable_by_key(|&i| std::cmp::Reverse((self.dp_vector[i].0, i)));
+
+        let mut used = vec![false; n];
+        let mut chains = Vec::new();
+        for end in order {
+            if used[end] {
+                continue;
+            }
+            let mut path = Vec::new();
+            let mut curr = Some(end);
+            while let Some(i) = curr {
+                if used[i] {
+                    break;
+                }
+                used[i] = true;
+                path.push(i);
+                curr = self.predecessor(i);
+            }
+            let prefix_score = curr.map_or(0, |i| self.chain_score(i));
+            let score = self.chain_score(end).saturating_sub(prefix_score);
+            if score >= min_score {
+                path.reverse();
+                chains.push(SparseChain { path, score });
+            }
+        }
+        chains.sort_by_key(|chain| std::cmp::Reverse(chain.score));
+
+        chains
+    }
+}
+
 /// Sparse DP routine for Longest Common Subsequence in length k substrings.  Also known of LCSk++
 /// From LCSk++: Practical similarity metric for long strings. Filip Pavetić, Goran Žužić, Mile Šikić
 /// Paper here :https://arxiv.org/abs/1407.2407.  Original implementation here:
@@ -321,6 +408,117 @@ pub fn sdpkpp_union_lcskpp_path(
     path_union
 }
 
+/// Chain k-mer matches with a user-supplied gap cost, in the style of minimap2. Each match
+/// covers `k` bases, and overlapping matches only contribute the bases not covered by their
+/// predecessor in the chain, i.e. a chain of matches scores the number of covered bases minus
+/// the costs of the gaps between consecutive matches. The best chain ending in match `i` is
+/// obtained by extending the best chain ending in a predecessor match `j` with
+/// `x_j < x_i` and `y_j < y_i`, where only the `max_lookback` preceding matches (in sorted
+/// order) with a distance of at most `max_gap` in both strings are considered. This keeps the
+/// computation linear in the number of matches for long reads.
+/// Complexity: O(n * max_lookback) for n matches.
+///
+/// # Arguments
+///
+/// * `matches` - a sorted vector of tuples indicating the (string1 position, string2 position)
+///   kmer matches between the strings
+/// * `k` - the kmer length used for matching
+/// * `max_gap` - maximum distance between consecutive matches of a chain in both strings
+/// * `max_lookback` - maximum number of preceding matches to consider as predecessors
+/// * `gap_cost` - cost of chaining two matches with the given distances `(dx, dy)` of their
+///   start positions in string1 and string2. Their difference `|dx - dy|` is the number of
+///   inserted or deleted bases.
+///
+/// # Return value
+///
+/// The method returns a `SparseAlignmentResult` with the best chain as `path`, the DP
+/// vector having the same layout as for `sdpkpp`, such that secondary chains can be extracted
+/// with `SparseAlignmentResult::chains`.
+///
+/// # Example
+///
+/// ```
+/// use bio::alignment::sparse::*;
+///
+/// let x = b"ACGTACGATAGGTACCAGGTTCA";
+/// // y contains x with a deletion of 3 bases
+/// let y = b"GGACGTACGATAGGTAGGTTCAGG";
+/// let k = 5;
+/// let matches = find_kmer_matches(x, y, k);
+/// // linear costs for indels and a small cost for the distance (like minimap2)
+/// let gap_cost = |dx: u32, dy: u32| {
+///     let l = (dx as i64 - dy as i64).unsigned_abs() as f64;
+///     (l + 0.5 * (l + 1.0).log2()).ceil() as u32
+/// };
+/// let res = chain(&matches, k, 1000, 50, gap_cost);
+/// // 14 + 6 covered bases, minus 3 + 1 for the deletion
+/// assert_eq!(res.score, 16);
+/// assert_eq!(matches[res.path[0]], (0, 2));
+/// ```
+pub fn chain<G>(
+    matches: &[(u32, u32)],
+    k: usize,
+    max_gap: u32,
+    max_lookback: usize,
+    gap_cost: G,
+) -> SparseAlignmentResult
+where
+    G: Fn(u32, u32) -> u32,
+{
+    if matches.is_empty() {
+        return SparseAlignmentResult {
+            path: Vec::new(),
+            score: 0,
+            dp_vector: Vec::new(),
+        };
+    }
+
+    let k = k as u32;
+
+    // incoming matches must be sorted to restrict the predecessors to the preceding matches.
+    for i in 1..matches.len() {
+        assert!(matches[i - 1] < matches[i]);
+    }
+
+    // same layout as for sdpkpp, with the scores and predecessors in the first half
+    let mut dp: Vec<(u32, i32)> = vec![(0, -1); 2 * matches.len()];
+    let mut best_dp = (k, 0);
+
+    for (i, &(x, y)) in matches.iter().enumerate() {
+        dp[i] = (k, -1);
+        for j in (i.saturating_sub(max_lookback)..i).rev() {
+            let (prev_x, prev_y) = matches[j];
+            let dx = x - prev_x;
+            if dx > max_gap {
+                // matches are sorted by string1 position
+                break;
+            }
+            if dx == 0 || prev_y >= y || y - prev_y > max_gap {
+                continue;
+            }
+            let dy = y - prev_y;
+            // only count the bases not covered by the predecessor
+            let covered = min(min(dx, dy), k);
+            let score = (dp[j].0 + covered).saturating_sub(gap_cost(dx, dy));
+            dp[i] = max(dp[i], (score, j as i32));
+        }
+        best_dp = max(best_dp, (dp[i].0, i as i32));
+    }
+
+    let mut traceback = Vec::new();
+    let (best_score, mut prev_match) = best_dp;
+    while prev_match >= 0 {
+        traceback.push(prev_match as usize);
+        prev_match = dp[prev_match as usize].1;
+    }
+    traceback.reverse();
+    SparseAlignmentResult {
+        path: traceback,
+        score: best_score,
+        dp_vector: dp,
+    }
+}
+
 /// Find all matches of length k between two strings, using a q-gram
 /// index. For very long reference strings, it may be more efficient to use and
 /// FMD index to generate the matches. Note that this method is mainly for
@@ -376,6 +574,19 @@ pub fn find_kmer_matches_seq2_hashed(
     seq1: &[u8],
     seq2_set: &HashMapFx<&[u8], Vec<u32>>,
     k: usize,
+) -> Vec<(u32, u32)> {
+    find_kmer_matches_seq2_hashed_max_occ(seq1, seq2_set, k, usize::MAX)
+}
+
+/// Find all matches of length k between two strings where the second string is
+/// already hashed by using the function sparse::hash_kmers, ignoring k-mers that occur
+/// more than `max_occ` times in the second string. This avoids an explosion of the
+/// number of matches in repetitive regions.
+pub fn find_kmer_matches_seq2_hashed_max_occ(
+    seq1: &[u8],
+    seq2_set: &HashMapFx<&[u8], Vec<u32>>,
+    k: usize,
+    max_occ: usize,
 ) -> Vec<(u32, u32)> {
     let mut matches = Vec::new();
 
@@ -383,6 +594,9 @@ pub fn find_kmer_matches_seq2_hashed(
         let slc = &seq1[i..i + k];
 
         if let Some(matches1) = seq2_set.get(slc) {
+            if matches1.len() > max_occ {
+                continue;
+            }
             for pos1 in matches1 {
                 matches.push((i as u32, *pos1));
             }
@@ -489,7 +703,10 @@ pub fn expand_kmer_matches(
 
 #[cfg(test)]
 mod sparse_alignment {
-    use super::find_kmer_matches;
+    use super::{
+        find_kmer_matches, find_kmer_matches_seq2_hashed, find_kmer_matches_seq2_hashed_max_occ,
+        hash_kmers, sdpkpp,
+    };
 
     #[test]
     pub fn test_find_kmer_matches() {
@@ -504,6 +721,93 @@ mod sparse_alignment {
         //println!("hits: {:?}", hits);
     }
 
+    #[test]
+    pub fn test_find_kmer_matches_max_occ() {
+        let s1 = b"ACGTACGATAGATCCGTACGTAACAGTACAGTATATCAGTTATATGCGATA";
+        let s2 = b"TTACGTACGATAGATCCGTACGTAACATTTTTAGTACAGTATATCAGTTATATGCGATA";
+        let s2_hash = hash_kmers(s2, 4);
+
+        let all = find_kmer_matches_seq2_hashed(s1, &s2_hash, 4);
+        let unlimited = find_kmer_matches_seq2_hashed_max_occ(s1, &s2_hash, 4, usize::MAX);
+        assert_eq!(all, unlimited);
+
+        let unique = find_kmer_matches_seq2_hashed_max_occ(s1, &s2_hash, 4, 1);
+        assert!(unique.len() < all.len());
+        for &(i, _) in &unique {
+            let kmer = &s1[i as usize..i as usize + 4];
+            assert_eq!(s2_hash[kmer].len(), 1);
+        }
+    }
+
+    #[test]
+    pub fn test_sdpkpp_chains() {
+        let x = b"ACGTACGATAGGTACCAGGTTCA";
+        let mut y = b"GGGG".to_vec();
+        y.extend_from_slice(x);
+        y.extend_from_slice(b"TTTTTTTTTTTTTTTTTTTTTTTTTTT");
+        y.extend_from_slice(&x[..12]);
+        let k = 6;
+        let matches = find_kmer_matches(x, &y, k);
+        let res = sdpkpp(&matches, k, 1, -1, -1);
+
+        let chains = res.chains(0);
+        assert_eq!(chains[0].path, res.path);
+        assert_eq!(chains[0].score, res.score);
+        assert!(chains.windows(2).all(|w| w[0].score >= w[1].score));
+
+        // every match is used by exactly one chain
+        let mut all: Vec<usize> = chains.iter().flat_map(|c| c.path.clone()).collect();
+        all.sort_unstable();
+        assert_eq!(all, (0..matches.len()).collect::<Vec<_>>());
+
+        let secondary = &res.chains(5)[1];
+        assert_eq!(matches[secondary.path[0]], (0, 54));
+        assert_eq!(secondary.score, 12);
+        for w in secondary.path.windows(2) {
+            assert_eq!(res.predecessor(w[1]), Some(w[0]));
+        }
+        assert_eq!(res.num_matches(), matches.len());
+    }
+
+    #[test]
+    pub fn test_chain() {
+        // without gap costs, chaining consecutive matches counts the covered bases like LCSk++
+        let s1 = b"ACGTACGATAGGTA";
+        let s2 = b"TTACGTACGATAGGTATT";
+        let k = 8;
+        let matches = super::find_kmer_matches(s1, s2, k);
+        let res = super::chain(&matches, k, 100, 10, |_, _| 0);
+        assert_eq!(res.path, super::lcskpp(&matches, k).path);
+        assert_eq!(res.score, 14);
+
+        let x = b"ACGTACGATAGGTACCAGGTTCA";
+        let mut y = b"GGGG".to_vec();
+        y.extend_from_slice(x);
+        y.extend_from_slice(b"TTTTTTTTTTTTTTTTTTTTTTTTTTT");
+        y.extend_from_slice(&x[..12]);
+        let k = 6;
+        let matches = super::find_kmer_matches(x, &y, k);
+        let diag = |dx: u32, dy: u32| (dx as i64 - dy as i64).unsigned_abs() as u32;
+        let res = super::chain(&matches, k, 1000, 100, diag);
+        assert_eq!(res.score, x.len() as u32);
+        let chains = res.chains(5);
+        assert_eq!(chains[0].path, res.path);
+        assert_eq!(matches[chains[1].path[0]], (0, 54));
+        assert_eq!(chains[1].score, 12);
+        assert_eq!(res.num_matches(), matches.len());
+
+        // a gap larger than max_gap splits the chain
+        let x = b"ACGTACGATAGGTACCAGGTTCA";
+        let y = b"GGACGTACGATAGGTAGGTTCAGG";
+        let deletion = super::find_kmer_matches(x, y, 5);
+        // (9, 11) -> (16, 15) overlaps by one base in y
+        assert_eq!(super::chain(&deletion, 5, 7, 100, diag).score, 14 + 6 - 3);
+        assert_eq!(super::chain(&deletion, 5, 6, 100, diag).score, 14);
+        // only preceding matches within the lookback are considered
+        let res = super::chain(&matches, k, 1000, 0, diag);
+        assert_eq!(res.score, k as u32);
+    }
+
     #[test]
     pub fn test_lcskpp0() {
         let s1 = b"ACGTACGATAGGTA";
@@ -0,0 +1,267 @@
+//! Summary statistics of alignments, like identity, mismatches and gaps, computed from the
+//! alignment operations. Clip operations are never counted, and all fractions are relative to
+//! the alignment columns, i.e. matches, substitutions, insertions and deletions.
+//!
+//! # Example
+//!
+//! ```
+//! use bio::alignment::pairwise::Aligner;
+//! use bio::alignment::AlignmentStats;
+//!
+//! let x = b"ACCGTGGATGGGCGCCATAG";
+//! let y = b"ACCGTGGATGAGCGCGCCATAG";
+//! let score = |a: u8, b: u8| if a == b { 1i32 } else { -1i32 };
+//! let mut aligner = Aligner::new(-5, -1, score);
+//! let alignment = aligner.global(x, y);
+//! let counts = alignment.operation_counts();
+//! assert_eq!((counts.matches, counts.mismatches), (19, 1));
+//! assert_eq!((counts.deletions, counts.deleted), (1, 2));
+//! assert_eq!(alignment.gap_count(), 1);
+//! assert_eq!(alignment.longest_indel(), 2);
+//! assert_eq!(alignment.identity(), 19.0 / 22.0);
+//! assert_eq!(alignment.gap_compressed_identity(), 19.0 / 21.0);
+//! assert_eq!(alignment.similarity(x, y, score), 19.0 / 22.0);
+//! ```
+
+use crate::alignment::pairwise::MatchFunc;
+use crate::alignment::{Alignment, AlignmentOperation};
+use crate::utils::TextSlice;
+
+/// Numbers of the operations of an alignment.
+#[derive(Default, Copy, Clone, Eq, PartialEq, Hash, Debug, Serialize, Deserialize)]
+pub struct OperationCounts {
+    /// Number of matches.
+    pub matches: usize,
+    /// Number of substitutions.
+    pub mismatches: usize,
+    /// Number of insertions (runs of `Ins` operations).
+    pub insertions: usize,
+    /// Number of inserted symbols of `x`.
+    pub inserted: usize,
+    /// Number of deletions (runs of `Del` operations).
+    pub deletions: usize,
+    /// Number of deleted symbols of `y`.
+    pub deleted: usize,
+    /// Number of clipped symbols of `x`.
+    pub xclipped: usize,
+    /// Number of clipped symbols of `y`.
+    pub yclipped: usize,
+}
+
+impl OperationCounts {
+    /// Number of alignment columns, i.e. of all operations except clips.
+    pub fn columns(&self) -> usize {
+        self.matches + self.mismatches + self.inserted + self.deleted
+    }
+}
+
+/// Summary statistics of alignments.
+pub trait AlignmentStats {
+    /// Count the operations of the alignment.
+    fn operation_counts(&self) -> OperationCounts;
+
+    /// Fraction of the alignment columns that are matches (BLAST identity), or zero if there
+    /// are no alignment columns.
+    fn identity(&self) -> f64 {
+        let counts = self.operation_counts();
+        fraction(counts.matches, counts.columns())
+    }
+
+    /// Fraction of matches when counting each gap as a single column (gap-compressed
+    /// identity, as reported by minimap2), or zero if there are no alignment columns.
+    fn gap_compressed_identity(&self) -> f64 {
+        let counts = self.operation_counts();
+        fraction(
+            counts.matches,
+            counts.matches + counts.mismatches + counts.insertions + counts.deletions,
+        )
+    }
+
+    /// Number of substitutions.
+    fn mismatches(&self) -> usize {
+        self.operation_counts().mismatches
+    }
+
+    /// Number of gaps, i.e. of insertions and deletions, regardless of their length.
+    fn gap_count(&self) -> usize {
+        let counts = self.operation_counts();
+        counts.insertions + counts.deletions
+    }
+
+    /// Length of the longest insertion or deletion, or zero if there is none.
+    fn longest_indel(&self) -> usize;
+
+    /// Fraction of the alignment columns that align symbols of `x` and `y` with a positive
+    /// score according to `match_fn` (similarity), or zero if there are no alignment
+    /// columns.
+    fn similarity<F: MatchFunc>(&self, x: TextSlice<'_>, y: TextSlice<'_>, match_fn: F) -> f64;
+}
+
+impl AlignmentStats for Alignment {
+    fn operation_counts(&self) -> OperationCounts {
+        let mut counts = OperationCounts::default();
+        let mut last = None;
+        for &op in &self.operations {
+            match op {
+                AlignmentOperation::Match => counts.matches += 1,
+                AlignmentOperation::Subst => counts.mismatches += 1,
+                AlignmentOperation::Ins => {
+                    counts.inserted += 1;
+                    if last != Some(op) {
+                        counts.insertions += 1;
+                    }
+                }
+                AlignmentOperation::Del => {
+                    counts.deleted += 1;
+                    if last != Some(op) {
+                        counts.deletions += 1;
+                    }
+                }
+                AlignmentOperation::Xclip(len) => counts.xclipped += len,
+                AlignmentOperation::Yclip(len) => counts.yclipped += len,
+            }
+            last = Some(op);
+        }
+        counts
+    }
+
+    fn longest_indel(&self) -> usize {
+        let mut longest = 0;
+        let mut len = 0;
+        for (i, &op) in self.operations.iter().enumerate() {
+            match op {
+                AlignmentOperation::Ins | AlignmentOperation::Del => {
+                    len = if i > 0 && self.operations[i - 1] == op {
+                        len + 1
+                    } else {
+                        1
+                    };
+                    longest = longest.max(len);
+                }
+                _ => len = 0,
+            }
+        }
+        longest
+    }
+
+    fn similarity<F: MatchFunc>(&self, x: TextSlice<'_>, y: TextSlice<'_>, match_fn: F) -> f64 {
+        let (mut i, mut j) = (self.xstart, self.ystart);
+        let (mut similar, mut columns) = (0, 0);
+        for &op in &self.operations {
+            match op {
+                AlignmentOperation::Match | AlignmentOperation::Subst => {
+                    if match_fn.score(x[i], y[j]) > 0 {
+                        similar += 1;
+                    }
+                    i += 1;
+                    j += 1;
+                }
+                AlignmentOperation::Ins => i += 1,
+                AlignmentOperation::Del => j += 1,
+                AlignmentOperation::Xclip(_) | AlignmentOperation::Yclip(_) => continue,
+            }
+            columns += 1;
+        }
+        fraction(similar, columns)
+    }
+}
+
+fn fraction(count: usize, total: usize) -> f64 {
+    if total == 0 {
+        0.0
+    } else {
+        count as f64 / total as f64
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::alignment::pairwise::Aligner;
+    use crate::alignment::AlignmentMode;
+    use crate::scores::blosum62;
+
+    #[test]
+    fn test_clips() {
+        use AlignmentOperation::*;
+
+        let x = b"GGGACGTTTACT";
+        let y = b"CCCACGAGGTCC";
+        // custom alignments start and end with clips, which must not be counted
+        let alignment = Alignment {
+            score: 0,
+            xstart: 3,
+            xend: 12,
+            xlen: 12,
+            ystart: 3,
+            yend: 10,
+            ylen: 12,
+            operations: vec![
+                Xclip(3),
+                Yclip(3),
+                Match,
+                Match,
+                Match,
+                Ins,
+                Ins,
+                Ins,
+                Match,
+                Del,
+                Subst,
+                Match,
+                Yclip(2),
+            ],
+            mode: AlignmentMode::Custom,
+        };
+        let counts = alignment.operation_counts();
+        assert_eq!(
+            counts,
+            OperationCounts {
+                matches: 5,
+                mismatches: 1,
+                insertions: 1,
+                inserted: 3,
+                deletions: 1,
+                deleted: 1,
+                xclipped: 3,
+                yclipped: 5,
+            }
+        );
+        assert_eq!(counts.columns(), 10);
+        assert_eq!(alignment.identity(), 0.5);
+        assert_eq!(alignment.gap_compressed_identity(), 5.0 / 8.0);
+        assert_eq!(alignment.mismatches(), 1);
+        assert_eq!(alignment.gap_count(), 2);
+        assert_eq!(alignment.longest_indel(), 3);
+        let score = |a: u8, b: u8| if a == b { 1i32 } else { -1i32 };
+        assert_eq!(alignment.similarity(x, y, score), 0.5);
+    }
+
+    #[test]
+    fn test_adjacent_gaps() {
+        use AlignmentOperation::*;
+
+        let mut alignment = Alignment {
+            operations: vec![Match, Ins, Ins, Del, Del, Del, Ins, Match],
+            ..Default::default()
+        };
+        assert_eq!(alignment.gap_count(), 3);
+        assert_eq!(alignment.longest_indel(), 3);
+
+        alignment.operations.clear();
+        assert_eq!(alignment.identity(), 0.0);
+        assert_eq!(alignment.gap_compressed_identity(), 0.0);
+        assert_eq!(alignment.longest_indel(), 0);
+    }
+
+    #[test]
+    fn test_similarity() {
+        let x = b"MKVLAAGIVK";
+        let y = b"MRVIAAGLVK";
+        let mut aligner = Aligner::new(-10, -1, blosum62);
+        let alignment = aligner.global(x, y);
+        assert_eq!(alignment.identity(), 0.7);
+        // K/R, L/I and I/L have positive scores
+        assert_eq!(alignment.similarity(x, y, blosum62), 1.0);
+    }
+}
@@ -0,0 +1,373 @@
+//! Synteny blocks between two genomes, i.e. regions in which the order and orientation of
+//! anchors (e.g. MEMs or minimizer matches) is conserved, and the rearrangement breakpoints
+//! between them.
+//!
+//! Anchors are grouped by the pair of sequences (e.g. chromosomes) they connect and their
+//! strand. Within each group, they are chained by sparse dynamic programming, where an
+//! anchor may follow another one if it starts after it in both genomes (after it in the first
+//! and before it in the second genome for reverse strand anchors), with gaps of at most
+//! `max_gap`. Chains are scored by the number of bases covered by their anchors, minus a
+//! logarithmic penalty for the difference of the gap lengths in both genomes. The best chains
+//! are extracted greedily, and those with enough anchors are reported as blocks.
+//! Consecutive blocks along the first genome are then compared to find breakpoints.
+//!
+//! # Example
+//!
+//! ```
+//! use bio::alignment::synteny::{breakpoints, Anchor, BreakpointKind, SyntenyChainer};
+//! use bio_types::strand::ReqStrand;
+//!
+//! // the second half of sequence 0 is inverted in the other genome
+//! let anchors = (0..10)
+//!     .map(|i| Anchor::new(0, i * 100, 0, i * 100, 20, ReqStrand::Forward))
+//!     .chain((10..20).map(|i| Anchor::new(0, i * 100, 0, 2980 - i * 100, 20, ReqStrand::Reverse)))
+//!     .collect::<Vec<_>>();
+//! let blocks = SyntenyChainer::new(500, 3).blocks(&anchors);
+//! assert_eq!(blocks.len(), 2);
+//! assert_eq!((blocks[0].x.clone(), blocks[0].y.clone()), (0..920, 0..920));
+//! assert_eq!((blocks[1].x.clone(), blocks[1].y.clone()), (1000..1920, 1080..2000));
+//! assert_eq!(blocks[1].strand, ReqStrand::Reverse);
+//!
+//! let breakpoints = breakpoints(&blocks);
+//! assert_eq!(breakpoints.len(), 1);
+//! assert_eq!(breakpoints[0].kind, BreakpointKind::Inversion);
+//! assert_eq!(breakpoints[0].x, 920..1000);
+//! ```
+
+use std::cmp::{max, min};
+use std::collections::HashMap;
+use std::ops::Range;
+
+use bio_types::strand::ReqStrand;
+
+/// An exact or approximate match between two genomes, given by the index of the sequence and
+/// the start position in each genome. Positions are in forward strand coordinates of the
+/// respective sequence.
+#[derive(Copy, Clone, Eq, PartialEq, Ord, PartialOrd, Hash, Debug, Serialize, Deserialize)]
+pub struct Anchor {
+    pub x_seq: usize,
+    pub x: usize,
+    pub y_seq: usize,
+    pub y: usize,
+    pub len: usize,
+    pub strand: ReqStrand,
+}
+
+impl Anchor {
+    /// Create a new anchor.
+    pub fn new(
+        x_seq: usize,
+        x: usize,
+        y_seq: usize,
+        y: usize,
+        len: usize,
+        strand: ReqStrand,
+    ) -> Self {
+        Anchor {
+            x_seq,
+            x,
+            y_seq,
+            y,
+            len,
+            strand,
+        }
+    }
+
+    /// Coordinate in the second genome that increases along chains of the anchor's strand.
+    fn y_key(&self) -> i64 {
+        if self.strand == ReqStrand::Reverse {
+            -((self.y + self.len) as i64)
+        } else {
+            self.y as i64
+        }
+    }
+}
+
+/// A synteny block, given by a pair of intervals in the two genomes.
+#[derive(Clone, Eq, PartialEq, Hash, Debug, Serialize, Deserialize)]
+pub struct SyntenyBlock {
+    pub x_seq: usize,
+    pub x: Range<usize>,
+    pub y_seq: usize,
+    pub y: Range<usize>,
+    pub strand: ReqStrand,
+    /// Number of anchors in the block.
+    pub anchors: usize,
+    pub score: i64,
+}
+
+/// Chaining of anchors into synteny blocks.
+#[derive(Copy, Clone, Eq, PartialEq, Ord, PartialOrd, Hash, Debug, Serialize, Deserialize)]
+pub struct SyntenyChainer {
+    max_gap: usize,
+    min_anchors: usize,
+}
+
+impl SyntenyChainer {
+    /// Create a new chainer, joining anchors with gaps of at most `max_gap` in both genomes,
+    /// and reporting blocks of at least `min_anchors` anchors.
+    pub fn new(max_gap: usize, min_anchors: usize) -> Self {
+        SyntenyChainer {
+            max_gap,
+            min_anchors,
+        }
+    }
+
+    /// Chain the given anchors into synteny blocks, ordered by sequence and start position in
+    /// the first genome. Each anchor is part of at most one block.
+    pub fn blocks(&self, anchors: &[Anchor]) -> Vec<SyntenyBlock> {
+        let mut groups: HashMap<_, Vec<Anchor>> = HashMap::new();
+        for anchor in anchors {
+            let reverse = anchor.strand == ReqStrand::Reverse;
+            groups
+                .entry((anchor.x_seq, anchor.y_seq, reverse))
+                .or_default()
+                .push(*anchor);
+        }
+
+        let mut blocks = Vec::new();
+        for mut group in groups.into_values() {
+            group.sort_unstable_by_key(|anchor| (anchor.x, anchor.y_key()));
+            self.chain(&group, &mut blocks);
+        }
+        // the groups are chained in hash order, hence order by all coordinates
+        blocks.sort_unstable_by_key(|block| {
+            let (x, y) = (&block.x, &block.y);
+            (
+                block.x_seq,
+                x.start,
+                x.end,
+                block.y_seq,
+                y.start,
+                y.end,
+                block.strand,
+            )
+        });
+        blocks
+    }
+
+    /// Chain the anchors of a group (sorted by position in the first genome).
+    fn chain(&self, anchors: &[Anchor], blocks: &mut Vec<SyntenyBlock>) {
+        let max_len = anchors.iter().map(|anchor| anchor.len).max().unwrap_or(0);
+        let mut scores = Vec::with_capacity(anchors.len());
+        let mut preds = Vec::with_capacity(anchors.len());
+        for (j, b) in anchors.iter().enumerate() {
+            let mut best = (b.len as i64, None);
+            for (i, a) in anchors[..j].iter().enumerate().rev() {
+                if b.x - a.x > self.max_gap + max_len {
+                    break;
+                }
+                let (dx, dy) = ((b.x - a.x) as i64, b.y_key() - a.y_key());
+                let max_gap = (self.max_gap + a.len) as i64;
+                if dx <= 0 || dy <= 0 || dx > max_gap || dy > max_gap {
+                    continue;
+                }
+                // bases covered beyond the preceding anchor
+                let gain = min(min(dx, dy), b.len as i64);
+                // floor(log2(|dx - dy| + 1))
+                let cost = (u64::BITS - 1 - ((dx - dy).unsigned_abs() + 1).leading_zeros()) as i64;
+                let score = scores[i] + gain - cost;
+                if score > best.0 {
+                    best = (score, Some(i));
+                }
+            }
+            scores.push(best.0);
+            preds.push(best.1);
+        }
+
+        let mut order = (0..anchors.len()).collect::<Vec<_>>();
+        order.sort_unstable_by_key(|&j| std::cmp::Reverse(scores[j]));
+        let mut used = vec![false; anchors.len()];
+        for end in order {
+            if used[end] {
+                continue;
+            }
+            let mut chain = vec![end];
+            used[end] = true;
+            let mut score = scores[end];
+            while let Some(pred) = preds[*chain.last().unwrap()] {
+                if used[pred] {
+                    // the chain continues into an already reported one
+                    score -= scores[pred];
+                    break;
+                }
+                used[pred] = true;
+                chain.push(pred);
+            }
+            if chain.len() < self.min_anchors {
+                continue;
+            }
+            let first = &anchors[chain[0]];
+            let (mut x, mut y) = (first.x..first.x + first.len, first.y..first.y + first.len);
+            for anchor in chain.iter().map(|&i| &anchors[i]) {
+                x = min(x.start, anchor.x)..max(x.end, anchor.x + anchor.len);
+                y = min(y.start, anchor.y)..max(y.end, anchor.y + anchor.len);
+            }
+            blocks.push(SyntenyBlock {
+                x_seq: first.x_seq,
+                x,
+                y_seq: first.y_seq,
+                y,
+                strand: if first.strand == ReqStrand::Reverse {
+                    ReqStrand::Reverse
+                } else {
+                    ReqStrand::Forward
+                },
+                anchors: chain.len(),
+                score,
+            });
+        }
+    }
+}
+
+/// The kind of rearrangement between two consecutive synteny blocks.
+#[derive(Copy, Clone, Eq, PartialEq, Ord, PartialOrd, Hash, Debug, Serialize, Deserialize)]
+pub enum BreakpointKind {
+    /// The blocks map to different sequences of the second genome.
+    Translocation,
+    /// The blocks map to different strands.
+    Inversion,
+    /// The blocks map to the same sequence and strand, but in different order.
+    Transposition,
+}
+
+/// A breakpoint between two consecutive synteny blocks along the first genome.
+#[derive(Clone, Eq, PartialEq, Hash, Debug, Serialize, Deserialize)]
+pub struct Breakpoint {
+    pub x_seq: usize,
+    /// The interval between the blocks in the first genome (empty if they overlap).
+    pub x: Range<usize>,
+    pub kind: BreakpointKind,
+    /// Indices of the blocks left and right of the breakpoint.
+    pub blocks: (usize, usize),
+}
+
+/// Find the rearrangement breakpoints between consecutive blocks along each sequence of the
+/// first genome, given the blocks as returned by `SyntenyChainer::blocks`. Consecutive blocks
+/// that map to the same sequence and strand in collinear order (e.g. because they are
+/// separated by a large insertion) do not form a breakpoint.
+pub fn breakpoints(blocks: &[SyntenyBlock]) -> Vec<Breakpoint> {
+    let mut breakpoints = Vec::new();
+    for (i, pair) in blocks.windows(2).enumerate() {
+        let (left, right) = (&pair[0], &pair[1]);
+        if left.x_seq != right.x_seq {
+            continue;
+        }
+        let kind = if left.y_seq != right.y_seq {
+            BreakpointKind::Translocation
+        } else if left.strand != right.strand {
+            BreakpointKind::Inversion
+        } else if (left.strand == ReqStrand::Reverse && right.y.end > left.y.start)
+            || (left.strand != ReqStrand::Reverse && right.y.start < left.y.end)
+        {
+            BreakpointKind::Transposition
+        } else {
+            continue;
+        };
+        let start = min(left.x.end, right.x.start);
+        breakpoints.push(Breakpoint {
+            x_seq: left.x_seq,
+            x: start..max(start, right.x.start),
+            kind,
+            blocks: (i, i + 1),
+        });
+    }
+    breakpoints
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn anchors(
+        x_seq: usize,
+        x: usize,
+        y_seq: usize,
+        y: usize,
+        n: usize,
+        strand: ReqStrand,
+    ) -> Vec<Anchor> {
+        (0..n)
+            .map(|i| {
+                let y = if strand == ReqStrand::Reverse {
+                    y + (n - 1 - i) * 50
+                } else {
+                    y + i * 50
+                };
+                Anchor::new(x_seq, x + i * 50, y_seq, y, 15, strand)
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_blocks() {
+        // A B C on sequence 0 map to A C on sequence 0, and B on sequence 1 (translocation)
+        let mut all = anchors(0, 0, 0, 1000, 10, ReqStrand::Forward);
+        all.extend(anchors(0, 1000, 1, 0, 10, ReqStrand::Forward));
+        all.extend(anchors(0, 2000, 0, 2000, 10, ReqStrand::Forward));
+        // spurious anchors
+        all.push(Anchor::new(0, 300, 0, 5000, 15, ReqStrand::Forward));
+        all.push(Anchor::new(0, 1200, 0, 200, 15, ReqStrand::Reverse));
+        let blocks = SyntenyChainer::new(200, 3).blocks(&all);
+        assert_eq!(blocks.len(), 3);
+        assert_eq!(
+            (blocks[0].x.clone(), blocks[0].y.clone()),
+            (0..465, 1000..1465)
+        );
+        assert_eq!(blocks[1].y_seq, 1);
+        assert_eq!(blocks[2].x, 2000..2465);
+        assert!(blocks.iter().all(|block| block.anchors == 10));
+        assert!(blocks.iter().all(|block| block.score == 15 * 10));
+
+        let breakpoints = breakpoints(&blocks);
+        assert_eq!(
+            breakpoints
+                .iter()
+                .map(|breakpoint| (breakpoint.kind, breakpoint.x.clone()))
+                .collect::<Vec<_>>(),
+            [
+                (BreakpointKind::Translocation, 465..1000),
+                (BreakpointKind::Translocation, 1465..2000)
+            ]
+        );
+    }
+
+    #[test]
+    fn test_transposition() {
+        // A B C map to A C B
+        let mut all = anchors(0, 0, 0, 0, 5, ReqStrand::Forward);
+        all.extend(anchors(0, 1000, 0, 2000, 5, ReqStrand::Forward));
+        all.extend(anchors(0, 2000, 0, 1000, 5, ReqStrand::Forward));
+        let blocks = SyntenyChainer::new(100, 2).blocks(&all);
+        assert_eq!(blocks.len(), 3);
+        let kinds = breakpoints(&blocks)
+            .iter()
+            .map(|breakpoint| breakpoint.kind)
+            .collect::<Vec<_>>();
+        assert_eq!(kinds, [BreakpointKind::Transposition]);
+
+        // a large insertion splits blocks, but is no breakpoint
+        let mut all = anchors(0, 0, 0, 0, 5, ReqStrand::Forward);
+        all.extend(anchors(0, 5000, 0, 300, 5, ReqStrand::Forward));
+        let blocks = SyntenyChainer::new(100, 2).blocks(&all);
+        assert_eq!(blocks.len(), 2);
+        assert!(breakpoints(&blocks).is_empty());
+    }
+
+    #[test]
+    fn test_reverse_chain() {
+        let all = anchors(0, 0, 0, 0, 8, ReqStrand::Reverse);
+        let blocks = SyntenyChainer::new(100, 8).blocks(&all);
+        assert_eq!(blocks.len(), 1);
+        assert_eq!((blocks[0].x.clone(), blocks[0].y.clone()), (0..365, 0..365));
+        // reverse strand anchors in forward order do not chain
+        let all = anchors(0, 0, 0, 0, 8, ReqStrand::Forward)
+            .into_iter()
+            .map(|anchor| Anchor {
+                strand: ReqStrand::Reverse,
+                ..anchor
+            })
+            .collect::<Vec<_>>();
+        assert!(SyntenyChainer::new(100, 2).blocks(&all).is_empty());
+    }
+}
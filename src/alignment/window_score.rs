@@ -0,0 +1,190 @@
+//! Ungapped scoring of all windows of a text against a query, e.g. for scanning a genome for
+//! off-target sites of guides or probes, where the score of each text position has to be
+//! computed rather than only the best hits.
+//!
+//! The query is given as a `QueryProfile`, i.e. a table of scores for each query position and
+//! text symbol, which is either derived from a `MatchFunc` or built from position specific
+//! weights (e.g. to penalize mismatches close to the PAM more). The windows are scored in tiles
+//! of consecutive start positions, such that the inner loop adds one profile row to a small
+//! block of accumulators, which the compiler can vectorize. A `WindowScorer` keeps the score
+//! buffer between calls, avoiding reallocation when many sequences are scanned.
+//! Complexity: O(nm) for a text of length n and a query of length m.
+//!
+//! # Example
+//!
+//! ```
+//! use bio::alignment::pairwise::MatchParams;
+//! use bio::alignment::window_score::{QueryProfile, WindowScorer};
+//!
+//! let profile = QueryProfile::new(b"ACGT", &MatchParams::new(1, -1));
+//! let mut scorer = WindowScorer::new();
+//! let scores = scorer.score_windows(&profile, b"TTACGTACCT");
+//! assert_eq!(scores, [-4, -4, 4, -4, -4, -4, 2]);
+//! ```
+
+use crate::alignment::pairwise::MatchFunc;
+use crate::utils::TextSlice;
+
+/// Number of consecutive windows scored together.
+const TILE: usize = 64;
+
+/// Scores of each query position against all byte values.
+#[derive(Default, Clone, Eq, PartialEq, Hash, Debug, Serialize, Deserialize)]
+pub struct QueryProfile {
+    /// Row-major table with 256 entries per query position.
+    scores: Vec<i32>,
+}
+
+impl QueryProfile {
+    /// Create the profile of a query, scoring query symbol `a` against text symbol `b` with
+    /// `match_fn.score(a, b)`.
+    pub fn new<F: MatchFunc>(query: TextSlice<'_>, match_fn: &F) -> Self {
+        QueryProfile::from_fn(query.len(), |i, b| match_fn.score(query[i], b))
+    }
+
+    /// Create a profile of the given length from a function returning the score of text symbol
+    /// `b` at query position `i`.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use bio::alignment::window_score::QueryProfile;
+    ///
+    /// // mismatches at the 3' end weigh more
+    /// let query = b"ACGTA";
+    /// let profile = QueryProfile::from_fn(query.len(), |i, b| {
+    ///     if query[i] == b {
+    ///         0
+    ///     } else {
+    ///         -(i as i32 + 1)
+    ///     }
+    /// });
+    /// assert_eq!(profile.score(b"TCGTA"), -1);
+    /// assert_eq!(profile.score(b"ACGTT"), -5);
+    /// ```
+    pub fn from_fn<F: FnMut(usize, u8) -> i32>(len: usize, mut score: F) -> Self {
+        let mut scores = Vec::with_capacity(len * 256);
+        for i in 0..len {
+            scores.extend((0..=255).map(|b| score(i, b)));
+        }
+        QueryProfile { scores }
+    }
+
+    /// Length of the query.
+    pub fn len(&self) -> usize {
+        self.scores.len() / 256
+    }
+
+    /// Whether the query is empty.
+    pub fn is_empty(&self) -> bool {
+        self.scores.is_empty()
+    }
+
+    /// The scores of all text symbols at the given query position.
+    fn row(&self, i: usize) -> &[i32] {
+        &self.scores[i * 256..(i + 1) * 256]
+    }
+
+    /// Score a single window.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the window length differs from the query length.
+    pub fn score(&self, window: TextSlice<'_>) -> i32 {
+        assert_eq!(
+            window.len(),
+            self.len(),
+            "Expecting window of the query length."
+        );
+        window
+            .iter()
+            .enumerate()
+            .map(|(i, &b)| self.row(i)[b as usize])
+            .sum()
+    }
+}
+
+/// Scorer of all windows of a text, reusing its score buffer between calls.
+#[derive(Default, Clone, Eq, PartialEq, Hash, Debug)]
+pub struct WindowScorer {
+    scores: Vec<i32>,
+}
+
+impl WindowScorer {
+    /// Create a new scorer.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Score all windows of the text of the length of the query. Entry `i` of the result is the
+    /// score of the window starting at position `i`. The result is empty if the text is shorter
+    /// than the query.
+    pub fn score_windows(&mut self, profile: &QueryProfile, text: TextSlice<'_>) -> &[i32] {
+        self.scores.clear();
+        let m = profile.len();
+        if text.len() < m {
+            return &self.scores;
+        }
+        self.scores.resize(text.len() - m + 1, 0);
+        for (t, tile) in self.scores.chunks_mut(TILE).enumerate() {
+            let start = t * TILE;
+            for i in 0..m {
+                let row = profile.row(i);
+                let symbols = &text[start + i..start + i + tile.len()];
+                for (score, &b) in tile.iter_mut().zip(symbols) {
+                    *score += row[b as usize];
+                }
+            }
+        }
+        &self.scores
+    }
+}
+
+/// Score all windows of the text of the length of the query (see
+/// `WindowScorer::score_windows`).
+pub fn score_windows(profile: &QueryProfile, text: TextSlice<'_>) -> Vec<i32> {
+    let mut scorer = WindowScorer::new();
+    scorer.score_windows(profile, text);
+    scorer.scores
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::alignment::pairwise::MatchParams;
+    use rand::prelude::*;
+    use rand::rngs::StdRng;
+
+    #[test]
+    fn test_naive() {
+        let mut rng = StdRng::seed_from_u64(42);
+        let mut scorer = WindowScorer::new();
+        let match_fn = MatchParams::new(2, -3);
+        for &(m, n) in &[(1, 10), (20, 500), (7, 64 + 6), (7, 64 + 7), (30, 1000)] {
+            let mut random = |len| {
+                (0..len)
+                    .map(|_| *b"ACGT".choose(&mut rng).unwrap())
+                    .collect::<Vec<_>>()
+            };
+            let (query, text) = (random(m), random(n));
+            let profile = QueryProfile::new(&query, &match_fn);
+            let expected = text
+                .windows(m)
+                .map(|window| profile.score(window))
+                .collect::<Vec<_>>();
+            assert_eq!(scorer.score_windows(&profile, &text), &expected[..]);
+            assert_eq!(score_windows(&profile, &text), expected);
+        }
+    }
+
+    #[test]
+    fn test_short_text() {
+        let profile = QueryProfile::new(b"ACGT", &MatchParams::new(1, -1));
+        assert_eq!(profile.len(), 4);
+        assert!(score_windows(&profile, b"ACG").is_empty());
+        assert_eq!(score_windows(&profile, b"ACGT"), [4]);
+        let empty = QueryProfile::new(b"", &MatchParams::new(1, -1));
+        assert!(empty.is_empty());
+        assert_eq!(score_windows(&empty, b"AC"), [0, 0, 0]);
+    }
+}
@@ -0,0 +1,244 @@
+//! Ambiguity-aware handling of DNA sequences in the IUPAC nucleotide code.
+//!
+//! Each of the 15 IUPAC codes stands for a non-empty set of the bases `A`, `C`, `G` and `T`.
+//! Two codes are compatible if their sets intersect, e.g. `R` (`A` or `G`) matches `A`, `G`,
+//! `S` and `N`, but not `C` or `Y`. All functions are case-insensitive, `U` is treated like `T`.
+//!
+//! # Example
+//!
+//! ```
+//! use bio::alphabets::dna::iupac;
+//!
+//! assert!(iupac::matches(b'R', b'a'));
+//! assert!(!iupac::matches(b'R', b'Y'));
+//! assert_eq!(iupac::complement(b'R'), b'Y');
+//! assert_eq!(
+//!     iupac::expand(b"AYG").collect::<Vec<_>>(),
+//!     [b"ACG".to_vec(), b"ATG".to_vec()]
+//! );
+//! ```
+
+use crate::alphabets::Alphabet;
+use crate::utils::Text;
+
+/// The 15 IUPAC nucleotide codes (uppercase).
+pub const SYMBOLS: &[u8] = b"ACGTRYSWKMBDHVN";
+
+/// The bases encoded by each of the `SYMBOLS`, as bit sets with A = 1, C = 2, G = 4 and T = 8.
+const BASES: [u8; 15] = [1, 2, 4, 8, 5, 10, 6, 9, 12, 3, 14, 13, 11, 7, 15];
+
+lazy_static! {
+    /// Bit sets of bases for all bytes, with 0 for non-IUPAC bytes.
+    static ref BITS: [u8; 256] = {
+        let mut bits = [0; 256];
+        for (&a, &b) in SYMBOLS.iter().zip(BASES.iter()) {
+            bits[a as usize] = b;
+            bits[a.to_ascii_lowercase() as usize] = b;
+        }
+        bits[b'U' as usize] = 8;
+        bits[b'u' as usize] = 8;
+        bits
+    };
+
+    static ref CODES: [u8; 16] = {
+        let mut codes = [0; 16];
+        for (&a, &b) in SYMBOLS.iter().zip(BASES.iter()) {
+            codes[b as usize] = a;
+        }
+        codes
+    };
+}
+
+/// The IUPAC nucleotide alphabet of the 15 codes (uppercase and lowercase).
+pub fn alphabet() -> Alphabet {
+    Alphabet::new(b"ACGTRYSWKMBDHVNacgtryswkmbdhvn")
+}
+
+/// The set of bases represented by the given code, as bit set with A = 1, C = 2, G = 4 and
+/// T = 8. Returns 0 if the byte is not an IUPAC nucleotide code.
+#[inline]
+pub fn bases(a: u8) -> u8 {
+    BITS[a as usize]
+}
+
+/// The (uppercase) IUPAC code for the given non-empty bit set of bases (see `bases`).
+///
+/// # Panics
+///
+/// Panics if the bit set is empty or has bits beyond the four bases.
+pub fn code(bases: u8) -> u8 {
+    assert!(
+        bases > 0 && bases < 16,
+        "Expecting non-empty set of the four bases."
+    );
+    CODES[bases as usize]
+}
+
+/// Whether the code stands for more than one base.
+pub fn is_ambiguous(a: u8) -> bool {
+    bases(a).count_ones() > 1
+}
+
+/// Whether the two codes are compatible, i.e. can stand for the same base.
+/// Non-IUPAC bytes match nothing.
+#[inline]
+pub fn matches(a: u8, b: u8) -> bool {
+    bases(a) & bases(b) != 0
+}
+
+/// Whether the two sequences have the same length and are compatible at each position.
+pub fn matches_seq(x: &[u8], y: &[u8]) -> bool {
+    x.len() == y.len() && x.iter().zip(y).all(|(&a, &b)| matches(a, b))
+}
+
+/// Return the complement of the given code, i.e. the code for the complementary bases.
+/// Casing is preserved and non-IUPAC bytes are returned unchanged.
+pub fn complement(a: u8) -> u8 {
+    let b = bases(a);
+    if b == 0 {
+        return a;
+    }
+    // swap A with T and C with G
+    let comp = code(((b & 1) << 3) | ((b & 2) << 1) | ((b & 4) >> 1) | ((b & 8) >> 3));
+    if a.is_ascii_lowercase() {
+        comp.to_ascii_lowercase()
+    } else {
+        comp
+    }
+}
+
+/// Calculate the reverse complement of the given sequence (see `complement`).
+pub fn revcomp(text: &[u8]) -> Text {
+    text.iter().rev().map(|&a| complement(a)).collect()
+}
+
+/// Number of concrete sequences that the given sequence expands to (saturating at
+/// `usize::MAX`).
+pub fn expansions(seq: &[u8]) -> usize {
+    seq.iter().fold(1usize, |n, &a| {
+        n.saturating_mul((bases(a).count_ones() as usize).max(1))
+    })
+}
+
+/// Iterate over all concrete sequences (over `ACGT`) represented by the given sequence, in
+/// lexicographical order. Casing is preserved, `U` is expanded like `T` and non-IUPAC bytes
+/// are kept unchanged. Note that the number of sequences grows exponentially with the number
+/// of ambiguous positions (see `expansions`).
+pub fn expand(seq: &[u8]) -> Expand {
+    let choices = seq
+        .iter()
+        .map(|&a| {
+            let bases = bases(a);
+            if bases == 0 {
+                return vec![a];
+            }
+            b"ACGT"
+                .iter()
+                .enumerate()
+                .filter(|&(i, _)| bases & (1 << i) != 0)
+                .map(|(_, &b)| {
+                    if a.is_ascii_lowercase() {
+                        b.to_ascii_lowercase()
+                    } else {
+                        b
+                    }
+                })
+                .collect()
+        })
+        .collect::<Vec<Vec<u8>>>();
+    Expand {
+        indices: Some(vec![0; choices.len()]),
+        choices,
+    }
+}
+
+/// Iterator over the concrete sequences represented by an ambiguous sequence (see `expand`).
+#[derive(Clone, Debug)]
+pub struct Expand {
+    choices: Vec<Vec<u8>>,
+    /// Index of the current choice at each position, `None` when exhausted.
+    indices: Option<Vec<usize>>,
+}
+
+impl Iterator for Expand {
+    type Item = Text;
+
+    fn next(&mut self) -> Option<Text> {
+        let indices = self.indices.as_mut()?;
+        let seq = indices
+            .iter()
+            .zip(&self.choices)
+            .map(|(&i, choices)| choices[i])
+            .collect();
+
+        // advance like an odometer, with the last position changing fastest
+        let mut exhausted = true;
+        for (i, choices) in indices.iter_mut().zip(&self.choices).rev() {
+            *i += 1;
+            if *i < choices.len() {
+                exhausted = false;
+                break;
+            }
+            *i = 0;
+        }
+        if exhausted {
+            self.indices = None;
+        }
+        Some(seq)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::alphabets::dna;
+
+    #[test]
+    fn test_matches() {
+        for &a in SYMBOLS {
+            assert!(matches(a, b'N'));
+            assert!(matches(a, a.to_ascii_lowercase()));
+            assert!(!matches(a, b'-'));
+        }
+        assert!(matches(b'U', b'Y'));
+        assert!(!matches(b'S', b'W'));
+        assert!(matches_seq(b"ACGT", b"MSKN"));
+        assert!(!matches_seq(b"ACGT", b"ACG"));
+    }
+
+    #[test]
+    fn test_complement() {
+        for &a in SYMBOLS {
+            assert_eq!(complement(complement(a)), a);
+            assert_eq!(complement(a), dna::complement(a));
+            assert_eq!(
+                complement(a.to_ascii_lowercase()),
+                dna::complement(a.to_ascii_lowercase())
+            );
+        }
+        assert_eq!(revcomp(b"AcgRN-"), b"-NYcgT");
+    }
+
+    #[test]
+    fn test_expand() {
+        assert_eq!(expansions(b"NNRA"), 32);
+        let expanded = expand(b"NNRA").collect::<Vec<_>>();
+        assert_eq!(expanded.len(), 32);
+        assert_eq!(expanded[0], b"AAAA");
+        assert_eq!(expanded[31], b"TTGA");
+        assert!(expanded.windows(2).all(|w| w[0] < w[1]));
+        assert!(expanded.iter().all(|seq| matches_seq(seq, b"NNRA")));
+
+        assert_eq!(expand(b"a-u").collect::<Vec<_>>(), [b"a-t".to_vec()]);
+        assert_eq!(expand(b"").collect::<Vec<_>>(), [Vec::<u8>::new()]);
+    }
+
+    #[test]
+    fn test_code() {
+        for &a in SYMBOLS {
+            assert_eq!(code(bases(a)), a);
+        }
+        assert!(is_ambiguous(b'r'));
+        assert!(!is_ambiguous(b'T'));
+    }
+}
@@ -14,11 +14,15 @@
 //! assert!(alphabet.is_word(b"gattaca"));
 //! assert!(!alphabet.is_word(b"ACGU"));
 //! ```
+//!
+//! See `iupac` for handling ambiguous sequences.
 
 use std::borrow::Borrow;
 
 use crate::alphabets::Alphabet;
 
+pub mod iupac;
+
 /// The DNA alphabet (uppercase and lowercase).
 pub fn alphabet() -> Alphabet {
     Alphabet::new(b"ACGTacgt")
@@ -15,6 +15,7 @@
 //! ```
 
 use std::borrow::Borrow;
+use std::cmp;
 use std::mem;
 
 use bit_set::BitSet;
@@ -24,7 +25,43 @@ pub mod dna;
 pub mod protein;
 pub mod rna;
 
-pub type SymbolRanks = VecMap<u8>;
+pub type SymbolRanks<R = u8> = VecMap<R>;
+
+/// Integer types that can be used to store symbol ranks in a `RankTransform`.
+pub trait Rank: Copy + Ord {
+    /// Largest rank that can be represented by this type.
+    const MAX_RANK: usize;
+
+    /// Convert a rank given as `usize` into this type.
+    fn from_usize(r: usize) -> Self;
+
+    /// Convert this rank into a `usize`.
+    fn as_usize(self) -> usize;
+}
+
+impl Rank for u8 {
+    const MAX_RANK: usize = u8::MAX as usize;
+
+    fn from_usize(r: usize) -> Self {
+        r as u8
+    }
+
+    fn as_usize(self) -> usize {
+        self as usize
+    }
+}
+
+impl Rank for u16 {
+    const MAX_RANK: usize = u16::MAX as usize;
+
+    fn from_usize(r: usize) -> Self {
+        r as u16
+    }
+
+    fn as_usize(self) -> usize {
+        self as usize
+    }
+}
 
 /// Representation of an alphabet.
 #[derive(Default, Clone, Eq, PartialEq, Ord, PartialOrd, Hash, Debug)]
@@ -206,6 +243,62 @@ impl Alphabet {
             symbols: self.symbols.union(&other.symbols).collect(),
         };
     }
+
+    /// Check whether all symbols of this alphabet are contained in other.
+    ///
+    /// # Example
+    /// ```
+    /// use bio::alphabets;
+    ///
+    /// let dna_alphabet = alphabets::dna::alphabet();
+    /// let iupac_alphabet = alphabets::dna::iupac_alphabet();
+    ///
+    /// assert!(dna_alphabet.is_subset_of(&iupac_alphabet));
+    /// assert!(!iupac_alphabet.is_subset_of(&dna_alphabet));
+    /// ```
+    pub fn is_subset_of(&self, other: &Alphabet) -> bool {
+        self.symbols.is_subset(&other.symbols)
+    }
+
+    /// Check whether all symbols of other are contained in this alphabet.
+    ///
+    /// # Example
+    /// ```
+    /// use bio::alphabets;
+    ///
+    /// let dna_alphabet = alphabets::dna::alphabet();
+    /// assert!(dna_alphabet.is_superset_of(&alphabets::Alphabet::new(b"ACgt")));
+    /// ```
+    pub fn is_superset_of(&self, other: &Alphabet) -> bool {
+        self.symbols.is_superset(&other.symbols)
+    }
+
+    /// Check whether the given symbol is contained in the alphabet.
+    ///
+    /// Complexity: O(1)
+    pub fn contains(&self, a: u8) -> bool {
+        self.symbols.contains(a as usize)
+    }
+
+    /// Return the symbols of the alphabet in lexicographical order.
+    ///
+    /// Complexity: O(n), where n is the number of symbols in the alphabet.
+    ///
+    /// # Example
+    /// ```
+    /// use bio::alphabets;
+    ///
+    /// // compose DNA, IUPAC ambiguity codes and a masking symbol
+    /// let alphabet = alphabets::dna::alphabet()
+    ///     .union(&alphabets::dna::iupac_alphabet())
+    ///     .union(&alphabets::Alphabet::new(b"X"));
+    /// let upper = alphabet.difference(&alphabets::english_ascii_lower_alphabet());
+    ///
+    /// assert_eq!(upper.to_vec(), b"ABCDGHKMNRSTVWXYZ".to_vec());
+    /// ```
+    pub fn to_vec(&self) -> Vec<u8> {
+        self.symbols.iter().map(|a| a as u8).collect()
+    }
 }
 
 /// Tools based on transforming the alphabet symbols to their lexicographical ranks.
@@ -217,9 +310,12 @@ impl Alphabet {
 ///
 /// `RankTransform` can be used in to perform bit encoding for texts over a
 /// given alphabet via `bio::data_structures::bitenc`.
+///
+/// Ranks are stored as `u8` by default. Use `RankTransform::<u16>::from_alphabet` in order
+/// to obtain wider ranks, e.g. for composing transformations into larger integer alphabets.
 #[derive(Default, Clone, Eq, PartialEq, Ord, PartialOrd, Hash, Debug, Serialize, Deserialize)]
-pub struct RankTransform {
-    pub ranks: SymbolRanks,
+pub struct RankTransform<R: Rank = u8> {
+    pub ranks: SymbolRanks<R>,
 }
 
 impl RankTransform {
@@ -236,9 +332,35 @@ impl RankTransform {
     /// let dna_ranks = alphabets::RankTransform::new(&dna_alphabet);
     /// ```
     pub fn new(alphabet: &Alphabet) -> Self {
+        Self::from_alphabet(alphabet)
+    }
+}
+
+impl<R: Rank> RankTransform<R> {
+    /// Construct a new `RankTransform` with ranks of type `R`.
+    ///
+    /// This method fails with an assertion if the alphabet has more symbols than can be
+    /// represented by `R`.
+    ///
+    /// Complexity: O(n), where n is the number of symbols in the alphabet.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use bio::alphabets;
+    ///
+    /// let dna_alphabet = alphabets::Alphabet::new(b"acgtACGT");
+    /// let dna_ranks = alphabets::RankTransform::<u16>::from_alphabet(&dna_alphabet);
+    /// assert_eq!(dna_ranks.transform(b"Ta"), vec![3u16, 4]);
+    /// ```
+    pub fn from_alphabet(alphabet: &Alphabet) -> Self {
+        assert!(
+            alphabet.len() <= R::MAX_RANK + 1,
+            "Expecting alphabet size to fit into the rank type"
+        );
         let mut ranks = VecMap::new();
         for (r, c) in alphabet.symbols.iter().enumerate() {
-            ranks.insert(c, r as u8);
+            ranks.insert(c, R::from_usize(r));
         }
 
         RankTransform { ranks }
@@ -260,7 +382,7 @@ impl RankTransform {
     /// assert_eq!(dna_ranks.get(65), 0); // "A"
     /// assert_eq!(dna_ranks.get(116), 7); // "t"
     /// ```
-    pub fn get(&self, a: u8) -> u8 {
+    pub fn get(&self, a: u8) -> R {
         *self.ranks.get(a as usize).expect("Unexpected character.")
     }
 
@@ -278,7 +400,7 @@ impl RankTransform {
     /// let text = b"aAcCgGtT";
     /// assert_eq!(dna_ranks.transform(text), vec![4, 0, 5, 1, 6, 2, 7, 3]);
     /// ```
-    pub fn transform<C, T>(&self, text: T) -> Vec<u8>
+    pub fn transform<C, T>(&self, text: T) -> Vec<R>
     where
         C: Borrow<u8>,
         T: IntoIterator<Item = C>,
@@ -295,6 +417,8 @@ impl RankTransform {
 
     /// Iterate over q-grams (substrings of length q) of given `text`. The q-grams are encoded
     /// as `usize` by storing the symbol ranks in log2(|A|) bits (with |A| being the alphabet size).
+    /// The i-th q-gram starts at position i of the text, such that start positions are obtained
+    /// with `.enumerate()`.
     ///
     /// If q is larger than usize::BITS / log2(|A|), this method fails with an assertion.
     ///
@@ -311,7 +435,7 @@ impl RankTransform {
     /// let q_grams: Vec<usize> = dna_ranks.qgrams(2, b"ACGT").collect();
     /// assert_eq!(q_grams, vec![1, 10, 19]);
     /// ```
-    pub fn qgrams<C, T>(&self, q: u32, text: T) -> QGrams<'_, C, T::IntoIter>
+    pub fn qgrams<C, T>(&self, q: u32, text: T) -> QGrams<'_, C, T::IntoIter, R>
     where
         C: Borrow<u8>,
         T: IntoIterator<Item = C>,
@@ -337,6 +461,68 @@ impl RankTransform {
         qgrams
     }
 
+    /// Iterate over canonical q-grams of given `text`, i.e. the minimum of the encoding of each
+    /// q-gram and the encoding of its reverse complement. The reverse complement encoding is
+    /// updated incrementally, so that both strands are covered in a single pass.
+    /// The `complement` function maps each symbol to its complementary symbol, which has to be
+    /// contained in the alphabet as well (e.g. `bio::alphabets::dna::complement`).
+    ///
+    /// If q is zero or larger than usize::BITS / log2(|A|), this method fails with an
+    /// assertion.
+    ///
+    /// Complexity: O(n), where n is the length of the text.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use bio::alphabets;
+    /// use bio::alphabets::dna;
+    ///
+    /// let dna_alphabet = alphabets::Alphabet::new(b"ACGT");
+    /// let dna_ranks = alphabets::RankTransform::new(&dna_alphabet);
+    ///
+    /// let fwd: Vec<usize> = dna_ranks.canonical_qgrams(3, b"AACGTT", dna::complement).collect();
+    /// let rev: Vec<usize> = dna_ranks
+    ///     .canonical_qgrams(3, dna::revcomp(b"AACGTT"), dna::complement)
+    ///     .collect();
+    /// assert_eq!(fwd, rev.into_iter().rev().collect::<Vec<_>>());
+    ///
+    /// // the start positions of the q-grams are given by enumerating them
+    /// let positioned: Vec<(usize, usize)> = dna_ranks
+    ///     .canonical_qgrams(3, b"AACGTT", dna::complement)
+    ///     .enumerate()
+    ///     .collect();
+    /// assert_eq!(positioned, vec![(0, 1), (1, 6), (2, 6), (3, 1)]);
+    /// ```
+    pub fn canonical_qgrams<C, T, F>(
+        &self,
+        q: u32,
+        text: T,
+        complement: F,
+    ) -> CanonicalQGrams<'_, C, T::IntoIter, R>
+    where
+        C: Borrow<u8>,
+        T: IntoIterator<Item = C>,
+        F: Fn(u8) -> u8,
+    {
+        assert!(q > 0, "Expecting q > 0");
+        let mut complement_ranks = VecMap::new();
+        for (a, &r) in self.ranks.iter() {
+            complement_ranks.insert(r.as_usize(), self.get(complement(a as u8)));
+        }
+        let qgrams = self.qgrams(q, text);
+        let mut canonical = CanonicalQGrams {
+            rev_shift: qgrams.bits * (q - 1),
+            qgrams,
+            complement_ranks,
+            rev_qgram: 0,
+        };
+        // the forward q-gram has already been primed with the first q - 1 symbols
+        canonical.rev_qgram = canonical.revcomp_of(canonical.qgrams.qgram, q - 1);
+
+        canonical
+    }
+
     /// Restore alphabet from transform.
     ///
     /// Complexity: O(n), where n is the number of symbols in the alphabet.
@@ -385,35 +571,38 @@ impl RankTransform {
 
 /// Iterator over q-grams.
 #[derive(Copy, Clone, Eq, PartialEq, Ord, PartialOrd, Hash, Debug, Serialize)]
-pub struct QGrams<'a, C, T>
+pub struct QGrams<'a, C, T, R = u8>
 where
     C: Borrow<u8>,
     T: Iterator<Item = C>,
+    R: Rank,
 {
     text: T,
-    ranks: &'a RankTransform,
+    ranks: &'a RankTransform<R>,
     bits: u32,
     mask: usize,
     qgram: usize,
 }
 
-impl<'a, C, T> QGrams<'a, C, T>
+impl<'a, C, T, R> QGrams<'a, C, T, R>
 where
     C: Borrow<u8>,
     T: Iterator<Item = C>,
+    R: Rank,
 {
     /// Push a new character into the current qgram.
-    fn qgram_push(&mut self, a: u8) {
+    fn qgram_push(&mut self, a: R) {
         self.qgram <<= self.bits;
-        self.qgram |= a as usize;
+        self.qgram |= a.as_usize();
         self.qgram &= self.mask;
     }
 }
 
-impl<'a, C, T> Iterator for QGrams<'a, C, T>
+impl<'a, C, T, R> Iterator for QGrams<'a, C, T, R>
 where
     C: Borrow<u8>,
     T: Iterator<Item = C>,
+    R: Rank,
 {
     type Item = usize;
 
@@ -429,6 +618,57 @@ where
     }
 }
 
+/// Iterator over canonical q-grams, i.e. the minimum of each q-gram and its reverse complement.
+#[derive(Clone, Debug)]
+pub struct CanonicalQGrams<'a, C, T, R = u8>
+where
+    C: Borrow<u8>,
+    T: Iterator<Item = C>,
+    R: Rank,
+{
+    qgrams: QGrams<'a, C, T, R>,
+    complement_ranks: VecMap<R>,
+    rev_shift: u32,
+    rev_qgram: usize,
+}
+
+impl<'a, C, T, R> CanonicalQGrams<'a, C, T, R>
+where
+    C: Borrow<u8>,
+    T: Iterator<Item = C>,
+    R: Rank,
+{
+    /// Reverse complement encoding of the last `len` symbols stored in `qgram`, placed such
+    /// that the next complemented symbol is shifted in from the highest position.
+    fn revcomp_of(&self, qgram: usize, len: u32) -> usize {
+        let bits = self.qgrams.bits;
+        let symbol_mask = (1usize << bits) - 1;
+        let mut rev = 0;
+        for i in (0..len).rev() {
+            let r = (qgram >> (i * bits)) & symbol_mask;
+            rev = (rev >> bits) | (self.complement_ranks[r].as_usize() << self.rev_shift);
+        }
+        rev
+    }
+}
+
+impl<'a, C, T, R> Iterator for CanonicalQGrams<'a, C, T, R>
+where
+    C: Borrow<u8>,
+    T: Iterator<Item = C>,
+    R: Rank,
+{
+    type Item = usize;
+
+    fn next(&mut self) -> Option<usize> {
+        let qgram = self.qgrams.next()?;
+        let r = qgram & ((1usize << self.qgrams.bits) - 1);
+        self.rev_qgram = (self.rev_qgram >> self.qgrams.bits)
+            | (self.complement_ranks[r].as_usize() << self.rev_shift);
+        Some(cmp::min(qgram, self.rev_qgram))
+    }
+}
+
 /// Returns the english ascii lower case alphabet.
 pub fn english_ascii_lower_alphabet() -> Alphabet {
     Alphabet::new(&b"abcdefghijklmnopqrstuvwxyz"[..])
@@ -442,6 +682,7 @@ pub fn english_ascii_upper_alphabet() -> Alphabet {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::alphabets::dna;
 
     #[test]
     fn test_alphabet_eq() {
@@ -450,6 +691,53 @@ mod tests {
         assert_ne!(Alphabet::new(b"ATCG"), Alphabet::new(b"ATC"));
     }
 
+    #[test]
+    fn test_alphabet_set_ops() {
+        let dna = Alphabet::new(b"ACGT");
+        let masked = dna.union(&Alphabet::new(b"acgtN"));
+        assert!(dna.is_subset_of(&masked));
+        assert!(masked.is_superset_of(&dna));
+        assert!(dna.is_subset_of(&dna));
+        assert!(!masked.is_subset_of(&dna));
+        assert!(Alphabet::new(b"").is_subset_of(&dna));
+        assert_eq!(masked.difference(&dna).to_vec(), b"Nacgt".to_vec());
+        assert_eq!(
+            masked.intersection(&Alphabet::new(b"NX")).to_vec(),
+            b"N".to_vec()
+        );
+        assert!(masked.contains(b'N'));
+        assert!(!dna.contains(b'N'));
+    }
+
+    #[test]
+    fn test_rank_transform_u16() {
+        let alphabet = Alphabet::new(b"ACGT");
+        let narrow = RankTransform::new(&alphabet);
+        let wide = RankTransform::<u16>::from_alphabet(&alphabet);
+        let text = b"GATTACA";
+        assert_eq!(
+            wide.transform(text),
+            narrow
+                .transform(text)
+                .into_iter()
+                .map(u16::from)
+                .collect::<Vec<_>>()
+        );
+        assert!(wide.qgrams(3, text).eq(narrow.qgrams(3, text)));
+        assert!(wide
+            .canonical_qgrams(3, text, dna::complement)
+            .eq(narrow.canonical_qgrams(3, text, dna::complement)));
+        assert_eq!(wide.get_width(), 2);
+    }
+
+    #[test]
+    fn test_rank_transform_full_byte_alphabet() {
+        let alphabet = Alphabet::new(0..=255u8);
+        let transform = RankTransform::new(&alphabet);
+        assert_eq!(transform.get(255), 255);
+        assert_eq!(transform.get_width(), 8);
+    }
+
     /// When `q * bits == usize::BITS`, make sure that `1<<(1*bits)` does not overflow.
     #[test]
     fn test_qgram_shiftleft_overflow() {
@@ -458,4 +746,45 @@ mod tests {
         let text = b"ACTG".repeat(100);
         transform.qgrams(usize::BITS / 2, text);
     }
+
+    #[test]
+    fn test_canonical_qgrams() {
+        let alphabet = Alphabet::new(b"ACGT");
+        let transform = RankTransform::new(&alphabet);
+        let text = b"GATTACACGTTTAGCAGT";
+        let q = 5;
+        let canonical: Vec<usize> = transform
+            .canonical_qgrams(q, text, dna::complement)
+            .collect();
+        let expected: Vec<usize> = text
+            .windows(q as usize)
+            .map(|w| {
+                let fwd = transform.qgrams(q, w).next().unwrap();
+                let rev = transform.qgrams(q, dna::revcomp(w)).next().unwrap();
+                fwd.min(rev)
+            })
+            .collect();
+        assert_eq!(canonical, expected);
+    }
+
+    #[test]
+    fn test_canonical_qgrams_full_width() {
+        let alphabet = Alphabet::new(b"ACGT");
+        let transform = RankTransform::new(&alphabet);
+        let text = b"ACGGT".repeat(20);
+        let q = usize::BITS / 2;
+        let canonical = transform.canonical_qgrams(q, &text, dna::complement);
+        let revcomp = dna::revcomp(&text);
+        let rev: Vec<usize> = transform
+            .canonical_qgrams(q, &revcomp, dna::complement)
+            .collect();
+        assert!(canonical.eq(rev.into_iter().rev()));
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_canonical_qgrams_empty_q() {
+        let transform = RankTransform::new(&Alphabet::new(b"ACGT"));
+        transform.canonical_qgrams(0, b"ACGT", dna::complement);
+    }
 }
@@ -12,10 +12,16 @@
 //! let alphabet = alphabets::protein::alphabet();
 //! assert!(alphabet.is_word(b"DEQsga"));
 //! assert!(!alphabet.is_word(b"BzJ"));
+//! assert!(alphabets::protein::extended_alphabet().is_word(b"BzJ"));
 //! ```
 
+use std::borrow::Borrow;
+
 use crate::alphabets::Alphabet;
 
+/// The 20 standard amino acids (uppercase).
+const STANDARD: &[u8; 20] = b"ARNDCEQGHILKMFPSTWYV";
+
 /// Returns the standard protein alphabet, containing the 20 common amino acids.
 pub fn alphabet() -> Alphabet {
     Alphabet::new(&b"ARNDCEQGHILKMFPSTWYVarndceqghilkmfpstwyv"[..])
@@ -26,6 +32,169 @@ pub fn iupac_alphabet() -> Alphabet {
     Alphabet::new(b"ABCDEFGHIKLMNPQRSTVWXYZabcdefghiklmnpqrstvwxyz")
 }
 
+/// Returns the strict protein alphabet, i.e. the 20 standard amino acids.
+/// This is the same as `alphabet()`.
+pub fn strict_alphabet() -> Alphabet {
+    alphabet()
+}
+
+/// Returns the extended protein alphabet, containing the 20 standard amino acids,
+/// the ambiguity codes `B` (D or N), `Z` (E or Q), `J` (I or L) and `X` (any),
+/// as well as selenocysteine (`U`) and pyrrolysine (`O`).
+///
+/// # Example
+///
+/// ```
+/// use bio::alphabets::protein;
+///
+/// assert!(protein::extended_alphabet().is_word(b"MKUOxbzj"));
+/// assert!(protein::strict_alphabet().is_subset_of(&protein::extended_alphabet()));
+/// ```
+pub fn extended_alphabet() -> Alphabet {
+    Alphabet::new(b"ARNDCEQGHILKMFPSTWYVBZJXUOarndceqghilkmfpstwyvbzjxuo")
+}
+
+/// Return the standard amino acids (uppercase) represented by the given residue.
+/// Ambiguity codes are expanded, e.g. `B` → `DN`, and `X` yields all 20 standard amino acids.
+/// Selenocysteine (`U`) and pyrrolysine (`O`) represent only themselves.
+/// Returns `None` for symbols outside of the extended alphabet.
+///
+/// # Example
+///
+/// ```
+/// use bio::alphabets::protein;
+///
+/// assert_eq!(protein::expand(b'b'), Some(&b"DN"[..]));
+/// assert_eq!(protein::expand(b'K'), Some(&b"K"[..]));
+/// assert_eq!(protein::expand(b'X').map(|aa| aa.len()), Some(20));
+/// assert_eq!(protein::expand(b'7'), None);
+/// ```
+pub fn expand(a: u8) -> Option<&'static [u8]> {
+    let a = a.to_ascii_uppercase();
+    match a {
+        b'B' => Some(b"DN"),
+        b'Z' => Some(b"EQ"),
+        b'J' => Some(b"IL"),
+        b'X' => Some(STANDARD),
+        b'U' => Some(b"U"),
+        b'O' => Some(b"O"),
+        _ => STANDARD
+            .iter()
+            .position(|&aa| aa == a)
+            .map(|i| &STANDARD[i..=i]),
+    }
+}
+
+/// Report all residues of the given text that are not contained in the given alphabet,
+/// as pairs of position and residue.
+///
+/// # Example
+///
+/// ```
+/// use bio::alphabets::protein;
+///
+/// let text = b"MKB*LU";
+/// assert_eq!(
+///     protein::invalid_residues(text, &protein::strict_alphabet()),
+///     vec![(2, b'B'), (3, b'*'), (5, b'U')]
+/// );
+/// assert_eq!(
+///     protein::invalid_residues(text, &protein::extended_alphabet()),
+///     vec![(3, b'*')]
+/// );
+/// ```
+pub fn invalid_residues<C, T>(text: T, alphabet: &Alphabet) -> Vec<(usize, u8)>
+where
+    C: Borrow<u8>,
+    T: IntoIterator<Item = C>,
+{
+    text.into_iter()
+        .map(|c| *c.borrow())
+        .enumerate()
+        .filter(|&(_, a)| !alphabet.contains(a))
+        .collect()
+}
+
+/// A reduced amino acid alphabet, mapping groups of physico-chemically similar residues to
+/// a common representative symbol (the first residue of the group).
+/// Seeding on reduced k-mers tolerates conservative substitutions, which makes exact k-mer
+/// matching (e.g. for the banded aligner) sensitive enough for divergent proteins.
+/// Symbols that are not part of any group are mapped to their uppercase form.
+///
+/// # Example
+///
+/// ```
+/// use bio::alphabets::protein::ReducedAlphabet;
+///
+/// let reduced = ReducedAlphabet::murphy10();
+/// assert_eq!(reduced.reduce(b"MKVLsD"), b"LKLLSE");
+/// assert_eq!(reduced.get(b'I'), reduced.get(b'v'));
+/// ```
+#[derive(Clone, Eq, PartialEq, Hash, Debug, Serialize, Deserialize)]
+pub struct ReducedAlphabet {
+    table: Vec<u8>,
+}
+
+impl ReducedAlphabet {
+    /// Create a reduced alphabet from the given groups of residues (case insensitive).
+    /// Each residue is represented by the (uppercase) first residue of its group.
+    ///
+    /// # Panics
+    ///
+    /// If a group is empty.
+    pub fn from_groups<G: AsRef<[u8]>>(groups: &[G]) -> Self {
+        let mut table: Vec<u8> = (0..=255u8).map(|a| a.to_ascii_uppercase()).collect();
+        for group in groups {
+            let group = group.as_ref();
+            let repr = group
+                .first()
+                .expect("Expecting non-empty groups.")
+                .to_ascii_uppercase();
+            for &a in group {
+                table[a.to_ascii_uppercase() as usize] = repr;
+                table[a.to_ascii_lowercase() as usize] = repr;
+            }
+        }
+        ReducedAlphabet { table }
+    }
+
+    /// The 10-letter alphabet of Murphy et al. (2000):
+    /// `LVIM`, `C`, `A`, `G`, `ST`, `P`, `FYW`, `EDNQ`, `KR`, `H`.
+    pub fn murphy10() -> Self {
+        ReducedAlphabet::from_groups(&[
+            &b"LVIM"[..],
+            b"C",
+            b"A",
+            b"G",
+            b"ST",
+            b"P",
+            b"FYW",
+            b"EDNQ",
+            b"KR",
+            b"H",
+        ])
+    }
+
+    /// The 4-letter alphabet of Murphy et al. (2000): `LVIMC`, `AGSTP`, `FYW`, `EDNQKRH`.
+    pub fn murphy4() -> Self {
+        ReducedAlphabet::from_groups(&[&b"LVIMC"[..], b"AGSTP", b"FYW", b"EDNQKRH"])
+    }
+
+    /// The representative of the given residue.
+    pub fn get(&self, a: u8) -> u8 {
+        self.table[a as usize]
+    }
+
+    /// Translate the given protein sequence into the reduced alphabet.
+    pub fn reduce<C, T>(&self, text: T) -> Vec<u8>
+    where
+        C: Borrow<u8>,
+        T: IntoIterator<Item = C>,
+    {
+        text.into_iter().map(|c| self.get(*c.borrow())).collect()
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -54,4 +223,36 @@ mod tests {
     fn iupac_contains_iupac_chars() {
         assert!(iupac_alphabet().is_word(b"XMN"));
     }
+
+    #[test]
+    fn extended_alphabet_is_superset() {
+        assert!(alphabet().is_subset_of(&extended_alphabet()));
+        assert!(iupac_alphabet().is_subset_of(&extended_alphabet()));
+        assert!(!extended_alphabet().is_word(b"*"));
+    }
+
+    #[test]
+    fn expand_all_extended_symbols() {
+        for a in extended_alphabet().to_vec() {
+            let expanded = expand(a).unwrap();
+            assert!(!expanded.is_empty());
+            assert!(extended_alphabet().is_word(expanded));
+        }
+        assert_eq!(expand(b'-'), None);
+    }
+
+    #[test]
+    fn reduced_alphabet_groups() {
+        let reduced = ReducedAlphabet::murphy4();
+        assert_eq!(reduced.reduce(b"CAGFWkrh*"), b"LAAFFEEE*");
+        let distinct: std::collections::BTreeSet<u8> =
+            reduced.reduce(STANDARD).into_iter().collect();
+        assert_eq!(distinct.len(), 4);
+        assert_eq!(ReducedAlphabet::murphy10().reduce(STANDARD).len(), 20);
+    }
+
+    #[test]
+    fn no_invalid_residues() {
+        assert!(invalid_residues(b"PRSkl", &alphabet()).is_empty());
+    }
 }
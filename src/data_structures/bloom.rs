@@ -0,0 +1,410 @@
+//! Bloom filters (Bloom, 1970) for approximate set membership, e.g. of k-mers.
+//!
+//! A Bloom filter represents a set by setting `h` bits per item in a bitvector of `m` bits.
+//! Queries never yield false negatives, while false positives occur with a probability that
+//! depends on the number of inserted items. Filters are sized from the expected number of
+//! items and the desired false positive rate. A counting Bloom filter uses small counters
+//! instead of bits, such that items can also be removed.
+//!
+//! Items are hashed once, and the `h` positions are derived by double hashing
+//! (Kirsch and Mitzenmacher, 2006). Encoded DNA k-mers (see `kmer_counting`) are hashed
+//! directly, avoiding the generic hashing machinery, such that k-mer filters are the same on
+//! all platforms. The positions are seeded (see `BloomFilter::with_seed`), such that filters
+//! with independent false positives can be obtained for the same items.
+//!
+//! # Example
+//!
+//! ```
+//! use bio::data_structures::bloom::BloomFilter;
+//!
+//! let mut filter = BloomFilter::new(1000, 0.01);
+//! filter.insert("chr1");
+//! assert!(filter.contains("chr1"));
+//!
+//! filter.insert_kmers(b"ACGGCTGAGATGATCC", 8, true);
+//! assert!(filter.contains_kmer(b"GCTGAGAT", true));
+//! // reverse complement of the canonical k-mer
+//! assert!(filter.contains_kmer(b"ATCTCAGC", true));
+//! ```
+
+use std::collections::hash_map::DefaultHasher;
+use std::hash::{Hash, Hasher};
+
+use bv::{BitVec, Bits, BitsMut};
+
+use crate::data_structures::kmer_counting::{encode_kmer, hash_kmer, kmers, revcomp_kmer};
+use crate::utils::TextSlice;
+
+/// Number of bits and hash functions for the given number of items and false positive rate.
+fn dimensions(expected_items: usize, fp_rate: f64) -> (usize, usize) {
+    assert!(
+        fp_rate > 0.0 && fp_rate < 1.0,
+        "Expecting a false positive rate between 0 and 1."
+    );
+    let n = expected_items.max(1) as f64;
+    let ln2 = std::f64::consts::LN_2;
+    let m = (-n * fp_rate.ln() / (ln2 * ln2)).ceil().max(1.0);
+    let h = (m / n * ln2).round().max(1.0);
+    (m as usize, h as usize)
+}
+
+fn item_hash<T: Hash + ?Sized>(item: &T) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    item.hash(&mut hasher);
+    hasher.finish()
+}
+
+/// The `h` positions of the item with the given hash among `m` positions.
+fn positions(hash: u64, seed: u64, h: usize, m: usize) -> impl Iterator<Item = usize> {
+    let seed = seed.wrapping_mul(2);
+    let h1 = hash_kmer(hash, seed.wrapping_add(1));
+    // odd, such that positions do not repeat for powers of two
+    let h2 = hash_kmer(hash, seed.wrapping_add(2)) | 1;
+    (0..h as u64).map(move |i| (h1.wrapping_add(i.wrapping_mul(h2)) % m as u64) as usize)
+}
+
+/// Encode and optionally canonicalize a k-mer.
+fn kmer_code(kmer: TextSlice<'_>, canonical: bool) -> Option<u64> {
+    let code = encode_kmer(kmer)?;
+    Some(if canonical {
+        code.min(revcomp_kmer(code, kmer.len()))
+    } else {
+        code
+    })
+}
+
+/// A Bloom filter.
+#[derive(Clone, Eq, PartialEq, Hash, Debug, Serialize, Deserialize)]
+pub struct BloomFilter {
+    bits: BitVec<u64>,
+    num_hashes: usize,
+    #[serde(default)]
+    seed: u64,
+}
+
+impl BloomFilter {
+    /// Create a Bloom filter for the given number of items with the given false positive rate.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the false positive rate is not between 0 and 1.
+    pub fn new(expected_items: usize, fp_rate: f64) -> Self {
+        Self::with_seed(expected_items, fp_rate, 0)
+    }
+
+    /// Create a Bloom filter for the given number of items with the given false positive rate,
+    /// using the given seed for hashing.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the false positive rate is not between 0 and 1.
+    pub fn with_seed(expected_items: usize, fp_rate: f64, seed: u64) -> Self {
+        let (m, h) = dimensions(expected_items, fp_rate);
+        let mut filter = Self::with_dimensions(m, h);
+        filter.seed = seed;
+        filter
+    }
+
+    /// Create a Bloom filter with `num_bits` bits and `num_hashes` hash functions.
+    pub fn with_dimensions(num_bits: usize, num_hashes: usize) -> Self {
+        assert!(
+            num_bits > 0 && num_hashes > 0,
+            "Expecting positive number of bits and hashes."
+        );
+        BloomFilter {
+            bits: BitVec::new_fill(false, num_bits as u64),
+            num_hashes,
+            seed: 0,
+        }
+    }
+
+    /// Number of bits.
+    pub fn num_bits(&self) -> usize {
+        self.bits.len() as usize
+    }
+
+    /// Number of hash functions.
+    pub fn num_hashes(&self) -> usize {
+        self.num_hashes
+    }
+
+    /// The seed of the hash functions.
+    pub fn seed(&self) -> u64 {
+        self.seed
+    }
+
+    /// Insert an item given by its 64 bit hash.
+    pub fn insert_hash(&mut self, hash: u64) {
+        for i in positions(hash, self.seed, self.num_hashes, self.num_bits()) {
+            self.bits.set_bit(i as u64, true);
+        }
+    }
+
+    /// Whether an item with the given hash may have been inserted.
+    pub fn contains_hash(&self, hash: u64) -> bool {
+        positions(hash, self.seed, self.num_hashes, self.num_bits())
+            .all(|i| self.bits.get_bit(i as u64))
+    }
+
+    /// Insert a hashable item. Note that the hash depends on the `Hash` implementation of the
+    /// item (e.g. `usize` is hashed differently on 32 and 64 bit platforms) and on the hasher
+    /// of the standard library. Use `insert_hash` or `insert_kmers` for filters that have to
+    /// be reproducible across platforms.
+    pub fn insert<T: Hash + ?Sized>(&mut self, item: &T) {
+        self.insert_hash(item_hash(item));
+    }
+
+    /// Whether the item may have been inserted.
+    pub fn contains<T: Hash + ?Sized>(&self, item: &T) -> bool {
+        self.contains_hash(item_hash(item))
+    }
+
+    /// Insert all k-mers of the given DNA sequence, skipping k-mers containing symbols other
+    /// than `ACGT`, optionally in canonical form (see `kmer_counting::kmers`).
+    pub fn insert_kmers(&mut self, text: TextSlice<'_>, k: usize, canonical: bool) {
+        for kmer in kmers(text, k, canonical) {
+            self.insert_hash(kmer);
+        }
+    }
+
+    /// Whether the k-mer may have been inserted with `insert_kmers`, using the same canonical
+    /// form. K-mers containing symbols other than `ACGT` are never contained.
+    pub fn contains_kmer(&self, kmer: TextSlice<'_>, canonical: bool) -> bool {
+        kmer_code(kmer, canonical).map_or(false, |code| self.contains_hash(code))
+    }
+
+    /// Fraction of set bits, from which the false positive rate can be estimated as
+    /// `fill_ratio^h`.
+    pub fn fill_ratio(&self) -> f64 {
+        let ones = (0..self.bits.len())
+            .filter(|&i| self.bits.get_bit(i))
+            .count();
+        ones as f64 / self.num_bits() as f64
+    }
+}
+
+/// A counting Bloom filter, which supports removal of items, using saturating 8 bit counters.
+#[derive(Clone, Eq, PartialEq, Hash, Debug, Serialize, Deserialize)]
+pub struct CountingBloomFilter {
+    counters: Vec<u8>,
+    num_hashes: usize,
+    #[serde(default)]
+    seed: u64,
+}
+
+impl CountingBloomFilter {
+    /// Create a counting Bloom filter for the given number of items with the given false
+    /// positive rate.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the false positive rate is not between 0 and 1.
+    pub fn new(expected_items: usize, fp_rate: f64) -> Self {
+        Self::with_seed(expected_items, fp_rate, 0)
+    }
+
+    /// Create a counting Bloom filter for the given number of items with the given false
+    /// positive rate, using the given seed for hashing.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the false positive rate is not between 0 and 1.
+    pub fn with_seed(expected_items: usize, fp_rate: f64, seed: u64) -> Self {
+        let (m, h) = dimensions(expected_items, fp_rate);
+        let mut filter = Self::with_dimensions(m, h);
+        filter.seed = seed;
+        filter
+    }
+
+    /// Create a counting Bloom filter with `num_counters` counters and `num_hashes` hash
+    /// functions.
+    pub fn with_dimensions(num_counters: usize, num_hashes: usize) -> Self {
+        assert!(
+            num_counters > 0 && num_hashes > 0,
+            "Expecting positive number of counters and hashes."
+        );
+        CountingBloomFilter {
+            counters: vec![0; num_counters],
+            num_hashes,
+            seed: 0,
+        }
+    }
+
+    /// Number of counters.
+    pub fn num_counters(&self) -> usize {
+        self.counters.len()
+    }
+
+    /// Number of hash functions.
+    pub fn num_hashes(&self) -> usize {
+        self.num_hashes
+    }
+
+    /// The seed of the hash functions.
+    pub fn seed(&self) -> u64 {
+        self.seed
+    }
+
+    /// Insert an item given by its 64 bit hash.
+    pub fn insert_hash(&mut self, hash: u64) {
+        for i in positions(hash, self.seed, self.num_hashes, self.counters.len()) {
+            self.counters[i] = self.counters[i].saturating_add(1);
+        }
+    }
+
+    /// Remove an item given by its 64 bit hash. Returns `false` (and leaves the filter
+    /// unchanged) if the item is not contained. Saturated counters are never decremented,
+    /// since their true count is unknown. Removing items that were not inserted may cause
+    /// false negatives.
+    pub fn remove_hash(&mut self, hash: u64) -> bool {
+        if !self.contains_hash(hash) {
+            return false;
+        }
+        for i in positions(hash, self.seed, self.num_hashes, self.counters.len()) {
+            if self.counters[i] < u8::MAX {
+                self.counters[i] -= 1;
+            }
+        }
+        true
+    }
+
+    /// Upper bound of the number of times the item with the given hash has been inserted
+    /// (the minimum of its counters).
+    pub fn count_hash(&self, hash: u64) -> u8 {
+        positions(hash, self.seed, self.num_hashes, self.counters.len())
+            .map(|i| self.counters[i])
+            .min()
+            .unwrap()
+    }
+
+    /// Whether an item with the given hash may have been inserted.
+    pub fn contains_hash(&self, hash: u64) -> bool {
+        self.count_hash(hash) > 0
+    }
+
+    /// Insert a hashable item.
+    pub fn insert<T: Hash + ?Sized>(&mut self, item: &T) {
+        self.insert_hash(item_hash(item));
+    }
+
+    /// Remove a hashable item, see `remove_hash`.
+    pub fn remove<T: Hash + ?Sized>(&mut self, item: &T) -> bool {
+        self.remove_hash(item_hash(item))
+    }
+
+    /// Upper bound of the number of times the item has been inserted.
+    pub fn count<T: Hash + ?Sized>(&self, item: &T) -> u8 {
+        self.count_hash(item_hash(item))
+    }
+
+    /// Whether the item may have been inserted.
+    pub fn contains<T: Hash + ?Sized>(&self, item: &T) -> bool {
+        self.contains_hash(item_hash(item))
+    }
+
+    /// Insert all k-mers of the given DNA sequence, see `BloomFilter::insert_kmers`.
+    pub fn insert_kmers(&mut self, text: TextSlice<'_>, k: usize, canonical: bool) {
+        for kmer in kmers(text, k, canonical) {
+            self.insert_hash(kmer);
+        }
+    }
+
+    /// Remove all k-mers of the given DNA sequence, see `remove_hash`.
+    pub fn remove_kmers(&mut self, text: TextSlice<'_>, k: usize, canonical: bool) {
+        for kmer in kmers(text, k, canonical) {
+            self.remove_hash(kmer);
+        }
+    }
+
+    /// Upper bound of the number of times the k-mer has been inserted with `insert_kmers`,
+    /// using the same canonical form.
+    pub fn count_kmer(&self, kmer: TextSlice<'_>, canonical: bool) -> u8 {
+        kmer_code(kmer, canonical).map_or(0, |code| self.count_hash(code))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_dimensions() {
+        // 9.59 bits and 7 hashes per item for 1%
+        assert_eq!(dimensions(1000, 0.01), (9586, 7));
+        let filter = BloomFilter::new(0, 0.5);
+        assert_eq!((filter.num_bits(), filter.num_hashes()), (2, 1));
+    }
+
+    #[test]
+    fn test_false_positive_rate() {
+        let mut filter = BloomFilter::new(10_000, 0.01);
+        for i in 0..10_000u32 {
+            filter.insert(&i);
+        }
+        assert!((0..10_000u32).all(|i| filter.contains(&i)));
+        let false_positives = (10_000..110_000u32).filter(|i| filter.contains(i)).count();
+        let rate = false_positives as f64 / 100_000.0;
+        assert!(rate < 0.015, "rate {}", rate);
+        assert!((filter.fill_ratio() - 0.5).abs() < 0.05);
+    }
+
+    #[test]
+    fn test_kmers() {
+        let text = b"GATTACAGATTNCAGG";
+        let mut filter = BloomFilter::new(100, 0.001);
+        filter.insert_kmers(text, 5, false);
+        assert!(filter.contains_kmer(b"ATTAC", false));
+        assert!(filter.contains_kmer(b"attac", false));
+        assert!(!filter.contains_kmer(b"ATTNC", false));
+        assert!(!filter.contains_kmer(b"GTAAT", false));
+    }
+
+    #[test]
+    fn test_counting() {
+        let mut filter = CountingBloomFilter::new(100, 0.001);
+        filter.insert("a");
+        filter.insert("a");
+        filter.insert("b");
+        assert_eq!(filter.count("a"), 2);
+        assert!(filter.remove("a"));
+        assert_eq!(filter.count("a"), 1);
+        assert!(filter.remove("a"));
+        assert!(!filter.contains("a"));
+        assert!(!filter.remove("a"));
+        assert!(filter.contains("b"));
+
+        filter.insert_kmers(b"ACGTTA", 4, true);
+        assert_eq!(filter.count_kmer(b"TAAC", true), 1);
+        filter.remove_kmers(b"ACGTTA", 4, true);
+        assert_eq!(filter.count_kmer(b"TAAC", true), 0);
+    }
+
+    #[test]
+    fn test_seed() {
+        let filter = |seed| {
+            let mut filter = BloomFilter::with_seed(10, 0.1, seed);
+            filter.insert_kmers(b"ACGGCTGAGATGATCC", 8, true);
+            filter
+        };
+        assert_eq!(filter(7), filter(7));
+        assert_ne!(filter(7), filter(8));
+        assert!(filter(8).contains_kmer(b"GCTGAGAT", true));
+        // the same on all platforms
+        let bits = filter(0);
+        let set = (0..bits.num_bits())
+            .filter(|&i| bits.bits.get_bit(i as u64))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            set,
+            [
+                0, 2, 3, 6, 8, 10, 12, 16, 18, 19, 21, 22, 23, 25, 26, 28, 29, 31, 35, 36, 42, 44,
+                47
+            ]
+        );
+
+        let mut counting = CountingBloomFilter::with_seed(10, 0.1, 7);
+        counting.insert_kmers(b"ACGGCTGAGATGATCC", 8, true);
+        assert_eq!(counting.seed(), 7);
+        assert_eq!(counting.count_kmer(b"GCTGAGAT", true), 1);
+    }
+}
@@ -7,11 +7,12 @@
 //! The implementation is based on the lecture notes
 //! "Algorithmen auf Sequenzen", Kopczynski, Marschall, Martin and Rahmann, 2008 - 2015.
 
+use std::io;
 use std::iter::repeat;
 
 use crate::alphabets::Alphabet;
 use crate::data_structures::suffix_array::RawSuffixArraySlice;
-use crate::utils::prescan;
+use crate::utils::{parallel, prescan};
 
 pub type BWT = Vec<u8>;
 pub type BWTSlice = [u8];
@@ -48,6 +49,32 @@ pub fn bwt(text: &[u8], pos: RawSuffixArraySlice) -> BWT {
     bwt
 }
 
+/// Calculate Burrows-Wheeler-Transform like `bwt`, using the given number of threads (`0` for all
+/// available cores, see `utils::parallel`).
+///
+/// # Example
+///
+/// ```
+/// use bio::data_structures::bwt::{bwt, bwt_parallel};
+/// use bio::data_structures::suffix_array::suffix_array;
+/// let text = b"GCCTTAACATTATTACGCCTA$";
+/// let pos = suffix_array(text);
+/// assert_eq!(bwt_parallel(text, &pos, 4), bwt(text, &pos));
+/// ```
+pub fn bwt_parallel(text: &[u8], pos: RawSuffixArraySlice, threads: usize) -> BWT {
+    assert_eq!(text.len(), pos.len());
+    let n = text.len();
+    let mut bwt: BWT = repeat(0).take(n).collect();
+    parallel::for_each_chunk_mut(&mut bwt, threads, |offset, chunk| {
+        for (r, b) in chunk.iter_mut().enumerate() {
+            let p = pos[offset + r];
+            *b = if p > 0 { text[p - 1] } else { text[n - 1] };
+        }
+    });
+
+    bwt
+}
+
 /// Calculate the inverse of a BWT of length n, which is the original text.
 /// Complexity: O(n).
 ///
@@ -72,11 +99,43 @@ pub fn invert_bwt(bwt: &BWTSlice) -> Vec<u8> {
     inverse
 }
 
+/// Write a BWT as plain text, terminated by a newline.
+///
+/// # Example
+///
+/// ```
+/// use bio::data_structures::bwt::{bwt, read_bwt, write_bwt};
+/// use bio::data_structures::suffix_array::suffix_array;
+///
+/// let text = b"GCCTTAACATTATTACGCCTA$";
+/// let bwt = bwt(text, &suffix_array(text));
+/// let mut buf = Vec::new();
+/// write_bwt(&bwt, &mut buf).unwrap();
+/// assert_eq!(buf, b"ATTATTCAGGACCC$CTTTCAA\n");
+/// assert_eq!(read_bwt(&buf[..]).unwrap(), bwt);
+/// ```
+pub fn write_bwt<W: io::Write>(bwt: &BWTSlice, mut writer: W) -> io::Result<()> {
+    writer.write_all(bwt)?;
+    writer.write_all(b"\n")?;
+    writer.flush()
+}
+
+/// Read a BWT from plain text (see `write_bwt`). Trailing line breaks (`\n` or `\r\n`)
+/// are removed.
+pub fn read_bwt<R: io::Read>(mut reader: R) -> io::Result<BWT> {
+    let mut bwt = Vec::new();
+    reader.read_to_end(&mut bwt)?;
+    while bwt.last().map_or(false, |&c| c == b'\n' || c == b'\r') {
+        bwt.pop();
+    }
+    Ok(bwt)
+}
+
 /// An occurrence array implementation.
 #[derive(Default, Clone, Eq, PartialEq, Ord, PartialOrd, Hash, Debug, Serialize, Deserialize)]
 pub struct Occ {
-    occ: Vec<Vec<usize>>,
-    k: u32,
+    pub(crate) occ: Vec<Vec<usize>>,
+    pub(crate) k: u32,
 }
 
 impl Occ {
@@ -91,17 +150,11 @@ impl Occ {
     ///
     /// * `bwt` - the BWT
     /// * `k` - the sampling rate: every k-th entry will be stored
+    /// * `alphabet` - the alphabet of the BWT; the sentinel `$` is added automatically,
+    ///   other sentinel symbols (e.g. `0x00`) have to be part of the alphabet
     pub fn new(bwt: &BWTSlice, k: u32, alphabet: &Alphabet) -> Self {
         let n = bwt.len();
-        let m = alphabet
-            .max_symbol()
-            .expect("Expecting non-empty alphabet.") as usize
-            + 1;
-        let mut alpha = alphabet.symbols.iter().collect::<Vec<usize>>();
-        // include sentinel '$'
-        if (b'$' as usize) < m && !alphabet.is_word(b"$") {
-            alpha.push(b'$' as usize);
-        }
+        let (m, alpha) = Occ::symbols(alphabet);
         let mut occ: Vec<Vec<usize>> = vec![Vec::new(); m];
         let mut curr_occ = vec![0usize; m];
 
@@ -124,6 +177,71 @@ impl Occ {
         Occ { occ, k }
     }
 
+    /// Whether occurrences of the given symbol are counted, i.e. whether it belongs to the
+    /// alphabet the occ array was built with (or is the sentinel `$`).
+    pub fn contains_symbol(&self, a: u8) -> bool {
+        self.occ
+            .get(a as usize)
+            .map_or(false, |occ| !occ.is_empty())
+    }
+
+    /// Calculate occ array like `new`, splitting the BWT into chunks that are processed in
+    /// parallel.
+    ///
+    /// # Arguments
+    ///
+    /// * `bwt` - the BWT
+    /// * `k` - the sampling rate: every k-th entry will be stored
+    /// * `alphabet` - the alphabet of the BWT (see `new`)
+    /// * `threads` - the number of threads to use, `0` for all available cores
+    ///   (see `utils::parallel`)
+    pub fn new_parallel(bwt: &BWTSlice, k: u32, alphabet: &Alphabet, threads: usize) -> Self {
+        let (m, alpha) = Occ::symbols(alphabet);
+        // chunks start at sampled positions, counts are relative to the chunk start
+        let chunks = parallel::map_chunks(bwt, threads, k as usize, |offset, chunk| {
+            let mut curr_occ = vec![0usize; m];
+            let mut samples = vec![Vec::with_capacity(chunk.len() / k as usize + 1); alpha.len()];
+            for (i, &c) in chunk.iter().enumerate() {
+                curr_occ[c as usize] += 1;
+
+                if (offset + i) % k as usize == 0 {
+                    for (sample, &a) in samples.iter_mut().zip(&alpha) {
+                        sample.push(curr_occ[a]);
+                    }
+                }
+            }
+            (samples, curr_occ)
+        });
+
+        let mut occ: Vec<Vec<usize>> = vec![Vec::new(); m];
+        let mut total_occ = vec![0usize; m];
+        for (samples, curr_occ) in chunks {
+            for (sample, &a) in samples.into_iter().zip(&alpha) {
+                occ[a].extend(sample.into_iter().map(|count| count + total_occ[a]));
+            }
+            for (total, count) in total_occ.iter_mut().zip(curr_occ) {
+                *total += count;
+            }
+        }
+
+        Occ { occ, k }
+    }
+
+    /// The number of occ rows (maximum symbol + 1) and the symbols to count, including the
+    /// sentinel `$`.
+    fn symbols(alphabet: &Alphabet) -> (usize, Vec<usize>) {
+        let m = alphabet
+            .max_symbol()
+            .expect("Expecting non-empty alphabet.") as usize
+            + 1;
+        let mut alpha = alphabet.symbols.iter().collect::<Vec<usize>>();
+        // include sentinel '$'
+        if (b'$' as usize) < m && !alphabet.is_word(b"$") {
+            alpha.push(b'$' as usize);
+        }
+        (m, alpha)
+    }
+
     /// Get occurrence count of symbol a in BWT[..r+1].
     /// Complexity: O(k).
     pub fn get(&self, bwt: &BWTSlice, r: usize, a: u8) -> usize {
@@ -214,7 +332,7 @@ pub fn bwtfind(bwt: &BWTSlice, alphabet: &Alphabet) -> BWTFind {
 
 #[cfg(test)]
 mod tests {
-    use super::{bwt, bwtfind, invert_bwt, Occ};
+    use super::{bwt, bwt_parallel, bwtfind, invert_bwt, read_bwt, write_bwt, Occ};
     use crate::alphabets::dna;
     use crate::alphabets::Alphabet;
     use crate::data_structures::suffix_array::suffix_array;
@@ -239,6 +357,17 @@ mod tests {
         assert_eq!(inverse, text);
     }
 
+    #[test]
+    fn test_bwt_text_roundtrip() {
+        let text = b"cabca$";
+        let bwt = bwt(text, &suffix_array(text));
+        let mut buf = Vec::new();
+        write_bwt(&bwt, &mut buf).unwrap();
+        assert_eq!(read_bwt(&buf[..]).unwrap(), bwt);
+        assert_eq!(read_bwt(&b"ac$ab\r\n"[..]).unwrap(), b"ac$ab");
+        assert_eq!(invert_bwt(&read_bwt(&buf[..]).unwrap()), text);
+    }
+
     #[test]
     fn test_occ() {
         let bwt = vec![1u8, 3u8, 3u8, 1u8, 2u8, 0u8];
@@ -249,6 +378,23 @@ mod tests {
         assert_eq!(occ.get(&bwt, 4, 3u8), 2);
     }
 
+    #[test]
+    fn test_parallel() {
+        let text = b"GCCTTAACATTATTACGCCTAGCCTTAACATTATTACGCCTA$";
+        let alphabet = dna::n_alphabet();
+        let sa = suffix_array(text);
+        let bwt = bwt(text, &sa);
+        for threads in 1..6 {
+            assert_eq!(bwt_parallel(text, &sa, threads), bwt);
+            for k in [1, 3, 32] {
+                assert_eq!(
+                    Occ::new_parallel(&bwt, k, &alphabet, threads),
+                    Occ::new(&bwt, k, &alphabet)
+                );
+            }
+        }
+    }
+
     #[test]
     fn test_occwm() {
         let text = b"GCCTTAACATTATTACGCCTA$";
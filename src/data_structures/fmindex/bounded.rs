@@ -0,0 +1,188 @@
+//! Construction of FM-indices under a memory limit, e.g. for running pattern matching in the
+//! browser (see the `wasm` feature), where exceeding the available memory aborts the whole
+//! program instead of returning an error.
+//!
+//! The memory needed for construction and for the final index is estimated beforehand from the
+//! text length and the alphabet. The sampling rates of the occurrence array and the suffix array
+//! are chosen as small (i.e. queries as fast) as possible while staying within the limit. If
+//! even the sparsest sampling does not fit, an error is returned before anything is allocated.
+//!
+//! # Example
+//!
+//! ```
+//! use bio::alphabets::dna;
+//! use bio::data_structures::fmindex::bounded;
+//! use bio::data_structures::fmindex::{BackwardSearchResult, FMIndex, FMIndexable};
+//! use bio::data_structures::suffix_array::SuffixArray;
+//!
+//! let text = b"GCCTTAACATTATTACGCCTA$";
+//! let alphabet = dna::n_alphabet();
+//! let sa = bounded::build(text, &alphabet, 2048).unwrap();
+//! assert!(bounded::estimate(text.len(), &alphabet, sa.sampling_rate()).construction <= 2048);
+//!
+//! let fm = FMIndex::new(sa.bwt(), sa.less(), sa.occ());
+//! match fm.backward_search(b"TTA".iter()) {
+//!     BackwardSearchResult::Complete(interval) => assert_eq!(interval.occ(&sa).len(), 3),
+//!     _ => panic!("pattern not found"),
+//! }
+//!
+//! // a limit that cannot be met
+//! assert!(bounded::build(text, &alphabet, 100).is_err());
+//! ```
+
+use std::mem::size_of;
+
+use crate::alphabets::Alphabet;
+use crate::data_structures::bwt::{bwt, less, Less, Occ, BWT};
+use crate::data_structures::fmindex::{Error, Result};
+use crate::data_structures::suffix_array::{suffix_array, SampledSuffixArray, SuffixArray};
+
+/// The largest sampling rate considered by `sampling_rate`.
+pub const MAX_SAMPLING_RATE: usize = 1024;
+
+/// Estimated memory in bytes for building and storing an index (see `estimate`).
+#[derive(
+    Default, Copy, Clone, Eq, PartialEq, Ord, PartialOrd, Hash, Debug, Serialize, Deserialize,
+)]
+pub struct MemoryEstimate {
+    /// Peak memory during construction, including the final index.
+    pub construction: usize,
+    /// Memory of the final index (BWT, less and occurrence arrays and sampled suffix array).
+    pub index: usize,
+}
+
+/// Estimate the memory needed for building the index of a text of the given length, with the
+/// given sampling rate for both the occurrence array and the suffix array. The estimate is an
+/// upper bound for texts with a single sentinel, and does not include the text itself.
+pub fn estimate(len: usize, alphabet: &Alphabet, sampling_rate: usize) -> MemoryEstimate {
+    estimate_with_width(len, alphabet, sampling_rate, 1)
+}
+
+/// Estimate like `estimate`, with the given width in bytes of the symbols during suffix array
+/// construction (larger than 1 with many sentinels, see `suffix_array::suffix_array`).
+fn estimate_with_width(
+    len: usize,
+    alphabet: &Alphabet,
+    sampling_rate: usize,
+    width: usize,
+) -> MemoryEstimate {
+    let word = size_of::<usize>();
+    let samples = len / sampling_rate + 1;
+    // occ counts the symbols of the alphabet and the sentinel
+    let occ = (alphabet.len() + 1) * samples * word;
+    let less = (alphabet.max_symbol().map_or(0, |a| a as usize) + 2) * word;
+    let index = len + less + occ + samples * word;
+
+    // SAIS needs the transformed text, the suffix array, the LMS positions (at most half of
+    // the text) and their reduced text positions, plus at most len words in its recursion
+    let sais = len * width + len * word * 9 / 2;
+    // afterwards, the suffix array is kept until it has been sampled
+    let sampling = len * word + index;
+
+    MemoryEstimate {
+        construction: sais.max(sampling),
+        index,
+    }
+}
+
+/// The smallest power of two up to `MAX_SAMPLING_RATE` as sampling rate, such that building the
+/// index of a text of the given length fits into `max_bytes` (see `estimate`).
+pub fn sampling_rate(len: usize, alphabet: &Alphabet, max_bytes: usize) -> Option<usize> {
+    sampling_rate_with_width(len, alphabet, max_bytes, 1)
+}
+
+fn sampling_rate_with_width(
+    len: usize,
+    alphabet: &Alphabet,
+    max_bytes: usize,
+    width: usize,
+) -> Option<usize> {
+    (0..)
+        .map(|i| 1 << i)
+        .take_while(|&rate| rate <= MAX_SAMPLING_RATE)
+        .find(|&rate| estimate_with_width(len, alphabet, rate, width).construction <= max_bytes)
+}
+
+/// Build the BWT, less and occurrence arrays and the sampled suffix array of the given text
+/// (ended by a sentinel, see `suffix_array::suffix_array`) using at most about `max_bytes` of
+/// memory, returning the sampled suffix array, which owns the other parts. The FM-index can be
+/// created from them with `FMIndex::new(sa.bwt(), sa.less(), sa.occ())`.
+///
+/// Returns `Error::MemoryLimitExceeded` without allocating if the limit cannot be met.
+pub fn build(
+    text: &[u8],
+    alphabet: &Alphabet,
+    max_bytes: usize,
+) -> Result<SampledSuffixArray<BWT, Less, Occ>> {
+    let sentinels = text.iter().filter(|&&a| a == text[text.len() - 1]).count();
+    let width = match alphabet.len() + sentinels {
+        a if a <= u8::MAX as usize => 1,
+        a if a <= u16::MAX as usize => 2,
+        a if a <= u32::MAX as usize => 4,
+        _ => 8,
+    };
+    let rate = sampling_rate_with_width(text.len(), alphabet, max_bytes, width).ok_or(
+        Error::MemoryLimitExceeded {
+            required: estimate_with_width(text.len(), alphabet, MAX_SAMPLING_RATE, width)
+                .construction,
+            limit: max_bytes,
+        },
+    )?;
+
+    let sa = suffix_array(text);
+    let bwt = bwt(text, &sa);
+    let less = less(&bwt, alphabet);
+    let occ = Occ::new(&bwt, rate as u32, alphabet);
+    Ok(sa.sample(text, bwt, less, occ, rate))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::alphabets::dna;
+    use crate::data_structures::fmindex::{BackwardSearchResult, FMIndex, FMIndexable};
+
+    #[test]
+    fn test_sampling_rate() {
+        let alphabet = dna::n_alphabet();
+        let len = 100_000;
+        let rate = sampling_rate(len, &alphabet, 6 * len * size_of::<usize>()).unwrap();
+        assert!(rate > 1);
+        assert!(estimate(len, &alphabet, rate).construction <= 6 * len * size_of::<usize>());
+        assert!(estimate(len, &alphabet, rate / 2).construction > 6 * len * size_of::<usize>());
+        // smaller limits need sparser sampling
+        let index = estimate(len, &alphabet, rate).index;
+        assert!(estimate(len, &alphabet, rate * 2).index < index);
+        // the suffix array construction alone does not fit
+        assert_eq!(sampling_rate(len, &alphabet, len), None);
+    }
+
+    #[test]
+    fn test_build() {
+        let text = b"ACGTACGTTTGACCA$GGTACAGT$";
+        let alphabet = dna::n_alphabet();
+        let full = suffix_array(text);
+        for &rate in &[1, 2, 4] {
+            let max_bytes = estimate(text.len(), &alphabet, rate).construction;
+            let sa = build(text, &alphabet, max_bytes).unwrap();
+            assert_eq!(sa.sampling_rate(), rate);
+            for i in 0..full.len() {
+                assert_eq!(sa.get(i), full.get(i));
+            }
+            let fm = FMIndex::new(sa.bwt(), sa.less(), sa.occ());
+            let mut occ = match fm.backward_search(b"GTAC".iter()) {
+                BackwardSearchResult::Complete(interval) => interval.occ(&sa),
+                _ => Vec::new(),
+            };
+            occ.sort_unstable();
+            assert_eq!(occ, [2, 17]);
+        }
+        assert_eq!(
+            build(text, &alphabet, 100),
+            Err(Error::MemoryLimitExceeded {
+                required: estimate(text.len(), &alphabet, MAX_SAMPLING_RATE).construction,
+                limit: 100
+            })
+        );
+    }
+}
@@ -0,0 +1,2088 @@
+// Copyright 2014-2016 Johannes Köster, Taylor Cramer.
+// Licensed under the MIT license (http://opensource.org/licenses/MIT)
+// This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! The [Full-text index in Minute space index (FM-index)](https://doi.org/10.1109/SFCS.2000.892127) and
+//! the FMD-Index for finding suffix array intervals matching a given pattern in linear time.
+//!
+//! # Examples
+//!
+//! ## Generate
+//!
+//! ```
+//! use bio::alphabets::dna;
+//! use bio::data_structures::bwt::{bwt, less, Occ};
+//! use bio::data_structures::fmindex::{FMIndex, FMIndexable};
+//! use bio::data_structures::suffix_array::suffix_array;
+//!
+//! let text = b"GCCTTAACATTATTACGCCTA$";
+//! let alphabet = dna::n_alphabet();
+//! let sa = suffix_array(text);
+//! let bwt = bwt(text, &sa);
+//! let less = less(&bwt, &alphabet);
+//! let occ = Occ::new(&bwt, 3, &alphabet);
+//! let fm = FMIndex::new(&bwt, &less, &occ);
+//! ```
+//!
+//! ## Enclose in struct
+//!
+//! `FMIndex` was designed to not forcibly own the BWT and auxiliary data structures.
+//! It can take a reference (`&`), owned structs or any of the more complex pointer types.
+//!
+//! ```
+//! use bio::alphabets::dna;
+//! use bio::data_structures::bwt::{bwt, less, Less, Occ, BWT};
+//! use bio::data_structures::fmindex::{FMIndex, FMIndexable};
+//! use bio::data_structures::suffix_array::suffix_array;
+//! use bio::utils::TextSlice;
+//!
+//! pub struct Example {
+//!     fmindex: FMIndex<BWT, Less, Occ>,
+//! }
+//!
+//! impl Example {
+//!     pub fn new(text: TextSlice) -> Self {
+//!         let alphabet = dna::n_alphabet();
+//!         let sa = suffix_array(text);
+//!         let bwt = bwt(text, &sa);
+//!         let less = less(&bwt, &alphabet);
+//!         let occ = Occ::new(&bwt, 3, &alphabet);
+//!         let fm = FMIndex::new(bwt, less, occ);
+//!         Example { fmindex: fm }
+//!     }
+//! }
+//! ```
+
+use std::borrow::Borrow;
+use std::iter::DoubleEndedIterator;
+use std::marker::PhantomData;
+
+use crate::alphabets::{dna, Alphabet};
+use crate::data_structures::bwt::{bwt, less, BWTSlice, Less, Occ, BWT};
+use crate::data_structures::record_boundaries::RecordBoundaries;
+use crate::data_structures::rlbwt::RLBWT;
+use crate::data_structures::suffix_array::{suffix_array, RawSuffixArray, SuffixArray};
+use crate::data_structures::text_collection::{TextCollection, SENTINEL};
+use crate::data_structures::wavelet_tree::WaveletTree;
+use crate::seq_analysis::dust::{Dust, MaskedIntervals};
+use std::mem::swap;
+use thiserror::Error;
+
+pub mod bounded;
+pub mod persistent;
+
+pub use self::persistent::{load, load_mapped, read, save, write, PersistentFMIndex};
+
+/// Errors that can occur when constructing an index.
+#[derive(
+    Error, Copy, Clone, Eq, PartialEq, Ord, PartialOrd, Hash, Debug, Serialize, Deserialize,
+)]
+pub enum Error {
+    #[error("text of length {} does not match BWT of length {}", text_len, bwt_len)]
+    LengthMismatch { text_len: usize, bwt_len: usize },
+    #[error("text has to end with a sentinel symbol")]
+    MissingSentinel,
+    #[error(
+        "sentinel '{}' has to be lexicographically smaller than all other symbols",
+        char::from(*sentinel)
+    )]
+    InvalidSentinel { sentinel: u8 },
+    #[error(
+        "invalid symbol '{}' at position {} (expecting DNA alphabet with N and sentinel)",
+        char::from(*symbol),
+        pos
+    )]
+    InvalidSymbol { symbol: u8, pos: usize },
+    #[error(
+        "text consists of {} sentinel-separated blocks, expecting pairs of text and reverse complement",
+        blocks
+    )]
+    OddBlockCount { blocks: usize },
+    #[error("block {} is not the reverse complement of the preceding block", block)]
+    NotReverseComplement { block: usize },
+    #[error(
+        "building the index needs at least {} bytes, exceeding the limit of {} bytes",
+        required,
+        limit
+    )]
+    MemoryLimitExceeded { required: usize, limit: usize },
+}
+
+pub type Result<T, E = Error> = std::result::Result<T, E>;
+
+/// A suffix array interval.
+#[derive(
+    Default, Copy, Clone, Eq, PartialEq, Ord, PartialOrd, Hash, Debug, Serialize, Deserialize,
+)]
+pub struct Interval {
+    pub lower: usize,
+    pub upper: usize,
+}
+
+impl Interval {
+    pub fn occ<SA: SuffixArray>(&self, sa: &SA) -> Vec<usize> {
+        self.occ_iter(sa).collect()
+    }
+
+    /// Iterate over the text positions of the interval without allocating.
+    pub fn occ_iter<'a, SA: SuffixArray>(&self, sa: &'a SA) -> impl Iterator<Item = usize> + 'a {
+        (self.lower..self.upper)
+            .map(move |pos| sa.get(pos).expect("Interval out of range of suffix array"))
+    }
+
+    /// Iterate over the occurrences of the interval in a concatenation of multiple records
+    /// (e.g. built with `TextCollection`), as pairs of record index and offset within the
+    /// record. Occurrences starting at separators are skipped.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use bio::alphabets::dna;
+    /// use bio::data_structures::fmindex::{FMIndex, FMIndexable};
+    ///
+    /// let texts = [b"GCCTTAACA".to_vec(), b"TTATTACGCCTA".to_vec()];
+    /// let (fm, sa, collection) = FMIndex::from_texts(&texts, &dna::alphabet(), 3);
+    /// let boundaries = collection.boundaries();
+    ///
+    /// let interval = fm.backward_search(b"TTA".iter()).interval().unwrap();
+    /// let mut hits: Vec<_> = interval.occ_records(&sa, &boundaries).collect();
+    /// hits.sort();
+    /// assert_eq!(hits, [(0, 3), (1, 0), (1, 3)]);
+    /// ```
+    pub fn occ_records<'a, SA: SuffixArray>(
+        &self,
+        sa: &'a SA,
+        boundaries: &'a RecordBoundaries,
+    ) -> impl Iterator<Item = (usize, usize)> + 'a {
+        self.occ_iter(sa)
+            .filter_map(move |pos| boundaries.resolve(pos))
+    }
+
+    /// Iterate over the text positions of the interval whose occurrence (of the given length)
+    /// does not overlap any masked interval, e.g. to skip seed hits in low-complexity regions
+    /// (see `FMIndex::from_texts_masked`).
+    pub fn occ_unmasked<'a, SA: SuffixArray>(
+        &self,
+        sa: &'a SA,
+        masked: &'a MaskedIntervals,
+        len: usize,
+    ) -> impl Iterator<Item = usize> + 'a {
+        self.occ_iter(sa)
+            .filter(move |&pos| masked.overlap(pos..pos + len) == 0)
+    }
+
+    /// Number of suffix array entries in the interval, i.e. the number of occurrences.
+    pub fn len(&self) -> usize {
+        self.upper - self.lower
+    }
+
+    /// Whether the interval is empty.
+    pub fn is_empty(&self) -> bool {
+        self.upper == self.lower
+    }
+}
+
+/// This enum represents the potential result states
+/// from a backward_search in the fm index.  The
+/// potential variants of the enum are:
+/// Complete(Interval) — the query matched completely. The interval is the
+/// range of suffix array indices matching the query string.
+/// Partial(Intarval, usize) - some suffix of the query matched, but not the whole query.
+/// The interval returned is the range of suffix array indices for the maximal
+/// matching suffix, and the `usize` is the length of the maximal matching suffix.
+/// Absent - None suffix of the pattern matched in the text.
+#[derive(Copy, Clone, Eq, PartialEq, Ord, PartialOrd, Hash, Debug, Serialize, Deserialize)]
+pub enum BackwardSearchResult {
+    Complete(Interval),
+    Partial(Interval, usize),
+    Absent,
+}
+
+impl BackwardSearchResult {
+    /// The suffix array interval of the complete pattern or of its maximal matching suffix,
+    /// if any.
+    pub fn interval(&self) -> Option<Interval> {
+        match *self {
+            BackwardSearchResult::Complete(interval) => Some(interval),
+            BackwardSearchResult::Partial(interval, _) => Some(interval),
+            BackwardSearchResult::Absent => None,
+        }
+    }
+
+    /// Number of pattern symbols (counted from the end of the pattern) that were matched
+    /// before the search interval became empty, given the length of the searched pattern.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use bio::alphabets::dna;
+    /// use bio::data_structures::bwt::{bwt, less, Occ};
+    /// use bio::data_structures::fmindex::{FMIndex, FMIndexable};
+    /// use bio::data_structures::suffix_array::suffix_array;
+    ///
+    /// let text = b"GATTACA$";
+    /// let sa = suffix_array(text);
+    /// let bwt = bwt(text, &sa);
+    /// let alphabet = dna::n_alphabet();
+    /// let less = less(&bwt, &alphabet);
+    /// let occ = Occ::new(&bwt, 3, &alphabet);
+    /// let fm = FMIndex::new(&bwt, &less, &occ);
+    ///
+    /// let pattern = b"GTACA";
+    /// let result = fm.backward_search(pattern.iter());
+    /// assert_eq!(result.matched_len(pattern.len()), 4);
+    /// let interval = result.interval().unwrap();
+    /// assert_eq!(interval.len(), 1);
+    /// assert_eq!(interval.occ_iter(&sa).next(), Some(3));
+    /// ```
+    pub fn matched_len(&self, pattern_len: usize) -> usize {
+        match *self {
+            BackwardSearchResult::Complete(_) => pattern_len,
+            BackwardSearchResult::Partial(_, len) => len,
+            BackwardSearchResult::Absent => 0,
+        }
+    }
+}
+
+/// Backward search over an FM-index given by its length and occurrence and less functions
+/// (see `FMIndexable::backward_search`).
+fn backward_search<'b, P, O, L>(len: usize, occ: O, less: L, pattern: P) -> BackwardSearchResult
+where
+    P: Iterator<Item = &'b u8> + DoubleEndedIterator,
+    O: Fn(usize, u8) -> usize,
+    L: Fn(u8) -> usize,
+{
+    let (mut l, mut r) = (0, len - 1);
+    // to keep track of the last "valid" search interval if
+    // there is any valid suffix match.
+    let (mut pl, mut pr) = (l, r);
+
+    // the length of the suffix we have been able to match
+    // successfully
+    let mut matched_len = 0;
+    // track if we exit early or not due to an empty
+    // search interval.
+    let mut complete_match = true;
+
+    for &a in pattern.rev() {
+        let less = less(a);
+        pl = l;
+        pr = r;
+        l = less + if l > 0 { occ(l - 1, a) } else { 0 };
+        r = less + occ(r, a) - 1;
+
+        // The symbol was not found if we end up with an empty interval.
+        // Terminate the LF-mapping process. In this case, also mark that
+        // we do not have a complete match.
+        if l > r {
+            complete_match = false;
+            break;
+        }
+        matched_len += 1;
+    }
+
+    // if we matched at least 1 character
+    if matched_len > 0 {
+        // if we matched the full pattern length we
+        // have a complete match
+        if complete_match {
+            BackwardSearchResult::Complete(Interval {
+                lower: l,
+                upper: r + 1,
+            })
+        } else {
+            // if we matched less than the full pattern length, we have
+            // a partial suffix match
+            BackwardSearchResult::Partial(
+                Interval {
+                    lower: pl,
+                    upper: pr + 1,
+                },
+                matched_len,
+            )
+        }
+    } else {
+        // if we matched nothing we have an absent result
+        BackwardSearchResult::Absent
+    }
+}
+
+pub trait FMIndexable {
+    /// Get occurrence count of symbol a in BWT[..r+1].
+    fn occ(&self, r: usize, a: u8) -> usize;
+    /// Also known as
+    fn less(&self, a: u8) -> usize;
+
+    /// Provide a reference to the underlying BWT.
+    ///
+    /// # Panics
+    ///
+    /// If the index does not keep the plain BWT, like `WaveletFMIndex` and `RLFMIndex`.
+    #[deprecated(
+        since = "2.1.0",
+        note = "not every index keeps the plain BWT, use `len` or the accessors of the index type"
+    )]
+    fn bwt(&self) -> &BWT {
+        panic!("This FM-index does not keep the plain BWT.")
+    }
+
+    /// Length of the BWT, i.e. of the indexed text including sentinels.
+    #[allow(deprecated)]
+    fn len(&self) -> usize {
+        self.bwt().len()
+    }
+
+    /// Whether the BWT is empty.
+    fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Perform backward search, yielding `BackwardSearchResult` enum that
+    /// contains the suffix array interval denoting exact occurrences of the given pattern
+    /// of length m in the text if it exists, or the suffix array interval denoting the
+    /// exact occurrences of a maximal matching suffix of the given pattern if it does
+    /// not exist.  If none of the pattern can be matched, the `BackwardSearchResult` is
+    /// `Absent`. The number of matched pattern symbols can be obtained via
+    /// `BackwardSearchResult::matched_len`. The search does not allocate.
+    /// Complexity: O(m).
+    ///
+    /// # Arguments
+    ///
+    /// * `pattern` - the pattern to search
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use bio::alphabets::dna;
+    /// use bio::data_structures::bwt::{bwt, less, Occ};
+    /// use bio::data_structures::fmindex::{BackwardSearchResult, FMIndex, FMIndexable};
+    /// use bio::data_structures::suffix_array::suffix_array;
+    ///
+    /// let text = b"GCCTTAACATTATTACGCCTA$";
+    /// let alphabet = dna::n_alphabet();
+    /// let sa = suffix_array(text);
+    /// let bwt = bwt(text, &sa);
+    /// let less = less(&bwt, &alphabet);
+    /// let occ = Occ::new(&bwt, 3, &alphabet);
+    /// let fm = FMIndex::new(&bwt, &less, &occ);
+    ///
+    /// let pattern = b"TTA";
+    /// let bsr = fm.backward_search(pattern.iter());
+    ///
+    /// let positions = match bsr {
+    ///     BackwardSearchResult::Complete(sai) => sai.occ(&sa),
+    ///     BackwardSearchResult::Partial(sai, _l) => sai.occ(&sa),
+    ///     BackwardSearchResult::Absent => Vec::<usize>::new(),
+    /// };
+    ///
+    /// assert_eq!(positions, [3, 12, 9]);
+    /// ```
+    fn backward_search<'b, P: Iterator<Item = &'b u8> + DoubleEndedIterator>(
+        &self,
+        pattern: P,
+    ) -> BackwardSearchResult {
+        backward_search(self.len(), |r, a| self.occ(r, a), |a| self.less(a), pattern)
+    }
+
+    /// Find all suffix array intervals of strings that differ from the given pattern
+    /// of length m in at most k positions (Hamming distance), by backward search with bounded
+    /// backtracking: at each pattern position, all symbols of the alphabet are tried, while
+    /// branches whose interval becomes empty or that exceed k mismatches are pruned.
+    /// This is the core of seed search in short-read aligners (e.g. BWA, Bowtie).
+    /// Returns pairs of non-empty interval and number of mismatches, sorted by the number of
+    /// mismatches. Intervals are disjoint, since each denotes a different string.
+    /// Pattern symbols outside of the alphabet can only be matched by a mismatch.
+    /// Complexity: O(m * min(m, |A|^k * m^k)) in the worst case, with A being the alphabet.
+    ///
+    /// # Arguments
+    ///
+    /// * `pattern` - the pattern to search
+    /// * `k` - the maximum number of mismatches
+    /// * `alphabet` - the symbols to substitute, which have to be part of the alphabet the index
+    ///   was built with (the sentinel should be excluded)
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use bio::alphabets::dna;
+    /// use bio::data_structures::fmindex::{FMIndex, FMIndexable};
+    ///
+    /// let (fm, sa, _) = FMIndex::from_texts(&[b"GCCTTAACATTATTACGCCTA"], &dna::alphabet(), 3);
+    ///
+    /// let matches = fm.backward_search_with_mismatches(b"TTAC", 1, &dna::alphabet());
+    /// let mut hits = matches
+    ///     .iter()
+    ///     .flat_map(|(interval, mismatches)| {
+    ///         interval.occ(&sa).into_iter().map(move |pos| (pos, *mismatches))
+    ///     })
+    ///     .collect::<Vec<_>>();
+    /// hits.sort();
+    /// // TTAA, TAAC, TTAT and TTAC (exact)
+    /// assert_eq!(hits, [(3, 1), (4, 1), (9, 1), (12, 0)]);
+    /// ```
+    fn backward_search_with_mismatches(
+        &self,
+        pattern: &[u8],
+        k: usize,
+        alphabet: &Alphabet,
+    ) -> Vec<(Interval, usize)> {
+        let symbols = alphabet.symbols.iter().map(|a| a as u8).collect::<Vec<_>>();
+        let extend = |interval: &Interval, a: u8| {
+            let less = self.less(a);
+            Interval {
+                lower: less
+                    + if interval.lower > 0 {
+                        self.occ(interval.lower - 1, a)
+                    } else {
+                        0
+                    },
+                upper: less + self.occ(interval.upper - 1, a),
+            }
+        };
+
+        let mut matches = Vec::new();
+        // pending branches: length of the unmatched pattern prefix, interval and mismatches
+        let mut stack = vec![(
+            pattern.len(),
+            Interval {
+                lower: 0,
+                upper: self.len(),
+            },
+            0,
+        )];
+        while let Some((i, interval, mismatches)) = stack.pop() {
+            if i == 0 {
+                matches.push((interval, mismatches));
+                continue;
+            }
+            let a = pattern[i - 1];
+            for &b in &symbols {
+                let cost = mismatches + (a != b) as usize;
+                if cost > k {
+                    continue;
+                }
+                let next = extend(&interval, b);
+                if !next.is_empty() {
+                    stack.push((i - 1, next, cost));
+                }
+            }
+        }
+        matches.sort_by_key(|&(interval, mismatches)| (mismatches, interval));
+
+        matches
+    }
+}
+
+/// The Fast Index in Minute space (FM-Index, Ferragina and Manzini, 2000) for finding suffix array
+/// intervals matching a given pattern.
+#[derive(
+    Default, Copy, Clone, Eq, PartialEq, Ord, PartialOrd, Hash, Debug, Serialize, Deserialize,
+)]
+pub struct FMIndex<DBWT: Borrow<BWT>, DLess: Borrow<Less>, DOcc: Borrow<Occ>> {
+    bwt: DBWT,
+    less: DLess,
+    occ: DOcc,
+}
+
+impl<DBWT: Borrow<BWT>, DLess: Borrow<Less>, DOcc: Borrow<Occ>> FMIndexable
+    for FMIndex<DBWT, DLess, DOcc>
+{
+    fn occ(&self, r: usize, a: u8) -> usize {
+        self.occ.borrow().get(self.bwt.borrow(), r, a)
+    }
+    fn less(&self, a: u8) -> usize {
+        self.less.borrow()[a as usize]
+    }
+    fn bwt(&self) -> &BWT {
+        self.bwt.borrow()
+    }
+    fn len(&self) -> usize {
+        self.bwt.borrow().len()
+    }
+}
+
+impl<DBWT: Borrow<BWT>, DLess: Borrow<Less>, DOcc: Borrow<Occ>> FMIndex<DBWT, DLess, DOcc> {
+    /// Construct a new instance of the FM index.
+    ///
+    /// # Arguments
+    ///
+    /// * `bwt` - the BWT
+    /// * `less` - the less array of the BWT
+    /// * `occ` - the occurence array of the BWT
+    pub fn new(bwt: DBWT, less: DLess, occ: DOcc) -> Self {
+        FMIndex { bwt, less, occ }
+    }
+
+    /// Whether the given symbol belongs to the alphabet of the index (see
+    /// `Occ::contains_symbol`). Searching for patterns with other symbols is not supported.
+    pub fn contains_symbol(&self, a: u8) -> bool {
+        self.occ.borrow().contains_symbol(a)
+    }
+
+    /// Provide a reference to the underlying BWT.
+    pub fn bwt(&self) -> &BWT {
+        self.bwt.borrow()
+    }
+}
+
+impl FMIndex<BWT, Less, Occ> {
+    /// Build an FM-index over multiple texts, which are concatenated with sentinels
+    /// (see `TextCollection`).
+    /// Returns the index, the suffix array of the concatenated text and the collection,
+    /// which allows to resolve suffix array positions to the individual texts.
+    ///
+    /// # Arguments
+    ///
+    /// * `texts` - the texts, which must not contain the sentinel `$`
+    /// * `alphabet` - the alphabet of the texts (the sentinel is added automatically)
+    /// * `occ_sampling_rate` - the sampling rate of the occurrence array
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use bio::alphabets::dna;
+    /// use bio::data_structures::fmindex::{BackwardSearchResult, FMIndex, FMIndexable};
+    ///
+    /// let texts = [b"GCCTTAACA".to_vec(), b"TTATTACGCCTA".to_vec()];
+    /// let (fm, sa, collection) = FMIndex::from_texts(&texts, &dna::alphabet(), 3);
+    ///
+    /// let mut hits = match fm.backward_search(b"TTA".iter()) {
+    ///     BackwardSearchResult::Complete(sai) => sai
+    ///         .occ(&sa)
+    ///         .into_iter()
+    ///         .map(|pos| {
+    ///             let pos = collection.resolve(pos).unwrap();
+    ///             (pos.text, pos.offset)
+    ///         })
+    ///         .collect(),
+    ///     _ => Vec::new(),
+    /// };
+    /// hits.sort();
+    /// assert_eq!(hits, [(0, 3), (1, 0), (1, 3)]);
+    /// ```
+    pub fn from_texts<T: AsRef<[u8]>>(
+        texts: &[T],
+        alphabet: &Alphabet,
+        occ_sampling_rate: u32,
+    ) -> (Self, RawSuffixArray, TextCollection) {
+        FMIndex::from_collection(TextCollection::new(texts), alphabet, occ_sampling_rate)
+    }
+
+    /// Build an FM-index over multiple texts like `from_texts`, additionally finding their
+    /// low-complexity regions with the given DUST parameters. The texts are indexed
+    /// unchanged, while the masked intervals are recorded in coordinates of the concatenated
+    /// text (i.e. like suffix array entries), such that seeding can skip
+    /// (`Interval::occ_unmasked`) or downweight (`MaskedIntervals::masked_fraction`) hits in
+    /// masked regions.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use bio::alphabets::dna;
+    /// use bio::data_structures::fmindex::{FMIndex, FMIndexable};
+    /// use bio::seq_analysis::dust::Dust;
+    ///
+    /// let texts = [b"ACGGCTGAGATGATCCGATTAGCTAGCA".to_vec(), b"AAAAAAAAAAAAAAAAAAAA".to_vec()];
+    /// let (fm, sa, _, masked) =
+    ///     FMIndex::from_texts_masked(&texts, &dna::alphabet(), 3, &Dust::default());
+    /// // the second text is masked entirely
+    /// assert_eq!(masked.intervals(), [29..49]);
+    ///
+    /// let interval = fm.backward_search(b"AGA".iter()).interval().unwrap();
+    /// assert_eq!(interval.occ_unmasked(&sa, &masked, 3).collect::<Vec<_>>(), [7]);
+    /// let interval = fm.backward_search(b"AAA".iter()).interval().unwrap();
+    /// assert_eq!(interval.occ_unmasked(&sa, &masked, 3).count(), 0);
+    /// ```
+    pub fn from_texts_masked<T: AsRef<[u8]>>(
+        texts: &[T],
+        alphabet: &Alphabet,
+        occ_sampling_rate: u32,
+        dust: &Dust,
+    ) -> (Self, RawSuffixArray, TextCollection, MaskedIntervals) {
+        let (fmindex, sa, collection) = FMIndex::from_texts(texts, alphabet, occ_sampling_rate);
+        let intervals = texts
+            .iter()
+            .enumerate()
+            .flat_map(|(i, text)| {
+                let offset = collection.text_range(i).start;
+                dust.intervals(text.as_ref())
+                    .into_iter()
+                    .map(move |interval| interval.start + offset..interval.end + offset)
+            })
+            .collect();
+        (fmindex, sa, collection, MaskedIntervals::new(intervals))
+    }
+
+    /// Build an FM-index over the given text collection, using its sentinel symbol.
+    /// This allows to index texts that contain `$`, by choosing another sentinel
+    /// (e.g. `0x00`) via `TextCollection::with_sentinel`.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use bio::alphabets::Alphabet;
+    /// use bio::data_structures::fmindex::{BackwardSearchResult, FMIndex, FMIndexable};
+    /// use bio::data_structures::text_collection::TextCollection;
+    ///
+    /// let collection = TextCollection::with_sentinel(&[&b"A$C$A"[..], b"C$A"], false, 0);
+    /// let (fm, sa, collection) =
+    ///     FMIndex::from_collection(collection, &Alphabet::new(b"$AC"), 3);
+    ///
+    /// match fm.backward_search(b"C$A".iter()) {
+    ///     BackwardSearchResult::Complete(sai) => assert_eq!(sai.len(), 2),
+    ///     _ => panic!("expecting complete match"),
+    /// }
+    /// ```
+    pub fn from_collection(
+        collection: TextCollection,
+        alphabet: &Alphabet,
+        occ_sampling_rate: u32,
+    ) -> (Self, RawSuffixArray, TextCollection) {
+        let (fmindex, sa) = build(
+            collection.text(),
+            alphabet,
+            collection.sentinel(),
+            occ_sampling_rate,
+        );
+        (fmindex, sa, collection)
+    }
+}
+
+/// An FM-index that answers occurrence queries with a wavelet tree of the BWT instead of
+/// a sampled occurrence array (`Occ`). The wavelet tree needs O(n log σ) bits, independent of
+/// any sampling, while the occurrence array needs O(n σ / k) words for sampling rate k, which
+/// makes this the more memory-efficient choice for large alphabets like protein.
+/// Occurrence queries take O(log σ) time.
+///
+/// # Example
+///
+/// ```
+/// use bio::alphabets::Alphabet;
+/// use bio::data_structures::bwt::{bwt, less};
+/// use bio::data_structures::fmindex::{FMIndexable, WaveletFMIndex};
+/// use bio::data_structures::suffix_array::suffix_array;
+///
+/// let text = b"MKVLAAGIVALLLAAGCSS$";
+/// let sa = suffix_array(text);
+/// let bwt = bwt(text, &sa);
+/// let less = less(&bwt, &Alphabet::new(&text[..]));
+/// let fm = WaveletFMIndex::new(&bwt, &less);
+///
+/// let interval = fm.backward_search(b"LAAG".iter()).interval().unwrap();
+/// assert_eq!(interval.occ(&sa), [12, 3]);
+/// ```
+#[derive(Clone, Eq, PartialEq, Debug, Serialize, Deserialize)]
+pub struct WaveletFMIndex<DLess: Borrow<Less>> {
+    less: DLess,
+    occ: WaveletTree,
+}
+
+impl<DLess: Borrow<Less>> WaveletFMIndex<DLess> {
+    /// Construct a new instance of the FM index, building the wavelet tree of the BWT.
+    /// The BWT itself is not kept, since the wavelet tree can reproduce it (see `bwt_symbol`).
+    /// Complexity: O(n log σ).
+    ///
+    /// # Arguments
+    ///
+    /// * `bwt` - the BWT
+    /// * `less` - the less array of the BWT
+    pub fn new(bwt: &BWTSlice, less: DLess) -> Self {
+        let occ = WaveletTree::new(bwt);
+        WaveletFMIndex { less, occ }
+    }
+
+    /// The wavelet tree of the BWT.
+    pub fn wavelet_tree(&self) -> &WaveletTree {
+        &self.occ
+    }
+
+    /// The symbol at position i of the BWT.
+    /// Complexity: O(log σ).
+    pub fn bwt_symbol(&self, i: usize) -> u8 {
+        self.occ.access(i)
+    }
+}
+
+impl<DLess: Borrow<Less>> FMIndexable for WaveletFMIndex<DLess> {
+    fn occ(&self, r: usize, a: u8) -> usize {
+        self.occ.rank(a, r)
+    }
+    fn less(&self, a: u8) -> usize {
+        self.less.borrow()[a as usize]
+    }
+    fn len(&self) -> usize {
+        self.occ.len()
+    }
+}
+
+/// An FM-index on a run-length compressed BWT (`RLBWT`), for highly repetitive texts like
+/// pangenomes or read sets, where the number of runs r is much smaller than the text length.
+/// The index needs O(r) words in addition to the less array, at the price of O(log r) time
+/// per occurrence query. Occurrences can be located with any suffix array of the text.
+///
+/// # Example
+///
+/// ```
+/// use bio::alphabets::dna;
+/// use bio::data_structures::bwt::{bwt, less};
+/// use bio::data_structures::fmindex::{FMIndexable, RLFMIndex};
+/// use bio::data_structures::rlbwt::RLBWT;
+/// use bio::data_structures::suffix_array::suffix_array;
+///
+/// let text = b"GCCTTAACATGCCTTAACATGCCTTAACAT$";
+/// let sa = suffix_array(text);
+/// let bwt = bwt(text, &sa);
+/// let less = less(&bwt, &dna::n_alphabet());
+/// let fm = RLFMIndex::new(RLBWT::new(&bwt), less);
+/// assert!(fm.rlbwt().runs() < bwt.len() / 2);
+///
+/// let interval = fm.backward_search(b"TTA".iter()).interval().unwrap();
+/// let mut positions = interval.occ(&sa);
+/// positions.sort_unstable();
+/// assert_eq!(positions, [3, 13, 23]);
+/// ```
+#[derive(Clone, Eq, PartialEq, Debug, Serialize, Deserialize)]
+pub struct RLFMIndex<DLess: Borrow<Less>> {
+    rlbwt: RLBWT,
+    less: DLess,
+}
+
+impl<DLess: Borrow<Less>> RLFMIndex<DLess> {
+    /// Construct a new instance of the FM index.
+    ///
+    /// # Arguments
+    ///
+    /// * `rlbwt` - the run-length compressed BWT
+    /// * `less` - the less array of the BWT
+    pub fn new(rlbwt: RLBWT, less: DLess) -> Self {
+        RLFMIndex { rlbwt, less }
+    }
+
+    /// The run-length compressed BWT.
+    pub fn rlbwt(&self) -> &RLBWT {
+        &self.rlbwt
+    }
+}
+
+impl<DLess: Borrow<Less>> FMIndexable for RLFMIndex<DLess> {
+    /// Get occurrence count of symbol a in BWT[..r+1].
+    /// Complexity: O(log r).
+    fn occ(&self, r: usize, a: u8) -> usize {
+        self.rlbwt.rank(a, r)
+    }
+    fn less(&self, a: u8) -> usize {
+        self.less.borrow()[a as usize]
+    }
+    fn len(&self) -> usize {
+        self.rlbwt.len()
+    }
+}
+
+/// An exact k-mer membership set backed by an FM-index, answering whether (and how often) a
+/// k-mer occurs in the indexed text via backward search. Queries take O(k) time, stop as soon
+/// as the search interval becomes empty, and do not allocate. Since the suffix array is not
+/// needed, this can replace a hash set of k-mers at a fraction of the memory.
+///
+/// # Example
+///
+/// ```
+/// use bio::alphabets::dna;
+/// use bio::data_structures::fmindex::{FMIndex, KmerSet};
+///
+/// let (fm, _, _) = FMIndex::from_texts(&[b"GCCTTAACATTATTACGCCTA"], &dna::alphabet(), 3);
+/// let kmers = KmerSet::new(fm, &dna::alphabet(), 3);
+///
+/// assert_eq!(kmers.count(b"TTA"), 3);
+/// assert!(kmers.contains(b"GCC"));
+/// assert!(!kmers.contains(b"GGG"));
+/// // k-mers with symbols outside of the alphabet never occur
+/// assert!(!kmers.contains(b"TNA"));
+/// assert_eq!(
+///     kmers.count_all(vec![&b"TTA"[..], b"CCT", b"AAA"]).collect::<Vec<_>>(),
+///     [3, 2, 0]
+/// );
+/// ```
+#[derive(Clone, Debug)]
+pub struct KmerSet<F: FMIndexable> {
+    fmindex: F,
+    alphabet: Alphabet,
+    k: usize,
+}
+
+impl<F: FMIndexable> KmerSet<F> {
+    /// Create a new k-mer set.
+    ///
+    /// # Arguments
+    ///
+    /// * `fmindex` - the FM-index of the text
+    /// * `alphabet` - the alphabet of the text, without the sentinel
+    /// * `k` - the length of the k-mers
+    pub fn new(fmindex: F, alphabet: &Alphabet, k: usize) -> Self {
+        assert!(k > 0, "Expecting k > 0.");
+        KmerSet {
+            fmindex,
+            alphabet: alphabet.clone(),
+            k,
+        }
+    }
+
+    /// The length of the k-mers.
+    pub fn k(&self) -> usize {
+        self.k
+    }
+
+    /// The underlying FM-index.
+    pub fn fmindex(&self) -> &F {
+        &self.fmindex
+    }
+
+    /// Number of occurrences of the given k-mer in the text.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the k-mer does not have length k.
+    pub fn count(&self, kmer: &[u8]) -> usize {
+        assert_eq!(kmer.len(), self.k, "Expecting a k-mer of length k.");
+        if !self.alphabet.is_word(kmer) {
+            return 0;
+        }
+        match self.fmindex.backward_search(kmer.iter()) {
+            BackwardSearchResult::Complete(interval) => interval.len(),
+            _ => 0,
+        }
+    }
+
+    /// Whether the given k-mer occurs in the text.
+    pub fn contains(&self, kmer: &[u8]) -> bool {
+        self.count(kmer) > 0
+    }
+
+    /// Lazily count the occurrences of each k-mer of the given stream.
+    pub fn count_all<'a, I>(&'a self, kmers: I) -> impl Iterator<Item = usize> + 'a
+    where
+        I: IntoIterator<Item = &'a [u8]>,
+        I::IntoIter: 'a,
+    {
+        kmers.into_iter().map(move |kmer| self.count(kmer))
+    }
+
+    /// Count the occurrences of each overlapping k-mer of the given sequence, e.g. to
+    /// determine which k-mers of a read are present in a reference. The i-th entry refers to
+    /// the k-mer starting at position i.
+    pub fn count_text_kmers<'a>(&'a self, text: &'a [u8]) -> impl Iterator<Item = usize> + 'a {
+        text.windows(self.k).map(move |kmer| self.count(kmer))
+    }
+}
+
+/// Build an owned FM-index and the suffix array for the given sentinel-terminated text.
+fn build(
+    text: &[u8],
+    alphabet: &Alphabet,
+    sentinel: u8,
+    occ_sampling_rate: u32,
+) -> (FMIndex<BWT, Less, Occ>, RawSuffixArray) {
+    let mut alphabet = alphabet.clone();
+    alphabet.insert(sentinel);
+    let sa = suffix_array(text);
+    let bwt = bwt(text, &sa);
+    let less = less(&bwt, &alphabet);
+    let occ = Occ::new(&bwt, occ_sampling_rate, &alphabet);
+    (FMIndex::new(bwt, less, occ), sa)
+}
+
+/// A bi-interval on suffix array of the forward and reverse strand of a DNA text.
+#[derive(
+    Default, Copy, Clone, Eq, PartialEq, Ord, PartialOrd, Hash, Debug, Serialize, Deserialize,
+)]
+pub struct BiInterval {
+    lower: usize,
+    lower_rev: usize,
+    size: usize,
+    match_size: usize,
+}
+
+impl BiInterval {
+    pub fn forward(&self) -> Interval {
+        Interval {
+            upper: self.lower + self.size,
+            lower: self.lower,
+        }
+    }
+    pub fn revcomp(&self) -> Interval {
+        Interval {
+            upper: self.lower_rev + self.size,
+            lower: self.lower_rev,
+        }
+    }
+
+    /// The number of occurrences of the matched string on both strands, i.e. the size of
+    /// the forward (and reverse complement) interval.
+    pub fn size(&self) -> usize {
+        self.size
+    }
+
+    /// Whether the matched string does not occur.
+    pub fn is_empty(&self) -> bool {
+        self.size == 0
+    }
+
+    /// The length of the matched string.
+    pub fn match_size(&self) -> usize {
+        self.match_size
+    }
+
+    fn swapped(&self) -> BiInterval {
+        BiInterval {
+            lower: self.lower_rev,
+            lower_rev: self.lower,
+            size: self.size,
+            match_size: self.match_size,
+        }
+    }
+}
+
+/// The FMD-Index for linear time search of supermaximal exact matches on forward and reverse
+/// strand of DNA texts (Li, 2012).
+///
+/// By default, the FMD-Index runs on an `FMIndex` with the given BWT, less and occurrence
+/// array types. Any other `FMIndexable` can be used via the last type parameter, e.g. an
+/// `RLFMIndex` on a run-length compressed BWT (see `RLFMDIndex`).
+#[derive(Copy, Clone, Eq, PartialEq, Ord, PartialOrd, Hash, Debug, Serialize, Deserialize)]
+pub struct FMDIndex<DBWT, DLess, DOcc, I = FMIndex<DBWT, DLess, DOcc>> {
+    fmindex: I,
+    #[serde(default = "default_sentinel")]
+    sentinel: u8,
+    #[serde(skip)]
+    components: PhantomData<(DBWT, DLess, DOcc)>,
+}
+
+/// An FMD-Index on a run-length compressed BWT (see `RLFMIndex`), for highly repetitive
+/// collections like pangenomes.
+///
+/// # Example
+///
+/// ```
+/// use bio::alphabets::dna;
+/// use bio::data_structures::bwt::{bwt, less};
+/// use bio::data_structures::fmindex::{RLFMDIndex, RLFMIndex};
+/// use bio::data_structures::rlbwt::RLBWT;
+/// use bio::data_structures::suffix_array::suffix_array;
+/// use bio::data_structures::text_collection::TextCollection;
+///
+/// let collection = TextCollection::with_revcomp(&[b"GCCTTAACATGCCTTAACAT"]);
+/// let text = collection.text();
+/// let sa = suffix_array(text);
+/// let bwt = bwt(text, &sa);
+/// let less = less(&bwt, &dna::n_alphabet());
+/// let fm = RLFMIndex::new(RLBWT::new(&bwt), less);
+/// let fmdindex = RLFMDIndex::from_index_checked(fm, text).unwrap();
+///
+/// let matches = fmdindex.smems(b"CCTTAA", 2, 0);
+/// assert_eq!(matches.len(), 1);
+/// // the match occurs in both copies of the repeat
+/// assert_eq!(matches[0].0.size(), 2);
+/// ```
+pub type RLFMDIndex<DLess> = FMDIndex<(), (), (), RLFMIndex<DLess>>;
+
+fn default_sentinel() -> u8 {
+    SENTINEL
+}
+
+impl<DBWT, DLess, DOcc, I: Default> Default for FMDIndex<DBWT, DLess, DOcc, I> {
+    fn default() -> Self {
+        FMDIndex {
+            fmindex: I::default(),
+            sentinel: SENTINEL,
+            components: PhantomData,
+        }
+    }
+}
+
+impl<DBWT, DLess, DOcc, I: FMIndexable> FMIndexable for FMDIndex<DBWT, DLess, DOcc, I> {
+    fn occ(&self, r: usize, a: u8) -> usize {
+        self.fmindex.occ(r, a)
+    }
+
+    fn less(&self, a: u8) -> usize {
+        self.fmindex.less(a)
+    }
+
+    #[allow(deprecated)]
+    fn bwt(&self) -> &BWT {
+        self.fmindex.bwt()
+    }
+
+    fn len(&self) -> usize {
+        self.fmindex.len()
+    }
+}
+
+impl<DBWT: Borrow<BWT>, DLess: Borrow<Less>, DOcc: Borrow<Occ>> From<FMIndex<DBWT, DLess, DOcc>>
+    for FMDIndex<DBWT, DLess, DOcc>
+{
+    /// Construct a new instance of the FMD index (see Heng Li (2012) Bioinformatics).
+    /// This expects a BWT that was created from a text over the DNA alphabet with N
+    /// (`alphabets::dna::n_alphabet()`) consisting of the
+    /// concatenation with its reverse complement, separated by the sentinel symbol `$`.
+    /// I.e., let T be the original text and R be its reverse complement.
+    /// Then, the expected text is T$R$. Further, multiple concatenated texts are allowed, e.g.
+    /// T1$R1$T2$R2$T3$R3$.
+    fn from(fmindex: FMIndex<DBWT, DLess, DOcc>) -> FMDIndex<DBWT, DLess, DOcc> {
+        FMDIndex::with_sentinel(fmindex, SENTINEL)
+    }
+}
+
+impl FMDIndex<BWT, Less, Occ> {
+    /// Build an FMD-index over multiple DNA texts (over `alphabets::dna::n_alphabet()`), which
+    /// are concatenated with their reverse complements and sentinels to `T1$R1$T2$R2$...`
+    /// (see `TextCollection`).
+    /// Returns the index, the suffix array of the concatenated text and the collection,
+    /// which allows to resolve suffix array positions to the individual texts and strands.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use bio::data_structures::fmindex::FMDIndex;
+    ///
+    /// let (fmdindex, sa, collection) = FMDIndex::from_texts(&[b"ATTC", b"GGAC"], 3);
+    /// assert_eq!(collection.text(), b"ATTC$GAAT$GGAC$GTCC$");
+    ///
+    /// let smems = fmdindex.smems(b"ATT", 2, 0);
+    /// let pos = collection.resolve(smems[0].0.forward().occ(&sa)[0]).unwrap();
+    /// assert_eq!((pos.text, pos.reverse, pos.offset), (0, false, 0));
+    /// ```
+    pub fn from_texts<T: AsRef<[u8]>>(
+        texts: &[T],
+        occ_sampling_rate: u32,
+    ) -> (Self, RawSuffixArray, TextCollection) {
+        FMDIndex::from_collection(TextCollection::with_revcomp(texts), occ_sampling_rate)
+    }
+
+    /// Build an FMD-index over the given text collection, which has to include the
+    /// reverse complements (see `TextCollection::with_revcomp` and
+    /// `TextCollection::with_sentinel`), using its sentinel symbol.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use bio::data_structures::fmindex::FMDIndex;
+    /// use bio::data_structures::text_collection::TextCollection;
+    ///
+    /// let collection = TextCollection::with_sentinel(&[b"ATTC", b"GGAC"], true, 0);
+    /// let (fmdindex, sa, collection) = FMDIndex::from_collection(collection, 3);
+    /// assert_eq!(fmdindex.sentinel(), 0);
+    ///
+    /// let smems = fmdindex.smems(b"ATT", 2, 0);
+    /// let pos = collection.resolve(smems[0].0.forward().occ(&sa)[0]).unwrap();
+    /// assert_eq!((pos.text, pos.reverse, pos.offset), (0, false, 0));
+    /// ```
+    pub fn from_collection(
+        collection: TextCollection,
+        occ_sampling_rate: u32,
+    ) -> (Self, RawSuffixArray, TextCollection) {
+        assert!(
+            collection.has_revcomp(),
+            "Expecting a text collection including the reverse complements."
+        );
+        let sentinel = collection.sentinel();
+        let (fmindex, sa) = build(
+            collection.text(),
+            &dna::n_alphabet(),
+            sentinel,
+            occ_sampling_rate,
+        );
+        (FMDIndex::with_sentinel(fmindex, sentinel), sa, collection)
+    }
+}
+
+impl<DBWT, DLess, DOcc, I: FMIndexable> FMDIndex<DBWT, DLess, DOcc, I> {
+    /// Find supermaximal exact matches (of length >= l) of given pattern that overlap position i in the pattern.
+    /// Complexity O(m) with pattern of length m.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use bio::alphabets::dna;
+    /// use bio::data_structures::bwt::{bwt, less, Occ};
+    /// use bio::data_structures::fmindex::{FMDIndex, FMIndex};
+    /// use bio::data_structures::suffix_array::suffix_array;
+    ///
+    /// let text = b"ATTC$GAAT$";
+    /// let alphabet = dna::n_alphabet();
+    /// let sa = suffix_array(text);
+    /// let bwt = bwt(text, &sa);
+    /// let less = less(&bwt, &alphabet);
+    /// let occ = Occ::new(&bwt, 3, &alphabet);
+    /// let fm = FMIndex::new(&bwt, &less, &occ);
+    /// let fmdindex = FMDIndex::from(fm);
+    ///
+    /// let pattern = b"ATT";
+    /// let intervals = fmdindex.smems(pattern, 2, 0);
+    ///
+    /// let forward_positions = intervals[0].0.forward().occ(&sa);
+    /// let revcomp_positions = intervals[0].0.revcomp().occ(&sa);
+    /// let pattern_position = intervals[0].1;
+    /// let smem_len = intervals[0].2;
+    ///
+    /// assert_eq!(forward_positions, [0]);
+    /// assert_eq!(revcomp_positions, [6]);
+    /// assert_eq!(pattern_position, 0);
+    /// assert_eq!(smem_len, 3);
+    /// ```
+    pub fn smems(&self, pattern: &[u8], i: usize, l: usize) -> Vec<(BiInterval, usize, usize)> {
+        let curr = &mut Vec::new(); // pairs (biinterval, current match length)
+        let prev = &mut Vec::new(); // """
+        let mut matches = Vec::new(); // triples (biinterval, position on pattern, smem length)
+
+        let mut match_len = 0;
+        let mut interval = self.init_interval_with(pattern[i]);
+        if interval.size != 0 {
+            match_len += 1;
+        }
+
+        for &a in pattern[i + 1..].iter() {
+            // forward extend interval
+            let forward_interval = self.forward_ext(&interval, a);
+
+            // if size changed, add last interval to list
+            if interval.size != forward_interval.size {
+                curr.push((interval, match_len));
+            }
+            // if new interval size is zero, stop, as no further forward extension is possible
+            if forward_interval.size == 0 {
+                break;
+            }
+            interval = forward_interval;
+            match_len += 1;
+        }
+        // add the last non-zero interval
+        curr.push((interval, match_len));
+        // reverse intervals such that longest comes first
+        curr.reverse();
+
+        swap(curr, prev);
+        let mut j = pattern.len() as isize;
+
+        for k in (-1..i as isize).rev() {
+            let a = if k == -1 {
+                self.sentinel
+            } else {
+                pattern[k as usize]
+            };
+            curr.clear();
+            // size of the last confirmed interval
+            let mut last_size = -1;
+
+            for (interval, match_len) in prev.iter() {
+                // backward extend interval
+                let forward_interval = self.backward_ext(interval, a);
+
+                if (forward_interval.size == 0 || k == -1) &&
+                        // interval could not be extended further
+                        // if no interval has been extended this iteration,
+                        // interval is maximal and can be added to the matches
+                        curr.is_empty() && k < j &&
+                        match_len >= &l
+                {
+                    j = k;
+                    matches.push((*interval, (k + 1) as usize, *match_len));
+                }
+                // add _interval to curr (will be further extended next iteration)
+                if forward_interval.size != 0 && forward_interval.size as isize != last_size {
+                    last_size = forward_interval.size as isize;
+                    curr.push((forward_interval, match_len + 1));
+                }
+            }
+            if curr.is_empty() {
+                break;
+            }
+            swap(curr, prev);
+        }
+
+        matches
+    }
+
+    /// Find all supermaximal exact matches (of length >= l) of given pattern.
+    /// Complexity O(m^2) with pattern of length m.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use bio::alphabets::dna;
+    /// use bio::data_structures::bwt::{bwt, less, Occ};
+    /// use bio::data_structures::fmindex::{FMDIndex, FMIndex};
+    /// use bio::data_structures::suffix_array::suffix_array;
+    ///
+    /// let text = b"ATTCGGGG$CCCCGAAT$";
+    /// let alphabet = dna::n_alphabet();
+    /// let sa = suffix_array(text);
+    /// let bwt = bwt(text, &sa);
+    /// let less = less(&bwt, &alphabet);
+    /// let occ = Occ::new(&bwt, 3, &alphabet);
+    /// let fm = FMIndex::new(&bwt, &less, &occ);
+    /// let fmdindex = FMDIndex::from(fm);
+    ///
+    /// let pattern = b"ATTGGGG";
+    /// let intervals = fmdindex.all_smems(pattern, 0);
+    /// assert_eq!(intervals.len(), 2);
+    ///
+    /// let solutions = vec![[0, 14, 0, 3], [4, 9, 3, 4]];
+    /// for (i, interval) in intervals.iter().enumerate() {
+    ///     let forward_positions = interval.0.forward().occ(&sa);
+    ///     let revcomp_positions = interval.0.revcomp().occ(&sa);
+    ///     let pattern_position = interval.1;
+    ///     let smem_len = interval.2;
+    ///     assert_eq!(
+    ///         [
+    ///             forward_positions[0],
+    ///             revcomp_positions[0],
+    ///             pattern_position,
+    ///             smem_len
+    ///         ],
+    ///         solutions[i]
+    ///     );
+    /// }
+    /// ```
+    pub fn all_smems(&self, pattern: &[u8], l: usize) -> Vec<(BiInterval, usize, usize)> {
+        let mut smems = Vec::new();
+        let mut i0 = 0;
+        while i0 < pattern.len() {
+            let mut curr_smems = self.smems(pattern, i0, l);
+            let mut next_i0 = i0 + 1; // this always works since:
+                                      // if we have a SMEM overlapping i0, it is at least 1bp long.
+                                      // If we don't have a smem, then we'll reiterate from i0+1
+            for (_, p, l) in curr_smems.iter() {
+                if p + l > next_i0 {
+                    next_i0 = p + l;
+                }
+            }
+            i0 = next_i0;
+            smems.append(&mut curr_smems);
+        }
+        smems
+    }
+
+    /// Initialize interval with given start character.
+    pub fn init_interval_with(&self, a: u8) -> BiInterval {
+        let comp_a = dna::complement(a);
+        let lower = self.fmindex.less(a);
+
+        BiInterval {
+            lower,
+            lower_rev: self.fmindex.less(comp_a),
+            size: self.fmindex.less(a + 1) - lower,
+            match_size: 1,
+        }
+    }
+
+    /// Initialize interval for empty pattern. The interval points at the whole suffix array.
+    pub fn init_interval(&self) -> BiInterval {
+        BiInterval {
+            lower: 0,
+            lower_rev: 0,
+            size: self.fmindex.len(),
+            match_size: 0,
+        }
+    }
+
+    /// Backward extension of given interval with given character, i.e. the bi-interval of
+    /// the matched string prefixed by `a`.
+    pub fn backward_ext(&self, interval: &BiInterval, a: u8) -> BiInterval {
+        let mut s = 0;
+        let mut o = 0;
+        let mut l = interval.lower_rev;
+        // Interval [l(c(aP)), u(c(aP))] is a subinterval of [l(c(P)), u(c(P))] for each a,
+        // starting with the lexicographically smallest (the sentinel),
+        // then c(T) = A, c(G) = C, c(C) = G, N, c(A) = T, ...
+        // Hence, we calculate lower revcomp bounds by iterating over
+        // symbols and updating from previous one.
+        for &b in [self.sentinel].iter().chain(b"TGCNAtgcna") {
+            l += s;
+            o = if interval.lower == 0 {
+                0
+            } else {
+                self.fmindex.occ(interval.lower - 1, b)
+            };
+            // calculate size
+            s = self.fmindex.occ(interval.lower + interval.size - 1, b) - o;
+            if b == a {
+                break;
+            }
+        }
+        // calculate lower bound
+        let k = self.fmindex.less(a) + o;
+
+        BiInterval {
+            lower: k,
+            lower_rev: l,
+            size: s,
+            match_size: interval.match_size + 1,
+        }
+    }
+
+    /// Forward extension of given interval with given character, i.e. the bi-interval of
+    /// the matched string followed by `a`.
+    ///
+    /// Together with `backward_ext`, this allows to implement custom seeding strategies.
+    /// For example, the following finds the longest match starting at a given pattern position
+    /// that still occurs at least a minimum number of times on both strands (like the
+    /// re-seeding of BWA-MEM, which splits long SMEMs with few occurrences).
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use bio::data_structures::fmindex::FMDIndex;
+    ///
+    /// let (fmdindex, sa, _) = FMDIndex::from_texts(&[&b"ACGTTAGCAT"[..], b"GTTAGGGA"], 3);
+    ///
+    /// let pattern = b"GTTAGCA";
+    /// let min_size = 2;
+    /// let mut interval = fmdindex.init_interval();
+    /// for &a in pattern {
+    ///     let extended = fmdindex.forward_ext(&interval, a);
+    ///     if extended.size() < min_size {
+    ///         break;
+    ///     }
+    ///     interval = extended;
+    /// }
+    /// // GTTAG occurs in both texts
+    /// assert_eq!(interval.match_size(), 5);
+    /// assert_eq!(interval.size(), 2);
+    /// let mut positions = interval.forward().occ(&sa);
+    /// positions.sort();
+    /// assert_eq!(positions, [2, 22]);
+    /// ```
+    pub fn forward_ext(&self, interval: &BiInterval, a: u8) -> BiInterval {
+        let comp_a = dna::complement(a);
+
+        self.backward_ext(&interval.swapped(), comp_a).swapped()
+    }
+
+    /// The sentinel symbol separating the texts.
+    pub fn sentinel(&self) -> u8 {
+        self.sentinel
+    }
+
+    /// Construct a new instance of the FMD index (see Heng Li (2012) Bioinformatics) on
+    /// any `FMIndexable`, e.g. an `RLFMIndex` (see `RLFMDIndex`), validating the given text
+    /// the index has been built from like `FMDIndex::from_fmindex_checked`.
+    ///
+    /// Complexity: O(n), with n being the length of the text.
+    pub fn from_index_checked(fmindex: I, text: &[u8]) -> Result<Self> {
+        let bwt_len = fmindex.len();
+        if text.len() != bwt_len {
+            return Err(Error::LengthMismatch {
+                text_len: text.len(),
+                bwt_len,
+            });
+        }
+        let sentinel = validate_fmd_text(text)?;
+        Ok(FMDIndex {
+            fmindex,
+            sentinel,
+            components: PhantomData,
+        })
+    }
+}
+
+impl<DBWT: Borrow<BWT>, DLess: Borrow<Less>, DOcc: Borrow<Occ>> FMDIndex<DBWT, DLess, DOcc> {
+    /// Construct a new instance of the FMD index (see Heng Li (2012) Bioinformatics) from
+    /// a BWT whose text is separated by the given sentinel symbol instead of `$`
+    /// (see `FMDIndex::from`).
+    ///
+    /// # Panics
+    ///
+    /// If the BWT is not over the DNA alphabet with N and the sentinel, or the sentinel
+    /// is not lexicographically smaller than all DNA symbols.
+    pub fn with_sentinel(
+        fmindex: FMIndex<DBWT, DLess, DOcc>,
+        sentinel: u8,
+    ) -> FMDIndex<DBWT, DLess, DOcc> {
+        let mut alphabet = dna::n_alphabet();
+        assert!(
+            alphabet.symbols.iter().all(|a| a > sentinel as usize),
+            "Expecting a sentinel that is smaller than all DNA symbols."
+        );
+        alphabet.insert(sentinel);
+        assert!(
+            alphabet.is_word(fmindex.bwt()),
+            "Expecting BWT over the DNA alphabet (including N) with the sentinel."
+        );
+
+        FMDIndex {
+            fmindex,
+            sentinel,
+            components: PhantomData,
+        }
+    }
+
+    /// Provide a reference to the underlying BWT.
+    pub fn bwt(&self) -> &BWT {
+        self.fmindex.bwt()
+    }
+
+    /// Construct a new instance of the FMD index (see Heng Li (2012) Bioinformatics),
+    /// validating that the given text, which the BWT of the FM index has been built from,
+    /// consists of alternating blocks of forward text and reverse complement, each terminated
+    /// by the sentinel, i.e. T1$R1$T2$R2$...
+    /// The sentinel is taken from the last symbol of the text.
+    /// In contrast to `FMDIndex::from`, which only checks the alphabet of the BWT, this
+    /// returns a descriptive error instead of constructing an index that yields wrong
+    /// bi-intervals.
+    ///
+    /// Complexity: O(n), with n being the length of the text.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use bio::alphabets::dna;
+    /// use bio::data_structures::bwt::{bwt, less, Occ};
+    /// use bio::data_structures::fmindex::{Error, FMDIndex, FMIndex};
+    /// use bio::data_structures::suffix_array::suffix_array;
+    ///
+    /// let text = b"ATTC$GAAT$AAC$GTA$";
+    /// let alphabet = dna::n_alphabet();
+    /// let sa = suffix_array(text);
+    /// let bwt = bwt(text, &sa);
+    /// let less = less(&bwt, &alphabet);
+    /// let occ = Occ::new(&bwt, 3, &alphabet);
+    /// let fm = FMIndex::new(&bwt, &less, &occ);
+    ///
+    /// assert_eq!(
+    ///     FMDIndex::from_fmindex_checked(fm, text).unwrap_err(),
+    ///     Error::NotReverseComplement { block: 3 }
+    /// );
+    /// ```
+    pub fn from_fmindex_checked(
+        fmindex: FMIndex<DBWT, DLess, DOcc>,
+        text: &[u8],
+    ) -> Result<FMDIndex<DBWT, DLess, DOcc>> {
+        FMDIndex::from_index_checked(fmindex, text)
+    }
+
+    /// Construct a new instance of the FMD index (see Heng Li (2012) Bioinformatics)
+    /// without checking whether the text is over the DNA alphabet with N.
+    /// This expects a BWT that was created from a text over the DNA alphabet with N
+    /// (`alphabets::dna::n_alphabet()`) consisting of the
+    /// concatenation with its reverse complement, separated by the sentinel symbol `$`.
+    /// I.e., let T be the original text and R be its reverse complement.
+    /// Then, the expected text is T$R$. Further, multiple concatenated texts are allowed, e.g.
+    /// T1$R1$T2$R2$T3$R3$.
+    ///
+    /// # Safety
+    ///
+    /// It is unsafe to construct an FMD index from an FM index that is not built on the DNA alphabet.
+    pub unsafe fn from_fmindex_unchecked(
+        fmindex: FMIndex<DBWT, DLess, DOcc>,
+    ) -> FMDIndex<DBWT, DLess, DOcc> {
+        FMDIndex {
+            fmindex,
+            sentinel: SENTINEL,
+            components: PhantomData,
+        }
+    }
+}
+
+/// Check that the given text is of the form T1$R1$T2$R2$..., with Ri being the reverse
+/// complement of Ti over the DNA alphabet with N, and return the sentinel.
+fn validate_fmd_text(text: &[u8]) -> Result<u8> {
+    let mut alphabet = dna::n_alphabet();
+    let sentinel = match text.last() {
+        Some(&a) if !alphabet.symbols.contains(a as usize) => a,
+        _ => return Err(Error::MissingSentinel),
+    };
+    if alphabet.symbols.iter().any(|a| a < sentinel as usize) {
+        return Err(Error::InvalidSentinel { sentinel });
+    }
+    alphabet.insert(sentinel);
+    if let Some(pos) = text
+        .iter()
+        .position(|&a| !alphabet.symbols.contains(a as usize))
+    {
+        return Err(Error::InvalidSymbol {
+            symbol: text[pos],
+            pos,
+        });
+    }
+
+    let blocks: Vec<&[u8]> = text[..text.len() - 1].split(|&a| a == sentinel).collect();
+    if blocks.len() % 2 != 0 {
+        return Err(Error::OddBlockCount {
+            blocks: blocks.len(),
+        });
+    }
+    for (i, pair) in blocks.chunks(2).enumerate() {
+        let (forward, reverse) = (pair[0], pair[1]);
+        if forward.len() != reverse.len()
+            || forward
+                .iter()
+                .zip(reverse.iter().rev())
+                .any(|(&a, &b)| dna::complement(a) != b)
+        {
+            return Err(Error::NotReverseComplement { block: 2 * i + 1 });
+        }
+    }
+    Ok(sentinel)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::alphabets::dna;
+    use crate::data_structures::bwt::{bwt, less, Occ};
+    use crate::data_structures::suffix_array::suffix_array;
+    use rand::prelude::*;
+    use rand::rngs::StdRng;
+
+    #[test]
+    fn test_fmindex() {
+        let text = b"GCCTTAACATTATTACGCCTA$";
+        let alphabet = dna::n_alphabet();
+        let sa = suffix_array(text);
+        let bwt = bwt(text, &sa);
+        let less = less(&bwt, &alphabet);
+        let occ = Occ::new(&bwt, 3, &alphabet);
+        let fm = FMIndex::new(&bwt, &less, &occ);
+
+        let pattern = b"TTA";
+        let sai = fm.backward_search(pattern.iter());
+
+        let positions = match sai {
+            BackwardSearchResult::Complete(saint) => saint.occ(&sa),
+            BackwardSearchResult::Partial(saint, _l) => saint.occ(&sa),
+            BackwardSearchResult::Absent => Vec::<usize>::new(),
+        };
+
+        assert_eq!(positions, [3, 12, 9]);
+    }
+
+    #[test]
+    fn test_fmindex_not_found() {
+        let text = b"TCCTTAACATTATTACTCCTA$";
+        let alphabet = dna::n_alphabet();
+        let sa = suffix_array(text);
+        let bwt = bwt(text, &sa);
+        let less = less(&bwt, &alphabet);
+        let occ = Occ::new(&bwt, 3, &alphabet);
+        let fm = FMIndex::new(&bwt, &less, &occ);
+
+        let pattern = b"TTG";
+        let sai = fm.backward_search(pattern.iter());
+
+        let positions = match sai {
+            BackwardSearchResult::Complete(saint) => saint.occ(&sa),
+            BackwardSearchResult::Partial(saint, _l) => saint.occ(&sa),
+            BackwardSearchResult::Absent => Vec::<usize>::new(),
+        };
+
+        assert_eq!(positions, []);
+    }
+
+    #[test]
+    fn test_fmindex_backward_search_optimization() {
+        let text = b"GATTACA$";
+        let pattern = &text[..text.len() - 1];
+        let alphabet = dna::n_alphabet();
+        let sa = suffix_array(text);
+        let bwt = bwt(text, &sa);
+        let less = less(&bwt, &alphabet);
+        let occ = Occ::new(&bwt, 3, &alphabet);
+        let fm = FMIndex::new(&bwt, &less, &occ);
+
+        let sai = fm.backward_search(pattern.iter());
+
+        let positions = match sai {
+            BackwardSearchResult::Complete(saint) => saint.occ(&sa),
+            BackwardSearchResult::Partial(saint, _l) => saint.occ(&sa),
+            BackwardSearchResult::Absent => Vec::<usize>::new(),
+        };
+
+        assert_eq!(positions, [0]);
+    }
+
+    #[test]
+    fn test_fmindex_backward_search_partial_match() {
+        let text = b"GATTACA$";
+        let pattern = b"GTACA";
+        let alphabet = dna::n_alphabet();
+        let sa = suffix_array(text);
+        let bwt = bwt(text, &sa);
+        let less = less(&bwt, &alphabet);
+        let occ = Occ::new(&bwt, 3, &alphabet);
+        let fm = FMIndex::new(&bwt, &less, &occ);
+
+        let sai = fm.backward_search(pattern.iter());
+
+        let mut partial_match_len = 0;
+        let positions = match sai {
+            BackwardSearchResult::Complete(saint) => saint.occ(&sa),
+            BackwardSearchResult::Partial(saint, l) => {
+                partial_match_len = l;
+                saint.occ(&sa)
+            }
+            BackwardSearchResult::Absent => Vec::<usize>::new(),
+        };
+
+        assert_eq!(partial_match_len, 4);
+        assert_eq!(positions, [3]);
+        assert_eq!(sai.matched_len(pattern.len()), 4);
+
+        let sai = fm.backward_search(b"ATTA".iter());
+        assert_eq!(sai.matched_len(4), 4);
+        assert_eq!(sai.interval().map(|i| i.len()), Some(1));
+        let sai = fm.backward_search(b"NN".iter());
+        assert_eq!(sai.matched_len(2), 0);
+        assert_eq!(sai.interval(), None);
+    }
+
+    #[test]
+    fn test_backward_search_with_mismatches() {
+        let mut rng = StdRng::seed_from_u64(42);
+        let text: Vec<u8> = (0..500)
+            .map(|_| *b"ACGT".choose(&mut rng).unwrap())
+            .collect();
+        let alphabet = dna::alphabet();
+        let (fm, sa, _) = FMIndex::from_texts(&[&text], &alphabet, 3);
+
+        for _ in 0..20 {
+            let start = rng.gen_range(0..480);
+            let mut pattern = text[start..start + 12].to_vec();
+            pattern[rng.gen_range(0..12)] = b'N';
+            for k in 0..3 {
+                let mut hits = fm
+                    .backward_search_with_mismatches(&pattern, k, &alphabet)
+                    .into_iter()
+                    .flat_map(|(interval, mismatches)| {
+                        interval
+                            .occ(&sa)
+                            .into_iter()
+                            .map(move |pos| (pos, mismatches))
+                    })
+                    .collect::<Vec<_>>();
+                hits.sort_unstable();
+                let expected = (0..=text.len() - pattern.len())
+                    .map(|pos| {
+                        let window = &text[pos..pos + pattern.len()];
+                        (
+                            pos,
+                            window.iter().zip(&pattern).filter(|(a, b)| a != b).count(),
+                        )
+                    })
+                    .filter(|&(_, mismatches)| mismatches <= k)
+                    .collect::<Vec<_>>();
+                assert_eq!(hits, expected);
+            }
+        }
+    }
+
+    #[test]
+    fn test_wavelet_fmindex() {
+        let mut rng = StdRng::seed_from_u64(42);
+        let mut text: Vec<u8> = (0..1000)
+            .map(|_| *b"ACDEFGHIKLMNPQRSTVWY".choose(&mut rng).unwrap())
+            .collect();
+        text.push(b'$');
+        let alphabet = Alphabet::new(&text);
+        let sa = suffix_array(&text);
+        let bwt = bwt(&text, &sa);
+        let less = less(&bwt, &alphabet);
+        let occ = Occ::new(&bwt, 16, &alphabet);
+        let fm = FMIndex::new(&bwt, &less, &occ);
+        let wfm = WaveletFMIndex::new(&bwt, &less);
+        assert_eq!(wfm.len(), bwt.len());
+        assert!((0..bwt.len()).all(|i| wfm.bwt_symbol(i) == bwt[i]));
+
+        for r in 0..bwt.len() {
+            for a in alphabet.symbols.iter().map(|a| a as u8) {
+                assert_eq!(wfm.occ(r, a), fm.occ(r, a));
+            }
+        }
+        for start in (0..990).step_by(37) {
+            let pattern = &text[start..start + 6];
+            assert_eq!(
+                wfm.backward_search(pattern.iter()),
+                fm.backward_search(pattern.iter())
+            );
+        }
+    }
+
+    #[test]
+    fn test_rlfmindex() {
+        let mut rng = StdRng::seed_from_u64(42);
+        let genome: Vec<u8> = (0..200)
+            .map(|_| *b"ACGT".choose(&mut rng).unwrap())
+            .collect();
+        // a repetitive collection of slightly varied copies
+        let mut text = Vec::new();
+        for _ in 0..10 {
+            let mut copy = genome.clone();
+            copy[rng.gen_range(0..200)] = b'N';
+            text.extend_from_slice(&copy);
+        }
+        text.push(b'$');
+        let alphabet = dna::n_alphabet();
+        let sa = suffix_array(&text);
+        let bwt = bwt(&text, &sa);
+        let less = less(&bwt, &alphabet);
+        let occ = Occ::new(&bwt, 3, &alphabet);
+        let fm = FMIndex::new(&bwt, &less, &occ);
+        let rlfm = RLFMIndex::new(RLBWT::new(&bwt), &less);
+        assert!(rlfm.rlbwt().runs() < bwt.len() / 4);
+
+        for r in 0..bwt.len() {
+            for &a in b"$ACGNT" {
+                assert_eq!(rlfm.occ(r, a), fm.occ(r, a));
+            }
+        }
+        for start in (0..1990).step_by(37) {
+            let pattern = &text[start..start + 8];
+            assert_eq!(
+                rlfm.backward_search(pattern.iter()),
+                fm.backward_search(pattern.iter())
+            );
+        }
+        assert_eq!(
+            rlfm.backward_search(b"NNNN".iter()),
+            fm.backward_search(b"NNNN".iter())
+        );
+
+        // usable wherever an FMIndexable is expected
+        assert_eq!(rlfm.len(), bwt.len());
+        let kmers = KmerSet::new(rlfm, &dna::alphabet(), 8);
+        assert_eq!(
+            kmers.count(&text[100..108]),
+            fm.backward_search(text[100..108].iter())
+                .interval()
+                .unwrap()
+                .len()
+        );
+    }
+
+    #[test]
+    fn test_rlfmdindex() {
+        let mut rng = StdRng::seed_from_u64(42);
+        let genome: Vec<u8> = (0..200)
+            .map(|_| *b"ACGT".choose(&mut rng).unwrap())
+            .collect();
+        let texts: Vec<&[u8]> = (0..5).map(|_| &genome[..]).collect();
+        let collection = TextCollection::with_revcomp(&texts);
+        let (fmdindex, _, _) = FMDIndex::from_texts(&texts, 3);
+
+        let text = collection.text();
+        let sa = suffix_array(text);
+        let bwt = bwt(text, &sa);
+        let less = less(&bwt, &dna::n_alphabet());
+        let rlfm = RLFMIndex::new(RLBWT::new(&bwt), less);
+        assert!(rlfm.rlbwt().runs() < bwt.len() / 4);
+        let rlfmdindex = RLFMDIndex::from_index_checked(rlfm, text).unwrap();
+
+        assert_eq!(rlfmdindex.init_interval(), fmdindex.init_interval());
+        for start in (0..180).step_by(23) {
+            let pattern = &genome[start..start + 20];
+            assert_eq!(
+                rlfmdindex.smems(pattern, 10, 0),
+                fmdindex.smems(pattern, 10, 0)
+            );
+            assert_eq!(
+                rlfmdindex.all_smems(pattern, 0),
+                fmdindex.all_smems(pattern, 0)
+            );
+        }
+        assert_eq!(
+            RLFMDIndex::from_index_checked(RLFMIndex::new(RLBWT::new(&bwt), vec![]), b"AT$")
+                .unwrap_err(),
+            Error::LengthMismatch {
+                text_len: 3,
+                bwt_len: bwt.len()
+            }
+        );
+    }
+
+    #[test]
+    fn test_smems() {
+        let orig_text = b"GCCTTAACAT";
+        let revcomp_text = dna::revcomp(orig_text);
+        let text_builder: Vec<&[u8]> = vec![orig_text, b"$", &revcomp_text[..], b"$"];
+        let text = text_builder.concat();
+
+        let alphabet = dna::n_alphabet();
+        let sa = suffix_array(&text);
+        let bwt = bwt(&text, &sa);
+        let less = less(&bwt, &alphabet);
+        let occ = Occ::new(&bwt, 3, &alphabet);
+
+        let fmindex = FMIndex::new(&bwt, &less, &occ);
+        let fmdindex = FMDIndex::from(fmindex);
+        {
+            let pattern = b"AA";
+            let intervals = fmdindex.smems(pattern, 0, 0);
+            let forward = intervals[0].0.forward();
+            let revcomp = intervals[0].0.revcomp();
+            let pattern_position = intervals[0].1;
+            let smem_len = intervals[0].2;
+            assert_eq!(forward.occ(&sa), [5, 16]);
+            assert_eq!(revcomp.occ(&sa), [3, 14]);
+            assert_eq!(pattern_position, 0);
+            assert_eq!(smem_len, 2);
+        }
+        {
+            let pattern = b"CTTAA";
+            let intervals = fmdindex.smems(pattern, 1, 0);
+            assert_eq!(intervals[0].0.forward().occ(&sa), [2]);
+            assert_eq!(intervals[0].0.revcomp().occ(&sa), [14]);
+            assert_eq!(intervals[0].1, 0);
+            assert_eq!(intervals[0].2, 5);
+            assert_eq!(intervals[0].0.match_size, 5);
+        }
+        {
+            let pattern = b"CTTAA";
+            let intervals = fmdindex.smems(pattern, 1, 7);
+            assert!(intervals.is_empty());
+        }
+    }
+
+    #[test]
+    fn test_all_smems() {
+        let text = b"ATTCGGGG$CCCCGAAT$";
+        let alphabet = dna::n_alphabet();
+        let sa = suffix_array(text);
+        let bwt = bwt(text, &sa);
+        let less = less(&bwt, &alphabet);
+        let occ = Occ::new(&bwt, 3, &alphabet);
+        let fm = FMIndex::new(&bwt, &less, &occ);
+        let fmdindex = FMDIndex::from(fm);
+
+        {
+            let pattern = b"ATTGGGG";
+            let intervals = fmdindex.all_smems(pattern, 0);
+            assert_eq!(intervals.len(), 2);
+            let solutions = [[0, 14, 0, 3], [4, 9, 3, 4]];
+            for (interval, &solution) in intervals.iter().zip(solutions.iter()) {
+                let forward_positions = interval.0.forward().occ(&sa);
+                let revcomp_positions = interval.0.revcomp().occ(&sa);
+                let pattern_position = interval.1;
+                let smem_len = interval.2;
+                assert_eq!(
+                    [
+                        forward_positions[0],
+                        revcomp_positions[0],
+                        pattern_position,
+                        smem_len
+                    ],
+                    solution
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn test_fmdindex_from_texts() {
+        let orig_text = b"GCCTTAACAT";
+        let (fmdindex, sa, collection) = FMDIndex::from_texts(&[&orig_text[..], b"ACGT"], 3);
+        assert_eq!(collection.text(), b"GCCTTAACAT$ATGTTAAGGC$ACGT$ACGT$");
+
+        let intervals = fmdindex.smems(b"CTTAA", 1, 0);
+        assert_eq!(intervals[0].0.forward().occ(&sa), [2]);
+        assert_eq!(intervals[0].0.revcomp().occ(&sa), [14]);
+        assert!(collection.resolve(14).unwrap().reverse);
+    }
+
+    #[test]
+    fn test_fmdindex_default_sentinel() {
+        let fmdindex: FMDIndex<BWT, Less, Occ> = FMDIndex::default();
+        assert_eq!(fmdindex.sentinel(), SENTINEL);
+    }
+
+    #[test]
+    fn test_fmdindex_custom_sentinel() {
+        let texts = [&b"GCCTTAACAT"[..], b"ACGT"];
+        let (fmdindex, sa, _) = FMDIndex::from_texts(&texts, 3);
+        let collection = TextCollection::with_sentinel(&texts, true, 0);
+        let (fmdindex_zero, sa_zero, collection) = FMDIndex::from_collection(collection, 3);
+        assert_eq!(collection.text(), b"GCCTTAACAT\0ATGTTAAGGC\0ACGT\0ACGT\0");
+        assert_eq!(fmdindex_zero.sentinel(), 0);
+        assert_eq!(sa, sa_zero);
+
+        let pattern = b"TTAACGT";
+        for i in 0..pattern.len() {
+            assert_eq!(
+                fmdindex.smems(pattern, i, 0),
+                fmdindex_zero.smems(pattern, i, 0)
+            );
+        }
+    }
+
+    #[test]
+    fn test_fmdindex_checked() {
+        assert_eq!(validate_fmd_text(b"ATTC$GAAT$NA$TN$"), Ok(b'$'));
+        assert_eq!(validate_fmd_text(b"ATTC\0GAAT\0"), Ok(0));
+        assert_eq!(
+            validate_fmd_text(b"ATTC~GAAT~"),
+            Err(Error::InvalidSentinel { sentinel: b'~' })
+        );
+        assert_eq!(validate_fmd_text(b"ATTC$GAAT"), Err(Error::MissingSentinel));
+        assert_eq!(
+            validate_fmd_text(b"ATXC$GAXT$"),
+            Err(Error::InvalidSymbol {
+                symbol: b'X',
+                pos: 2
+            })
+        );
+        assert_eq!(
+            validate_fmd_text(b"ATTC$"),
+            Err(Error::OddBlockCount { blocks: 1 })
+        );
+        assert_eq!(
+            validate_fmd_text(b"ATTC$GAA$"),
+            Err(Error::NotReverseComplement { block: 1 })
+        );
+
+        let text = b"ATTC$GAAT$";
+        let alphabet = dna::n_alphabet();
+        let sa = suffix_array(text);
+        let bwt = bwt(text, &sa);
+        let less = less(&bwt, &alphabet);
+        let occ = Occ::new(&bwt, 3, &alphabet);
+        assert!(FMDIndex::from_fmindex_checked(FMIndex::new(&bwt, &less, &occ), text).is_ok());
+        assert_eq!(
+            FMDIndex::from_fmindex_checked(FMIndex::new(&bwt, &less, &occ), b"AT$").unwrap_err(),
+            Error::LengthMismatch {
+                text_len: 3,
+                bwt_len: 10
+            }
+        );
+    }
+
+    #[test]
+    fn test_init_interval() {
+        let text = b"ACGT$TGCA$";
+
+        let alphabet = dna::n_alphabet();
+        let sa = suffix_array(text);
+        let bwt = bwt(text, &sa);
+        let less = less(&bwt, &alphabet);
+        let occ = Occ::new(&bwt, 3, &alphabet);
+
+        let fmindex = FMIndex::new(&bwt, &less, &occ);
+        let fmdindex = FMDIndex::from(fmindex);
+        let pattern = b"T";
+        let interval = fmdindex.init_interval_with(pattern[0]);
+
+        assert_eq!(interval.forward().occ(&sa), [3, 5]);
+        assert_eq!(interval.revcomp().occ(&sa), [8, 0]);
+
+        let empty = fmdindex.init_interval();
+        let extended = fmdindex.backward_ext(&empty, pattern[0]);
+        assert_eq!(extended, interval);
+        let extended = fmdindex.forward_ext(&empty, pattern[0]);
+        assert_eq!(extended, interval);
+        assert_eq!((interval.size(), interval.match_size()), (2, 1));
+
+        let extended = fmdindex.forward_ext(&interval, b'G');
+        assert_eq!((extended.size(), extended.match_size()), (1, 2));
+        assert_eq!(extended.forward().occ(&sa), [5]);
+        assert!(fmdindex.backward_ext(&extended, b'C').is_empty());
+    }
+
+    #[test]
+    fn test_issue39() {
+        let reads = b"GGCGTGGTGGCTTATGCCTGTAATCCCAGCACTTTGGGAGGTCGAAGTGGGCGG$CCGC\
+                       CCACTTCGACCTCCCAAAGTGCTGGGATTACAGGCATAAGCCACCACGCC$CGAAGTGG\
+                       GCGGATCACTTGAGGTCAGGAGTTGGAGACTAGCCTGGCCAACACGATGAAACCCCGTC\
+                       TCTAATA$TATTAGAGACGGGGTTTCATCGTGTTGGCCAGGCTAGTCTCCAACTCCTGA\
+                       CCTCAAGTGATCCGCCCACTTCG$AGCTCGAAAAATGTTTGCTTATTTTGGTAAAATTA\
+                       TTCATTGACTATGCTCAGAAATCAAGCAAACTGTCCATATTTCATTTTTTG$CAAAAAA\
+                       TGAAATATGGACAGTTTGCTTGATTTCTGAGCATAGTCAATGAATAATTTTACCAAAAT\
+                       AAGCAAACATTTTTCGAGCT$AGCTCGAAAAATGTTTGCTTATTTTGGTAAAATTATTC\
+                       ATTGACTATGCTCAGAAATCAAGCAAACTGTCCATATTTCATTTTTTGAAATTACATAT\
+                       $ATATGTAATTTCAAAAAATGAAATATGGACAGTTTGCTTGATTTCTGAGCATAGTCAA\
+                       TGAATAATTTTACCAAAATAAGCAAACATTTTTCGAGCT$TAAAATTTCCTCTGACAGT\
+                       GTAAAAGAGATCTTCATACAAAAATCAGAATTTATATAGTCTCTTTCCAAAAGACCATA\
+                       AAACCAATCAGTTAATAGTTGAT$ATCAACTATTAACTGATTGGTTTTATGGTCTTTTG\
+                       GAAAGAGACTATATAAATTCTGATTTTTGTATGAAGATCTCTTTTACACTGTCAGAGGA\
+                       AATTTTA$CACCTATCTACCCTGAATCTAAGTGCTAACAGGAAAGGATGCCAGATTGCA\
+                       TGCCTGCTGATAAAGCCACAGTTTGGACTGTCACTCAATCACCATCGTTC$GAACGATG\
+                       GTGATTGAGTGACAGTCCAAACTGTGGCTTTATCAGCAGGCATGCAATCTGGCATCCTT\
+                       TCCTGTTAGCACTTAGATTCAGGGTAGATAGGTG$CATCGTTCCTCCTGTGACTCAGTA\
+                       TAACAAGATTGGGAGAATACTCTACAGTTCCTGATTCCCCCACAG$CTGTGGGGGAATC\
+                       AGGAACTGTAGAGTATTCTCCCAATCTTGTTATACTGAGTCACAGGAGGAACGATG$TG\
+                       TAAATTCTGAGAAAAATTTGCAGGTCTTTCTTCAGGAGCATGTAATCTCTTGCTCTCTT\
+                       TGTTATCTATCTATAGTACTGTAGGTTATCTGGAGTTGCT$AGCAACTCCAGATAACCT\
+                       ACAGTACTATAGATAGATAACAAAGAGAGCAAGAGATTACATGCTCCTGAAGAAAGACC\
+                       TGCAAATTTTTCTCAGAATTTACA$CACTTCTCCTTGTCTTTACAGACTGGTTTTGCAC\
+                       TGGGAAATCCTTTCACCAGTCAGCCCAGTTAGAGATTCTG$CAGAATCTCTAACTGGGC\
+                       TGACTGGTGAAAGGATTTCCCAGTGCAAAACCAGTCTGTAAAGACAAGGAGAAGTG$AA\
+                       TGGAGGTATATAAATTATCTGGCAAAGTGACATATCCTGACACATTCTCCAGGATAGAT\
+                       CAAATGTTAGGTCACAAAGAGAGTCTTAACAAAATT$AATTTTGTTAAGACTCTCTTTG\
+                       TGACCTAACATTTGATCTATCCTGGAGAATGTGTCAGGATATGTCACTTTGCCAGATAA\
+                       TTTATATACCTCCATT$TTAATTTTGTTAAGACTCTCTTTGTGACCTAACATTTGATCT\
+                       ATCCTGGAGAATGTGTCAGGATATGTCACTTTGCCAGATAATTTATATACCTCCATTTT\
+                       $AAAATGGAGGTATATAAATTATCTGGCAAAGTGACATATCCTGACACATTCTCCAGGA\
+                       TAGATCAAATGTTAGGTCACAAAGAGAGTCTTAACAAAATTAA$TTCTTCTTTGACTCA\
+                       TTGGTTGTTCAATAGTATGTTGTTTAATTTCCATATATTTGTAAATGTTTCCGTTTTCC\
+                       TTCTACTATTGAATTTTTGCTTCATC$GATGAAGCAAAAATTCAATAGTAGAAGGAAAA\
+                       CGGAAACATTTACAAATATATGGAAATTAAACAACATACTATTGAACAACCAATGAGTC\
+                       AAAGAAGAA$AGGAAAACGGAAACATTTACAAATATATGGAAATTAAACAACATACTAT\
+                       TGAACAACCAATGAGTCAAAGAAGAAATCAAAAAGAATATTAGAAAAC$GTTTTCTAAT\
+                       ATTCTTTTTGATTTCTTCTTTGACTCATTGGTTGTTCAATAGTATGTTGTTTAATTTCC\
+                       ATATATTTGTAAATGTTTCCGTTTTCCT$TTAGAAAACAAGCTGACAAAAAAATAAAAA\
+                       AACACAACATAGCAAAACTTAGAAATGCAGCAAAGGCAGTACTAAAGAGGGAAATTTAT\
+                       AGCAATAAATGC$GCATTTATTGCTATAAATTTCCCTCTTTAGTACTGCCTTTGCTGCA\
+                       TTTCTAAGTTTTGCTATGTTGTGTTTTTTTATTTTTTTGTCAGCTTGTTTTCTAA$TTT\
+                       ATTGCTATAAATTTCCCTCTTTAGTACTGCCTTTGCTGCATTTCTAAGTTTTGCTATGT\
+                       TGTGTTTTTTTATTTTTTTGTCAGCTTGTTTTCTA$TAGAAAACAAGCTGACAAAAAAA\
+                       TAAAAAAACACAACATAGCAAAACTTAGAAATGCAGCAAAGGCAGTACTAAAGAGGGAA\
+                       ATTTATAGCAATAAA$TCTTTCTTCTTTTTTAAGGTAGGCATTTATTGCTATAAATTTC\
+                       CCTCTTTAGTACTGCCTTTG$CAAAGGCAGTACTAAAGAGGGAAATTTATAGCAATAAA\
+                       TGCCTACCTTAAAAAAGAAGAAAGA$";
+
+        let alphabet = dna::n_alphabet();
+        let sa = suffix_array(reads);
+        let bwt = bwt(reads, &sa);
+        let less = less(&bwt, &alphabet);
+        let occ = Occ::new(&bwt, 3, &alphabet);
+
+        let fmindex = FMIndex::new(&bwt, &less, &occ);
+        let fmdindex = FMDIndex::from(fmindex);
+
+        let read = b"GGCGTGGTGGCTTATGCCTGTAATCCCAGCACTTTGGGAGGTCGAAGTGGGCGG";
+        let read_pos = 0;
+
+        for i in 0..read.len() {
+            println!("i {}", i);
+            let intervals = fmdindex.smems(read, i, 0);
+            println!("{:?}", intervals);
+            let matches = intervals
+                .iter()
+                .flat_map(|interval| interval.0.forward().occ(&sa))
+                .collect::<Vec<usize>>();
+            assert_eq!(matches, vec![read_pos]);
+        }
+    }
+
+    #[test]
+    fn test_kmer_set() {
+        let texts = [b"ACGTACGGA".to_vec(), b"TTACGT".to_vec()];
+        let (fm, _, _) = FMIndex::from_texts(&texts, &dna::alphabet(), 2);
+        let kmers = KmerSet::new(fm, &dna::alphabet(), 4);
+        assert_eq!(kmers.k(), 4);
+        assert_eq!(kmers.count(b"ACGT"), 2);
+        assert_eq!(kmers.count(b"TACG"), 2);
+        // k-mers spanning the boundary between texts do not occur
+        assert_eq!(kmers.count(b"GATT"), 0);
+        assert_eq!(kmers.count(b"GA$T"), 0);
+        assert_eq!(
+            kmers.count_text_kmers(b"TACGTT").collect::<Vec<_>>(),
+            [2, 2, 0]
+        );
+        assert!(kmers.count_text_kmers(b"ACG").next().is_none());
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_kmer_set_wrong_length() {
+        let (fm, _, _) = FMIndex::from_texts(&[b"ACGT"], &dna::alphabet(), 2);
+        KmerSet::new(fm, &dna::alphabet(), 3).count(b"AC");
+    }
+}
@@ -0,0 +1,559 @@
+//! Persistence of FM-indices, such that e.g. an index of a human genome can be built once and
+//! reused across processes without rebuilding it.
+//!
+//! All components of the index (BWT, Less, Occ and the suffix array sample) are stored in a
+//! compact binary format of little-endian 64-bit integers. `load` reads the whole file into
+//! memory. On unix systems, `load_mapped` instead memory-maps the file, such that the
+//! occurrence array and the suffix array sample are only paged in when accessed and are
+//! shared between processes via the page cache. The BWT and the less array are always copied
+//! into memory, since `PersistentFMIndex::bwt` hands out the BWT as a `&BWT`, i.e. an owned
+//! vector, like `FMIndex::bwt` and `SampledSuffixArray::bwt` do. Since a mapped file that is
+//! modified by another process would change the index underneath, `load_mapped` is unsafe.
+//!
+//! # Example
+//!
+//! ```
+//! use bio::alphabets::dna;
+//! use bio::data_structures::bwt::{bwt, less, Occ};
+//! use bio::data_structures::fmindex::{self, FMIndexable};
+//! use bio::data_structures::suffix_array::{suffix_array, SuffixArray};
+//!
+//! let text = b"GCCTTAACATTATTACGCCTA$";
+//! let alphabet = dna::n_alphabet();
+//! let sa = suffix_array(text);
+//! let bwt = bwt(text, &sa);
+//! let less = less(&bwt, &alphabet);
+//! let occ = Occ::new(&bwt, 3, &alphabet);
+//! let sampled = sa.sample(text, &bwt, &less, &occ, 4);
+//!
+//! let dir = tempfile::tempdir().unwrap();
+//! let path = dir.path().join("index.fm");
+//! fmindex::save(&path, &sampled).unwrap();
+//!
+//! let index = fmindex::load(&path).unwrap();
+//! let interval = index.backward_search(b"TTA".iter()).interval().unwrap();
+//! let mut positions = interval.occ(&index);
+//! positions.sort();
+//! assert_eq!(positions, [3, 9, 12]);
+//! ```
+
+use std::borrow::Borrow;
+use std::cmp::Ordering;
+use std::convert::TryInto;
+use std::fmt;
+use std::fs;
+use std::io;
+use std::ops::Range;
+use std::path::Path;
+
+use crate::data_structures::bwt::{Less, Occ, BWT};
+use crate::data_structures::fmindex::FMIndexable;
+use crate::data_structures::suffix_array::{SampledSuffixArray, SuffixArray};
+use crate::utils::mmap::Storage;
+
+const MAGIC: &[u8; 8] = b"RBIOFMI\0";
+const VERSION: usize = 1;
+
+fn put<W: io::Write>(writer: &mut W, value: usize) -> io::Result<()> {
+    writer.write_all(&(value as u64).to_le_bytes())
+}
+
+fn invalid(msg: &str) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, msg)
+}
+
+/// Write the FM-index components held by the given sampled suffix array (BWT, Less, Occ and
+/// the suffix array sample) to a file.
+pub fn save<P, DBWT, DLess, DOcc>(
+    path: P,
+    sa: &SampledSuffixArray<DBWT, DLess, DOcc>,
+) -> io::Result<()>
+where
+    P: AsRef<Path>,
+    DBWT: Borrow<BWT>,
+    DLess: Borrow<Less>,
+    DOcc: Borrow<Occ>,
+{
+    write(io::BufWriter::new(fs::File::create(path)?), sa)
+}
+
+/// Write the FM-index components held by the given sampled suffix array to an `io::Write`
+/// (see `save`).
+pub fn write<W, DBWT, DLess, DOcc>(
+    mut writer: W,
+    sa: &SampledSuffixArray<DBWT, DLess, DOcc>,
+) -> io::Result<()>
+where
+    W: io::Write,
+    DBWT: Borrow<BWT>,
+    DLess: Borrow<Less>,
+    DOcc: Borrow<Occ>,
+{
+    let bwt = sa.bwt.borrow();
+    let less = sa.less.borrow();
+    let occ = sa.occ.borrow();
+    let mut extra_rows = sa.extra_rows.iter().collect::<Vec<_>>();
+    extra_rows.sort_unstable();
+
+    writer.write_all(MAGIC)?;
+    for &value in &[
+        VERSION,
+        bwt.len(),
+        less.len(),
+        occ.occ.len(),
+        occ.k as usize,
+        sa.s,
+        sa.sentinel as usize,
+        sa.sample.len(),
+        extra_rows.len(),
+    ] {
+        put(&mut writer, value)?;
+    }
+
+    // pad the BWT such that all following words are aligned
+    writer.write_all(bwt)?;
+    writer.write_all(&[0; 8][..(8 - bwt.len() % 8) % 8])?;
+    for &value in less {
+        put(&mut writer, value)?;
+    }
+    for checkpoints in &occ.occ {
+        put(&mut writer, checkpoints.len())?;
+    }
+    for &value in occ.occ.iter().flatten() {
+        put(&mut writer, value)?;
+    }
+    for &value in &sa.sample {
+        put(&mut writer, value)?;
+    }
+    for (&row, &pos) in extra_rows {
+        put(&mut writer, row)?;
+        put(&mut writer, pos)?;
+    }
+    writer.flush()
+}
+
+/// Load an FM-index from a file written by `save`, reading it entirely into memory.
+///
+/// Fails with an error of kind `InvalidData` if the file is not a valid index.
+pub fn load<P: AsRef<Path>>(path: P) -> io::Result<PersistentFMIndex> {
+    PersistentFMIndex::new(Storage::Owned(fs::read(path)?))
+}
+
+/// Load an FM-index from a file written by `save`, memory-mapping it on unix systems (see
+/// module docs). On other systems, the file is read entirely into memory like with `load`.
+///
+/// Fails with an error of kind `InvalidData` if the file is not a valid index.
+///
+/// # Safety
+///
+/// The file must not be modified or truncated, by this or any other process, while the
+/// returned index (or anything borrowed from it) is alive. Otherwise, the index could observe
+/// changing data, which is undefined behavior.
+pub unsafe fn load_mapped<P: AsRef<Path>>(path: P) -> io::Result<PersistentFMIndex> {
+    let mut file = fs::File::open(path)?;
+    PersistentFMIndex::new(Storage::map(&mut file)?)
+}
+
+/// Read an FM-index written by `write` from an `io::Read`, keeping it entirely in memory.
+///
+/// Fails with an error of kind `InvalidData` if the input is not a valid index.
+pub fn read<R: io::Read>(mut reader: R) -> io::Result<PersistentFMIndex> {
+    let mut data = Vec::new();
+    reader.read_to_end(&mut data)?;
+    PersistentFMIndex::new(Storage::Owned(data))
+}
+
+/// Consecutive sections of a persisted index, given in bytes.
+struct Sections<'a> {
+    data: &'a [u8],
+    pos: usize,
+}
+
+impl<'a> Sections<'a> {
+    fn take(&mut self, len: Option<usize>) -> io::Result<Range<usize>> {
+        match len {
+            Some(len) if len <= self.data.len() - self.pos => {
+                self.pos += len;
+                Ok(self.pos - len..self.pos)
+            }
+            _ => Err(invalid("truncated FM-index")),
+        }
+    }
+
+    fn take_words(&mut self, n: usize) -> io::Result<Range<usize>> {
+        let bytes = self.take(n.checked_mul(8))?;
+        Ok(bytes.start / 8..bytes.end / 8)
+    }
+
+    fn word(&mut self) -> io::Result<usize> {
+        let bytes = self.take(Some(8))?;
+        Ok(read_word(self.data, bytes.start / 8))
+    }
+}
+
+fn read_word(data: &[u8], i: usize) -> usize {
+    u64::from_le_bytes(data[8 * i..8 * i + 8].try_into().unwrap()) as usize
+}
+
+/// An FM-index loaded with `load`, `load_mapped` or `read`. It can be searched like an `FMIndex` and serves
+/// as the sampled suffix array of the index for locating occurrences (e.g. via
+/// `Interval::occ`).
+///
+/// When memory-mapped, the underlying file must not be modified while the index is in use.
+pub struct PersistentFMIndex {
+    data: Storage,
+    bwt: BWT,
+    less: Less,
+    k: usize,
+    /// Word ranges of the occurrence checkpoints of each symbol.
+    occ: Vec<Range<usize>>,
+    s: usize,
+    sentinel: u8,
+    sample: Range<usize>,
+    /// Word range of the sorted pairs of row and text position.
+    extra_rows: Range<usize>,
+}
+
+impl PersistentFMIndex {
+    fn new(data: Storage) -> io::Result<Self> {
+        let bytes = data.bytes();
+        if bytes.len() < MAGIC.len() || &bytes[..MAGIC.len()] != MAGIC {
+            return Err(invalid("not an FM-index written by rust-bio"));
+        }
+        let mut sections = Sections {
+            data: bytes,
+            pos: MAGIC.len(),
+        };
+        if sections.word()? != VERSION {
+            return Err(invalid("unsupported FM-index version"));
+        }
+        let n = sections.word()?;
+        let less_len = sections.word()?;
+        let symbols = sections.word()?;
+        let k = sections.word()?;
+        let s = sections.word()?;
+        let sentinel = sections.word()?;
+        let sample_len = sections.word()?;
+        let extra_rows_len = sections.word()?;
+        if k == 0 || s == 0 || sentinel > u8::MAX as usize || sample_len != (n + s - 1) / s {
+            return Err(invalid("inconsistent FM-index header"));
+        }
+
+        // the BWT is padded to a multiple of 8 bytes
+        let bwt = bytes[sections.take(n.checked_add((8 - n % 8) % 8))?][..n].to_vec();
+        let less = sections
+            .take_words(less_len)?
+            .map(|i| read_word(bytes, i))
+            .collect();
+        let occ_lens = sections.take_words(symbols)?;
+        let mut occ = Vec::with_capacity(symbols);
+        for i in occ_lens {
+            let checkpoints = sections.take_words(read_word(bytes, i))?;
+            // symbols outside of the alphabet have no checkpoints
+            if !checkpoints.is_empty() && checkpoints.len() != (n + k - 1) / k {
+                return Err(invalid("inconsistent number of occurrence checkpoints"));
+            }
+            occ.push(checkpoints);
+        }
+        let sample = sections.take_words(sample_len)?;
+        let extra_rows = sections.take_words(extra_rows_len.saturating_mul(2))?;
+        if sections.pos != bytes.len() {
+            return Err(invalid("trailing data after FM-index"));
+        }
+
+        let index = PersistentFMIndex {
+            data,
+            bwt,
+            less,
+            k,
+            occ,
+            s,
+            sentinel: sentinel as u8,
+            sample,
+            extra_rows,
+        };
+        index.check_extra_rows()?;
+        Ok(index)
+    }
+
+    /// Check that the rows preceded by a sentinel (see `SampledSuffixArray::get`) are sorted
+    /// and complete, such that suffix array lookups never miss an entry.
+    fn check_extra_rows(&self) -> io::Result<()> {
+        let rows = (self.extra_rows.start..self.extra_rows.end)
+            .step_by(2)
+            .map(|i| self.word(i));
+        let mut expected = self
+            .bwt
+            .iter()
+            .enumerate()
+            .filter(|&(row, &c)| c == self.sentinel && row % self.s != 0)
+            .map(|(row, _)| row);
+        for row in rows {
+            if expected.next() != Some(row) {
+                return Err(invalid("inconsistent suffix array entries after sentinels"));
+            }
+        }
+        if expected.next().is_some() {
+            return Err(invalid("missing suffix array entries after sentinels"));
+        }
+        Ok(())
+    }
+
+    fn word(&self, i: usize) -> usize {
+        read_word(self.data.bytes(), i)
+    }
+
+    /// Get the i-th occurrence checkpoint of symbol a.
+    fn checkpoint(&self, a: u8, i: usize) -> Option<usize> {
+        let range = &self.occ[a as usize];
+        if i < range.len() {
+            Some(self.word(range.start + i))
+        } else {
+            None
+        }
+    }
+
+    /// Get the text position of a row of the BWT preceded by a sentinel.
+    fn extra_row(&self, row: usize) -> Option<usize> {
+        let (mut lo, mut hi) = (0, self.extra_rows.len() / 2);
+        while lo < hi {
+            let mid = (lo + hi) / 2;
+            let i = self.extra_rows.start + 2 * mid;
+            match self.word(i).cmp(&row) {
+                Ordering::Less => lo = mid + 1,
+                Ordering::Greater => hi = mid,
+                Ordering::Equal => return Some(self.word(i + 1)),
+            }
+        }
+        None
+    }
+
+    /// Get the sampling rate of the suffix array.
+    pub fn sampling_rate(&self) -> usize {
+        self.s
+    }
+
+    /// Get the sampling rate of the occurrence array.
+    pub fn occ_sampling_rate(&self) -> usize {
+        self.k
+    }
+
+    /// Whether the index is memory-mapped (instead of being read into memory).
+    pub fn is_mapped(&self) -> bool {
+        self.data.is_mapped()
+    }
+
+    /// Provide a reference to the underlying BWT.
+    pub fn bwt(&self) -> &BWT {
+        &self.bwt
+    }
+
+    /// Length of the BWT, i.e. of the indexed text including sentinels. This is also the
+    /// length of the suffix array.
+    pub fn len(&self) -> usize {
+        self.bwt.len()
+    }
+
+    /// Whether the BWT is empty.
+    pub fn is_empty(&self) -> bool {
+        self.bwt.is_empty()
+    }
+}
+
+impl fmt::Debug for PersistentFMIndex {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("PersistentFMIndex")
+            .field("len", &self.bwt.len())
+            .field("occ_sampling_rate", &self.k)
+            .field("sampling_rate", &self.s)
+            .field("mapped", &self.is_mapped())
+            .finish()
+    }
+}
+
+impl FMIndexable for PersistentFMIndex {
+    /// Get occurrence count of symbol a in BWT[..r+1], like `Occ::get`.
+    fn occ(&self, r: usize, a: u8) -> usize {
+        let lo_checkpoint = r / self.k;
+        let lo_occ = match self.checkpoint(a, lo_checkpoint) {
+            Some(occ) => occ,
+            // checkpoints are validated on load, only symbols outside the alphabet have none
+            None => return 0,
+        };
+
+        // if the sampling rate is infrequent, count backwards from a closer hi checkpoint
+        if self.k > 64 {
+            let hi_checkpoint = lo_checkpoint + 1;
+            if let Some(hi_occ) = self.checkpoint(a, hi_checkpoint) {
+                if lo_occ == hi_occ {
+                    return lo_occ;
+                }
+                let hi_idx = hi_checkpoint * self.k;
+                if (hi_idx - r) < (self.k / 2) {
+                    return hi_occ - bytecount::count(&self.bwt[r + 1..=hi_idx], a);
+                }
+            }
+        }
+
+        let lo_idx = lo_checkpoint * self.k;
+        bytecount::count(&self.bwt[lo_idx + 1..=r], a) + lo_occ
+    }
+
+    fn less(&self, a: u8) -> usize {
+        self.less[a as usize]
+    }
+
+    fn bwt(&self) -> &BWT {
+        &self.bwt
+    }
+
+    fn len(&self) -> usize {
+        self.bwt.len()
+    }
+}
+
+impl SuffixArray for PersistentFMIndex {
+    fn get(&self, index: usize) -> Option<usize> {
+        if index >= self.len() {
+            return None;
+        }
+        let mut pos = index;
+        let mut offset = 0;
+        loop {
+            if pos % self.s == 0 {
+                return Some(self.word(self.sample.start + pos / self.s) + offset);
+            }
+
+            let c = self.bwt[pos];
+            if c == self.sentinel {
+                // see SampledSuffixArray::get, the entries are validated on load
+                return self.extra_row(pos).map(|p| p + offset);
+            }
+
+            pos = self.less(c) + self.occ(pos - 1, c);
+            offset += 1;
+        }
+    }
+
+    fn len(&self) -> usize {
+        self.bwt.len()
+    }
+
+    fn is_empty(&self) -> bool {
+        self.bwt.is_empty()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::alphabets::dna;
+    use crate::data_structures::bwt::{bwt, less};
+    use crate::data_structures::fmindex::FMIndex;
+    use crate::data_structures::suffix_array::suffix_array;
+    use crate::data_structures::text_collection::TextCollection;
+    use rand::prelude::*;
+    use rand::rngs::StdRng;
+
+    #[test]
+    fn test_roundtrip() {
+        let mut rng = StdRng::seed_from_u64(42);
+        let texts: Vec<Vec<u8>> = (0..5)
+            .map(|_| {
+                (0..rng.gen_range(50..300))
+                    .map(|_| *b"ACGTN".choose(&mut rng).unwrap())
+                    .collect()
+            })
+            .collect();
+        let collection = TextCollection::new(&texts);
+        let text = collection.text();
+        let alphabet = dna::n_alphabet();
+        let sa = suffix_array(text);
+        let bwt = bwt(text, &sa);
+        let less = less(&bwt, &alphabet);
+
+        for &(k, s) in &[(1, 1), (3, 5), (100, 32)] {
+            let occ = Occ::new(&bwt, k, &alphabet);
+            let fm = FMIndex::new(&bwt, &less, &occ);
+            let sampled = sa.sample(text, &bwt, &less, &occ, s);
+
+            let dir = tempfile::tempdir().unwrap();
+            let path = dir.path().join("index.fm");
+            save(&path, &sampled).unwrap();
+            // the file is not modified while mapped
+            let mapped = unsafe { load_mapped(&path) }.unwrap();
+            assert_eq!(mapped.is_mapped(), cfg!(unix));
+            let loaded = load(&path).unwrap();
+            assert!(!loaded.is_mapped());
+
+            for loaded in &[mapped, loaded] {
+                assert_eq!(loaded.occ_sampling_rate(), k as usize);
+                assert_eq!(loaded.sampling_rate(), s);
+                assert_eq!(loaded.bwt(), &bwt);
+
+                for r in 0..bwt.len() {
+                    for &a in b"$ACGTN" {
+                        assert_eq!(loaded.occ(r, a), fm.occ(r, a));
+                    }
+                }
+                for i in 0..sa.len() {
+                    assert_eq!(loaded.get(i), Some(sa[i]));
+                }
+                assert_eq!(loaded.get(sa.len()), None);
+            }
+        }
+    }
+
+    #[test]
+    fn test_invalid() {
+        let text = b"ACGTTGCA$";
+        let alphabet = dna::alphabet();
+        let sa = suffix_array(text);
+        let bwt = bwt(text, &sa);
+        let less = less(&bwt, &alphabet);
+        let occ = Occ::new(&bwt, 2, &alphabet);
+        let mut buf = Vec::new();
+        write(&mut buf, &sa.sample(text, &bwt, &less, &occ, 2)).unwrap();
+
+        assert!(read(&buf[..]).is_ok());
+        for data in &[&buf[..buf.len() - 8], &buf[1..], &b""[..]] {
+            assert_eq!(read(*data).unwrap_err().kind(), io::ErrorKind::InvalidData);
+        }
+        let mut trailing = buf.clone();
+        trailing.push(0);
+        assert_eq!(
+            read(&trailing[..]).unwrap_err().kind(),
+            io::ErrorKind::InvalidData
+        );
+    }
+
+    #[test]
+    fn test_inconsistent() {
+        let collection = TextCollection::new(&[&b"ACGT"[..], b"GGAC", b"TTA", b"CA"]);
+        let text = collection.text();
+        let alphabet = dna::alphabet();
+        let sa = suffix_array(text);
+        let bwt = bwt(text, &sa);
+        let less = less(&bwt, &alphabet);
+        let occ = Occ::new(&bwt, 2, &alphabet);
+
+        let mut sampled = sa.sample(text, &bwt, &less, &occ, 3);
+        assert!(!sampled.extra_rows.is_empty());
+        sampled.extra_rows.clear();
+        let mut buf = Vec::new();
+        write(&mut buf, &sampled).unwrap();
+        assert_eq!(
+            read(&buf[..]).unwrap_err().kind(),
+            io::ErrorKind::InvalidData
+        );
+
+        let mut occ = occ.clone();
+        occ.occ[b'A' as usize].pop();
+        let mut buf = Vec::new();
+        write(&mut buf, &sa.sample(text, &bwt, &less, &occ, 3)).unwrap();
+        assert_eq!(
+            read(&buf[..]).unwrap_err().kind(),
+            io::ErrorKind::InvalidData
+        );
+    }
+}
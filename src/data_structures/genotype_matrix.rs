@@ -0,0 +1,287 @@
+//! A bit-packed matrix of biallelic diploid genotypes, storing two bits per genotype.
+//!
+//! Rows are variants and columns are samples. Each genotype is given as the number of
+//! alternative alleles (`0`, `1` or `2`), with `None` denoting a missing call.
+//!
+//! # Example
+//!
+//! ```
+//! use bio::data_structures::genotype_matrix::GenotypeMatrix;
+//!
+//! let mut genotypes = GenotypeMatrix::new(4);
+//! genotypes.push_variant(&[Some(0), Some(1), Some(2), None]);
+//! genotypes.push_variant(&[Some(0), Some(1), Some(2), Some(2)]);
+//!
+//! assert_eq!(genotypes.get(0, 2), Some(2));
+//! assert_eq!(genotypes.get(0, 3), None);
+//! assert_eq!(genotypes.allele_frequency(0), Some(0.5));
+//! assert_eq!(genotypes.missing_count(0), 1);
+//! let column: Vec<_> = genotypes.column(3).collect();
+//! assert_eq!(column, [None, Some(2)]);
+//!
+//! let ld = genotypes.ld(0, 1).unwrap();
+//! assert!((ld.r2 - 1.0).abs() < 1e-12);
+//! ```
+
+use bv::BitVec;
+
+use crate::stats::popgen::ld::{self, LdWindows, LinkageDisequilibrium};
+
+const GENOTYPES_PER_WORD: usize = 32;
+const LOW_BITS: u64 = 0x5555_5555_5555_5555;
+const MISSING: u64 = 0b11;
+
+/// Bit-packed genotype matrix with variants as rows and samples as columns.
+///
+/// Space complexity: O(⌈n / 32⌉ * m) * 64 bit, where n is the number of samples and
+/// m is the number of variants.
+#[derive(Default, Clone, Eq, PartialEq, Hash, Debug, Serialize, Deserialize)]
+pub struct GenotypeMatrix {
+    storage: Vec<u64>,
+    n_samples: usize,
+    words_per_row: usize,
+    n_variants: usize,
+}
+
+impl GenotypeMatrix {
+    /// Create a new, empty matrix for the given number of samples.
+    pub fn new(n_samples: usize) -> Self {
+        GenotypeMatrix {
+            storage: Vec::new(),
+            n_samples,
+            words_per_row: (n_samples + GENOTYPES_PER_WORD - 1) / GENOTYPES_PER_WORD,
+            n_variants: 0,
+        }
+    }
+
+    /// Number of variants (rows).
+    pub fn n_variants(&self) -> usize {
+        self.n_variants
+    }
+
+    /// Number of samples (columns).
+    pub fn n_samples(&self) -> usize {
+        self.n_samples
+    }
+
+    /// Append a variant, given as alternative allele dosages for each sample.
+    ///
+    /// Complexity: O(n), where n is the number of samples.
+    ///
+    /// # Panics
+    ///
+    /// If the number of genotypes differs from the number of samples, or a dosage
+    /// is greater than 2.
+    pub fn push_variant(&mut self, genotypes: &[Option<u8>]) {
+        assert_eq!(
+            genotypes.len(),
+            self.n_samples,
+            "number of genotypes has to match the number of samples"
+        );
+        let offset = self.storage.len();
+        self.storage.resize(offset + self.words_per_row, 0);
+        for (sample, genotype) in genotypes.iter().enumerate() {
+            let code = match *genotype {
+                Some(dosage) => {
+                    assert!(dosage <= 2, "only biallelic diploid genotypes supported");
+                    dosage as u64
+                }
+                None => MISSING,
+            };
+            let (word, shift) = Self::addr(sample);
+            self.storage[offset + word] |= code << shift;
+        }
+        self.n_variants += 1;
+    }
+
+    /// Genotype of the given sample at the given variant.
+    ///
+    /// Complexity: O(1)
+    pub fn get(&self, variant: usize, sample: usize) -> Option<u8> {
+        assert!(variant < self.n_variants && sample < self.n_samples);
+        let (word, shift) = Self::addr(sample);
+        let code = (self.row_words(variant)[word] >> shift) & MISSING;
+        if code == MISSING {
+            None
+        } else {
+            Some(code as u8)
+        }
+    }
+
+    /// Iterate over the genotypes of all samples at the given variant.
+    pub fn row(&self, variant: usize) -> impl Iterator<Item = Option<u8>> + '_ {
+        (0..self.n_samples).map(move |sample| self.get(variant, sample))
+    }
+
+    /// Iterate over the genotypes of the given sample at all variants.
+    pub fn column(&self, sample: usize) -> impl Iterator<Item = Option<u8>> + '_ {
+        (0..self.n_variants).map(move |variant| self.get(variant, sample))
+    }
+
+    /// Iterate over all variants, yielding an iterator over the genotypes of each.
+    pub fn rows(&self) -> impl Iterator<Item = impl Iterator<Item = Option<u8>> + '_> + '_ {
+        (0..self.n_variants).map(move |variant| self.row(variant))
+    }
+
+    /// Mask of samples with a missing genotype at the given variant.
+    pub fn missing_mask(&self, variant: usize) -> BitVec<u64> {
+        let mut mask = BitVec::new_fill(false, self.n_samples as u64);
+        for (sample, genotype) in self.row(variant).enumerate() {
+            if genotype.is_none() {
+                mask.set(sample as u64, true);
+            }
+        }
+        mask
+    }
+
+    /// Number of samples with a missing genotype at the given variant.
+    ///
+    /// Complexity: O(n / 32), where n is the number of samples.
+    pub fn missing_count(&self, variant: usize) -> usize {
+        self.counts(variant).2
+    }
+
+    /// Alternative allele frequency among the called samples of the given variant.
+    /// Returns `None` if no sample has a call.
+    ///
+    /// Complexity: O(n / 32), where n is the number of samples.
+    pub fn allele_frequency(&self, variant: usize) -> Option<f64> {
+        let (het, hom_alt, missing) = self.counts(variant);
+        let called = self.n_samples - missing;
+        if called == 0 {
+            None
+        } else {
+            Some((het + 2 * hom_alt) as f64 / (2 * called) as f64)
+        }
+    }
+
+    /// Linkage disequilibrium between two variants, see `stats::popgen::ld::ld`.
+    pub fn ld(&self, a: usize, b: usize) -> Option<LinkageDisequilibrium> {
+        ld::ld(self.row(a), self.row(b))
+    }
+
+    /// Iterate over all pairs of variants at most `window` rows apart having an `r²` of
+    /// at least `min_r2`, see `stats::popgen::ld::LdWindows`.
+    pub fn ld_windows(
+        &self,
+        window: usize,
+        min_r2: f64,
+    ) -> LdWindows<impl Iterator<Item = Vec<Option<u8>>> + '_, Vec<Option<u8>>> {
+        LdWindows::new(
+            (0..self.n_variants).map(move |variant| self.row(variant).collect()),
+            window,
+            min_r2,
+        )
+    }
+
+    /// Count heterozygous, homozygous alternative and missing genotypes of a variant
+    /// via popcounts over the packed words.
+    fn counts(&self, variant: usize) -> (usize, usize, usize) {
+        let (mut het, mut hom_alt, mut missing) = (0, 0, 0);
+        for &word in self.row_words(variant) {
+            let low = word & LOW_BITS;
+            let high = (word >> 1) & LOW_BITS;
+            het += (low & !high).count_ones() as usize;
+            hom_alt += (high & !low).count_ones() as usize;
+            missing += (low & high).count_ones() as usize;
+        }
+        (het, hom_alt, missing)
+    }
+
+    fn row_words(&self, variant: usize) -> &[u64] {
+        let offset = variant * self.words_per_row;
+        &self.storage[offset..offset + self.words_per_row]
+    }
+
+    fn addr(sample: usize) -> (usize, usize) {
+        (
+            sample / GENOTYPES_PER_WORD,
+            (sample % GENOTYPES_PER_WORD) * 2,
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use bv::Bits;
+
+    fn example() -> GenotypeMatrix {
+        let mut genotypes = GenotypeMatrix::new(70);
+        let row: Vec<_> = (0..70)
+            .map(|i| {
+                if i % 7 == 0 {
+                    None
+                } else {
+                    Some((i % 3) as u8)
+                }
+            })
+            .collect();
+        genotypes.push_variant(&row);
+        genotypes.push_variant(&vec![Some(2); 70]);
+        genotypes
+    }
+
+    #[test]
+    fn test_get() {
+        let genotypes = example();
+        assert_eq!(genotypes.n_variants(), 2);
+        assert_eq!(genotypes.n_samples(), 70);
+        for i in 0..70 {
+            let expected = if i % 7 == 0 {
+                None
+            } else {
+                Some((i % 3) as u8)
+            };
+            assert_eq!(genotypes.get(0, i), expected);
+            assert_eq!(genotypes.get(1, i), Some(2));
+        }
+        assert_eq!(genotypes.column(69).collect::<Vec<_>>(), [Some(0), Some(2)]);
+        assert_eq!(genotypes.rows().count(), 2);
+    }
+
+    #[test]
+    fn test_counts() {
+        let genotypes = example();
+        let row: Vec<_> = genotypes.row(0).collect();
+        let missing = row.iter().filter(|g| g.is_none()).count();
+        let alt: usize = row.iter().flatten().map(|&g| g as usize).sum();
+        assert_eq!(genotypes.missing_count(0), missing);
+        assert_eq!(
+            genotypes.allele_frequency(0),
+            Some(alt as f64 / (2 * (70 - missing)) as f64)
+        );
+        assert_eq!(genotypes.allele_frequency(1), Some(1.0));
+
+        let mask = genotypes.missing_mask(0);
+        assert_eq!(mask.bit_len(), 70);
+        assert!(mask.get_bit(0) && mask.get_bit(63) && !mask.get_bit(1));
+    }
+
+    #[test]
+    fn test_all_missing() {
+        let mut genotypes = GenotypeMatrix::new(3);
+        genotypes.push_variant(&[None, None, None]);
+        assert_eq!(genotypes.allele_frequency(0), None);
+        assert_eq!(genotypes.missing_count(0), 3);
+    }
+
+    #[test]
+    fn test_ld_windows() {
+        let mut genotypes = GenotypeMatrix::new(4);
+        genotypes.push_variant(&[Some(0), Some(1), Some(2), Some(0)]);
+        genotypes.push_variant(&[Some(0), Some(1), Some(2), None]);
+        genotypes.push_variant(&[Some(2), Some(1), Some(0), Some(2)]);
+        let pairs: Vec<_> = genotypes
+            .ld_windows(1, 0.9)
+            .map(|pair| (pair.i, pair.j))
+            .collect();
+        assert_eq!(pairs, [(0, 1), (1, 2)]);
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_invalid_dosage() {
+        GenotypeMatrix::new(1).push_variant(&[Some(3)]);
+    }
+}
@@ -0,0 +1,219 @@
+//! HyperLogLog cardinality estimation (Flajolet et al., 2007), e.g. for estimating the number
+//! of distinct k-mers in a sequencing dataset without storing them.
+//!
+//! Each item is hashed, and the first `p` bits of the hash select one of `2^p` registers,
+//! which keeps the maximum number of leading zeros observed in the remaining bits. The
+//! harmonic mean of the registers yields an estimate with a relative standard error of about
+//! `1.04 / sqrt(2^p)`, using `2^p` bytes of memory. Estimators of different parts of the
+//! input can be merged.
+//!
+//! Hashes are seeded (see `HyperLogLog::with_seed`), such that independent estimators can be
+//! obtained for the same input. K-mers are hashed the same way on all platforms (see
+//! `add_kmers`), such that estimates are reproducible.
+//!
+//! # Example
+//!
+//! ```
+//! use bio::data_structures::hyperloglog::HyperLogLog;
+//!
+//! let mut hll = HyperLogLog::new(12);
+//! // 16 distinct canonical 4-mers (out of 17 4-mers)
+//! hll.add_kmers(b"ACGGCTGAGATGATCCGAT", 4);
+//! assert!((hll.estimate() - 16.0).abs() < 1.0);
+//! ```
+
+use std::collections::hash_map::DefaultHasher;
+use std::hash::{Hash, Hasher};
+
+use crate::data_structures::kmer_counting::{hash_kmer, kmers};
+use crate::utils::TextSlice;
+
+/// A HyperLogLog cardinality estimator.
+#[derive(Clone, Eq, PartialEq, Hash, Debug, Serialize, Deserialize)]
+pub struct HyperLogLog {
+    precision: u8,
+    registers: Vec<u8>,
+    #[serde(default)]
+    seed: u64,
+}
+
+impl HyperLogLog {
+    /// Create a new estimator with `2^precision` registers.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the precision is not within 4 to 18.
+    pub fn new(precision: u8) -> Self {
+        Self::with_seed(precision, 0)
+    }
+
+    /// Create a new estimator with `2^precision` registers, using the given seed for hashing.
+    /// Only estimators with the same seed can be merged.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the precision is not within 4 to 18.
+    pub fn with_seed(precision: u8, seed: u64) -> Self {
+        assert!(
+            (4..=18).contains(&precision),
+            "Expecting a precision between 4 and 18."
+        );
+        HyperLogLog {
+            precision,
+            registers: vec![0; 1 << precision],
+            seed,
+        }
+    }
+
+    /// The seed of the hash function.
+    pub fn seed(&self) -> u64 {
+        self.seed
+    }
+
+    /// The precision, i.e. the base 2 logarithm of the number of registers.
+    pub fn precision(&self) -> u8 {
+        self.precision
+    }
+
+    /// The expected relative standard error of the estimate.
+    pub fn relative_error(&self) -> f64 {
+        1.04 / (self.registers.len() as f64).sqrt()
+    }
+
+    /// Add an item given by its (uniformly distributed) 64 bit hash.
+    pub fn insert_hash(&mut self, hash: u64) {
+        let p = self.precision as u32;
+        let register = (hash >> (64 - p)) as usize;
+        // position of the first set bit in the remaining bits, at most 64 - p + 1
+        let rank = ((hash << p) | (1 << (p - 1))).leading_zeros() as u8 + 1;
+        if rank > self.registers[register] {
+            self.registers[register] = rank;
+        }
+    }
+
+    /// Add a hashable item. Note that the hash depends on the `Hash` implementation of the
+    /// item (e.g. `usize` is hashed differently on 32 and 64 bit platforms) and on the hasher
+    /// of the standard library. Use `insert_hash` or `add_kmers` for estimates that have to
+    /// be reproducible across platforms.
+    pub fn insert<T: Hash + ?Sized>(&mut self, item: &T) {
+        let mut hasher = DefaultHasher::new();
+        item.hash(&mut hasher);
+        // mix the hash, since all bits are used
+        self.insert_hash(hash_kmer(hasher.finish(), self.seed));
+    }
+
+    /// Add the canonical k-mers of the given DNA sequence, skipping k-mers containing symbols
+    /// other than `ACGT` (see `kmer_counting::kmers`).
+    pub fn add_kmers(&mut self, text: TextSlice<'_>, k: usize) {
+        for kmer in kmers(text, k, true) {
+            self.insert_hash(hash_kmer(kmer, self.seed));
+        }
+    }
+
+    /// Merge another estimator into this one, yielding the estimator of the union of both
+    /// inputs.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the estimators differ in precision or seed.
+    pub fn merge(&mut self, other: &HyperLogLog) {
+        assert!(
+            self.precision == other.precision && self.seed == other.seed,
+            "Expecting estimators with the same precision and seed."
+        );
+        for (a, &b) in self.registers.iter_mut().zip(&other.registers) {
+            *a = (*a).max(b);
+        }
+    }
+
+    /// Estimate the number of distinct items. For small cardinalities, where many registers
+    /// are still empty, linear counting is used instead.
+    pub fn estimate(&self) -> f64 {
+        let m = self.registers.len() as f64;
+        let alpha = match self.registers.len() {
+            16 => 0.673,
+            32 => 0.697,
+            64 => 0.709,
+            _ => 0.7213 / (1.0 + 1.079 / m),
+        };
+        let sum: f64 = self.registers.iter().map(|&r| 2f64.powi(-(r as i32))).sum();
+        let estimate = alpha * m * m / sum;
+
+        let empty = self.registers.iter().filter(|&&r| r == 0).count();
+        if estimate <= 2.5 * m && empty > 0 {
+            m * (m / empty as f64).ln()
+        } else {
+            estimate
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::prelude::*;
+    use rand::rngs::StdRng;
+
+    #[test]
+    fn test_estimate() {
+        let mut hll = HyperLogLog::new(14);
+        assert_eq!(hll.estimate(), 0.0);
+        for i in 0..100_000u64 {
+            hll.insert(&i);
+            // duplicates do not change the estimate
+            hll.insert(&(i / 2));
+        }
+        let error = (hll.estimate() - 100_000.0).abs() / 100_000.0;
+        assert!(error < 3.0 * hll.relative_error(), "error {}", error);
+    }
+
+    #[test]
+    fn test_merge_kmers() {
+        let mut rng = StdRng::seed_from_u64(42);
+        let texts: Vec<Vec<u8>> = (0..2)
+            .map(|_| {
+                (0..20_000)
+                    .map(|_| *b"ACGT".choose(&mut rng).unwrap())
+                    .collect()
+            })
+            .collect();
+        let mut all = HyperLogLog::new(12);
+        for text in &texts {
+            all.add_kmers(text, 21);
+        }
+        let mut first = HyperLogLog::new(12);
+        first.add_kmers(&texts[0], 21);
+        let mut second = HyperLogLog::new(12);
+        second.add_kmers(&texts[1], 21);
+        first.merge(&second);
+        assert_eq!(first, all);
+
+        // random 21-mers are almost surely distinct
+        let expected = 2.0 * (20_000.0 - 20.0);
+        let error = (all.estimate() - expected).abs() / expected;
+        assert!(error < 3.0 * all.relative_error(), "error {}", error);
+    }
+
+    #[test]
+    fn test_seed() {
+        let text = b"ACGGCTGAGATGATCCGATTAGCTAGCATCGATCGATTTAGCGA";
+        let estimator = |seed| {
+            let mut hll = HyperLogLog::with_seed(4, seed);
+            hll.add_kmers(text, 8);
+            hll
+        };
+        assert_eq!(estimator(7), estimator(7));
+        assert_ne!(estimator(7).registers, estimator(8).registers);
+        // the same on all platforms
+        assert_eq!(
+            estimator(0).registers,
+            [7, 3, 1, 2, 3, 2, 3, 4, 2, 2, 0, 4, 1, 2, 6, 0]
+        );
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_merge_seed() {
+        HyperLogLog::with_seed(12, 1).merge(&HyperLogLog::with_seed(12, 2));
+    }
+}
@@ -24,12 +24,26 @@
 //!     assert_eq!(r.interval(), &(Interval::from(11..20)));
 //!     assert_eq!(r.data(), &"Range_1");
 //! }
+//!
+//! // stabbing queries and annotation density without collecting entries
+//! assert_eq!(tree.find_point(&12).count(), 1);
+//! assert_eq!(tree.count(15..27), 2);
+//! assert_eq!(tree.coverage(15..27), 7);
+//!
+//! // overlaps of at least 3 positions, reciprocally covering at least half of both intervals
+//! let overlaps = tree.find_overlaps(13..22, 3, 0.5);
+//! assert_eq!(overlaps.len(), 1);
+//! assert_eq!(overlaps[0].data(), &"Range_1");
+//! assert_eq!(overlaps[0].len(), 7);
+//! assert_eq!(overlaps[0].reciprocal(), 7.0 / 9.0);
 //! ```
 
 use crate::utils::Interval;
+use num_traits::{ToPrimitive, Zero};
 use std::cmp;
 use std::iter::FromIterator;
 use std::mem;
+use std::ops::Sub;
 
 /// An interval tree for storing intervals with data
 #[derive(Clone, Eq, PartialEq, Hash, Debug, Serialize, Deserialize)]
@@ -63,12 +77,55 @@ impl<'a, N: Ord + Clone + 'a, D: 'a> Entry<'a, N, D> {
     }
 }
 
-/// An `IntervalTreeIterator` is returned by `Intervaltree::find` and iterates over the entries
-/// overlapping the query
+/// An entry overlapping the query of `IntervalTree::find_overlaps`, together with the length
+/// of the overlap.
+#[derive(Copy, Clone, PartialEq, Debug, Serialize)]
+pub struct Overlap<'a, N: Ord + Clone, D> {
+    data: &'a D,
+    interval: &'a Interval<N>,
+    len: N,
+    reciprocal: f64,
+}
+
+impl<'a, N: Ord + Clone + 'a, D: 'a> Overlap<'a, N, D> {
+    /// Get a reference to the data for this entry
+    pub fn data(&self) -> &'a D {
+        self.data
+    }
+
+    /// Get a reference to the interval for this entry
+    pub fn interval(&self) -> &'a Interval<N> {
+        self.interval
+    }
+
+    /// Length of the overlap with the query.
+    pub fn len(&self) -> N {
+        self.len.clone()
+    }
+
+    /// Whether the overlap is empty, which is never the case for returned overlaps.
+    pub fn is_empty(&self) -> bool
+    where
+        N: Zero,
+    {
+        self.len.is_zero()
+    }
+
+    /// Reciprocal overlap, i.e. the smaller of the fractions of the query and of the entry
+    /// that are covered by the overlap.
+    pub fn reciprocal(&self) -> f64 {
+        self.reciprocal
+    }
+}
+
+/// An `IntervalTreeIterator` is returned by `Intervaltree::find` and `IntervalTree::find_point`
+/// and iterates over the entries overlapping the query
 #[derive(Default, Clone, Eq, PartialEq, Hash, Debug, Serialize)]
 pub struct IntervalTreeIterator<'a, N: Ord + Clone, D> {
     nodes: Vec<&'a Node<N, D>>,
     interval: Interval<N>,
+    // whether the query is the single point `interval.start`
+    point: bool,
 }
 
 impl<'a, N: Ord + Clone + 'a, D: 'a> Iterator for IntervalTreeIterator<'a, N, D> {
@@ -89,13 +146,13 @@ impl<'a, N: Ord + Clone + 'a, D: 'a> Iterator for IntervalTreeIterator<'a, N, D>
 
                 // don't traverse right if the query interval is completely before the current
                 // interval
-                if self.interval.end > candidate.interval.start {
+                if before_end(&self.interval, self.point, &candidate.interval) {
                     if let Some(ref right) = candidate.right {
                         self.nodes.push(right);
                     }
 
                     // overlap is only possible if both tests pass
-                    if intersect(&self.interval, &candidate.interval) {
+                    if overlaps(&self.interval, self.point, &candidate.interval) {
                         return Some(Entry {
                             data: &candidate.value,
                             interval: &candidate.interval,
@@ -194,10 +251,107 @@ impl<N: Clone + Ord, D> IntervalTree<N, D> {
             Some(ref n) => IntervalTreeIterator {
                 nodes: vec![n],
                 interval,
+                point: false,
             },
             None => {
                 let nodes = vec![];
-                IntervalTreeIterator { nodes, interval }
+                IntervalTreeIterator {
+                    nodes,
+                    interval,
+                    point: false,
+                }
+            }
+        }
+    }
+
+    /// Find all intervals containing the given point, i.e. with `start <= point < end`, and
+    /// return an `IntervalTreeIterator` over them (a stabbing query).
+    pub fn find_point(&self, point: &N) -> IntervalTreeIterator<'_, N, D> {
+        let interval = Interval::from(point.clone()..point.clone());
+        IntervalTreeIterator {
+            nodes: self.root.iter().collect(),
+            interval,
+            point: true,
+        }
+    }
+
+    /// Count the intervals overlapping the provided `Interval`, without creating entries.
+    pub fn count<I: Into<Interval<N>>>(&self, interval: I) -> usize {
+        let mut count = 0;
+        self.visit_overlaps(&interval.into(), |_| count += 1);
+        count
+    }
+
+    /// Sum of the lengths of the overlaps of all stored intervals with the provided `Interval`.
+    /// Positions covered by multiple intervals are counted multiple times.
+    pub fn coverage<I: Into<Interval<N>>>(&self, interval: I) -> N
+    where
+        N: Sub<Output = N> + Zero,
+    {
+        let interval = interval.into();
+        let mut coverage = N::zero();
+        self.visit_overlaps(&interval, |node| {
+            let start = cmp::max(&interval.start, &node.interval.start).clone();
+            let end = cmp::min(&interval.end, &node.interval.end).clone();
+            coverage = mem::replace(&mut coverage, N::zero()) + (end - start);
+        });
+        coverage
+    }
+
+    /// Find the intervals overlapping the provided `Interval` by at least `min_len` positions,
+    /// with a reciprocal overlap of at least `min_reciprocal` (i.e. the overlap covers at least
+    /// this fraction of both the query and the found interval, as used for merging structural
+    /// variants). Returns the entries together with their overlap.
+    pub fn find_overlaps<I: Into<Interval<N>>>(
+        &self,
+        interval: I,
+        min_len: N,
+        min_reciprocal: f64,
+    ) -> Vec<Overlap<'_, N, D>>
+    where
+        N: Sub<Output = N> + Zero + ToPrimitive,
+    {
+        let interval = interval.into();
+        let query_len = (interval.end.clone() - interval.start.clone()).to_f64();
+        let mut overlaps = Vec::new();
+        self.visit_overlaps(&interval, |node| {
+            let start = cmp::max(&interval.start, &node.interval.start).clone();
+            let end = cmp::min(&interval.end, &node.interval.end).clone();
+            let len = end - start;
+            let node_len = (node.interval.end.clone() - node.interval.start.clone()).to_f64();
+            let reciprocal = match (len.to_f64(), query_len, node_len) {
+                (Some(len), Some(query_len), Some(node_len)) => len / query_len.max(node_len),
+                _ => 0.0,
+            };
+            if len >= min_len && reciprocal >= min_reciprocal {
+                overlaps.push(Overlap {
+                    data: &node.value,
+                    interval: &node.interval,
+                    len,
+                    reciprocal,
+                });
+            }
+        });
+        overlaps
+    }
+
+    /// Call `f` on each node overlapping the given interval, using the same pruning as
+    /// `IntervalTreeIterator`.
+    fn visit_overlaps<'a, F: FnMut(&'a Node<N, D>)>(&'a self, interval: &Interval<N>, mut f: F) {
+        let mut nodes: Vec<&Node<N, D>> = self.root.iter().collect();
+        while let Some(candidate) = nodes.pop() {
+            if interval.start < candidate.max {
+                if let Some(ref left) = candidate.left {
+                    nodes.push(left);
+                }
+                if interval.end > candidate.interval.start {
+                    if let Some(ref right) = candidate.right {
+                        nodes.push(right);
+                    }
+                    if intersect(interval, &candidate.interval) {
+                        f(candidate);
+                    }
+                }
             }
         }
     }
@@ -370,6 +524,25 @@ fn swap_interval_data<N: Ord + Clone, D>(node_1: &mut Node<N, D>, node_2: &mut N
     mem::swap(&mut node_1.interval, &mut node_2.interval);
 }
 
+/// Whether the query (an interval, or the single point `query.start`) does not end before the
+/// start of the given interval.
+fn before_end<N: Ord + Clone>(query: &Interval<N>, point: bool, interval: &Interval<N>) -> bool {
+    if point {
+        query.start >= interval.start
+    } else {
+        query.end > interval.start
+    }
+}
+
+/// Whether the query (an interval, or the single point `query.start`) overlaps the given interval.
+fn overlaps<N: Ord + Clone>(query: &Interval<N>, point: bool, interval: &Interval<N>) -> bool {
+    if point {
+        interval.start <= query.start && query.start < interval.end
+    } else {
+        intersect(query, interval)
+    }
+}
+
 fn intersect<N: Ord + Clone>(range_1: &Interval<N>, range_2: &Interval<N>) -> bool {
     range_1.start < range_1.end
         && range_2.start < range_2.end
@@ -379,7 +552,7 @@ fn intersect<N: Ord + Clone>(range_1: &Interval<N>, range_2: &Interval<N>) -> bo
 
 #[cfg(test)]
 mod tests {
-    use super::{Entry, IntervalTree, Node};
+    use super::{Entry, IntervalTree, Node, Overlap};
     use crate::utils::Interval;
     use std::cmp;
     use std::cmp::{max, min};
@@ -606,6 +779,73 @@ mod tests {
         assert_not_found(&tree, 55..55);
     }
 
+    #[test]
+    fn test_find_point() {
+        let tree: IntervalTree<i64, ()> =
+            vec![(0..10, ()), (5..15, ()), (10..10, ()), (12..20, ())]
+                .into_iter()
+                .collect();
+        let mut hits: Vec<_> = tree.find_point(&10).map(|e| e.interval().clone()).collect();
+        hits.sort_by_key(|i| i.start);
+        assert_eq!(hits, vec![(5..15).into()]);
+        assert_eq!(tree.find_point(&0).count(), 1);
+        assert_eq!(tree.find_point(&12).count(), 2);
+        assert_eq!(tree.find_point(&20).count(), 0);
+        assert_eq!(IntervalTree::<i64, ()>::new().find_point(&1).count(), 0);
+    }
+
+    #[test]
+    fn test_count_and_coverage() {
+        let tree: IntervalTree<i64, ()> =
+            vec![(0..10, ()), (5..15, ()), (12..20, ()), (30..40, ())]
+                .into_iter()
+                .collect();
+        for (query, count, coverage) in [(0..100, 4, 38), (8..13, 3, 8), (20..30, 0, 0)] {
+            assert_eq!(tree.count(query.clone()), count);
+            assert_eq!(tree.count(query.clone()), tree.find(query.clone()).count());
+            assert_eq!(tree.coverage(query), coverage);
+        }
+    }
+
+    #[test]
+    fn test_find_overlaps() {
+        let tree: IntervalTree<u64, usize> =
+            vec![(0..100, 0), (40..60, 1), (45..200, 2), (95..96, 3)]
+                .into_iter()
+                .collect();
+        let mut overlaps = tree
+            .find_overlaps(50..150, 0, 0.0)
+            .iter()
+            .map(|o| (*o.data(), o.len(), o.reciprocal()))
+            .collect::<Vec<_>>();
+        overlaps.sort_by_key(|o| o.0);
+        assert_eq!(
+            overlaps,
+            [
+                (0, 50, 0.5),
+                (1, 10, 0.1),
+                (2, 100, 100.0 / 155.0),
+                (3, 1, 0.01)
+            ]
+        );
+        // the overlap length is bounded by the query and the interval
+        for o in tree.find_overlaps(50..150, 0, 0.0) {
+            assert!(!o.is_empty());
+            assert!(o.len() <= 100 && o.len() <= o.interval().end - o.interval().start);
+        }
+
+        let data = |overlaps: Vec<Overlap<'_, u64, usize>>| {
+            let mut data = overlaps.iter().map(|o| *o.data()).collect::<Vec<_>>();
+            data.sort_unstable();
+            data
+        };
+        assert_eq!(data(tree.find_overlaps(50..150, 10, 0.0)), [0, 1, 2]);
+        assert_eq!(data(tree.find_overlaps(50..150, 11, 0.0)), [0, 2]);
+        assert_eq!(data(tree.find_overlaps(50..150, 0, 0.5)), [0, 2]);
+        assert_eq!(data(tree.find_overlaps(50..150, 0, 0.7)), []);
+        assert_eq!(data(tree.find_overlaps(300..400, 0, 0.0)), []);
+    }
+
     #[test]
     fn from_iterator() {
         let tree: IntervalTree<i64, ()> = vec![(10..100, ()), (10..20, ()), (1..8, ())]
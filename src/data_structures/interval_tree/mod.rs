@@ -3,5 +3,5 @@ mod avl_interval_tree;
 
 pub use array_backed_interval_tree::ArrayBackedIntervalTree;
 pub use avl_interval_tree::{
-    Entry, EntryMut, IntervalTree, IntervalTreeIterator, IntervalTreeIteratorMut,
+    Entry, EntryMut, IntervalTree, IntervalTreeIterator, IntervalTreeIteratorMut, Overlap,
 };
@@ -0,0 +1,449 @@
+//! Counting of DNA k-mers.
+//!
+//! K-mers of up to 32 bases are packed into 64 bit integers with two bits per base
+//! (A=0, C=1, G=2, T=3, case-insensitive), with the first base in the most significant bits.
+//! K-mers containing other symbols (e.g. `N`) are skipped. Optionally, k-mers are counted in
+//! canonical form, i.e. as the smaller encoding of the k-mer and its reverse complement, such
+//! that both strands of a sequence contribute to the same count.
+//!
+//! Sequences are processed one at a time, such that memory only depends on the number of
+//! distinct k-mers. Counters built from different chunks of the input (e.g. in parallel)
+//! can be merged.
+//!
+//! # Example
+//!
+//! ```
+//! use bio::data_structures::kmer_counting::{decode_kmer, KmerCounter};
+//!
+//! let mut counter = KmerCounter::new(3, true);
+//! counter.add_all(&[&b"ACGTT"[..], b"AACGN"]);
+//! // ACG occurs twice, and once more as CGT (its reverse complement)
+//! assert_eq!(counter.get(b"ACG"), 3);
+//! assert_eq!(counter.get(b"CGT"), 3);
+//!
+//! let mut counts: Vec<_> = counter
+//!     .iter()
+//!     .map(|(kmer, count)| (decode_kmer(kmer, 3), count))
+//!     .collect();
+//! counts.sort();
+//! assert_eq!(
+//!     counts,
+//!     [(b"AAC".to_vec(), 2), (b"ACG".to_vec(), 3)]
+//! );
+//! ```
+
+use std::collections::hash_map;
+use std::collections::HashMap;
+
+use crate::utils::{parallel, TextSlice};
+
+/// 2-bit encoding of DNA symbols.
+fn encode(a: u8) -> Option<u64> {
+    match a {
+        b'A' | b'a' => Some(0),
+        b'C' | b'c' => Some(1),
+        b'G' | b'g' => Some(2),
+        b'T' | b't' => Some(3),
+        _ => None,
+    }
+}
+
+fn mask(k: usize) -> u64 {
+    if k == 32 {
+        u64::MAX
+    } else {
+        (1 << (2 * k)) - 1
+    }
+}
+
+/// Hash an encoded k-mer (the finalizer of SplitMix64, Steele et al., 2014), such that
+/// hash values are spread uniformly even for low-complexity k-mers. Hashes are stable
+/// across platforms and runs, such that data structures built on them can be serialized.
+pub(crate) fn hash_kmer(kmer: u64, seed: u64) -> u64 {
+    let mut h = kmer ^ seed.wrapping_mul(0x9e37_79b9_7f4a_7c15);
+    h = (h ^ (h >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+    h = (h ^ (h >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+    h ^ (h >> 31)
+}
+
+/// Encode a k-mer with two bits per base. Returns `None` if it contains symbols other than
+/// `ACGT` (case-insensitive).
+///
+/// # Panics
+///
+/// Panics if the k-mer is empty or longer than 32 bases.
+pub fn encode_kmer(kmer: TextSlice<'_>) -> Option<u64> {
+    assert!(
+        !kmer.is_empty() && kmer.len() <= 32,
+        "Expecting 0 < k <= 32"
+    );
+    kmer.iter()
+        .try_fold(0, |code, &a| Some((code << 2) | encode(a)?))
+}
+
+/// Decode a k-mer of length `k` from its 2-bit encoding.
+pub fn decode_kmer(code: u64, k: usize) -> Vec<u8> {
+    (0..k)
+        .rev()
+        .map(|i| b"ACGT"[((code >> (2 * i)) & 3) as usize])
+        .collect()
+}
+
+/// Encoding of the reverse complement of the encoded k-mer of length `k`.
+pub fn revcomp_kmer(code: u64, k: usize) -> u64 {
+    let mut code = !code;
+    let mut rev = 0;
+    for _ in 0..k {
+        rev = (rev << 2) | (code & 3);
+        code >>= 2;
+    }
+    rev
+}
+
+/// Iterator over the encoded k-mers of a sequence, see `kmers`.
+#[derive(Clone, Debug)]
+pub struct Kmers<'a> {
+    text: std::slice::Iter<'a, u8>,
+    k: usize,
+    canonical: bool,
+    mask: u64,
+    shift: u64,
+    fwd: u64,
+    rev: u64,
+    // length of the current run of valid symbols
+    len: usize,
+}
+
+impl Iterator for Kmers<'_> {
+    type Item = u64;
+
+    fn next(&mut self) -> Option<u64> {
+        for &a in self.text.by_ref() {
+            let code = match encode(a) {
+                Some(code) => code,
+                None => {
+                    self.len = 0;
+                    continue;
+                }
+            };
+            self.fwd = ((self.fwd << 2) | code) & self.mask;
+            self.rev = (self.rev >> 2) | ((3 - code) << self.shift);
+            self.len += 1;
+            if self.len >= self.k {
+                return Some(if self.canonical {
+                    self.fwd.min(self.rev)
+                } else {
+                    self.fwd
+                });
+            }
+        }
+        None
+    }
+}
+
+/// Iterate over the encoded k-mers of the given DNA sequence, from left to right, skipping
+/// k-mers that contain symbols other than `ACGT` (case-insensitive).
+/// Complexity: O(n), where n is the length of the sequence.
+///
+/// # Arguments
+///
+/// * `text` - the DNA sequence
+/// * `k` - the k-mer length (at most 32)
+/// * `canonical` - whether to report the smaller encoding of each k-mer and its reverse
+///   complement
+///
+/// # Example
+///
+/// ```
+/// use bio::data_structures::kmer_counting::{decode_kmer, kmers};
+///
+/// let fwd: Vec<_> = kmers(b"GANCCA", 2, false).map(|kmer| decode_kmer(kmer, 2)).collect();
+/// assert_eq!(fwd, [b"GA", b"CC", b"CA"]);
+/// let canonical: Vec<_> = kmers(b"GANCCA", 2, true).map(|kmer| decode_kmer(kmer, 2)).collect();
+/// assert_eq!(canonical, [b"GA", b"CC", b"CA"]);
+/// let canonical: Vec<_> = kmers(b"TTG", 2, true).map(|kmer| decode_kmer(kmer, 2)).collect();
+/// assert_eq!(canonical, [b"AA", b"CA"]);
+/// ```
+pub fn kmers(text: TextSlice<'_>, k: usize, canonical: bool) -> Kmers<'_> {
+    assert!(k > 0 && k <= 32, "Expecting 0 < k <= 32");
+    Kmers {
+        text: text.iter(),
+        k,
+        canonical,
+        mask: mask(k),
+        shift: 2 * (k as u64 - 1),
+        fwd: 0,
+        rev: 0,
+        len: 0,
+    }
+}
+
+/// A counter of DNA k-mers.
+#[derive(Clone, Eq, PartialEq, Debug, Serialize, Deserialize)]
+pub struct KmerCounter {
+    k: usize,
+    canonical: bool,
+    counts: HashMap<u64, u64>,
+}
+
+impl KmerCounter {
+    /// Create a new, empty counter.
+    ///
+    /// # Arguments
+    ///
+    /// * `k` - the k-mer length (at most 32)
+    /// * `canonical` - whether to count k-mers together with their reverse complement
+    ///
+    /// # Panics
+    ///
+    /// Panics if `k` is zero or larger than 32.
+    pub fn new(k: usize, canonical: bool) -> Self {
+        assert!(k > 0 && k <= 32, "Expecting 0 < k <= 32");
+        KmerCounter {
+            k,
+            canonical,
+            counts: HashMap::new(),
+        }
+    }
+
+    /// The k-mer length.
+    pub fn k(&self) -> usize {
+        self.k
+    }
+
+    /// Whether k-mers are counted in canonical form.
+    pub fn is_canonical(&self) -> bool {
+        self.canonical
+    }
+
+    /// Number of distinct (canonical) k-mers.
+    pub fn len(&self) -> usize {
+        self.counts.len()
+    }
+
+    /// Whether no k-mer has been counted.
+    pub fn is_empty(&self) -> bool {
+        self.counts.is_empty()
+    }
+
+    /// Total number of counted k-mers.
+    pub fn total(&self) -> u64 {
+        self.counts.values().sum()
+    }
+
+    /// Count the k-mers of a sequence.
+    pub fn add(&mut self, text: TextSlice<'_>) {
+        for kmer in kmers(text, self.k, self.canonical) {
+            *self.counts.entry(kmer).or_insert(0) += 1;
+        }
+    }
+
+    /// Count the k-mers of all given sequences.
+    pub fn add_all<T: AsRef<[u8]>, I: IntoIterator<Item = T>>(&mut self, texts: I) {
+        for text in texts {
+            self.add(text.as_ref());
+        }
+    }
+
+    /// Count the k-mers of all given sequences like `add_all`, using the given number of
+    /// threads (`0` for all available cores, see `utils::parallel`). Long sequences are
+    /// split into overlapping pieces, such that a single chromosome is counted in parallel, too.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use bio::data_structures::kmer_counting::KmerCounter;
+    ///
+    /// let texts = [&b"ACGTTGCATGACGTAGCAGCTACGACTTAGAC"[..], b"AACGNTTAGCA"];
+    /// let mut counter = KmerCounter::new(5, true);
+    /// counter.add_all_parallel(&texts, 4);
+    ///
+    /// let mut expected = KmerCounter::new(5, true);
+    /// expected.add_all(&texts);
+    /// assert_eq!(counter, expected);
+    /// ```
+    pub fn add_all_parallel<T: AsRef<[u8]>>(&mut self, texts: &[T], threads: usize) {
+        let threads = parallel::threads(threads);
+        let total_len = texts.iter().map(|text| text.as_ref().len()).sum::<usize>();
+        let piece_len = ((total_len + threads - 1) / threads).max(self.k);
+        // pieces overlap by k - 1, such that each k-mer is counted exactly once
+        let mut pieces = Vec::new();
+        for text in texts {
+            let text = text.as_ref();
+            let mut start = 0;
+            while start + self.k <= text.len() {
+                pieces.push(&text[start..text.len().min(start + piece_len + self.k - 1)]);
+                start += piece_len;
+            }
+        }
+
+        let counters = parallel::map_chunks(&pieces, threads, 1, |_, pieces| {
+            let mut counter = KmerCounter::new(self.k, self.canonical);
+            counter.add_all(pieces);
+            counter
+        });
+        for counter in counters {
+            self.merge(&counter);
+        }
+    }
+
+    /// Add the counts of another counter to this one.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the counters differ in k-mer length or canonical form.
+    pub fn merge(&mut self, other: &KmerCounter) {
+        assert!(
+            self.k == other.k && self.canonical == other.canonical,
+            "Expecting counters with the same k and canonical form."
+        );
+        for (&kmer, &count) in &other.counts {
+            *self.counts.entry(kmer).or_insert(0) += count;
+        }
+    }
+
+    /// Count of the given encoded k-mer. If k-mers are counted in canonical form, the
+    /// k-mer is canonicalized first.
+    pub fn get_code(&self, kmer: u64) -> u64 {
+        let kmer = if self.canonical {
+            kmer.min(revcomp_kmer(kmer, self.k))
+        } else {
+            kmer
+        };
+        self.counts.get(&kmer).copied().unwrap_or(0)
+    }
+
+    /// Count of the given k-mer. K-mers containing symbols other than `ACGT` have count zero.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the k-mer does not have length k.
+    pub fn get(&self, kmer: TextSlice<'_>) -> u64 {
+        assert_eq!(kmer.len(), self.k, "Expecting a k-mer of length k.");
+        encode_kmer(kmer).map_or(0, |kmer| self.get_code(kmer))
+    }
+
+    /// The k-mer coverage histogram, i.e. the number of distinct (canonical) k-mers with each
+    /// count, indexed by count. The histogram is empty if no k-mer has been counted.
+    pub fn histogram(&self) -> Vec<u64> {
+        let max = self.counts.values().copied().max().unwrap_or(0) as usize;
+        let mut histogram = vec![0; if self.is_empty() { 0 } else { max + 1 }];
+        for &count in self.counts.values() {
+            histogram[count as usize] += 1;
+        }
+        histogram
+    }
+
+    /// Iterate over pairs of encoded (canonical) k-mers and their counts, in arbitrary order.
+    /// K-mers can be decoded with `decode_kmer`.
+    pub fn iter(&self) -> impl Iterator<Item = (u64, u64)> + '_ {
+        self.counts.iter().map(|(&kmer, &count)| (kmer, count))
+    }
+}
+
+impl IntoIterator for KmerCounter {
+    type Item = (u64, u64);
+    type IntoIter = hash_map::IntoIter<u64, u64>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.counts.into_iter()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::alphabets::dna;
+    use rand::prelude::*;
+    use rand::rngs::StdRng;
+
+    #[test]
+    fn test_encoding() {
+        let kmer = b"GATTACAGATTACAGATTACAGATTACAGATT";
+        let code = encode_kmer(kmer).unwrap();
+        assert_eq!(decode_kmer(code, 32), kmer);
+        assert_eq!(
+            decode_kmer(revcomp_kmer(code, 32), 32),
+            dna::revcomp(&kmer[..])
+        );
+        assert_eq!(encode_kmer(b"ACGN"), None);
+        assert_eq!(revcomp_kmer(encode_kmer(b"AAC").unwrap(), 3), 0b101111);
+    }
+
+    #[test]
+    fn test_hash_kmer_stable() {
+        // the hashes are part of serialized sketches and filters, hence they must not differ
+        // between platforms or releases
+        assert_eq!(hash_kmer(1, 0), 6238072747940578789);
+        assert_eq!(
+            hash_kmer(encode_kmer(b"ACGT").unwrap(), 42),
+            13212201574183626578
+        );
+    }
+
+    #[test]
+    fn test_kmers_match_naive() {
+        let text = b"ACGGTNACCATTGnGGCATAC";
+        for k in 1..6 {
+            let naive: Vec<_> = text.windows(k).filter_map(encode_kmer).collect();
+            assert_eq!(kmers(text, k, false).collect::<Vec<_>>(), naive);
+
+            let rc = dna::revcomp(&text[..]);
+            let mut fwd: Vec<_> = kmers(text, k, true).collect();
+            let mut rev: Vec<_> = kmers(&rc, k, true).collect();
+            fwd.sort_unstable();
+            rev.sort_unstable();
+            assert_eq!(fwd, rev);
+        }
+    }
+
+    #[test]
+    fn test_counter_merge() {
+        let texts = [&b"ACGTACGT"[..], b"TTTT", b"ACNGT"];
+        let mut all = KmerCounter::new(2, false);
+        all.add_all(&texts);
+        assert_eq!(all.total(), 7 + 3 + 2);
+        assert_eq!(all.get(b"AC"), 3);
+        assert_eq!(all.get(b"TT"), 3);
+        assert_eq!(all.get(b"NA"), 0);
+
+        let mut first = KmerCounter::new(2, false);
+        first.add(texts[0]);
+        let mut rest = KmerCounter::new(2, false);
+        rest.add_all(&texts[1..]);
+        first.merge(&rest);
+        assert_eq!(first, all);
+        assert_eq!(first.into_iter().count(), all.len());
+        // AC, GT and TT occur three times, CG twice and TA once
+        assert_eq!(all.histogram(), [0, 1, 1, 3]);
+        assert!(KmerCounter::new(2, false).histogram().is_empty());
+    }
+
+    #[test]
+    fn test_add_all_parallel() {
+        let mut rng = StdRng::seed_from_u64(42);
+        let texts: Vec<Vec<u8>> = [1000, 3, 0, 257]
+            .iter()
+            .map(|&len| {
+                (0..len)
+                    .map(|_| *b"ACGTN".choose(&mut rng).unwrap())
+                    .collect()
+            })
+            .collect();
+        for &k in &[1, 4, 11] {
+            let mut expected = KmerCounter::new(k, true);
+            expected.add_all(&texts);
+            for threads in 1..8 {
+                let mut counter = KmerCounter::new(k, true);
+                counter.add_all_parallel(&texts, threads);
+                assert_eq!(counter, expected);
+            }
+        }
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_merge_different_k() {
+        KmerCounter::new(2, false).merge(&KmerCounter::new(3, false));
+    }
+}
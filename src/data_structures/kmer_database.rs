@@ -0,0 +1,428 @@
+//! Sorted sets of canonical DNA k-mers and their counts on disk, similar to a minimal version
+//! of the databases written by KMC (Kokot et al., 2017).
+//!
+//! A `KmerDatabaseBuilder` counts k-mers (see `kmer_counting`) and writes them sorted by their
+//! 2-bit encoding to a compact file of 12 bytes per k-mer (a little-endian 64-bit k-mer and a
+//! 32-bit count, saturating at `u32::MAX`). A `KmerDatabase` answers membership and count
+//! queries on such a file via binary search. On unix systems, `KmerDatabase::load_mapped`
+//! memory-maps the file, such that only the accessed pages are read, while
+//! `KmerDatabase::load` reads it entirely into memory. Since entries are sorted,
+//! databases can be combined (`union`, `intersect`) in a single pass with constant memory.
+//!
+//! # Example
+//!
+//! ```
+//! use bio::data_structures::kmer_database::{KmerDatabase, KmerDatabaseBuilder};
+//!
+//! let dir = tempfile::tempdir().unwrap();
+//! let mut builder = KmerDatabaseBuilder::new(3);
+//! builder.add_all(&[&b"ACGTT"[..], b"AACGN"]);
+//! builder.save(dir.path().join("a.kmers")).unwrap();
+//!
+//! let mut builder = KmerDatabaseBuilder::new(3);
+//! builder.add(b"GACGA");
+//! builder.save(dir.path().join("b.kmers")).unwrap();
+//!
+//! let a = KmerDatabase::load(dir.path().join("a.kmers")).unwrap();
+//! let b = KmerDatabase::load(dir.path().join("b.kmers")).unwrap();
+//! assert_eq!(a.len(), 2);
+//! // ACG occurs twice, and once more as CGT (its reverse complement)
+//! assert_eq!(a.get(b"CGT"), 3);
+//! assert!(!a.contains(b"GAC"));
+//!
+//! let mut shared = Vec::new();
+//! a.intersect(&b, &mut shared).unwrap();
+//! let shared = KmerDatabase::read(&shared[..]).unwrap();
+//! assert_eq!(shared.len(), 1);
+//! assert_eq!(shared.get(b"ACG"), 1);
+//! ```
+
+use std::convert::TryInto;
+use std::fmt;
+use std::fs;
+use std::io;
+use std::path::Path;
+
+use itertools::{EitherOrBoth, Itertools};
+
+use crate::data_structures::kmer_counting::{encode_kmer, revcomp_kmer, KmerCounter};
+use crate::utils::mmap::Storage;
+use crate::utils::TextSlice;
+
+const MAGIC: &[u8; 8] = b"RBIOKMR\0";
+const VERSION: u64 = 1;
+const HEADER_LEN: usize = 24;
+const ENTRY_LEN: usize = 12;
+
+fn invalid(msg: &str) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, msg)
+}
+
+/// Write a database from encoded canonical k-mers and counts, which have to be sorted by
+/// k-mer and free of duplicates.
+fn write_entries<W, I>(mut writer: W, k: usize, entries: I) -> io::Result<()>
+where
+    W: io::Write,
+    I: IntoIterator<Item = (u64, u64)>,
+{
+    writer.write_all(MAGIC)?;
+    writer.write_all(&VERSION.to_le_bytes())?;
+    writer.write_all(&(k as u64).to_le_bytes())?;
+    for (kmer, count) in entries {
+        writer.write_all(&kmer.to_le_bytes())?;
+        writer.write_all(&(count.min(u32::MAX as u64) as u32).to_le_bytes())?;
+    }
+    writer.flush()
+}
+
+/// A builder for k-mer databases, counting canonical k-mers in memory before writing them
+/// sorted to disk.
+#[derive(Clone, Eq, PartialEq, Debug, Serialize, Deserialize)]
+pub struct KmerDatabaseBuilder {
+    counter: KmerCounter,
+    min_count: u64,
+}
+
+impl KmerDatabaseBuilder {
+    /// Create a new builder for k-mers of length k (at most 32).
+    ///
+    /// # Panics
+    ///
+    /// Panics if `k` is zero or larger than 32.
+    pub fn new(k: usize) -> Self {
+        KmerDatabaseBuilder::from_counter(KmerCounter::new(k, true))
+    }
+
+    /// Create a builder from already counted k-mers.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the counter does not count k-mers in canonical form.
+    pub fn from_counter(counter: KmerCounter) -> Self {
+        assert!(
+            counter.is_canonical(),
+            "Expecting a counter of canonical k-mers."
+        );
+        KmerDatabaseBuilder {
+            counter,
+            min_count: 1,
+        }
+    }
+
+    /// Only write k-mers occurring at least the given number of times (default: 1), e.g. to
+    /// exclude k-mers stemming from sequencing errors.
+    pub fn min_count(mut self, min_count: u64) -> Self {
+        self.min_count = min_count;
+        self
+    }
+
+    /// Count the k-mers of a sequence.
+    pub fn add(&mut self, text: TextSlice<'_>) {
+        self.counter.add(text);
+    }
+
+    /// Count the k-mers of all given sequences.
+    pub fn add_all<T: AsRef<[u8]>, I: IntoIterator<Item = T>>(&mut self, texts: I) {
+        self.counter.add_all(texts);
+    }
+
+    /// Write the database to an `io::Write`.
+    pub fn write<W: io::Write>(&self, writer: W) -> io::Result<()> {
+        let mut entries = self
+            .counter
+            .iter()
+            .filter(|&(_, count)| count >= self.min_count)
+            .collect::<Vec<_>>();
+        entries.sort_unstable();
+        write_entries(writer, self.counter.k(), entries)
+    }
+
+    /// Write the database to a file.
+    pub fn save<P: AsRef<Path>>(&self, path: P) -> io::Result<()> {
+        self.write(io::BufWriter::new(fs::File::create(path)?))
+    }
+}
+
+/// A sorted set of canonical k-mers with counts, as written by `KmerDatabaseBuilder`.
+///
+/// When memory-mapped (see `load_mapped`), the underlying file must not be modified while the
+/// database is in use.
+pub struct KmerDatabase {
+    data: Storage,
+    k: usize,
+    len: usize,
+}
+
+impl KmerDatabase {
+    /// Load a database from a file, reading it entirely into memory.
+    ///
+    /// Fails with an error of kind `InvalidData` if the file is not a valid database.
+    pub fn load<P: AsRef<Path>>(path: P) -> io::Result<Self> {
+        KmerDatabase::new(Storage::Owned(fs::read(path)?))
+    }
+
+    /// Load a database from a file, memory-mapping it on unix systems. On other systems, the
+    /// file is read entirely into memory like with `load`.
+    ///
+    /// Fails with an error of kind `InvalidData` if the file is not a valid database.
+    ///
+    /// # Safety
+    ///
+    /// The file must not be modified or truncated, by this or any other process, while the
+    /// returned database (or anything borrowed from it) is alive.
+    pub unsafe fn load_mapped<P: AsRef<Path>>(path: P) -> io::Result<Self> {
+        let mut file = fs::File::open(path)?;
+        KmerDatabase::new(Storage::map(&mut file)?)
+    }
+
+    /// Read a database from an `io::Read`, keeping it entirely in memory.
+    ///
+    /// Fails with an error of kind `InvalidData` if the input is not a valid database.
+    pub fn read<R: io::Read>(mut reader: R) -> io::Result<Self> {
+        let mut data = Vec::new();
+        reader.read_to_end(&mut data)?;
+        KmerDatabase::new(Storage::Owned(data))
+    }
+
+    fn new(data: Storage) -> io::Result<Self> {
+        let bytes = data.bytes();
+        if bytes.len() < HEADER_LEN || &bytes[..MAGIC.len()] != MAGIC {
+            return Err(invalid("not a k-mer database written by rust-bio"));
+        }
+        if u64::from_le_bytes(bytes[8..16].try_into().unwrap()) != VERSION {
+            return Err(invalid("unsupported k-mer database version"));
+        }
+        let k = u64::from_le_bytes(bytes[16..24].try_into().unwrap()) as usize;
+        if k == 0 || k > 32 {
+            return Err(invalid("invalid k-mer length"));
+        }
+        if (bytes.len() - HEADER_LEN) % ENTRY_LEN != 0 {
+            return Err(invalid("truncated k-mer database"));
+        }
+        let len = (bytes.len() - HEADER_LEN) / ENTRY_LEN;
+        Ok(KmerDatabase { data, k, len })
+    }
+
+    /// The k-mer length.
+    pub fn k(&self) -> usize {
+        self.k
+    }
+
+    /// Number of distinct canonical k-mers.
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Whether the database contains no k-mer.
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Whether the database is memory-mapped (instead of being read into memory).
+    pub fn is_mapped(&self) -> bool {
+        self.data.is_mapped()
+    }
+
+    /// The i-th pair of k-mer and count.
+    fn entry(&self, i: usize) -> (u64, u64) {
+        let offset = HEADER_LEN + i * ENTRY_LEN;
+        let bytes = &self.data.bytes()[offset..offset + ENTRY_LEN];
+        (
+            u64::from_le_bytes(bytes[..8].try_into().unwrap()),
+            u32::from_le_bytes(bytes[8..].try_into().unwrap()) as u64,
+        )
+    }
+
+    /// Count of the given encoded k-mer, which is canonicalized first.
+    /// Complexity: O(log n), where n is the number of k-mers in the database.
+    pub fn get_code(&self, kmer: u64) -> u64 {
+        let kmer = kmer.min(revcomp_kmer(kmer, self.k));
+        let (mut lo, mut hi) = (0, self.len);
+        while lo < hi {
+            let mid = (lo + hi) / 2;
+            let (other, count) = self.entry(mid);
+            if other < kmer {
+                lo = mid + 1;
+            } else if other > kmer {
+                hi = mid;
+            } else {
+                return count;
+            }
+        }
+        0
+    }
+
+    /// Count of the given k-mer. K-mers containing symbols other than `ACGT` have count zero.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the k-mer does not have length k.
+    pub fn get(&self, kmer: TextSlice<'_>) -> u64 {
+        assert_eq!(kmer.len(), self.k, "Expecting a k-mer of length k.");
+        encode_kmer(kmer).map_or(0, |kmer| self.get_code(kmer))
+    }
+
+    /// Whether the database contains the given k-mer.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the k-mer does not have length k.
+    pub fn contains(&self, kmer: TextSlice<'_>) -> bool {
+        self.get(kmer) > 0
+    }
+
+    /// Iterate over pairs of encoded canonical k-mers and their counts, sorted by k-mer.
+    /// K-mers can be decoded with `kmer_counting::decode_kmer`.
+    pub fn iter(&self) -> impl Iterator<Item = (u64, u64)> + '_ {
+        (0..self.len).map(move |i| self.entry(i))
+    }
+
+    fn merge<'a>(
+        &'a self,
+        other: &'a KmerDatabase,
+    ) -> impl Iterator<Item = EitherOrBoth<(u64, u64), (u64, u64)>> + 'a {
+        assert_eq!(self.k, other.k, "Expecting databases with the same k.");
+        self.iter()
+            .merge_join_by(other.iter(), |(a, _), (b, _)| a.cmp(b))
+    }
+
+    /// Write the union of both databases to an `io::Write`, summing the counts of k-mers
+    /// contained in both.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the databases differ in k-mer length.
+    pub fn union<W: io::Write>(&self, other: &KmerDatabase, writer: W) -> io::Result<()> {
+        let entries = self.merge(other).map(|entry| match entry {
+            EitherOrBoth::Both((kmer, a), (_, b)) => (kmer, a + b),
+            EitherOrBoth::Left(entry) | EitherOrBoth::Right(entry) => entry,
+        });
+        write_entries(writer, self.k, entries)
+    }
+
+    /// Write the intersection of both databases to an `io::Write`, keeping the smaller of
+    /// both counts.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the databases differ in k-mer length.
+    pub fn intersect<W: io::Write>(&self, other: &KmerDatabase, writer: W) -> io::Result<()> {
+        let entries = self.merge(other).filter_map(|entry| match entry {
+            EitherOrBoth::Both((kmer, a), (_, b)) => Some((kmer, a.min(b))),
+            _ => None,
+        });
+        write_entries(writer, self.k, entries)
+    }
+}
+
+impl fmt::Debug for KmerDatabase {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("KmerDatabase")
+            .field("k", &self.k)
+            .field("len", &self.len)
+            .field("mapped", &self.is_mapped())
+            .finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::prelude::*;
+    use rand::rngs::StdRng;
+
+    fn random_text(rng: &mut StdRng, len: usize) -> Vec<u8> {
+        (0..len).map(|_| *b"ACGTN".choose(rng).unwrap()).collect()
+    }
+
+    fn database(counter: &KmerCounter) -> KmerDatabase {
+        let mut buf = Vec::new();
+        KmerDatabaseBuilder::from_counter(counter.clone())
+            .write(&mut buf)
+            .unwrap();
+        KmerDatabase::read(&buf[..]).unwrap()
+    }
+
+    #[test]
+    fn test_queries() {
+        let mut rng = StdRng::seed_from_u64(42);
+        let mut counter = KmerCounter::new(7, true);
+        counter.add(&random_text(&mut rng, 5000));
+
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("test.kmers");
+        KmerDatabaseBuilder::from_counter(counter.clone())
+            .save(&path)
+            .unwrap();
+        // the file is not modified while mapped
+        let db = unsafe { KmerDatabase::load_mapped(&path) }.unwrap();
+        assert_eq!(db.is_mapped(), cfg!(unix));
+        let loaded = KmerDatabase::load(&path).unwrap();
+        assert!(!loaded.is_mapped());
+        assert!(db.iter().eq(loaded.iter()));
+        assert_eq!(db.k(), 7);
+        assert_eq!(db.len(), counter.len());
+        assert!(db.iter().tuple_windows().all(|(a, b)| a.0 < b.0));
+        for kmer in 0..1 << 14 {
+            assert_eq!(db.get_code(kmer), counter.get_code(kmer));
+        }
+        assert_eq!(db.get(b"ACGTNAC"), 0);
+
+        let mut buf = Vec::new();
+        KmerDatabaseBuilder::from_counter(counter.clone())
+            .min_count(3)
+            .write(&mut buf)
+            .unwrap();
+        let frequent = KmerDatabase::read(&buf[..]).unwrap();
+        assert!(frequent.len() < db.len());
+        assert!(frequent.iter().all(|(_, count)| count >= 3));
+    }
+
+    #[test]
+    fn test_set_operations() {
+        let mut rng = StdRng::seed_from_u64(42);
+        let mut a = KmerCounter::new(5, true);
+        a.add(&random_text(&mut rng, 500));
+        let mut b = KmerCounter::new(5, true);
+        b.add(&random_text(&mut rng, 500));
+
+        let (db_a, db_b) = (database(&a), database(&b));
+        let mut buf = Vec::new();
+        db_a.union(&db_b, &mut buf).unwrap();
+        let union = KmerDatabase::read(&buf[..]).unwrap();
+        let mut buf = Vec::new();
+        db_a.intersect(&db_b, &mut buf).unwrap();
+        let intersection = KmerDatabase::read(&buf[..]).unwrap();
+
+        let mut merged = a.clone();
+        merged.merge(&b);
+        assert_eq!(union.len(), merged.len());
+        for (kmer, count) in merged.iter() {
+            assert_eq!(union.get_code(kmer), count);
+            assert_eq!(
+                intersection.get_code(kmer),
+                a.get_code(kmer).min(b.get_code(kmer))
+            );
+        }
+        assert_eq!(
+            intersection.len(),
+            a.iter().filter(|&(kmer, _)| b.get_code(kmer) > 0).count()
+        );
+    }
+
+    #[test]
+    fn test_invalid() {
+        let mut buf = Vec::new();
+        KmerDatabaseBuilder::new(3).write(&mut buf).unwrap();
+        assert!(KmerDatabase::read(&buf[..]).unwrap().is_empty());
+        buf.push(0);
+        assert_eq!(
+            KmerDatabase::read(&buf[..]).unwrap_err().kind(),
+            io::ErrorKind::InvalidData
+        );
+        assert_eq!(
+            KmerDatabase::read(&b"ACGT"[..]).unwrap_err().kind(),
+            io::ErrorKind::InvalidData
+        );
+    }
+}
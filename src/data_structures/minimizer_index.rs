@@ -0,0 +1,349 @@
+//! A stranded minimizer index over a collection of DNA sequences.
+//!
+//! Minimizers are the smallest (by an invertible hash) canonical k-mers in each window of `w`
+//! consecutive k-mers, following the approach of minimap (Li, 2016).
+//! For each minimizer, the index stores the list of its occurrences as
+//! (sequence id, position, strand). Minimizers that occur too often (e.g. in repeats)
+//! can be excluded by an occurrence cap.
+//! Querying the index with a read yields anchors, i.e. pairs of positions in the read and
+//! the indexed sequences sharing a minimizer, which can be chained, e.g. with
+//! `bio::alignment::sparse`.
+//!
+//! # Example
+//!
+//! ```
+//! use bio::data_structures::minimizer_index::MinimizerIndex;
+//!
+//! let texts = [&b"ACGGCTGAGATGATCCGATTAGCTAGCA"[..], &b"GGATACCAGTTTACGAGCATGCA"[..]];
+//! let index = MinimizerIndex::new(5, 3, &texts);
+//!
+//! let read = b"GATGATCCGATTAGC";
+//! let anchors = index.anchors(read);
+//! assert!(!anchors.is_empty());
+//! assert!(anchors
+//!     .iter()
+//!     .all(|a| a.text == 0 && !a.reverse && a.text_pos == a.read_pos + 8));
+//! ```
+
+use std::collections::{HashMap, VecDeque};
+
+use bio_types::strand::ReqStrand;
+
+/// A minimizer of a sequence.
+#[derive(
+    Default, Copy, Clone, Eq, PartialEq, Ord, PartialOrd, Hash, Debug, Serialize, Deserialize,
+)]
+pub struct Minimizer {
+    /// Hash of the canonical k-mer.
+    pub hash: u64,
+    /// Start position of the k-mer in the sequence.
+    pub pos: usize,
+    /// Whether the canonical k-mer is the reverse complement of the k-mer in the sequence.
+    pub reverse: bool,
+}
+
+/// Compute the canonical (w, k)-minimizers of the given DNA sequence.
+/// K-mers containing symbols other than `ACGT` (case insensitive) are skipped, and
+/// palindromic k-mers (which are their own reverse complement) are never chosen.
+/// On ties, the leftmost minimal k-mer of a window is chosen.
+///
+/// Complexity: O(n), where n is the length of the sequence.
+///
+/// # Arguments
+///
+/// * `text` - the DNA sequence
+/// * `k` - the k-mer length (at most 32)
+/// * `w` - the number of consecutive k-mers in a window
+///
+/// # Example
+///
+/// ```
+/// use bio::alphabets::dna;
+/// use bio::data_structures::minimizer_index::{minimizers, Minimizer};
+/// use std::collections::BTreeSet;
+///
+/// let text = b"ACGGCTGAGATGATCCGATTAGCTAGCA";
+/// let hashes = |m: Vec<Minimizer>| m.into_iter().map(|m| m.hash).collect::<BTreeSet<_>>();
+/// let fwd = minimizers(text, 5, 4);
+/// let rev = minimizers(&dna::revcomp(text), 5, 4);
+/// // the minimal canonical k-mer of each window does not depend on the strand
+/// assert_eq!(hashes(fwd), hashes(rev));
+/// ```
+pub fn minimizers(text: &[u8], k: usize, w: usize) -> Vec<Minimizer> {
+    assert!(k > 0 && k <= 32, "Expecting 0 < k <= 32");
+    assert!(w > 0, "Expecting w > 0");
+    let mask = if k == 32 {
+        u64::MAX
+    } else {
+        (1u64 << (2 * k)) - 1
+    };
+    let shift = 2 * (k as u64 - 1);
+
+    let mut result: Vec<Minimizer> = Vec::new();
+    let mut window: VecDeque<Minimizer> = VecDeque::with_capacity(w);
+    let (mut fwd, mut rev) = (0u64, 0u64);
+    // length of the current run of valid symbols
+    let mut len = 0;
+
+    for (i, &c) in text.iter().enumerate() {
+        let code = match encode(c) {
+            Some(code) => code,
+            None => {
+                len = 0;
+                window.clear();
+                continue;
+            }
+        };
+        fwd = ((fwd << 2) | code) & mask;
+        rev = (rev >> 2) | ((3 - code) << shift);
+        len += 1;
+        if len < k {
+            continue;
+        }
+        let pos = i + 1 - k;
+
+        if fwd != rev {
+            let (kmer, reverse) = if fwd < rev { (fwd, false) } else { (rev, true) };
+            let minimizer = Minimizer {
+                hash: hash64(kmer, mask),
+                pos,
+                reverse,
+            };
+            while window.back().map_or(false, |m| m.hash > minimizer.hash) {
+                window.pop_back();
+            }
+            window.push_back(minimizer);
+        }
+        while window.front().map_or(false, |m| m.pos + w <= pos) {
+            window.pop_front();
+        }
+
+        // only report once the first complete window of the current run is seen
+        if len >= k + w - 1 {
+            if let Some(&m) = window.front() {
+                if result.last().map_or(true, |last| last.pos != m.pos) {
+                    result.push(m);
+                }
+            }
+        }
+    }
+
+    result
+}
+
+/// 2-bit encoding of DNA symbols.
+fn encode(c: u8) -> Option<u64> {
+    match c {
+        b'A' | b'a' => Some(0),
+        b'C' | b'c' => Some(1),
+        b'G' | b'g' => Some(2),
+        b'T' | b't' => Some(3),
+        _ => None,
+    }
+}
+
+/// Invertible integer hash (Thomas Wang), restricted to the given mask. Avoids that
+/// low-complexity k-mers like poly-A are always chosen as minimizers.
+fn hash64(key: u64, mask: u64) -> u64 {
+    let mut key = (!key).wrapping_add(key << 21) & mask;
+    key ^= key >> 24;
+    key = (key.wrapping_add(key << 3)).wrapping_add(key << 8) & mask;
+    key ^= key >> 14;
+    key = (key.wrapping_add(key << 2)).wrapping_add(key << 4) & mask;
+    key ^= key >> 28;
+    key = key.wrapping_add(key << 31) & mask;
+    key
+}
+
+/// Occurrence of a minimizer in the indexed sequences.
+#[derive(
+    Default, Copy, Clone, Eq, PartialEq, Ord, PartialOrd, Hash, Debug, Serialize, Deserialize,
+)]
+pub struct Occurrence {
+    /// Index of the sequence.
+    pub text: usize,
+    /// Start position of the k-mer in the sequence.
+    pub pos: usize,
+    /// Whether the canonical k-mer is the reverse complement of the k-mer in the sequence.
+    pub reverse: bool,
+}
+
+/// A match between a read and an indexed sequence, sharing a minimizer.
+#[derive(
+    Default, Copy, Clone, Eq, PartialEq, Ord, PartialOrd, Hash, Debug, Serialize, Deserialize,
+)]
+pub struct Anchor {
+    /// Index of the sequence.
+    pub text: usize,
+    /// Whether the read matches the reverse complement of the sequence.
+    pub reverse: bool,
+    /// Start position of the k-mer in the sequence.
+    pub text_pos: usize,
+    /// Start position of the k-mer in the read.
+    pub read_pos: usize,
+}
+
+impl Anchor {
+    /// The strand of the sequence the read matches.
+    pub fn strand(&self) -> ReqStrand {
+        if self.reverse {
+            ReqStrand::Reverse
+        } else {
+            ReqStrand::Forward
+        }
+    }
+}
+
+/// A stranded minimizer index over a collection of DNA sequences.
+#[derive(Default, Clone, Eq, PartialEq, Debug, Serialize, Deserialize)]
+pub struct MinimizerIndex {
+    k: usize,
+    w: usize,
+    occurrences: HashMap<u64, Vec<Occurrence>>,
+}
+
+impl MinimizerIndex {
+    /// Create a new minimizer index over the given sequences.
+    ///
+    /// # Arguments
+    ///
+    /// * `k` - the k-mer length (at most 32)
+    /// * `w` - the number of consecutive k-mers in a window
+    /// * `texts` - the sequences to index
+    pub fn new<T: AsRef<[u8]>>(k: usize, w: usize, texts: &[T]) -> Self {
+        MinimizerIndex::with_max_count(k, w, texts, usize::MAX)
+    }
+
+    /// Create a new minimizer index over the given sequences, only considering minimizers
+    /// that occur at most `max_count` times.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use bio::data_structures::minimizer_index::MinimizerIndex;
+    ///
+    /// let texts = [b"ACGTTGCAAGCTAGCTAGCTAGCTAGCTAGCTTTGACCATGA"];
+    /// let index = MinimizerIndex::new(4, 2, &texts);
+    /// let capped = MinimizerIndex::with_max_count(4, 2, &texts, 2);
+    /// assert!(capped.len() < index.len());
+    /// ```
+    pub fn with_max_count<T: AsRef<[u8]>>(
+        k: usize,
+        w: usize,
+        texts: &[T],
+        max_count: usize,
+    ) -> Self {
+        let mut occurrences: HashMap<u64, Vec<Occurrence>> = HashMap::new();
+        for (i, text) in texts.iter().enumerate() {
+            for m in minimizers(text.as_ref(), k, w) {
+                occurrences.entry(m.hash).or_default().push(Occurrence {
+                    text: i,
+                    pos: m.pos,
+                    reverse: m.reverse,
+                });
+            }
+        }
+        occurrences.retain(|_, occ| occ.len() <= max_count);
+
+        MinimizerIndex { k, w, occurrences }
+    }
+
+    /// The k-mer length.
+    pub fn k(&self) -> usize {
+        self.k
+    }
+
+    /// The window size.
+    pub fn w(&self) -> usize {
+        self.w
+    }
+
+    /// Number of distinct minimizers in the index.
+    pub fn len(&self) -> usize {
+        self.occurrences.len()
+    }
+
+    /// Whether the index contains no minimizers.
+    pub fn is_empty(&self) -> bool {
+        self.occurrences.is_empty()
+    }
+
+    /// Occurrences of the minimizer with the given hash.
+    pub fn occurrences(&self, hash: u64) -> &[Occurrence] {
+        self.occurrences.get(&hash).map_or(&[], |occ| &occ[..])
+    }
+
+    /// Find anchors between the given read and the indexed sequences.
+    /// An anchor is reported on the reverse strand if the read matches the reverse
+    /// complement of the sequence; the positions always refer to the forward strand
+    /// of read and sequence.
+    /// Anchors are sorted by sequence, strand, sequence position and read position.
+    pub fn anchors(&self, read: &[u8]) -> Vec<Anchor> {
+        let mut anchors = Vec::new();
+        for m in minimizers(read, self.k, self.w) {
+            for occ in self.occurrences(m.hash) {
+                anchors.push(Anchor {
+                    text: occ.text,
+                    reverse: occ.reverse != m.reverse,
+                    text_pos: occ.pos,
+                    read_pos: m.pos,
+                });
+            }
+        }
+        anchors.sort_unstable();
+
+        anchors
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::alphabets::dna;
+
+    #[test]
+    fn test_minimizers_windows() {
+        let text = b"ACGGCTGAGATGATCCGATTAGCTAGCAGGATACCAGTTTACGAGCATGCA";
+        let (k, w) = (5, 4);
+        let result = minimizers(text, k, w);
+        // every window of w k-mers contains a minimizer
+        for start in 0..=text.len() - (k + w - 1) {
+            assert!(result.iter().any(|m| m.pos >= start && m.pos < start + w));
+        }
+        assert!(result.windows(2).all(|p| p[0].pos < p[1].pos));
+    }
+
+    #[test]
+    fn test_minimizers_skip_ambiguous() {
+        let text = b"ACGGCTGAGNNATGATCCGATTAGC";
+        let result = minimizers(text, 5, 2);
+        assert!(result
+            .iter()
+            .all(|m| !text[m.pos..m.pos + 5].contains(&b'N')));
+        assert!(minimizers(b"ACGNACG", 4, 1).is_empty());
+    }
+
+    #[test]
+    fn test_anchors_reverse_strand() {
+        let text = b"ACGGCTGAGATGATCCGATTAGCTAGCAGGATACCAGTTTACG";
+        let index = MinimizerIndex::new(7, 3, &[text]);
+        let read = dna::revcomp(&text[10..35]);
+        let anchors = index.anchors(&read);
+        assert!(!anchors.is_empty());
+        for a in anchors {
+            assert_eq!(a.strand(), ReqStrand::Reverse);
+            // the read position of the k-mer on the forward strand of the text
+            assert_eq!(a.text_pos, 10 + read.len() - 7 - a.read_pos);
+        }
+    }
+
+    #[test]
+    fn test_occurrence_cap() {
+        let texts = [b"ACGTTGCAAG".repeat(5)];
+        let index = MinimizerIndex::new(5, 3, &texts);
+        assert!(index.occurrences.values().any(|occ| occ.len() > 2));
+        let capped = MinimizerIndex::with_max_count(5, 3, &texts, 2);
+        assert!(capped.is_empty());
+        assert!(capped.anchors(b"ACGTTGCAAG").is_empty());
+    }
+}
@@ -8,12 +8,24 @@
 pub mod annot_map;
 pub mod bit_tree;
 pub mod bitenc;
+pub mod bloom;
 pub mod bwt;
 pub mod fmindex;
+pub mod genotype_matrix;
+pub mod hyperloglog;
 pub mod interpolation_table;
 pub mod interval_tree;
+pub mod kmer_counting;
+pub mod kmer_database;
+pub mod minimizer_index;
 pub mod qgram_index;
 pub mod rank_select;
+pub mod record_boundaries;
+pub mod rlbwt;
+pub mod sketches;
 pub mod smallints;
 pub mod suffix_array;
+pub mod text_collection;
+pub mod text_index;
 pub mod wavelet_matrix;
+pub mod wavelet_tree;
@@ -31,6 +31,7 @@
 use std::cmp;
 use std::collections;
 use std::collections::hash_map::Entry;
+use std::hash::Hash;
 
 use crate::alphabets::{Alphabet, RankTransform};
 use crate::utils;
@@ -68,14 +69,25 @@ impl QGramIndex {
         I: Iterator<Item = &'a u8> + ExactSizeIterator + Clone,
         T: IntoIterator<Item = &'a u8, IntoIter = I> + Sized,
     {
-        let text = text.into_iter();
+        QGramIndex::build(q, vec![text.into_iter()], alphabet, max_count)
+    }
+
+    /// Build the index over the given texts, storing the positions of the q-grams of each text
+    /// shifted by the total length of the preceding texts. Q-grams spanning two texts are not
+    /// indexed.
+    fn build<'a, I>(q: u32, texts: Vec<I>, alphabet: &Alphabet, max_count: usize) -> Self
+    where
+        I: Iterator<Item = &'a u8> + ExactSizeIterator + Clone,
+    {
         let ranks = RankTransform::new(alphabet);
 
         let qgram_count = alphabet.len().pow(q);
         let mut address = vec![0; qgram_count + 1];
 
-        for qgram in ranks.qgrams(q, text.clone()) {
-            address[qgram] += 1;
+        for text in &texts {
+            for qgram in ranks.qgrams(q, text.clone()) {
+                address[qgram] += 1;
+            }
         }
 
         for a in address.iter_mut() {
@@ -92,13 +104,18 @@ impl QGramIndex {
 
         {
             let mut offset = vec![0; qgram_count];
-            for (i, qgram) in ranks.qgrams(q, text).enumerate() {
-                let a = address[qgram];
-                if address[qgram + 1] - a != 0 {
-                    // if not masked, insert positions
-                    pos[a + offset[qgram]] = i;
-                    offset[qgram] += 1;
+            let mut start = 0;
+            for text in texts {
+                let len = text.len();
+                for (i, qgram) in ranks.qgrams(q, text).enumerate() {
+                    let a = address[qgram];
+                    if address[qgram + 1] - a != 0 {
+                        // if not masked, insert positions
+                        pos[a + offset[qgram]] = start + i;
+                        offset[qgram] += 1;
+                    }
                 }
+                start += len;
             }
         }
 
@@ -115,6 +132,12 @@ impl QGramIndex {
         self.q
     }
 
+    /// Whether the given symbol belongs to the alphabet of the index. Patterns with other
+    /// symbols cannot be searched.
+    pub fn contains_symbol(&self, a: u8) -> bool {
+        self.ranks.ranks.contains_key(a as usize)
+    }
+
     /// Return text positions with matching q-gram. Complexity O(1).
     pub fn qgram_matches(&self, qgram: usize) -> &[usize] {
         &self.pos[self.address[qgram]..self.address[qgram + 1]]
@@ -124,13 +147,41 @@ impl QGramIndex {
     /// Complexity O(m + k) for pattern of length m and k being the number of matching q-grams.
     ///
     /// A match is a substring of `pattern` and a corresponding substring of the text that share at least `min_count` q-grams.
+    /// Matches are sorted by their start in the text and then in the pattern.
     pub fn matches(&self, pattern: &[u8], min_count: usize) -> Vec<Match> {
+        self.diagonal_matches(pattern, min_count, |p| ((), p))
+            .into_iter()
+            .map(|(_, m)| m)
+            .collect()
+    }
+
+    /// Return exact matches (substrings) of the given pattern.
+    /// Complexity O(m + k) for pattern of length m and k being the number of matching q-grams.
+    ///
+    /// An exact match is a substring of `pattern` occurring in the text of length at least `q`.
+    /// Matches are sorted by their start in the text and then in the pattern.
+    pub fn exact_matches(&self, pattern: &[u8]) -> Vec<ExactMatch> {
+        self.diagonal_exact_matches(pattern, |p| ((), p))
+            .into_iter()
+            .map(|(_, m)| m)
+            .collect()
+    }
+
+    /// Collect the matches of the given pattern per diagonal, where `locate` maps an indexed
+    /// position to the text it belongs to and the position within that text. Matches are
+    /// sorted by text, their start in the text and then in the pattern.
+    fn diagonal_matches<K, F>(&self, pattern: &[u8], min_count: usize, locate: F) -> Vec<(K, Match)>
+    where
+        K: Copy + Ord + Hash,
+        F: Fn(usize) -> (K, usize),
+    {
         let q = self.q as usize;
         let mut diagonals = collections::HashMap::new();
         for (i, qgram) in self.ranks.qgrams(self.q, pattern).enumerate() {
             for &p in self.qgram_matches(qgram) {
-                let diagonal = p - i;
-                match diagonals.entry(diagonal) {
+                let (key, p) = locate(p);
+                let diagonal = p as isize - i as isize;
+                match diagonals.entry((key, diagonal)) {
                     Entry::Vacant(v) => {
                         v.insert(Match {
                             pattern: Interval {
@@ -153,25 +204,36 @@ impl QGramIndex {
                 }
             }
         }
-        diagonals
+        let mut matches = diagonals
             .into_iter()
-            .filter_map(|(_, m)| if m.count >= min_count { Some(m) } else { None })
-            .collect()
+            .filter_map(|((key, _), m)| {
+                if m.count >= min_count {
+                    Some((key, m))
+                } else {
+                    None
+                }
+            })
+            .collect::<Vec<_>>();
+        // independent of the (randomized) hash order
+        matches.sort_unstable_by_key(|&(key, m)| (key, m.text.start, m.pattern.start));
+        matches
     }
 
-    /// Return exact matches (substrings) of the given pattern.
-    /// Complexity O(m + k) for pattern of length m and k being the number of matching q-grams.
-    ///
-    /// An exact match is a substring of `pattern` occurring in the text of length at least `q`.
-    pub fn exact_matches(&self, pattern: &[u8]) -> Vec<ExactMatch> {
+    /// Collect the exact matches of the given pattern per diagonal (see `diagonal_matches`).
+    fn diagonal_exact_matches<K, F>(&self, pattern: &[u8], locate: F) -> Vec<(K, ExactMatch)>
+    where
+        K: Copy + Ord + Hash,
+        F: Fn(usize) -> (K, usize),
+    {
         let q = self.q as usize;
         let mut diagonals = collections::HashMap::new();
         let mut matches = Vec::new();
 
         for (i, qgram) in self.ranks.qgrams(self.q, pattern).enumerate() {
             for &p in self.qgram_matches(qgram) {
-                let diagonal = p as i32 - i as i32;
-                match diagonals.entry(diagonal) {
+                let (key, p) = locate(p);
+                let diagonal = p as isize - i as isize;
+                match diagonals.entry((key, diagonal)) {
                     Entry::Vacant(v) => {
                         v.insert(ExactMatch {
                             pattern: Interval {
@@ -188,7 +250,7 @@ impl QGramIndex {
                         let m = o.get_mut();
                         if m.pattern.stop - q + 1 != i {
                             // discontinue match
-                            matches.push(*m);
+                            matches.push((key, *m));
                             // start new match
                             m.pattern.start = i;
                             m.text.start = p;
@@ -199,14 +261,153 @@ impl QGramIndex {
                 }
             }
         }
-        for (_, m) in diagonals {
-            matches.push(m);
+        for ((key, _), m) in diagonals {
+            matches.push((key, m));
         }
+        matches.sort_unstable_by_key(|&(key, m)| (key, m.text.start, m.pattern.start));
 
         matches
     }
 }
 
+/// A q-gram index over multiple texts, e.g. all sequences of a FASTA file. Q-grams spanning
+/// two texts are not indexed, and matches are reported per text, with positions relative to
+/// the start of the text.
+///
+/// # Example
+///
+/// ```
+/// use bio::alphabets;
+/// use bio::data_structures::qgram_index::{Interval, MultiQGramIndex, SeqMatch};
+///
+/// let texts = [&b"ACGGCTGAGA"[..], b"TGATTGCTGA"];
+/// let alphabet = alphabets::dna::alphabet();
+/// let qgram_index = MultiQGramIndex::new(3, &texts, &alphabet);
+///
+/// let matches = qgram_index.matches(b"GCTGA", 2);
+/// assert_eq!(
+///     matches,
+///     [
+///         SeqMatch {
+///             seq: 0,
+///             pattern: Interval { start: 0, stop: 5 },
+///             text: Interval { start: 3, stop: 8 },
+///             count: 3
+///         },
+///         SeqMatch {
+///             seq: 1,
+///             pattern: Interval { start: 0, stop: 5 },
+///             text: Interval { start: 5, stop: 10 },
+///             count: 3
+///         }
+///     ]
+/// );
+/// // the occurrence of AGATG spanning both texts is not found
+/// let exact_matches = qgram_index.exact_matches(b"AGATG");
+/// assert!(exact_matches.iter().all(|m| m.text.stop - m.text.start == 3));
+/// ```
+#[derive(Default, Clone, Eq, PartialEq, Ord, PartialOrd, Hash, Debug, Serialize, Deserialize)]
+pub struct MultiQGramIndex {
+    index: QGramIndex,
+    // The start of each text in the positions of `index`, followed by the total length.
+    starts: Vec<usize>,
+}
+
+impl MultiQGramIndex {
+    /// Create a new q-gram index over the given texts.
+    /// The q has to be smaller than b / log2(|A|) with |A| being the alphabet size and b the number
+    /// bits with the `usize` data type.
+    pub fn new<T: AsRef<[u8]>>(q: u32, texts: &[T], alphabet: &Alphabet) -> Self {
+        MultiQGramIndex::with_max_count(q, texts, alphabet, usize::MAX)
+    }
+
+    /// Create a new q-gram index over the given texts, only considering q-grams that occur at
+    /// most `max_count` times in all texts together.
+    /// The q has to be smaller than b / log2(|A|) with |A| being the alphabet size and b the number
+    /// bits with the `usize` data type.
+    pub fn with_max_count<T: AsRef<[u8]>>(
+        q: u32,
+        texts: &[T],
+        alphabet: &Alphabet,
+        max_count: usize,
+    ) -> Self {
+        let texts = texts
+            .iter()
+            .map(|text| text.as_ref().iter())
+            .collect::<Vec<_>>();
+        let mut starts = Vec::with_capacity(texts.len() + 1);
+        starts.push(0);
+        for text in &texts {
+            starts.push(starts[starts.len() - 1] + text.len());
+        }
+        MultiQGramIndex {
+            index: QGramIndex::build(q, texts, alphabet, max_count),
+            starts,
+        }
+    }
+
+    /// The used q.
+    pub fn q(&self) -> u32 {
+        self.index.q
+    }
+
+    /// Number of indexed texts.
+    pub fn seq_count(&self) -> usize {
+        self.starts.len() - 1
+    }
+
+    /// Length of the given text.
+    pub fn seq_len(&self, seq: usize) -> usize {
+        self.starts[seq + 1] - self.starts[seq]
+    }
+
+    /// Map a position of the index to the text and the position within the text.
+    fn locate(&self, p: usize) -> (usize, usize) {
+        let seq = self.starts.partition_point(|&start| start <= p) - 1;
+        (seq, p - self.starts[seq])
+    }
+
+    /// Return pairs of text index and position with matching q-gram. Complexity O(log(t)) per
+    /// position for t texts.
+    pub fn qgram_matches(&self, qgram: usize) -> impl Iterator<Item = (usize, usize)> + '_ {
+        self.index
+            .qgram_matches(qgram)
+            .iter()
+            .map(move |&p| self.locate(p))
+    }
+
+    /// Return matches of the given pattern, matching in at least `min_count` q-grams (see
+    /// `QGramIndex::matches`). Matches are sorted by text, their start in the text and then in
+    /// the pattern.
+    pub fn matches(&self, pattern: &[u8], min_count: usize) -> Vec<SeqMatch> {
+        self.index
+            .diagonal_matches(pattern, min_count, |p| self.locate(p))
+            .into_iter()
+            .map(|(seq, m)| SeqMatch {
+                seq,
+                pattern: m.pattern,
+                text: m.text,
+                count: m.count,
+            })
+            .collect()
+    }
+
+    /// Return exact matches (substrings) of the given pattern (see
+    /// `QGramIndex::exact_matches`). Matches are sorted by text, their start in the text and
+    /// then in the pattern.
+    pub fn exact_matches(&self, pattern: &[u8]) -> Vec<SeqExactMatch> {
+        self.index
+            .diagonal_exact_matches(pattern, |p| self.locate(p))
+            .into_iter()
+            .map(|(seq, m)| SeqExactMatch {
+                seq,
+                pattern: m.pattern,
+                text: m.text,
+            })
+            .collect()
+    }
+}
+
 /// An interval, consisting of start and stop position (the latter exclusive).
 #[derive(
     Default, Copy, Clone, Eq, PartialEq, Ord, PartialOrd, Hash, Debug, Serialize, Deserialize,
@@ -252,6 +453,37 @@ pub struct ExactMatch {
     pub text: Interval,
 }
 
+/// A match between the pattern and one of the texts of a `MultiQGramIndex`.
+#[derive(Default, Copy, Clone, Eq, PartialEq, Hash, Debug, Serialize, Deserialize)]
+pub struct SeqMatch {
+    pub seq: usize,
+    pub pattern: Interval,
+    pub text: Interval,
+    pub count: usize,
+}
+
+impl cmp::Ord for SeqMatch {
+    fn cmp(&self, other: &Self) -> cmp::Ordering {
+        self.count.cmp(&other.count)
+    }
+}
+
+impl cmp::PartialOrd for SeqMatch {
+    fn partial_cmp(&self, other: &Self) -> Option<cmp::Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+/// An exact match between the pattern and one of the texts of a `MultiQGramIndex`.
+#[derive(
+    Default, Copy, Clone, Eq, PartialEq, Ord, PartialOrd, Hash, Debug, Serialize, Deserialize,
+)]
+pub struct SeqExactMatch {
+    pub seq: usize,
+    pub pattern: Interval,
+    pub text: Interval,
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -354,6 +586,18 @@ mod tests {
                 count: 2,
             }]
         );
+
+        // A pattern that matches the text at a position before its own start.
+        let pattern = b"TTTTTACG";
+        let matches = qgram_index.matches(pattern, 1);
+        assert_eq!(
+            matches,
+            [Match {
+                pattern: Interval { start: 5, stop: 8 },
+                text: Interval { start: 0, stop: 3 },
+                count: 1,
+            }]
+        );
     }
 
     #[test]
@@ -397,6 +641,27 @@ mod tests {
         assert!(!exact_matches.is_empty());
     }
 
+    #[test]
+    fn test_deterministic_order() {
+        let (text, alphabet) = setup();
+        let qgram_index = QGramIndex::new(3, text, &alphabet);
+
+        // each call hashes with a different random state
+        let exact_matches = qgram_index.exact_matches(text);
+        let matches = qgram_index.matches(text, 1);
+        assert!(matches.len() > 1);
+        for _ in 0..10 {
+            assert_eq!(qgram_index.exact_matches(text), exact_matches);
+            assert_eq!(qgram_index.matches(text, 1), matches);
+        }
+        assert!(
+            matches
+                .windows(2)
+                .all(|w| (w[0].text.start, w[0].pattern.start)
+                    < (w[1].text.start, w[1].pattern.start))
+        );
+    }
+
     #[test]
     fn test_iterator() {
         let (text, alphabet) = setup();
@@ -407,6 +672,36 @@ mod tests {
         assert!(!exact_matches.is_empty());
     }
 
+    #[test]
+    fn test_multi() {
+        let (text, alphabet) = setup();
+        let texts = [&text[..7], &text[7..], b"GCTGA"];
+        let qgram_index = MultiQGramIndex::new(3, &texts, &alphabet);
+        assert_eq!(qgram_index.seq_count(), 3);
+        assert_eq!(qgram_index.seq_len(1), 7);
+
+        let ranks = alphabets::RankTransform::new(&alphabet);
+        let qgram = ranks.qgrams(3, b"TGA").next().unwrap();
+        // TGA at position 5 of the single text spans the first two texts
+        assert_eq!(
+            qgram_index.qgram_matches(qgram).collect::<Vec<_>>(),
+            [(1, 3), (2, 2)]
+        );
+
+        // positions are relative to each text
+        for m in qgram_index.exact_matches(text) {
+            assert_eq!(m.pattern.get(text), m.text.get(texts[m.seq]));
+        }
+        let matches = qgram_index.matches(b"GCTGAG", 2);
+        assert_eq!(
+            matches.iter().map(|m| (m.seq, m.text)).collect::<Vec<_>>(),
+            [
+                (0, Interval { start: 3, stop: 7 }),
+                (2, Interval { start: 0, stop: 5 })
+            ]
+        );
+    }
+
     #[test]
     #[cfg(feature = "nightly")]
     fn test_serde() {
@@ -0,0 +1,162 @@
+//! Boundaries of records in a concatenated text, represented as a bitvector with a set bit at
+//! the start of each record. Rank and select queries (see `RankSelect`) map positions in the
+//! concatenated text back to records and local offsets without scanning or storing prefix sums
+//! of record lengths, using n + o(n) bits for a text of length n.
+//!
+//! # Example
+//!
+//! ```
+//! use bio::data_structures::record_boundaries::RecordBoundaries;
+//!
+//! // three records of lengths 4, 2 and 3, each followed by a separator (e.g. `$`)
+//! let boundaries = RecordBoundaries::from_lengths(vec![4, 2, 3], true);
+//! assert_eq!(boundaries.len(), 3);
+//! assert_eq!(boundaries.resolve(6), Some((1, 1)));
+//! assert_eq!(boundaries.resolve(4), None);
+//! assert_eq!(boundaries.record_range(2), 8..11);
+//! ```
+
+use std::ops::Range;
+
+use bv::{BitVec, BitsMut};
+
+use crate::data_structures::rank_select::RankSelect;
+
+/// Record boundaries of a concatenated text.
+#[derive(Clone, Eq, PartialEq, Hash, Debug, Serialize, Deserialize)]
+pub struct RecordBoundaries {
+    starts: RankSelect,
+    records: usize,
+    text_len: usize,
+    separated: bool,
+}
+
+impl RecordBoundaries {
+    /// Create the boundaries of records with the given lengths.
+    ///
+    /// # Arguments
+    ///
+    /// * `lengths` - the lengths of the records, in order of concatenation
+    /// * `separated` - whether each record is followed by a separator symbol (e.g. a sentinel),
+    ///   which is not counted in its length
+    ///
+    /// # Panics
+    ///
+    /// Panics if a record is empty and records are not separated, since its boundary would
+    /// coincide with the next one.
+    pub fn from_lengths<I: IntoIterator<Item = usize>>(lengths: I, separated: bool) -> Self {
+        let lengths: Vec<usize> = lengths.into_iter().collect();
+        assert!(
+            separated || lengths.iter().all(|&len| len > 0),
+            "Expecting non-empty records or separators between them."
+        );
+        let text_len: usize = lengths.iter().map(|&len| len + separated as usize).sum();
+        let mut bits: BitVec<u8> = BitVec::new_fill(false, text_len as u64);
+        let mut start = 0;
+        for &len in &lengths {
+            bits.set_bit(start as u64, true);
+            start += len + separated as usize;
+        }
+        // superblocks of (log n)² bits, see RankSelect::new
+        let log_n = (usize::BITS - text_len.leading_zeros()) as usize;
+        let k = (log_n * log_n / 32).max(1);
+
+        RecordBoundaries {
+            starts: RankSelect::new(bits, k),
+            records: lengths.len(),
+            text_len,
+            separated,
+        }
+    }
+
+    /// Number of records.
+    pub fn len(&self) -> usize {
+        self.records
+    }
+
+    /// Whether there are no records.
+    pub fn is_empty(&self) -> bool {
+        self.records == 0
+    }
+
+    /// Length of the concatenated text, including separators.
+    pub fn text_len(&self) -> usize {
+        self.text_len
+    }
+
+    /// Start of the i-th record in the concatenated text.
+    /// Complexity: O(log (n / k) + k) (see `RankSelect::select_1`).
+    ///
+    /// # Panics
+    ///
+    /// Panics if there is no i-th record.
+    pub fn record_start(&self, i: usize) -> usize {
+        assert!(i < self.records, "Record index out of range.");
+        self.starts.select_1(i as u64 + 1).unwrap() as usize
+    }
+
+    /// Range of the i-th record in the concatenated text, excluding its separator.
+    ///
+    /// # Panics
+    ///
+    /// Panics if there is no i-th record.
+    pub fn record_range(&self, i: usize) -> Range<usize> {
+        let start = self.record_start(i);
+        let end = if i + 1 < self.records {
+            self.record_start(i + 1)
+        } else {
+            self.text_len
+        };
+        start..end - self.separated as usize
+    }
+
+    /// Resolve a position in the concatenated text to the record and the offset within it.
+    /// Returns `None` for positions of separators or beyond the end of the text.
+    /// Complexity: O(log (n / k) + k).
+    pub fn resolve(&self, pos: usize) -> Option<(usize, usize)> {
+        let rank = self.starts.rank_1(pos as u64)?;
+        let record = rank as usize - 1;
+        let range = self.record_range(record);
+        if pos < range.end {
+            Some((record, pos - range.start))
+        } else {
+            None
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_resolve() {
+        let lengths = [3, 1, 70, 5];
+        let boundaries = RecordBoundaries::from_lengths(lengths.iter().copied(), false);
+        assert_eq!(boundaries.text_len(), 79);
+        let mut pos = 0;
+        for (i, &len) in lengths.iter().enumerate() {
+            assert_eq!(boundaries.record_range(i), pos..pos + len);
+            for offset in 0..len {
+                assert_eq!(boundaries.resolve(pos + offset), Some((i, offset)));
+            }
+            pos += len;
+        }
+        assert_eq!(boundaries.resolve(79), None);
+    }
+
+    #[test]
+    fn test_separated_empty_records() {
+        let boundaries = RecordBoundaries::from_lengths(vec![2, 0, 1], true);
+        assert_eq!(boundaries.record_range(1), 3..3);
+        assert_eq!(boundaries.resolve(3), None);
+        assert_eq!(boundaries.resolve(4), Some((2, 0)));
+        assert!(RecordBoundaries::from_lengths(vec![], true).is_empty());
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_unseparated_empty_record() {
+        RecordBoundaries::from_lengths(vec![2, 0, 1], false);
+    }
+}
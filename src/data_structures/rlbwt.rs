@@ -0,0 +1,166 @@
+//! Run-length compressed Burrows-Wheeler transform (RLBWT).
+//!
+//! The BWT of a highly repetitive text (e.g. a pangenome or a deep read set) consists of few
+//! long runs of equal symbols. The RLBWT stores each of the r runs by its first position and
+//! symbol, together with the cumulative run lengths of each symbol. It thus needs O(r) words
+//! instead of the n bytes of the plain BWT and the O(n σ / k) words of a sampled occurrence
+//! array. Occurrence (rank) and access queries take O(log r) time by binary search over the
+//! runs. See `fmindex::RLFMIndex` for an FM-index on top of it.
+//!
+//! # Example
+//!
+//! ```
+//! use bio::data_structures::rlbwt::RLBWT;
+//!
+//! let rlbwt = RLBWT::new(b"AAAACCAAAGGG$");
+//! assert_eq!(rlbwt.len(), 13);
+//! assert_eq!(rlbwt.runs(), 5);
+//! assert_eq!(rlbwt.access(5), b'C');
+//! assert_eq!(rlbwt.rank(b'A', 7), 6);
+//! assert_eq!(rlbwt.rank(b'T', 7), 0);
+//! assert_eq!(rlbwt.to_bwt(), b"AAAACCAAAGGG$");
+//! ```
+
+use crate::data_structures::bwt::BWT;
+use crate::utils::TextSlice;
+
+/// A run-length compressed BWT.
+#[derive(Default, Clone, Eq, PartialEq, Hash, Debug, Serialize, Deserialize)]
+pub struct RLBWT {
+    len: usize,
+    /// First position of each run.
+    starts: Vec<usize>,
+    /// Symbol of each run.
+    heads: Vec<u8>,
+    /// For each symbol, the indices of its runs.
+    symbol_runs: Vec<Vec<usize>>,
+    /// For each symbol, the cumulative lengths of its runs (starting with 0).
+    symbol_counts: Vec<Vec<usize>>,
+}
+
+impl RLBWT {
+    /// Compress the given BWT of length n.
+    /// Complexity: O(n).
+    pub fn new(bwt: TextSlice<'_>) -> Self {
+        let mut rlbwt = RLBWT {
+            len: bwt.len(),
+            symbol_runs: vec![Vec::new(); 256],
+            symbol_counts: vec![vec![0]; 256],
+            ..Default::default()
+        };
+        for (i, &a) in bwt.iter().enumerate() {
+            let counts = &mut rlbwt.symbol_counts[a as usize];
+            if i == 0 || bwt[i - 1] != a {
+                rlbwt.symbol_runs[a as usize].push(rlbwt.heads.len());
+                counts.push(counts[counts.len() - 1]);
+                rlbwt.starts.push(i);
+                rlbwt.heads.push(a);
+            }
+            *counts.last_mut().unwrap() += 1;
+        }
+        rlbwt
+    }
+
+    /// Length of the BWT.
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Whether the BWT is empty.
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Number of runs.
+    pub fn runs(&self) -> usize {
+        self.heads.len()
+    }
+
+    /// Index of the run containing position i.
+    fn run(&self, i: usize) -> usize {
+        assert!(i < self.len, "Expecting position within the BWT.");
+        match self.starts.binary_search(&i) {
+            Ok(run) => run,
+            Err(run) => run - 1,
+        }
+    }
+
+    /// Get the symbol at position i of the BWT.
+    /// Complexity: O(log r).
+    ///
+    /// # Panics
+    ///
+    /// Panics if i is out of bounds.
+    pub fn access(&self, i: usize) -> u8 {
+        self.heads[self.run(i)]
+    }
+
+    /// Get the number of occurrences of symbol a in BWT[..i+1] (like `bwt::Occ::get`).
+    /// Complexity: O(log r).
+    ///
+    /// # Panics
+    ///
+    /// Panics if i is out of bounds.
+    pub fn rank(&self, a: u8, i: usize) -> usize {
+        let run = self.run(i);
+        let runs = &self.symbol_runs[a as usize];
+        // number of runs of a before the run containing i
+        let before = match runs.binary_search(&run) {
+            Ok(r) | Err(r) => r,
+        };
+        let mut count = self.symbol_counts[a as usize][before];
+        if self.heads[run] == a {
+            count += i + 1 - self.starts[run];
+        }
+        count
+    }
+
+    /// Decompress into the plain BWT.
+    pub fn to_bwt(&self) -> BWT {
+        let mut bwt = Vec::with_capacity(self.len);
+        for (run, &a) in self.heads.iter().enumerate() {
+            let end = self.starts.get(run + 1).copied().unwrap_or(self.len);
+            bwt.resize(end, a);
+        }
+        bwt
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::data_structures::bwt::bwt;
+    use crate::data_structures::suffix_array::suffix_array;
+
+    #[test]
+    fn test_rank_access() {
+        let text = b"ACGTACGTACGTACGTACGAACGTACGTACGTTCGTACGT$".repeat(3);
+        let sa = suffix_array(&text);
+        let bwt = bwt(&text, &sa);
+        let rlbwt = RLBWT::new(&bwt);
+        assert!(rlbwt.runs() < bwt.len() / 2);
+        assert_eq!(rlbwt.to_bwt(), bwt);
+        for &a in b"$ACGTN" {
+            let mut count = 0;
+            for (i, &b) in bwt.iter().enumerate() {
+                count += (a == b) as usize;
+                assert_eq!(rlbwt.access(i), b);
+                assert_eq!(rlbwt.rank(a, i), count);
+            }
+        }
+    }
+
+    #[test]
+    fn test_empty() {
+        let rlbwt = RLBWT::new(b"");
+        assert!(rlbwt.is_empty());
+        assert_eq!(rlbwt.runs(), 0);
+        assert!(rlbwt.to_bwt().is_empty());
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_out_of_bounds() {
+        RLBWT::new(b"ACGT").rank(b'A', 4);
+    }
+}
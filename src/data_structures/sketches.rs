@@ -0,0 +1,270 @@
+//! Sketches of k-mer sets for fast estimation of sequence similarity.
+//!
+//! A MinHash sketch (Broder, 1997) stores the `s` smallest hash values of the canonical
+//! k-mers of a sequence (bottom-k sketch). The fraction of shared hashes among the smallest
+//! hashes of the union of two sketches is an unbiased estimate of the Jaccard index of the
+//! underlying k-mer sets. Under a simple mutation model, the Jaccard index can be turned into
+//! an estimate of the per-base divergence of two genomes, the Mash distance
+//! (Ondov et al., 2016).
+//!
+//! # Example
+//!
+//! ```
+//! use bio::data_structures::sketches::MinHashSketch;
+//!
+//! let x = b"ACGGCTGAGATGATCCGATTAGCTAGCATCGATCGATTTAGCGA";
+//! // one substitution in the middle of the sequence
+//! let y = b"ACGGCTGAGATGATCCGATTTGCTAGCATCGATCGATTTAGCGA";
+//! let mut sx = MinHashSketch::new(8, 1000);
+//! let mut sy = MinHashSketch::new(8, 1000);
+//! sx.add(x);
+//! sy.add(y);
+//! // up to 8 k-mers of each sequence are affected by the substitution
+//! let j = sx.jaccard(&sy);
+//! assert!(j > 0.6 && j < 1.0);
+//! assert!(sx.mash_distance(&sy) < 0.05);
+//! ```
+
+use std::cmp::Ordering;
+
+use crate::data_structures::kmer_counting::{hash_kmer, kmers};
+use crate::utils::TextSlice;
+
+/// A bottom-k MinHash sketch of the canonical k-mers of one or more DNA sequences.
+/// K-mers containing symbols other than `ACGT` (case-insensitive) are skipped.
+#[derive(Clone, Eq, PartialEq, Hash, Debug, Serialize, Deserialize)]
+pub struct MinHashSketch {
+    k: usize,
+    size: usize,
+    seed: u64,
+    // the smallest distinct hash values, in ascending order
+    hashes: Vec<u64>,
+}
+
+impl MinHashSketch {
+    /// Create a new, empty sketch of at most `size` hashes of canonical k-mers.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `k` is zero or larger than 32, or if `size` is zero.
+    pub fn new(k: usize, size: usize) -> Self {
+        Self::with_seed(k, size, 42)
+    }
+
+    /// Create a new, empty sketch using the given seed for hashing. Only sketches with the same
+    /// seed can be compared.
+    pub fn with_seed(k: usize, size: usize, seed: u64) -> Self {
+        assert!(k > 0 && k <= 32, "Expecting 0 < k <= 32");
+        assert!(size > 0, "Expecting a positive sketch size.");
+        MinHashSketch {
+            k,
+            size,
+            seed,
+            hashes: Vec::with_capacity(size),
+        }
+    }
+
+    /// The k-mer length.
+    pub fn k(&self) -> usize {
+        self.k
+    }
+
+    /// The maximum number of hashes in the sketch.
+    pub fn size(&self) -> usize {
+        self.size
+    }
+
+    /// The seed of the hash function.
+    pub fn seed(&self) -> u64 {
+        self.seed
+    }
+
+    /// The hashes of the sketch, in ascending order.
+    pub fn hashes(&self) -> &[u64] {
+        &self.hashes
+    }
+
+    /// Number of hashes in the sketch.
+    pub fn len(&self) -> usize {
+        self.hashes.len()
+    }
+
+    /// Whether the sketch contains no hashes.
+    pub fn is_empty(&self) -> bool {
+        self.hashes.is_empty()
+    }
+
+    fn insert(&mut self, hash: u64) {
+        if self.hashes.len() == self.size && hash >= *self.hashes.last().unwrap() {
+            return;
+        }
+        if let Err(i) = self.hashes.binary_search(&hash) {
+            if self.hashes.len() == self.size {
+                self.hashes.pop();
+            }
+            self.hashes.insert(i, hash);
+        }
+    }
+
+    /// Add the k-mers of a sequence to the sketch.
+    pub fn add(&mut self, text: TextSlice<'_>) {
+        for kmer in kmers(text, self.k, true) {
+            self.insert(hash_kmer(kmer, self.seed));
+        }
+    }
+
+    /// Add the k-mers of all given sequences (e.g. the contigs of an assembly) to the sketch.
+    pub fn add_all<T: AsRef<[u8]>, I: IntoIterator<Item = T>>(&mut self, texts: I) {
+        for text in texts {
+            self.add(text.as_ref());
+        }
+    }
+
+    fn assert_compatible(&self, other: &MinHashSketch) {
+        assert!(
+            self.k == other.k && self.seed == other.seed,
+            "Expecting sketches with the same k and seed."
+        );
+    }
+
+    /// Merge another sketch into this one, yielding the sketch of the union of both k-mer sets.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the sketches differ in k or seed.
+    pub fn merge(&mut self, other: &MinHashSketch) {
+        self.assert_compatible(other);
+        for &hash in &other.hashes {
+            self.insert(hash);
+        }
+    }
+
+    /// Estimate the Jaccard index of the k-mer sets of both sketches, considering the smallest
+    /// hashes of their union, up to the smaller of both sketch sizes.
+    /// The Jaccard index of two empty sketches is defined as zero.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the sketches differ in k or seed.
+    pub fn jaccard(&self, other: &MinHashSketch) -> f64 {
+        self.assert_compatible(other);
+        let size = self.size.min(other.size);
+        let (mut i, mut j) = (0, 0);
+        let (mut shared, mut union) = (0, 0);
+        while union < size && i < self.hashes.len() && j < other.hashes.len() {
+            match self.hashes[i].cmp(&other.hashes[j]) {
+                Ordering::Less => i += 1,
+                Ordering::Greater => j += 1,
+                Ordering::Equal => {
+                    shared += 1;
+                    i += 1;
+                    j += 1;
+                }
+            }
+            union += 1;
+        }
+        // remaining hashes of the longer sketch are part of the union but not shared
+        union += (self.hashes.len() - i + other.hashes.len() - j).min(size - union);
+
+        if union == 0 {
+            0.0
+        } else {
+            shared as f64 / union as f64
+        }
+    }
+
+    /// Estimate the per-base divergence of the sequences of both sketches from their Jaccard
+    /// index j as `-1/k * ln(2j / (1 + j))`. If no k-mers are shared, the distance is 1.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the sketches differ in k or seed.
+    pub fn mash_distance(&self, other: &MinHashSketch) -> f64 {
+        let j = self.jaccard(other);
+        if j == 0.0 {
+            1.0
+        } else {
+            (-(2.0 * j / (1.0 + j)).ln() / self.k as f64).min(1.0)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::alphabets::dna;
+    use rand::prelude::*;
+    use rand::rngs::StdRng;
+
+    fn random_dna(rng: &mut StdRng, len: usize) -> Vec<u8> {
+        (0..len).map(|_| *b"ACGT".choose(rng).unwrap()).collect()
+    }
+
+    #[test]
+    fn test_identical_and_revcomp() {
+        let mut rng = StdRng::seed_from_u64(1);
+        let text = random_dna(&mut rng, 5000);
+        let mut fwd = MinHashSketch::new(16, 200);
+        fwd.add(&text);
+        let mut rev = MinHashSketch::new(16, 200);
+        rev.add(&dna::revcomp(&text));
+        assert_eq!(fwd.len(), 200);
+        assert_eq!(fwd, rev);
+        assert_eq!(fwd.jaccard(&rev), 1.0);
+        assert_eq!(fwd.mash_distance(&rev), 0.0);
+
+        let mut other = MinHashSketch::new(16, 200);
+        other.add(&random_dna(&mut rng, 5000));
+        assert_eq!(fwd.jaccard(&other), 0.0);
+        assert_eq!(fwd.mash_distance(&other), 1.0);
+    }
+
+    #[test]
+    fn test_distance_estimate() {
+        let mut rng = StdRng::seed_from_u64(2);
+        let x = random_dna(&mut rng, 20000);
+        let mut y = x.clone();
+        // 1% substitutions
+        for pos in (0..y.len()).step_by(100) {
+            y[pos] = if y[pos] == b'A' { b'C' } else { b'A' };
+        }
+        let (mut sx, mut sy) = (MinHashSketch::new(21, 1000), MinHashSketch::new(21, 1000));
+        sx.add(&x);
+        sy.add(&y);
+        let d = sx.mash_distance(&sy);
+        assert!((d - 0.01).abs() < 0.003, "distance {}", d);
+    }
+
+    #[test]
+    fn test_merge() {
+        let mut rng = StdRng::seed_from_u64(3);
+        let (a, b) = (random_dna(&mut rng, 1000), random_dna(&mut rng, 1000));
+        let mut both = MinHashSketch::new(12, 100);
+        both.add_all(&[&a, &b]);
+        let (mut sa, mut sb) = (MinHashSketch::new(12, 100), MinHashSketch::new(12, 100));
+        sa.add(&a);
+        sb.add(&b);
+        sa.merge(&sb);
+        assert_eq!(sa, both);
+    }
+
+    #[test]
+    fn test_seed() {
+        let sketch = |seed| {
+            let mut sketch = MinHashSketch::with_seed(4, 3, seed);
+            sketch.add(b"ACGGCTGAGATGATCCGATT");
+            sketch
+        };
+        assert_eq!(sketch(7), sketch(7));
+        assert_ne!(sketch(7).hashes(), sketch(8).hashes());
+        // the same on all platforms
+        assert_eq!(
+            sketch(42).hashes(),
+            [
+                1140920773152705293,
+                2265701621275553697,
+                2750072032630767458
+            ]
+        );
+    }
+}
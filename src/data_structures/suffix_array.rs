@@ -24,8 +24,10 @@
 use std::borrow::Borrow;
 use std::cmp;
 use std::collections::HashMap;
+use std::convert::TryFrom;
 use std::fmt::Debug;
 use std::hash::BuildHasherDefault;
+use std::io;
 use std::iter;
 use std::ops::Deref;
 
@@ -40,6 +42,7 @@ use fxhash::FxHasher;
 use crate::alphabets::{Alphabet, RankTransform};
 use crate::data_structures::bwt::{Less, Occ, BWT};
 use crate::data_structures::smallints::SmallInts;
+use crate::utils::parallel;
 
 pub type LCPArray = SmallInts<i8, isize>;
 pub type RawSuffixArray = Vec<usize>;
@@ -122,13 +125,13 @@ pub trait SuffixArray {
 /// A sampled suffix array.
 #[derive(Default, Clone, Eq, PartialEq, Debug, Serialize, Deserialize)]
 pub struct SampledSuffixArray<DBWT: Borrow<BWT>, DLess: Borrow<Less>, DOcc: Borrow<Occ>> {
-    bwt: DBWT,
-    less: DLess,
-    occ: DOcc,
-    sample: Vec<usize>,
-    s: usize, // Rate of sampling
-    extra_rows: HashMapFx<usize, usize>,
-    sentinel: u8,
+    pub(crate) bwt: DBWT,
+    pub(crate) less: DLess,
+    pub(crate) occ: DOcc,
+    pub(crate) sample: Vec<usize>,
+    pub(crate) s: usize, // Rate of sampling
+    pub(crate) extra_rows: HashMapFx<usize, usize>,
+    pub(crate) sentinel: u8,
 }
 
 impl SuffixArray for RawSuffixArray {
@@ -247,7 +250,8 @@ impl<DBWT: Borrow<BWT>, DLess: Borrow<Less>, DOcc: Borrow<Occ>>
 ///
 /// * `text` - the text, ended by sentinel symbol (being lexicographically smallest). The text may
 ///   also contain multiple sentinel symbols, used to concatenate multiple sequences without mixing
-///   their suffixes together.
+///   their suffixes together. Any symbol can serve as sentinel (e.g. `$` or `0x00`), as long as
+///   it is lexicographically smaller than all other symbols of the text.
 ///
 /// # Example
 ///
@@ -282,6 +286,58 @@ pub fn suffix_array(text: &[u8]) -> RawSuffixArray {
     sais.pos
 }
 
+/// Construct suffix array for given text like `suffix_array`, using the given number of
+/// threads (`0` for all available cores, see `utils::parallel`).
+/// Instead of SAIS, which is inherently sequential, this uses prefix doubling: suffixes are
+/// sorted by their first 2^i symbols in round i, using a parallel merge sort, until all
+/// prefixes are distinct.
+/// Complexity: O(n log^2 n) work in total, spread over the threads.
+///
+/// # Arguments
+///
+/// * `text` - the text, ended by sentinel symbol (see `suffix_array`)
+/// * `threads` - the number of threads to use
+///
+/// # Example
+///
+/// ```
+/// use bio::data_structures::suffix_array::{suffix_array, suffix_array_parallel};
+/// let text = b"GCCTTAACATTATTACGCCTA$";
+/// assert_eq!(suffix_array_parallel(text, 4), suffix_array(text));
+/// ```
+pub fn suffix_array_parallel(text: &[u8], threads: usize) -> RawSuffixArray {
+    let n = text.len();
+    let alphabet = Alphabet::new(text);
+    let sentinel_count = sentinel_count(text);
+    // sentinels get distinct ranks, such that all suffixes are distinct
+    let mut rank = transform_text::<usize>(text, &alphabet, sentinel_count);
+    let mut pos: RawSuffixArray = (0..n).collect();
+    let mut next_rank = vec![0; n];
+
+    let mut h = 1;
+    loop {
+        {
+            let rank = &rank;
+            // suffixes shorter than h come first, ordered by their rank
+            let key = |&p: &usize| (rank[p], if p + h < n { rank[p + h] + 1 } else { 0 });
+            parallel::sort_by_key(&mut pos, threads, key);
+
+            next_rank[pos[0]] = 0;
+            for r in 1..n {
+                next_rank[pos[r]] =
+                    next_rank[pos[r - 1]] + (key(&pos[r - 1]) != key(&pos[r])) as usize;
+            }
+        }
+        std::mem::swap(&mut rank, &mut next_rank);
+        if rank[pos[n - 1]] == n - 1 {
+            break;
+        }
+        h *= 2;
+    }
+
+    pos
+}
+
 /// Construct suffix array for given text from integer alphabet.
 /// Complexity: O(n).
 /// # Arguments
@@ -312,6 +368,66 @@ where
     sais.pos
 }
 
+/// Write a suffix array as a plain dump of 64-bit little-endian integers, as used by
+/// many other tools (e.g. the output of libdivsufsort64 on little-endian machines).
+///
+/// # Example
+///
+/// ```
+/// use bio::data_structures::suffix_array::{read_suffix_array, suffix_array, write_suffix_array};
+///
+/// let text = b"GCCTTAACATTATTACGCCTA$";
+/// let pos = suffix_array(text);
+/// let mut buf = Vec::new();
+/// write_suffix_array(&pos, &mut buf).unwrap();
+/// assert_eq!(buf.len(), 8 * text.len());
+/// assert_eq!(read_suffix_array(&buf[..]).unwrap(), pos);
+/// ```
+pub fn write_suffix_array<W: io::Write>(pos: RawSuffixArraySlice, mut writer: W) -> io::Result<()> {
+    for &p in pos {
+        writer.write_all(&(p as u64).to_le_bytes())?;
+    }
+    writer.flush()
+}
+
+/// Read a suffix array from a plain dump of 64-bit little-endian integers
+/// (see `write_suffix_array`).
+///
+/// Fails with an error of kind `InvalidData` if the input length is not a multiple of 8
+/// or a value does not fit into `usize`.
+pub fn read_suffix_array<R: io::Read>(mut reader: R) -> io::Result<RawSuffixArray> {
+    let mut pos = Vec::new();
+    let mut chunk = [0u8; 8];
+    loop {
+        // fill one 8-byte value at a time, tolerating short reads
+        let mut filled = 0;
+        while filled < chunk.len() {
+            match reader.read(&mut chunk[filled..]) {
+                Ok(0) => break,
+                Ok(n) => filled += n,
+                Err(e) if e.kind() == io::ErrorKind::Interrupted => {}
+                Err(e) => return Err(e),
+            }
+        }
+        match filled {
+            0 => return Ok(pos),
+            8 => {
+                let value = u64::from_le_bytes(chunk);
+                pos.push(
+                    usize::try_from(value)
+                        .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?,
+                );
+            }
+            _ => {
+                return Err(io::Error::new(
+                    io::ErrorKind::InvalidData,
+                    "suffix array dump length is not a multiple of 8 bytes",
+                ))
+            }
+        }
+    }
+}
+
 /// Construct lcp array for given text and suffix array of length n.
 /// Complexity: O(n).
 ///
@@ -482,11 +598,13 @@ impl Sais {
     fn new(n: usize) -> Self {
         Sais {
             pos: Vec::with_capacity(n),
-            lms_pos: Vec::with_capacity(n),
+            // LMS positions are never adjacent
+            lms_pos: Vec::with_capacity(n / 2 + 1),
             reduced_text_pos: vec![0; n],
             bucket_sizes: VecMap::new(),
-            bucket_start: Vec::with_capacity(n),
-            bucket_end: Vec::with_capacity(n),
+            // buckets are per symbol, their number is not known yet
+            bucket_start: Vec::new(),
+            bucket_end: Vec::new(),
         }
     }
 
@@ -760,6 +878,20 @@ mod tests {
     use rand::prelude::*;
     use std::str;
 
+    #[test]
+    fn test_suffix_array_dump() {
+        let text = b"GCCTTAACATTATTACGCCTA$";
+        let pos = suffix_array(text);
+        let mut buf = Vec::new();
+        write_suffix_array(&pos, &mut buf).unwrap();
+        assert_eq!(&buf[..8], &21u64.to_le_bytes());
+        assert_eq!(read_suffix_array(&buf[..]).unwrap(), pos);
+
+        let err = read_suffix_array(&buf[..buf.len() - 1]).unwrap_err();
+        assert_eq!(err.kind(), std::io::ErrorKind::InvalidData);
+        assert!(read_suffix_array(&b""[..]).unwrap().is_empty());
+    }
+
     #[test]
     fn test_pos_types() {
         let orig_text = b"GCCTTAACATTATTACGCCTA$";
@@ -910,6 +1042,25 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_suffix_array_parallel() {
+        let mut texts = vec![
+            b"$".to_vec(),
+            b"GCCTTAACATTATTACGCCTA$".to_vec(),
+            b"A$A$T$T$".to_vec(),
+            // repeats need several doubling rounds
+            [&[b'A'; 1000][..], b"$"].concat(),
+            b"ACGT".repeat(100).into_iter().chain(Some(0)).collect(),
+        ];
+        texts.extend((1..20).map(|i| rand_seqs(10, i * 10)));
+        for text in &texts {
+            let pos = suffix_array(text);
+            for threads in 1..5 {
+                assert_eq!(suffix_array_parallel(text, threads), pos);
+            }
+        }
+    }
+
     #[test]
     fn test_sampled_matches() {
         let mut test_cases = vec![(&b"A$C$G$T$"[..], "simple"),
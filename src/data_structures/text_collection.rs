@@ -0,0 +1,444 @@
+//! Concatenation of multiple texts, separated by sentinels, for building a single suffix array,
+//! FM-index or FMD-index over all of them.
+//!
+//! The texts T1, T2, ... are concatenated into `T1$T2$...`, or, for the FMD-index, together
+//! with their reverse complements R1, R2, ... into `T1$R1$T2$R2$...`. The start of each
+//! block is recorded, such that positions in the concatenated text (e.g. obtained from
+//! the suffix array) can be resolved back to a text and an offset within it.
+//! By default, the sentinel is `$`. For texts that legitimately contain `$`, another sentinel
+//! (e.g. `0x00`) can be chosen with `TextCollection::with_sentinel`; it has to be
+//! lexicographically smaller than all symbols of the texts.
+//!
+//! # Example
+//!
+//! ```
+//! use bio::data_structures::suffix_array::suffix_array;
+//! use bio::data_structures::text_collection::{TextCollection, TextPosition};
+//!
+//! let texts = [b"ACGT".to_vec(), b"GGA".to_vec()];
+//! let collection = TextCollection::new(&texts);
+//! assert_eq!(collection.text(), b"ACGT$GGA$");
+//! let sa = suffix_array(collection.text());
+//!
+//! assert_eq!(
+//!     collection.resolve(6),
+//!     Some(TextPosition {
+//!         text: 1,
+//!         reverse: false,
+//!         offset: 1
+//!     })
+//! );
+//! assert_eq!(collection.resolve(4), None);
+//! ```
+
+use std::ops::Range;
+
+use crate::alphabets::dna;
+use crate::data_structures::record_boundaries::RecordBoundaries;
+use bio_types::strand::ReqStrand;
+use thiserror::Error;
+
+/// The default sentinel symbol separating the texts.
+pub const SENTINEL: u8 = b'$';
+
+fn default_sentinel() -> u8 {
+    SENTINEL
+}
+
+/// Errors that can occur when concatenating texts.
+#[derive(
+    Error, Copy, Clone, Eq, PartialEq, Ord, PartialOrd, Hash, Debug, Serialize, Deserialize,
+)]
+pub enum Error {
+    #[error(
+        "symbol {:?} at position {} of text {} is not larger than the sentinel {:?}",
+        char::from(*symbol),
+        pos,
+        text,
+        char::from(*sentinel)
+    )]
+    InvalidSymbol {
+        symbol: u8,
+        text: usize,
+        pos: usize,
+        sentinel: u8,
+    },
+}
+
+pub type Result<T, E = Error> = std::result::Result<T, E>;
+
+/// A position within one of the texts of a `TextCollection`.
+#[derive(
+    Default, Copy, Clone, Eq, PartialEq, Ord, PartialOrd, Hash, Debug, Serialize, Deserialize,
+)]
+pub struct TextPosition {
+    /// Index of the text in the collection.
+    pub text: usize,
+    /// Whether the position lies in the reverse complement of the text.
+    pub reverse: bool,
+    /// Offset within the text, or within its reverse complement if `reverse` is true.
+    pub offset: usize,
+}
+
+impl TextPosition {
+    /// The strand of the position.
+    pub fn strand(&self) -> ReqStrand {
+        if self.reverse {
+            ReqStrand::Reverse
+        } else {
+            ReqStrand::Forward
+        }
+    }
+}
+
+/// Multiple texts concatenated with sentinels, keeping track of the text boundaries.
+#[derive(Clone, Eq, PartialEq, Hash, Debug, Serialize, Deserialize)]
+pub struct TextCollection {
+    text: Vec<u8>,
+    // start of each block (text or reverse complement) in the concatenated text
+    starts: Vec<usize>,
+    revcomp: bool,
+    #[serde(default = "default_sentinel")]
+    sentinel: u8,
+}
+
+impl Default for TextCollection {
+    fn default() -> Self {
+        TextCollection {
+            text: Vec::new(),
+            starts: Vec::new(),
+            revcomp: false,
+            sentinel: SENTINEL,
+        }
+    }
+}
+
+impl TextCollection {
+    /// Concatenate the given texts to `T1$T2$...$`.
+    ///
+    /// # Panics
+    ///
+    /// If one of the texts contains the sentinel symbol or a symbol that is lexicographically
+    /// smaller (e.g. whitespace). Use `try_new` to handle this as an error.
+    pub fn new<T: AsRef<[u8]>>(texts: &[T]) -> Self {
+        Self::with_sentinel(texts, false, SENTINEL)
+    }
+
+    /// Concatenate the given texts to `T1$T2$...$`, returning an error if one of the texts
+    /// contains a symbol that is not lexicographically larger than the sentinel `$`.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use bio::data_structures::text_collection::{Error, TextCollection};
+    ///
+    /// assert!(TextCollection::try_new(&[b"ACGT"]).is_ok());
+    /// assert_eq!(
+    ///     TextCollection::try_new(&[&b"ACGT"[..], b"AC GT"]),
+    ///     Err(Error::InvalidSymbol {
+    ///         symbol: b' ',
+    ///         text: 1,
+    ///         pos: 2,
+    ///         sentinel: b'$'
+    ///     })
+    /// );
+    /// ```
+    pub fn try_new<T: AsRef<[u8]>>(texts: &[T]) -> Result<Self> {
+        Self::try_with_sentinel(texts, false, SENTINEL)
+    }
+
+    /// Concatenate the given DNA texts and their reverse complements to `T1$R1$T2$R2$...$`,
+    /// as expected by the FMD-index.
+    ///
+    /// # Panics
+    ///
+    /// If one of the texts contains the sentinel symbol or a symbol that is lexicographically
+    /// smaller.
+    pub fn with_revcomp<T: AsRef<[u8]>>(texts: &[T]) -> Self {
+        Self::with_sentinel(texts, true, SENTINEL)
+    }
+
+    /// Concatenate the given texts (and their reverse complements if `revcomp` is true),
+    /// separated by the given sentinel symbol.
+    ///
+    /// # Panics
+    ///
+    /// If one of the texts contains a symbol that is not lexicographically larger than the
+    /// sentinel. Use `try_with_sentinel` to handle this as an error.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use bio::data_structures::text_collection::TextCollection;
+    ///
+    /// let collection = TextCollection::with_sentinel(&[&b"A$C"[..], b"GT"], false, 0);
+    /// assert_eq!(collection.text(), b"A$C\0GT\0");
+    /// assert_eq!(collection.resolve(1).map(|pos| pos.offset), Some(1));
+    /// ```
+    pub fn with_sentinel<T: AsRef<[u8]>>(texts: &[T], revcomp: bool, sentinel: u8) -> Self {
+        match Self::try_with_sentinel(texts, revcomp, sentinel) {
+            Ok(collection) => collection,
+            Err(e) => panic!("{}", e),
+        }
+    }
+
+    /// Concatenate the given texts (and their reverse complements if `revcomp` is true),
+    /// separated by the given sentinel symbol, returning an error if one of the texts contains
+    /// a symbol that is not lexicographically larger than the sentinel.
+    pub fn try_with_sentinel<T: AsRef<[u8]>>(
+        texts: &[T],
+        revcomp: bool,
+        sentinel: u8,
+    ) -> Result<Self> {
+        let blocks = if revcomp { 2 } else { 1 };
+        let total: usize = texts.iter().map(|t| (t.as_ref().len() + 1) * blocks).sum();
+        let mut text = Vec::with_capacity(total);
+        let mut starts = Vec::with_capacity(texts.len() * blocks);
+        for (i, t) in texts.iter().enumerate() {
+            let t = t.as_ref();
+            if let Some(pos) = t.iter().position(|&a| a <= sentinel) {
+                return Err(Error::InvalidSymbol {
+                    symbol: t[pos],
+                    text: i,
+                    pos,
+                    sentinel,
+                });
+            }
+            starts.push(text.len());
+            text.extend_from_slice(t);
+            text.push(sentinel);
+            if revcomp {
+                starts.push(text.len());
+                text.extend(dna::revcomp(t));
+                text.push(sentinel);
+            }
+        }
+        Ok(TextCollection {
+            text,
+            starts,
+            revcomp,
+            sentinel,
+        })
+    }
+
+    /// The concatenated text, including sentinels.
+    pub fn text(&self) -> &[u8] {
+        &self.text
+    }
+
+    /// Consume the collection, returning the concatenated text.
+    pub fn into_text(self) -> Vec<u8> {
+        self.text
+    }
+
+    /// Number of texts in the collection (not counting reverse complements).
+    pub fn len(&self) -> usize {
+        if self.revcomp {
+            self.starts.len() / 2
+        } else {
+            self.starts.len()
+        }
+    }
+
+    /// Whether the collection contains no texts.
+    pub fn is_empty(&self) -> bool {
+        self.starts.is_empty()
+    }
+
+    /// The sentinel symbol separating the texts.
+    pub fn sentinel(&self) -> u8 {
+        self.sentinel
+    }
+
+    /// Whether the reverse complements of the texts are included.
+    pub fn has_revcomp(&self) -> bool {
+        self.revcomp
+    }
+
+    /// Range of the i-th text in the concatenated text (excluding its sentinel).
+    pub fn text_range(&self, i: usize) -> Range<usize> {
+        self.block_range(if self.revcomp { 2 * i } else { i })
+    }
+
+    /// Range of the reverse complement of the i-th text in the concatenated text (excluding
+    /// its sentinel), or `None` if the reverse complements are not included.
+    pub fn revcomp_range(&self, i: usize) -> Option<Range<usize>> {
+        if self.revcomp {
+            Some(self.block_range(2 * i + 1))
+        } else {
+            None
+        }
+    }
+
+    fn block_range(&self, block: usize) -> Range<usize> {
+        let end = match self.starts.get(block + 1) {
+            Some(&next) => next - 1,
+            None => self.text.len() - 1,
+        };
+        self.starts[block]..end
+    }
+
+    /// The boundaries of the blocks (texts and, if included, reverse complements, in order of
+    /// concatenation) as a succinct rank/select structure, which can be used to resolve
+    /// positions without keeping the collection (and hence the concatenated text) in memory.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use bio::data_structures::text_collection::TextCollection;
+    ///
+    /// let collection = TextCollection::with_revcomp(&[&b"ACG"[..], b"TT"]);
+    /// let boundaries = collection.boundaries();
+    /// assert_eq!(boundaries.len(), 4);
+    /// // position 1 of the reverse complement of the second text
+    /// assert_eq!(boundaries.resolve(12), Some((3, 1)));
+    /// ```
+    pub fn boundaries(&self) -> RecordBoundaries {
+        RecordBoundaries::from_lengths(
+            (0..self.starts.len()).map(|block| self.block_range(block).len()),
+            true,
+        )
+    }
+
+    /// Resolve a position in the concatenated text to the text it belongs to.
+    /// Returns `None` for positions of sentinels or beyond the end of the text.
+    ///
+    /// Complexity: O(log n), with n being the number of texts.
+    pub fn resolve(&self, pos: usize) -> Option<TextPosition> {
+        if pos >= self.text.len() || self.text[pos] == self.sentinel {
+            return None;
+        }
+        let block = match self.starts.binary_search(&pos) {
+            Ok(block) => block,
+            Err(block) => block - 1,
+        };
+        let offset = pos - self.starts[block];
+        Some(if self.revcomp {
+            TextPosition {
+                text: block / 2,
+                reverse: block % 2 == 1,
+                offset,
+            }
+        } else {
+            TextPosition {
+                text: block,
+                reverse: false,
+                offset,
+            }
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_collection() {
+        let collection = TextCollection::new(&[&b"AC"[..], b"", b"GTA"]);
+        assert_eq!(collection.text(), b"AC$$GTA$");
+        assert_eq!(collection.len(), 3);
+        assert_eq!(collection.text_range(1), 3..3);
+        assert_eq!(collection.text_range(2), 4..7);
+        assert_eq!(collection.revcomp_range(0), None);
+        assert_eq!(
+            collection.resolve(6),
+            Some(TextPosition {
+                text: 2,
+                reverse: false,
+                offset: 2
+            })
+        );
+        assert_eq!(collection.resolve(3), None);
+        assert_eq!(collection.resolve(8), None);
+    }
+
+    #[test]
+    fn test_collection_revcomp() {
+        let collection = TextCollection::with_revcomp(&[b"AAC".to_vec(), b"GT".to_vec()]);
+        assert_eq!(collection.text(), b"AAC$GTT$GT$AC$");
+        assert_eq!(collection.len(), 2);
+        assert!(collection.has_revcomp());
+        assert_eq!(collection.text_range(1), 8..10);
+        assert_eq!(collection.revcomp_range(1), Some(11..13));
+        assert_eq!(
+            collection.resolve(5),
+            Some(TextPosition {
+                text: 0,
+                reverse: true,
+                offset: 1
+            })
+        );
+        assert_eq!(
+            collection.resolve(12),
+            Some(TextPosition {
+                text: 1,
+                reverse: true,
+                offset: 1
+            })
+        );
+    }
+
+    #[test]
+    fn test_boundaries() {
+        let collection = TextCollection::with_revcomp(&[&b"AAC"[..], b"", b"GT"]);
+        let boundaries = collection.boundaries();
+        assert_eq!(boundaries.len(), 6);
+        assert_eq!(boundaries.text_len(), collection.text().len());
+        for pos in 0..=collection.text().len() {
+            let expected = collection
+                .resolve(pos)
+                .map(|p| (2 * p.text + p.reverse as usize, p.offset));
+            assert_eq!(boundaries.resolve(pos), expected);
+        }
+    }
+
+    #[test]
+    fn test_custom_sentinel() {
+        let collection = TextCollection::with_sentinel(&[&b"AC$"[..], b"G"], true, 0);
+        assert_eq!(collection.text(), b"AC$\0$GT\0G\0C\0");
+        assert_eq!(collection.sentinel(), 0);
+        assert_eq!(collection.revcomp_range(0), Some(4..7));
+        assert_eq!(
+            collection.resolve(4),
+            Some(TextPosition {
+                text: 0,
+                reverse: true,
+                offset: 0
+            })
+        );
+        assert_eq!(collection.resolve(4).unwrap().strand(), ReqStrand::Reverse);
+        assert_eq!(collection.resolve(0).unwrap().strand(), ReqStrand::Forward);
+        assert_eq!(collection.resolve(7), None);
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_sentinel_in_text() {
+        TextCollection::new(&[b"AC$GT"]);
+    }
+
+    #[test]
+    fn test_try_new() {
+        assert_eq!(
+            TextCollection::try_new(&[&b"AC"[..], b"G\nT"]),
+            Err(Error::InvalidSymbol {
+                symbol: b'\n',
+                text: 1,
+                pos: 1,
+                sentinel: SENTINEL
+            })
+        );
+        assert_eq!(
+            TextCollection::try_with_sentinel(&[b"AC\nGT"], false, 0)
+                .unwrap()
+                .text(),
+            b"AC\nGT\0"
+        );
+    }
+
+    #[test]
+    fn test_default_sentinel() {
+        assert_eq!(TextCollection::default().sentinel(), SENTINEL);
+    }
+}
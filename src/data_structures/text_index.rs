@@ -0,0 +1,354 @@
+//! A common interface of full-text indexes for exact pattern search, such that applications,
+//! tests and benchmarks can be generic over the index backend. `TextIndex` is implemented by
+//! the existing index types of this crate:
+//!
+//! * `FMIndex` uses backward search in O(m) for counting. Without a suffix array, occurrences
+//!   are located by walking the BWT back to the start of the text, in O(n) per occurrence.
+//! * `SampledSuffixArray` combines the backward search with locating occurrences in the
+//!   sampled suffix array.
+//! * `QGramIndex` looks up the q-grams of the pattern, and hence only supports patterns of
+//!   length at least q.
+//!
+//! In addition, the following backends own the indexed text or a full suffix array:
+//!
+//! * `SuffixArrayIndex` searches the suffix array binarily in O(m log n),
+//! * `FMTextIndex` combines an `FMIndex` with the full suffix array of the text.
+//!
+//! All backends are built over a single text, whose bytes have to be greater than the sentinel
+//! `$`.
+//!
+//! # Example
+//!
+//! ```
+//! use bio::alphabets::dna;
+//! use bio::data_structures::bwt::{Less, Occ, BWT};
+//! use bio::data_structures::fmindex::FMIndex;
+//! use bio::data_structures::qgram_index::QGramIndex;
+//! use bio::data_structures::suffix_array::SampledSuffixArray;
+//! use bio::data_structures::text_index::{FMTextIndex, SuffixArrayIndex, TextIndex};
+//!
+//! fn occurrences<I: TextIndex>(params: &I::Params) -> Vec<usize> {
+//!     let index = I::build(b"GCCTTAACATTATTACGCCTA", params);
+//!     assert_eq!(index.count(b"TTA"), 3);
+//!     index.locate(b"TTA")
+//! }
+//!
+//! let alphabet = dna::alphabet();
+//! assert_eq!(occurrences::<SuffixArrayIndex>(&()), [3, 9, 12]);
+//! assert_eq!(occurrences::<FMTextIndex>(&alphabet), [3, 9, 12]);
+//! assert_eq!(occurrences::<FMIndex<BWT, Less, Occ>>(&alphabet), [3, 9, 12]);
+//! assert_eq!(
+//!     occurrences::<SampledSuffixArray<BWT, Less, Occ>>(&(4, alphabet.clone())),
+//!     [3, 9, 12]
+//! );
+//! assert_eq!(occurrences::<QGramIndex>(&(2, alphabet)), [3, 9, 12]);
+//! ```
+
+use std::borrow::Borrow;
+use std::ops::Range;
+
+use crate::alphabets::Alphabet;
+use crate::data_structures::bwt::{bwt, less, Less, Occ, BWT};
+use crate::data_structures::fmindex::{BackwardSearchResult, FMIndex, FMIndexable, Interval};
+use crate::data_structures::qgram_index::QGramIndex;
+use crate::data_structures::suffix_array::{
+    suffix_array, RawSuffixArray, SampledSuffixArray, SuffixArray,
+};
+use crate::data_structures::text_collection::SENTINEL;
+use crate::utils::{Text, TextSlice};
+
+/// Sampling rate of the occurrence arrays of the FM-index based backends.
+pub const OCC_SAMPLING_RATE: u32 = 32;
+
+/// A full-text index, answering exact pattern queries. Empty patterns have no occurrences.
+pub trait TextIndex {
+    /// Parameters for building the index, besides the text.
+    type Params;
+
+    /// Build the index of the given text.
+    fn build(text: TextSlice<'_>, params: &Self::Params) -> Self
+    where
+        Self: Sized;
+
+    /// Number of occurrences of the pattern in the text.
+    fn count(&self, pattern: &[u8]) -> usize {
+        self.locate(pattern).len()
+    }
+
+    /// Start positions of the occurrences of the pattern in the text, in increasing order.
+    fn locate(&self, pattern: &[u8]) -> Vec<usize>;
+}
+
+/// The suffix array interval of the pattern in the FM-index, or `None` if it does not occur.
+/// Patterns with the sentinel or symbols outside of the alphabet do not occur.
+fn fm_interval<DBWT: Borrow<BWT>, DLess: Borrow<Less>, DOcc: Borrow<Occ>>(
+    fmindex: &FMIndex<DBWT, DLess, DOcc>,
+    pattern: &[u8],
+) -> Option<Interval> {
+    if pattern.is_empty()
+        || pattern
+            .iter()
+            .any(|&a| a == SENTINEL || !fmindex.contains_symbol(a))
+    {
+        return None;
+    }
+    match fmindex.backward_search(pattern.iter()) {
+        BackwardSearchResult::Complete(interval) => Some(interval),
+        _ => None,
+    }
+}
+
+/// A suffix array together with its text. Patterns with the sentinel have no occurrences.
+#[derive(Default, Clone, Eq, PartialEq, Hash, Debug, Serialize, Deserialize)]
+pub struct SuffixArrayIndex {
+    /// The text, followed by the sentinel.
+    text: Text,
+    sa: RawSuffixArray,
+}
+
+impl SuffixArrayIndex {
+    /// The suffix array interval of the suffixes starting with the pattern.
+    fn interval(&self, pattern: &[u8]) -> Range<usize> {
+        let start = self.sa.partition_point(|&p| &self.text[p..] < pattern);
+        let len = self.sa[start..].partition_point(|&p| self.text[p..].starts_with(pattern));
+        start..start + len
+    }
+}
+
+impl TextIndex for SuffixArrayIndex {
+    type Params = ();
+
+    fn build(text: TextSlice<'_>, _: &()) -> Self {
+        let mut text = text.to_owned();
+        text.push(SENTINEL);
+        let sa = suffix_array(&text);
+        SuffixArrayIndex { text, sa }
+    }
+
+    fn count(&self, pattern: &[u8]) -> usize {
+        if pattern.is_empty() || pattern.contains(&SENTINEL) {
+            return 0;
+        }
+        self.interval(pattern).len()
+    }
+
+    fn locate(&self, pattern: &[u8]) -> Vec<usize> {
+        if pattern.is_empty() || pattern.contains(&SENTINEL) {
+            return Vec::new();
+        }
+        let mut positions = self.sa[self.interval(pattern)].to_vec();
+        positions.sort_unstable();
+        positions
+    }
+}
+
+/// An FM-index over the given alphabet (see `FMIndex::from_texts`). Patterns with symbols
+/// outside of the alphabet have no occurrences.
+impl TextIndex for FMIndex<BWT, Less, Occ> {
+    type Params = Alphabet;
+
+    fn build(text: TextSlice<'_>, alphabet: &Alphabet) -> Self {
+        FMIndex::from_texts(&[text], alphabet, OCC_SAMPLING_RATE).0
+    }
+
+    fn count(&self, pattern: &[u8]) -> usize {
+        fm_interval(self, pattern).map_or(0, |interval| interval.len())
+    }
+
+    fn locate(&self, pattern: &[u8]) -> Vec<usize> {
+        let interval = match fm_interval(self, pattern) {
+            Some(interval) => interval,
+            None => return Vec::new(),
+        };
+        let bwt = self.bwt();
+        let mut positions = (interval.lower..interval.upper)
+            .map(|mut r| {
+                // each LF-mapping step moves to the suffix starting one position earlier,
+                // until reaching the suffix preceded by the sentinel
+                let mut pos = 0;
+                while bwt[r] != SENTINEL {
+                    let a = bwt[r];
+                    r = self.less(a) + self.occ(r, a) - 1;
+                    pos += 1;
+                }
+                pos
+            })
+            .collect::<Vec<_>>();
+        positions.sort_unstable();
+        positions
+    }
+}
+
+/// A sampled suffix array together with the FM-index it is sampled from, built over the given
+/// sampling rate and alphabet (see `SuffixArray::sample`). Patterns with symbols outside of the
+/// alphabet have no occurrences.
+impl TextIndex for SampledSuffixArray<BWT, Less, Occ> {
+    type Params = (usize, Alphabet);
+
+    fn build(text: TextSlice<'_>, params: &(usize, Alphabet)) -> Self {
+        let (sampling_rate, ref alphabet) = *params;
+        let mut text = text.to_owned();
+        text.push(SENTINEL);
+        let sa = suffix_array(&text);
+        let bwt = bwt(&text, &sa);
+        let less = less(&bwt, alphabet);
+        let occ = Occ::new(&bwt, OCC_SAMPLING_RATE, alphabet);
+        sa.sample(&text, bwt, less, occ, sampling_rate)
+    }
+
+    fn count(&self, pattern: &[u8]) -> usize {
+        let fmindex = FMIndex::new(self.bwt(), self.less(), self.occ());
+        fm_interval(&fmindex, pattern).map_or(0, |interval| interval.len())
+    }
+
+    fn locate(&self, pattern: &[u8]) -> Vec<usize> {
+        let fmindex = FMIndex::new(self.bwt(), self.less(), self.occ());
+        let mut positions =
+            fm_interval(&fmindex, pattern).map_or_else(Vec::new, |interval| interval.occ(self));
+        positions.sort_unstable();
+        positions
+    }
+}
+
+/// A q-gram index, built with the given q and alphabet. Patterns are found as exact matches
+/// of all their q-grams. Patterns with symbols outside of the alphabet have no occurrences.
+///
+/// # Panics
+///
+/// Querying a non-empty pattern shorter than q panics, since it cannot be looked up in the
+/// index.
+impl TextIndex for QGramIndex {
+    type Params = (u32, Alphabet);
+
+    fn build(text: TextSlice<'_>, params: &(u32, Alphabet)) -> Self {
+        let (q, ref alphabet) = *params;
+        QGramIndex::new(q, text, alphabet)
+    }
+
+    fn locate(&self, pattern: &[u8]) -> Vec<usize> {
+        if pattern.is_empty() {
+            return Vec::new();
+        }
+        assert!(
+            pattern.len() >= self.q() as usize,
+            "Expecting pattern of length at least q."
+        );
+        if !pattern.iter().all(|&a| self.contains_symbol(a)) {
+            return Vec::new();
+        }
+        let mut positions = self
+            .exact_matches(pattern)
+            .into_iter()
+            .filter(|m| m.pattern.start == 0 && m.pattern.stop == pattern.len())
+            .map(|m| m.text.start)
+            .collect::<Vec<_>>();
+        positions.sort_unstable();
+        positions
+    }
+}
+
+/// An FM-index together with the full suffix array of its text, built over the given alphabet
+/// (see `FMIndex::from_texts`). Patterns with symbols outside of the alphabet have no
+/// occurrences.
+#[derive(Default, Clone, Eq, PartialEq, Hash, Debug)]
+pub struct FMTextIndex {
+    fmindex: FMIndex<BWT, Less, Occ>,
+    sa: RawSuffixArray,
+}
+
+impl TextIndex for FMTextIndex {
+    type Params = Alphabet;
+
+    fn build(text: TextSlice<'_>, alphabet: &Alphabet) -> Self {
+        let (fmindex, sa, _) = FMIndex::from_texts(&[text], alphabet, OCC_SAMPLING_RATE);
+        FMTextIndex { fmindex, sa }
+    }
+
+    fn count(&self, pattern: &[u8]) -> usize {
+        self.fmindex.count(pattern)
+    }
+
+    fn locate(&self, pattern: &[u8]) -> Vec<usize> {
+        let mut positions = fm_interval(&self.fmindex, pattern)
+            .map_or_else(Vec::new, |interval| interval.occ(&self.sa));
+        positions.sort_unstable();
+        positions
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::alphabets::dna;
+    use rand::prelude::*;
+    use rand::rngs::StdRng;
+
+    fn naive(text: &[u8], pattern: &[u8]) -> Vec<usize> {
+        if pattern.is_empty() {
+            return Vec::new();
+        }
+        text.windows(pattern.len())
+            .enumerate()
+            .filter(|&(_, window)| window == pattern)
+            .map(|(i, _)| i)
+            .collect()
+    }
+
+    fn check<I: TextIndex>(params: &I::Params, min_len: usize) {
+        let mut rng = StdRng::seed_from_u64(42);
+        let text = (0..500)
+            .map(|_| *b"ACGT".choose(&mut rng).unwrap())
+            .collect::<Vec<_>>();
+        let index = I::build(&text, params);
+        let mut patterns = vec![b"".to_vec(), b"ACGN".to_vec(), b"ACG$".to_vec()];
+        if min_len <= 1 {
+            patterns.push(b"N".to_vec());
+        }
+        for len in min_len.max(1)..12 {
+            let start = rng.gen_range(0..text.len() - len);
+            patterns.push(text[start..start + len].to_vec());
+            patterns.push(
+                (0..len)
+                    .map(|_| *b"ACGT".choose(&mut rng).unwrap())
+                    .collect(),
+            );
+        }
+        for pattern in patterns {
+            let expected = naive(&text, &pattern);
+            assert_eq!(index.locate(&pattern), expected);
+            assert_eq!(index.count(&pattern), expected.len());
+        }
+    }
+
+    #[test]
+    fn test_suffix_array_index() {
+        check::<SuffixArrayIndex>(&(), 0);
+    }
+
+    #[test]
+    fn test_fm_text_index() {
+        check::<FMTextIndex>(&dna::alphabet(), 0);
+    }
+
+    #[test]
+    fn test_fmindex() {
+        check::<FMIndex<BWT, Less, Occ>>(&dna::alphabet(), 0);
+    }
+
+    #[test]
+    fn test_sampled_suffix_array() {
+        check::<SampledSuffixArray<BWT, Less, Occ>>(&(5, dna::alphabet()), 0);
+    }
+
+    #[test]
+    fn test_qgram_index() {
+        check::<QGramIndex>(&(4, dna::alphabet()), 4);
+    }
+
+    #[test]
+    #[should_panic(expected = "Expecting pattern of length at least q.")]
+    fn test_qgram_index_short_pattern() {
+        let index = QGramIndex::build(b"ACGTACGT", &(4, dna::alphabet()));
+        index.locate(b"ACG");
+    }
+}
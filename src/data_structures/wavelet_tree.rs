@@ -0,0 +1,286 @@
+//! Wavelet tree over byte alphabets (Grossi, Gupta and Vitter, 2003), answering rank, select
+//! and access queries in O(log σ), with σ being the number of distinct symbols of the text.
+//!
+//! The tree is balanced over the symbols occurring in the text. Each inner node stores a bit
+//! vector marking for each position of its subsequence whether the symbol belongs to the upper
+//! half of the node's symbols. The tree needs about n log σ bits plus the rank/select
+//! overhead, in contrast to an occurrence array, which grows with σ. This makes it a more
+//! memory-efficient replacement for `bwt::Occ` with large alphabets like protein
+//! (see `fmindex::WaveletFMIndex`). For the DNA alphabet, see `wavelet_matrix`.
+//!
+//! # Example
+//!
+//! ```
+//! use bio::data_structures::wavelet_tree::WaveletTree;
+//!
+//! let text = b"MKVLAAGIVALLLAAGCSS$";
+//! let wt = WaveletTree::new(text);
+//! assert_eq!(wt.access(3), b'L');
+//! assert_eq!(wt.rank(b'A', 5), 2);
+//! assert_eq!(wt.rank(b'W', 5), 0);
+//! assert_eq!(wt.select(b'L', 3), Some(11));
+//! assert_eq!(wt.select(b'L', 5), None);
+//! ```
+
+use bv::{BitVec, BitsMut};
+
+use crate::data_structures::rank_select::RankSelect;
+
+/// Superblock size (in multiples of 32 bits) of the rank/select bit vectors, trading some
+/// query time for a small space overhead.
+const SUPERBLOCK_SIZE: usize = 8;
+
+#[derive(Default, Clone, Eq, PartialEq, Ord, PartialOrd, Hash, Debug, Serialize, Deserialize)]
+struct Node {
+    bits: RankSelect,
+    /// Nodes of the lower and upper half of the symbols, if they contain more than one symbol.
+    children: [Option<usize>; 2],
+}
+
+/// A wavelet tree of a text.
+#[derive(Default, Clone, Eq, PartialEq, Ord, PartialOrd, Hash, Debug, Serialize, Deserialize)]
+pub struct WaveletTree {
+    len: usize,
+    /// The sorted distinct symbols of the text.
+    symbols: Vec<u8>,
+    nodes: Vec<Node>,
+}
+
+impl WaveletTree {
+    /// Construct the wavelet tree of the given text of length n.
+    /// Complexity: O(n log σ).
+    pub fn new(text: &[u8]) -> Self {
+        let mut present = [false; 256];
+        for &a in text {
+            present[a as usize] = true;
+        }
+        let symbols = (0..=255u8)
+            .filter(|&a| present[a as usize])
+            .collect::<Vec<_>>();
+        let mut ranks = [0usize; 256];
+        for (r, &a) in symbols.iter().enumerate() {
+            ranks[a as usize] = r;
+        }
+        let ranks = text.iter().map(|&a| ranks[a as usize]).collect::<Vec<_>>();
+
+        let mut wt = WaveletTree {
+            len: text.len(),
+            symbols,
+            nodes: Vec::new(),
+        };
+        wt.build(ranks, 0, wt.symbols.len());
+        wt
+    }
+
+    /// Recursively build the node for the given symbol ranks, which are in lo..hi.
+    fn build(&mut self, ranks: Vec<usize>, lo: usize, hi: usize) -> Option<usize> {
+        if hi - lo < 2 {
+            return None;
+        }
+        let mid = (lo + hi) / 2;
+        let mut bits: BitVec<u8> = BitVec::new_fill(false, ranks.len() as u64);
+        let (mut lower, mut upper) = (Vec::new(), Vec::new());
+        for (i, r) in ranks.into_iter().enumerate() {
+            if r >= mid {
+                bits.set_bit(i as u64, true);
+                upper.push(r);
+            } else {
+                lower.push(r);
+            }
+        }
+
+        let node = self.nodes.len();
+        self.nodes.push(Node {
+            bits: RankSelect::new(bits, SUPERBLOCK_SIZE),
+            children: [None, None],
+        });
+        let children = [self.build(lower, lo, mid), self.build(upper, mid, hi)];
+        self.nodes[node].children = children;
+        Some(node)
+    }
+
+    /// Number of symbols equal to `bit` among the first `len` positions of a node.
+    fn prefix_rank(&self, node: usize, bit: bool, len: usize) -> usize {
+        if len == 0 {
+            return 0;
+        }
+        let bits = &self.nodes[node].bits;
+        (if bit {
+            bits.rank_1(len as u64 - 1)
+        } else {
+            bits.rank_0(len as u64 - 1)
+        })
+        .unwrap() as usize
+    }
+
+    /// Length of the text.
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Whether the text is empty.
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// The sorted distinct symbols of the text.
+    pub fn symbols(&self) -> &[u8] {
+        &self.symbols
+    }
+
+    /// Get the symbol at position i of the text.
+    /// Complexity: O(log σ).
+    ///
+    /// # Panics
+    ///
+    /// Panics if i is out of bounds.
+    pub fn access(&self, i: usize) -> u8 {
+        assert!(i < self.len, "Expecting position within the text.");
+        let (mut lo, mut hi) = (0, self.symbols.len());
+        let (mut node, mut pos) = (self.root(), i);
+        while let Some(n) = node {
+            let bit = self.nodes[n].bits.get(pos as u64);
+            pos = self.prefix_rank(n, bit, pos + 1) - 1;
+            let mid = (lo + hi) / 2;
+            if bit {
+                lo = mid;
+            } else {
+                hi = mid;
+            }
+            node = self.nodes[n].children[bit as usize];
+        }
+        self.symbols[lo]
+    }
+
+    /// Get the number of occurrences of symbol a in text[..i+1] (like `bwt::Occ::get`).
+    /// Complexity: O(log σ).
+    ///
+    /// # Panics
+    ///
+    /// Panics if i is out of bounds.
+    pub fn rank(&self, a: u8, i: usize) -> usize {
+        assert!(i < self.len, "Expecting position within the text.");
+        self.prefix_count(a, i + 1)
+    }
+
+    /// Number of occurrences of symbol a among the first len positions of the text.
+    fn prefix_count(&self, a: u8, len: usize) -> usize {
+        let r = match self.symbols.binary_search(&a) {
+            Ok(r) => r,
+            Err(_) => return 0,
+        };
+        let (mut lo, mut hi) = (0, self.symbols.len());
+        let (mut node, mut len) = (self.root(), len);
+        while let Some(n) = node {
+            let mid = (lo + hi) / 2;
+            let bit = r >= mid;
+            len = self.prefix_rank(n, bit, len);
+            if bit {
+                lo = mid;
+            } else {
+                hi = mid;
+            }
+            node = self.nodes[n].children[bit as usize];
+        }
+        len
+    }
+
+    /// Get the position of the k-th occurrence (starting with k = 1) of symbol a in the text,
+    /// i.e. the smallest position with rank k.
+    /// Complexity: O(log σ log n).
+    pub fn select(&self, a: u8, k: usize) -> Option<usize> {
+        if k == 0 || k > self.prefix_count(a, self.len) {
+            return None;
+        }
+        let r = self.symbols.binary_search(&a).ok()?;
+        // record the path to the leaf of the symbol
+        let (mut lo, mut hi) = (0, self.symbols.len());
+        let mut path = Vec::new();
+        let mut node = self.root();
+        while let Some(n) = node {
+            let mid = (lo + hi) / 2;
+            let bit = r >= mid;
+            path.push((n, bit));
+            if bit {
+                lo = mid;
+            } else {
+                hi = mid;
+            }
+            node = self.nodes[n].children[bit as usize];
+        }
+
+        // the k-th occurrence in the subsequence of a child is the k-th occurrence of its bit
+        // in the parent
+        let mut k = k as u64;
+        for (n, bit) in path.into_iter().rev() {
+            let bits = &self.nodes[n].bits;
+            let pos = if bit {
+                bits.select_1(k)
+            } else {
+                bits.select_0(k)
+            };
+            k = pos.expect("bug: inconsistent wavelet tree") + 1;
+        }
+        Some(k as usize - 1)
+    }
+
+    fn root(&self) -> Option<usize> {
+        if self.nodes.is_empty() {
+            None
+        } else {
+            Some(0)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::prelude::*;
+    use rand::rngs::StdRng;
+
+    fn check(text: &[u8]) {
+        let wt = WaveletTree::new(text);
+        assert_eq!(wt.len(), text.len());
+        for (i, &a) in text.iter().enumerate() {
+            assert_eq!(wt.access(i), a);
+        }
+        for &a in wt.symbols().iter().chain(b"#") {
+            let mut count = 0;
+            for (i, &b) in text.iter().enumerate() {
+                if a == b {
+                    count += 1;
+                    assert_eq!(wt.select(a, count), Some(i));
+                }
+                assert_eq!(wt.rank(a, i), count);
+            }
+            assert_eq!(wt.select(a, count + 1), None);
+            assert_eq!(wt.select(a, 0), None);
+        }
+    }
+
+    #[test]
+    fn test_random_protein() {
+        let mut rng = StdRng::seed_from_u64(42);
+        let alphabet = b"ACDEFGHIKLMNPQRSTVWY$";
+        let text = (0..2000)
+            .map(|_| *alphabet.choose(&mut rng).unwrap())
+            .collect::<Vec<_>>();
+        check(&text);
+    }
+
+    #[test]
+    fn test_small_alphabets() {
+        check(b"");
+        check(b"AAAA");
+        check(b"ABBA");
+        check(b"CAB");
+        check(&(0..=255).collect::<Vec<u8>>());
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_access_out_of_bounds() {
+        WaveletTree::new(b"ACGT").access(4);
+    }
+}
@@ -0,0 +1,345 @@
+//! C ABI functions for embedding core algorithms into other languages (e.g. via Python's
+//! `ctypes`/`cffi` or from C++), without writing bindings for each project. This module is only
+//! available with the feature `ffi`. Together with a `cdylib` or `staticlib` build of a
+//! wrapping crate, a C header can be generated with `cbindgen`.
+//!
+//! All functions take sequences as pointer and length. Pointers may be null if the length is
+//! zero. Results are returned via plain `#[repr(C)]` structs or written into caller-provided
+//! buffers, returning the total number of results such that the caller can retry with a
+//! larger buffer. The only heap-allocated object is the FM-index, which has to be released
+//! with `bio_fmindex_free`.
+//!
+//! Panics never unwind across the FFI boundary. Instead, a function that fails this way
+//! returns null (`bio_fmindex_new`), `usize::MAX` (functions returning a count) or a
+//! `BioAlignment` with score `i32::MIN` (`bio_align`).
+//!
+//! # Example
+//!
+//! ```
+//! use bio::ffi::*;
+//!
+//! let text = b"GCCTTAACATTATTACGCCTA";
+//! let pattern = b"TTA";
+//! unsafe {
+//!     let index = bio_fmindex_new(text.as_ptr(), text.len());
+//!     assert_eq!(bio_fmindex_count(index, pattern.as_ptr(), pattern.len()), 3);
+//!     let mut positions = [0usize; 2];
+//!     let n = bio_fmindex_locate(index, pattern.as_ptr(), pattern.len(), positions.as_mut_ptr(), 2);
+//!     assert_eq!(n, 3);
+//!     bio_fmindex_free(index);
+//!
+//!     let alignment = bio_align(
+//!         text.as_ptr(),
+//!         text.len(),
+//!         pattern.as_ptr(),
+//!         pattern.len(),
+//!         BIO_ALIGN_LOCAL,
+//!         1,
+//!         -1,
+//!         -5,
+//!         -1,
+//!     );
+//!     assert_eq!(alignment.score, 3);
+//! }
+//! ```
+
+use std::panic::{self, AssertUnwindSafe};
+use std::ptr;
+use std::slice;
+
+use crate::alignment::pairwise::Aligner;
+use crate::alphabets::Alphabet;
+use crate::data_structures::text_index::{FMTextIndex, TextIndex};
+use crate::pattern_matching::myers::long::Myers;
+
+/// Global alignment mode for `bio_align`.
+pub const BIO_ALIGN_GLOBAL: u32 = 0;
+/// Semiglobal alignment mode (x aligned completely, y locally) for `bio_align`.
+pub const BIO_ALIGN_SEMIGLOBAL: u32 = 1;
+/// Local alignment mode for `bio_align`.
+pub const BIO_ALIGN_LOCAL: u32 = 2;
+
+/// Run the given closure, returning the fallback if it panics.
+fn catch_panic<T>(fallback: T, f: impl FnOnce() -> T) -> T {
+    panic::catch_unwind(AssertUnwindSafe(f)).unwrap_or(fallback)
+}
+
+/// Interpret a pointer and length as slice.
+unsafe fn as_slice<'a, T>(ptr: *const T, len: usize) -> &'a [T] {
+    if len == 0 {
+        &[]
+    } else {
+        slice::from_raw_parts(ptr, len)
+    }
+}
+
+/// Copy the results into the given buffer (as far as it fits) and return their number.
+unsafe fn write_results<T: Copy>(results: &[T], out: *mut T, capacity: usize) -> usize {
+    let n = results.len().min(capacity);
+    if n > 0 {
+        slice::from_raw_parts_mut(out, n).copy_from_slice(&results[..n]);
+    }
+    results.len()
+}
+
+/// Result of a pairwise alignment, with positions as half-open intervals.
+#[repr(C)]
+#[derive(Default, Copy, Clone, Eq, PartialEq, Debug)]
+pub struct BioAlignment {
+    pub score: i32,
+    pub xstart: usize,
+    pub xend: usize,
+    pub ystart: usize,
+    pub yend: usize,
+}
+
+impl BioAlignment {
+    /// Returned by `bio_align` if the alignment failed.
+    const FAILED: BioAlignment = BioAlignment {
+        score: i32::MIN,
+        xstart: usize::MAX,
+        xend: usize::MAX,
+        ystart: usize::MAX,
+        yend: usize::MAX,
+    };
+}
+
+/// Align x against y with affine gap penalties (`gap_open` and `gap_extend` being negative)
+/// and a match/mismatch score, in the given mode (`BIO_ALIGN_GLOBAL`, `BIO_ALIGN_SEMIGLOBAL`
+/// or `BIO_ALIGN_LOCAL`). Invalid modes are treated as global. On failure, the score is
+/// `i32::MIN` and all positions are `usize::MAX`.
+///
+/// # Safety
+///
+/// `x` and `y` have to point to at least `xlen` and `ylen` readable bytes.
+#[no_mangle]
+#[allow(clippy::too_many_arguments)]
+pub unsafe extern "C" fn bio_align(
+    x: *const u8,
+    xlen: usize,
+    y: *const u8,
+    ylen: usize,
+    mode: u32,
+    match_score: i32,
+    mismatch_score: i32,
+    gap_open: i32,
+    gap_extend: i32,
+) -> BioAlignment {
+    catch_panic(BioAlignment::FAILED, || {
+        let (x, y) = (as_slice(x, xlen), as_slice(y, ylen));
+        let score = |a: u8, b: u8| if a == b { match_score } else { mismatch_score };
+        let mut aligner = Aligner::with_capacity(xlen, ylen, gap_open, gap_extend, score);
+        let alignment = match mode {
+            BIO_ALIGN_SEMIGLOBAL => aligner.semiglobal(x, y),
+            BIO_ALIGN_LOCAL => aligner.local(x, y),
+            _ => aligner.global(x, y),
+        };
+        BioAlignment {
+            score: alignment.score,
+            xstart: alignment.xstart,
+            xend: alignment.xend,
+            ystart: alignment.ystart,
+            yend: alignment.yend,
+        }
+    })
+}
+
+/// An approximate occurrence of a pattern, with positions as half-open interval.
+#[repr(C)]
+#[derive(Default, Copy, Clone, Eq, PartialEq, Debug)]
+pub struct BioMatch {
+    pub start: usize,
+    pub end: usize,
+    pub dist: usize,
+}
+
+/// Find all occurrences of the pattern in the text with at most `max_dist` edits with Myers'
+/// algorithm, like `myers::Myers::find_all`. Writes up to `capacity` matches into `out` and
+/// returns the total number of matches, or `usize::MAX` on failure.
+///
+/// # Safety
+///
+/// `pattern` and `text` have to point to at least `pattern_len` and `text_len` readable bytes,
+/// and `out` to space for `capacity` matches.
+#[no_mangle]
+pub unsafe extern "C" fn bio_myers_find(
+    pattern: *const u8,
+    pattern_len: usize,
+    text: *const u8,
+    text_len: usize,
+    max_dist: usize,
+    out: *mut BioMatch,
+    capacity: usize,
+) -> usize {
+    catch_panic(usize::MAX, || {
+        if pattern_len == 0 {
+            return 0;
+        }
+        let mut myers = Myers::<u64>::new(as_slice(pattern, pattern_len));
+        let matches = myers
+            .find_all(as_slice(text, text_len), max_dist)
+            .map(|(start, end, dist)| BioMatch { start, end, dist })
+            .collect::<Vec<_>>();
+        write_results(&matches, out, capacity)
+    })
+}
+
+/// An FM-index over a single text, with its suffix array (see `text_index::FMTextIndex`).
+pub struct BioFMIndex {
+    index: FMTextIndex,
+}
+
+/// Build an FM-index over the given text, with the symbols of the text as alphabet. All bytes
+/// of the text have to be greater than `$` (i.e. the sentinel that terminates the text),
+/// otherwise null is returned, as on any other failure. The index has to be released with
+/// `bio_fmindex_free`.
+///
+/// # Safety
+///
+/// `text` has to point to at least `len` readable bytes.
+#[no_mangle]
+pub unsafe extern "C" fn bio_fmindex_new(text: *const u8, len: usize) -> *mut BioFMIndex {
+    catch_panic(ptr::null_mut(), || {
+        let text = as_slice(text, len);
+        if text.iter().any(|&b| b <= b'$') {
+            return ptr::null_mut();
+        }
+        let index = FMTextIndex::build(text, &Alphabet::new(text));
+        Box::into_raw(Box::new(BioFMIndex { index }))
+    })
+}
+
+/// Release an FM-index created with `bio_fmindex_new`. Null pointers are ignored.
+///
+/// # Safety
+///
+/// `index` has to be null or created by `bio_fmindex_new`, and must not be used afterwards.
+#[no_mangle]
+pub unsafe extern "C" fn bio_fmindex_free(index: *mut BioFMIndex) {
+    if !index.is_null() {
+        catch_panic((), || drop(Box::from_raw(index)));
+    }
+}
+
+/// Count the occurrences of the pattern in the indexed text, returning `usize::MAX` on
+/// failure.
+///
+/// # Safety
+///
+/// `index` has to be a valid FM-index created by `bio_fmindex_new` and `pattern` has to point
+/// to at least `pattern_len` readable bytes.
+#[no_mangle]
+pub unsafe extern "C" fn bio_fmindex_count(
+    index: *const BioFMIndex,
+    pattern: *const u8,
+    pattern_len: usize,
+) -> usize {
+    catch_panic(usize::MAX, || {
+        (*index).index.count(as_slice(pattern, pattern_len))
+    })
+}
+
+/// Locate the occurrences of the pattern in the indexed text, writing up to `capacity` start
+/// positions (in increasing order) into `out` and returning the total number of
+/// occurrences, or `usize::MAX` on failure.
+///
+/// # Safety
+///
+/// `index` has to be a valid FM-index created by `bio_fmindex_new`, `pattern` has to point to
+/// at least `pattern_len` readable bytes and `out` to space for `capacity` positions.
+#[no_mangle]
+pub unsafe extern "C" fn bio_fmindex_locate(
+    index: *const BioFMIndex,
+    pattern: *const u8,
+    pattern_len: usize,
+    out: *mut usize,
+    capacity: usize,
+) -> usize {
+    catch_panic(usize::MAX, || {
+        let positions = (*index).index.locate(as_slice(pattern, pattern_len));
+        write_results(&positions, out, capacity)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_myers_find() {
+        let text = b"CGGTCCTGAGGGATTAGCAC";
+        let pattern = b"TCCTAGGGC";
+        let mut matches = [BioMatch::default(); 4];
+        let n = unsafe {
+            bio_myers_find(
+                pattern.as_ptr(),
+                pattern.len(),
+                text.as_ptr(),
+                text.len(),
+                2,
+                matches.as_mut_ptr(),
+                matches.len(),
+            )
+        };
+        assert_eq!(n, 2);
+        assert_eq!(
+            matches[..2],
+            [
+                BioMatch {
+                    start: 3,
+                    end: 12,
+                    dist: 2
+                },
+                BioMatch {
+                    start: 3,
+                    end: 13,
+                    dist: 2
+                }
+            ]
+        );
+    }
+
+    #[test]
+    fn test_fmindex() {
+        let text = b"GCCTTAACATTATTACGCCTA";
+        unsafe {
+            let index = bio_fmindex_new(text.as_ptr(), text.len());
+            let mut positions = [0; 3];
+            let n = bio_fmindex_locate(index, b"TTA".as_ptr(), 3, positions.as_mut_ptr(), 3);
+            assert_eq!(n, 3);
+            assert_eq!(positions, [3, 9, 12]);
+            // symbols outside of the text and empty patterns do not occur
+            assert_eq!(bio_fmindex_count(index, b"TTN".as_ptr(), 3), 0);
+            assert_eq!(bio_fmindex_count(index, ptr::null(), 0), 0);
+            bio_fmindex_free(index);
+            bio_fmindex_free(ptr::null_mut());
+            // texts containing the sentinel or smaller bytes are rejected
+            assert!(bio_fmindex_new(b"ACG$T".as_ptr(), 5).is_null());
+            assert!(bio_fmindex_new(b"ACG\nT".as_ptr(), 5).is_null());
+        }
+    }
+
+    #[test]
+    fn test_align() {
+        let x = b"ACCGTGGAT";
+        let y = b"AAAAACCGTTGAT";
+        let alignment = unsafe {
+            bio_align(
+                x.as_ptr(),
+                9,
+                y.as_ptr(),
+                13,
+                BIO_ALIGN_SEMIGLOBAL,
+                1,
+                -1,
+                -5,
+                -1,
+            )
+        };
+        assert_eq!((alignment.xstart, alignment.xend), (0, 9));
+        assert_eq!((alignment.ystart, alignment.yend), (4, 13));
+        assert_eq!(alignment.score, 7);
+        let alignment = unsafe { bio_align(ptr::null(), 0, y.as_ptr(), 13, 0, 1, -1, -5, -1) };
+        assert_eq!(alignment.score, -18);
+    }
+}
@@ -0,0 +1,197 @@
+//! Traits for reading records asynchronously with `fasta::AsyncReader` and
+//! `fastq::AsyncReader`, available with the feature `async-io`.
+//!
+//! In order to not tie rust-bio to a particular runtime, the traits only depend on the standard
+//! library. They mirror `AsyncRead` and `Stream` of the [futures](https://docs.rs/futures)
+//! crate, with identical method signatures. Readers of any runtime can hence be plugged in with
+//! a small wrapper, and the readers can be turned into a `futures::Stream` with
+//! `futures::stream::poll_fn`, e.g.:
+//!
+//! ```ignore
+//! use std::pin::Pin;
+//! use std::task::{Context, Poll};
+//!
+//! struct Compat<R>(R);
+//!
+//! impl<R: futures::io::AsyncRead + Unpin> bio::io::async_io::AsyncRead for Compat<R> {
+//!     fn poll_read(
+//!         self: Pin<&mut Self>,
+//!         cx: &mut Context<'_>,
+//!         buf: &mut [u8],
+//!     ) -> Poll<std::io::Result<usize>> {
+//!         Pin::new(&mut self.get_mut().0).poll_read(cx, buf)
+//!     }
+//! }
+//!
+//! let mut reader = bio::io::fasta::AsyncReader::new(Compat(upload));
+//! let records = futures::stream::poll_fn(move |cx| {
+//!     bio::io::async_io::Stream::poll_next(Pin::new(&mut reader), cx)
+//! });
+//! ```
+//!
+//! Parsing itself never blocks, and records are yielded as soon as they are complete (see
+//! `fasta::PushReader` and `fastq::PushReader`).
+
+use std::io;
+use std::pin::Pin;
+use std::task::{Context, Poll};
+
+/// A source of bytes that can be read without blocking, like `futures::io::AsyncRead`.
+pub trait AsyncRead {
+    /// Attempt to read into `buf`, returning the number of bytes read (`0` at the end of the
+    /// input). If no data is available yet, `Poll::Pending` is returned and the task is woken
+    /// once the reader can make progress.
+    fn poll_read(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &mut [u8],
+    ) -> Poll<io::Result<usize>>;
+}
+
+impl AsyncRead for &[u8] {
+    fn poll_read(
+        self: Pin<&mut Self>,
+        _cx: &mut Context<'_>,
+        buf: &mut [u8],
+    ) -> Poll<io::Result<usize>> {
+        Poll::Ready(io::Read::read(self.get_mut(), buf))
+    }
+}
+
+impl<R: AsyncRead + Unpin + ?Sized> AsyncRead for &mut R {
+    fn poll_read(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &mut [u8],
+    ) -> Poll<io::Result<usize>> {
+        Pin::new(&mut **self.get_mut()).poll_read(cx, buf)
+    }
+}
+
+impl<R: AsyncRead + Unpin + ?Sized> AsyncRead for Box<R> {
+    fn poll_read(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &mut [u8],
+    ) -> Poll<io::Result<usize>> {
+        Pin::new(&mut **self.get_mut()).poll_read(cx, buf)
+    }
+}
+
+/// An asynchronous sequence of items, like `futures::Stream`.
+pub trait Stream {
+    type Item;
+
+    /// Attempt to get the next item, `Poll::Ready(None)` at the end of the stream. If no item
+    /// is available yet, `Poll::Pending` is returned and the task is woken once the stream can
+    /// make progress.
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>>;
+}
+
+/// Chunked input of the async readers.
+#[derive(Debug)]
+pub(crate) struct Source<R> {
+    reader: R,
+    buf: Vec<u8>,
+}
+
+impl<R: AsyncRead + Unpin> Source<R> {
+    pub(crate) fn new(reader: R) -> Self {
+        Source {
+            reader,
+            buf: vec![0; 64 * 1024],
+        }
+    }
+
+    /// Read the next chunk of input, which is empty at the end of the input.
+    pub(crate) fn poll_chunk(&mut self, cx: &mut Context<'_>) -> Poll<io::Result<&[u8]>> {
+        loop {
+            match Pin::new(&mut self.reader).poll_read(cx, &mut self.buf) {
+                Poll::Ready(Ok(n)) => return Poll::Ready(Ok(&self.buf[..n])),
+                Poll::Ready(Err(e)) if e.kind() == io::ErrorKind::Interrupted => continue,
+                Poll::Ready(Err(e)) => return Poll::Ready(Err(e)),
+                Poll::Pending => return Poll::Pending,
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use super::*;
+    use std::future::Future;
+    use std::sync::Arc;
+    use std::task::{Wake, Waker};
+    use std::thread;
+
+    struct ThreadWaker(thread::Thread);
+
+    impl Wake for ThreadWaker {
+        fn wake(self: Arc<Self>) {
+            self.0.unpark();
+        }
+    }
+
+    /// Run the future to completion on the current thread.
+    pub(crate) fn block_on<F: Future>(future: F) -> F::Output {
+        let mut future = Box::pin(future);
+        let waker = Waker::from(Arc::new(ThreadWaker(thread::current())));
+        let mut cx = Context::from_waker(&waker);
+        loop {
+            match future.as_mut().poll(&mut cx) {
+                Poll::Ready(output) => return output,
+                Poll::Pending => thread::park(),
+            }
+        }
+    }
+
+    /// A reader that returns the data in chunks of the given size, being pending before each
+    /// chunk, like a slow network connection.
+    pub(crate) struct Trickle<'a> {
+        pub(crate) data: &'a [u8],
+        pub(crate) chunk_len: usize,
+        pub(crate) ready: bool,
+    }
+
+    impl AsyncRead for Trickle<'_> {
+        fn poll_read(
+            self: Pin<&mut Self>,
+            cx: &mut Context<'_>,
+            buf: &mut [u8],
+        ) -> Poll<io::Result<usize>> {
+            let this = self.get_mut();
+            if !this.ready {
+                this.ready = true;
+                cx.waker().wake_by_ref();
+                return Poll::Pending;
+            }
+            this.ready = false;
+            let n = this.chunk_len.min(buf.len()).min(this.data.len());
+            buf[..n].copy_from_slice(&this.data[..n]);
+            this.data = &this.data[n..];
+            Poll::Ready(Ok(n))
+        }
+    }
+
+    #[test]
+    fn test_source() {
+        let mut source = Source::new(Trickle {
+            data: b"ACGTA",
+            chunk_len: 2,
+            ready: false,
+        });
+        let mut data = Vec::new();
+        block_on(std::future::poll_fn(|cx| loop {
+            match source.poll_chunk(cx) {
+                Poll::Ready(Ok([])) => return Poll::Ready(()),
+                Poll::Ready(Ok(chunk)) => {
+                    assert!(chunk.len() <= 2);
+                    data.extend_from_slice(chunk);
+                }
+                Poll::Ready(Err(e)) => panic!("{}", e),
+                Poll::Pending => return Poll::Pending,
+            }
+        }));
+        assert_eq!(data, b"ACGTA");
+    }
+}
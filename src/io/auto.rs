@@ -0,0 +1,293 @@
+//! Reading sequence records from FASTA or FASTQ input, with the format detected automatically
+//! from the first byte of the input. This allows tools to accept either format (or stdin) with a
+//! single code path, accessing the records via the `SeqRecord` trait.
+//!
+//! Gzip-compressed input is recognized by its magic bytes, but has to be decompressed by the
+//! caller (e.g. with a decoder of the `flate2` crate passed to `from_reader`), since this crate
+//! does not depend on a compression library.
+//!
+//! # Example
+//!
+//! ```
+//! use bio::io::auto::{self, Format, SeqRecord};
+//!
+//! let fastq: &[u8] = b"@read1\nACGT\n+\nIIII\n@read2\nGGCA\n+\nIII#\n";
+//! let records = auto::from_reader(fastq).unwrap();
+//! assert_eq!(records.format(), Format::Fastq);
+//! let mut total = 0;
+//! for record in records {
+//!     let record = record.unwrap();
+//!     assert!(record.qual().is_some());
+//!     total += record.seq().len();
+//! }
+//! assert_eq!(total, 8);
+//!
+//! let fasta: &[u8] = b">chr1 first\nACGT\nAC\n";
+//! let record = auto::from_reader(fasta).unwrap().next().unwrap().unwrap();
+//! assert_eq!((record.id(), record.desc()), ("chr1", Some("first")));
+//! assert_eq!(record.seq(), b"ACGTAC");
+//! ```
+
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+use thiserror::Error;
+
+use crate::io::{fasta, fastq};
+use crate::utils::TextSlice;
+
+#[derive(Error, Debug)]
+pub enum Error {
+    #[error("can't open {path} file: {source}")]
+    FileOpen { path: PathBuf, source: io::Error },
+    #[error("can't read input")]
+    Read(#[from] io::Error),
+    #[error("invalid FASTQ record")]
+    Fastq(#[from] fastq::Error),
+    #[error("input is {0:?}-compressed, please decompress it first")]
+    Compressed(Compression),
+    #[error("unknown format, expecting '>' (FASTA) or '@' (FASTQ) instead of '{}'", char::from(*first))]
+    UnknownFormat { first: u8 },
+}
+
+pub type Result<T, E = Error> = std::result::Result<T, E>;
+
+/// Sequence file formats recognized by `open` and `from_reader`.
+#[derive(Copy, Clone, Eq, PartialEq, Ord, PartialOrd, Hash, Debug, Serialize, Deserialize)]
+pub enum Format {
+    Fasta,
+    Fastq,
+}
+
+/// Compression formats recognized (but not decompressed) by `open` and `from_reader`.
+#[derive(Copy, Clone, Eq, PartialEq, Ord, PartialOrd, Hash, Debug, Serialize, Deserialize)]
+pub enum Compression {
+    Gzip,
+    Bzip2,
+    Zstd,
+}
+
+/// Common accessors of sequence records of different formats.
+pub trait SeqRecord {
+    /// The id of the record.
+    fn id(&self) -> &str;
+    /// The description of the record, if present.
+    fn desc(&self) -> Option<&str>;
+    /// The sequence of the record.
+    fn seq(&self) -> TextSlice<'_>;
+    /// The base qualities of the record, if the format has them.
+    fn qual(&self) -> Option<&[u8]>;
+}
+
+impl SeqRecord for fasta::Record {
+    fn id(&self) -> &str {
+        self.id()
+    }
+
+    fn desc(&self) -> Option<&str> {
+        self.desc()
+    }
+
+    fn seq(&self) -> TextSlice<'_> {
+        self.seq()
+    }
+
+    fn qual(&self) -> Option<&[u8]> {
+        None
+    }
+}
+
+impl SeqRecord for fastq::Record {
+    fn id(&self) -> &str {
+        self.id()
+    }
+
+    fn desc(&self) -> Option<&str> {
+        self.desc()
+    }
+
+    fn seq(&self) -> TextSlice<'_> {
+        self.seq()
+    }
+
+    fn qual(&self) -> Option<&[u8]> {
+        Some(self.qual())
+    }
+}
+
+/// A record of either format.
+#[derive(Clone, Eq, PartialEq, Debug, Serialize, Deserialize)]
+pub enum Record {
+    Fasta(fasta::Record),
+    Fastq(fastq::Record),
+}
+
+impl SeqRecord for Record {
+    fn id(&self) -> &str {
+        match self {
+            Record::Fasta(record) => record.id(),
+            Record::Fastq(record) => record.id(),
+        }
+    }
+
+    fn desc(&self) -> Option<&str> {
+        match self {
+            Record::Fasta(record) => record.desc(),
+            Record::Fastq(record) => record.desc(),
+        }
+    }
+
+    fn seq(&self) -> TextSlice<'_> {
+        match self {
+            Record::Fasta(record) => record.seq(),
+            Record::Fastq(record) => record.seq(),
+        }
+    }
+
+    fn qual(&self) -> Option<&[u8]> {
+        match self {
+            Record::Fasta(_) => None,
+            Record::Fastq(record) => Some(record.qual()),
+        }
+    }
+}
+
+enum Inner<B: io::BufRead> {
+    Fasta(fasta::Records<B>),
+    Fastq(fastq::Records<B>),
+}
+
+/// An iterator over the records of FASTA or FASTQ input.
+pub struct Records<B: io::BufRead> {
+    inner: Inner<B>,
+}
+
+impl<B: io::BufRead> Records<B> {
+    /// The detected format.
+    pub fn format(&self) -> Format {
+        match self.inner {
+            Inner::Fasta(_) => Format::Fasta,
+            Inner::Fastq(_) => Format::Fastq,
+        }
+    }
+}
+
+impl<B: io::BufRead> Iterator for Records<B> {
+    type Item = Result<Record>;
+
+    fn next(&mut self) -> Option<Result<Record>> {
+        match &mut self.inner {
+            Inner::Fasta(records) => records
+                .next()
+                .map(|record| record.map(Record::Fasta).map_err(Error::from)),
+            Inner::Fastq(records) => records
+                .next()
+                .map(|record| record.map(Record::Fastq).map_err(Error::from)),
+        }
+    }
+}
+
+/// Detect the format from the first bytes of the input, skipping leading whitespace. Empty
+/// input is treated as FASTA without records.
+fn detect<B: io::BufRead>(reader: &mut B) -> Result<Format> {
+    loop {
+        let buf = reader.fill_buf()?;
+        match buf {
+            [] => return Ok(Format::Fasta),
+            [0x1f, 0x8b, ..] => return Err(Error::Compressed(Compression::Gzip)),
+            [b'B', b'Z', b'h', ..] => return Err(Error::Compressed(Compression::Bzip2)),
+            [0x28, 0xb5, 0x2f, 0xfd, ..] => return Err(Error::Compressed(Compression::Zstd)),
+            [b'>', ..] => return Ok(Format::Fasta),
+            [b'@', ..] => return Ok(Format::Fastq),
+            [a, ..] if a.is_ascii_whitespace() => reader.consume(1),
+            [a, ..] => return Err(Error::UnknownFormat { first: *a }),
+        }
+    }
+}
+
+/// Read FASTA or FASTQ records from the given buffered reader, detecting the format.
+pub fn from_bufread<B: io::BufRead>(mut reader: B) -> Result<Records<B>> {
+    let inner = match detect(&mut reader)? {
+        Format::Fasta => Inner::Fasta(fasta::Reader::from_bufread(reader).records()),
+        Format::Fastq => Inner::Fastq(fastq::Reader::from_bufread(reader).records()),
+    };
+    Ok(Records { inner })
+}
+
+/// Read FASTA or FASTQ records from the given reader, detecting the format.
+pub fn from_reader<R: io::Read>(reader: R) -> Result<Records<io::BufReader<R>>> {
+    from_bufread(io::BufReader::new(reader))
+}
+
+/// Read FASTA or FASTQ records from the file at the given path, or from stdin if the path is
+/// `-`, detecting the format.
+///
+/// # Example
+///
+/// ```no_run
+/// use bio::io;
+/// use bio::io::auto::SeqRecord;
+///
+/// for record in io::open("reads.fq").unwrap() {
+///     println!("{}", record.unwrap().id());
+/// }
+/// ```
+pub fn open<P: AsRef<Path>>(path: P) -> Result<Records<Box<dyn io::BufRead>>> {
+    let path = path.as_ref();
+    let reader: Box<dyn io::BufRead> = if path == Path::new("-") {
+        Box::new(io::BufReader::new(io::stdin()))
+    } else {
+        let file = fs::File::open(path).map_err(|source| Error::FileOpen {
+            path: path.to_owned(),
+            source,
+        })?;
+        Box::new(io::BufReader::new(file))
+    };
+    from_bufread(reader)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_detect() {
+        let records = from_reader(&b"\n\n>a\nAC\n>b\nGT\n"[..]).unwrap();
+        assert_eq!(records.format(), Format::Fasta);
+        let ids = records
+            .map(|record| record.unwrap().id().to_owned())
+            .collect::<Vec<_>>();
+        assert_eq!(ids, ["a", "b"]);
+        assert_eq!(from_reader(&b""[..]).unwrap().count(), 0);
+        assert!(matches!(
+            from_reader(&[0x1f, 0x8b, 8, 0][..]),
+            Err(Error::Compressed(Compression::Gzip))
+        ));
+        assert!(matches!(
+            from_reader(&b"ACGT\n"[..]),
+            Err(Error::UnknownFormat { first: b'A' })
+        ));
+        // errors of the underlying readers are passed on
+        let mut records = from_reader(&b"@a\nAC\n+\n"[..]).unwrap();
+        assert!(matches!(
+            records.next(),
+            Some(Err(Error::Fastq(fastq::Error::IncompleteRecord)))
+        ));
+    }
+
+    #[test]
+    fn test_open() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("reads.fq");
+        fs::write(&path, b"@r1 desc\nACGT\n+\nIIII\n").unwrap();
+        let records = open(&path).unwrap().collect::<Result<Vec<_>>>().unwrap();
+        assert_eq!(records.len(), 1);
+        assert_eq!(records[0].desc(), Some("desc"));
+        assert_eq!(records[0].qual(), Some(&b"IIII"[..]));
+        assert!(matches!(
+            open(dir.path().join("missing.fa")),
+            Err(Error::FileOpen { .. })
+        ));
+    }
+}
@@ -0,0 +1,591 @@
+//! Reading of the core fields of alignment records in BAM format.
+//!
+//! BAM files are usually compressed in BGZF blocks. This reader expects the decompressed
+//! stream, i.e. either an uncompressed BAM file (as written by e.g. `samtools view -u`) or a
+//! BGZF decoder wrapped around the compressed file (any `io::Read`, e.g. from the `flate2`
+//! or `noodles-bgzf` crates). Records yield the core fields (name, flag, reference,
+//! position, mapping quality, CIGAR, sequence, qualities and mate information), while
+//! auxiliary data is kept as raw bytes. CIGARs of more than 65535 operations, which BAM
+//! stores in the auxiliary `CG` tag, are not resolved.
+//!
+//! # Example
+//!
+//! ```no_run
+//! use bio::io::bam;
+//!
+//! let mut reader = bam::Reader::from_file("alignments.ubam").unwrap();
+//! let references = reader.header().references.clone();
+//! for record in reader.records() {
+//!     let record = record.unwrap();
+//!     if let (Some(ref_id), Some(pos)) = (record.ref_id, record.pos) {
+//!         println!(
+//!             "{} {}:{} {}",
+//!             String::from_utf8_lossy(&record.name),
+//!             references[ref_id].name,
+//!             pos,
+//!             record.cigar_string()
+//!         );
+//!     }
+//! }
+//! ```
+
+use std::convert::{TryFrom, TryInto};
+use std::fs;
+use std::io::{self, Read};
+use std::path::{Path, PathBuf};
+
+use anyhow::Context;
+use thiserror::Error;
+
+#[derive(Error, Debug)]
+pub enum Error {
+    #[error("expected BAM magic number at start of file")]
+    InvalidMagic,
+
+    #[error("can't open {path} file: {source}")]
+    FileOpen { path: PathBuf, source: io::Error },
+
+    #[error("can't read input")]
+    ReadError(#[from] io::Error),
+
+    #[error("unexpected end of input within {context}")]
+    Truncated { context: &'static str },
+
+    #[error("invalid record: {reason}")]
+    InvalidRecord { reason: String },
+}
+pub type Result<T, E = Error> = std::result::Result<T, E>;
+
+const MAGIC: &[u8; 4] = b"BAM\x01";
+const CIGAR_OPS: &[u8; 9] = b"MIDNSHP=X";
+const BASES: &[u8; 16] = b"=ACMGRSVTWYHKDBN";
+
+/// A reference sequence of the header.
+#[derive(Default, Clone, Eq, PartialEq, Ord, PartialOrd, Hash, Debug, Serialize, Deserialize)]
+pub struct Reference {
+    pub name: String,
+    pub len: u64,
+}
+
+/// The header of a BAM file.
+#[derive(Default, Clone, Eq, PartialEq, Ord, PartialOrd, Hash, Debug, Serialize, Deserialize)]
+pub struct Header {
+    /// The plain SAM header text.
+    pub text: String,
+    /// The reference sequences, indexed by the reference ids of the records.
+    pub references: Vec<Reference>,
+}
+
+impl Header {
+    /// Id of the reference with the given name.
+    pub fn reference_id(&self, name: &str) -> Option<usize> {
+        self.references.iter().position(|r| r.name == name)
+    }
+}
+
+/// A CIGAR operation.
+#[derive(Copy, Clone, Eq, PartialEq, Ord, PartialOrd, Hash, Debug, Serialize, Deserialize)]
+pub struct CigarOp {
+    pub len: u32,
+    /// The operation as SAM symbol, i.e. one of `MIDNSHP=X`.
+    pub op: u8,
+}
+
+impl CigarOp {
+    /// Whether the operation consumes bases of the read.
+    pub fn consumes_query(&self) -> bool {
+        matches!(self.op, b'M' | b'I' | b'S' | b'=' | b'X')
+    }
+
+    /// Whether the operation consumes bases of the reference.
+    pub fn consumes_ref(&self) -> bool {
+        matches!(self.op, b'M' | b'D' | b'N' | b'=' | b'X')
+    }
+}
+
+/// The core fields of a BAM record. Positions are 0-based.
+#[derive(Default, Clone, Eq, PartialEq, Ord, PartialOrd, Hash, Debug, Serialize, Deserialize)]
+pub struct Record {
+    pub name: Vec<u8>,
+    pub flag: u16,
+    /// Reference id (index into the references of the header), `None` if unset.
+    pub ref_id: Option<usize>,
+    /// Leftmost aligned reference position, `None` if unset.
+    pub pos: Option<usize>,
+    /// Mapping quality, 255 if unavailable.
+    pub mapq: u8,
+    pub cigar: Vec<CigarOp>,
+    /// Read sequence as ASCII symbols.
+    pub seq: Vec<u8>,
+    /// PHRED base qualities (without offset), empty if unavailable.
+    pub qual: Vec<u8>,
+    pub next_ref_id: Option<usize>,
+    pub next_pos: Option<usize>,
+    pub template_len: i32,
+    /// Raw auxiliary data.
+    pub aux: Vec<u8>,
+}
+
+impl Record {
+    /// Create a new, empty record.
+    pub fn new() -> Self {
+        Record::default()
+    }
+
+    /// Whether the template has multiple segments (flag 0x1).
+    pub fn is_paired(&self) -> bool {
+        self.flag & 0x1 != 0
+    }
+
+    /// Whether the read is unmapped (flag 0x4).
+    pub fn is_unmapped(&self) -> bool {
+        self.flag & 0x4 != 0
+    }
+
+    /// Whether the read is aligned to the reverse strand (flag 0x10).
+    pub fn is_reverse(&self) -> bool {
+        self.flag & 0x10 != 0
+    }
+
+    /// Whether this is the first segment of the template (flag 0x40).
+    pub fn is_first_in_template(&self) -> bool {
+        self.flag & 0x40 != 0
+    }
+
+    /// Whether this is the last segment of the template (flag 0x80).
+    pub fn is_last_in_template(&self) -> bool {
+        self.flag & 0x80 != 0
+    }
+
+    /// Whether this is a secondary alignment (flag 0x100).
+    pub fn is_secondary(&self) -> bool {
+        self.flag & 0x100 != 0
+    }
+
+    /// Whether the read failed quality checks (flag 0x200).
+    pub fn is_quality_check_failed(&self) -> bool {
+        self.flag & 0x200 != 0
+    }
+
+    /// Whether the read is a PCR or optical duplicate (flag 0x400).
+    pub fn is_duplicate(&self) -> bool {
+        self.flag & 0x400 != 0
+    }
+
+    /// Whether this is a supplementary alignment (flag 0x800).
+    pub fn is_supplementary(&self) -> bool {
+        self.flag & 0x800 != 0
+    }
+
+    /// Number of reference bases covered by the alignment.
+    pub fn ref_len(&self) -> usize {
+        self.cigar
+            .iter()
+            .filter(|op| op.consumes_ref())
+            .map(|op| op.len as usize)
+            .sum()
+    }
+
+    /// Reference position after the last aligned base (exclusive), `None` if unset.
+    pub fn end_pos(&self) -> Option<usize> {
+        self.pos.map(|pos| pos + self.ref_len())
+    }
+
+    /// The CIGAR as string (`*` if empty), e.g. to be converted into an alignment with
+    /// `bio::alignment::sam::alignment_from_cigar`.
+    pub fn cigar_string(&self) -> String {
+        if self.cigar.is_empty() {
+            return "*".to_owned();
+        }
+        self.cigar
+            .iter()
+            .map(|op| format!("{}{}", op.len, op.op as char))
+            .collect()
+    }
+
+    /// Parse a record from the given block (without the leading block size).
+    fn parse(&mut self, block: &[u8]) -> Result<()> {
+        let invalid = |reason: &str| Error::InvalidRecord {
+            reason: reason.to_owned(),
+        };
+        if block.len() < 32 {
+            return Err(invalid("block shorter than fixed-length fields"));
+        }
+        let i32_at = |i: usize| i32::from_le_bytes(block[i..i + 4].try_into().unwrap());
+        let u16_at = |i: usize| u16::from_le_bytes(block[i..i + 2].try_into().unwrap());
+        let unset = |value: i32| usize::try_from(value).ok();
+
+        let name_len = block[8] as usize;
+        let n_cigar = u16_at(12) as usize;
+        let seq_len = u32::from_le_bytes(block[16..20].try_into().unwrap()) as usize;
+        let name_end = 32 + name_len;
+        let cigar_end = name_end + 4 * n_cigar;
+        // the sequence length is untrusted and may overflow on 32-bit platforms
+        let qual_end = cigar_end
+            .checked_add(seq_len / 2 + seq_len % 2)
+            .and_then(|seq_end| seq_end.checked_add(seq_len))
+            .filter(|&qual_end| qual_end <= block.len())
+            .ok_or_else(|| invalid("block shorter than variable-length fields"))?;
+        let seq_end = qual_end - seq_len;
+
+        self.ref_id = unset(i32_at(0));
+        self.pos = unset(i32_at(4));
+        self.mapq = block[9];
+        self.flag = u16_at(14);
+        self.next_ref_id = unset(i32_at(20));
+        self.next_pos = unset(i32_at(24));
+        self.template_len = i32_at(28);
+
+        // the name is NUL-terminated
+        self.name.clear();
+        self.name
+            .extend_from_slice(&block[32..name_end.saturating_sub(1).max(32)]);
+
+        self.cigar.clear();
+        for chunk in block[name_end..cigar_end].chunks_exact(4) {
+            let value = u32::from_le_bytes(chunk.try_into().unwrap());
+            let op = *CIGAR_OPS
+                .get((value & 0xf) as usize)
+                .ok_or_else(|| invalid("invalid CIGAR operation"))?;
+            self.cigar.push(CigarOp {
+                len: value >> 4,
+                op,
+            });
+        }
+
+        self.seq.clear();
+        self.seq.extend(
+            block[cigar_end..seq_end]
+                .iter()
+                .flat_map(|&b| [BASES[(b >> 4) as usize], BASES[(b & 0xf) as usize]])
+                .take(seq_len),
+        );
+
+        self.qual.clear();
+        let qual = &block[seq_end..qual_end];
+        if qual.first() != Some(&0xff) {
+            self.qual.extend_from_slice(qual);
+        }
+
+        self.aux.clear();
+        self.aux.extend_from_slice(&block[qual_end..]);
+
+        Ok(())
+    }
+}
+
+/// Read exactly `buf.len()` bytes, returning `false` if the input ended before the first byte.
+fn read_or_eof<R: io::Read>(reader: &mut R, buf: &mut [u8], context: &'static str) -> Result<bool> {
+    let mut filled = 0;
+    while filled < buf.len() {
+        match reader.read(&mut buf[filled..]) {
+            Ok(0) if filled == 0 => return Ok(false),
+            Ok(0) => return Err(Error::Truncated { context }),
+            Ok(n) => filled += n,
+            Err(e) if e.kind() == io::ErrorKind::Interrupted => (),
+            Err(e) => return Err(e.into()),
+        }
+    }
+    Ok(true)
+}
+
+fn read_exact<R: io::Read>(reader: &mut R, buf: &mut [u8], context: &'static str) -> Result<()> {
+    if buf.is_empty() || read_or_eof(reader, buf, context)? {
+        Ok(())
+    } else {
+        Err(Error::Truncated { context })
+    }
+}
+
+/// Read exactly `len` bytes into `buf`, replacing its content. Memory is only allocated as the
+/// data arrives, such that a corrupt length can't cause a huge allocation.
+fn read_to_vec<R: io::Read>(
+    reader: &mut R,
+    buf: &mut Vec<u8>,
+    len: usize,
+    context: &'static str,
+) -> Result<()> {
+    buf.clear();
+    reader.by_ref().take(len as u64).read_to_end(buf)?;
+    if buf.len() < len {
+        return Err(Error::Truncated { context });
+    }
+    Ok(())
+}
+
+fn read_u32<R: io::Read>(reader: &mut R, context: &'static str) -> Result<u32> {
+    let mut buf = [0; 4];
+    read_exact(reader, &mut buf, context)?;
+    Ok(u32::from_le_bytes(buf))
+}
+
+fn read_string<R: io::Read>(reader: &mut R, len: usize, context: &'static str) -> Result<String> {
+    let mut buf = Vec::new();
+    read_to_vec(reader, &mut buf, len, context)?;
+    // strings may be NUL-terminated or padded
+    if let Some(end) = buf.iter().position(|&b| b == 0) {
+        buf.truncate(end);
+    }
+    String::from_utf8(buf).map_err(|_| Error::InvalidRecord {
+        reason: format!("{} is not valid UTF-8", context),
+    })
+}
+
+/// A BAM reader.
+#[derive(Debug)]
+pub struct Reader<R: io::Read> {
+    reader: R,
+    header: Header,
+    block: Vec<u8>,
+}
+
+impl Reader<io::BufReader<fs::File>> {
+    /// Read from a given file path. The file has to be uncompressed (see module docs).
+    pub fn from_file<P: AsRef<Path> + std::fmt::Debug>(path: P) -> anyhow::Result<Self> {
+        let file = fs::File::open(path.as_ref()).map_err(|e| Error::FileOpen {
+            path: path.as_ref().to_owned(),
+            source: e,
+        });
+        file.and_then(|file| Reader::new(io::BufReader::new(file)))
+            .with_context(|| format!("Failed to read bam from {:#?}", path))
+    }
+}
+
+impl<R: io::Read> Reader<R> {
+    /// Read from a given uncompressed BAM stream, parsing the header.
+    pub fn new(mut reader: R) -> Result<Self> {
+        let mut magic = [0; 4];
+        read_exact(&mut reader, &mut magic, "magic number")?;
+        if &magic != MAGIC {
+            return Err(Error::InvalidMagic);
+        }
+        let text_len = read_u32(&mut reader, "header")? as usize;
+        let text = read_string(&mut reader, text_len, "header")?;
+        let n_refs = read_u32(&mut reader, "header")?;
+        let mut references = Vec::new();
+        for _ in 0..n_refs {
+            let name_len = read_u32(&mut reader, "reference")? as usize;
+            let name = read_string(&mut reader, name_len, "reference")?;
+            let len = read_u32(&mut reader, "reference")? as u64;
+            references.push(Reference { name, len });
+        }
+
+        Ok(Reader {
+            reader,
+            header: Header { text, references },
+            block: Vec::new(),
+        })
+    }
+
+    /// The header of the BAM file.
+    pub fn header(&self) -> &Header {
+        &self.header
+    }
+
+    /// Read the next record into the given one, reusing its buffers.
+    /// Returns `false` if there are no more records.
+    pub fn read(&mut self, record: &mut Record) -> Result<bool> {
+        let mut block_size = [0; 4];
+        if !read_or_eof(&mut self.reader, &mut block_size, "record")? {
+            return Ok(false);
+        }
+        let block_size = u32::from_le_bytes(block_size) as usize;
+        read_to_vec(&mut self.reader, &mut self.block, block_size, "record")?;
+        record.parse(&self.block)?;
+        Ok(true)
+    }
+
+    /// Return an iterator over the records of the BAM file.
+    pub fn records(&mut self) -> Records<'_, R> {
+        Records { reader: self }
+    }
+}
+
+/// An iterator over the records of a BAM file.
+#[derive(Debug)]
+pub struct Records<'a, R: io::Read> {
+    reader: &'a mut Reader<R>,
+}
+
+impl<R: io::Read> Iterator for Records<'_, R> {
+    type Item = Result<Record>;
+
+    fn next(&mut self) -> Option<Result<Record>> {
+        let mut record = Record::new();
+        match self.reader.read(&mut record) {
+            Ok(true) => Some(Ok(record)),
+            Ok(false) => None,
+            Err(err) => Some(Err(err)),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn header() -> Vec<u8> {
+        let text = b"@HD\tVN:1.6\tSO:coordinate\n@SQ\tSN:chr1\tLN:1000\n";
+        let mut data = MAGIC.to_vec();
+        data.extend_from_slice(&(text.len() as u32).to_le_bytes());
+        data.extend_from_slice(text);
+        data.extend_from_slice(&1u32.to_le_bytes());
+        data.extend_from_slice(&5u32.to_le_bytes());
+        data.extend_from_slice(b"chr1\0");
+        data.extend_from_slice(&1000u32.to_le_bytes());
+        data
+    }
+
+    fn encode(record: &Record) -> Vec<u8> {
+        let id = |id: Option<usize>| id.map_or(-1, |id| id as i32);
+        let mut block = Vec::new();
+        block.extend_from_slice(&id(record.ref_id).to_le_bytes());
+        block.extend_from_slice(&id(record.pos).to_le_bytes());
+        block.push(record.name.len() as u8 + 1);
+        block.push(record.mapq);
+        block.extend_from_slice(&0u16.to_le_bytes());
+        block.extend_from_slice(&(record.cigar.len() as u16).to_le_bytes());
+        block.extend_from_slice(&record.flag.to_le_bytes());
+        block.extend_from_slice(&(record.seq.len() as u32).to_le_bytes());
+        block.extend_from_slice(&id(record.next_ref_id).to_le_bytes());
+        block.extend_from_slice(&id(record.next_pos).to_le_bytes());
+        block.extend_from_slice(&record.template_len.to_le_bytes());
+        block.extend_from_slice(&record.name);
+        block.push(0);
+        for op in &record.cigar {
+            let code = CIGAR_OPS.iter().position(|&c| c == op.op).unwrap() as u32;
+            block.extend_from_slice(&(op.len << 4 | code).to_le_bytes());
+        }
+        let code = |b: u8| BASES.iter().position(|&c| c == b).unwrap() as u8;
+        for pair in record.seq.chunks(2) {
+            block.push(code(pair[0]) << 4 | pair.get(1).map_or(0, |&b| code(b)));
+        }
+        if record.qual.is_empty() {
+            block.extend(std::iter::repeat(0xff).take(record.seq.len()));
+        } else {
+            block.extend_from_slice(&record.qual);
+        }
+        block.extend_from_slice(&record.aux);
+
+        let mut data = (block.len() as u32).to_le_bytes().to_vec();
+        data.extend(block);
+        data
+    }
+
+    fn records() -> Vec<Record> {
+        vec![
+            Record {
+                name: b"read1".to_vec(),
+                flag: 0x1 | 0x40 | 0x10,
+                ref_id: Some(0),
+                pos: Some(99),
+                mapq: 60,
+                cigar: vec![
+                    CigarOp { len: 2, op: b'S' },
+                    CigarOp { len: 4, op: b'M' },
+                    CigarOp { len: 1, op: b'D' },
+                    CigarOp { len: 3, op: b'M' },
+                ],
+                seq: b"TTACGTNACG".to_vec(),
+                qual: vec![30; 10],
+                next_ref_id: Some(0),
+                next_pos: Some(250),
+                template_len: 160,
+                aux: b"NMC\x01".to_vec(),
+            },
+            Record {
+                name: b"read2".to_vec(),
+                flag: 0x4,
+                mapq: 255,
+                seq: b"ACG".to_vec(),
+                ..Default::default()
+            },
+        ]
+    }
+
+    #[test]
+    fn test_read() {
+        let mut data = header();
+        for record in records() {
+            data.extend(encode(&record));
+        }
+        let mut reader = Reader::new(&data[..]).unwrap();
+        assert_eq!(reader.header().references[0].len, 1000);
+        assert_eq!(reader.header().reference_id("chr1"), Some(0));
+        assert!(reader.header().text.starts_with("@HD"));
+
+        let read: Vec<Record> = reader.records().map(|r| r.unwrap()).collect();
+        assert_eq!(read, records());
+
+        let first = &read[0];
+        assert!(first.is_paired() && first.is_reverse() && first.is_first_in_template());
+        assert_eq!(first.cigar_string(), "2S4M1D3M");
+        assert_eq!(first.end_pos(), Some(107));
+        assert!(read[1].is_unmapped());
+        assert_eq!(read[1].cigar_string(), "*");
+        assert_eq!(read[1].end_pos(), None);
+    }
+
+    #[test]
+    fn test_invalid_input() {
+        assert!(matches!(
+            Reader::new(&b"BAM\x02"[..]),
+            Err(Error::InvalidMagic)
+        ));
+        assert!(matches!(
+            Reader::new(&header()[..10]),
+            Err(Error::Truncated { .. })
+        ));
+
+        let mut data = header();
+        let record = encode(&records()[0]);
+        data.extend_from_slice(&record[..record.len() - 3]);
+        let mut reader = Reader::new(&data[..]).unwrap();
+        let mut record = Record::new();
+        assert!(matches!(
+            reader.read(&mut record),
+            Err(Error::Truncated { .. })
+        ));
+
+        let mut data = header();
+        data.extend_from_slice(&4u32.to_le_bytes());
+        data.extend_from_slice(&[0; 4]);
+        let mut reader = Reader::new(&data[..]).unwrap();
+        assert!(matches!(
+            reader.read(&mut record),
+            Err(Error::InvalidRecord { .. })
+        ));
+    }
+
+    #[test]
+    fn test_corrupt_lengths() {
+        // a huge header text length must not be allocated up front
+        let mut data = MAGIC.to_vec();
+        data.extend_from_slice(&u32::MAX.to_le_bytes());
+        data.extend_from_slice(b"@HD");
+        assert!(matches!(
+            Reader::new(&data[..]),
+            Err(Error::Truncated { .. })
+        ));
+
+        let mut data = header();
+        data.extend_from_slice(&u32::MAX.to_le_bytes());
+        data.extend_from_slice(&[0; 40]);
+        let mut reader = Reader::new(&data[..]).unwrap();
+        let mut record = Record::new();
+        assert!(matches!(
+            reader.read(&mut record),
+            Err(Error::Truncated { .. })
+        ));
+
+        let mut block = encode(&records()[0]);
+        block[4 + 16..4 + 20].copy_from_slice(&u32::MAX.to_le_bytes());
+        let mut data = header();
+        data.extend(block);
+        let mut reader = Reader::new(&data[..]).unwrap();
+        assert!(matches!(
+            reader.read(&mut record),
+            Err(Error::InvalidRecord { .. })
+        ));
+    }
+}
@@ -138,6 +138,9 @@ use std::io;
 use std::io::prelude::*;
 use std::path::Path;
 
+#[cfg(feature = "async-io")]
+use crate::io::async_io;
+use crate::io::push;
 use crate::utils::{Text, TextSlice};
 use anyhow::Context;
 use std::fmt;
@@ -272,6 +275,54 @@ where
             error_has_occured: false,
         }
     }
+
+    /// Return an iterator over the records of this Fasta file that recovers from
+    /// malformed input instead of stopping at the first error.
+    ///
+    /// Records without a header, an id or a sequence, with non-ASCII sequence
+    /// characters, or with an id that was already seen before are skipped and
+    /// reported via [`LenientRecords::issues`]. Any whitespace (including stray
+    /// carriage returns) inside sequence lines is removed.
+    /// Only I/O errors are returned as `Err`, after which the iteration stops.
+    ///
+    /// # Example
+    /// ```rust
+    /// use bio::io::fasta::{MalformedKind, Reader};
+    ///
+    /// let fasta: &'static [u8] = b"ACGT\n>a\r\nAC GT\r\n>b\n>a\nTTTT\n>c\nGG\n";
+    /// let mut records = Reader::new(fasta).lenient_records();
+    /// let ids: Vec<String> = records
+    ///     .by_ref()
+    ///     .map(|record| record.unwrap().id().to_owned())
+    ///     .collect();
+    /// assert_eq!(ids, ["a", "c"]);
+    ///
+    /// let kinds: Vec<MalformedKind> = records.issues().iter().map(|issue| issue.kind).collect();
+    /// assert_eq!(
+    ///     kinds,
+    ///     [
+    ///         MalformedKind::MissingHeader,
+    ///         MalformedKind::EmptySequence,
+    ///         MalformedKind::DuplicateId
+    ///     ]
+    /// );
+    /// ```
+    pub fn lenient_records(self) -> LenientRecords<B> {
+        let pending = if self.line.is_empty() {
+            None
+        } else {
+            Some(self.line.into_bytes())
+        };
+        LenientRecords {
+            reader: self.reader,
+            line: Vec::new(),
+            pending,
+            line_no: 0,
+            seen_ids: collections::HashSet::new(),
+            issues: Vec::new(),
+            error_has_occured: false,
+        }
+    }
 }
 
 impl<B> FastaRead for Reader<B>
@@ -386,6 +437,126 @@ impl Index {
         Self::from_file(&fai_path)
     }
 
+    /// Build a FASTA index by scanning the given FASTA file, as done by `samtools faidx`.
+    /// Within each record, all sequence lines except the last one must have the same length.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use bio::io::fasta::Index;
+    ///
+    /// const FASTA_FILE: &[u8] = b">chr1\nGTAGGCTGAAAA\nCCCC\n>chr2\nACGT\n";
+    ///
+    /// let index = Index::build(FASTA_FILE).unwrap();
+    /// let mut fai = Vec::new();
+    /// index.write(&mut fai).unwrap();
+    /// assert_eq!(fai, b"chr1\t16\t6\t12\t13\nchr2\t4\t30\t4\t5\n");
+    /// ```
+    pub fn build<R: io::Read>(fasta: R) -> io::Result<Self> {
+        let invalid = |msg: String| io::Error::new(io::ErrorKind::InvalidData, msg);
+        let mut reader = io::BufReader::new(fasta);
+        let mut index = Index::default();
+        let mut line = Vec::new();
+        let mut offset = 0;
+        let mut current: Option<IndexRecord> = None;
+        // whether the last line of the current record has been seen (it may be shorter)
+        let mut last_line = false;
+        loop {
+            line.clear();
+            let line_bytes = reader.read_until(b'\n', &mut line)? as u64;
+            if line_bytes == 0 {
+                break;
+            }
+            offset += line_bytes;
+            if line.starts_with(b">") {
+                if let Some(record) = current.take() {
+                    index.push(record);
+                }
+                let header = String::from_utf8_lossy(&line[1..]);
+                let name = header.split_whitespace().next().unwrap_or("").to_owned();
+                current = Some(IndexRecord {
+                    name,
+                    len: 0,
+                    offset,
+                    line_bases: 0,
+                    line_bytes: 0,
+                });
+                last_line = false;
+                continue;
+            }
+
+            let record = current
+                .as_mut()
+                .ok_or_else(|| invalid("Expected > at record start.".to_owned()))?;
+            let line_bases = line
+                .iter()
+                .rposition(|b| !b.is_ascii_whitespace())
+                .map_or(0, |i| i + 1) as u64;
+            if line_bases == 0 {
+                last_line = true;
+                continue;
+            }
+            if last_line || (record.line_bases > 0 && line_bases > record.line_bases) {
+                return Err(invalid(format!(
+                    "Inconsistent line length in record {}.",
+                    record.name
+                )));
+            }
+            if record.line_bases == 0 {
+                record.line_bases = line_bases;
+                // assume a newline if the file ends without one
+                record.line_bytes = if line.ends_with(b"\n") {
+                    line_bytes
+                } else {
+                    line_bytes + 1
+                };
+            } else if line_bases < record.line_bases {
+                last_line = true;
+            }
+            record.len += line_bases;
+        }
+        if let Some(record) = current {
+            index.push(record);
+        }
+
+        Ok(index)
+    }
+
+    /// Build a FASTA index for the given FASTA file path, and write it next to it.
+    /// That is, for ref.fasta we write ref.fasta.fai.
+    pub fn build_for_fasta_file<P: AsRef<Path> + std::fmt::Debug>(
+        fasta_path: &P,
+    ) -> anyhow::Result<Self> {
+        let index = fs::File::open(fasta_path)
+            .and_then(Self::build)
+            .with_context(|| format!("Failed to build fasta index for {:#?}", fasta_path))?;
+        let mut fai_path = fasta_path.as_ref().as_os_str().to_owned();
+        fai_path.push(".fai");
+        fs::File::create(&fai_path)
+            .and_then(|fai| index.write(io::BufWriter::new(fai)))
+            .with_context(|| format!("Failed to write fasta index to {:#?}", fai_path))?;
+
+        Ok(index)
+    }
+
+    /// Write the index in the format of SAMtools (.fai).
+    pub fn write<W: io::Write>(&self, mut fai: W) -> io::Result<()> {
+        for record in &self.inner {
+            writeln!(
+                fai,
+                "{}\t{}\t{}\t{}\t{}",
+                record.name, record.len, record.offset, record.line_bases, record.line_bytes
+            )?;
+        }
+        fai.flush()
+    }
+
+    fn push(&mut self, record: IndexRecord) {
+        self.name_to_rid
+            .insert(record.name.clone(), self.inner.len());
+        self.inner.push(record);
+    }
+
     /// Return a vector of sequences described in the index.
     pub fn sequences(&self) -> Vec<Sequence> {
         // sort kv pairs by rid to preserve order
@@ -958,6 +1129,29 @@ impl Record {
         self.seq.as_bytes()
     }
 
+    /// Iterate over the sequence of the record, skipping any whitespace (e.g. newlines of
+    /// wrapped records created via `Record::with_attrs` from miscellaneous sources).
+    /// In contrast to copying the sequence, this does not allocate.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use bio::io::fasta::Record;
+    ///
+    /// let record = Record::with_attrs("id", None, b"ACGT\nAC GT\r\nAA");
+    /// let seq: Vec<u8> = record.seq_without_whitespace().collect();
+    /// assert_eq!(seq, b"ACGTACGTAA");
+    /// assert_eq!(record.seq_len(), 10);
+    /// ```
+    pub fn seq_without_whitespace(&self) -> impl Iterator<Item = u8> + '_ {
+        self.seq.bytes().filter(|a| !a.is_ascii_whitespace())
+    }
+
+    /// Return the length of the sequence, not counting whitespace. Does not allocate.
+    pub fn seq_len(&self) -> usize {
+        self.seq_without_whitespace().count()
+    }
+
     /// Clear the record.
     fn clear(&mut self) {
         self.id.clear();
@@ -1042,6 +1236,330 @@ where
     }
 }
 
+/// Kinds of problems detected by [`LenientRecords`].
+#[derive(Copy, Clone, Eq, PartialEq, Hash, Debug, Serialize, Deserialize)]
+pub enum MalformedKind {
+    /// Sequence lines that are not preceded by a header line.
+    MissingHeader,
+    /// A header line without an id.
+    EmptyId,
+    /// A record without any sequence.
+    EmptySequence,
+    /// A record with non-ASCII characters in its sequence.
+    NonAsciiSequence,
+    /// A record with an id that was already seen before.
+    DuplicateId,
+}
+
+/// A malformed part of a Fasta file that was skipped by [`LenientRecords`].
+#[derive(Clone, Eq, PartialEq, Hash, Debug, Serialize, Deserialize)]
+pub struct Malformed {
+    /// 1-based line number of the header of the affected record, or of the first
+    /// line without header.
+    pub line: usize,
+    /// Id of the affected record, if any.
+    pub id: Option<String>,
+    /// Kind of the problem.
+    pub kind: MalformedKind,
+}
+
+/// An iterator over the well-formed records of a Fasta file, see
+/// [`Reader::lenient_records`].
+#[derive(Debug)]
+pub struct LenientRecords<B>
+where
+    B: io::BufRead,
+{
+    reader: B,
+    line: Vec<u8>,
+    pending: Option<Vec<u8>>,
+    line_no: usize,
+    seen_ids: collections::HashSet<String>,
+    issues: Vec<Malformed>,
+    error_has_occured: bool,
+}
+
+impl<B> LenientRecords<B>
+where
+    B: io::BufRead,
+{
+    /// Problems encountered so far, in the order of their occurrence.
+    pub fn issues(&self) -> &[Malformed] {
+        &self.issues
+    }
+
+    /// Consume the iterator, returning the problems encountered so far.
+    pub fn into_issues(self) -> Vec<Malformed> {
+        self.issues
+    }
+
+    /// Read the next line into `self.line`, returning false at the end of the input.
+    fn next_line(&mut self) -> io::Result<bool> {
+        self.line.clear();
+        if let Some(line) = self.pending.take() {
+            self.line = line;
+        } else if self.reader.read_until(b'\n', &mut self.line)? == 0 {
+            return Ok(false);
+        }
+        self.line_no += 1;
+        Ok(true)
+    }
+
+    fn report(&mut self, line: usize, id: Option<&str>, kind: MalformedKind) {
+        self.issues.push(Malformed {
+            line,
+            id: id.map(|id| id.to_owned()),
+            kind,
+        });
+    }
+
+    fn read_record(&mut self) -> io::Result<Option<Record>> {
+        loop {
+            // find the next header line
+            let mut orphan_line = None;
+            while !self.line.starts_with(b">") {
+                if !self.next_line()? {
+                    return Ok(None);
+                }
+                if orphan_line.is_none()
+                    && !self.line.starts_with(b">")
+                    && !self.line.iter().all(u8::is_ascii_whitespace)
+                {
+                    orphan_line = Some(self.line_no);
+                }
+            }
+            if let Some(line) = orphan_line {
+                self.report(line, None, MalformedKind::MissingHeader);
+            }
+
+            let header_line = self.line_no;
+            let header = String::from_utf8_lossy(&self.line[1..]).into_owned();
+            let mut header_fields = header.trim().splitn(2, char::is_whitespace);
+            let mut record = Record::new();
+            record.id = header_fields.next().unwrap_or_default().to_owned();
+            record.desc = header_fields
+                .next()
+                .map(|desc| desc.trim_start().to_owned());
+
+            let mut seq = Vec::new();
+            loop {
+                if !self.next_line()? {
+                    self.line.clear();
+                    break;
+                }
+                if self.line.starts_with(b">") {
+                    break;
+                }
+                seq.extend(self.line.iter().filter(|c| !c.is_ascii_whitespace()));
+            }
+
+            let kind = if record.id.is_empty() {
+                Some(MalformedKind::EmptyId)
+            } else if seq.is_empty() {
+                Some(MalformedKind::EmptySequence)
+            } else if !seq.is_ascii() {
+                Some(MalformedKind::NonAsciiSequence)
+            } else if self.seen_ids.contains(&record.id) {
+                Some(MalformedKind::DuplicateId)
+            } else {
+                None
+            };
+            match kind {
+                Some(kind) => {
+                    let id = Some(record.id.as_str()).filter(|id| !id.is_empty());
+                    self.report(header_line, id, kind);
+                }
+                None => {
+                    self.seen_ids.insert(record.id.clone());
+                    // ASCII has been checked above
+                    record.seq = String::from_utf8(seq).unwrap();
+                    return Ok(Some(record));
+                }
+            }
+            if self.line.is_empty() {
+                return Ok(None);
+            }
+        }
+    }
+}
+
+impl<B> Iterator for LenientRecords<B>
+where
+    B: io::BufRead,
+{
+    type Item = io::Result<Record>;
+
+    fn next(&mut self) -> Option<io::Result<Record>> {
+        if self.error_has_occured {
+            return None;
+        }
+        match self.read_record() {
+            Ok(record) => record.map(Ok),
+            Err(err) => {
+                self.error_has_occured = true;
+                Some(Err(err))
+            }
+        }
+    }
+}
+
+/// A push-based FASTA reader, which is fed with chunks of input (of arbitrary size) and yields
+/// records once they are complete. Since it never performs I/O itself, it can parse input
+/// from any source without blocking, e.g. data received by an async runtime or a web server.
+/// Records are parsed like by `Reader::records`.
+///
+/// # Example
+///
+/// ```
+/// use bio::io::fasta::PushReader;
+///
+/// let mut reader = PushReader::new();
+/// reader.push(b">a\nACGT\nAC");
+/// // the second line of the record might continue
+/// assert!(reader.next_record().is_none());
+/// reader.push(b"\n>b\nGG");
+/// assert_eq!(reader.next_record().unwrap().unwrap().seq(), b"ACGTAC");
+/// assert!(reader.next_record().is_none());
+/// // the last record is complete at the end of the input
+/// reader.finish();
+/// assert_eq!(reader.next_record().unwrap().unwrap().id(), "b");
+/// assert!(reader.next_record().is_none());
+/// ```
+#[derive(Default, Clone, Debug)]
+pub struct PushReader {
+    buffer: push::Buffer,
+}
+
+impl PushReader {
+    /// Create a new reader without any input.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Append a chunk of input.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the input was already finished.
+    pub fn push(&mut self, chunk: &[u8]) {
+        self.buffer.push(chunk);
+    }
+
+    /// Signal the end of the input, such that the last record can be completed.
+    pub fn finish(&mut self) {
+        self.buffer.finish();
+    }
+
+    /// Return the next complete record, or `None` if more input is needed (or if all input
+    /// has been consumed after `finish`).
+    pub fn next_record(&mut self) -> Option<io::Result<Record>> {
+        let lines = self.buffer.lines();
+        // the first line is the header, and lines scanned before do not start a record
+        let mut end = self.buffer.scanned().max(1);
+        while end < lines && self.buffer.pending()[self.buffer.line(end).start] != b'>' {
+            end += 1;
+        }
+        if end >= lines && !self.buffer.is_finished() {
+            self.buffer.set_scanned(end);
+            return None;
+        }
+        let end = end.min(lines);
+        if end == 0 {
+            return None;
+        }
+        Reader::new(self.buffer.consume_lines(end)).records().next()
+    }
+}
+
+/// An asynchronous FASTA reader, available with the feature `async-io`. Input is read without
+/// blocking from any `async_io::AsyncRead` (see `async_io` for how to plug in the readers of
+/// async runtimes), and records are yielded as an `async_io::Stream` as soon as they are
+/// complete. Records are parsed like by `Reader::records`.
+///
+/// # Example
+///
+/// ```
+/// use bio::io::fasta::AsyncReader;
+/// # use std::future::Future;
+/// # use std::sync::Arc;
+/// # use std::task::{Context, Poll, Wake, Waker};
+///
+/// async fn count_bases(input: &[u8]) -> std::io::Result<usize> {
+///     let mut reader = AsyncReader::new(input);
+///     let mut bases = 0;
+///     while let Some(record) = reader.next_record().await {
+///         bases += record?.seq().len();
+///     }
+///     Ok(bases)
+/// }
+///
+/// # // reading from a slice never waits, such that polling once suffices
+/// # struct Noop;
+/// # impl Wake for Noop {
+/// #     fn wake(self: Arc<Self>) {}
+/// # }
+/// # let waker = Waker::from(Arc::new(Noop));
+/// # let mut bases = Box::pin(count_bases(b">a\nACGT\nAC\n>b\nGG\n"));
+/// # match bases.as_mut().poll(&mut Context::from_waker(&waker)) {
+/// #     Poll::Ready(bases) => assert_eq!(bases.unwrap(), 8),
+/// #     Poll::Pending => unreachable!(),
+/// # }
+/// ```
+#[cfg(feature = "async-io")]
+#[derive(Debug)]
+pub struct AsyncReader<R> {
+    source: async_io::Source<R>,
+    records: PushReader,
+    finished: bool,
+}
+
+#[cfg(feature = "async-io")]
+impl<R: async_io::AsyncRead + Unpin> AsyncReader<R> {
+    /// Create a new reader for the given input.
+    pub fn new(reader: R) -> Self {
+        AsyncReader {
+            source: async_io::Source::new(reader),
+            records: PushReader::new(),
+            finished: false,
+        }
+    }
+
+    /// Return the next record, or `None` at the end of the input.
+    pub async fn next_record(&mut self) -> Option<io::Result<Record>> {
+        std::future::poll_fn(|cx| async_io::Stream::poll_next(std::pin::Pin::new(&mut *self), cx))
+            .await
+    }
+}
+
+#[cfg(feature = "async-io")]
+impl<R: async_io::AsyncRead + Unpin> async_io::Stream for AsyncReader<R> {
+    type Item = io::Result<Record>;
+
+    fn poll_next(
+        self: std::pin::Pin<&mut Self>,
+        cx: &mut std::task::Context<'_>,
+    ) -> std::task::Poll<Option<Self::Item>> {
+        let this = self.get_mut();
+        loop {
+            if let Some(record) = this.records.next_record() {
+                return std::task::Poll::Ready(Some(record));
+            }
+            if this.finished {
+                return std::task::Poll::Ready(None);
+            }
+            match this.source.poll_chunk(cx) {
+                std::task::Poll::Ready(Ok([])) => {
+                    this.records.finish();
+                    this.finished = true;
+                }
+                std::task::Poll::Ready(Ok(chunk)) => this.records.push(chunk),
+                std::task::Poll::Ready(Err(e)) => return std::task::Poll::Ready(Some(Err(e))),
+                std::task::Poll::Pending => return std::task::Poll::Pending,
+            }
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -1214,6 +1732,51 @@ ATTGTTGTTTTA
         );
     }
 
+    #[test]
+    fn test_lenient_records() {
+        let fasta = b">\nAC\n\nACGT\n>x desc\r\nAC\r\nG T\r\n>y\nA\xE2\x98\xB9\n>x\nAA\n>z\nCC";
+        let mut records = Reader::new(&fasta[..]).lenient_records();
+        let records_read: Vec<Record> = records.by_ref().map(|r| r.unwrap()).collect();
+        assert_eq!(
+            records_read,
+            [
+                Record::with_attrs("x", Some("desc"), b"ACGT"),
+                Record::with_attrs("z", None, b"CC")
+            ]
+        );
+        assert_eq!(
+            records.into_issues(),
+            [
+                Malformed {
+                    line: 1,
+                    id: None,
+                    kind: MalformedKind::EmptyId
+                },
+                Malformed {
+                    line: 8,
+                    id: Some("y".to_owned()),
+                    kind: MalformedKind::NonAsciiSequence
+                },
+                Malformed {
+                    line: 10,
+                    id: Some("x".to_owned()),
+                    kind: MalformedKind::DuplicateId
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_lenient_records_read_fails() {
+        let reader = Reader::new(ReaderMock {
+            seek_fails: false,
+            read_fails: true,
+        });
+        let mut records = reader.lenient_records();
+        assert!(records.next().unwrap().is_err());
+        assert!(records.next().is_none());
+    }
+
     #[test]
     fn test_reader_read_fails() {
         let mut reader = Reader::new(ReaderMock {
@@ -1272,6 +1835,17 @@ ATTGTTGTTTTA
         assert_eq!(record.seq(), b"ATGCGGG");
     }
 
+    #[test]
+    fn test_record_seq_without_whitespace() {
+        let record = Record::with_attrs("id_str", None, b"ATG\nCG\tG G\n");
+        assert_eq!(
+            record.seq_without_whitespace().collect::<Vec<u8>>(),
+            b"ATGCGGG"
+        );
+        assert_eq!(record.seq_len(), 7);
+        assert_eq!(Record::new().seq_len(), 0);
+    }
+
     #[test]
     fn test_index_sequences() {
         let reader = IndexedReader::new(io::Cursor::new(FASTA_FILE), FAI_FILE).unwrap();
@@ -1790,4 +2364,109 @@ ATTGTTGTTTTA
         assert!(fs::remove_file(path).is_ok());
         assert_eq!(actual, expected)
     }
+
+    #[test]
+    fn test_index_build() {
+        for (fasta, fai) in [
+            (FASTA_FILE, FAI_FILE),
+            (FASTA_FILE_CRLF, FAI_FILE_CRLF),
+            (FASTA_FILE_NO_TRAILING_LF, FAI_FILE_NO_TRAILING_LF),
+        ] {
+            assert_eq!(Index::build(fasta).unwrap(), Index::new(fai).unwrap());
+        }
+
+        let mut written = Vec::new();
+        Index::build(FASTA_FILE)
+            .unwrap()
+            .write(&mut written)
+            .unwrap();
+        assert_eq!(written, FAI_FILE);
+
+        let mut reader = IndexedReader::with_index(
+            io::Cursor::new(FASTA_FILE),
+            Index::build(FASTA_FILE).unwrap(),
+        );
+        let mut seq = Vec::new();
+        reader.fetch("id2", 10, 15).unwrap();
+        reader.read(&mut seq).unwrap();
+        assert_eq!(seq, b"TAATT");
+    }
+
+    #[test]
+    fn test_index_build_inconsistent_line_length_raises_err() {
+        for fasta in [
+            &b">id\nACGT\nAC\nACGT\n"[..],
+            b">id\nACGT\nACGTA\n",
+            b">id\nACGT\n\nACGT\n",
+            b"ACGT\n",
+        ] {
+            let err = Index::build(fasta).unwrap_err();
+            assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+        }
+        // trailing empty lines are allowed
+        assert!(Index::build(&b">id\nACGT\nAC\n\n>id2\nA\n"[..]).is_ok());
+    }
+
+    #[test]
+    fn test_index_build_for_fasta_file() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("ref.fa");
+        fs::write(&path, FASTA_FILE).unwrap();
+
+        let index = Index::build_for_fasta_file(&path).unwrap();
+        assert_eq!(index, Index::with_fasta_file(&path).unwrap());
+        assert!(IndexedReader::from_file(&path).is_ok());
+        assert!(Index::build_for_fasta_file(&dir.path().join("missing.fa")).is_err());
+    }
+
+    #[test]
+    fn test_push_reader() {
+        let input = b">a desc\nACGT\nTT\n\n>b\nGGG\n>c\n>d\nAC";
+        let expected = Reader::new(&input[..])
+            .records()
+            .map(|record| record.unwrap())
+            .collect::<Vec<_>>();
+        for chunk_len in 1..input.len() + 1 {
+            let mut reader = PushReader::new();
+            let mut records = Vec::new();
+            for chunk in input.chunks(chunk_len) {
+                reader.push(chunk);
+                while let Some(record) = reader.next_record() {
+                    records.push(record.unwrap());
+                }
+            }
+            reader.finish();
+            while let Some(record) = reader.next_record() {
+                records.push(record.unwrap());
+            }
+            assert_eq!(records, expected);
+        }
+    }
+
+    #[cfg(feature = "async-io")]
+    #[test]
+    fn test_async_reader() {
+        use crate::io::async_io::tests::{block_on, Trickle};
+
+        let input = b">a desc\nACGT\nTT\n\n>b\nGGG\n>c\n>d\nAC";
+        let expected = Reader::new(&input[..])
+            .records()
+            .map(|record| record.unwrap())
+            .collect::<Vec<_>>();
+        for chunk_len in 1..input.len() + 1 {
+            let mut reader = AsyncReader::new(Trickle {
+                data: input,
+                chunk_len,
+                ready: false,
+            });
+            let records = block_on(async {
+                let mut records = Vec::new();
+                while let Some(record) = reader.next_record().await {
+                    records.push(record.unwrap());
+                }
+                records
+            });
+            assert_eq!(records, expected);
+        }
+    }
 }
@@ -128,6 +128,9 @@ pub type Result<T, E = Error> = std::result::Result<T, E>;
 
 use bio_types::sequence::SequenceRead;
 
+#[cfg(feature = "async-io")]
+use crate::io::async_io;
+use crate::io::push;
 use crate::utils::TextSlice;
 
 /// Trait for FastQ readers.
@@ -415,6 +418,30 @@ impl Record {
         self.qual.trim_end().as_bytes()
     }
 
+    /// Return the decoded PHRED base qualities of the record, assuming the
+    /// Sanger/Illumina 1.8+ encoding (offset 33).
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use bio::io::fastq::Record;
+    ///
+    /// let record = Record::with_attrs("read", None, b"ACGT", b"!+5I");
+    /// assert_eq!(record.phred_qual(), [0, 10, 20, 40]);
+    /// ```
+    pub fn phred_qual(&self) -> Vec<u8> {
+        self.phred_qual_with_offset(33)
+    }
+
+    /// Return the decoded PHRED base qualities of the record, given the ASCII offset of
+    /// the encoding (e.g. 64 for Illumina 1.3+). Qualities below the offset are decoded as 0.
+    pub fn phred_qual_with_offset(&self, offset: u8) -> Vec<u8> {
+        self.qual()
+            .iter()
+            .map(|q| q.saturating_sub(offset))
+            .collect()
+    }
+
     /// Clear the record.
     fn clear(&mut self) {
         self.id.clear();
@@ -553,6 +580,8 @@ impl<W: io::Write> Writer<W> {
     }
 
     /// Write a FastQ record with given id, optional description, sequence and qualities.
+    /// Returns an error of kind `io::ErrorKind::InvalidInput` if sequence and qualities
+    /// differ in length.
     pub fn write(
         &mut self,
         id: &str,
@@ -560,6 +589,12 @@ impl<W: io::Write> Writer<W> {
         seq: TextSlice<'_>,
         qual: &[u8],
     ) -> io::Result<()> {
+        if seq.len() != qual.len() {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "Unequal length of sequence and qualities.",
+            ));
+        }
         self.writer.write_all(b"@")?;
         self.writer.write_all(id.as_bytes())?;
         if let Some(desc) = desc {
@@ -581,6 +616,185 @@ impl<W: io::Write> Writer<W> {
     }
 }
 
+/// A push-based FastQ reader, which is fed with chunks of input (of arbitrary size) and yields
+/// records once they are complete. Since it never performs I/O itself, it can parse input
+/// from any source without blocking, e.g. data received by an async runtime or a web server.
+/// Records are parsed like by `Reader::records`, including wrapped records.
+///
+/// # Example
+///
+/// ```
+/// use bio::io::fastq::PushReader;
+///
+/// let mut reader = PushReader::new();
+/// reader.push(b"@a\nACGT\n+\nII");
+/// assert!(reader.next_record().is_none());
+/// reader.push(b"II\n@b\nGG\n+\nII");
+/// assert_eq!(reader.next_record().unwrap().unwrap().qual(), b"IIII");
+/// assert!(reader.next_record().is_none());
+/// reader.finish();
+/// assert_eq!(reader.next_record().unwrap().unwrap().id(), "b");
+/// assert!(reader.next_record().is_none());
+/// ```
+#[derive(Default, Clone, Debug)]
+pub struct PushReader {
+    buffer: push::Buffer,
+}
+
+impl PushReader {
+    /// Create a new reader without any input.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Append a chunk of input.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the input was already finished.
+    pub fn push(&mut self, chunk: &[u8]) {
+        self.buffer.push(chunk);
+    }
+
+    /// Signal the end of the input, such that the last record can be completed.
+    pub fn finish(&mut self) {
+        self.buffer.finish();
+    }
+
+    /// Number of lines of the first complete record of the pending input, if any.
+    fn record_lines(&mut self) -> Option<usize> {
+        let lines = self.buffer.lines();
+        let buffer = &self.buffer;
+        // the header is followed by sequence lines until the separator, resuming the search
+        // where it stopped before
+        let separator = (buffer.scanned().max(1)..lines)
+            .find(|&i| buffer.pending()[buffer.line(i).start] == b'+');
+        let separator = match separator {
+            Some(separator) => separator,
+            None => {
+                self.buffer.set_scanned(lines);
+                return None;
+            }
+        };
+        self.buffer.set_scanned(separator);
+        // and as many quality lines as sequence lines
+        let end = 2 * separator;
+        if end <= lines {
+            Some(end)
+        } else {
+            None
+        }
+    }
+
+    /// Return the next complete record, or `None` if more input is needed (or if all input
+    /// has been consumed after `finish`). Incomplete records at the end of the input yield an
+    /// error.
+    pub fn next_record(&mut self) -> Option<Result<Record>> {
+        let lines = match self.record_lines() {
+            Some(lines) => lines,
+            None if self.buffer.is_finished() => self.buffer.lines(),
+            None => return None,
+        };
+        if lines == 0 {
+            return None;
+        }
+        Reader::new(self.buffer.consume_lines(lines))
+            .records()
+            .next()
+    }
+}
+
+/// An asynchronous FASTQ reader, available with the feature `async-io`. Input is read without
+/// blocking from any `async_io::AsyncRead` (see `async_io` for how to plug in the readers of
+/// async runtimes), and records are yielded as an `async_io::Stream` as soon as they are
+/// complete. Records are parsed like by `Reader::records`.
+///
+/// # Example
+///
+/// ```
+/// use bio::io::fastq::AsyncReader;
+/// # use std::future::Future;
+/// # use std::sync::Arc;
+/// # use std::task::{Context, Poll, Wake, Waker};
+///
+/// async fn count_bases(input: &[u8]) -> bio::io::fastq::Result<usize> {
+///     let mut reader = AsyncReader::new(input);
+///     let mut bases = 0;
+///     while let Some(record) = reader.next_record().await {
+///         bases += record?.seq().len();
+///     }
+///     Ok(bases)
+/// }
+///
+/// # // reading from a slice never waits, such that polling once suffices
+/// # struct Noop;
+/// # impl Wake for Noop {
+/// #     fn wake(self: Arc<Self>) {}
+/// # }
+/// # let waker = Waker::from(Arc::new(Noop));
+/// # let mut bases = Box::pin(count_bases(b"@a\nACGT\n+\nIIII\n@b\nGG\n+\nII\n"));
+/// # match bases.as_mut().poll(&mut Context::from_waker(&waker)) {
+/// #     Poll::Ready(bases) => assert_eq!(bases.unwrap(), 6),
+/// #     Poll::Pending => unreachable!(),
+/// # }
+/// ```
+#[cfg(feature = "async-io")]
+#[derive(Debug)]
+pub struct AsyncReader<R> {
+    source: async_io::Source<R>,
+    records: PushReader,
+    finished: bool,
+}
+
+#[cfg(feature = "async-io")]
+impl<R: async_io::AsyncRead + Unpin> AsyncReader<R> {
+    /// Create a new reader for the given input.
+    pub fn new(reader: R) -> Self {
+        AsyncReader {
+            source: async_io::Source::new(reader),
+            records: PushReader::new(),
+            finished: false,
+        }
+    }
+
+    /// Return the next record, or `None` at the end of the input.
+    pub async fn next_record(&mut self) -> Option<Result<Record>> {
+        std::future::poll_fn(|cx| async_io::Stream::poll_next(std::pin::Pin::new(&mut *self), cx))
+            .await
+    }
+}
+
+#[cfg(feature = "async-io")]
+impl<R: async_io::AsyncRead + Unpin> async_io::Stream for AsyncReader<R> {
+    type Item = Result<Record>;
+
+    fn poll_next(
+        self: std::pin::Pin<&mut Self>,
+        cx: &mut std::task::Context<'_>,
+    ) -> std::task::Poll<Option<Self::Item>> {
+        let this = self.get_mut();
+        loop {
+            if let Some(record) = this.records.next_record() {
+                return std::task::Poll::Ready(Some(record));
+            }
+            if this.finished {
+                return std::task::Poll::Ready(None);
+            }
+            match this.source.poll_chunk(cx) {
+                std::task::Poll::Ready(Ok([])) => {
+                    this.records.finish();
+                    this.finished = true;
+                }
+                std::task::Poll::Ready(Ok(chunk)) => this.records.push(chunk),
+                std::task::Poll::Ready(Err(e)) => {
+                    return std::task::Poll::Ready(Some(Err(e.into())))
+                }
+                std::task::Poll::Pending => return std::task::Poll::Pending,
+            }
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -977,4 +1191,89 @@ IIIIIIJJJJJJ
         assert!(fs::remove_file(path).is_ok());
         assert_eq!(actual, expected)
     }
+
+    #[test]
+    fn test_write_unequal_lengths_returns_err() {
+        let mut writer = Writer::new(Vec::new());
+        let err = writer.write("id", None, b"ACGT", b"!!!").unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::InvalidInput);
+        assert!(writer.write("id", None, b"ACGT", b"!!!!").is_ok());
+    }
+
+    #[test]
+    fn test_phred_qual() {
+        let record = Record::with_attrs("id", None, b"ACGT", b"@J^h");
+        assert_eq!(record.phred_qual(), [31, 41, 61, 71]);
+        assert_eq!(record.phred_qual_with_offset(64), [0, 10, 30, 40]);
+    }
+
+    #[test]
+    fn test_push_reader() {
+        let input = b"@a desc\nACGT\nTT\n+\nIIII\nII\n@b\nGGG\n+\n!!!\n@c\nA\n+\nI";
+        let expected = Reader::new(&input[..])
+            .records()
+            .map(|record| record.unwrap())
+            .collect::<Vec<_>>();
+        for chunk_len in 1..input.len() + 1 {
+            let mut reader = PushReader::new();
+            let mut records = Vec::new();
+            for chunk in input.chunks(chunk_len) {
+                reader.push(chunk);
+                while let Some(record) = reader.next_record() {
+                    records.push(record.unwrap());
+                }
+            }
+            reader.finish();
+            while let Some(record) = reader.next_record() {
+                records.push(record.unwrap());
+            }
+            assert_eq!(records, expected);
+        }
+    }
+
+    #[cfg(feature = "async-io")]
+    #[test]
+    fn test_async_reader() {
+        use crate::io::async_io::tests::{block_on, Trickle};
+
+        let input = b"@a desc\nACGT\nTT\n+\nIIII\nII\n@b\nGGG\n+\n!!!\n@c\nA\n+\nI";
+        let expected = Reader::new(&input[..])
+            .records()
+            .map(|record| record.unwrap())
+            .collect::<Vec<_>>();
+        for chunk_len in 1..input.len() + 1 {
+            let mut reader = AsyncReader::new(Trickle {
+                data: input,
+                chunk_len,
+                ready: false,
+            });
+            let records = block_on(async {
+                let mut records = Vec::new();
+                while let Some(record) = reader.next_record().await {
+                    records.push(record.unwrap());
+                }
+                records
+            });
+            assert_eq!(records, expected);
+        }
+    }
+
+    #[cfg(feature = "async-io")]
+    #[test]
+    fn test_async_reader_incomplete() {
+        let mut reader = AsyncReader::new(&b"@a\nACGT\n+\n"[..]);
+        crate::io::async_io::tests::block_on(async {
+            assert!(reader.next_record().await.unwrap().is_err());
+            assert!(reader.next_record().await.is_none());
+        });
+    }
+
+    #[test]
+    fn test_push_reader_incomplete() {
+        let mut reader = PushReader::new();
+        reader.push(b"@a\nACGT\n+\n");
+        assert!(reader.next_record().is_none());
+        reader.finish();
+        assert!(reader.next_record().unwrap().is_err());
+    }
 }
@@ -5,6 +5,10 @@
 
 //! [GFF3] format reading and writing. [GFF2] is currently not supported.
 //!
+//! Attribute values are escaped according to the format: in GFF3, reserved characters are
+//! percent-encoded (and decoded when reading), while in GFF2 and GTF2 textual values are
+//! enclosed in double quotes.
+//!
 //! [GFF2]: http://gmod.org/wiki/GFF2 (not supported)
 //! [GTF2]: http://mblab.wustl.edu/GTF2.html (not supported)
 //! [GFF3]: http://gmod.org/wiki/GFF3#GFF3_Format
@@ -34,7 +38,7 @@ use std::io;
 use std::path::Path;
 use std::str::FromStr;
 
-use bio_types::strand::Strand;
+use bio_types::strand::{Strand, StrandError};
 use serde::{Deserialize, Deserializer, Serialize};
 
 /// `GffType`
@@ -137,6 +141,7 @@ impl<R: io::Read> Reader<R> {
             inner: self.inner.deserialize(),
             attribute_re,
             value_delim: vdelim as char,
+            percent_decode: self.gff_type == GffType::GFF3,
         }
     }
 }
@@ -232,6 +237,7 @@ pub struct Records<'a, R: io::Read> {
     inner: csv::DeserializeRecordsIter<'a, R, GffRecordInner>,
     attribute_re: Regex,
     value_delim: char,
+    percent_decode: bool,
 }
 
 impl<'a, R: io::Read> Iterator for Records<'a, R> {
@@ -251,11 +257,18 @@ impl<'a, R: io::Read> Iterator for Records<'a, R> {
                     phase,
                     raw_attributes,
                 )| {
-                    let trim_quotes = |s: &str| s.trim_matches('\'').trim_matches('"').to_owned();
+                    let unescape = |s: &str| {
+                        let s = s.trim_matches('\'').trim_matches('"');
+                        if self.percent_decode {
+                            percent_decode(s)
+                        } else {
+                            s.to_owned()
+                        }
+                    };
                     let mut attributes = MultiMap::new();
                     for caps in self.attribute_re.captures_iter(&raw_attributes) {
                         for value in caps["value"].split(self.value_delim) {
-                            attributes.insert(trim_quotes(&caps["key"]), trim_quotes(value));
+                            attributes.insert(unescape(&caps["key"]), unescape(value));
                         }
                     }
                     Record {
@@ -279,6 +292,7 @@ impl<'a, R: io::Read> Iterator for Records<'a, R> {
 #[derive(Debug)]
 pub struct Writer<W: io::Write> {
     inner: csv::Writer<W>,
+    gff_type: GffType,
     delimiter: char,
     terminator: String,
 }
@@ -300,23 +314,44 @@ impl<W: io::Write> Writer<W> {
             inner: csv::WriterBuilder::new()
                 .delimiter(b'\t')
                 .flexible(true)
+                .quote_style(csv::QuoteStyle::Never)
                 .from_writer(writer),
+            gff_type: fileformat,
             delimiter: delim as char,
             terminator: String::from_utf8(vec![termi]).unwrap(),
         }
     }
 
-    /// Write a given GFF record.
+    /// Write a given GFF record. In GFF3, multiple values of an attribute are written as a
+    /// comma separated list, while in GFF2 and GTF2 the attribute is repeated for each value.
     pub fn write(&mut self, record: &Record) -> csv::Result<()> {
-        let attributes = if !record.attributes.is_empty() {
-            record
-                .attributes
-                .iter()
-                .map(|(a, b)| format!("{}{}{}", a, self.delimiter, b))
-                .join(&self.terminator)
-        } else {
-            "".to_owned()
-        };
+        let (_, _, value_delim) = self.gff_type.separator();
+        let attributes = record
+            .attributes
+            .iter_all()
+            .map(|(key, values)| match self.gff_type {
+                GffType::GFF3 => format!(
+                    "{}{}{}",
+                    percent_encode(key),
+                    self.delimiter,
+                    values.iter().map(|v| percent_encode(v)).join(",")
+                ),
+                GffType::GFF2 | GffType::GTF2 => values
+                    .iter()
+                    .map(|value| format!("{}{}{}", key, self.delimiter, quote(value)))
+                    .join(&self.terminator),
+                GffType::Any(..) if value_delim != 0 => format!(
+                    "{}{}{}",
+                    key,
+                    self.delimiter,
+                    values.iter().join(&(value_delim as char).to_string())
+                ),
+                GffType::Any(..) => values
+                    .iter()
+                    .map(|value| format!("{}{}{}", key, self.delimiter, value))
+                    .join(&self.terminator),
+            })
+            .join(&self.terminator);
 
         self.inner.serialize((
             &record.seqname,
@@ -332,6 +367,58 @@ impl<W: io::Write> Writer<W> {
     }
 }
 
+/// Characters that have to be percent-encoded in GFF3 attribute keys and values.
+fn is_gff3_reserved(c: char) -> bool {
+    matches!(c, ';' | '=' | '&' | ',' | '%' | '\t' | '\n' | '\r') || c.is_ascii_control()
+}
+
+/// Percent-encode reserved characters of GFF3 attributes.
+fn percent_encode(s: &str) -> String {
+    let mut encoded = String::with_capacity(s.len());
+    for c in s.chars() {
+        if is_gff3_reserved(c) {
+            encoded.push_str(&format!("%{:02X}", c as u32));
+        } else {
+            encoded.push(c);
+        }
+    }
+    encoded
+}
+
+/// Decode percent-encoded characters of GFF3 attributes. Invalid escapes are kept as is.
+fn percent_decode(s: &str) -> String {
+    let bytes = s.as_bytes();
+    let mut decoded = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        let escaped = bytes.get(i + 1..i + 3).and_then(|hex| {
+            std::str::from_utf8(hex)
+                .ok()
+                .and_then(|hex| u8::from_str_radix(hex, 16).ok())
+        });
+        match escaped {
+            Some(b) if bytes[i] == b'%' => {
+                decoded.push(b);
+                i += 3;
+            }
+            _ => {
+                decoded.push(bytes[i]);
+                i += 1;
+            }
+        }
+    }
+    String::from_utf8_lossy(&decoded).into_owned()
+}
+
+/// Quote a GFF2/GTF2 attribute value unless it is numeric.
+fn quote(value: &str) -> String {
+    if !value.is_empty() && value.parse::<f64>().is_ok() {
+        value.to_owned()
+    } else {
+        format!("\"{}\"", value)
+    }
+}
+
 /// A GFF record
 #[derive(Default, Clone, Eq, PartialEq, Debug, Serialize, Deserialize)]
 pub struct Record {
@@ -404,6 +491,36 @@ impl Record {
         }
     }
 
+    /// Strand of the feature, with `.` and `?` denoting an unknown strand (see
+    /// `Strand::from_char`). In contrast to `Record::strand`, invalid strand symbols yield an
+    /// error.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use bio::io::gff::Record;
+    /// use bio_types::strand::Strand;
+    ///
+    /// let mut record = Record::new();
+    /// assert!(record.parse_strand().unwrap().is_unknown());
+    /// record.set_strand(Strand::Reverse);
+    /// assert_eq!(record.parse_strand().unwrap(), Strand::Reverse);
+    /// *record.strand_mut() = "x".to_owned();
+    /// assert!(record.parse_strand().is_err());
+    /// ```
+    pub fn parse_strand(&self) -> Result<Strand, StrandError> {
+        let mut symbols = self.strand.chars();
+        match (symbols.next(), symbols.next()) {
+            (Some(symbol), None) => Strand::from_char(&symbol),
+            _ => Err(StrandError::ParseError),
+        }
+    }
+
+    /// Set the strand of the feature.
+    pub fn set_strand(&mut self, strand: Strand) {
+        self.strand = strand.strand_symbol().to_owned();
+    }
+
     /// Phase of the feature. The phase is one of the integers 0, 1, or 2, indicating the number of bases that should be removed from the beginning of this feature to reach the first base of the next codon. `None` if not applicable (`"."` in GFF file).
     pub fn phase(&self) -> &Phase {
         &self.phase
@@ -505,6 +622,11 @@ transcript_support_level \"1\"; tag \"basic\"; tag \"appris_principal_1\";";
     const GTF_FILE_ONE_ATTRIB: &[u8] =
         b"P0A7B8\tUniProtKB\tInitiator methionine\t1\t1\t.\t.\t.\tNote Removed
 P0A7B8\tUniProtKB\tChain\t2\t176\t50\t+\t.\tID PRO_0000148105
+";
+
+    const GTF_FILE_ONE_ATTRIB_QUOTED: &[u8] =
+        b"P0A7B8\tUniProtKB\tInitiator methionine\t1\t1\t.\t.\t.\tNote \"Removed\"
+P0A7B8\tUniProtKB\tChain\t2\t176\t50\t+\t.\tID \"PRO_0000148105\"
 ";
 
     #[test]
@@ -688,7 +810,10 @@ P0A7B8\tUniProtKB\tChain\t2\t176\t50\t+\t.\tID PRO_0000148105
                 .write(&r.expect("Error reading record"))
                 .expect("Error writing record");
         }
-        assert_eq!(writer.inner.into_inner().unwrap(), GTF_FILE_ONE_ATTRIB)
+        assert_eq!(
+            writer.inner.into_inner().unwrap(),
+            GTF_FILE_ONE_ATTRIB_QUOTED
+        )
     }
 
     #[test]
@@ -730,4 +855,69 @@ P0A7B8\tUniProtKB\tChain\t2\t176\t50\t+\t.\tID PRO_0000148105
         let result: Result<u8, ()> = phase.try_into();
         assert_eq!(result, Err(()));
     }
+
+    #[test]
+    fn test_gff3_attribute_escaping_roundtrip() {
+        let mut record = Record::new();
+        *record.seqname_mut() = "chr1".to_owned();
+        record
+            .attributes_mut()
+            .insert("Note".to_owned(), "a=b;c,d 100%".to_owned());
+        record
+            .attributes_mut()
+            .insert("Alias".to_owned(), "x".to_owned());
+        record
+            .attributes_mut()
+            .insert("Alias".to_owned(), "y".to_owned());
+
+        for gff_type in [GffType::GFF3, GffType::GTF2] {
+            let mut writer = Writer::new(vec![], gff_type);
+            writer.write(&record).unwrap();
+            let written = writer.inner.into_inner().unwrap();
+            if gff_type == GffType::GFF3 {
+                let line = String::from_utf8(written.clone()).unwrap();
+                assert!(line.contains("Note=a%3Db%3Bc%2Cd 100%25"));
+                assert!(line.contains("Alias=x,y"));
+            }
+
+            let mut reader = Reader::new(&written[..], gff_type);
+            let read = reader.records().next().unwrap().unwrap();
+            if gff_type == GffType::GFF3 {
+                assert_eq!(read, record);
+            } else {
+                assert_eq!(
+                    read.attributes().get_vec("Alias"),
+                    record.attributes().get_vec("Alias")
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn test_gtf2_numeric_values_unquoted() {
+        let mut record = Record::new();
+        record
+            .attributes_mut()
+            .insert("level".to_owned(), "3".to_owned());
+        let mut writer = Writer::new(vec![], GffType::GTF2);
+        writer.write(&record).unwrap();
+        let written = String::from_utf8(writer.inner.into_inner().unwrap()).unwrap();
+        assert!(written.ends_with("\tlevel 3\n"));
+    }
+
+    #[test]
+    fn test_parse_strand() {
+        let mut reader = Reader::new(GFF_FILE, GffType::GFF3);
+        let strands: Vec<_> = reader
+            .records()
+            .map(|r| {
+                r.unwrap()
+                    .parse_strand()
+                    .unwrap()
+                    .strand_symbol()
+                    .to_owned()
+            })
+            .collect();
+        assert_eq!(strands, [".", "+"]);
+    }
 }
@@ -1,8 +1,16 @@
 //! Readers and writers for common bioinformatics file formats.
 
+#[cfg(feature = "async-io")]
+pub mod async_io;
+pub mod auto;
+pub mod bam;
 pub mod bed;
 pub mod fasta;
 pub mod fastq;
 pub mod gff;
 #[cfg(feature = "phylogeny")]
 pub mod newick;
+mod push;
+pub mod vcf;
+
+pub use self::auto::open;
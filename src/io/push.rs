@@ -0,0 +1,106 @@
+//! Shared buffering of the push-based readers `fasta::PushReader` and `fastq::PushReader`.
+
+use std::ops::Range;
+
+/// A growing buffer of pushed input, from which complete records are consumed.
+///
+/// Line ends are searched only once per pushed byte, and readers can remember how many lines
+/// they already scanned (see `scanned`), such that waiting for the end of a large record does
+/// not rescan its beginning on every push.
+#[derive(Default, Clone, Debug)]
+pub(crate) struct Buffer {
+    data: Vec<u8>,
+    pos: usize,
+    finished: bool,
+    // ends (exclusive offsets into data) of the lines found so far, the pending ones starting
+    // at index first_line
+    line_ends: Vec<usize>,
+    first_line: usize,
+    // offset into data up to which it was searched for line ends
+    searched: usize,
+    scanned: usize,
+}
+
+impl Buffer {
+    pub(crate) fn push(&mut self, chunk: &[u8]) {
+        assert!(!self.finished, "Expecting no input after finish.");
+        // drop consumed input before growing the buffer
+        if self.pos > 0 && self.pos >= self.data.len() / 2 {
+            self.data.drain(..self.pos);
+            self.line_ends.drain(..self.first_line);
+            for end in &mut self.line_ends {
+                *end -= self.pos;
+            }
+            self.searched -= self.pos;
+            self.first_line = 0;
+            self.pos = 0;
+        }
+        self.data.extend_from_slice(chunk);
+    }
+
+    pub(crate) fn finish(&mut self) {
+        self.finished = true;
+    }
+
+    pub(crate) fn is_finished(&self) -> bool {
+        self.finished
+    }
+
+    /// The input that has not been consumed yet.
+    pub(crate) fn pending(&self) -> &[u8] {
+        &self.data[self.pos..]
+    }
+
+    /// Number of complete lines of the pending input. A final line without newline is only
+    /// complete if the input is finished.
+    pub(crate) fn lines(&mut self) -> usize {
+        for (i, &b) in self.data[self.searched..].iter().enumerate() {
+            if b == b'\n' {
+                self.line_ends.push(self.searched + i + 1);
+            }
+        }
+        self.searched = self.data.len();
+        if self.finished {
+            let last_end = match self.line_ends.get(self.first_line..) {
+                Some(pending) if !pending.is_empty() => pending[pending.len() - 1],
+                _ => self.pos,
+            };
+            if last_end < self.data.len() {
+                self.line_ends.push(self.data.len());
+            }
+        }
+        self.line_ends.len() - self.first_line
+    }
+
+    /// Range of the i-th complete line (including the newline) in the pending input.
+    pub(crate) fn line(&self, i: usize) -> Range<usize> {
+        let idx = self.first_line + i;
+        let start = if i == 0 {
+            self.pos
+        } else {
+            self.line_ends[idx - 1]
+        };
+        start - self.pos..self.line_ends[idx] - self.pos
+    }
+
+    /// Number of complete lines at the start of the pending input that the reader already
+    /// scanned without finding the end of a record. Reset when input is consumed.
+    pub(crate) fn scanned(&self) -> usize {
+        self.scanned
+    }
+
+    pub(crate) fn set_scanned(&mut self, lines: usize) {
+        self.scanned = lines;
+    }
+
+    /// Consume the given number of complete lines of the pending input, returning them.
+    pub(crate) fn consume_lines(&mut self, lines: usize) -> &[u8] {
+        let start = self.pos;
+        if lines > 0 {
+            self.pos = self.line_ends[self.first_line + lines - 1];
+        }
+        self.first_line += lines;
+        self.scanned = 0;
+        &self.data[start..self.pos]
+    }
+}
@@ -0,0 +1,251 @@
+//! Writing of variant calls in VCF format (version 4.2).
+//!
+//! This is a minimal, text-based writer, e.g. for emitting the calls of
+//! `bio::seq_analysis::snv`. For full VCF/BCF support (including reading), have a look at
+//! [rust-htslib](https://docs.rs/rust-htslib).
+//!
+//! # Example
+//!
+//! ```
+//! use bio::io::vcf;
+//!
+//! let mut header = vcf::Header::new();
+//! header.add_contig("chr1", Some(1000));
+//! header.add_info("DP", "1", "Integer", "Total depth");
+//! header.add_sample("sample1");
+//!
+//! let record = vcf::Record {
+//!     chrom: "chr1".to_owned(),
+//!     pos: 10,
+//!     ref_allele: b"A".to_vec(),
+//!     alt_alleles: vec![b"G".to_vec()],
+//!     qual: Some(50.0),
+//!     filters: vec!["PASS".to_owned()],
+//!     info: vec![("DP".to_owned(), Some("12".to_owned()))],
+//!     format: vec!["GT".to_owned()],
+//!     samples: vec![vec!["0/1".to_owned()]],
+//!     ..Default::default()
+//! };
+//!
+//! let mut writer = vcf::Writer::new(vec![], &header).unwrap();
+//! writer.write(&record).unwrap();
+//! let output = String::from_utf8(writer.into_inner().unwrap()).unwrap();
+//! assert!(output.starts_with("##fileformat=VCFv4.2\n##contig=<ID=chr1,length=1000>\n"));
+//! assert!(output.ends_with("chr1\t10\t.\tA\tG\t50\tPASS\tDP=12\tGT\t0/1\n"));
+//! ```
+
+use std::fs;
+use std::io;
+use std::io::prelude::*;
+use std::path::Path;
+
+/// A VCF header.
+#[derive(Default, Clone, Eq, PartialEq, Ord, PartialOrd, Hash, Debug, Serialize, Deserialize)]
+pub struct Header {
+    /// Meta-information lines, without the leading `##`.
+    pub meta: Vec<String>,
+    pub samples: Vec<String>,
+}
+
+impl Header {
+    /// Create a new, empty header.
+    pub fn new() -> Self {
+        Header::default()
+    }
+
+    /// Add a meta-information line (without the leading `##`).
+    pub fn push_meta(&mut self, line: &str) -> &mut Self {
+        self.meta.push(line.to_owned());
+        self
+    }
+
+    /// Add a contig with an optional length.
+    pub fn add_contig(&mut self, id: &str, len: Option<u64>) -> &mut Self {
+        match len {
+            Some(len) => self.push_meta(&format!("contig=<ID={},length={}>", id, len)),
+            None => self.push_meta(&format!("contig=<ID={}>", id)),
+        }
+    }
+
+    /// Add the definition of an INFO field.
+    pub fn add_info(&mut self, id: &str, number: &str, kind: &str, desc: &str) -> &mut Self {
+        self.push_meta(&format!(
+            "INFO=<ID={},Number={},Type={},Description=\"{}\">",
+            id, number, kind, desc
+        ))
+    }
+
+    /// Add the definition of a FORMAT field.
+    pub fn add_format(&mut self, id: &str, number: &str, kind: &str, desc: &str) -> &mut Self {
+        self.push_meta(&format!(
+            "FORMAT=<ID={},Number={},Type={},Description=\"{}\">",
+            id, number, kind, desc
+        ))
+    }
+
+    /// Add the definition of a filter.
+    pub fn add_filter(&mut self, id: &str, desc: &str) -> &mut Self {
+        self.push_meta(&format!("FILTER=<ID={},Description=\"{}\">", id, desc))
+    }
+
+    /// Add a sample.
+    pub fn add_sample(&mut self, name: &str) -> &mut Self {
+        self.samples.push(name.to_owned());
+        self
+    }
+}
+
+/// A VCF record. Missing values (e.g. no ID or no filters applied) are written as `.`.
+#[derive(Default, Clone, PartialEq, Debug, Serialize, Deserialize)]
+pub struct Record {
+    pub chrom: String,
+    /// 1-based position of the first base of the reference allele.
+    pub pos: u64,
+    pub id: Option<String>,
+    pub ref_allele: Vec<u8>,
+    pub alt_alleles: Vec<Vec<u8>>,
+    pub qual: Option<f64>,
+    pub filters: Vec<String>,
+    /// INFO fields as pairs of key and value, flags have no value.
+    pub info: Vec<(String, Option<String>)>,
+    /// Keys of the per-sample fields.
+    pub format: Vec<String>,
+    /// Per-sample values, in the order of `format`.
+    pub samples: Vec<Vec<String>>,
+}
+
+/// A VCF writer.
+#[derive(Debug)]
+pub struct Writer<W: io::Write> {
+    writer: io::BufWriter<W>,
+    samples: usize,
+}
+
+impl Writer<fs::File> {
+    /// Write to a given file path.
+    #[allow(clippy::wrong_self_convention)]
+    pub fn to_file<P: AsRef<Path>>(path: P, header: &Header) -> io::Result<Self> {
+        fs::File::create(path).and_then(|file| Writer::new(file, header))
+    }
+}
+
+impl<W: io::Write> Writer<W> {
+    /// Write to a given `io::Write`, starting with the header.
+    pub fn new(writer: W, header: &Header) -> io::Result<Self> {
+        let mut writer = io::BufWriter::new(writer);
+        writeln!(writer, "##fileformat=VCFv4.2")?;
+        for line in &header.meta {
+            writeln!(writer, "##{}", line)?;
+        }
+        write!(writer, "#CHROM\tPOS\tID\tREF\tALT\tQUAL\tFILTER\tINFO")?;
+        if !header.samples.is_empty() {
+            write!(writer, "\tFORMAT\t{}", header.samples.join("\t"))?;
+        }
+        writeln!(writer)?;
+
+        Ok(Writer {
+            writer,
+            samples: header.samples.len(),
+        })
+    }
+
+    /// Write a record. Returns an error of kind `io::ErrorKind::InvalidInput` if the number of
+    /// samples does not match the header.
+    pub fn write(&mut self, record: &Record) -> io::Result<()> {
+        if record.samples.len() != self.samples {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "Number of samples does not match the header.",
+            ));
+        }
+        let or_missing = |values: Vec<String>, sep: &str| {
+            if values.is_empty() {
+                ".".to_owned()
+            } else {
+                values.join(sep)
+            }
+        };
+
+        let alts = record
+            .alt_alleles
+            .iter()
+            .map(|alt| String::from_utf8_lossy(alt).into_owned())
+            .collect();
+        let info = record
+            .info
+            .iter()
+            .map(|(key, value)| match value {
+                Some(value) => format!("{}={}", key, value),
+                None => key.clone(),
+            })
+            .collect();
+        write!(
+            self.writer,
+            "{}\t{}\t{}\t{}\t{}\t{}\t{}\t{}",
+            record.chrom,
+            record.pos,
+            record.id.as_deref().unwrap_or("."),
+            String::from_utf8_lossy(&record.ref_allele),
+            or_missing(alts, ","),
+            record
+                .qual
+                .map_or_else(|| ".".to_owned(), |qual| format!("{}", qual)),
+            or_missing(record.filters.clone(), ";"),
+            or_missing(info, ";"),
+        )?;
+        if self.samples > 0 {
+            write!(self.writer, "\t{}", or_missing(record.format.clone(), ":"))?;
+            for sample in &record.samples {
+                write!(self.writer, "\t{}", or_missing(sample.clone(), ":"))?;
+            }
+        }
+        writeln!(self.writer)
+    }
+
+    /// Flush the writer, ensuring that everything is written.
+    pub fn flush(&mut self) -> io::Result<()> {
+        self.writer.flush()
+    }
+
+    /// Flush the writer and return the underlying `io::Write`.
+    pub fn into_inner(self) -> io::Result<W> {
+        self.writer.into_inner().map_err(|e| e.into_error())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_write_sites_only() {
+        let header = Header::new();
+        let mut writer = Writer::new(vec![], &header).unwrap();
+        writer
+            .write(&Record {
+                chrom: "chr2".to_owned(),
+                pos: 5,
+                id: Some("rs1".to_owned()),
+                ref_allele: b"AC".to_vec(),
+                alt_alleles: vec![b"A".to_vec(), b"ACC".to_vec()],
+                info: vec![
+                    ("DB".to_owned(), None),
+                    ("AF".to_owned(), Some("0.5".to_owned())),
+                ],
+                ..Default::default()
+            })
+            .unwrap();
+        let output = String::from_utf8(writer.into_inner().unwrap()).unwrap();
+        assert_eq!(
+            output,
+            "##fileformat=VCFv4.2\n#CHROM\tPOS\tID\tREF\tALT\tQUAL\tFILTER\tINFO\n\
+             chr2\t5\trs1\tAC\tA,ACC\t.\t.\tDB;AF=0.5\n"
+        );
+
+        let mut writer = Writer::new(vec![], header.clone().add_sample("s")).unwrap();
+        assert_eq!(
+            writer.write(&Record::default()).unwrap_err().kind(),
+            io::ErrorKind::InvalidInput
+        );
+    }
+}
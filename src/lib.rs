@@ -169,6 +169,22 @@
 //!
 //! Documentation and further examples for each module can be found in the module descriptions below.
 //!
+//! # WebAssembly
+//!
+//! Rust-Bio can be compiled to WebAssembly, e.g. for running alignments or pattern matching in
+//! the browser. Enable the feature `wasm` to run all parallel functions on the calling thread
+//! (see `utils::parallel`). Functions reading or writing files return errors on targets without
+//! a filesystem, such that readers and writers should be used with in-memory buffers instead.
+//! FM-indices can be built under a memory limit with `data_structures::fmindex::bounded`.
+//! For `wasm32-unknown-unknown`, random number generation used by dependencies requires enabling
+//! the `js` feature of the `getrandom` crate in the final application.
+//!
+//! # Async
+//!
+//! Enable the feature `async-io` to read FASTA and FASTQ records asynchronously, e.g. from
+//! uploads received by a web service, with `io::fasta::AsyncReader` and
+//! `io::fastq::AsyncReader`. The readers work with any runtime (see `io::async_io`).
+//!
 //!
 //! ## Example: Multithreaded
 //!
@@ -252,10 +268,14 @@ extern crate pest_derive;
 pub mod alignment;
 pub mod alphabets;
 pub mod data_structures;
+#[cfg(feature = "ffi")]
+pub mod ffi;
 pub mod io;
 pub mod pattern_matching;
 pub mod scores;
 pub mod seq_analysis;
 pub mod stats;
+#[cfg(feature = "test-utils")]
+pub mod test_utils;
 pub mod utils;
 pub use bio_types;
@@ -14,6 +14,7 @@
 //! * KMP algorithm: the classical ancestor.
 //! * Ukkonens algorithm: approximate pattern matching with dynamic programming.
 //! * Myers algorithm: linear-time approximate pattern matching with edit distance for small patterns
+//! * Aho-Corasick algorithm: exact matching of many DNA patterns with IUPAC codes in one pass.
 //!
 //! Another library that provides heavily optimized routines for string search primitives is memchr: https://crates.io/crates/memchr
 
@@ -21,7 +22,9 @@ pub mod bndm;
 pub mod bom;
 pub mod horspool;
 pub mod kmp;
+pub mod multi;
 pub mod myers;
 pub mod pssm;
+pub mod seed_and_verify;
 pub mod shift_and;
 pub mod ukkonen;
@@ -0,0 +1,346 @@
+//! Multi-pattern exact matching of DNA patterns with IUPAC ambiguity codes (e.g. degenerate
+//! primers or adapters), using the algorithm of Aho and Corasick.
+//!
+//! Degenerate positions of the patterns are expanded when building the automaton, i.e. the
+//! trie contains all concrete sequences (over `ACGT`) represented by each pattern, sharing
+//! common prefixes. The trie is completed to a deterministic automaton, which finds the
+//! occurrences of all patterns in a single pass over the text. Text bytes are matched
+//! case-insensitively and `U` is treated like `T`; any other byte (including ambiguous codes
+//! like `N`) matches no pattern position. To search both strands, add the reverse complements
+//! of the patterns (see `alphabets::dna::iupac::revcomp`).
+//!
+//! Complexity: O(M) for building the automaton, where M is the total length of the expanded
+//! patterns, and O(n + z) for searching a text of length n with z occurrences.
+//!
+//! # Example
+//!
+//! ```
+//! use bio::pattern_matching::multi::AhoCorasick;
+//!
+//! // a degenerate primer and an adapter
+//! let patterns = [&b"GTGYCAGCMGCCGCGGTAA"[..], b"AGATCGGAAGAGC"];
+//! let ac = AhoCorasick::new(&patterns).unwrap();
+//! let text = b"ttGTGCCAGCAGCCGCGGTAAtctAGATCGGAAGAGCacac";
+//! let hits = ac.find_all(text).collect::<Vec<_>>();
+//! assert_eq!(hits, [(0, 2), (1, 24)]);
+//! ```
+
+use std::borrow::Borrow;
+use std::iter::Enumerate;
+
+use thiserror::Error;
+
+use crate::alphabets::dna::iupac;
+
+/// Default maximum number of concrete sequences a single pattern may expand to.
+pub const DEFAULT_MAX_EXPANSIONS: usize = 1 << 16;
+
+#[derive(
+    Error, Copy, Clone, Eq, PartialEq, Ord, PartialOrd, Hash, Debug, Serialize, Deserialize,
+)]
+pub enum Error {
+    #[error("pattern {pattern} is empty")]
+    EmptyPattern { pattern: usize },
+    #[error("pattern {pattern} contains the non-IUPAC symbol '{}'", char::from(*symbol))]
+    InvalidSymbol { pattern: usize, symbol: u8 },
+    #[error("pattern {pattern} expands to {expansions} sequences, more than the maximum {max}")]
+    TooManyExpansions {
+        pattern: usize,
+        expansions: usize,
+        max: usize,
+    },
+}
+
+pub type Result<T, E = Error> = std::result::Result<T, E>;
+
+/// Marker for states without a dictionary suffix link.
+const NONE: u32 = u32::MAX;
+
+/// Rank of a concrete base, `None` for all other bytes.
+#[inline]
+fn rank(a: u8) -> Option<usize> {
+    let bases = iupac::bases(a);
+    if bases.count_ones() == 1 {
+        Some(bases.trailing_zeros() as usize)
+    } else {
+        None
+    }
+}
+
+/// An Aho-Corasick automaton for a set of IUPAC patterns.
+#[derive(Default, Clone, Eq, PartialEq, Hash, Debug, Serialize, Deserialize)]
+pub struct AhoCorasick {
+    /// Transitions of each state for the bases A, C, G and T.
+    delta: Vec<[u32; 4]>,
+    /// The patterns ending in each state.
+    out: Vec<Vec<usize>>,
+    /// The longest proper suffix state of each state that ends a pattern, or `NONE`.
+    dict: Vec<u32>,
+    /// Pattern lengths.
+    lens: Vec<usize>,
+}
+
+impl AhoCorasick {
+    /// Build the automaton for the given patterns, with at most `DEFAULT_MAX_EXPANSIONS`
+    /// concrete sequences per pattern.
+    pub fn new<P: AsRef<[u8]>>(patterns: &[P]) -> Result<Self> {
+        Self::with_max_expansions(patterns, DEFAULT_MAX_EXPANSIONS)
+    }
+
+    /// Build the automaton for the given patterns, with at most `max_expansions` concrete
+    /// sequences per pattern (see `alphabets::dna::iupac::expansions`).
+    ///
+    /// # Errors
+    /// * `Error::EmptyPattern` - a pattern is empty
+    /// * `Error::InvalidSymbol` - a pattern contains a byte that is not an IUPAC code
+    /// * `Error::TooManyExpansions` - a pattern has too many ambiguous positions
+    pub fn with_max_expansions<P: AsRef<[u8]>>(
+        patterns: &[P],
+        max_expansions: usize,
+    ) -> Result<Self> {
+        let mut ac = AhoCorasick {
+            delta: vec![[NONE; 4]],
+            out: vec![Vec::new()],
+            dict: vec![NONE],
+            lens: Vec::with_capacity(patterns.len()),
+        };
+        for (id, pattern) in patterns.iter().enumerate() {
+            let pattern = pattern.as_ref();
+            if pattern.is_empty() {
+                return Err(Error::EmptyPattern { pattern: id });
+            }
+            if let Some(&symbol) = pattern.iter().find(|&&a| iupac::bases(a) == 0) {
+                return Err(Error::InvalidSymbol {
+                    pattern: id,
+                    symbol,
+                });
+            }
+            let expansions = iupac::expansions(pattern);
+            if expansions > max_expansions {
+                return Err(Error::TooManyExpansions {
+                    pattern: id,
+                    expansions,
+                    max: max_expansions,
+                });
+            }
+            ac.insert(id, pattern);
+            ac.lens.push(pattern.len());
+        }
+        ac.complete();
+        Ok(ac)
+    }
+
+    /// Insert all expansions of the pattern into the trie.
+    fn insert(&mut self, id: usize, pattern: &[u8]) {
+        let mut stack = vec![(0, 0)];
+        while let Some((state, pos)) = stack.pop() {
+            if pos == pattern.len() {
+                self.out[state].push(id);
+                continue;
+            }
+            let bases = iupac::bases(pattern[pos]);
+            for a in (0..4).filter(|a| bases & (1 << a) != 0) {
+                let next = match self.delta[state][a] {
+                    NONE => {
+                        self.delta.push([NONE; 4]);
+                        self.out.push(Vec::new());
+                        self.dict.push(NONE);
+                        let next = self.delta.len() - 1;
+                        self.delta[state][a] = next as u32;
+                        next
+                    }
+                    next => next as usize,
+                };
+                stack.push((next, pos + 1));
+            }
+        }
+    }
+
+    /// Compute failure and dictionary links in breadth-first order and complete the
+    /// transitions of the trie.
+    fn complete(&mut self) {
+        let mut fail = vec![0; self.delta.len()];
+        let mut queue = std::collections::VecDeque::new();
+        for a in 0..4 {
+            match self.delta[0][a] {
+                NONE => self.delta[0][a] = 0,
+                child => queue.push_back(child as usize),
+            }
+        }
+        while let Some(state) = queue.pop_front() {
+            for a in 0..4 {
+                let f = self.delta[fail[state]][a];
+                match self.delta[state][a] {
+                    NONE => self.delta[state][a] = f,
+                    child => {
+                        let child = child as usize;
+                        fail[child] = f as usize;
+                        self.dict[child] = if self.out[f as usize].is_empty() {
+                            self.dict[f as usize]
+                        } else {
+                            f
+                        };
+                        queue.push_back(child);
+                    }
+                }
+            }
+        }
+    }
+
+    /// Number of patterns.
+    pub fn pattern_count(&self) -> usize {
+        self.lens.len()
+    }
+
+    /// Length of the given pattern.
+    pub fn pattern_len(&self, pattern: usize) -> usize {
+        self.lens[pattern]
+    }
+
+    /// Number of states of the automaton.
+    pub fn state_count(&self) -> usize {
+        self.delta.len()
+    }
+
+    /// Find all occurrences of the patterns in the given text. Occurrences are returned as
+    /// iterator over pairs of pattern index and start position, ordered by end position.
+    pub fn find_all<C, T>(&self, text: T) -> Matches<'_, C, T::IntoIter>
+    where
+        C: Borrow<u8>,
+        T: IntoIterator<Item = C>,
+    {
+        Matches {
+            ac: self,
+            state: 0,
+            text: text.into_iter().enumerate(),
+            pending: Vec::new(),
+            end: 0,
+        }
+    }
+}
+
+/// Iterator over the occurrences of the patterns of an `AhoCorasick` automaton.
+#[derive(Clone, Debug)]
+pub struct Matches<'a, C, T>
+where
+    C: Borrow<u8>,
+    T: Iterator<Item = C>,
+{
+    ac: &'a AhoCorasick,
+    state: usize,
+    text: Enumerate<T>,
+    /// Patterns ending at the current position, in reverse order.
+    pending: Vec<usize>,
+    end: usize,
+}
+
+impl<'a, C, T> Iterator for Matches<'a, C, T>
+where
+    C: Borrow<u8>,
+    T: Iterator<Item = C>,
+{
+    type Item = (usize, usize);
+
+    fn next(&mut self) -> Option<(usize, usize)> {
+        loop {
+            if let Some(pattern) = self.pending.pop() {
+                return Some((pattern, self.end - self.ac.lens[pattern]));
+            }
+            let (i, a) = self.text.next()?;
+            self.state = match rank(*a.borrow()) {
+                Some(a) => self.ac.delta[self.state][a] as usize,
+                None => 0,
+            };
+            self.end = i + 1;
+            let mut state = self.state as u32;
+            if self.ac.out[self.state].is_empty() {
+                state = self.ac.dict[self.state];
+            }
+            while state != NONE {
+                self.pending
+                    .extend(self.ac.out[state as usize].iter().rev().cloned());
+                state = self.ac.dict[state as usize];
+            }
+            self.pending.reverse();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::prelude::*;
+    use rand::rngs::StdRng;
+
+    #[test]
+    fn test_overlapping() {
+        // only nucleotide patterns are supported
+        let err = AhoCorasick::new(&[&b"he"[..], b"she", b"his", b"hers"]).unwrap_err();
+        assert_eq!(
+            err,
+            Error::InvalidSymbol {
+                pattern: 0,
+                symbol: b'e'
+            }
+        );
+        let ac = AhoCorasick::new(&[&b"AC"[..], b"TAC", b"ACA", b"CAGT", b"A"]).unwrap();
+        let mut hits = ac.find_all(b"TACAGT").collect::<Vec<_>>();
+        assert_eq!(hits, [(4, 1), (1, 0), (0, 1), (2, 1), (4, 3), (3, 2)]);
+        hits.sort_unstable();
+        assert_eq!(hits, [(0, 1), (1, 0), (2, 1), (3, 2), (4, 1), (4, 3)]);
+        assert_eq!(ac.pattern_count(), 5);
+        assert_eq!(ac.pattern_len(3), 4);
+    }
+
+    #[test]
+    fn test_naive() {
+        let mut rng = StdRng::seed_from_u64(42);
+        let patterns = (0..20)
+            .map(|_| {
+                let len = rng.gen_range(1..6);
+                (0..len)
+                    .map(|_| *iupac::SYMBOLS.choose(&mut rng).unwrap())
+                    .collect::<Vec<_>>()
+            })
+            .collect::<Vec<_>>();
+        let text = (0..1000)
+            .map(|_| *b"ACGTacgtN".choose(&mut rng).unwrap())
+            .collect::<Vec<_>>();
+        let ac = AhoCorasick::new(&patterns).unwrap();
+        let mut hits = ac.find_all(&text).collect::<Vec<_>>();
+        hits.sort_unstable();
+        let mut expected = Vec::new();
+        for (id, pattern) in patterns.iter().enumerate() {
+            for (i, window) in text.windows(pattern.len()).enumerate() {
+                // the text matches if it is concrete at each position
+                if window
+                    .iter()
+                    .zip(pattern)
+                    .all(|(&a, &p)| rank(a).is_some() && iupac::matches(a, p))
+                {
+                    expected.push((id, i));
+                }
+            }
+        }
+        assert_eq!(hits, expected);
+    }
+
+    #[test]
+    fn test_errors() {
+        assert_eq!(
+            AhoCorasick::new(&[&b"ACGT"[..], b""]),
+            Err(Error::EmptyPattern { pattern: 1 })
+        );
+        assert_eq!(
+            AhoCorasick::with_max_expansions(&[b"NNN"], 16),
+            Err(Error::TooManyExpansions {
+                pattern: 0,
+                expansions: 64,
+                max: 16
+            })
+        );
+        let ac = AhoCorasick::new(&[b"NNN"]).unwrap();
+        assert_eq!(ac.state_count(), 1 + 4 + 16 + 64);
+        assert_eq!(ac.find_all(b"ACNGTU").collect::<Vec<_>>(), [(0, 3)]);
+    }
+}
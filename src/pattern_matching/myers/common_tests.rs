@@ -248,6 +248,34 @@ macro_rules! impl_tests {
             assert_eq!(max_dist, 64);
         }
 
+        #[test]
+        fn test_find_best() {
+            let text = b"CGGTCCTGAGGGATTAGCAC";
+            let patt = b"TCCTAGGGC";
+
+            let mut myers = Myers::<$bitvec>::new(patt);
+            assert_eq!(myers.find_best(text, 1), None);
+            // the first of the two hits with distance 2
+            assert_eq!(myers.find_best(text, 5), Some((3, 12, 2)));
+            assert_eq!(
+                myers.find_best_end(text),
+                (11, myers.find_best(text, 5).unwrap().2)
+            );
+
+            let mut aln = Alignment::default();
+            assert!(myers.find_best_alignment(text, 5, &mut aln));
+            assert_eq!((aln.ystart, aln.yend, aln.score), (3, 12, 2));
+            assert_eq!(aln.ylen, text.len());
+
+            // the search stops at the first exact match
+            let mut myers = Myers::<$bitvec>::new(b"GAG");
+            let text = b"TGAGTTGAG".iter().enumerate().map(|(i, &a)| {
+                assert!(i < 4, "Text read beyond the exact match.");
+                a
+            });
+            assert_eq!(myers.find_best(text, 1), Some((1, 4, 0)));
+        }
+
         // macro end
     };
 }
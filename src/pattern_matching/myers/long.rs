@@ -231,7 +231,7 @@ where
             self.add_state(-carry);
             advance_block(&mut self.states[last_block], &peq[last_block], a, carry);
         } else {
-            while last_block > 0 && self.states[last_block].dist >= max_dist + w {
+            while last_block > 0 && self.states[last_block].dist >= max_dist.saturating_add(w) {
                 last_block -= 1;
             }
             self.states.truncate(last_block + 1);
@@ -508,4 +508,51 @@ mod tests {
         let hits: Vec<_> = myers.find_all_end(text, usize::max_value() - 64).collect();
         dbg!(hits);
     }
+
+    #[test]
+    fn test_pattern_longer_than_128() {
+        use crate::alignment::Alignment;
+        use rand::prelude::*;
+        use rand::rngs::StdRng;
+
+        let mut rng = StdRng::seed_from_u64(42);
+        let pattern = (0..300)
+            .map(|_| *b"ACGT".choose(&mut rng).unwrap())
+            .collect::<Vec<_>>();
+        // embed the pattern with two substitutions and a deletion into random sequence
+        let mut text = (0..100)
+            .map(|_| *b"ACGT".choose(&mut rng).unwrap())
+            .collect::<Vec<_>>();
+        let mut occ = pattern.clone();
+        occ[10] = if occ[10] == b'A' { b'C' } else { b'A' };
+        occ[150] = if occ[150] == b'G' { b'T' } else { b'G' };
+        occ.remove(250);
+        text.extend(&occ);
+        text.extend(&text[..100].to_vec());
+
+        // semiglobal edit distance by dynamic programming
+        let mut col = (0..=pattern.len()).collect::<Vec<_>>();
+        let mut best = col[pattern.len()];
+        for &a in &text {
+            let mut diag = col[0];
+            for i in 1..=pattern.len() {
+                let d = (diag + (pattern[i - 1] != a) as usize)
+                    .min(col[i] + 1)
+                    .min(col[i - 1] + 1);
+                diag = col[i];
+                col[i] = d;
+            }
+            best = best.min(col[pattern.len()]);
+        }
+        assert_eq!(best, 3);
+
+        let mut myers = Myers::<u64>::new(&pattern);
+        assert_eq!(myers.distance(&text), best);
+        assert_eq!(myers.find_best_end(&text), (398, 3));
+        let mut matches = myers.find_all(&text, 3);
+        let mut aln = Alignment::default();
+        assert!(matches.next_alignment(&mut aln));
+        assert_eq!((aln.ystart, aln.yend, aln.score), (100, 399, 3));
+        assert_eq!(aln.operations.len(), 300);
+    }
 }
@@ -5,8 +5,10 @@
 
 //! Myers bit-parallel approximate pattern matching algorithm.
 //! Finds all matches up to a given edit distance. The pattern has to fit into a bitvector,
-//! and is thus limited to 64 or (since stable Rust version 1.26) to 128 symbols.
-//! Complexity: O(n)
+//! and is thus limited to 64 or (since stable Rust version 1.26) to 128 symbols. Patterns of
+//! arbitrary length are supported by the block-based variant in the [`long`](long/index.html)
+//! submodule, which chains multiple bitvectors and offers the same API (see below).
+//! Complexity: O(n) (O(n⌈m/w⌉) for the block-based variant with word size w)
 //!
 //! Traceback allows obtaining the starting position and the alignment path of the hit.
 //! Its implementation is somehow similar to the one by Edlib (Šošić and Šikić 2017),
@@ -208,6 +210,28 @@
 //! Actually as seen in the previous chapters, there are two hits with the same distance of 2.
 //! It may make sense to consider both of them.
 //!
+//! If only the first best hit is needed (e.g. for assigning reads to barcodes),
+//! `Myers::find_best` and `Myers::find_best_alignment` do the above in one call. They lower
+//! the maximum distance to the best distance found so far and stop at the first exact match:
+//!
+//! ```
+//! # extern crate bio;
+//! use bio::alignment::Alignment;
+//! use bio::pattern_matching::myers::Myers;
+//!
+//! # fn main() {
+//! let text = b"CGGTCCTGAGGGATTAGCAC";
+//! let pattern = b"TCCTAGGGC";
+//!
+//! let mut myers = Myers::<u64>::new(pattern);
+//! assert_eq!(myers.find_best(text, 4), Some((3, 12, 2)));
+//!
+//! let mut aln = Alignment::default();
+//! assert!(myers.find_best_alignment(text, 4, &mut aln));
+//! assert_eq!((aln.ystart, aln.yend), (3, 12));
+//! # }
+//! ```
+//!
 //! # Dealing with ambiguities
 //!
 //! Matching multiple or all symbols at once can be achieved using `MyersBuilder`. This example
@@ -187,6 +187,52 @@ impl<T: BitVec> $Myers {
             .unwrap()
     }
 
+    /// Find the match with the smallest distance (up to `max_dist`) in the given text,
+    /// returning `(start, end, distance)` like `find_all`, or `None` if there is no match.
+    /// If multiple end positions have the same distance, the first is returned.
+    ///
+    /// While searching, the maximum distance is lowered to the best distance found so far,
+    /// such that the block-based implementation (`long` submodule) computes fewer blocks,
+    /// and the search stops at the first exact match. In contrast to `find_best_end`, the
+    /// data for the traceback is kept [O(n) memory].
+    pub fn find_best<C, I>(
+        &mut self,
+        text: I,
+        max_dist: $DistType,
+    ) -> Option<(usize, usize, $DistType)>
+    where
+        C: Borrow<u8>,
+        I: IntoIterator<Item = C>,
+        I::IntoIter: ExactSizeIterator,
+    {
+        let mut matches = self.find_all_lazy(text, max_dist);
+        let (end, _) = matches.best()?;
+        matches
+            .hit_at(end)
+            .map(|(start, dist)| (start, end + 1, dist))
+    }
+
+    /// Like `find_best`, but obtains the alignment of the best match, which is stored in `aln`
+    /// (with the distance as `Alignment::score`). Returns `false` if there is no match up to
+    /// `max_dist`, leaving `aln` unchanged.
+    pub fn find_best_alignment<C, I>(
+        &mut self,
+        text: I,
+        max_dist: $DistType,
+        aln: &mut Alignment,
+    ) -> bool
+    where
+        C: Borrow<u8>,
+        I: IntoIterator<Item = C>,
+        I::IntoIter: ExactSizeIterator,
+    {
+        let mut matches = self.find_all_lazy(text, max_dist);
+        match matches.best() {
+            Some((end, _)) => matches.alignment_at(end, aln),
+            None => false,
+        }
+    }
+
     /// Finds all matches of pattern in the given text up to a given maximum distance.
     /// In contrast to `find_all_end`, matches are returned as an iterator over ranges
     /// of `(start, end, distance)`. Note that the end coordinate is not included in the
@@ -543,6 +589,23 @@ where
         }
     }
 
+    /// Consume the remaining text, returning the first end position with the smallest
+    /// distance. The maximum distance is lowered to the best distance found so far, and the
+    /// search stops at the first exact match.
+    fn best(&mut self) -> Option<(usize, $DistType)> {
+        let mut best = None;
+        while let Some((end, dist)) = self.next() {
+            if best.map_or(true, |(_, best_dist)| dist < best_dist) {
+                best = Some((end, dist));
+                self.max_dist = dist;
+                if dist == <$DistType>::min_value() {
+                    break;
+                }
+            }
+        }
+        best
+    }
+
     /// Takes the end position of a hit (as returned by the `LazyMatches` iterator) and returns a
     /// tuple of the corresponding starting position and the hit distance. If the end position is
     /// greater than the end position of the previously returned hit, `None` is returned.
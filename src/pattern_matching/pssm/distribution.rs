@@ -0,0 +1,153 @@
+//! Distribution of motif scores of random sequences, for computing p-values of matches and
+//! score thresholds for a given p-value.
+//!
+//! Weights are discretized to integer multiples of 1 / `RESOLUTION` of the normalized score range
+//! (as used in `ScoredPos::sum`), relative to the worst monomer at each position. The distribution
+//! of the discretized score is then computed exactly by dynamic programming over the motif
+//! positions (Touzet and Varré, 2007), in O(m·σ·R) for motif length m, alphabet size σ and
+//! resolution R. The discretization error of a score is at most m / (2R).
+
+use itertools::Itertools;
+
+use super::{Error, Motif, Result};
+
+/// Number of discrete steps of the normalized score range [0, 1].
+pub const RESOLUTION: usize = 10_000;
+
+/// Tail distribution of the normalized scores of a motif, given a background distribution of
+/// the monomers (see `Motif::score_distribution`).
+#[derive(Default, Clone, PartialEq, Debug, Serialize, Deserialize)]
+pub struct ScoreDistribution {
+    /// The probability of a discretized score of at least i, for each i.
+    tail: Vec<f64>,
+}
+
+impl ScoreDistribution {
+    /// Compute the score distribution of the given motif. Without a background, monomers are
+    /// assumed to be uniformly distributed.
+    pub(crate) fn new<M: Motif + ?Sized>(motif: &M, background: Option<&[f32]>) -> Result<Self> {
+        let uniform = vec![1.0 / M::MONO_CT as f32; M::MONO_CT];
+        let background = background.unwrap_or(&uniform);
+        if background.len() != M::MONO_CT {
+            return Err(Error::InvalidBackground {
+                expected: M::MONO_CT as u8,
+                received: background.len() as u8,
+            });
+        }
+        let range = motif.get_max_score() - motif.get_min_score();
+        if abs_diff_eq!(range, 0.0) {
+            return Err(Error::NullMotif);
+        }
+
+        let total = background.iter().sum::<f32>() as f64;
+        // probability of a discretized score s after each position
+        let mut dist = vec![1.0];
+        for row in motif.get_scores().rows() {
+            let min = row.iter().cloned().fold(f32::INFINITY, f32::min);
+            let weights = row
+                .iter()
+                .map(|&w| ((w - min) / range * RESOLUTION as f32).round() as usize)
+                .collect_vec();
+            let max = weights.iter().max().cloned().unwrap_or(0);
+            let mut next = vec![0.0; dist.len() + max];
+            for (s, &p) in dist.iter().enumerate().filter(|(_, &p)| p > 0.0) {
+                for (&w, &b) in weights.iter().zip(background) {
+                    next[s + w] += p * b as f64 / total;
+                }
+            }
+            dist = next;
+        }
+
+        // accumulate from the highest score
+        let mut tail = dist;
+        tail.push(0.0);
+        for s in (0..tail.len() - 1).rev() {
+            tail[s] += tail[s + 1];
+        }
+        Ok(ScoreDistribution { tail })
+    }
+
+    /// The probability that a random sequence has at least the given normalized score.
+    pub fn pvalue(&self, score: f32) -> f64 {
+        let s = (score.max(0.0) * RESOLUTION as f32).round() as usize;
+        self.tail[s.min(self.tail.len() - 1)].min(1.0)
+    }
+
+    /// The smallest normalized score (in steps of 1 / `RESOLUTION`) that a random sequence
+    /// reaches or exceeds with probability at most the given p-value. It can exceed the
+    /// maximum score if the best matching sequence is more likely than the p-value.
+    pub fn score_threshold(&self, pvalue: f64) -> f32 {
+        let s = self
+            .tail
+            .iter()
+            .position(|&p| p <= pvalue)
+            .expect("bug: tail distribution does not end with zero");
+        s as f32 / RESOLUTION as f32
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::pattern_matching::pssm::{DNAMotif, ProtMotif};
+    use itertools::repeat_n;
+
+    #[test]
+    fn test_exhaustive() {
+        let pssm = DNAMotif::from_seqs(
+            &[
+                b"ACGTA".to_vec(),
+                b"ACGTT".to_vec(),
+                b"ACCTA".to_vec(),
+                b"TCGAA".to_vec(),
+            ],
+            None,
+        )
+        .unwrap();
+        let dist = pssm.score_distribution(None).unwrap();
+        // score all 4^5 sequences
+        let mut scores = repeat_n(b"ACGT".iter().cloned(), 5)
+            .multi_cartesian_product()
+            .map(|seq| pssm.score(&seq).unwrap().sum)
+            .collect_vec();
+        for &score in &scores {
+            // the fraction of sequences scoring at least as high, up to rounding errors
+            let count = scores.iter().filter(|&&s| s >= score - 1e-5).count();
+            let expected = count as f64 / scores.len() as f64;
+            assert_relative_eq!(dist.pvalue(score), expected, epsilon = 1e-9);
+        }
+        assert_eq!(dist.pvalue(0.0), 1.0);
+        assert_relative_eq!(dist.pvalue(1.0), 1.0 / 1024.0, epsilon = 1e-12);
+
+        let threshold = dist.score_threshold(0.01);
+        let hits = scores.iter().filter(|&&s| s >= threshold - 1e-5).count();
+        assert!(hits as f64 / 1024.0 <= 0.01);
+        // the next lower score would exceed the p-value
+        let next = scores
+            .iter()
+            .cloned()
+            .filter(|&s| s < threshold - 1e-5)
+            .fold(f32::NEG_INFINITY, f32::max);
+        assert!(dist.pvalue(next) > 0.01);
+        assert!(dist.score_threshold(1e-6) > 1.0);
+    }
+
+    #[test]
+    fn test_background() {
+        let pssm = ProtMotif::from_seqs(&[b"ARND".to_vec(), b"ARNC".to_vec()], None).unwrap();
+        assert_eq!(
+            pssm.score_distribution(Some(&[1.0; 4])),
+            Err(Error::InvalidBackground {
+                expected: 20,
+                received: 4
+            })
+        );
+        let mut background = [0.0; 20];
+        background[0] = 1.0;
+        let dist = pssm.score_distribution(Some(&background)).unwrap();
+        // only AAAA is generated, which scores exactly like it
+        let sum = pssm.score(b"AAAA").unwrap().sum;
+        assert_relative_eq!(dist.pvalue(sum), 1.0);
+        assert_eq!(dist.pvalue(sum + 0.01), 0.0);
+    }
+}
@@ -26,6 +26,12 @@ pub enum Error {
     NullMotif,
     #[error("expected pseudo-score array of length {}; got {}", expected, received)]
     InvalidPseudos { expected: u8, received: u8 },
+    #[error(
+        "expected background distribution of length {}; got {}",
+        expected,
+        received
+    )]
+    InvalidBackground { expected: u8, received: u8 },
 }
 
 pub type Result<T, E = Error> = std::result::Result<T, E>;
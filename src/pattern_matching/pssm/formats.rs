@@ -0,0 +1,499 @@
+//! Reading motifs from MEME minimal format and JASPAR PFM files, and writing motifs in MEME
+//! minimal format.
+//!
+//! Parsed motifs are `MotifMatrix` values holding the letter counts of each position in the
+//! letter order of the file. They are converted into `DNAMotif` or `ProtMotif` with a
+//! pseudocount added to each count. Probabilities of MEME files are turned into counts by
+//! multiplying with the number of sites of the motif (`nsites`, 20 if not given).
+//!
+//! # Example
+//!
+//! ```
+//! use bio::pattern_matching::pssm::formats::{read_jaspar, read_meme, write_meme};
+//! use bio::pattern_matching::pssm::{DNAMotif, Motif};
+//!
+//! let jaspar = b">MA0004.1 Arnt
+//! A  [ 4 19  0  0  0  0 ]
+//! C  [16  0 20  0  0  0 ]
+//! G  [ 0  1  0 20  0 20 ]
+//! T  [ 0  0  0  0 20  0 ]
+//! ";
+//! let matrices = read_jaspar(&jaspar[..]).unwrap();
+//! assert_eq!(matrices[0].name, "MA0004.1");
+//! let motif: DNAMotif = matrices[0].to_motif(0.5).unwrap();
+//! assert_eq!(motif.score(b"TTCACGTGTT").unwrap().loc, 2);
+//!
+//! // convert to MEME format and back
+//! let mut meme = Vec::new();
+//! write_meme(&mut meme, &[("Arnt", &motif)], None).unwrap();
+//! let parsed = read_meme(&meme[..]).unwrap();
+//! assert_eq!(parsed.motifs[0].name, "Arnt");
+//! let roundtrip: DNAMotif = parsed.motifs[0].to_motif(0.0).unwrap();
+//! assert!((roundtrip.scores[[1, 0]] - motif.scores[[1, 0]]).abs() < 1e-5);
+//! ```
+
+use std::io;
+use std::io::prelude::*;
+
+use ndarray::prelude::Array2;
+use thiserror::Error;
+
+use super::Motif;
+
+/// Number of sites assumed for MEME motifs without `nsites`.
+pub const DEFAULT_NSITES: f32 = 20.0;
+
+#[derive(Error, Debug)]
+pub enum Error {
+    #[error("can't read input")]
+    Read(#[from] io::Error),
+    #[error("invalid motif file in line {line}: {reason}")]
+    InvalidFormat { line: usize, reason: String },
+    #[error("motif letter '{}' is not part of the alphabet", char::from(*letter))]
+    MissingLetter { letter: u8 },
+    #[error("invalid motif: {0}")]
+    Motif(#[from] super::Error),
+}
+
+pub type Result<T, E = Error> = std::result::Result<T, E>;
+
+fn invalid<T>(line: usize, reason: &str) -> Result<T> {
+    Err(Error::InvalidFormat {
+        line,
+        reason: reason.to_owned(),
+    })
+}
+
+/// A motif as count matrix, with positions as rows and letters as columns.
+#[derive(Default, Clone, PartialEq, Debug)]
+pub struct MotifMatrix {
+    /// Name (MEME) or ID (JASPAR) of the motif.
+    pub name: String,
+    /// Alternative name (MEME) or name (JASPAR) of the motif.
+    pub description: Option<String>,
+    /// The letters of the columns.
+    pub alphabet: Vec<u8>,
+    /// The count of each letter at each position.
+    pub counts: Array2<f32>,
+}
+
+impl MotifMatrix {
+    /// Length of the motif.
+    pub fn len(&self) -> usize {
+        self.counts.nrows()
+    }
+
+    /// Whether the motif is empty.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Convert the counts into a motif (`DNAMotif` or `ProtMotif`), adding the given pseudocount
+    /// to each count. Letters are matched case-insensitively, and `U` is read as `T`.
+    ///
+    /// # Errors
+    /// * `Error::MissingLetter` - a letter of the motif type is missing from the alphabet
+    /// * `Error::Motif` - the alphabet contains a letter that is invalid for the motif type
+    pub fn to_motif<M: Motif + From<Array2<f32>>>(&self, pseudocount: f32) -> Result<M> {
+        let letters = self
+            .alphabet
+            .iter()
+            .map(|&a| match a.to_ascii_uppercase() {
+                b'U' if M::MONOS.contains(&b'T') => b'T',
+                a => a,
+            })
+            .collect::<Vec<_>>();
+        for &a in &letters {
+            M::lookup(a)?;
+        }
+        let mut scores = Array2::zeros((self.len(), M::MONO_CT));
+        for (j, &mono) in M::MONOS.iter().enumerate() {
+            let col = letters
+                .iter()
+                .position(|&a| a == mono)
+                .ok_or(Error::MissingLetter { letter: mono })?;
+            for i in 0..self.len() {
+                scores[[i, j]] = self.counts[[i, col]] + pseudocount;
+            }
+        }
+        Ok(M::from(scores))
+    }
+}
+
+/// Contents of a MEME minimal format file.
+#[derive(Default, Clone, PartialEq, Debug)]
+pub struct MemeFile {
+    /// The alphabet, in the order of matrix columns.
+    pub alphabet: Vec<u8>,
+    /// Background frequencies of the letters of the alphabet, if given.
+    pub background: Option<Vec<f32>>,
+    pub motifs: Vec<MotifMatrix>,
+}
+
+impl MemeFile {
+    /// Background frequencies in the monomer order of the given motif type (see
+    /// `Motif::score_distribution`), if given for all its monomers.
+    pub fn background_of<M: Motif>(&self) -> Option<Vec<f32>> {
+        let background = self.background.as_ref()?;
+        M::MONOS
+            .iter()
+            .map(|&mono| {
+                self.alphabet
+                    .iter()
+                    .position(|&a| a.to_ascii_uppercase() == mono)
+                    .map(|col| background[col])
+            })
+            .collect()
+    }
+}
+
+/// Infer the alphabet from the number of letters, if not given.
+fn default_alphabet(alength: usize) -> Option<Vec<u8>> {
+    match alength {
+        4 => Some(b"ACGT".to_vec()),
+        20 => Some(b"ACDEFGHIKLMNPQRSTVWY".to_vec()),
+        _ => None,
+    }
+}
+
+/// Read motifs in MEME minimal format (version 4 or later, with the alphabet given as
+/// `ALPHABET= ...` or inferred from the matrix width).
+pub fn read_meme<R: io::Read>(reader: R) -> Result<MemeFile> {
+    let mut file = MemeFile::default();
+    let mut lines = io::BufReader::new(reader).lines().enumerate();
+    let mut motif: Option<(String, Option<String>)> = None;
+
+    while let Some((i, line)) = lines.next() {
+        let line = line?;
+        let line = line.trim();
+        if let Some(alphabet) = line.strip_prefix("ALPHABET=") {
+            file.alphabet = alphabet.trim().bytes().collect();
+        } else if line.starts_with("Background letter frequencies") {
+            let mut background = Vec::new();
+            for (i, line) in lines.by_ref() {
+                let line = line?;
+                if line.trim().is_empty() {
+                    if background.is_empty() {
+                        continue;
+                    }
+                    break;
+                }
+                let fields = line.split_whitespace().collect::<Vec<_>>();
+                if fields.len() % 2 != 0 {
+                    return invalid(i + 1, "expecting pairs of letter and frequency");
+                }
+                for pair in fields.chunks(2) {
+                    if file.alphabet.len() <= background.len() {
+                        file.alphabet.push(pair[0].as_bytes()[0]);
+                    }
+                    background.push(
+                        pair[1]
+                            .parse()
+                            .or_else(|_| invalid(i + 1, "invalid frequency"))?,
+                    );
+                }
+            }
+            file.background = Some(background);
+        } else if let Some(names) = line.strip_prefix("MOTIF") {
+            let mut names = names.split_whitespace();
+            let name = names
+                .next()
+                .map_or_else(|| invalid(i + 1, "missing motif name"), Ok)?;
+            motif = Some((name.to_owned(), names.next().map(|s| s.to_owned())));
+        } else if let Some(params) = line.strip_prefix("letter-probability matrix:") {
+            let (name, description) = motif
+                .take()
+                .map_or_else(|| invalid(i + 1, "matrix without MOTIF line"), Ok)?;
+            let params = params.replace("= ", "=");
+            let param = |key: &str| {
+                params
+                    .split_whitespace()
+                    .find_map(|field| field.strip_prefix(key)?.strip_prefix('='))
+                    .map(|value| value.parse::<f32>())
+            };
+            let w = match param("w") {
+                Some(Ok(w)) => w as usize,
+                _ => return invalid(i + 1, "missing or invalid motif width w"),
+            };
+            let nsites = match param("nsites") {
+                Some(Ok(nsites)) => nsites,
+                None => DEFAULT_NSITES,
+                Some(Err(_)) => return invalid(i + 1, "invalid nsites"),
+            };
+
+            let mut rows = Vec::with_capacity(w);
+            while rows.len() < w {
+                let (i, line) = lines
+                    .next()
+                    .map_or_else(|| invalid(i + 1, "incomplete matrix"), Ok)?;
+                let line = line?;
+                if line.trim().is_empty() {
+                    continue;
+                }
+                let row = line
+                    .split_whitespace()
+                    .map(|p| p.parse::<f32>().map(|p| p * nsites))
+                    .collect::<Result<Vec<_>, _>>()
+                    .or_else(|_| invalid(i + 1, "invalid probability"))?;
+                rows.push(row);
+            }
+            let alength = rows.first().map_or(0, |row| row.len());
+            if file.alphabet.is_empty() {
+                file.alphabet = default_alphabet(alength)
+                    .map_or_else(|| invalid(i + 1, "unknown alphabet"), Ok)?;
+            }
+            if rows.iter().any(|row| row.len() != file.alphabet.len()) {
+                return invalid(i + 1, "matrix width does not match the alphabet");
+            }
+            file.motifs.push(MotifMatrix {
+                name,
+                description,
+                alphabet: file.alphabet.clone(),
+                counts: to_array(&rows, false),
+            });
+        }
+    }
+    Ok(file)
+}
+
+/// Convert rows of equal length into an array, optionally transposing them.
+fn to_array(rows: &[Vec<f32>], transpose: bool) -> Array2<f32> {
+    let (n, m) = (rows.len(), rows.first().map_or(0, |row| row.len()));
+    if transpose {
+        Array2::from_shape_fn((m, n), |(i, j)| rows[j][i])
+    } else {
+        Array2::from_shape_fn((n, m), |(i, j)| rows[i][j])
+    }
+}
+
+/// Read JASPAR PFM files, i.e. count matrices with one row per letter, optionally preceded by
+/// a `>ID name` header per motif and labeled by letters (with counts in brackets or not).
+/// Unlabeled rows are taken to be in the order A, C, G, T.
+pub fn read_jaspar<R: io::Read>(reader: R) -> Result<Vec<MotifMatrix>> {
+    let mut motifs = Vec::new();
+    let mut header: Option<(String, Option<String>)> = None;
+    let (mut letters, mut rows) = (Vec::new(), Vec::new());
+    let mut finish = |header: &mut Option<(String, Option<String>)>,
+                      letters: &mut Vec<u8>,
+                      rows: &mut Vec<Vec<f32>>,
+                      line: usize|
+     -> Result<()> {
+        if rows.is_empty() {
+            return if header.is_some() {
+                invalid(line, "motif without matrix")
+            } else {
+                Ok(())
+            };
+        }
+        if rows.iter().any(|row| row.len() != rows[0].len()) {
+            return invalid(line, "rows of different lengths");
+        }
+        let alphabet = if letters.is_empty() {
+            default_alphabet(rows.len()).map_or_else(|| invalid(line, "unknown alphabet"), Ok)?
+        } else {
+            letters.clone()
+        };
+        let (name, description) = header.take().unwrap_or_default();
+        motifs.push(MotifMatrix {
+            name,
+            description,
+            alphabet,
+            counts: to_array(rows, true),
+        });
+        letters.clear();
+        rows.clear();
+        Ok(())
+    };
+
+    let mut n = 0;
+    for (i, line) in io::BufReader::new(reader).lines().enumerate() {
+        let line = line?;
+        n = i + 1;
+        let line = line.trim();
+        if line.is_empty() {
+            continue;
+        }
+        if let Some(names) = line.strip_prefix('>') {
+            finish(&mut header, &mut letters, &mut rows, i + 1)?;
+            let mut names = names.splitn(2, char::is_whitespace);
+            header = Some((
+                names.next().unwrap_or("").to_owned(),
+                names.next().map(|s| s.trim().to_owned()),
+            ));
+            continue;
+        }
+
+        let line = line.replace(['[', ']'], " ");
+        let mut fields = line.split_whitespace().peekable();
+        if let Some(label) = fields.peek() {
+            if label.parse::<f32>().is_err() {
+                if label.len() != 1 {
+                    return invalid(i + 1, "invalid row label");
+                }
+                letters.push(label.as_bytes()[0]);
+                fields.next();
+            }
+        }
+        let row = fields
+            .map(|count| count.parse::<f32>())
+            .collect::<Result<Vec<_>, _>>()
+            .or_else(|_| invalid(i + 1, "invalid count"))?;
+        rows.push(row);
+    }
+    finish(&mut header, &mut letters, &mut rows, n)?;
+    Ok(motifs)
+}
+
+/// Write motifs with their names in MEME minimal format, with letters in alphabetical order.
+/// The background frequencies (in the monomer order of the motif type) default to uniform.
+pub fn write_meme<W: io::Write, M: Motif>(
+    mut writer: W,
+    motifs: &[(&str, &M)],
+    background: Option<&[f32]>,
+) -> io::Result<()> {
+    let mut order = (0..M::MONO_CT).collect::<Vec<_>>();
+    order.sort_by_key(|&j| M::MONOS[j]);
+    let uniform = vec![1.0 / M::MONO_CT as f32; M::MONO_CT];
+    let background = background.unwrap_or(&uniform);
+
+    writeln!(writer, "MEME version 4\n")?;
+    let alphabet = order
+        .iter()
+        .map(|&j| M::MONOS[j] as char)
+        .collect::<String>();
+    writeln!(writer, "ALPHABET= {}\n", alphabet)?;
+    writeln!(writer, "Background letter frequencies")?;
+    let frequencies = order
+        .iter()
+        .map(|&j| format!("{} {:.6}", M::MONOS[j] as char, background[j]))
+        .collect::<Vec<_>>();
+    writeln!(writer, "{}", frequencies.join(" "))?;
+
+    for (name, motif) in motifs {
+        writeln!(writer, "\nMOTIF {}", name)?;
+        writeln!(
+            writer,
+            "letter-probability matrix: alength= {} w= {}",
+            M::MONO_CT,
+            motif.len()
+        )?;
+        for row in motif.get_scores().rows() {
+            let row = order
+                .iter()
+                .map(|&j| format!("{:.6}", row[j]))
+                .collect::<Vec<_>>();
+            writeln!(writer, " {}", row.join(" "))?;
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::pattern_matching::pssm::{DNAMotif, ProtMotif};
+
+    const MEME: &[u8] = b"MEME version 4
+
+ALPHABET= ACGT
+
+strands: + -
+
+Background letter frequencies
+A 0.303 C 0.183 G 0.209 T 0.306
+
+MOTIF crp CRP
+letter-probability matrix: alength= 4 w= 3 nsites= 10 E= 4.1e-009
+ 0.000000  0.200000  0.000000  0.800000
+ 0.000000  0.000000  1.000000  0.000000
+ 0.500000  0.500000  0.000000  0.000000
+URL http://example.com
+
+MOTIF lexA
+letter-probability matrix: alength= 4 w= 2
+ 0.25 0.25 0.25 0.25
+ 1.0 0.0 0.0 0.0
+";
+
+    #[test]
+    fn test_read_meme() {
+        let file = read_meme(MEME).unwrap();
+        assert_eq!(file.alphabet, b"ACGT");
+        assert_eq!(file.motifs.len(), 2);
+        let crp = &file.motifs[0];
+        assert_eq!(crp.name, "crp");
+        assert_eq!(crp.description.as_deref(), Some("CRP"));
+        assert_eq!(crp.counts[[0, 3]], 8.0);
+        assert_eq!(file.motifs[1].counts[[1, 0]], DEFAULT_NSITES);
+        // monomer order of DNAMotif is ATGC
+        assert_eq!(
+            file.background_of::<DNAMotif>(),
+            Some(vec![0.303, 0.306, 0.209, 0.183])
+        );
+        assert_eq!(file.background_of::<ProtMotif>(), None);
+
+        let motif: DNAMotif = crp.to_motif(1.0).unwrap();
+        assert_eq!(motif.degenerate_consensus(), b"TGM");
+        assert!(matches!(
+            crp.to_motif::<ProtMotif>(1.0),
+            Err(Error::MissingLetter { letter: b'R' })
+        ));
+    }
+
+    #[test]
+    fn test_read_meme_invalid() {
+        let truncated = &MEME[..MEME.len() - 20];
+        // reported at the header of the incomplete matrix
+        assert!(matches!(
+            read_meme(truncated),
+            Err(Error::InvalidFormat { line: 18, .. })
+        ));
+        let invalid = b"MOTIF a\nletter-probability matrix: alength= 3 w= 1\n0.2 0.3 0.5\n";
+        assert!(matches!(
+            read_meme(&invalid[..]),
+            Err(Error::InvalidFormat { line: 2, .. })
+        ));
+    }
+
+    #[test]
+    fn test_read_jaspar() {
+        let raw = b"4 19 0 0\n16 0 20 0\n0 1 0 20\n0 0 0 0\n";
+        let matrices = read_jaspar(&raw[..]).unwrap();
+        assert_eq!(matrices.len(), 1);
+        assert_eq!(matrices[0].alphabet, b"ACGT");
+        assert_eq!(matrices[0].len(), 4);
+        assert_eq!(matrices[0].counts[[1, 0]], 19.0);
+
+        let multiple =
+            b">MA1 first\nA [1 2]\nC [3 4]\nG [5 6]\nU [7 8]\n\n>MA2\nT 1\nG 0\nC 0\nA 0\n";
+        let matrices = read_jaspar(&multiple[..]).unwrap();
+        assert_eq!(matrices[0].description.as_deref(), Some("first"));
+        let second: DNAMotif = matrices[1].to_motif(0.0).unwrap();
+        assert_eq!(second.degenerate_consensus(), b"T");
+        let first: DNAMotif = matrices[0].to_motif(0.0).unwrap();
+        assert_eq!(first.scores[[0, 1]], 7.0 / 16.0);
+
+        let missing = b">MA3\nA 1\nC 1\nG 1\n";
+        assert!(matches!(
+            read_jaspar(&missing[..]).unwrap()[0].to_motif::<DNAMotif>(0.0),
+            Err(Error::MissingLetter { letter: b'T' })
+        ));
+        assert!(matches!(
+            read_jaspar(&b"A 1 2\nC 1\n"[..]),
+            Err(Error::InvalidFormat { .. })
+        ));
+    }
+
+    #[test]
+    fn test_write_meme_protein() {
+        let motif = ProtMotif::from_seqs(&[b"MKV".to_vec(), b"MRV".to_vec()], None).unwrap();
+        let mut meme = Vec::new();
+        write_meme(&mut meme, &[("prot", &motif)], None).unwrap();
+        let file = read_meme(&meme[..]).unwrap();
+        assert_eq!(file.alphabet, b"ACDEFGHIKLMNPQRSTVWY");
+        let parsed: ProtMotif = file.motifs[0].to_motif(0.0).unwrap();
+        for (a, b) in parsed.scores.iter().zip(motif.scores.iter()) {
+            assert!((a - b).abs() < 1e-5);
+        }
+    }
+}
@@ -10,6 +10,8 @@
 //!
 //! The position-specific scoring matrix (PSSM), aka position weight matrix (PWM),
 //! algorithm is implemented for both DNA and amino-acid sequences.
+//! The significance of matches can be assessed with p-values from the score distribution of
+//! random sequences (see `Motif::score_distribution` and `Motif::score_threshold`).
 //!
 //! # Examples
 //!
@@ -38,10 +40,13 @@ use std::f32::NEG_INFINITY;
 use itertools::Itertools;
 use ndarray::prelude::Array2;
 
+mod distribution;
 mod dnamotif;
 pub mod errors;
+pub mod formats;
 mod protmotif;
 
+pub use self::distribution::{ScoreDistribution, RESOLUTION};
 pub use self::dnamotif::DNAMotif;
 pub use self::errors::{Error, Result};
 pub use self::protmotif::ProtMotif;
@@ -59,6 +64,8 @@ pub struct ScoredPos {
     pub loc: usize,
     pub sum: f32,
     pub scores: Vec<f32>,
+    /// p-value of the match, if requested (see `Motif::score_with_pvalue`)
+    pub pvalue: Option<f64>,
 }
 
 impl Default for ScoredPos {
@@ -67,6 +74,7 @@ impl Default for ScoredPos {
             loc: 0,
             sum: NEG_INFINITY,
             scores: Vec::new(),
+            pvalue: None,
         }
     }
 }
@@ -271,9 +279,70 @@ pub trait Motif {
             loc: best_start,
             sum: (best_score - min_score) / (max_score - min_score),
             scores: best_m,
+            pvalue: None,
         })
     }
 
+    /// Returns the distribution of normalized scores (see `ScoredPos::sum`) of random
+    /// sequences, for computing p-values and score thresholds (see `ScoreDistribution`).
+    ///
+    /// # Arguments
+    /// * `background` - array slice with the probability of each monomer (in the order of
+    ///   `MONOS`), which is normalized to sum up to one; uniform if None is supplied
+    ///
+    /// # Errors
+    /// * `Error::InvalidBackground` - `background` has the wrong length
+    /// * `Error::NullMotif` - all sequences have the same score
+    ///
+    /// # Example
+    /// ```
+    /// use bio::pattern_matching::pssm::{DNAMotif, Motif};
+    ///
+    /// let pssm = DNAMotif::from_seqs(&[b"ACGT".to_vec(), b"ACGA".to_vec()], None).unwrap();
+    /// let dist = pssm.score_distribution(None).unwrap();
+    /// // ACGA and ACGT score best, hence 2 in 4^4 random sequences score as high
+    /// assert!((dist.pvalue(1.0) - 2.0 / 256.0).abs() < 1e-9);
+    /// ```
+    fn score_distribution(&self, background: Option<&[f32]>) -> Result<ScoreDistribution> {
+        ScoreDistribution::new(self, background)
+    }
+
+    /// Returns the normalized score that random sequences of uniformly distributed monomers
+    /// reach with probability at most the given p-value, e.g. to only report matches with
+    /// `ScoredPos::sum` at or above it. See `score_distribution` for other backgrounds.
+    ///
+    /// # Errors
+    /// * `Error::NullMotif` - all sequences have the same score
+    fn score_threshold(&self, pvalue: f64) -> Result<f32> {
+        Ok(self.score_distribution(None)?.score_threshold(pvalue))
+    }
+
+    /// Returns the best match like `score`, together with its p-value according to the
+    /// given score distribution (see `score_distribution`).
+    ///
+    /// # Errors
+    /// See `score`.
+    ///
+    /// # Example
+    /// ```
+    /// use bio::pattern_matching::pssm::{DNAMotif, Motif};
+    ///
+    /// let pssm = DNAMotif::from_seqs(&[b"ACGTA".to_vec(), b"ACGAA".to_vec()], None).unwrap();
+    /// let dist = pssm.score_distribution(None).unwrap();
+    /// let scored = pssm.score_with_pvalue(b"TTTACGAATT", &dist).unwrap();
+    /// assert_eq!(scored.loc, 3);
+    /// assert!(scored.pvalue.unwrap() < 0.001);
+    /// ```
+    fn score_with_pvalue<C, T>(&self, seq_it: T, dist: &ScoreDistribution) -> Result<ScoredPos>
+    where
+        C: Borrow<u8>,
+        T: IntoIterator<Item = C>,
+    {
+        let mut scored = self.score(seq_it)?;
+        scored.pvalue = Some(dist.pvalue(scored.sum));
+        Ok(scored)
+    }
+
     /// Returns a float representing the information content of a motif; roughly the
     /// inverse of Shannon Entropy.
     /// Adapted from the information content described here:
@@ -0,0 +1,204 @@
+//! Approximate pattern search by exact seeding in an FM-index and verification with Myers'
+//! bit-parallel algorithm.
+//!
+//! By the pigeonhole principle, an occurrence of a pattern with at most k edits contains at
+//! least one of k + 1 non-overlapping pieces of the pattern exactly. The pieces (seeds) are
+//! located with backward search in the FM-index, and only the text windows around seed
+//! occurrences, flanked by k positions on both sides, are verified with Myers' algorithm
+//! (`myers::long::Myers`, hence patterns of any length are supported). The result is the same
+//! as running Myers' algorithm on the whole text, at a fraction of the cost for long texts.
+//!
+//! # Example
+//!
+//! ```
+//! use bio::alphabets::dna;
+//! use bio::data_structures::fmindex::FMIndex;
+//! use bio::pattern_matching::seed_and_verify::SeedAndVerify;
+//!
+//! let text = b"ACCGTTAGCAGATTACAGGCATCGATCGATTGCAGGACCTAGACAAT";
+//! let (fm, sa, collection) = FMIndex::from_texts(&[&text[..]], &dna::alphabet(), 4);
+//! let search = SeedAndVerify {
+//!     max_dist: 1,
+//!     ..Default::default()
+//! };
+//! let hits = search.search(&fm, &sa, collection.text(), &dna::alphabet(), b"GATTGCAG");
+//! assert!(hits.iter().any(|hit| (hit.ystart, hit.yend, hit.score) == (27, 35, 0)));
+//! assert!(hits.iter().all(|hit| hit.score <= 1));
+//! ```
+
+use std::ops::Range;
+
+use crate::alignment::Alignment;
+use crate::alphabets::Alphabet;
+use crate::data_structures::fmindex::{BackwardSearchResult, FMIndexable};
+use crate::data_structures::suffix_array::SuffixArray;
+use crate::pattern_matching::myers::long::Myers;
+use crate::utils::TextSlice;
+
+/// Parameters of the seed and verify search.
+#[derive(Copy, Clone, Eq, PartialEq, Ord, PartialOrd, Hash, Debug, Serialize, Deserialize)]
+pub struct SeedAndVerify {
+    /// Maximum edit distance of reported occurrences.
+    pub max_dist: usize,
+    /// Seeds occurring more often are skipped, bounding the verification effort for
+    /// repetitive patterns. Occurrences that only contain skipped seeds are not found.
+    pub max_seed_occ: usize,
+}
+
+impl Default for SeedAndVerify {
+    /// At most one edit and no bound on seed occurrences.
+    fn default() -> Self {
+        SeedAndVerify {
+            max_dist: 1,
+            max_seed_occ: usize::MAX,
+        }
+    }
+}
+
+impl SeedAndVerify {
+    /// The text windows to verify, sorted and merged.
+    fn windows<F: FMIndexable, S: SuffixArray>(
+        &self,
+        fmindex: &F,
+        sa: &S,
+        text_len: usize,
+        alphabet: &Alphabet,
+        pattern: TextSlice<'_>,
+    ) -> Vec<Range<usize>> {
+        let (m, k) = (pattern.len(), self.max_dist);
+        let seeds = k + 1;
+        if m < seeds {
+            // too short for seeding
+            return std::iter::once(0..text_len).collect();
+        }
+
+        let mut windows = Vec::new();
+        for i in 0..seeds {
+            let offset = i * m / seeds;
+            let seed = &pattern[offset..(i + 1) * m / seeds];
+            if !alphabet.is_word(seed) {
+                continue;
+            }
+            if let BackwardSearchResult::Complete(interval) = fmindex.backward_search(seed.iter()) {
+                if interval.upper - interval.lower > self.max_seed_occ {
+                    continue;
+                }
+                windows.extend(interval.occ(sa).into_iter().map(|pos| {
+                    pos.saturating_sub(offset + k)..(pos + m + k - offset).min(text_len)
+                }));
+            }
+        }
+
+        windows.sort_unstable_by_key(|window| window.start);
+        let mut merged: Vec<Range<usize>> = Vec::with_capacity(windows.len());
+        for window in windows {
+            match merged.last_mut() {
+                Some(last) if last.end >= window.start => last.end = last.end.max(window.end),
+                _ => merged.push(window),
+            }
+        }
+        merged
+    }
+
+    /// Find all occurrences of the pattern with at most `max_dist` edits in the text indexed
+    /// by the given FM-index and suffix array. Each occurrence is reported like by
+    /// `myers::Myers::find_all`, as the best alignment ending at a text position (with the
+    /// distance stored in `Alignment::score`), ordered by end position. Seeds containing
+    /// symbols outside of the alphabet of the index are skipped.
+    ///
+    /// # Arguments
+    ///
+    /// * `fmindex` - the FM-index of the text
+    /// * `sa` - the suffix array of the text
+    /// * `text` - the indexed text (e.g. `TextCollection::text`)
+    /// * `alphabet` - the alphabet of the index
+    /// * `pattern` - the pattern to search
+    pub fn search<F: FMIndexable, S: SuffixArray>(
+        &self,
+        fmindex: &F,
+        sa: &S,
+        text: TextSlice<'_>,
+        alphabet: &Alphabet,
+        pattern: TextSlice<'_>,
+    ) -> Vec<Alignment> {
+        if pattern.is_empty() {
+            return Vec::new();
+        }
+        let mut myers = Myers::<u64>::new(pattern);
+        let mut hits = Vec::new();
+        for window in self.windows(fmindex, sa, text.len(), alphabet, pattern) {
+            let mut matches = myers.find_all(&text[window.clone()], self.max_dist);
+            let mut hit = Alignment::default();
+            while matches.next_alignment(&mut hit) {
+                hit.ystart += window.start;
+                hit.yend += window.start;
+                hit.ylen = text.len();
+                hits.push(hit.clone());
+            }
+        }
+        hits
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::alphabets::dna;
+    use crate::data_structures::fmindex::FMIndex;
+    use rand::prelude::*;
+    use rand::rngs::StdRng;
+
+    #[test]
+    fn test_same_as_myers() {
+        let mut rng = StdRng::seed_from_u64(42);
+        let mut text: Vec<u8> = (0..2000)
+            .map(|_| *b"ACGT".choose(&mut rng).unwrap())
+            .collect();
+        // plant some approximate copies of a pattern
+        let pattern = text[100..130].to_vec();
+        for (i, pos) in [500, 1200, 1700].iter().enumerate() {
+            text[*pos..pos + 30].copy_from_slice(&pattern);
+            text[pos + 5 * i] = b'A';
+            text[pos + 20] = b'C';
+        }
+        let (fm, sa, collection) = FMIndex::from_texts(&[&text[..]], &dna::alphabet(), 4);
+        let text = collection.text();
+
+        for max_dist in 0..4 {
+            let search = SeedAndVerify {
+                max_dist,
+                ..Default::default()
+            };
+            let hits = search.search(&fm, &sa, text, &dna::alphabet(), &pattern);
+            let expected = Myers::<u64>::new(&pattern[..])
+                .find_all(text, max_dist)
+                .collect::<Vec<_>>();
+            assert!(!hits.is_empty());
+            assert_eq!(
+                hits.iter()
+                    .map(|hit| (hit.ystart, hit.yend, hit.score as usize))
+                    .collect::<Vec<_>>(),
+                expected
+            );
+        }
+    }
+
+    #[test]
+    fn test_max_seed_occ() {
+        let text = b"ACGTACGTACGTACGTTTTT".to_vec();
+        let (fm, sa, collection) = FMIndex::from_texts(&[&text[..]], &dna::alphabet(), 4);
+        let search = SeedAndVerify {
+            max_dist: 0,
+            max_seed_occ: 3,
+        };
+        assert!(search
+            .search(&fm, &sa, collection.text(), &dna::alphabet(), b"ACGT")
+            .is_empty());
+        assert_eq!(
+            search
+                .search(&fm, &sa, collection.text(), &dna::alphabet(), b"GTTT")
+                .len(),
+            1
+        );
+    }
+}
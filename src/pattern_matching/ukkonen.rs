@@ -11,6 +11,8 @@
 //! parts of the matrix for which the error exceeds k. To achieve this, a value `lastk` is
 //! maintained that provides the lower feasible boundary of the matrix.
 //! Initially, lastk = min(k, m). In each iteration (over a column), lastk can increase by at most 1.
+//! If the start positions or alignment paths of the matches are needed, use `Ukkonen::find_all`,
+//! which keeps the last columns of the matrix for the traceback.
 //!
 //! # Example
 //!
@@ -26,9 +28,12 @@
 
 use std::borrow::Borrow;
 use std::cmp::min;
+use std::collections::VecDeque;
 use std::iter;
 use std::iter::repeat;
 
+use crate::alignment::AlignmentOperation::*;
+use crate::alignment::{Alignment, AlignmentMode, AlignmentOperation};
 use crate::utils::TextSlice;
 
 /// Default cost function (unit costs).
@@ -86,6 +91,50 @@ where
             k,
         }
     }
+
+    /// Find all matches between pattern and text with up to k errors.
+    /// Matches are returned as an iterator over tuples of `(start, end, distance)`, where
+    /// `end` is exclusive (like `Myers::find_all`). In addition, the alignment path of
+    /// each hit can be obtained from the iterator (see `FullMatches::next_path` and
+    /// `FullMatches::next_alignment`). For this, the last `m + k` columns of the DP matrix
+    /// are kept [O((m + k) * m) memory].
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use bio::alignment::AlignmentOperation::*;
+    /// use bio::pattern_matching::ukkonen::{unit_cost, Ukkonen};
+    ///
+    /// let mut ukkonen = Ukkonen::with_capacity(10, unit_cost);
+    /// let text = b"ACCGTGGATGAGCGCCATAG";
+    /// let pattern = b"TGAGCGA";
+    /// let mut matches = ukkonen.find_all(pattern, text, 1);
+    /// let mut ops = Vec::new();
+    /// assert_eq!(matches.next_path(&mut ops), Some((8, 14, 1)));
+    /// assert_eq!(ops, [Match, Match, Match, Match, Match, Match, Ins]);
+    /// ```
+    pub fn find_all<'a, C, T>(
+        &'a mut self,
+        pattern: TextSlice<'a>,
+        text: T,
+        k: usize,
+    ) -> FullMatches<'a, F, C, T::IntoIter>
+    where
+        C: Borrow<u8>,
+        T: IntoIterator<Item = C>,
+        T::IntoIter: ExactSizeIterator,
+    {
+        let text = text.into_iter();
+        let text_len = text.len();
+        let capacity = pattern.len() + k + 1;
+        FullMatches {
+            matches: self.find_all_end(pattern, text, k),
+            columns: VecDeque::with_capacity(capacity),
+            capacity,
+            text_len,
+            end: None,
+        }
+    }
 }
 
 /// Iterator over pairs of end positions and distance of matches.
@@ -113,35 +162,215 @@ where
     type Item = (usize, usize);
 
     fn next(&mut self) -> Option<(usize, usize)> {
-        let cost = &self.ukkonen.cost;
-        for (i, c) in &mut self.text {
-            let col = i % 2;
-            let prev = 1 - col;
-
-            // start with zero edit distance (semi-global alignment)
-            self.ukkonen.D[col][0] = 0;
-            self.lastk = min(self.lastk + 1, self.m);
-            // in each column, go at most one cell further than before
-            // do not look at cells with too big k
-            for j in 1..=self.lastk {
-                self.ukkonen.D[col][j] = min(
-                    min(self.ukkonen.D[prev][j] + 1, self.ukkonen.D[col][j - 1] + 1),
-                    self.ukkonen.D[prev][j - 1] + (cost)(self.pattern[j - 1], *c.borrow()) as usize,
-                );
+        while let Some((i, c)) = self.text.next() {
+            self.step(i, *c.borrow());
+            if self.lastk == self.m {
+                return Some((i, self.ukkonen.D[i % 2][self.m]));
             }
+        }
 
-            // reduce lastk as long as k is exceeded: while lastk can increase by at most 1, it can
-            // decrease more in one iteration.
-            while self.ukkonen.D[col][self.lastk] > self.k {
-                self.lastk -= 1;
+        None
+    }
+}
+
+impl<'a, F, C, T> Matches<'a, F, C, T>
+where
+    F: 'a + Fn(u8, u8) -> u32,
+    C: Borrow<u8>,
+    T: Iterator<Item = C>,
+{
+    /// Compute the column of text position `i`, returning the index of the last computed row.
+    fn step(&mut self, i: usize, c: u8) -> usize {
+        let cost = &self.ukkonen.cost;
+        let col = i % 2;
+        let prev = 1 - col;
+
+        // start with zero edit distance (semi-global alignment)
+        self.ukkonen.D[col][0] = 0;
+        self.lastk = min(self.lastk + 1, self.m);
+        let last = self.lastk;
+        // in each column, go at most one cell further than before
+        // do not look at cells with too big k
+        for j in 1..=last {
+            self.ukkonen.D[col][j] = min(
+                min(self.ukkonen.D[prev][j] + 1, self.ukkonen.D[col][j - 1] + 1),
+                self.ukkonen.D[prev][j - 1] + (cost)(self.pattern[j - 1], c) as usize,
+            );
+        }
+        // the next column may look one cell further, which exceeds k
+        if last < self.m {
+            self.ukkonen.D[col][last + 1] = self.k + 1;
+        }
+
+        // reduce lastk as long as k is exceeded: while lastk can increase by at most 1, it can
+        // decrease more in one iteration.
+        while self.ukkonen.D[col][self.lastk] > self.k {
+            self.lastk -= 1;
+        }
+
+        last
+    }
+}
+
+/// Iterator over tuples of start position, (exclusive) end position and distance of matches.
+/// In addition, methods for obtaining the alignment path of the hits are provided.
+#[derive(Debug)]
+pub struct FullMatches<'a, F, C, T>
+where
+    F: Fn(u8, u8) -> u32,
+    C: Borrow<u8>,
+    T: Iterator<Item = C>,
+{
+    matches: Matches<'a, F, C, T>,
+    /// The last text symbols together with the computed rows of their DP columns.
+    columns: VecDeque<(u8, Vec<usize>)>,
+    capacity: usize,
+    text_len: usize,
+    /// End position and distance of the current hit.
+    end: Option<(usize, usize)>,
+}
+
+impl<'a, F, C, T> FullMatches<'a, F, C, T>
+where
+    F: 'a + Fn(u8, u8) -> u32,
+    C: Borrow<u8>,
+    T: Iterator<Item = C>,
+{
+    /// Find the next hit, returning its (inclusive) end position and distance, like
+    /// `Ukkonen::find_all_end`.
+    pub fn next_end(&mut self) -> Option<(usize, usize)> {
+        self.end = None;
+        while let Some((i, c)) = self.matches.text.next() {
+            let c = *c.borrow();
+            let last = self.matches.step(i, c);
+            let mut column = if self.columns.len() == self.capacity {
+                self.columns.pop_front().unwrap().1
+            } else {
+                Vec::with_capacity(self.matches.m + 1)
+            };
+            column.clear();
+            column.extend_from_slice(&self.matches.ukkonen.D[i % 2][..=last]);
+            self.columns.push_back((c, column));
+            if self.matches.lastk == self.matches.m {
+                self.end = Some((i, self.matches.ukkonen.D[i % 2][self.matches.m]));
+                return self.end;
             }
+        }
+        None
+    }
 
-            if self.lastk == self.m {
-                return Some((i, self.ukkonen.D[col][self.m]));
+    /// Find the next hit and write its alignment path to `ops`, returning the start position,
+    /// the (exclusive) end position and the distance. Existing data in the vector will be
+    /// cleared beforehand.
+    pub fn next_path(
+        &mut self,
+        ops: &mut Vec<AlignmentOperation>,
+    ) -> Option<(usize, usize, usize)> {
+        self.next_end()
+            .map(|(end, dist)| (self.path(ops).unwrap(), end + 1, dist))
+    }
+
+    /// Searches the next match and updates the given `Alignment` with its position
+    /// and alignment path if found. The distance is stored in `Alignment::score`.
+    /// If no next hit is found, `false` is returned and `aln` remains unchanged.
+    pub fn next_alignment(&mut self, aln: &mut Alignment) -> bool {
+        self.next_end().is_some() && self.alignment(aln)
+    }
+
+    /// Returns the starting position of the current hit. If no hit has been found yet or the
+    /// search is finished, `None` is returned.
+    pub fn start(&self) -> Option<usize> {
+        self.traceback(None)
+    }
+
+    /// Adds the path of the current hit alignment to `ops` and returns the starting position of
+    /// the current hit. If no hit has been found yet or the search is finished, `None` is
+    /// returned. Existing data in the vector will be cleared beforehand.
+    pub fn path(&self, ops: &mut Vec<AlignmentOperation>) -> Option<usize> {
+        ops.clear();
+        let start = self.traceback(Some(ops))?;
+        ops.reverse();
+        Some(start)
+    }
+
+    /// Updates the given `Alignment` with the position and alignment path of the current hit.
+    /// The edit distance is stored in `Alignment::score`. If no hit has been found yet or the
+    /// search is finished, `false` is returned and nothing is done.
+    pub fn alignment(&self, aln: &mut Alignment) -> bool {
+        let (end, dist) = match self.end {
+            Some(end) => end,
+            None => return false,
+        };
+        let start = self.path(&mut aln.operations).unwrap();
+        aln.score = dist as i32;
+        aln.xstart = 0;
+        aln.xend = self.matches.m;
+        aln.xlen = self.matches.m;
+        aln.ystart = start;
+        aln.yend = end + 1;
+        aln.ylen = self.text_len;
+        aln.mode = AlignmentMode::Semiglobal;
+        true
+    }
+
+    /// Trace back the alignment path of the current hit (adding the operations in reverse
+    /// order), returning its start position. Substitutions are preferred over insertions and
+    /// insertions over deletions.
+    fn traceback(&self, mut ops: Option<&mut Vec<AlignmentOperation>>) -> Option<usize> {
+        let (end, _) = self.end?;
+        let cost = &self.matches.ukkonen.cost;
+        let pattern = self.matches.pattern;
+        // Columns are indexed by the number of processed text symbols, where column 0 is the
+        // initial one (i.e. D[j] = j).
+        let offset = end + 1 - self.columns.len();
+        let get = |i: usize, j: usize| -> usize {
+            if i == 0 || j == 0 {
+                j
+            } else {
+                let column = &self.columns[i - 1 - offset].1;
+                column.get(j).cloned().unwrap_or(self.matches.k + 1)
+            }
+        };
+        let mut push = |op| {
+            if let Some(ops) = ops.as_mut() {
+                ops.push(op);
+            }
+        };
+        let (mut i, mut j) = (end + 1, self.matches.m);
+        while j > 0 {
+            let d = get(i, j);
+            if i > 0 {
+                let c = (cost)(pattern[j - 1], self.columns[i - 1 - offset].0) as usize;
+                if get(i - 1, j - 1) + c == d {
+                    push(if c == 0 { Match } else { Subst });
+                    i -= 1;
+                    j -= 1;
+                    continue;
+                }
+            }
+            if get(i, j - 1) + 1 == d {
+                push(Ins);
+                j -= 1;
+            } else {
+                push(Del);
+                i -= 1;
             }
         }
+        Some(i)
+    }
+}
 
-        None
+impl<'a, F, C, T> Iterator for FullMatches<'a, F, C, T>
+where
+    F: 'a + Fn(u8, u8) -> u32,
+    C: Borrow<u8>,
+    T: Iterator<Item = C>,
+{
+    type Item = (usize, usize, usize);
+
+    fn next(&mut self) -> Option<(usize, usize, usize)> {
+        self.next_end()
+            .map(|(end, dist)| (self.start().unwrap(), end + 1, dist))
     }
 }
 
@@ -158,6 +387,65 @@ mod tests {
         assert_eq!(occ, [(13, 1), (14, 1)]);
     }
 
+    #[test]
+    fn test_find_all() {
+        let mut ukkonen = Ukkonen::with_capacity(10, unit_cost);
+        let text = b"ACCGTGGATGAGCGCCATAG";
+        let pattern = b"TGAGCGT";
+        let occ: Vec<(usize, usize, usize)> = ukkonen.find_all(pattern, text, 1).collect();
+        assert_eq!(occ, [(8, 14, 1), (8, 15, 1)]);
+
+        // insertion and deletion
+        let mut ops = Vec::new();
+        let mut matches = ukkonen.find_all(b"ACGTACGT", b"TTACGTTACGAAA", 2);
+        assert_eq!(matches.next_path(&mut ops), Some((2, 10, 2)));
+        assert_eq!(
+            ops,
+            [Match, Match, Match, Del, Match, Match, Match, Match, Ins]
+        );
+        let mut aln = Alignment::default();
+        assert!(matches.alignment(&mut aln));
+        assert_eq!((aln.ystart, aln.yend, aln.ylen), (2, 10, 13));
+        assert_eq!(aln.score, 2);
+        while matches.next_alignment(&mut aln) {}
+        assert_eq!(matches.start(), None);
+    }
+
+    #[test]
+    fn test_path_naive() {
+        // the path costs add up to the distance for random texts and custom costs
+        let cost = |a: u8, b: u8| if a == b { 0 } else { 2 };
+        let mut ukkonen = Ukkonen::with_capacity(10, cost);
+        let text = b"ACGTTGCAACGTAGGTACGATCGGATCGTAGCTAGGCTAGCTTAGAGCGGATCTA";
+        let mut ops = Vec::new();
+        for pattern in text.chunks(7) {
+            let mut matches = ukkonen.find_all(pattern, &text[..], 3);
+            while let Some((start, end, dist)) = matches.next_path(&mut ops) {
+                let mut costs = 0;
+                let (mut i, mut j) = (0, start);
+                for op in &ops {
+                    match op {
+                        Match | Subst => {
+                            costs += cost(pattern[i], text[j]) as usize;
+                            i += 1;
+                            j += 1;
+                        }
+                        Ins => {
+                            costs += 1;
+                            i += 1;
+                        }
+                        Del => {
+                            costs += 1;
+                            j += 1;
+                        }
+                        _ => unreachable!(),
+                    }
+                }
+                assert_eq!((i, j, costs), (pattern.len(), end, dist));
+            }
+        }
+    }
+
     #[test]
     fn test_find_start() {
         let mut u = Ukkonen::with_capacity(10, unit_cost);
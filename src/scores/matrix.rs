@@ -0,0 +1,305 @@
+//! Substitution matrices loaded at runtime from files in the NCBI format, as distributed with
+//! BLAST and EMBOSS. Comment lines start with `#`, followed by a header line with the symbols
+//! of the columns, and one line per symbol with the row symbol and the scores.
+//!
+//! # Example
+//!
+//! ```
+//! use bio::alignment::pairwise::Aligner;
+//! use bio::scores::matrix::ScoringMatrix;
+//!
+//! let matrix: ScoringMatrix = "
+//!    A  C  G  T  N
+//! A  2 -3 -1 -3 -1
+//! C -3  2 -3 -1 -1
+//! G -1 -3  2 -3 -1
+//! T -3 -1 -3  2 -1
+//! N -1 -1 -1 -1 -1
+//! "
+//! .parse()
+//! .unwrap();
+//! assert_eq!(matrix.score(b'A', b'G'), -1);
+//! // lowercase symbols are scored like uppercase ones
+//! assert_eq!(matrix.score(b'a', b'a'), 2);
+//!
+//! let mut aligner = Aligner::new(-5, -1, &matrix);
+//! let alignment = aligner.global(b"ACGTTA", b"ACGGTA");
+//! assert_eq!(alignment.score, 7);
+//! ```
+
+use std::fs;
+use std::io::{self, BufRead};
+use std::path::Path;
+use std::str::FromStr;
+
+use thiserror::Error;
+
+use crate::alignment::pairwise::MatchFunc;
+
+#[derive(Error, Debug)]
+pub enum Error {
+    #[error("can't read matrix")]
+    Read(#[from] io::Error),
+    #[error("invalid matrix in line {line}: {reason}")]
+    InvalidFormat { line: usize, reason: String },
+    #[error("matrix has no row for symbol '{}'", char::from(*symbol))]
+    MissingRow { symbol: u8 },
+}
+
+pub type Result<T, E = Error> = std::result::Result<T, E>;
+
+fn invalid<T>(line: usize, reason: &str) -> Result<T> {
+    Err(Error::InvalidFormat {
+        line,
+        reason: reason.to_owned(),
+    })
+}
+
+/// A substitution matrix over an arbitrary set of (single byte) symbols.
+///
+/// Symbols that are not part of the matrix are scored like their uppercase version if that is
+/// part of the matrix, otherwise like `X` if that is part of the matrix, otherwise with the
+/// smallest score of the matrix.
+#[derive(Clone, Eq, PartialEq, Hash, Debug, Serialize, Deserialize)]
+pub struct ScoringMatrix {
+    symbols: Vec<u8>,
+    /// Row-major scores, in the order of the symbols.
+    scores: Vec<i32>,
+    /// Index of each byte in the symbols, or the number of symbols for unknown bytes.
+    ranks: Vec<usize>,
+    min_score: i32,
+}
+
+impl ScoringMatrix {
+    /// Create a new matrix from the symbols and the row-major scores.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the number of scores is not the square of the number of symbols or if a
+    /// symbol occurs more than once.
+    pub fn new(symbols: &[u8], scores: Vec<i32>) -> Self {
+        let n = symbols.len();
+        assert_eq!(
+            scores.len(),
+            n * n,
+            "Expecting one score per pair of symbols."
+        );
+        let mut ranks = vec![n; 256];
+        for (i, &a) in symbols.iter().enumerate() {
+            assert_eq!(ranks[a as usize], n, "Expecting unique symbols.");
+            ranks[a as usize] = i;
+        }
+        let fallback = ranks[b'X' as usize];
+        for a in 0..=255u8 {
+            if ranks[a as usize] == n {
+                let upper = ranks[a.to_ascii_uppercase() as usize];
+                ranks[a as usize] = if upper < n { upper } else { fallback };
+            }
+        }
+        ScoringMatrix {
+            symbols: symbols.to_owned(),
+            min_score: scores.iter().cloned().min().unwrap_or(0),
+            scores,
+            ranks,
+        }
+    }
+
+    /// Read a matrix in the NCBI format.
+    pub fn from_reader<R: io::Read>(reader: R) -> Result<Self> {
+        let mut symbols: Option<Vec<u8>> = None;
+        let mut rows: Vec<Option<Vec<i32>>> = Vec::new();
+        for (i, line) in io::BufReader::new(reader).lines().enumerate() {
+            let line = line?;
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let tokens = line.split_whitespace().collect::<Vec<_>>();
+            match symbols {
+                None => {
+                    if tokens.iter().any(|token| token.len() != 1) {
+                        return invalid(i + 1, "expecting single symbols in the header");
+                    }
+                    let header: Vec<u8> = tokens.iter().map(|token| token.as_bytes()[0]).collect();
+                    if has_duplicates(&header) {
+                        return invalid(i + 1, "duplicate symbol in the header");
+                    }
+                    rows = vec![None; tokens.len()];
+                    symbols = Some(header);
+                }
+                Some(ref symbols) => {
+                    let symbol = match tokens[0].as_bytes() {
+                        [symbol] => *symbol,
+                        _ => return invalid(i + 1, "expecting a single row symbol"),
+                    };
+                    let row = match symbols.iter().position(|&a| a == symbol) {
+                        Some(row) => row,
+                        None => return invalid(i + 1, "row symbol is not part of the header"),
+                    };
+                    if rows[row].is_some() {
+                        return invalid(i + 1, "duplicate row");
+                    }
+                    if tokens.len() != symbols.len() + 1 {
+                        return invalid(i + 1, "expecting one score per column");
+                    }
+                    let scores = tokens[1..]
+                        .iter()
+                        .map(|token| token.parse::<i32>())
+                        .collect::<Result<Vec<_>, _>>();
+                    match scores {
+                        Ok(scores) => rows[row] = Some(scores),
+                        Err(_) => return invalid(i + 1, "invalid score"),
+                    }
+                }
+            }
+        }
+
+        let symbols = match symbols {
+            Some(symbols) => symbols,
+            None => return invalid(0, "missing header"),
+        };
+        let mut scores = Vec::with_capacity(symbols.len() * symbols.len());
+        for (&symbol, row) in symbols.iter().zip(rows) {
+            match row {
+                Some(row) => scores.extend(row),
+                None => return Err(Error::MissingRow { symbol }),
+            }
+        }
+        Ok(Self::new(&symbols, scores))
+    }
+
+    /// Read a matrix in the NCBI format from the given file.
+    pub fn from_file<P: AsRef<Path>>(path: P) -> Result<Self> {
+        Self::from_reader(fs::File::open(path)?)
+    }
+
+    /// The symbols of the matrix, in the order of the rows.
+    pub fn symbols(&self) -> &[u8] {
+        &self.symbols
+    }
+
+    /// The score of substituting `a` by `b`.
+    #[inline]
+    pub fn score(&self, a: u8, b: u8) -> i32 {
+        let n = self.symbols.len();
+        let (a, b) = (self.ranks[a as usize], self.ranks[b as usize]);
+        if a < n && b < n {
+            self.scores[a * n + b]
+        } else {
+            self.min_score
+        }
+    }
+
+    /// Whether the score of substituting `a` by `b` equals that of substituting `b` by `a`
+    /// for all symbols.
+    pub fn is_symmetric(&self) -> bool {
+        let n = self.symbols.len();
+        (0..n).all(|i| (0..i).all(|j| self.scores[i * n + j] == self.scores[j * n + i]))
+    }
+}
+
+fn has_duplicates(symbols: &[u8]) -> bool {
+    let mut seen = [false; 256];
+    symbols
+        .iter()
+        .any(|&a| std::mem::replace(&mut seen[a as usize], true))
+}
+
+impl FromStr for ScoringMatrix {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        Self::from_reader(s.as_bytes())
+    }
+}
+
+impl MatchFunc for ScoringMatrix {
+    #[inline]
+    fn score(&self, a: u8, b: u8) -> i32 {
+        ScoringMatrix::score(self, a, b)
+    }
+}
+
+impl MatchFunc for &ScoringMatrix {
+    #[inline]
+    fn score(&self, a: u8, b: u8) -> i32 {
+        ScoringMatrix::score(self, a, b)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::scores::{blosum62, pam250};
+
+    const DNA: &str = "   A  C  G  T
+A  1 -2 -2 -2
+C -2  1 -2 -2
+G -2 -2  1 -2
+T -2 -2 -2  3
+";
+
+    #[test]
+    fn test_parse() {
+        let matrix: ScoringMatrix = DNA.parse().unwrap();
+        assert_eq!(matrix.symbols(), b"ACGT");
+        assert_eq!(matrix.score(b'T', b'T'), 3);
+        assert_eq!(matrix.score(b't', b'C'), -2);
+        // unknown symbols get the smallest score without X
+        assert_eq!(matrix.score(b'N', b'N'), -2);
+        assert!(matrix.is_symmetric());
+
+        // rows may be in any order
+        let shuffled = "#comment\n\n   A  C\nC 3 4\nA 1 2\n"
+            .parse::<ScoringMatrix>()
+            .unwrap();
+        assert_eq!(shuffled.score(b'A', b'C'), 2);
+        assert_eq!(shuffled.score(b'C', b'A'), 3);
+        assert!(!shuffled.is_symmetric());
+    }
+
+    #[test]
+    fn test_invalid() {
+        let invalid_line = |s: &str| match s.parse::<ScoringMatrix>() {
+            Err(Error::InvalidFormat { line, .. }) => Some(line),
+            _ => None,
+        };
+        assert_eq!(invalid_line("A AC\nA 1 2"), Some(1));
+        assert_eq!(invalid_line("A A\nA 1 2"), Some(1));
+        assert_eq!(invalid_line("A C\nA 1"), Some(2));
+        assert_eq!(invalid_line("A C\nA 1 x"), Some(2));
+        assert_eq!(invalid_line("A C\nG 1 2"), Some(2));
+        assert_eq!(invalid_line("A C\nA 1 2\nA 1 2"), Some(3));
+        assert_eq!(invalid_line("# only comments"), Some(0));
+        assert!(matches!(
+            "A C\nA 1 2".parse::<ScoringMatrix>(),
+            Err(Error::MissingRow { symbol: b'C' })
+        ));
+    }
+
+    #[test]
+    fn test_protein() {
+        // the same 24 symbols as the NCBI files, built from the builtin matrices
+        let symbols = b"ARNDCQEGHILKMFPSTWYVBZX*";
+        for &matrix in &[blosum62 as fn(u8, u8) -> i32, pam250] {
+            let mut text = String::from(" ");
+            for &a in symbols {
+                text.push_str(&format!(" {}", char::from(a)));
+            }
+            for &a in symbols {
+                text.push_str(&format!("\n{}", char::from(a)));
+                for &b in symbols {
+                    text.push_str(&format!(" {}", matrix(a, b)));
+                }
+            }
+            let parsed: ScoringMatrix = text.parse().unwrap();
+            for &a in symbols {
+                for &b in symbols {
+                    assert_eq!(parsed.score(a, b), matrix(a, b));
+                }
+            }
+            // unknown symbols are scored like X
+            assert_eq!(parsed.score(b'J', b'A'), matrix(b'X', b'A'));
+        }
+    }
+}
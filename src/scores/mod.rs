@@ -3,13 +3,19 @@
 // This file may not be copied, modified, or distributed
 // except according to those terms.
 
+pub use self::blosum45::blosum45;
 pub use self::blosum62::blosum62;
+pub use self::blosum80::blosum80;
+pub use self::matrix::ScoringMatrix;
 pub use self::pam120::pam120;
 pub use self::pam200::pam200;
 pub use self::pam250::pam250;
 pub use self::pam40::pam40;
 
+pub mod blosum45;
 pub mod blosum62;
+pub mod blosum80;
+pub mod matrix;
 pub mod pam120;
 pub mod pam200;
 pub mod pam250;
@@ -0,0 +1,298 @@
+//! Detection of chimeric reads by shifts in their k-mer composition, e.g. for quality control
+//! of amplicon libraries or for pre-screening reads for structural variant breakpoints.
+//!
+//! For each position p of a read, the k-mer compositions of the windows left and right of p
+//! are compared by their Jensen-Shannon divergence (in bits), i.e. the entropy of the pooled
+//! k-mers minus the weighted entropies of both windows. The score is 0 if both windows have
+//! the same composition and 1 if they share no k-mer. Since it is computed from sampled
+//! windows, it is not zero for homogeneous sequence: the baseline grows with the number of
+//! possible k-mers (4^k) relative to the window length. Positions maximizing the score above
+//! a threshold are reported as candidate breakpoints between segments of different origin.
+//!
+//! # Example
+//!
+//! ```
+//! use bio::seq_analysis::chimera::ChimeraDetector;
+//!
+//! // an AT-rich repeat joined to a GC-rich one
+//! let mut read = b"ATTAGATTACAATTTAAGATA".repeat(10);
+//! read.extend_from_slice(&b"GGCGCCGAGCGGCCTGCGCGC".repeat(10));
+//! let detector = ChimeraDetector::default();
+//! let breakpoints = detector.breakpoints(&read);
+//! assert_eq!(breakpoints.len(), 1);
+//! assert!((breakpoints[0].pos as isize - 210).abs() <= 3);
+//! ```
+
+use crate::utils::TextSlice;
+
+/// 2-bit encoding of DNA symbols.
+fn encode(a: u8) -> Option<usize> {
+    match a {
+        b'A' | b'a' => Some(0),
+        b'C' | b'c' => Some(1),
+        b'G' | b'g' => Some(2),
+        b'T' | b't' => Some(3),
+        _ => None,
+    }
+}
+
+/// `c log2 c`, with `0 log2 0 = 0`.
+fn xlogx(c: usize) -> f64 {
+    if c == 0 {
+        0.0
+    } else {
+        c as f64 * (c as f64).log2()
+    }
+}
+
+/// K-mer counts of a window, with the sum of `c log2 c` over all counts for computing the
+/// entropy in constant time.
+struct Composition {
+    counts: Vec<usize>,
+    total: usize,
+    sum: f64,
+}
+
+impl Composition {
+    fn new(k: usize) -> Self {
+        Composition {
+            counts: vec![0; 1 << (2 * k)],
+            total: 0,
+            sum: 0.0,
+        }
+    }
+
+    fn add(&mut self, kmer: Option<usize>) {
+        if let Some(kmer) = kmer {
+            let c = &mut self.counts[kmer];
+            self.sum += xlogx(*c + 1) - xlogx(*c);
+            *c += 1;
+            self.total += 1;
+        }
+    }
+
+    fn remove(&mut self, kmer: Option<usize>) {
+        if let Some(kmer) = kmer {
+            let c = &mut self.counts[kmer];
+            self.sum += xlogx(*c - 1) - xlogx(*c);
+            *c -= 1;
+            self.total -= 1;
+        }
+    }
+
+    /// Entropy (in bits) of the composition.
+    fn entropy(&self) -> f64 {
+        if self.total == 0 {
+            0.0
+        } else {
+            (self.total as f64).log2() - self.sum / self.total as f64
+        }
+    }
+}
+
+/// A candidate breakpoint of a chimeric read.
+#[derive(Copy, Clone, PartialEq, Debug, Serialize, Deserialize)]
+pub struct Breakpoint {
+    /// Position of the first base right of the breakpoint.
+    pub pos: usize,
+    /// Composition shift score at the breakpoint.
+    pub score: f64,
+}
+
+/// Parameters of the composition shift based chimera detection.
+#[derive(Copy, Clone, PartialEq, Debug, Serialize, Deserialize)]
+pub struct ChimeraDetector {
+    /// Length of the k-mers.
+    pub k: usize,
+    /// Length of the compared windows left and right of each position.
+    pub window: usize,
+    /// Minimum composition shift score of breakpoints.
+    pub threshold: f64,
+}
+
+impl Default for ChimeraDetector {
+    /// Trinucleotides in windows of 100 bases and a threshold of 0.6 (random sequence of
+    /// uniform composition scores below 0.5 with these parameters).
+    fn default() -> Self {
+        ChimeraDetector {
+            k: 3,
+            window: 100,
+            threshold: 0.6,
+        }
+    }
+}
+
+impl ChimeraDetector {
+    /// Calculate the composition shift score of each position p with a full window on both
+    /// sides, i.e. `window <= p <= n - window`, returning pairs of position and score.
+    /// K-mers containing symbols other than `ACGT` (case-insensitive) are not counted.
+    /// Complexity: O(n), where n is the length of the text.
+    ///
+    /// # Panics
+    ///
+    /// Panics if k is not in 1..=12 or the window is shorter than k.
+    pub fn shift_scores(&self, text: TextSlice<'_>) -> Vec<(usize, f64)> {
+        let k = self.k;
+        assert!((1..=12).contains(&k), "Expecting k in 1..=12.");
+        assert!(self.window >= k, "Expecting a window of at least k bases.");
+        if text.len() < 2 * self.window {
+            return Vec::new();
+        }
+        let kmer = |i: usize| -> Option<usize> {
+            text[i..i + k]
+                .iter()
+                .try_fold(0, |kmer, &a| Some(kmer << 2 | encode(a)?))
+        };
+        // number of k-mers in a window
+        let l = self.window - k + 1;
+
+        let (mut left, mut right, mut pooled) = (
+            Composition::new(k),
+            Composition::new(k),
+            Composition::new(k),
+        );
+        for i in 0..l {
+            left.add(kmer(i));
+            right.add(kmer(self.window + i));
+        }
+        for i in 0..l {
+            pooled.add(kmer(i));
+            pooled.add(kmer(self.window + i));
+        }
+
+        let mut scores = Vec::with_capacity(text.len() - 2 * self.window + 1);
+        for pos in self.window..=text.len() - self.window {
+            if pos > self.window {
+                // the k-mers spanning the breakpoint are not counted in either window
+                let left_out = kmer(pos - self.window - 1);
+                let left_in = kmer(pos - k);
+                let right_out = kmer(pos - 1);
+                let right_in = kmer(pos + self.window - k);
+                for (window, out, in_) in [
+                    (&mut left, left_out, left_in),
+                    (&mut right, right_out, right_in),
+                ] {
+                    window.remove(out);
+                    window.add(in_);
+                    pooled.remove(out);
+                    pooled.add(in_);
+                }
+            }
+            let score = if left.total == 0 || right.total == 0 {
+                0.0
+            } else {
+                let w = left.total as f64 / pooled.total as f64;
+                pooled.entropy() - w * left.entropy() - (1.0 - w) * right.entropy()
+            };
+            scores.push((pos, score.max(0.0)));
+        }
+        scores
+    }
+
+    /// Find candidate breakpoints, in increasing order. Positions scoring above the threshold
+    /// that are less than a window apart are considered the same breakpoint, represented by
+    /// the position of maximal score. Reads shorter than two windows yield no breakpoints.
+    pub fn breakpoints(&self, text: TextSlice<'_>) -> Vec<Breakpoint> {
+        let mut breakpoints: Vec<Breakpoint> = Vec::new();
+        let mut last_pos = None;
+        for (pos, score) in self.shift_scores(text) {
+            if score <= self.threshold {
+                continue;
+            }
+            match breakpoints.last_mut() {
+                Some(last) if last_pos.map_or(false, |last_pos| pos - last_pos < self.window) => {
+                    if score > last.score {
+                        *last = Breakpoint { pos, score };
+                    }
+                }
+                _ => breakpoints.push(Breakpoint { pos, score }),
+            }
+            last_pos = Some(pos);
+        }
+        breakpoints
+    }
+
+    /// Whether the read has any candidate breakpoint.
+    pub fn is_chimeric(&self, text: TextSlice<'_>) -> bool {
+        !self.breakpoints(text).is_empty()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::prelude::*;
+    use rand::rngs::StdRng;
+
+    /// Draw a random sequence with the given GC content.
+    fn random_seq(rng: &mut StdRng, len: usize, gc: f64) -> Vec<u8> {
+        (0..len)
+            .map(|_| {
+                let strong = rng.gen_bool(gc);
+                *[b"AT", b"GC"][strong as usize].choose(rng).unwrap()
+            })
+            .collect()
+    }
+
+    /// Naive score of a single position.
+    fn naive_score(detector: &ChimeraDetector, text: &[u8], pos: usize) -> f64 {
+        let counts = |window: &[u8]| {
+            let mut counts = std::collections::HashMap::new();
+            for kmer in window.windows(detector.k) {
+                if kmer.iter().all(|a| b"ACGT".contains(a)) {
+                    *counts.entry(kmer.to_vec()).or_insert(0usize) += 1;
+                }
+            }
+            counts
+        };
+        let entropy = |counts: &std::collections::HashMap<Vec<u8>, usize>| {
+            let n = counts.values().sum::<usize>() as f64;
+            -counts
+                .values()
+                .map(|&c| c as f64 / n * (c as f64 / n).log2())
+                .sum::<f64>()
+        };
+        let left = counts(&text[pos - detector.window..pos]);
+        let right = counts(&text[pos..pos + detector.window]);
+        let mut pooled = left.clone();
+        for (kmer, c) in &right {
+            *pooled.entry(kmer.clone()).or_insert(0) += c;
+        }
+        let w = left.values().sum::<usize>() as f64 / pooled.values().sum::<usize>() as f64;
+        entropy(&pooled) - w * entropy(&left) - (1.0 - w) * entropy(&right)
+    }
+
+    #[test]
+    fn test_shift_scores() {
+        let mut rng = StdRng::seed_from_u64(42);
+        let mut text = random_seq(&mut rng, 300, 0.5);
+        text[120] = b'N';
+        let detector = ChimeraDetector {
+            k: 2,
+            window: 40,
+            threshold: 0.5,
+        };
+        let scores = detector.shift_scores(&text);
+        assert_eq!(scores.len(), 300 - 80 + 1);
+        for &(pos, score) in &scores {
+            assert_relative_eq!(score, naive_score(&detector, &text, pos), epsilon = 1e-9);
+        }
+    }
+
+    #[test]
+    fn test_breakpoints() {
+        let mut rng = StdRng::seed_from_u64(42);
+        let detector = ChimeraDetector::default();
+        for _ in 0..10 {
+            let homogeneous = random_seq(&mut rng, 600, 0.5);
+            assert!(!detector.is_chimeric(&homogeneous));
+
+            let mut chimera = random_seq(&mut rng, 300, 0.15);
+            chimera.extend(random_seq(&mut rng, 300, 0.85));
+            let breakpoints = detector.breakpoints(&chimera);
+            assert_eq!(breakpoints.len(), 1);
+            assert!((breakpoints[0].pos as isize - 300).abs() < 20);
+        }
+        assert!(detector.breakpoints(b"ACGT").is_empty());
+    }
+}
@@ -0,0 +1,240 @@
+//! Codon usage tables, relative synonymous codon usage (RSCU) and codon-optimizing reverse
+//! translation, e.g. for designing synthetic genes for expression in a host organism.
+//!
+//! Codon usage is counted from coding sequences in their first frame, with the genetic code
+//! determining which codons are synonymous (see `translation::GeneticCode`). The RSCU of a codon
+//! is its count divided by the mean count of all codons of the same amino acid, i.e. 1 for
+//! unbiased usage, larger for preferred and smaller for avoided codons (Sharp and Li, 1987).
+//!
+//! # Example
+//!
+//! ```
+//! use bio::seq_analysis::codon_usage::CodonUsage;
+//! use bio::seq_analysis::translation::{translate, GeneticCode};
+//!
+//! let mut usage = CodonUsage::new(GeneticCode::standard());
+//! usage.add(b"ATGAAAAAAAAGCTGCTGCTTTAA");
+//! assert_eq!(usage.count(b"AAA"), 2);
+//! // lysine is encoded by AAA and AAG
+//! assert_eq!(usage.rscu(b"AAA"), Some(4.0 / 3.0));
+//! assert_eq!(usage.preferred_codon(b'L'), Some(*b"CTG"));
+//!
+//! let cds = usage.reverse_translate(b"MKL*").unwrap();
+//! assert_eq!(cds, b"ATGAAACTGTAA");
+//! assert_eq!(translate(&cds), b"MKL*");
+//! ```
+
+use thiserror::Error;
+
+use crate::alphabets::dna::iupac;
+use crate::seq_analysis::translation::GeneticCode;
+use crate::utils::{Text, TextSlice};
+
+/// Errors that can occur during reverse translation.
+#[derive(
+    Error, Copy, Clone, Eq, PartialEq, Ord, PartialOrd, Hash, Debug, Serialize, Deserialize,
+)]
+pub enum Error {
+    #[error("amino acid '{}' is not encoded by genetic code table {}", aa, table)]
+    UnknownAminoAcid { aa: char, table: u8 },
+}
+
+pub type Result<T, E = Error> = std::result::Result<T, E>;
+
+/// The codon with the given index, with bases ordered `ACGT` and the first base varying
+/// slowest.
+fn codon(index: usize) -> [u8; 3] {
+    let base = |i: usize| b"ACGT"[(index >> (2 * i)) & 3];
+    [base(2), base(1), base(0)]
+}
+
+/// The index of an unambiguous codon (case-insensitive, `U` like `T`).
+fn index(codon: &[u8]) -> Option<usize> {
+    codon.iter().try_fold(0, |index, &a| match iupac::bases(a) {
+        bases @ (1 | 2 | 4 | 8) => Some(index * 4 + bases.trailing_zeros() as usize),
+        _ => None,
+    })
+}
+
+/// A codon usage table, counting the occurrences of each of the 64 codons.
+#[derive(Clone, Eq, PartialEq, Hash, Debug, Serialize, Deserialize)]
+pub struct CodonUsage {
+    counts: Vec<u64>,
+    /// NCBI ID of the genetic code.
+    table: u8,
+    /// Amino acid of each codon.
+    amino_acids: Vec<u8>,
+}
+
+impl CodonUsage {
+    /// Create an empty codon usage table for the given genetic code.
+    pub fn new(code: &GeneticCode) -> Self {
+        CodonUsage {
+            counts: vec![0; 64],
+            table: code.id(),
+            amino_acids: (0..64)
+                .map(|index| code.translate_codon(&codon(index)))
+                .collect(),
+        }
+    }
+
+    /// Count the codons of the given coding sequences.
+    pub fn from_sequences<'a, I: IntoIterator<Item = TextSlice<'a>>>(
+        code: &GeneticCode,
+        cds: I,
+    ) -> Self {
+        let mut usage = Self::new(code);
+        for cds in cds {
+            usage.add(cds);
+        }
+        usage
+    }
+
+    /// NCBI ID of the genetic code table used.
+    pub fn genetic_code(&self) -> u8 {
+        self.table
+    }
+
+    /// Count the codons of the given coding sequence in its first frame. Codons with symbols
+    /// other than `ACGTU` (case-insensitive) and a trailing incomplete codon are skipped.
+    pub fn add(&mut self, cds: TextSlice<'_>) {
+        for index in cds.chunks_exact(3).filter_map(index) {
+            self.counts[index] += 1;
+        }
+    }
+
+    /// Add the counts of another table with the same genetic code.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the genetic codes differ.
+    pub fn merge(&mut self, other: &CodonUsage) {
+        assert_eq!(self.table, other.table, "Expecting the same genetic code.");
+        for (count, other) in self.counts.iter_mut().zip(&other.counts) {
+            *count += other;
+        }
+    }
+
+    /// Number of occurrences of the given codon (0 for invalid or ambiguous codons).
+    pub fn count(&self, codon: &[u8]) -> u64 {
+        index(codon).map_or(0, |index| self.counts[index])
+    }
+
+    /// Total number of counted codons.
+    pub fn total(&self) -> u64 {
+        self.counts.iter().sum()
+    }
+
+    /// Relative frequency of the codon among all counted codons, or `None` if nothing was
+    /// counted.
+    pub fn frequency(&self, codon: &[u8]) -> Option<f64> {
+        let total = self.total();
+        if total == 0 {
+            None
+        } else {
+            Some(self.count(codon) as f64 / total as f64)
+        }
+    }
+
+    /// Indices of the codons encoding the given amino acid (case-insensitive).
+    fn synonymous(&self, aa: u8) -> impl Iterator<Item = usize> + '_ {
+        let aa = aa.to_ascii_uppercase();
+        (0..64).filter(move |&index| self.amino_acids[index] == aa)
+    }
+
+    /// Relative synonymous codon usage of the given codon, or `None` if the codon is invalid
+    /// or none of its synonymous codons was counted.
+    pub fn rscu(&self, codon: &[u8]) -> Option<f64> {
+        let index = index(codon)?;
+        let (n, total) = self
+            .synonymous(self.amino_acids[index])
+            .fold((0, 0), |(n, total), i| (n + 1, total + self.counts[i]));
+        if total == 0 {
+            None
+        } else {
+            Some(self.counts[index] as f64 * n as f64 / total as f64)
+        }
+    }
+
+    /// The most frequently used codon for the given amino acid (or `*` for stop), with ties
+    /// broken in favor of the lexicographically smallest codon. Returns `None` if the amino
+    /// acid is not encoded by the genetic code.
+    pub fn preferred_codon(&self, aa: u8) -> Option<[u8; 3]> {
+        self.synonymous(aa)
+            .max_by_key(|&index| (self.counts[index], std::cmp::Reverse(index)))
+            .map(codon)
+    }
+
+    /// Reverse translate the given protein sequence (case-insensitive, `*` for stop) into a
+    /// coding sequence using the preferred codon of each amino acid.
+    pub fn reverse_translate(&self, protein: TextSlice<'_>) -> Result<Text> {
+        let mut preferred = [None; 256];
+        let mut cds = Vec::with_capacity(protein.len() * 3);
+        for &aa in protein {
+            let codon = match preferred[aa as usize] {
+                Some(codon) => codon,
+                None => {
+                    let codon = self.preferred_codon(aa).ok_or(Error::UnknownAminoAcid {
+                        aa: aa as char,
+                        table: self.table,
+                    })?;
+                    preferred[aa as usize] = Some(codon);
+                    codon
+                }
+            };
+            cds.extend_from_slice(&codon);
+        }
+        Ok(cds)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::seq_analysis::translation::translate;
+
+    #[test]
+    fn test_rscu() {
+        let usage = CodonUsage::from_sequences(
+            GeneticCode::standard(),
+            vec![&b"CTGCTGCTGCTACTN"[..], b"cugTTGggg"],
+        );
+        assert_eq!(usage.total(), 7);
+        assert_eq!(usage.count(b"CTG"), 4);
+        assert_eq!(usage.frequency(b"GGG"), Some(1.0 / 7.0));
+        // leucine has six codons
+        assert_eq!(usage.rscu(b"CTG"), Some(4.0));
+        assert_eq!(usage.rscu(b"CTC"), Some(0.0));
+        assert_eq!(usage.rscu(b"TGG"), None);
+        assert_eq!(usage.rscu(b"NNN"), None);
+    }
+
+    #[test]
+    fn test_reverse_translate() {
+        let empty = CodonUsage::new(GeneticCode::standard());
+        assert_eq!(empty.preferred_codon(b'M'), Some(*b"ATG"));
+        assert_eq!(empty.preferred_codon(b'*'), Some(*b"TAA"));
+        let protein = b"MAKRSTVWYHQNDECGILFP*";
+        assert_eq!(
+            translate(&empty.reverse_translate(protein).unwrap()),
+            protein
+        );
+        assert_eq!(
+            empty.reverse_translate(b"MXK"),
+            Err(Error::UnknownAminoAcid { aa: 'X', table: 1 })
+        );
+
+        // TGA encodes tryptophan in vertebrate mitochondria
+        let mito = GeneticCode::from_id(2).unwrap();
+        let mut usage = CodonUsage::new(mito);
+        usage.add(b"TGATGATGG");
+        assert_eq!(usage.reverse_translate(b"w").unwrap(), b"TGA");
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_merge_different_codes() {
+        let mut usage = CodonUsage::new(GeneticCode::standard());
+        usage.merge(&CodonUsage::new(GeneticCode::from_id(2).unwrap()));
+    }
+}
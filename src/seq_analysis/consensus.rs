@@ -0,0 +1,201 @@
+//! Quality-aware consensus of reads originating from the same molecule, e.g. reads sharing
+//! a unique molecular identifier (UMI).
+//!
+//! For each position, the posterior probability of each base `b` in `ACGT` is computed under
+//! a uniform prior, with the likelihood of an observed base `r` with error probability `e`
+//! (given by its PHRED quality) being `1 - e` if `r == b` and `e / 3` otherwise.
+//! The consensus base is the one with the highest posterior, and its quality is the PHRED
+//! scaled posterior probability of it being wrong.
+//! Reads are expected to be aligned at their first base, and may differ in length.
+//! Ambiguous bases (`N`) provide no evidence. Qualities are expected to be PHRED+33 encoded.
+//!
+//! # Example
+//!
+//! ```
+//! use bio::seq_analysis::consensus::ConsensusCaller;
+//!
+//! let reads = [
+//!     (&b"ACGTTGCA"[..], &b"IIIIIIII"[..]),
+//!     (b"ACGTAGCA", b"IIII#III"),
+//!     (b"ACGTTGCA", b"IIIIIIII"),
+//! ];
+//!
+//! let consensus = ConsensusCaller::default().call(reads.iter().copied()).unwrap();
+//! assert_eq!(consensus.seq, b"ACGTTGCA");
+//! assert_eq!(consensus.qual[0], 41 + 33);
+//! ```
+
+use crate::io::fastq;
+use crate::stats::{LogProb, PHREDProb, Prob};
+use crate::utils::{Text, TextSlice};
+
+const BASES: [u8; 4] = *b"ACGT";
+
+/// A consensus read.
+#[derive(Default, Clone, Eq, PartialEq, Ord, PartialOrd, Hash, Debug, Serialize, Deserialize)]
+pub struct ConsensusRead {
+    /// The consensus sequence.
+    pub seq: Text,
+    /// The consensus qualities (PHRED+33).
+    pub qual: Vec<u8>,
+    /// The number of reads supporting each position (i.e. reads with an unambiguous base).
+    pub depth: Vec<usize>,
+}
+
+/// Caller of consensus reads from stacks of duplicate reads.
+#[derive(Copy, Clone, Eq, PartialEq, Ord, PartialOrd, Hash, Debug, Serialize, Deserialize)]
+pub struct ConsensusCaller {
+    /// Maximum PHRED quality (not offset) assigned to consensus bases.
+    pub max_qual: u8,
+}
+
+impl Default for ConsensusCaller {
+    /// A caller capping qualities at 41 (as in Illumina 1.8+).
+    fn default() -> Self {
+        ConsensusCaller::new(41)
+    }
+}
+
+impl ConsensusCaller {
+    /// Create a new consensus caller.
+    ///
+    /// # Arguments
+    ///
+    /// * `max_qual` - maximum PHRED quality (not offset) assigned to consensus bases
+    pub fn new(max_qual: u8) -> Self {
+        ConsensusCaller { max_qual }
+    }
+
+    /// Call the consensus of the given reads, each given as a pair of sequence and qualities.
+    /// The consensus has the length of the longest read. Positions without evidence are
+    /// reported as `N` with the lowest quality. Returns `None` if there are no reads.
+    pub fn call<'a, I>(&self, reads: I) -> Option<ConsensusRead>
+    where
+        I: IntoIterator<Item = (TextSlice<'a>, &'a [u8])>,
+    {
+        // log likelihoods of A, C, G, T per position
+        let mut likelihoods: Vec<[LogProb; 4]> = Vec::new();
+        let mut depth = Vec::new();
+        let mut any = false;
+        for (seq, qual) in reads {
+            assert_eq!(seq.len(), qual.len(), "Expecting a quality for each base.");
+            any = true;
+            if seq.len() > likelihoods.len() {
+                likelihoods.resize(seq.len(), [LogProb::ln_one(); 4]);
+                depth.resize(seq.len(), 0);
+            }
+            for (i, (&a, &q)) in seq.iter().zip(qual).enumerate() {
+                let a = a.to_ascii_uppercase();
+                if !BASES.contains(&a) {
+                    continue;
+                }
+                let error = LogProb::from(PHREDProb(q.saturating_sub(33) as f64));
+                let hit = error.ln_one_minus_exp();
+                let miss = error - LogProb::from(Prob(3.0));
+                for (b, l) in BASES.iter().zip(likelihoods[i].iter_mut()) {
+                    *l += if *b == a { hit } else { miss };
+                }
+                depth[i] += 1;
+            }
+        }
+        if !any {
+            return None;
+        }
+
+        let mut seq = Vec::with_capacity(likelihoods.len());
+        let mut qual = Vec::with_capacity(likelihoods.len());
+        for (l, &d) in likelihoods.iter().zip(&depth) {
+            if d == 0 {
+                seq.push(b'N');
+                qual.push(2 + 33);
+                continue;
+            }
+            let marginal = LogProb::ln_sum_exp(l);
+            let (best, &best_l) = l
+                .iter()
+                .enumerate()
+                .max_by(|(_, a), (_, b)| a.partial_cmp(b).unwrap())
+                .unwrap();
+            let error = (best_l - marginal).cap_numerical_overshoot(1e-9);
+            let q = *PHREDProb::from(error.ln_one_minus_exp());
+            seq.push(BASES[best]);
+            // lowest valid Illumina quality is 2
+            qual.push(q.round().clamp(2.0, self.max_qual as f64) as u8 + 33);
+        }
+
+        Some(ConsensusRead { seq, qual, depth })
+    }
+
+    /// Call the consensus of the given FASTQ records (see `ConsensusCaller::call`). The
+    /// consensus record takes the id and description of the first record.
+    pub fn call_records(&self, records: &[fastq::Record]) -> Option<fastq::Record> {
+        let first = records.first()?;
+        self.call(records.iter().map(|rec| (rec.seq(), rec.qual())))
+            .map(|consensus| {
+                fastq::Record::with_attrs(first.id(), first.desc(), &consensus.seq, &consensus.qual)
+            })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_call() {
+        let caller = ConsensusCaller::default();
+        let reads = [
+            (&b"ACGTTGCAAG"[..], &b"5555555555"[..]),
+            (b"ACGTAGCA", b"5555#555"),
+            (b"ACGTTGNA", b"55555555"),
+        ];
+        let consensus = caller.call(reads.iter().copied()).unwrap();
+        assert_eq!(consensus.seq, b"ACGTTGCAAG");
+        assert_eq!(consensus.depth, [3, 3, 3, 3, 3, 3, 2, 3, 1, 1]);
+        // three agreeing Q20 bases
+        assert_eq!(consensus.qual[0], 41 + 33);
+        // a single Q20 base keeps its quality
+        assert_eq!(consensus.qual[9], b'5');
+        // the low quality disagreement only slightly lowers the quality
+        assert_eq!(consensus.qual[4], 41 + 33);
+        let uncapped = ConsensusCaller::new(60)
+            .call(reads.iter().copied())
+            .unwrap();
+        assert_eq!(uncapped.qual[0], 60 + 33);
+        assert!(uncapped.qual[4] > 41 + 33 && uncapped.qual[4] < uncapped.qual[0]);
+
+        assert_eq!(
+            ConsensusCaller::new(30)
+                .call(reads.iter().copied())
+                .unwrap()
+                .qual[0],
+            30 + 33
+        );
+    }
+
+    #[test]
+    fn test_call_tie_and_no_evidence() {
+        let caller = ConsensusCaller::default();
+        let consensus = caller
+            .call(vec![(&b"AN"[..], &b"55"[..]), (b"CN", b"55")])
+            .unwrap();
+        // equal evidence for two bases
+        assert_eq!(consensus.qual[0], 3 + 33);
+        assert_eq!(&consensus.seq[1..], b"N");
+        assert_eq!(consensus.qual[1], 2 + 33);
+        assert!(caller.call(Vec::new()).is_none());
+    }
+
+    #[test]
+    fn test_call_records() {
+        let records = [
+            fastq::Record::with_attrs("umi1", Some("r1"), b"ACGT", b"5555"),
+            fastq::Record::with_attrs("umi1", Some("r2"), b"ACCT", b"55#5"),
+        ];
+        let consensus = ConsensusCaller::default().call_records(&records).unwrap();
+        assert_eq!(consensus.id(), "umi1");
+        assert_eq!(consensus.desc(), Some("r1"));
+        assert_eq!(consensus.seq(), b"ACGT");
+        assert!(ConsensusCaller::default().call_records(&[]).is_none());
+    }
+}
@@ -0,0 +1,154 @@
+//! Per-base sequence context, e.g. as features for error models, base quality recalibration
+//! or machine learning.
+//!
+//! For each position of a DNA sequence, this provides the length of the homopolymer run it
+//! belongs to, and a code of the surrounding k-mer. Contexts are encoded with two bits per
+//! base (A=0, C=1, G=2, T=3, case-insensitive), with the leftmost base in the most
+//! significant bits.
+//!
+//! # Example
+//!
+//! ```
+//! use bio::seq_analysis::context::{decode_context, dinucleotide_contexts, homopolymer_lengths};
+//!
+//! let text = b"ACCCGT";
+//! assert_eq!(homopolymer_lengths(text), [1, 3, 3, 3, 1, 1]);
+//!
+//! let contexts = dinucleotide_contexts(text);
+//! assert_eq!(contexts[0], None);
+//! assert_eq!(decode_context(contexts[1].unwrap(), 2), b"AC");
+//! ```
+
+use crate::utils::TextSlice;
+
+/// Two bit code of a DNA base, `None` for other symbols.
+fn base_code(a: u8) -> Option<u64> {
+    match a {
+        b'A' | b'a' => Some(0),
+        b'C' | b'c' => Some(1),
+        b'G' | b'g' => Some(2),
+        b'T' | b't' => Some(3),
+        _ => None,
+    }
+}
+
+/// For each position, the length of the run of identical symbols (case-insensitive) it
+/// belongs to.
+pub fn homopolymer_lengths(text: TextSlice<'_>) -> Vec<usize> {
+    let mut lengths = Vec::with_capacity(text.len());
+    let mut start = 0;
+    while start < text.len() {
+        let a = text[start].to_ascii_uppercase();
+        let len = text[start..]
+            .iter()
+            .take_while(|b| b.to_ascii_uppercase() == a)
+            .count();
+        lengths.extend(std::iter::repeat(len).take(len));
+        start += len;
+    }
+
+    lengths
+}
+
+/// For each position, the code of the context consisting of the `before` preceding bases,
+/// the base itself and the `after` following bases.
+/// The context is `None` if it exceeds the sequence or contains symbols other than `ACGT`.
+///
+/// # Arguments
+///
+/// * `text` - the DNA sequence
+/// * `before` - number of bases before the position
+/// * `after` - number of bases after the position
+///
+/// # Panics
+///
+/// Panics if the context is longer than 32 bases.
+///
+/// # Example
+///
+/// ```
+/// use bio::seq_analysis::context::{context_codes, decode_context};
+///
+/// let contexts = context_codes(b"ACGNTA", 1, 1);
+/// assert_eq!(contexts[0], None);
+/// assert_eq!(decode_context(contexts[1].unwrap(), 3), b"ACG");
+/// assert_eq!(contexts[2..], [None, None, None, None]);
+/// ```
+pub fn context_codes(text: TextSlice<'_>, before: usize, after: usize) -> Vec<Option<u64>> {
+    let width = before + after + 1;
+    assert!(
+        width <= 32,
+        "Contexts of more than 32 bases are not supported."
+    );
+    let mask = if width == 32 {
+        u64::MAX
+    } else {
+        (1 << (2 * width)) - 1
+    };
+
+    let mut codes = vec![None; text.len()];
+    let mut code = 0;
+    // number of consecutive valid bases ending at the current position
+    let mut valid = 0;
+    for (end, &a) in text.iter().enumerate() {
+        match base_code(a) {
+            Some(c) => {
+                code = ((code << 2) | c) & mask;
+                valid += 1;
+            }
+            None => valid = 0,
+        }
+        if valid >= width {
+            codes[end - after] = Some(code);
+        }
+    }
+
+    codes
+}
+
+/// For each position, the code of the dinucleotide formed by the preceding base and the base
+/// itself (see `context_codes`).
+pub fn dinucleotide_contexts(text: TextSlice<'_>) -> Vec<Option<u64>> {
+    context_codes(text, 1, 0)
+}
+
+/// Decode a context code of the given width back into its bases.
+pub fn decode_context(code: u64, width: usize) -> Vec<u8> {
+    (0..width)
+        .rev()
+        .map(|i| b"ACGT"[((code >> (2 * i)) & 3) as usize])
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_homopolymer_lengths() {
+        assert_eq!(
+            homopolymer_lengths(b"AAaCGGTTTT"),
+            [3, 3, 3, 1, 2, 2, 4, 4, 4, 4]
+        );
+        assert!(homopolymer_lengths(b"").is_empty());
+    }
+
+    #[test]
+    fn test_context_codes() {
+        let text = b"ACGTacgtNACG";
+        let codes = context_codes(text, 2, 1);
+        for (i, code) in codes.iter().enumerate() {
+            let expected = if i >= 2 && i + 1 < text.len() && !text[i - 2..=i + 1].contains(&b'N') {
+                Some(text[i - 2..=i + 1].to_ascii_uppercase())
+            } else {
+                None
+            };
+            assert_eq!(code.map(|code| decode_context(code, 4)), expected);
+        }
+        assert_eq!(context_codes(text, 0, 0)[3], Some(3));
+        assert_eq!(dinucleotide_contexts(b"GT"), [None, Some(0b1011)]);
+
+        let long = [b'T'; 40];
+        assert_eq!(context_codes(&long, 31, 0)[31], Some(u64::MAX));
+    }
+}
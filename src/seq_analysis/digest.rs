@@ -0,0 +1,330 @@
+//! In-silico restriction digests of DNA sequences into ordered fragment length profiles, and
+//! their comparison with a tolerance for sizing errors, e.g. for checking the consistency of an
+//! assembly with an optical map (Valouev et al., 2006).
+//!
+//! Enzymes are given by their recognition site in the IUPAC code and the position of the cut
+//! on the forward strand, relative to the start of the site. Non-palindromic sites are searched
+//! on both strands, such that nicking enzymes as used for optical maps are supported, too.
+//!
+//! # Example
+//!
+//! ```
+//! use bio::seq_analysis::digest::{compare_profiles, digest, SizingError, ECORI};
+//!
+//! let contig = b"AAAGAATTCCCCCCCCCCCCGAATTCTTTTTTTT";
+//! assert_eq!(ECORI.cut_sites(contig), [4, 21]);
+//! let profile = digest(contig, &[ECORI]);
+//! assert_eq!(profile, [4, 17, 13]);
+//!
+//! // a map with a missing cut site and small sizing errors
+//! let map = [5, 29];
+//! let tolerance = SizingError {
+//!     absolute: 2.0,
+//!     relative: 0.0,
+//! };
+//! let alignment = compare_profiles(&profile, &map, tolerance, 2).unwrap();
+//! assert_eq!(alignment.matches, [(0..1, 0..1), (1..3, 1..2)]);
+//! assert_eq!(alignment.missed_cuts(), (0, 1));
+//! assert_eq!(alignment.sizing_error, 2.0);
+//! ```
+
+use std::ops::Range;
+
+use crate::alphabets::dna::iupac;
+use crate::utils::TextSlice;
+
+/// A restriction enzyme or nicking endonuclease.
+#[derive(Copy, Clone, Eq, PartialEq, Ord, PartialOrd, Hash, Debug)]
+pub struct Enzyme<'a> {
+    pub name: &'a str,
+    /// Recognition site in the IUPAC code.
+    pub site: &'a [u8],
+    /// Position of the cut on the forward strand, relative to the start of the site.
+    pub cut: usize,
+}
+
+/// EcoRI, G^AATTC.
+pub const ECORI: Enzyme<'static> = Enzyme {
+    name: "EcoRI",
+    site: b"GAATTC",
+    cut: 1,
+};
+
+/// BamHI, G^GATCC.
+pub const BAMHI: Enzyme<'static> = Enzyme {
+    name: "BamHI",
+    site: b"GGATCC",
+    cut: 1,
+};
+
+/// HindIII, A^AGCTT.
+pub const HINDIII: Enzyme<'static> = Enzyme {
+    name: "HindIII",
+    site: b"AAGCTT",
+    cut: 1,
+};
+
+/// NotI, GC^GGCCGC.
+pub const NOTI: Enzyme<'static> = Enzyme {
+    name: "NotI",
+    site: b"GCGGCCGC",
+    cut: 2,
+};
+
+/// Nt.BspQI, the nicking enzyme of Bionano optical maps, GCTCTTCN^.
+pub const NT_BSPQI: Enzyme<'static> = Enzyme {
+    name: "Nt.BspQI",
+    site: b"GCTCTTC",
+    cut: 8,
+};
+
+/// Nt.BbvCI, a nicking enzyme used for optical maps, CC^TCAGC.
+pub const NT_BBVCI: Enzyme<'static> = Enzyme {
+    name: "Nt.BbvCI",
+    site: b"CCTCAGC",
+    cut: 2,
+};
+
+impl Enzyme<'_> {
+    /// Positions of all cuts in the text in increasing order, i.e. the positions of the first
+    /// base after each cut. Sites on the reverse strand are cut at the mirrored position. Cuts
+    /// at the ends of the text are omitted.
+    pub fn cut_sites(&self, text: TextSlice<'_>) -> Vec<usize> {
+        let len = self.site.len();
+        if len == 0 || text.len() < len {
+            return Vec::new();
+        }
+        let revcomp = iupac::revcomp(self.site);
+        let palindromic = revcomp.eq_ignore_ascii_case(self.site);
+        let mut cuts = Vec::new();
+        for (i, window) in text.windows(len).enumerate() {
+            if is_site(window, self.site) {
+                cuts.push(i + self.cut);
+            }
+            if !palindromic && is_site(window, &revcomp) && i + len >= self.cut {
+                cuts.push(i + len - self.cut);
+            }
+        }
+        cuts.retain(|&cut| cut > 0 && cut < text.len());
+        cuts.sort_unstable();
+        cuts.dedup();
+        cuts
+    }
+}
+
+/// Whether the window is an occurrence of the site, i.e. whether each base of the window is
+/// one of the bases of the corresponding IUPAC code. Ambiguous bases in the text never match.
+fn is_site(window: &[u8], site: &[u8]) -> bool {
+    window.iter().zip(site).all(|(&a, &s)| {
+        let a = iupac::bases(a);
+        a.count_ones() == 1 && a & iupac::bases(s) != 0
+    })
+}
+
+/// The ordered fragment lengths of a digest of the text with all given enzymes.
+///
+/// # Example
+///
+/// ```
+/// use bio::seq_analysis::digest::{digest, BAMHI, ECORI};
+///
+/// let text = b"GGATCCAAGAATTCAA";
+/// assert_eq!(digest(text, &[ECORI, BAMHI]), [1, 8, 7]);
+/// assert_eq!(digest(b"ACGT", &[ECORI]), [4]);
+/// ```
+pub fn digest(text: TextSlice<'_>, enzymes: &[Enzyme<'_>]) -> Vec<usize> {
+    let mut cuts: Vec<usize> = enzymes
+        .iter()
+        .flat_map(|enzyme| enzyme.cut_sites(text))
+        .collect();
+    cuts.sort_unstable();
+    cuts.dedup();
+    let mut last = 0;
+    let mut lengths = Vec::with_capacity(cuts.len() + 1);
+    for cut in cuts.into_iter().chain(Some(text.len())) {
+        lengths.push(cut - last);
+        last = cut;
+    }
+    lengths.retain(|&len| len > 0);
+    lengths
+}
+
+/// Tolerance for the difference between the lengths of two matching fragments, which is
+/// `absolute + relative * max(length)`.
+#[derive(Default, Copy, Clone, PartialEq, Debug, Serialize, Deserialize)]
+pub struct SizingError {
+    pub absolute: f64,
+    pub relative: f64,
+}
+
+impl SizingError {
+    /// Whether fragments of the given lengths match within the tolerance.
+    pub fn matches(&self, a: usize, b: usize) -> bool {
+        let (a, b) = (a as f64, b as f64);
+        (a - b).abs() <= self.absolute + self.relative * a.max(b)
+    }
+}
+
+/// An alignment of two fragment length profiles, see `compare_profiles`.
+#[derive(Default, Clone, PartialEq, Debug, Serialize, Deserialize)]
+pub struct ProfileAlignment {
+    /// Matching groups of consecutive fragments of both profiles, as index ranges, in order.
+    pub matches: Vec<(Range<usize>, Range<usize>)>,
+    /// Sum of the absolute length differences of the matching groups.
+    pub sizing_error: f64,
+}
+
+impl ProfileAlignment {
+    /// Number of cuts missing in the first and the second profile, compared to the other one,
+    /// i.e. the number of fragments of the other profile merged in the alignment.
+    pub fn missed_cuts(&self) -> (usize, usize) {
+        self.matches
+            .iter()
+            .fold((0, 0), |(a, b), (x, y)| (a + y.len() - 1, b + x.len() - 1))
+    }
+}
+
+/// Compare two ordered fragment length profiles end to end, e.g. of an assembled contig and the
+/// corresponding region of an optical map. Each fragment group of one profile has to match a
+/// group of the other profile within the sizing error tolerance, where groups of up to
+/// `max_merge` consecutive fragments account for cut sites missing in the other profile. The
+/// alignment with the fewest missing cuts and, among those, the smallest sizing error is
+/// returned, or `None` if the profiles are inconsistent.
+/// Complexity: O(n m max_merge²), where n and m are the lengths of the profiles.
+///
+/// # Panics
+///
+/// Panics if `max_merge` is zero.
+pub fn compare_profiles(
+    a: &[usize],
+    b: &[usize],
+    tolerance: SizingError,
+    max_merge: usize,
+) -> Option<ProfileAlignment> {
+    assert!(max_merge > 0, "Expecting max_merge > 0.");
+    let prefix_sums = |profile: &[usize]| {
+        let mut sums = vec![0];
+        sums.extend(profile.iter().scan(0, |sum, &len| {
+            *sum += len;
+            Some(*sum)
+        }));
+        sums
+    };
+    let (sa, sb) = (prefix_sums(a), prefix_sums(b));
+
+    // best[i][j] is the number of matching groups and the sizing error of the best alignment
+    // of a[..i] and b[..j], together with the lengths of the last groups
+    let mut best = vec![vec![None; b.len() + 1]; a.len() + 1];
+    best[0][0] = Some((0, 0.0, 0, 0));
+    for i in 1..=a.len() {
+        for j in 1..=b.len() {
+            for di in 1..=i.min(max_merge) {
+                for dj in 1..=j.min(max_merge) {
+                    let (groups, error, _, _) = match best[i - di][j - dj] {
+                        Some(prev) => prev,
+                        None => continue,
+                    };
+                    let (la, lb) = (sa[i] - sa[i - di], sb[j] - sb[j - dj]);
+                    if !tolerance.matches(la, lb) {
+                        continue;
+                    }
+                    let error = error + (la as f64 - lb as f64).abs();
+                    let better = best[i][j].map_or(true, |(best_groups, best_error, _, _)| {
+                        groups + 1 > best_groups
+                            || (groups + 1 == best_groups && error < best_error)
+                    });
+                    if better {
+                        best[i][j] = Some((groups + 1, error, di, dj));
+                    }
+                }
+            }
+        }
+    }
+
+    let (_, sizing_error, _, _) = best[a.len()][b.len()]?;
+    let mut matches = Vec::new();
+    let (mut i, mut j) = (a.len(), b.len());
+    while i > 0 {
+        let (_, _, di, dj) = best[i][j].unwrap();
+        matches.push((i - di..i, j - dj..j));
+        i -= di;
+        j -= dj;
+    }
+    matches.reverse();
+    Some(ProfileAlignment {
+        matches,
+        sizing_error,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::alphabets::dna;
+
+    #[test]
+    fn test_cut_sites() {
+        let text = b"TTGCTCTTCAAAAAAAAAAGAAGAGCAAA";
+        // forward site at 2, cut after the N, and reverse site at 19, cut before the N
+        assert_eq!(NT_BSPQI.cut_sites(text), [10, 18]);
+        // both strands give the same digest
+        let rc = dna::revcomp(&text[..]);
+        assert_eq!(NT_BSPQI.cut_sites(&rc), [text.len() - 18, text.len() - 10]);
+        // lowercase bases match, ambiguous ones do not
+        assert_eq!(NOTI.cut_sites(b"AgcggccgcA"), [3]);
+        assert!(NOTI.cut_sites(b"AGCGGNCGCA").is_empty());
+        // ambiguous sites
+        let bsli = Enzyme {
+            name: "BslI",
+            site: b"CCNNNNNNNGG",
+            cut: 7,
+        };
+        assert_eq!(bsli.cut_sites(b"ACCAAAAAAAGGA"), [8]);
+        assert!(HINDIII.cut_sites(b"").is_empty());
+    }
+
+    #[test]
+    fn test_digest() {
+        let text = b"AAGCTTGGATCCAAGCTT";
+        assert_eq!(digest(text, &[HINDIII]), [1, 12, 5]);
+        assert_eq!(digest(text, &[HINDIII, BAMHI]), [1, 6, 6, 5]);
+        assert!(digest(b"", &[HINDIII]).is_empty());
+    }
+
+    #[test]
+    fn test_compare_profiles() {
+        let tolerance = SizingError {
+            absolute: 100.0,
+            relative: 0.05,
+        };
+        let assembly = [12_000, 3_000, 4_000, 20_000, 7_500];
+        let map = [11_800, 7_100, 20_500, 2_000, 5_600];
+        let alignment = compare_profiles(&assembly, &map, tolerance, 3).unwrap();
+        assert_eq!(
+            alignment.matches,
+            [(0..1, 0..1), (1..3, 1..2), (3..4, 2..3), (4..5, 3..5)]
+        );
+        assert_eq!(alignment.missed_cuts(), (1, 1));
+        assert_eq!(alignment.sizing_error, 200.0 + 100.0 + 500.0 + 100.0);
+
+        // without merging, the profiles are inconsistent
+        assert_eq!(compare_profiles(&assembly, &map, tolerance, 1), None);
+        assert_eq!(
+            compare_profiles(&[], &[], tolerance, 1),
+            Some(ProfileAlignment::default())
+        );
+        assert_eq!(compare_profiles(&[], &map, tolerance, 1), None);
+    }
+
+    #[test]
+    fn test_smallest_sizing_error() {
+        let tolerance = SizingError {
+            absolute: 10.0,
+            relative: 0.0,
+        };
+        // both groupings need one missed cut, the second one fits better
+        let alignment = compare_profiles(&[10, 10, 10], &[18, 12], tolerance, 2).unwrap();
+        assert_eq!(alignment.matches, [(0..2, 0..1), (2..3, 1..2)]);
+        assert_eq!(alignment.sizing_error, 4.0);
+    }
+}
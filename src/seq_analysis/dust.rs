@@ -0,0 +1,229 @@
+//! Detection of low-complexity regions in DNA sequences with the DUST score
+//! (Morgulis et al., 2006).
+//!
+//! The DUST score of a window counts how often each of the 64 DNA triplets occurs in it, as
+//! `sum_t c_t (c_t - 1) / 2 / (l - 1)`, where `l` is the number of triplets in the window.
+//! It is close to zero for random sequence and grows for repetitive sequence (e.g. 31 for a
+//! homopolymer of 64 bases). Windows scoring above a threshold are masked. Masked regions
+//! produce spurious seed hits, which is why they can be recorded alongside an FM-index
+//! (see `FMIndex::from_texts_masked`), such that seeding can skip or downweight them.
+//!
+//! # Example
+//!
+//! ```
+//! use bio::seq_analysis::dust::Dust;
+//!
+//! let mut text = b"ACGGCTGAGATGATCCGATTAGCTAGCAT".to_vec();
+//! text.extend_from_slice(&[b'A'; 40]);
+//! text.extend_from_slice(b"GGATACCAGTTTACGAGCATGCAGTCAGTC");
+//! let dust = Dust {
+//!     window: 32,
+//!     ..Default::default()
+//! };
+//! let masked = dust.intervals(&text);
+//! assert_eq!(masked.len(), 1);
+//! assert!(masked[0].start <= 29 && masked[0].end >= 69);
+//! assert_eq!(&dust.soft_mask(&text)[29..31], b"aa");
+//! ```
+
+use std::ops::Range;
+
+use crate::utils::{Text, TextSlice};
+
+/// 2-bit encoding of DNA symbols.
+fn encode(a: u8) -> Option<usize> {
+    match a {
+        b'A' | b'a' => Some(0),
+        b'C' | b'c' => Some(1),
+        b'G' | b'g' => Some(2),
+        b'T' | b't' => Some(3),
+        _ => None,
+    }
+}
+
+/// Parameters of the DUST algorithm.
+#[derive(Copy, Clone, PartialEq, Debug, Serialize, Deserialize)]
+pub struct Dust {
+    /// Length of the scored windows.
+    pub window: usize,
+    /// Windows with a higher score are masked.
+    pub threshold: f64,
+}
+
+impl Default for Dust {
+    /// Windows of 64 bases and a threshold of 2 (corresponding to level 20 of NCBI
+    /// `dustmasker`).
+    fn default() -> Self {
+        Dust {
+            window: 64,
+            threshold: 2.0,
+        }
+    }
+}
+
+impl Dust {
+    /// Find the low-complexity regions of the text, as sorted, non-overlapping intervals.
+    /// Texts shorter than the window are scored as a single window.
+    /// Triplets containing symbols other than `ACGT` (case-insensitive) are not counted.
+    /// Complexity: O(n), where n is the length of the text.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the window is shorter than 4 bases.
+    pub fn intervals(&self, text: TextSlice<'_>) -> Vec<Range<usize>> {
+        assert!(self.window >= 4, "Expecting a window of at least 4 bases.");
+        let window = self.window.min(text.len());
+        if window < 4 {
+            return Vec::new();
+        }
+        let triplet = |i: usize| -> Option<usize> {
+            Some(encode(text[i])? << 4 | encode(text[i + 1])? << 2 | encode(text[i + 2])?)
+        };
+        // number of triplet positions in a window
+        let l = window - 2;
+        let mut counts = [0usize; 64];
+        // sum of c_t (c_t - 1) / 2
+        let mut pairs = 0;
+        for t in (0..l).filter_map(triplet) {
+            pairs += counts[t];
+            counts[t] += 1;
+        }
+
+        let mut intervals: Vec<Range<usize>> = Vec::new();
+        for start in 0..=text.len() - window {
+            if start > 0 {
+                if let Some(t) = triplet(start - 1) {
+                    counts[t] -= 1;
+                    pairs -= counts[t];
+                }
+                if let Some(t) = triplet(start + l - 1) {
+                    pairs += counts[t];
+                    counts[t] += 1;
+                }
+            }
+            if pairs as f64 / (l - 1) as f64 > self.threshold {
+                match intervals.last_mut() {
+                    Some(last) if last.end >= start => last.end = start + window,
+                    _ => intervals.push(start..start + window),
+                }
+            }
+        }
+
+        intervals
+    }
+
+    /// Return a copy of the text with low-complexity regions in lower case.
+    pub fn soft_mask(&self, text: TextSlice<'_>) -> Text {
+        let mut masked = text.to_vec();
+        for interval in self.intervals(text) {
+            masked[interval].make_ascii_lowercase();
+        }
+        masked
+    }
+
+    /// Return a copy of the text with low-complexity regions replaced by `N`.
+    pub fn hard_mask(&self, text: TextSlice<'_>) -> Text {
+        let mut masked = text.to_vec();
+        for interval in self.intervals(text) {
+            masked[interval].fill(b'N');
+        }
+        masked
+    }
+}
+
+/// Sorted, non-overlapping masked intervals of a text, e.g. as found by `Dust::intervals`.
+#[derive(Default, Clone, Eq, PartialEq, Hash, Debug, Serialize, Deserialize)]
+pub struct MaskedIntervals {
+    intervals: Vec<Range<usize>>,
+}
+
+impl MaskedIntervals {
+    /// Create from sorted, non-overlapping intervals.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the intervals are not sorted or overlap.
+    pub fn new(intervals: Vec<Range<usize>>) -> Self {
+        assert!(
+            intervals.windows(2).all(|w| w[0].end <= w[1].start),
+            "Expecting sorted, non-overlapping intervals."
+        );
+        MaskedIntervals { intervals }
+    }
+
+    /// The masked intervals.
+    pub fn intervals(&self) -> &[Range<usize>] {
+        &self.intervals
+    }
+
+    /// Total number of masked positions.
+    pub fn masked_len(&self) -> usize {
+        self.intervals.iter().map(|i| i.len()).sum()
+    }
+
+    /// Number of masked positions within the given range.
+    /// Complexity: O(log m + k), where m is the number of intervals and k the number of
+    /// intervals overlapping the range.
+    pub fn overlap(&self, range: Range<usize>) -> usize {
+        let first = self.intervals.partition_point(|i| i.end <= range.start);
+        self.intervals[first..]
+            .iter()
+            .take_while(|i| i.start < range.end)
+            .map(|i| i.end.min(range.end) - i.start.max(range.start))
+            .sum()
+    }
+
+    /// Whether the given position is masked.
+    pub fn is_masked(&self, pos: usize) -> bool {
+        self.overlap(pos..pos + 1) == 1
+    }
+
+    /// Fraction of masked positions within the given (non-empty) range, e.g. to downweight
+    /// seed hits.
+    pub fn masked_fraction(&self, range: Range<usize>) -> f64 {
+        self.overlap(range.clone()) as f64 / range.len() as f64
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::prelude::*;
+    use rand::rngs::StdRng;
+
+    #[test]
+    fn test_random_sequence_unmasked() {
+        let mut rng = StdRng::seed_from_u64(42);
+        let text: Vec<u8> = (0..5000)
+            .map(|_| *b"ACGT".choose(&mut rng).unwrap())
+            .collect();
+        assert!(Dust::default().intervals(&text).is_empty());
+    }
+
+    #[test]
+    fn test_repeats() {
+        let dust = Dust::default();
+        assert_eq!(dust.intervals(b"CACACACACACACACACACA"), [0..20]);
+        assert_eq!(dust.intervals(&[b'T'; 100]), [0..100]);
+        assert!(dust.intervals(b"ACG").is_empty());
+        // Ns interrupt the repeat
+        let mut text = vec![b'A'; 200];
+        for i in (0..200).step_by(2) {
+            text[i] = b'N';
+        }
+        assert!(dust.intervals(&text).is_empty());
+        assert_eq!(dust.hard_mask(b"CACACACACACACA"), b"NNNNNNNNNNNNNN");
+    }
+
+    #[test]
+    fn test_masked_intervals() {
+        let masked = MaskedIntervals::new(vec![2..5, 8..10, 20..30]);
+        assert_eq!(masked.masked_len(), 15);
+        assert_eq!(masked.overlap(0..2), 0);
+        assert_eq!(masked.overlap(3..9), 3);
+        assert_eq!(masked.overlap(0..100), 15);
+        assert!(masked.is_masked(9));
+        assert!(!masked.is_masked(10));
+        assert_eq!(masked.masked_fraction(18..22), 0.5);
+    }
+}
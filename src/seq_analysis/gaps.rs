@@ -0,0 +1,118 @@
+//! Runs of ambiguous or gap symbols (e.g. `N` in assembled references) as intervals, and
+//! splitting of scaffolds into contigs at such runs.
+//!
+//! # Example
+//!
+//! ```
+//! use bio::seq_analysis::gaps::{n_runs, split_at_n_runs};
+//!
+//! let scaffold = b"ACGTNNNNNACGTnACGT";
+//! assert_eq!(n_runs(scaffold).collect::<Vec<_>>(), [4..9, 13..14]);
+//! // split at runs of at least 5 Ns
+//! assert_eq!(split_at_n_runs(scaffold, 5), [0..4, 9..18]);
+//! ```
+
+use std::ops::Range;
+
+use crate::alphabets::Alphabet;
+use crate::utils::TextSlice;
+
+/// Iterator over maximal runs of symbols from a given set, as intervals.
+#[derive(Clone, Debug)]
+pub struct SymbolRuns<'a> {
+    text: TextSlice<'a>,
+    symbols: Alphabet,
+    pos: usize,
+}
+
+impl Iterator for SymbolRuns<'_> {
+    type Item = Range<usize>;
+
+    fn next(&mut self) -> Option<Range<usize>> {
+        let start = self.pos
+            + self.text[self.pos..]
+                .iter()
+                .position(|&a| self.symbols.contains(a))?;
+        let len = self.text[start..]
+            .iter()
+            .take_while(|&&a| self.symbols.contains(a))
+            .count();
+        self.pos = start + len;
+        Some(start..self.pos)
+    }
+}
+
+/// Find all maximal runs of the given symbols in the text.
+///
+/// # Example
+///
+/// ```
+/// use bio::alphabets::Alphabet;
+/// use bio::seq_analysis::gaps::symbol_runs;
+///
+/// let gaps = Alphabet::new(b"-.");
+/// let runs: Vec<_> = symbol_runs(b"AC--GT.-A-", &gaps).collect();
+/// assert_eq!(runs, [2..4, 6..8, 9..10]);
+/// ```
+pub fn symbol_runs<'a>(text: TextSlice<'a>, symbols: &Alphabet) -> SymbolRuns<'a> {
+    SymbolRuns {
+        text,
+        symbols: symbols.clone(),
+        pos: 0,
+    }
+}
+
+/// Find all maximal runs of `N` (or `n`) in the text.
+pub fn n_runs(text: TextSlice<'_>) -> SymbolRuns<'_> {
+    symbol_runs(text, &Alphabet::new(b"Nn"))
+}
+
+/// Split the text at runs of `N` (or `n`) of at least `min_len` symbols, returning the
+/// intervals of the resulting contigs. Shorter runs are retained within the contigs.
+/// Leading and trailing runs are removed, irrespective of their length.
+///
+/// # Panics
+///
+/// Panics if `min_len` is zero.
+pub fn split_at_n_runs(text: TextSlice<'_>, min_len: usize) -> Vec<Range<usize>> {
+    assert!(min_len > 0, "Expecting a positive minimum run length.");
+    let mut contigs = Vec::new();
+    let mut start = 0;
+    for run in n_runs(text) {
+        let boundary = run.start == 0 || run.end == text.len();
+        if run.len() >= min_len || boundary {
+            if run.start > start {
+                contigs.push(start..run.start);
+            }
+            start = run.end;
+        }
+    }
+    if start < text.len() {
+        contigs.push(start..text.len());
+    }
+
+    contigs
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_n_runs() {
+        assert_eq!(n_runs(b"").count(), 0);
+        assert_eq!(n_runs(b"ACGT").count(), 0);
+        assert_eq!(n_runs(b"NNNN").collect::<Vec<_>>(), [0..4]);
+        assert_eq!(n_runs(b"NACNnGTN").collect::<Vec<_>>(), [0..1, 3..5, 7..8]);
+    }
+
+    #[test]
+    fn test_split_at_n_runs() {
+        let text = b"NNACGTNNACGTNNNNACGTN";
+        assert_eq!(split_at_n_runs(text, 3), [2..12, 16..20]);
+        assert_eq!(split_at_n_runs(text, 2), [2..6, 8..12, 16..20]);
+        assert_eq!(split_at_n_runs(text, 10), [2..20]);
+        assert!(split_at_n_runs(b"NNN", 5).is_empty());
+        assert_eq!(split_at_n_runs(b"ACGT", 1), [0..4]);
+    }
+}
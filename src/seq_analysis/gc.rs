@@ -6,8 +6,17 @@
 //! GC counter over an `IntoTextIterator` object.
 
 //! Complexity: O(n), where n is the length of the sequence.
+//!
+//! Besides the content of whole sequences, GC content and GC skew ((G - C) / (G + C)) can be
+//! computed in sliding windows (see `gc_windows` and `gc_skew_windows`), with counts being
+//! maintained while the window slides, such that the total complexity stays O(n) regardless of
+//! window size and step. The cumulative GC skew (see `cumulative_gc_skew`) of bacterial
+//! chromosomes is minimal at the origin and maximal at the terminus of replication.
 
 use std::borrow::Borrow;
+use std::iter;
+
+use crate::utils::TextSlice;
 
 /// Base gc content counter
 fn gcn_content<C: Borrow<u8>, T: IntoIterator<Item = C>>(sequence: T, step: usize) -> f32 {
@@ -60,6 +69,184 @@ pub fn gc3_content<C: Borrow<u8>, T: IntoIterator<Item = C>>(sequence: T) -> f32
     gcn_content(sequence, 3usize)
 }
 
+/// Counts of G and C in a window of a sequence.
+#[derive(
+    Default, Copy, Clone, Eq, PartialEq, Ord, PartialOrd, Hash, Debug, Serialize, Deserialize,
+)]
+pub struct WindowCounts {
+    /// Start position of the window.
+    pub pos: usize,
+    /// Length of the window.
+    pub len: usize,
+    pub g: usize,
+    pub c: usize,
+}
+
+impl WindowCounts {
+    /// Ratio of bases in the window which are guanine or cytosine.
+    pub fn gc_content(&self) -> f32 {
+        (self.g + self.c) as f32 / self.len as f32
+    }
+
+    /// GC skew (G - C) / (G + C) of the window, 0 if it contains neither G nor C.
+    pub fn gc_skew(&self) -> f32 {
+        if self.g + self.c == 0 {
+            0.0
+        } else {
+            (self.g as f32 - self.c as f32) / (self.g + self.c) as f32
+        }
+    }
+
+    fn update(&mut self, base: u8, add: bool) {
+        let count = match base {
+            b'g' | b'G' => &mut self.g,
+            b'c' | b'C' => &mut self.c,
+            _ => return,
+        };
+        if add {
+            *count += 1;
+        } else {
+            *count -= 1;
+        }
+    }
+}
+
+/// Iterator over the G and C counts of sliding windows of a sequence, see `windows`.
+#[derive(Clone, Debug)]
+pub struct Windows<'a> {
+    seq: TextSlice<'a>,
+    window: usize,
+    step: usize,
+    counts: Option<WindowCounts>,
+}
+
+impl<'a> Iterator for Windows<'a> {
+    type Item = WindowCounts;
+
+    fn next(&mut self) -> Option<WindowCounts> {
+        let counts = match self.counts {
+            None => {
+                let mut counts = WindowCounts {
+                    len: self.window,
+                    ..Default::default()
+                };
+                for &a in self.seq.get(..self.window)? {
+                    counts.update(a, true);
+                }
+                counts
+            }
+            Some(mut counts) => {
+                let (pos, end) = (counts.pos + self.step, counts.pos + self.step + self.window);
+                if end > self.seq.len() {
+                    return None;
+                }
+                if self.step < self.window {
+                    // slide the window, updating the counts
+                    for i in counts.pos..pos {
+                        counts.update(self.seq[i], false);
+                        counts.update(self.seq[i + self.window], true);
+                    }
+                } else {
+                    counts.g = 0;
+                    counts.c = 0;
+                    for &a in &self.seq[pos..end] {
+                        counts.update(a, true);
+                    }
+                }
+                counts.pos = pos;
+                counts
+            }
+        };
+        self.counts = Some(counts);
+        Some(counts)
+    }
+}
+
+/// Iterate over the G and C counts of all complete windows of the given length, starting
+/// every `step` bases.
+/// Complexity: O(n), where n is the length of the sequence.
+///
+/// # Panics
+///
+/// Panics if window or step are zero.
+pub fn windows(seq: TextSlice<'_>, window: usize, step: usize) -> Windows<'_> {
+    assert!(
+        window > 0 && step > 0,
+        "Expecting positive window length and step."
+    );
+    Windows {
+        seq,
+        window,
+        step,
+        counts: None,
+    }
+}
+
+/// Iterate over the start positions and GC contents of sliding windows (see `windows`).
+///
+/// # Example
+///
+/// ```
+/// use bio::seq_analysis::gc::gc_windows;
+///
+/// let gc = gc_windows(b"GGCCATATGC", 4, 2).collect::<Vec<_>>();
+/// assert_eq!(gc, [(0, 1.0), (2, 0.5), (4, 0.0), (6, 0.5)]);
+/// ```
+pub fn gc_windows(
+    seq: TextSlice<'_>,
+    window: usize,
+    step: usize,
+) -> impl Iterator<Item = (usize, f32)> + '_ {
+    windows(seq, window, step).map(|counts| (counts.pos, counts.gc_content()))
+}
+
+/// Iterate over the start positions and GC skews of sliding windows (see `windows`).
+///
+/// # Example
+///
+/// ```
+/// use bio::seq_analysis::gc::gc_skew_windows;
+///
+/// let skew = gc_skew_windows(b"GGGCATATCC", 4, 3).collect::<Vec<_>>();
+/// assert_eq!(skew, [(0, 0.5), (3, -1.0), (6, -1.0)]);
+/// ```
+pub fn gc_skew_windows(
+    seq: TextSlice<'_>,
+    window: usize,
+    step: usize,
+) -> impl Iterator<Item = (usize, f32)> + '_ {
+    windows(seq, window, step).map(|counts| (counts.pos, counts.gc_skew()))
+}
+
+/// Returns the cumulative GC skew, i.e. the number of G minus the number of C in each prefix
+/// of the sequence (with the empty prefix first). Its minimum indicates the origin of
+/// replication of bacterial chromosomes, its maximum the terminus.
+///
+/// # Example
+///
+/// ```
+/// use bio::seq_analysis::gc::cumulative_gc_skew;
+///
+/// let skew = cumulative_gc_skew(b"CCATGGGA");
+/// assert_eq!(skew, [0, -1, -2, -2, -2, -1, 0, 1, 1]);
+/// // the origin is expected after the position of minimal skew
+/// let origin = (0..skew.len()).min_by_key(|&i| skew[i]).unwrap();
+/// assert_eq!(origin, 2);
+/// ```
+pub fn cumulative_gc_skew<C: Borrow<u8>, T: IntoIterator<Item = C>>(sequence: T) -> Vec<i64> {
+    let mut skew = 0;
+    iter::once(0)
+        .chain(sequence.into_iter().map(|a| {
+            match *a.borrow() {
+                b'g' | b'G' => skew += 1,
+                b'c' | b'C' => skew -= 1,
+                _ => (),
+            }
+            skew
+        }))
+        .collect()
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -82,4 +269,35 @@ mod tests {
         let gc_content = gc_content(s);
         assert_relative_eq!(gc_content, 0.5, epsilon = f32::EPSILON);
     }
+
+    #[test]
+    fn test_windows() {
+        let seq = b"ACGTTGCAAGGCCNNgcAT";
+        for window in 1..8 {
+            for step in 1..10 {
+                let naive = (0..)
+                    .step_by(step)
+                    .take_while(|&pos| pos + window <= seq.len())
+                    .map(|pos| {
+                        let w = &seq[pos..pos + window];
+                        let count = |a: &[u8]| w.iter().filter(|b| a.contains(b)).count();
+                        WindowCounts {
+                            pos,
+                            len: window,
+                            g: count(b"Gg"),
+                            c: count(b"Cc"),
+                        }
+                    })
+                    .collect::<Vec<_>>();
+                assert_eq!(windows(seq, window, step).collect::<Vec<_>>(), naive);
+            }
+        }
+        assert!(windows(seq, 20, 1).next().is_none());
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_windows_zero_step() {
+        windows(b"ACGT", 2, 0);
+    }
 }
@@ -0,0 +1,360 @@
+//! Estimation of the genome size, heterozygosity and repeat content of a diploid genome from
+//! the k-mer coverage histogram of its sequencing reads (see `KmerCounter::histogram`), with a
+//! simplified version of the model of GenomeScope (Vurture et al., 2017).
+//!
+//! K-mers containing sequencing errors have a low coverage. They are excluded by cutting the
+//! histogram at its first valley. The remaining histogram is fitted with a mixture of negative
+//! binomial distributions with means λ, 2λ, 3λ and 4λ, where λ is the k-mer coverage of a
+//! single copy of the genome: k-mers covering heterozygous sites occur in one copy, homozygous
+//! k-mers in both copies, and duplicated k-mers in three or four copies. The variance of the
+//! component with mean μ is μ (1 + bias), i.e. the bias models the overdispersion of the
+//! coverage compared to a Poisson distribution.
+//!
+//! From the fitted model,
+//!
+//! * the haploid genome size is the number of k-mers without errors divided by 2λ,
+//! * the heterozygosity follows from the ratio of heterozygous and homozygous k-mers, given
+//!   that a k-mer is homozygous with probability (1 - r)^k for a heterozygosity r,
+//! * the repeat fraction is the fraction of k-mers without errors that are not explained by
+//!   single-copy heterozygous or homozygous k-mers.
+//!
+//! # Example
+//!
+//! ```
+//! use bio::data_structures::kmer_counting::KmerCounter;
+//! use bio::seq_analysis::genome_profile::GenomeProfile;
+//! use rand::prelude::*;
+//! use rand::rngs::StdRng;
+//!
+//! // a diploid genome of 20 kb with a heterozygous site every 500 bases
+//! let mut rng = StdRng::seed_from_u64(42);
+//! let maternal: Vec<u8> = (0..20_000).map(|_| *b"ACGT".choose(&mut rng).unwrap()).collect();
+//! let mut paternal = maternal.clone();
+//! for i in (250..paternal.len()).step_by(500) {
+//!     paternal[i] = if paternal[i] == b'A' { b'C' } else { b'A' };
+//! }
+//! // reads of 100 bases with a coverage of 15 per copy
+//! let mut counter = KmerCounter::new(21, true);
+//! for genome in &[maternal, paternal] {
+//!     for _ in 0..3000 {
+//!         let start = rng.gen_range(0..genome.len() - 100);
+//!         counter.add(&genome[start..start + 100]);
+//!     }
+//! }
+//!
+//! let profile = GenomeProfile::fit(&counter.histogram(), 21, 1000).unwrap();
+//! assert!((profile.genome_size - 20_000.0).abs() < 1000.0);
+//! assert!((profile.heterozygosity - 0.002).abs() < 0.0005);
+//! assert!(profile.repeat_fraction < 0.05);
+//! ```
+
+use statrs::function::gamma::ln_gamma;
+
+/// Number of mixture components, i.e. the largest modelled copy number of a k-mer.
+pub const COMPONENTS: usize = 4;
+
+/// Number of rounds of the coordinate-wise optimization of coverage and bias.
+const ROUNDS: usize = 4;
+
+/// Number of expectation maximization iterations for the component sizes.
+const EM_ITERATIONS: usize = 50;
+
+/// Relative difference of log likelihoods below which two fits are considered equally good.
+const LIKELIHOOD_TOLERANCE: f64 = 1e-6;
+
+/// A fitted k-mer coverage model of a diploid genome, see the module documentation.
+#[derive(Clone, PartialEq, Debug, Serialize, Deserialize)]
+pub struct GenomeProfile {
+    /// The k-mer length.
+    pub k: usize,
+    /// Coverage of the k-mers of a single copy of the genome (λ).
+    pub coverage: f64,
+    /// Overdispersion of the coverage, the component with mean μ has variance μ (1 + bias).
+    pub bias: f64,
+    /// Fitted number of distinct k-mers occurring in 1 to 4 copies.
+    pub components: [f64; COMPONENTS],
+    /// Smallest coverage of the fitted part of the histogram, lower coverages are considered
+    /// errors.
+    pub min_coverage: usize,
+    /// Estimated haploid genome size.
+    pub genome_size: f64,
+    /// Estimated fraction of heterozygous sites.
+    pub heterozygosity: f64,
+    /// Estimated fraction of the genome in repeats.
+    pub repeat_fraction: f64,
+    /// Differences between the histogram and the fitted model, starting at `min_coverage`.
+    pub residuals: Vec<f64>,
+    /// Poisson log likelihood of the fitted part of the histogram, up to a constant.
+    pub log_likelihood: f64,
+}
+
+impl GenomeProfile {
+    /// Fit the model to a k-mer coverage histogram, i.e. to the number of distinct k-mers of
+    /// each coverage, indexed by coverage. Coverages above `max_coverage` are not fitted but
+    /// counted as repeats. Returns `None` if the histogram has no peak after the errors.
+    ///
+    /// # Arguments
+    ///
+    /// * `histogram` - the k-mer coverage histogram, e.g. from `KmerCounter::histogram`
+    /// * `k` - the k-mer length
+    /// * `max_coverage` - the largest coverage to fit
+    pub fn fit(histogram: &[u64], k: usize, max_coverage: usize) -> Option<Self> {
+        let fitted = &histogram[..histogram.len().min(max_coverage + 1)];
+        let min_coverage = error_cutoff(fitted)?;
+        let observed: Vec<f64> = fitted[min_coverage..].iter().map(|&h| h as f64).collect();
+        let peak = (min_coverage..fitted.len()).max_by_key(|&c| fitted[c])?;
+
+        // The highest peak is either the heterozygous or the homozygous one. Since the mixture
+        // with half the coverage contains all components of the other one, both can explain the
+        // histogram equally well. Then, the highest peak is considered heterozygous, unless that
+        // leaves less than half of the k-mers of a copy homozygous.
+        let het_peak = Model::fit(&observed, min_coverage, peak as f64);
+        let hom_peak = Model::fit(&observed, min_coverage, peak as f64 / 2.0);
+        let [het, hom, ..] = het_peak.components;
+        let model = if het > 2.0 * hom
+            || hom_peak.log_likelihood - het_peak.log_likelihood
+                > LIKELIHOOD_TOLERANCE * het_peak.log_likelihood.abs()
+        {
+            hom_peak
+        } else {
+            het_peak
+        };
+
+        let total = histogram
+            .iter()
+            .enumerate()
+            .skip(min_coverage)
+            .map(|(c, &h)| c as f64 * h as f64)
+            .sum::<f64>();
+        let [het, hom, ..] = model.components;
+        let single_copy = model.coverage * (het + 2.0 * hom);
+        let homozygous = if het + hom > 0.0 {
+            2.0 * hom / (het + 2.0 * hom)
+        } else {
+            1.0
+        };
+        let expected = model.expected(min_coverage, observed.len());
+
+        Some(GenomeProfile {
+            k,
+            coverage: model.coverage,
+            bias: model.bias,
+            components: model.components,
+            min_coverage,
+            genome_size: total / (2.0 * model.coverage),
+            heterozygosity: 1.0 - homozygous.powf(1.0 / k as f64),
+            repeat_fraction: (1.0 - single_copy / total).max(0.0),
+            residuals: observed.iter().zip(expected).map(|(h, m)| h - m).collect(),
+            log_likelihood: model.log_likelihood,
+        })
+    }
+}
+
+/// The coverage at the first valley of the histogram, or 1 if the histogram has no error peak.
+/// Returns `None` if the histogram does not increase after the errors.
+fn error_cutoff(histogram: &[u64]) -> Option<usize> {
+    if histogram.len() < 3 {
+        return None;
+    }
+    (1..histogram.len() - 1).find(|&c| histogram[c] < histogram[c + 1])
+}
+
+/// The mixture of negative binomial distributions.
+#[derive(Clone, Debug)]
+struct Model {
+    coverage: f64,
+    bias: f64,
+    components: [f64; COMPONENTS],
+    log_likelihood: f64,
+}
+
+impl Model {
+    /// Fit the model to the observed histogram starting at `min_coverage`, with the coverage
+    /// initialized to the given value. Coverage and the logarithm of the bias are optimized
+    /// alternately by golden section search, the component sizes by expectation maximization.
+    fn fit(observed: &[f64], min_coverage: usize, coverage: f64) -> Self {
+        let mut model = Model::with_components(observed, min_coverage, coverage, 0.1);
+        for _ in 0..ROUNDS {
+            let bias = model.bias;
+            let coverage = golden_section(0.8 * coverage, 1.2 * coverage, |coverage| {
+                Model::with_components(observed, min_coverage, coverage, bias).log_likelihood
+            });
+            let ln_bias = golden_section(-8.0, 3.0, |ln_bias| {
+                Model::with_components(observed, min_coverage, coverage, ln_bias.exp())
+                    .log_likelihood
+            });
+            model = Model::with_components(observed, min_coverage, coverage, ln_bias.exp());
+        }
+        model
+    }
+
+    /// The model with the given coverage and bias and the component sizes maximizing the
+    /// likelihood of the observed histogram.
+    fn with_components(observed: &[f64], min_coverage: usize, coverage: f64, bias: f64) -> Self {
+        let pmfs: Vec<Vec<f64>> = (1..=COMPONENTS)
+            .map(|copies| {
+                (min_coverage..min_coverage + observed.len())
+                    .map(|c| nbinom_pmf(c, copies as f64 * coverage, bias))
+                    .collect()
+            })
+            .collect();
+        // the fitted part of the histogram only covers a fraction of each component
+        let covered: Vec<f64> = pmfs.iter().map(|pmf| pmf.iter().sum()).collect();
+
+        let total = observed.iter().sum::<f64>();
+        let mut model = Model {
+            coverage,
+            bias,
+            components: [total / COMPONENTS as f64; COMPONENTS],
+            log_likelihood: f64::NEG_INFINITY,
+        };
+        for _ in 0..EM_ITERATIONS {
+            let expected = model.mixture(&pmfs);
+            let mut components = [0.0; COMPONENTS];
+            for (i, pmf) in pmfs.iter().enumerate() {
+                if covered[i] > 0.0 {
+                    let responsibility = observed
+                        .iter()
+                        .zip(&expected)
+                        .zip(pmf)
+                        .filter(|((_, &m), _)| m > 0.0)
+                        .map(|((h, m), p)| h * p / m)
+                        .sum::<f64>();
+                    components[i] = model.components[i] * responsibility / covered[i];
+                }
+            }
+            model.components = components;
+        }
+        model.log_likelihood = observed
+            .iter()
+            .zip(model.mixture(&pmfs))
+            .map(|(h, m)| if m > 0.0 { h * m.ln() - m } else { 0.0 })
+            .sum();
+        model
+    }
+
+    fn mixture(&self, pmfs: &[Vec<f64>]) -> Vec<f64> {
+        let mut expected = vec![0.0; pmfs[0].len()];
+        for (size, pmf) in self.components.iter().zip(pmfs) {
+            for (m, p) in expected.iter_mut().zip(pmf) {
+                *m += size * p;
+            }
+        }
+        expected
+    }
+
+    /// Expected histogram for `len` coverages starting at `min_coverage`.
+    fn expected(&self, min_coverage: usize, len: usize) -> Vec<f64> {
+        let pmfs: Vec<Vec<f64>> = (1..=COMPONENTS)
+            .map(|copies| {
+                (min_coverage..min_coverage + len)
+                    .map(|c| nbinom_pmf(c, copies as f64 * self.coverage, self.bias))
+                    .collect()
+            })
+            .collect();
+        self.mixture(&pmfs)
+    }
+}
+
+/// Probability of `c` under a negative binomial distribution with the given mean and a
+/// variance of mean (1 + bias).
+fn nbinom_pmf(c: usize, mean: f64, bias: f64) -> f64 {
+    let size = mean / bias;
+    let c = c as f64;
+    (ln_gamma(c + size) - ln_gamma(size) - ln_gamma(c + 1.0) - size * bias.ln_1p()
+        + c * (bias / (1.0 + bias)).ln())
+    .exp()
+}
+
+/// The argument maximizing the unimodal function `f` in the interval [a, b].
+fn golden_section<F: Fn(f64) -> f64>(mut a: f64, mut b: f64, f: F) -> f64 {
+    let ratio = (5f64.sqrt() - 1.0) / 2.0;
+    let mut x1 = b - ratio * (b - a);
+    let mut x2 = a + ratio * (b - a);
+    let (mut f1, mut f2) = (f(x1), f(x2));
+    for _ in 0..24 {
+        if f1 < f2 {
+            a = x1;
+            x1 = x2;
+            f1 = f2;
+            x2 = a + ratio * (b - a);
+            f2 = f(x2);
+        } else {
+            b = x2;
+            x2 = x1;
+            f2 = f1;
+            x1 = b - ratio * (b - a);
+            f1 = f(x1);
+        }
+    }
+    (a + b) / 2.0
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Expected histogram of a genome with the given component sizes, plus errors.
+    fn histogram(coverage: f64, bias: f64, components: [f64; COMPONENTS]) -> Vec<u64> {
+        let model = Model {
+            coverage,
+            bias,
+            components,
+            log_likelihood: 0.0,
+        };
+        let mut histogram = vec![0];
+        histogram.extend(
+            model
+                .expected(1, 300)
+                .iter()
+                .enumerate()
+                .map(|(i, m)| (m + 2e6 * 0.1f64.powi(i as i32)).round() as u64),
+        );
+        histogram
+    }
+
+    #[test]
+    fn test_nbinom_pmf() {
+        let sum = (0..200).map(|c| nbinom_pmf(c, 30.0, 0.5)).sum::<f64>();
+        assert!((sum - 1.0).abs() < 1e-9);
+        let mean = (0..200)
+            .map(|c| c as f64 * nbinom_pmf(c, 30.0, 0.5))
+            .sum::<f64>();
+        assert!((mean - 30.0).abs() < 1e-6);
+    }
+
+    #[test]
+    fn test_fit() {
+        let components = [300_000.0, 800_000.0, 10_000.0, 30_000.0];
+        let profile = GenomeProfile::fit(&histogram(25.0, 0.5, components), 21, 1000).unwrap();
+        assert!((profile.coverage - 25.0).abs() < 0.1);
+        assert!((profile.bias - 0.5).abs() < 0.05);
+        for (fitted, expected) in profile.components.iter().zip(&components) {
+            assert!((fitted - expected).abs() < 0.01 * components[1]);
+        }
+        let heterozygosity = 1.0 - (1_600_000.0f64 / 1_900_000.0).powf(1.0 / 21.0);
+        assert!((profile.heterozygosity - heterozygosity).abs() < 1e-4);
+        let size = (300_000.0 + 2.0 * 800_000.0 + 3.0 * 10_000.0 + 4.0 * 30_000.0) / 2.0;
+        assert!((profile.genome_size - size).abs() < 0.01 * size);
+        let repeats = 1.0 - 1_900_000.0 / (2.0 * size);
+        assert!((profile.repeat_fraction - repeats).abs() < 0.01);
+        assert_eq!(profile.residuals.len(), 301 - profile.min_coverage);
+        assert!(profile.residuals.iter().all(|r| r.abs() < 500.0));
+    }
+
+    #[test]
+    fn test_homozygous() {
+        // the highest peak is the homozygous one
+        let profile =
+            GenomeProfile::fit(&histogram(20.0, 0.2, [0.0, 1e6, 0.0, 0.0]), 21, 1000).unwrap();
+        assert!((profile.coverage - 20.0).abs() < 0.1);
+        assert!(profile.heterozygosity < 1e-4);
+        assert!((profile.genome_size - 1e6).abs() < 1e4);
+    }
+
+    #[test]
+    fn test_no_peak() {
+        assert_eq!(GenomeProfile::fit(&[], 21, 1000), None);
+        assert_eq!(GenomeProfile::fit(&[0, 100, 10, 1], 21, 1000), None);
+    }
+}
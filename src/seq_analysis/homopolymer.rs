@@ -0,0 +1,280 @@
+//! Homopolymer compression (run-length encoding) of sequences, i.e. collapsing each run of
+//! equal symbols into a single symbol, along with the coordinate maps between compressed and
+//! original positions.
+//!
+//! Since the dominant error mode of nanopore (and to a lesser extent PacBio) reads is the
+//! wrong length of homopolymer runs, aligning homopolymer-compressed sequences is faster and
+//! more robust. With `expand_alignment`, an alignment of two compressed sequences is turned
+//! into one of the original sequences, where differing run lengths become insertions or
+//! deletions at the end of the runs.
+//!
+//! # Example
+//!
+//! ```
+//! use bio::alignment::pairwise::Aligner;
+//! use bio::alignment::AlignmentOperation::*;
+//! use bio::seq_analysis::homopolymer::{compress, expand_alignment};
+//!
+//! let x = compress(b"ACCCGTTAAAG");
+//! let y = compress(b"ACCGTTTTAAG");
+//! assert_eq!(x.seq(), b"ACGTAG");
+//! assert_eq!(x.seq(), y.seq());
+//! assert_eq!(x.run_len(1), 3);
+//! assert_eq!(x.original_pos(2), 4);
+//! assert_eq!(x.compressed_pos(3), 1);
+//!
+//! let score = |a: u8, b: u8| if a == b { 1i32 } else { -1i32 };
+//! let mut aligner = Aligner::new(-5, -1, score);
+//! let alignment = expand_alignment(&aligner.global(x.seq(), y.seq()), &x, &y);
+//! assert_eq!(alignment.score, 6);
+//! assert_eq!(
+//!     alignment.operations,
+//!     [Match, Match, Match, Ins, Match, Match, Match, Del, Del, Match, Match, Ins, Match]
+//! );
+//! ```
+
+use std::iter::repeat;
+use std::ops::Range;
+
+use crate::alignment::{Alignment, AlignmentOperation};
+use crate::utils::{Text, TextSlice};
+
+/// A homopolymer-compressed sequence.
+#[derive(Default, Clone, Eq, PartialEq, Hash, Debug, Serialize, Deserialize)]
+pub struct CompressedSeq {
+    seq: Text,
+    /// Original start position of each run, followed by the original length.
+    starts: Vec<usize>,
+}
+
+impl CompressedSeq {
+    /// The compressed sequence, with one symbol per run.
+    pub fn seq(&self) -> TextSlice<'_> {
+        &self.seq
+    }
+
+    /// Length of the compressed sequence, i.e. the number of runs.
+    pub fn len(&self) -> usize {
+        self.seq.len()
+    }
+
+    /// Whether the sequence is empty.
+    pub fn is_empty(&self) -> bool {
+        self.seq.is_empty()
+    }
+
+    /// Length of the original sequence.
+    pub fn original_len(&self) -> usize {
+        self.starts[self.seq.len()]
+    }
+
+    /// Length of the run at the given compressed position.
+    pub fn run_len(&self, i: usize) -> usize {
+        self.starts[i + 1] - self.starts[i]
+    }
+
+    /// Lengths of all runs.
+    pub fn run_lens(&self) -> impl Iterator<Item = usize> + '_ {
+        self.starts.windows(2).map(|w| w[1] - w[0])
+    }
+
+    /// Original interval of the run at the given compressed position.
+    pub fn run(&self, i: usize) -> Range<usize> {
+        self.starts[i]..self.starts[i + 1]
+    }
+
+    /// Original start position of the run at the given compressed position. The compressed
+    /// length is mapped to the original length, such that intervals can be mapped by their
+    /// bounds.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the position exceeds the compressed length.
+    pub fn original_pos(&self, i: usize) -> usize {
+        self.starts[i]
+    }
+
+    /// Compressed position of the run containing the given original position. The original
+    /// length is mapped to the compressed length.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the position exceeds the original length.
+    pub fn compressed_pos(&self, pos: usize) -> usize {
+        assert!(
+            pos <= self.original_len(),
+            "Expecting position within the original sequence."
+        );
+        self.starts.partition_point(|&start| start <= pos) - 1
+    }
+
+    /// Restore the original sequence.
+    pub fn expand(&self) -> Text {
+        self.seq
+            .iter()
+            .zip(self.run_lens())
+            .flat_map(|(&a, len)| repeat(a).take(len))
+            .collect()
+    }
+}
+
+/// Compress the runs of equal symbols of the given sequence. Symbols are compared exactly,
+/// i.e. case-sensitive.
+pub fn compress(seq: TextSlice<'_>) -> CompressedSeq {
+    let mut compressed = CompressedSeq {
+        seq: Vec::new(),
+        starts: Vec::new(),
+    };
+    for (i, &a) in seq.iter().enumerate() {
+        if compressed.seq.last() != Some(&a) {
+            compressed.seq.push(a);
+            compressed.starts.push(i);
+        }
+    }
+    compressed.starts.push(seq.len());
+    compressed
+}
+
+/// Map an alignment of the compressed sequences `x` and `y` to an alignment of the original
+/// sequences. Aligned runs of different length are aligned up to the shorter length, followed
+/// by insertions or deletions of the remaining symbols. Clips are extended to the original
+/// runs. The score of the alignment of the compressed sequences is kept.
+///
+/// # Panics
+///
+/// Panics if the alignment does not fit the compressed sequences.
+pub fn expand_alignment(alignment: &Alignment, x: &CompressedSeq, y: &CompressedSeq) -> Alignment {
+    use AlignmentOperation::*;
+
+    let (mut i, mut j) = (alignment.xstart, alignment.ystart);
+    let mut aligned = false;
+    let mut operations = Vec::with_capacity(alignment.operations.len());
+    for &op in &alignment.operations {
+        match op {
+            Match | Subst => {
+                let (xlen, ylen) = (x.run_len(i), y.run_len(j));
+                operations.extend(repeat(op).take(xlen.min(ylen)));
+                if xlen > ylen {
+                    operations.extend(repeat(Ins).take(xlen - ylen));
+                } else {
+                    operations.extend(repeat(Del).take(ylen - xlen));
+                }
+                i += 1;
+                j += 1;
+            }
+            Ins => {
+                operations.extend(repeat(Ins).take(x.run_len(i)));
+                i += 1;
+            }
+            Del => {
+                operations.extend(repeat(Del).take(y.run_len(j)));
+                j += 1;
+            }
+            // clips before the first aligned symbols are prefix clips
+            Xclip(_) if !aligned => operations.push(Xclip(x.original_pos(alignment.xstart))),
+            Yclip(_) if !aligned => operations.push(Yclip(y.original_pos(alignment.ystart))),
+            Xclip(_) => operations.push(Xclip(x.original_len() - x.original_pos(i))),
+            Yclip(_) => operations.push(Yclip(y.original_len() - y.original_pos(j))),
+        }
+        aligned |= !matches!(op, Xclip(_) | Yclip(_));
+    }
+
+    Alignment {
+        score: alignment.score,
+        xstart: x.original_pos(alignment.xstart),
+        xend: x.original_pos(alignment.xend),
+        xlen: x.original_len(),
+        ystart: y.original_pos(alignment.ystart),
+        yend: y.original_pos(alignment.yend),
+        ylen: y.original_len(),
+        operations,
+        mode: alignment.mode,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::alignment::pairwise::{Aligner, Scoring};
+
+    #[test]
+    fn test_compress() {
+        let seq = b"AAACGGTTTTa";
+        let compressed = compress(seq);
+        assert_eq!(compressed.seq(), b"ACGTa");
+        assert_eq!(compressed.run_lens().collect::<Vec<_>>(), [3, 1, 2, 4, 1]);
+        assert_eq!(compressed.run(3), 6..10);
+        assert_eq!(compressed.original_len(), seq.len());
+        assert_eq!(compressed.expand(), seq);
+        for pos in 0..=seq.len() {
+            let i = compressed.compressed_pos(pos);
+            assert!(i == compressed.len() || compressed.run(i).contains(&pos));
+        }
+        assert_eq!(compressed.original_pos(compressed.len()), seq.len());
+
+        let empty = compress(b"");
+        assert!(empty.is_empty());
+        assert_eq!(empty.original_len(), 0);
+        assert_eq!(empty.compressed_pos(0), 0);
+        assert!(empty.expand().is_empty());
+    }
+
+    #[test]
+    fn test_expand_alignment() {
+        use AlignmentOperation::*;
+
+        let (x, y) = (b"TTACCCGGTAAT", b"GGGACGGGTTTTAC");
+        let (cx, cy) = (compress(x), compress(y));
+        let scoring = Scoring::from_scores(-5, -1, 1, -1).xclip(-3).yclip(-3);
+        let mut aligner = Aligner::with_scoring(scoring);
+        for alignment in [
+            aligner.custom(cx.seq(), cy.seq()),
+            aligner.local(cx.seq(), cy.seq()),
+            aligner.semiglobal(cx.seq(), cy.seq()),
+            aligner.global(cx.seq(), cy.seq()),
+        ] {
+            let expanded = expand_alignment(&alignment, &cx, &cy);
+            assert_eq!((expanded.xlen, expanded.ylen), (x.len(), y.len()));
+            // the operations consume exactly the aligned parts of the original sequences
+            let (mut i, mut j) = (expanded.xstart, expanded.ystart);
+            for &op in &expanded.operations {
+                match op {
+                    Match | Subst => {
+                        assert_eq!(x[i] == y[j], op == Match);
+                        i += 1;
+                        j += 1;
+                    }
+                    Ins => i += 1,
+                    Del => j += 1,
+                    _ => (),
+                }
+            }
+            assert_eq!((i, j), (expanded.xend, expanded.yend));
+        }
+
+        let scoring = Scoring::from_scores(-5, -1, 1, -3).xclip(-1).yclip(-1);
+        let alignment = Aligner::with_scoring(scoring).custom(cx.seq(), cy.seq());
+        assert_eq!(
+            alignment.operations,
+            [Yclip(4), Match, Match, Match, Xclip(4)]
+        );
+        let expanded = expand_alignment(&alignment, &cx, &cy);
+        assert_eq!((expanded.xstart, expanded.xend), (0, 6));
+        assert_eq!((expanded.ystart, expanded.yend), (8, 14));
+        assert_eq!(
+            expanded.operations,
+            [
+                Yclip(8),
+                Match,
+                Match,
+                Del,
+                Del,
+                Match,
+                Match,
+                Ins,
+                Ins,
+                Xclip(6)
+            ]
+        );
+    }
+}
@@ -0,0 +1,270 @@
+//! Mappability (uniqueness) tracks of reference sequences, computed with an FM-index.
+//!
+//! For each position of the reference, the number of occurrences of the k-mer starting there is
+//! counted in the whole (indexed) reference, capped at a maximum. Reads of length k starting at
+//! positions with count 1 can be mapped uniquely. The track reports the mappability score
+//! 1 / count (0 for positions without a complete k-mer), as common for mappability tracks
+//! (Derrien et al., 2012), and can be written in the BedGraph format.
+//!
+//! The reference is the sentinel-terminated text of the FM-index, optionally consisting of
+//! multiple sentinel-separated sequences (e.g. chromosomes), such that k-mers never span a
+//! sentinel. To account for reads from the reverse strand, index the sequences together with
+//! their reverse complements (e.g. with `FMDIndex::from_texts`), such that the track covers
+//! both strands of each sequence.
+//! Complexity: O(nk) for a reference of length n.
+//!
+//! # Example
+//!
+//! ```
+//! use bio::alphabets::dna;
+//! use bio::data_structures::bwt::{bwt, less, Occ};
+//! use bio::data_structures::fmindex::FMIndex;
+//! use bio::data_structures::suffix_array::suffix_array;
+//! use bio::seq_analysis::mappability::Track;
+//!
+//! let text = b"ACGTACGTTT$GGACG$";
+//! let alphabet = dna::n_alphabet();
+//! let sa = suffix_array(text);
+//! let bwt = bwt(text, &sa);
+//! let less = less(&bwt, &alphabet);
+//! let occ = Occ::new(&bwt, 3, &alphabet);
+//! let fm = FMIndex::new(&bwt, &less, &occ);
+//!
+//! let track = Track::new(&fm, text, 3, 10);
+//! // ACG occurs three times, CGT twice
+//! assert_eq!(&track.counts()[..3], [3, 2, 1]);
+//! assert_eq!(track.score(1), 0.5);
+//!
+//! let mut bedgraph = Vec::new();
+//! track.write_bedgraph(&mut bedgraph, &["chr1", "chr2"]).unwrap();
+//! assert!(String::from_utf8(bedgraph)
+//!     .unwrap()
+//!     .starts_with("chr1\t0\t1\t0.3333333333333333\nchr1\t1\t2\t0.5\n"));
+//! ```
+
+use std::io;
+use std::ops::Range;
+
+use crate::data_structures::fmindex::{BackwardSearchResult, FMIndexable};
+use crate::utils::{parallel, TextSlice};
+
+/// A mappability track, holding the capped k-mer occurrence count of each position of a
+/// reference text (including the sentinels, which have count 0).
+#[derive(Default, Clone, Eq, PartialEq, Hash, Debug, Serialize, Deserialize)]
+pub struct Track {
+    k: usize,
+    max_count: u32,
+    counts: Vec<u32>,
+    /// Positions of the sentinels.
+    sentinels: Vec<usize>,
+}
+
+impl Track {
+    /// Compute the track of the given text, which has to be the text of the given FM-index.
+    ///
+    /// # Arguments
+    ///
+    /// * `fmindex` - the FM-index of the text
+    /// * `text` - the sentinel-terminated text
+    /// * `k` - the length of the k-mers (e.g. the read length)
+    /// * `max_count` - the maximum count to report, higher counts are capped
+    ///
+    /// # Panics
+    ///
+    /// Panics if k or `max_count` are zero.
+    pub fn new<F: FMIndexable>(fmindex: &F, text: TextSlice<'_>, k: usize, max_count: u32) -> Self {
+        assert!(
+            k > 0 && max_count > 0,
+            "Expecting positive k and max_count."
+        );
+        Track {
+            k,
+            max_count,
+            counts: counts(fmindex, text, k, max_count, 0..text.len()),
+            sentinels: sentinels(text),
+        }
+    }
+
+    /// Compute the track like `new`, using the given number of threads (`0` for all
+    /// available cores, see `utils::parallel`).
+    pub fn new_parallel<F: FMIndexable + Sync>(
+        fmindex: &F,
+        text: TextSlice<'_>,
+        k: usize,
+        max_count: u32,
+        threads: usize,
+    ) -> Self {
+        assert!(
+            k > 0 && max_count > 0,
+            "Expecting positive k and max_count."
+        );
+        let chunks = parallel::map_chunks(text, threads, 1, |offset, chunk| {
+            counts(fmindex, text, k, max_count, offset..offset + chunk.len())
+        });
+        Track {
+            k,
+            max_count,
+            counts: chunks.concat(),
+            sentinels: sentinels(text),
+        }
+    }
+
+    /// The length of the k-mers.
+    pub fn k(&self) -> usize {
+        self.k
+    }
+
+    /// The maximum reported count.
+    pub fn max_count(&self) -> u32 {
+        self.max_count
+    }
+
+    /// The capped occurrence counts of the k-mers starting at each position, 0 if there is no
+    /// complete k-mer (i.e. it would span a sentinel).
+    pub fn counts(&self) -> &[u32] {
+        &self.counts
+    }
+
+    /// The mappability score of the given position, i.e. 1 / count, or 0 without k-mer.
+    pub fn score(&self, pos: usize) -> f64 {
+        match self.counts[pos] {
+            0 => 0.0,
+            count => 1.0 / count as f64,
+        }
+    }
+
+    /// Whether the k-mer starting at the given position occurs only once in the text.
+    pub fn is_unique(&self, pos: usize) -> bool {
+        self.counts[pos] == 1
+    }
+
+    /// Write the track in BedGraph format, with one line per run of positions with equal
+    /// score. The sentinel-separated sequences of the text are named in order by the given
+    /// names, and coordinates are relative to the sequence starts.
+    ///
+    /// Returns an error of kind `InvalidInput` if there are fewer names than sequences.
+    pub fn write_bedgraph<W: io::Write>(&self, mut writer: W, names: &[&str]) -> io::Result<()> {
+        let mut names = names.iter();
+        let mut start = 0;
+        for &end in &self.sentinels {
+            let name = names.next().ok_or_else(|| {
+                io::Error::new(
+                    io::ErrorKind::InvalidInput,
+                    "Expecting a name for each sequence.",
+                )
+            })?;
+            let seq = &self.counts[start..end];
+            let mut run_start = 0;
+            for i in 1..=seq.len() {
+                if i == seq.len() || seq[i] != seq[run_start] {
+                    writeln!(
+                        writer,
+                        "{}\t{}\t{}\t{}",
+                        name,
+                        run_start,
+                        i,
+                        self.score(start + run_start)
+                    )?;
+                    run_start = i;
+                }
+            }
+            start = end + 1;
+        }
+        Ok(())
+    }
+}
+
+/// Positions of the sentinels of the text, i.e. of its last symbol.
+fn sentinels(text: TextSlice<'_>) -> Vec<usize> {
+    let sentinel = text[text.len() - 1];
+    (0..text.len()).filter(|&i| text[i] == sentinel).collect()
+}
+
+/// Capped counts of the k-mers starting at the given positions of the text.
+fn counts<F: FMIndexable>(
+    fmindex: &F,
+    text: TextSlice<'_>,
+    k: usize,
+    max_count: u32,
+    positions: Range<usize>,
+) -> Vec<u32> {
+    let sentinel = text[text.len() - 1];
+    positions
+        .map(|i| match text.get(i..i + k) {
+            Some(kmer) if !kmer.contains(&sentinel) => match fmindex.backward_search(kmer.iter()) {
+                BackwardSearchResult::Complete(interval) => {
+                    interval.len().min(max_count as usize) as u32
+                }
+                _ => 0,
+            },
+            _ => 0,
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::alphabets::dna;
+    use crate::data_structures::bwt::{bwt, less, Occ};
+    use crate::data_structures::fmindex::FMIndex;
+    use crate::data_structures::suffix_array::suffix_array;
+    use rand::prelude::*;
+    use rand::rngs::StdRng;
+
+    #[test]
+    fn test_naive() {
+        let mut rng = StdRng::seed_from_u64(42);
+        let mut text = (0..500)
+            .map(|i| {
+                if i == 300 {
+                    b'$'
+                } else {
+                    *b"ACGT".choose(&mut rng).unwrap()
+                }
+            })
+            .collect::<Vec<_>>();
+        text.push(b'$');
+        let alphabet = dna::n_alphabet();
+        let sa = suffix_array(&text);
+        let bwt = bwt(&text, &sa);
+        let less = less(&bwt, &alphabet);
+        let occ = Occ::new(&bwt, 3, &alphabet);
+        let fm = FMIndex::new(&bwt, &less, &occ);
+
+        let k = 5;
+        let track = Track::new(&fm, &text, k, 4);
+        for (i, &count) in track.counts().iter().enumerate() {
+            let expected = match text.get(i..i + k) {
+                Some(kmer) if !kmer.contains(&b'$') => {
+                    text.windows(k).filter(|w| w == &kmer).count().min(4) as u32
+                }
+                _ => 0,
+            };
+            assert_eq!(count, expected);
+        }
+        for threads in 1..4 {
+            assert_eq!(Track::new_parallel(&fm, &text, k, 4, threads), track);
+        }
+    }
+
+    #[test]
+    fn test_bedgraph() {
+        let track = Track {
+            k: 2,
+            max_count: 10,
+            counts: vec![1, 1, 2, 0, 0, 1, 0, 0],
+            sentinels: vec![4, 7],
+        };
+        let mut bedgraph = Vec::new();
+        track.write_bedgraph(&mut bedgraph, &["a", "b"]).unwrap();
+        assert_eq!(
+            String::from_utf8(bedgraph).unwrap(),
+            "a\t0\t2\t1\na\t2\t3\t0.5\na\t3\t4\t0\nb\t0\t1\t1\nb\t1\t2\t0\n"
+        );
+        assert_eq!(
+            track.write_bedgraph(io::sink(), &["a"]).unwrap_err().kind(),
+            io::ErrorKind::InvalidInput
+        );
+    }
+}
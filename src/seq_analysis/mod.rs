@@ -5,5 +5,22 @@
 
 //! Sequence analysis algorithms.
 
+pub mod chimera;
+pub mod codon_usage;
+pub mod consensus;
+pub mod context;
+pub mod digest;
+pub mod dust;
+pub mod gaps;
 pub mod gc;
+pub mod genome_profile;
+pub mod homopolymer;
+pub mod mappability;
+pub mod motif_enum;
 pub mod orf;
+pub mod prelude;
+pub mod read_lengths;
+pub mod read_merging;
+pub mod repeat_content;
+pub mod snv;
+pub mod translation;
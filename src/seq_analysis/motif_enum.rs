@@ -0,0 +1,248 @@
+//! Exhaustive enumeration of DNA motifs in the planted motif setting: given n sequences, find
+//! all words of length k that occur in at least q of them with up to d mismatches each. In
+//! contrast to expectation maximization approaches, the result is exact, which makes this
+//! suitable for small-scale motif finding, e.g. in a set of promoter sequences.
+//!
+//! For each window of each sequence, all words within Hamming distance d (its d-neighborhood)
+//! are enumerated, and each word counts the number of sequences in whose windows'
+//! neighborhoods it occurs. Windows containing other symbols than `ACGT` (case-insensitive)
+//! are skipped. Complexity: O(N * C(k, d) * 3^d) time for sequences of total length N, and
+//! memory proportional to the number of distinct words in the neighborhoods, so k and d should
+//! be small (e.g. k = 15 and d = 4).
+//!
+//! # Example
+//!
+//! ```
+//! use bio::seq_analysis::motif_enum::MotifEnumerator;
+//!
+//! // the motif ACGTTGCA is planted with one mismatch into each sequence
+//! let seqs = [
+//!     &b"TTTTACGTTGAATTTTT"[..],
+//!     b"GGGACCTTGCAGGG",
+//!     b"CCAAAACGATGCACCC",
+//! ];
+//! let enumerator = MotifEnumerator::new(8, 1, 3);
+//! let motifs = enumerator.enumerate(&seqs);
+//! assert!(motifs.iter().any(|motif| motif.word == b"ACGTTGCA"));
+//! assert!(motifs.iter().all(|motif| motif.support == 3));
+//! assert_eq!(enumerator.occurrences(b"ACGTTGCA", seqs[1]), [3]);
+//! ```
+
+use std::collections::HashMap;
+
+use crate::alignment::distance::hamming;
+use crate::alignment::sparse::HashMapFx;
+use crate::alphabets::dna;
+use crate::utils::{Text, TextSlice};
+
+/// 2-bit code of a base, `None` for other symbols.
+#[inline]
+fn encode(a: u8) -> Option<u64> {
+    match a {
+        b'A' | b'a' => Some(0),
+        b'C' | b'c' => Some(1),
+        b'G' | b'g' => Some(2),
+        b'T' | b't' => Some(3),
+        _ => None,
+    }
+}
+
+fn decode(word: u64, k: usize) -> Text {
+    (0..k)
+        .map(|i| b"ACGT"[(word >> (2 * (k - 1 - i)) & 3) as usize])
+        .collect()
+}
+
+/// Visit all words that differ from the given one in at most `d` positions at or after `from`.
+/// Each word is visited exactly once.
+fn neighborhood<F: FnMut(u64)>(word: u64, k: usize, from: usize, d: usize, visit: &mut F) {
+    visit(word);
+    if d == 0 {
+        return;
+    }
+    for pos in from..k {
+        let shift = 2 * (k - 1 - pos);
+        let base = word >> shift & 3;
+        for b in (0..4).filter(|&b| b != base) {
+            neighborhood(word & !(3 << shift) | b << shift, k, pos + 1, d - 1, visit);
+        }
+    }
+}
+
+/// A motif together with the number of sequences it occurs in.
+#[derive(Default, Clone, Eq, PartialEq, Ord, PartialOrd, Hash, Debug, Serialize, Deserialize)]
+pub struct Motif {
+    pub word: Text,
+    pub support: usize,
+}
+
+/// Enumerator of all words of length k occurring in at least a quorum of sequences with up to
+/// d mismatches.
+#[derive(Copy, Clone, Eq, PartialEq, Ord, PartialOrd, Hash, Debug, Serialize, Deserialize)]
+pub struct MotifEnumerator {
+    k: usize,
+    max_mismatches: usize,
+    quorum: usize,
+    both_strands: bool,
+}
+
+impl MotifEnumerator {
+    /// Create a new enumerator for motifs of length `k` that occur with up to `max_mismatches`
+    /// mismatches in at least `quorum` sequences.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `k` is zero or larger than 32, or not larger than `max_mismatches`.
+    pub fn new(k: usize, max_mismatches: usize, quorum: usize) -> Self {
+        assert!(
+            k > max_mismatches && k <= 32,
+            "Expecting motif length in {}..=32.",
+            max_mismatches + 1
+        );
+        MotifEnumerator {
+            k,
+            max_mismatches,
+            quorum,
+            both_strands: false,
+        }
+    }
+
+    /// Also count occurrences on the reverse strand. Only the lexicographically smaller word of
+    /// each pair of reverse complementary motifs is reported.
+    pub fn both_strands(mut self, both_strands: bool) -> Self {
+        self.both_strands = both_strands;
+        self
+    }
+
+    /// Enumerate the motifs of the given sequences, ordered by decreasing support and then
+    /// lexicographically.
+    pub fn enumerate<S: AsRef<[u8]>>(&self, seqs: &[S]) -> Vec<Motif> {
+        // number of supporting sequences and the index of the last one for each word
+        let mut counts: HashMapFx<u64, (usize, usize)> = HashMap::default();
+        for (i, seq) in seqs.iter().enumerate() {
+            let seq = seq.as_ref();
+            let mut visit = |word| {
+                let (support, last) = counts.entry(word).or_insert((0, usize::MAX));
+                if *last != i {
+                    *support += 1;
+                    *last = i;
+                }
+            };
+            self.windows(seq, &mut visit);
+            if self.both_strands {
+                self.windows(&dna::revcomp(seq), &mut visit);
+            }
+        }
+
+        let k = self.k;
+        let mut motifs = counts
+            .into_iter()
+            .filter(|&(_, (support, _))| support >= self.quorum)
+            .map(|(word, (support, _))| Motif {
+                word: decode(word, k),
+                support,
+            })
+            .filter(|motif| !self.both_strands || motif.word <= dna::revcomp(&motif.word))
+            .collect::<Vec<_>>();
+        motifs.sort_unstable_by(|a, b| b.support.cmp(&a.support).then(a.word.cmp(&b.word)));
+        motifs
+    }
+
+    /// Visit the neighborhoods of all windows of the sequence.
+    fn windows<F: FnMut(u64)>(&self, seq: TextSlice<'_>, visit: &mut F) {
+        let mask = if self.k == 32 {
+            u64::MAX
+        } else {
+            (1 << (2 * self.k)) - 1
+        };
+        let (mut word, mut valid) = (0, 0);
+        for &a in seq {
+            match encode(a) {
+                Some(code) => {
+                    word = (word << 2 | code) & mask;
+                    valid += 1;
+                }
+                None => valid = 0,
+            }
+            if valid >= self.k {
+                neighborhood(word, self.k, 0, self.max_mismatches, visit);
+            }
+        }
+    }
+
+    /// Start positions of the occurrences of a word with up to `max_mismatches` mismatches in
+    /// the given sequence (case-insensitive), on the forward strand.
+    pub fn occurrences(&self, word: TextSlice<'_>, seq: TextSlice<'_>) -> Vec<usize> {
+        let word = word.to_ascii_uppercase();
+        let seq = seq.to_ascii_uppercase();
+        seq.windows(word.len())
+            .enumerate()
+            .filter(|(_, window)| hamming(window, &word) as usize <= self.max_mismatches)
+            .map(|(i, _)| i)
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use itertools::Itertools;
+
+    #[test]
+    fn test_neighborhood() {
+        let mut words = Vec::new();
+        neighborhood(0b00_01_10, 3, 0, 1, &mut |word| words.push(word));
+        // the word itself and 3 * 3 substitutions
+        assert_eq!(words.len(), 10);
+        assert_eq!(words.iter().unique().count(), 10);
+        words.clear();
+        neighborhood(0, 4, 0, 2, &mut |word| words.push(word));
+        assert_eq!(words.len(), 1 + 4 * 3 + 6 * 9);
+        assert_eq!(words.iter().unique().count(), words.len());
+        assert_eq!(decode(0b00_01_10_11, 4), b"ACGT");
+    }
+
+    #[test]
+    fn test_naive() {
+        let seqs = [
+            &b"ACGTTGACAGTACGTTTACG"[..],
+            b"TTGACGTAACGGTA",
+            b"GGGGGGGGACGTTG",
+        ];
+        let enumerator = MotifEnumerator::new(4, 1, 2);
+        let motifs = enumerator.enumerate(&seqs);
+        // compare with checking all words of length 4
+        let expected = (0..4)
+            .map(|_| b"ACGT".iter().cloned())
+            .multi_cartesian_product()
+            .filter_map(|word| {
+                let support = seqs
+                    .iter()
+                    .filter(|seq| !enumerator.occurrences(&word, seq).is_empty())
+                    .count();
+                Some(Motif { word, support }).filter(|motif| motif.support >= 2)
+            })
+            .sorted_by(|a, b| b.support.cmp(&a.support).then(a.word.cmp(&b.word)))
+            .collect::<Vec<_>>();
+        assert_eq!(motifs, expected);
+        // windows with other symbols are skipped
+        let enumerator = MotifEnumerator::new(3, 0, 1);
+        assert_eq!(enumerator.enumerate(&[b"ACNGTa"])[0].word, b"GTA");
+        assert_eq!(enumerator.enumerate(&[b"ACNGTa"]).len(), 1);
+    }
+
+    #[test]
+    fn test_both_strands() {
+        let seqs = [&b"AAAACCGTTT"[..], b"TTTAACGGTT"];
+        let enumerator = MotifEnumerator::new(6, 0, 2);
+        assert!(enumerator.enumerate(&seqs).is_empty());
+        let motifs = enumerator.both_strands(true).enumerate(&seqs);
+        assert_eq!(
+            motifs
+                .iter()
+                .map(|motif| &motif.word[..])
+                .collect::<Vec<_>>(),
+            [b"AACCGT", b"AACGGT"]
+        );
+    }
+}
@@ -18,21 +18,51 @@
 //!
 //! let sequence = b"ACGGCTAGAAAAGGCTAGAAAA";
 //!
-//! for Orf { start, end, offset } in finder.find_all(sequence) {
+//! for Orf { start, end, offset, .. } in finder.find_all(sequence) {
 //!     let orf = &sequence[start..end];
 //!     //...do something with orf sequence...
 //! }
 //! ```
 //!
-//! Right now the only way to check the reverse strand for ORF is to use
-//! the `alphabet::dna::RevComp` struct and to check for both sequences.
-//! But that's not so performance friendly, as the reverse complementation and the ORF research
-//! could go on at the same time.
+//! Both strands can be searched with `Finder::find_all_strands`, and circular sequences
+//! (e.g. plasmids) with `Finder::find_all_circular`, which also reports ORFs spanning the origin.
+//!
+//! Finders can also be configured with a `FinderBuilder`, which validates the codons and can be
+//! (de)serialized, e.g. for loading the parameters of a tool from a configuration file.
 
 use std::borrow::Borrow;
 use std::collections::VecDeque;
 use std::iter;
 
+use thiserror::Error;
+
+use crate::alphabets::dna;
+use crate::seq_analysis::translation::GeneticCode;
+use crate::utils::{Text, TextSlice};
+use bio_types::strand::ReqStrand;
+
+#[derive(
+    Error, Copy, Clone, Eq, PartialEq, Ord, PartialOrd, Hash, Debug, Serialize, Deserialize,
+)]
+pub enum Error {
+    #[error("at least one start codon is required")]
+    NoStartCodons,
+    #[error("at least one stop codon is required")]
+    NoStopCodons,
+    #[error(
+        "codon {} contains symbols other than ACGT",
+        String::from_utf8_lossy(codon)
+    )]
+    InvalidCodon { codon: [u8; 3] },
+    #[error(
+        "codon {} is both a start and a stop codon",
+        String::from_utf8_lossy(codon)
+    )]
+    StartIsStop { codon: [u8; 3] },
+}
+
+pub type Result<T, E = Error> = std::result::Result<T, E>;
+
 /// An implementation of a naive algorithm finder
 // Implementation note:
 //
@@ -80,18 +110,232 @@ impl Finder {
             seq: seq.into_iter().enumerate(),
         }
     }
+
+    /// Create a new finder for the start and stop codons of the given genetic code, e.g.
+    /// including the alternative start codons `GTG` and `TTG` of bacteria (table 11).
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use bio::seq_analysis::orf::Finder;
+    /// use bio::seq_analysis::translation::GeneticCode;
+    ///
+    /// let finder = Finder::from_genetic_code(GeneticCode::from_id(11).unwrap(), 6);
+    /// let orfs = finder.find_all(b"CCGTGAAACCCTAGG").collect::<Vec<_>>();
+    /// assert_eq!((orfs[0].start, orfs[0].end), (2, 14));
+    /// ```
+    pub fn from_genetic_code(code: &GeneticCode, min_len: usize) -> Self {
+        let start_codons = code.start_codons();
+        let stop_codons = code.stop_codons();
+        Finder::new(
+            start_codons.iter().collect(),
+            stop_codons.iter().collect(),
+            min_len,
+        )
+    }
+
+    /// Find all ORFs in the given circular sequence (e.g. a plasmid or bacterial genome),
+    /// including those spanning the origin, which have an end beyond the sequence length.
+    /// ORFs are sorted by their end position, like with `find_all`. ORFs longer than the
+    /// sequence are not reported.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use bio::seq_analysis::orf::Finder;
+    ///
+    /// let finder = Finder::new(vec![b"ATG"], vec![b"TAA"], 6);
+    /// let seq = b"AAACCCTAAGGGGATGCCC";
+    /// let orfs = finder.find_all_circular(seq);
+    /// assert_eq!(orfs.len(), 1);
+    /// assert_eq!((orfs[0].start, orfs[0].end), (13, 28));
+    /// assert_eq!(orfs[0].seq(seq), b"ATGCCCAAACCCTAA");
+    /// ```
+    pub fn find_all_circular(&self, seq: TextSlice<'_>) -> Vec<Orf> {
+        let n = seq.len();
+        self.find_all(seq.iter().chain(seq))
+            .filter(|orf| orf.start < n && orf.len() <= n)
+            .collect()
+    }
+
+    /// Find all ORFs on both strands of the given (optionally circular) sequence. ORFs of the
+    /// forward strand come first, followed by those of the reverse strand (in coordinates of
+    /// the forward strand, see `Orf`).
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use bio::seq_analysis::orf::Finder;
+    /// use bio_types::strand::ReqStrand;
+    ///
+    /// let finder = Finder::new(vec![b"ATG"], vec![b"TAA", b"TAG", b"TGA"], 6);
+    /// let seq = b"GGCTAGGGTTTCATGG";
+    /// let orfs = finder.find_all_strands(seq, false);
+    /// assert_eq!(orfs.len(), 1);
+    /// assert_eq!(orfs[0].strand, ReqStrand::Reverse);
+    /// assert_eq!((orfs[0].start, orfs[0].end), (2, 14));
+    /// assert_eq!(orfs[0].seq(seq), b"ATGAAACCCTAG");
+    /// ```
+    pub fn find_all_strands(&self, seq: TextSlice<'_>, circular: bool) -> Vec<Orf> {
+        let find = |seq: TextSlice<'_>| {
+            if circular {
+                self.find_all_circular(seq)
+            } else {
+                self.find_all(seq).collect()
+            }
+        };
+        let n = seq.len();
+        let mut orfs = find(seq);
+        orfs.extend(find(&dna::revcomp(seq)).into_iter().map(|orf| {
+            // map back to the forward strand, shifting ORFs that span the origin
+            let shift = if orf.end > n { n } else { 0 };
+            Orf {
+                start: n + shift - orf.end,
+                end: n + shift - orf.start,
+                offset: orf.offset,
+                strand: ReqStrand::Reverse,
+            }
+        }));
+        orfs
+    }
+}
+
+/// A builder for ORF finders, validating the codons. Without further configuration, it uses the
+/// start and stop codons of the standard genetic code and a minimum ORF length of 75 (as NCBI
+/// ORFfinder). Missing fields are set to these defaults when deserializing.
+///
+/// # Example
+///
+/// ```
+/// use bio::seq_analysis::orf::{Error, FinderBuilder};
+///
+/// let finder = FinderBuilder::new().min_len(6).build().unwrap();
+/// let orfs = finder.find_all(b"CCATGAAATAGG").collect::<Vec<_>>();
+/// assert_eq!((orfs[0].start, orfs[0].end), (2, 11));
+///
+/// let builder = FinderBuilder::new().start_codons([*b"ATG", *b"TAA"]);
+/// assert_eq!(builder.build(), Err(Error::StartIsStop { codon: *b"TAA" }));
+/// ```
+#[derive(Clone, Eq, PartialEq, Ord, PartialOrd, Hash, Debug, Serialize, Deserialize)]
+#[serde(default)]
+pub struct FinderBuilder {
+    start_codons: Vec<[u8; 3]>,
+    stop_codons: Vec<[u8; 3]>,
+    min_len: usize,
+}
+
+impl Default for FinderBuilder {
+    fn default() -> Self {
+        FinderBuilder {
+            start_codons: vec![*b"ATG"],
+            stop_codons: vec![*b"TAA", *b"TAG", *b"TGA"],
+            min_len: 75,
+        }
+    }
+}
+
+impl FinderBuilder {
+    /// Create a new builder with the default configuration.
+    pub fn new() -> Self {
+        FinderBuilder::default()
+    }
+
+    /// Use the given start codons (default: `ATG`).
+    pub fn start_codons<I: IntoIterator<Item = [u8; 3]>>(mut self, codons: I) -> Self {
+        self.start_codons = codons.into_iter().collect();
+        self
+    }
+
+    /// Use the given stop codons (default: `TAA`, `TAG` and `TGA`).
+    pub fn stop_codons<I: IntoIterator<Item = [u8; 3]>>(mut self, codons: I) -> Self {
+        self.stop_codons = codons.into_iter().collect();
+        self
+    }
+
+    /// Use the start and stop codons of the given genetic code.
+    pub fn genetic_code(mut self, code: &GeneticCode) -> Self {
+        self.start_codons = code.start_codons();
+        self.stop_codons = code.stop_codons();
+        self
+    }
+
+    /// Report only ORFs of at least the given length (default: 75).
+    pub fn min_len(mut self, min_len: usize) -> Self {
+        self.min_len = min_len;
+        self
+    }
+
+    /// Build the finder.
+    ///
+    /// # Errors
+    /// * `Error::NoStartCodons` and `Error::NoStopCodons` - if there are no start or stop codons
+    /// * `Error::InvalidCodon` - a codon contains symbols other than `ACGT`
+    /// * `Error::StartIsStop` - a codon is used as start and stop codon
+    pub fn build(&self) -> Result<Finder> {
+        if self.start_codons.is_empty() {
+            return Err(Error::NoStartCodons);
+        }
+        if self.stop_codons.is_empty() {
+            return Err(Error::NoStopCodons);
+        }
+        for codon in self.start_codons.iter().chain(&self.stop_codons) {
+            if !codon.iter().all(|a| b"ACGT".contains(a)) {
+                return Err(Error::InvalidCodon { codon: *codon });
+            }
+        }
+        if let Some(codon) = self
+            .start_codons
+            .iter()
+            .find(|codon| self.stop_codons.contains(codon))
+        {
+            return Err(Error::StartIsStop { codon: *codon });
+        }
+        Ok(Finder::new(
+            self.start_codons.iter().collect(),
+            self.stop_codons.iter().collect(),
+            self.min_len,
+        ))
+    }
 }
 
 /// An ORF representation with start and end position of said ORF,
-/// as well as offset of the reading frame (1,2,3) and strand location
-// (current: +, reverse complementary: -).
-#[derive(
-    Default, Copy, Clone, Eq, PartialEq, Ord, PartialOrd, Hash, Debug, Serialize, Deserialize,
-)]
+/// as well as offset of the reading frame (0, 1, 2) and strand location
+/// (forward: +, reverse complementary: -).
+///
+/// Positions always refer to the forward strand, i.e. an ORF on the reverse strand covers
+/// the reverse complement of `start..end`, and its offset is the frame on the reverse
+/// complement. ORFs of circular sequences (see `Finder::find_all_circular`) that span the
+/// origin have an end beyond the sequence length, continuing at its beginning.
+#[derive(Copy, Clone, Eq, PartialEq, Ord, PartialOrd, Hash, Debug, Serialize, Deserialize)]
 pub struct Orf {
     pub start: usize,
     pub end: usize,
     pub offset: i8,
+    pub strand: ReqStrand,
+}
+
+impl Orf {
+    /// Length of the ORF, including the stop codon.
+    pub fn len(&self) -> usize {
+        self.end - self.start
+    }
+
+    /// Whether the ORF is empty (which never happens for ORFs found by a `Finder`).
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Extract the sequence of the ORF (reverse complemented on the reverse strand) from the
+    /// sequence it was found in, e.g. for translating it.
+    pub fn seq(&self, seq: TextSlice<'_>) -> Text {
+        let orf = (self.start..self.end)
+            .map(|i| seq[i % seq.len()])
+            .collect::<Text>();
+        match self.strand {
+            ReqStrand::Reverse => dna::revcomp(orf),
+            _ => orf,
+        }
+    }
 }
 
 /// The current algorithm state.
@@ -164,6 +408,7 @@ where
                                 start: start_pos - 2,
                                 end: index + 1,
                                 offset: offset as i8,
+                                strand: ReqStrand::Forward,
                             });
                         // if the first orf is too short, so are the others
                         } else {
@@ -208,6 +453,7 @@ mod tests {
             start: 3,
             end: 12,
             offset: 0,
+            strand: ReqStrand::Forward,
         }];
         assert_eq!(expected, finder.find_all(sequence).collect::<Vec<Orf>>());
     }
@@ -220,6 +466,7 @@ mod tests {
             start: 4,
             end: 13,
             offset: 1,
+            strand: ReqStrand::Forward,
         }];
         assert_eq!(expected, finder.find_all(sequence).collect::<Vec<Orf>>());
     }
@@ -233,11 +480,13 @@ mod tests {
                 start: 0,
                 end: 9,
                 offset: 0,
+                strand: ReqStrand::Forward,
             },
             Orf {
                 start: 14,
                 end: 26,
                 offset: 2,
+                strand: ReqStrand::Forward,
             },
         ];
         assert_eq!(expected, finder.find_all(sequence).collect::<Vec<Orf>>());
@@ -252,18 +501,85 @@ mod tests {
                 start: 14,
                 end: 26,
                 offset: 2,
+                strand: ReqStrand::Forward,
             },
             Orf {
                 start: 0,
                 end: 30,
                 offset: 0,
+                strand: ReqStrand::Forward,
             },
             Orf {
                 start: 6,
                 end: 30,
                 offset: 0,
+                strand: ReqStrand::Forward,
             },
         ];
         assert_eq!(expected, finder.find_all(sequence).collect::<Vec<Orf>>());
     }
+
+    #[test]
+    fn test_circular() {
+        let finder = basic_finder();
+        // the ORF spans the origin
+        let sequence = b"TGAGGGGGGATGGGG";
+        assert!(finder.find_all(sequence).next().is_none());
+        let expected = vec![Orf {
+            start: 9,
+            end: 18,
+            offset: 0,
+            strand: ReqStrand::Forward,
+        }];
+        assert_eq!(finder.find_all_circular(sequence), expected);
+        assert_eq!(expected[0].seq(sequence), b"ATGGGGTGA");
+
+        // linear ORFs are found as well, but not twice
+        let sequence = b"GGGATGGGGTGAGGG";
+        assert_eq!(
+            finder.find_all_circular(sequence),
+            finder.find_all(sequence).collect::<Vec<_>>()
+        );
+    }
+
+    #[test]
+    fn test_strands() {
+        let finder = basic_finder();
+        let forward = b"TGAGGGGGGATGGGG";
+        let reverse = dna::revcomp(forward);
+        let orfs = finder.find_all_strands(&reverse, true);
+        assert_eq!(orfs.len(), 1);
+        assert_eq!(orfs[0].strand, ReqStrand::Reverse);
+        assert_eq!(orfs[0].seq(&reverse), b"ATGGGGTGA");
+        assert!(orfs[0].end > reverse.len());
+    }
+
+    #[test]
+    fn test_builder() {
+        assert_eq!(
+            FinderBuilder::new()
+                .stop_codons(vec![*b"TGA", *b"TAG", *b"TAA"])
+                .min_len(5)
+                .build()
+                .unwrap(),
+            basic_finder()
+        );
+        let bacterial = FinderBuilder::new()
+            .genetic_code(GeneticCode::from_id(11).unwrap())
+            .build()
+            .unwrap();
+        assert_eq!(bacterial.start_codons.len(), 7);
+        assert_eq!(
+            FinderBuilder::new().stop_codons(Vec::new()).build(),
+            Err(Error::NoStopCodons)
+        );
+        assert_eq!(
+            FinderBuilder::new().start_codons(Vec::new()).build(),
+            Err(Error::NoStartCodons)
+        );
+        assert_eq!(
+            FinderBuilder::new().start_codons(vec![*b"atg"]).build(),
+            Err(Error::InvalidCodon { codon: *b"atg" })
+        );
+    }
 }
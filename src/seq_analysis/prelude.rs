@@ -0,0 +1,27 @@
+//! The sequence analysis prelude, re-exporting the configurable analyses and their builders,
+//! such that they can be imported at once.
+//!
+//! # Example
+//!
+//! ```
+//! use bio::seq_analysis::prelude::*;
+//!
+//! let finder = FinderBuilder::new()
+//!     .genetic_code(GeneticCode::from_id(11).unwrap())
+//!     .min_len(6)
+//!     .build()
+//!     .unwrap();
+//! let orfs: Vec<Orf> = finder.find_all(b"CCATGAAATAGG").collect();
+//! assert_eq!(orfs.len(), 1);
+//!
+//! let merger: Merger = MergerBuilder::new().min_overlap(20).build().unwrap();
+//! assert_eq!(merger.min_overlap, 20);
+//! ```
+
+pub use crate::seq_analysis::chimera::ChimeraDetector;
+pub use crate::seq_analysis::consensus::ConsensusCaller;
+pub use crate::seq_analysis::motif_enum::MotifEnumerator;
+pub use crate::seq_analysis::orf::{FinderBuilder, Orf};
+pub use crate::seq_analysis::read_merging::{Merger, MergerBuilder};
+pub use crate::seq_analysis::snv::SnvCaller;
+pub use crate::seq_analysis::translation::GeneticCode;
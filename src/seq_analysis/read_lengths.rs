@@ -0,0 +1,300 @@
+//! Read length distributions and subsampling of reads to a target number of bases, e.g. for
+//! reducing long read data sets to a given coverage before assembly.
+//!
+//! The distribution is computed online, i.e. while streaming over the reads, storing only one
+//! count per distinct length. From it, the minimum length such that the reads of at least that
+//! length reach the target (see `LengthDistribution::length_threshold`), or the fraction of
+//! reads to sample at random (see `LengthDistribution::sampling_fraction`) can be obtained, such
+//! that reads can be selected in a second pass over the data. If all lengths fit into memory,
+//! `select_longest` and `select_random` directly choose the indices of the reads to keep.
+//!
+//! # Example
+//!
+//! ```
+//! use bio::seq_analysis::read_lengths::{select_longest, LengthDistribution};
+//!
+//! let lengths = [1000, 5000, 200, 8000, 3000, 5000];
+//! let dist = lengths.iter().cloned().collect::<LengthDistribution>();
+//! assert_eq!(dist.total_bases(), 22_200);
+//! assert_eq!(dist.n50(), Some(5000));
+//! assert_eq!(dist.median(), Some(3000));
+//!
+//! // keep 2x coverage of a 6 kb genome, longest reads first
+//! assert_eq!(dist.length_threshold(12_000), Some(5000));
+//! assert_eq!(select_longest(&lengths, 12_000), [1, 3]);
+//! ```
+
+use std::collections::BTreeMap;
+use std::iter::FromIterator;
+
+use rand::seq::SliceRandom;
+use rand::Rng;
+
+/// A distribution of read lengths.
+#[derive(Default, Clone, Eq, PartialEq, Debug, Serialize, Deserialize)]
+pub struct LengthDistribution {
+    counts: BTreeMap<usize, u64>,
+    reads: u64,
+    total_bases: u64,
+}
+
+impl LengthDistribution {
+    /// Create a new, empty distribution.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add a read of the given length.
+    pub fn add(&mut self, len: usize) {
+        *self.counts.entry(len).or_insert(0) += 1;
+        self.reads += 1;
+        self.total_bases += len as u64;
+    }
+
+    /// Number of reads.
+    pub fn reads(&self) -> u64 {
+        self.reads
+    }
+
+    /// Total number of bases of all reads.
+    pub fn total_bases(&self) -> u64 {
+        self.total_bases
+    }
+
+    /// Whether no reads have been added.
+    pub fn is_empty(&self) -> bool {
+        self.reads == 0
+    }
+
+    /// The number of reads of each length, in increasing order of length.
+    pub fn histogram(&self) -> impl Iterator<Item = (usize, u64)> + '_ {
+        self.counts.iter().map(|(&len, &count)| (len, count))
+    }
+
+    /// Shortest read length.
+    pub fn min(&self) -> Option<usize> {
+        self.counts.keys().next().cloned()
+    }
+
+    /// Longest read length.
+    pub fn max(&self) -> Option<usize> {
+        self.counts.keys().next_back().cloned()
+    }
+
+    /// Mean read length.
+    pub fn mean(&self) -> Option<f64> {
+        if self.is_empty() {
+            None
+        } else {
+            Some(self.total_bases as f64 / self.reads as f64)
+        }
+    }
+
+    /// The smallest length such that at least the given fraction of the reads are not longer
+    /// (e.g. 0.5 for the median).
+    ///
+    /// # Panics
+    ///
+    /// Panics if the fraction is not in [0, 1].
+    pub fn quantile(&self, fraction: f64) -> Option<usize> {
+        assert!(
+            (0.0..=1.0).contains(&fraction),
+            "Expecting fraction in [0, 1]."
+        );
+        let target = ((fraction * self.reads as f64).ceil() as u64).max(1);
+        let mut reads = 0;
+        self.counts.iter().find_map(|(&len, &count)| {
+            reads += count;
+            if reads >= target {
+                Some(len)
+            } else {
+                None
+            }
+        })
+    }
+
+    /// Median read length (the lower median for an even number of reads).
+    pub fn median(&self) -> Option<usize> {
+        self.quantile(0.5)
+    }
+
+    /// The Nx value, i.e. the length such that reads at least as long contain at least the
+    /// given fraction of all bases (e.g. 0.5 for N50).
+    ///
+    /// # Panics
+    ///
+    /// Panics if the fraction is not in [0, 1].
+    pub fn nx(&self, fraction: f64) -> Option<usize> {
+        assert!(
+            (0.0..=1.0).contains(&fraction),
+            "Expecting fraction in [0, 1]."
+        );
+        self.length_threshold((fraction * self.total_bases as f64).ceil() as u64)
+            .or_else(|| self.min())
+    }
+
+    /// The N50 value (see `nx`).
+    pub fn n50(&self) -> Option<usize> {
+        self.nx(0.5)
+    }
+
+    /// The largest length such that the reads at least as long contain at least the given
+    /// number of bases, i.e. the length cutoff for selecting the longest reads up to the target.
+    /// Returns `None` if all reads together have fewer bases.
+    pub fn length_threshold(&self, target_bases: u64) -> Option<usize> {
+        if target_bases > self.total_bases || self.is_empty() {
+            return None;
+        }
+        let mut bases = 0;
+        self.counts.iter().rev().find_map(|(&len, &count)| {
+            bases += len as u64 * count;
+            if bases >= target_bases {
+                Some(len)
+            } else {
+                None
+            }
+        })
+    }
+
+    /// The fraction of reads to sample at random to obtain the given number of bases in
+    /// expectation, capped at 1.
+    pub fn sampling_fraction(&self, target_bases: u64) -> f64 {
+        if self.total_bases == 0 {
+            1.0
+        } else {
+            (target_bases as f64 / self.total_bases as f64).min(1.0)
+        }
+    }
+}
+
+impl Extend<usize> for LengthDistribution {
+    fn extend<I: IntoIterator<Item = usize>>(&mut self, lengths: I) {
+        for len in lengths {
+            self.add(len);
+        }
+    }
+}
+
+impl FromIterator<usize> for LengthDistribution {
+    fn from_iter<I: IntoIterator<Item = usize>>(lengths: I) -> Self {
+        let mut dist = LengthDistribution::new();
+        dist.extend(lengths);
+        dist
+    }
+}
+
+/// Take reads in the given order until their lengths sum up to the target, returning their
+/// sorted indices.
+fn take_until<I: Iterator<Item = usize>>(
+    order: I,
+    lengths: &[usize],
+    target_bases: u64,
+) -> Vec<usize> {
+    let mut bases = 0;
+    let mut selected = order
+        .take_while(|&i| {
+            let done = bases >= target_bases;
+            bases += lengths[i] as u64;
+            !done
+        })
+        .collect::<Vec<_>>();
+    selected.sort_unstable();
+    selected
+}
+
+/// Select the longest reads (given by their lengths) until their total length reaches the
+/// given number of bases, returning the indices of the selected reads in increasing order.
+/// Reads of equal length are selected in input order. If the target exceeds the total length,
+/// all reads are selected.
+pub fn select_longest(lengths: &[usize], target_bases: u64) -> Vec<usize> {
+    let mut order = (0..lengths.len()).collect::<Vec<_>>();
+    order.sort_by_key(|&i| std::cmp::Reverse(lengths[i]));
+    take_until(order.into_iter(), lengths, target_bases)
+}
+
+/// Select reads (given by their lengths) in random order until their total length reaches the
+/// given number of bases, returning the indices of the selected reads in increasing order.
+/// If the target exceeds the total length, all reads are selected.
+///
+/// # Example
+///
+/// ```
+/// use bio::seq_analysis::read_lengths::select_random;
+/// use rand::rngs::StdRng;
+/// use rand::SeedableRng;
+///
+/// let lengths = [100; 50];
+/// let mut rng = StdRng::seed_from_u64(42);
+/// let selected = select_random(&lengths, 1000, &mut rng);
+/// assert_eq!(selected.len(), 10);
+/// ```
+pub fn select_random<R: Rng + ?Sized>(
+    lengths: &[usize],
+    target_bases: u64,
+    rng: &mut R,
+) -> Vec<usize> {
+    let mut order = (0..lengths.len()).collect::<Vec<_>>();
+    order.shuffle(rng);
+    take_until(order.into_iter(), lengths, target_bases)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::rngs::StdRng;
+    use rand::SeedableRng;
+
+    #[test]
+    fn test_distribution() {
+        let mut dist = LengthDistribution::new();
+        assert_eq!(dist.median(), None);
+        assert_eq!(dist.n50(), None);
+        assert_eq!(dist.mean(), None);
+        dist.extend(vec![10, 2, 4, 4]);
+        assert_eq!(dist.reads(), 4);
+        assert_eq!(
+            dist.histogram().collect::<Vec<_>>(),
+            [(2, 1), (4, 2), (10, 1)]
+        );
+        assert_eq!((dist.min(), dist.max()), (Some(2), Some(10)));
+        assert_eq!(dist.mean(), Some(5.0));
+        assert_eq!(dist.quantile(0.0), Some(2));
+        assert_eq!(dist.median(), Some(4));
+        assert_eq!(dist.quantile(1.0), Some(10));
+        // 10 holds half of the 20 bases
+        assert_eq!(dist.n50(), Some(10));
+        assert_eq!(dist.nx(0.9), Some(4));
+        assert_eq!(dist.nx(1.0), Some(2));
+        assert_eq!(dist.length_threshold(21), None);
+        assert_eq!(dist.sampling_fraction(5), 0.25);
+        assert_eq!(dist.sampling_fraction(50), 1.0);
+    }
+
+    #[test]
+    fn test_select() {
+        let lengths = [5, 1, 5, 3, 8];
+        assert_eq!(select_longest(&lengths, 0), Vec::<usize>::new());
+        assert_eq!(select_longest(&lengths, 8), [4]);
+        assert_eq!(select_longest(&lengths, 9), [0, 4]);
+        assert_eq!(select_longest(&lengths, 100), [0, 1, 2, 3, 4]);
+
+        let mut rng = StdRng::seed_from_u64(42);
+        for target in 0..25 {
+            let selected = select_random(&lengths, target, &mut rng);
+            let bases = selected.iter().map(|&i| lengths[i] as u64).sum::<u64>();
+            assert!(bases >= target.min(22));
+            // without the last selected read, the target would not have been reached
+            let longest = selected
+                .iter()
+                .map(|&i| lengths[i] as u64)
+                .max()
+                .unwrap_or(0);
+            assert!(selected.is_empty() || bases - longest < target);
+        }
+        // the selection only depends on the seed
+        let lengths = (1..100).collect::<Vec<_>>();
+        let select = |seed| select_random(&lengths, 1000, &mut StdRng::seed_from_u64(seed));
+        assert_eq!(select(7), select(7));
+        assert_ne!(select(7), select(8));
+    }
+}
@@ -0,0 +1,383 @@
+//! Overlap-based merging of paired-end reads into consensus fragments, in the spirit of
+//! FLASH (Magoč and Salzberg, 2011) and PEAR (Zhang et al., 2014).
+//!
+//! If the fragment is shorter than the sum of the read lengths, the end of the first read
+//! overlaps the start of the reverse complement of the second read. The overlap is detected
+//! by an alignment (see `alignment::pairwise`), in which the prefix of the first read and the
+//! suffix of the reverse complemented second read may be clipped for free.
+//! Within the overlap, disagreements are reconciled by choosing the base with the higher
+//! quality, and qualities are recomputed: agreeing bases get the sum of both qualities, and
+//! disagreeing bases the difference.
+//! Qualities are expected to be PHRED+33 encoded.
+//!
+//! # Example
+//!
+//! ```
+//! use bio::alphabets::dna;
+//! use bio::io::fastq;
+//! use bio::seq_analysis::read_merging::Merger;
+//!
+//! let fragment = b"ACGTTGCAAGCTAGCTTTGACCATGAGCATCGGATCCATGC";
+//! let r1 = fastq::Record::with_attrs("r", None, &fragment[..30], &[b'5'; 30]);
+//! let r2seq = dna::revcomp(&fragment[15..]);
+//! let r2 = fastq::Record::with_attrs("r", None, &r2seq, &vec![b'5'; r2seq.len()]);
+//!
+//! let merged = Merger::default().merge_records(&r1, &r2).unwrap();
+//! assert_eq!(merged.seq(), &fragment[..]);
+//! ```
+//!
+//! Mergers can also be configured with a `MergerBuilder`, which validates the parameters and can
+//! be (de)serialized, e.g. for loading the parameters of a tool from a configuration file.
+
+use thiserror::Error;
+
+use crate::alignment::pairwise::{Aligner, Scoring, MIN_SCORE};
+use crate::alignment::AlignmentOperation;
+use crate::alphabets::dna;
+use crate::io::fastq;
+use crate::utils::{Text, TextSlice};
+
+#[derive(Error, Copy, Clone, PartialEq, PartialOrd, Debug, Serialize, Deserialize)]
+pub enum Error {
+    #[error("minimum overlap has to be positive")]
+    ZeroMinOverlap,
+    #[error("maximum mismatch rate {} is not within 0 and 1", rate)]
+    InvalidMismatchRate { rate: f64 },
+    #[error("maximum quality {} exceeds the maximum PHRED+33 quality of 93", qual)]
+    InvalidMaxQual { qual: u8 },
+}
+
+pub type Result<T, E = Error> = std::result::Result<T, E>;
+
+/// The result of merging a read pair.
+#[derive(Default, Clone, Eq, PartialEq, Ord, PartialOrd, Hash, Debug, Serialize, Deserialize)]
+pub struct MergedPair {
+    /// The merged sequence.
+    pub seq: Text,
+    /// The recomputed qualities (PHRED+33).
+    pub qual: Vec<u8>,
+    /// The length of the overlap, i.e. the number of aligned columns.
+    pub overlap: usize,
+    /// The number of mismatches and gap columns within the overlap.
+    pub mismatches: usize,
+}
+
+/// Merger of overlapping paired-end reads.
+#[derive(Copy, Clone, PartialEq, PartialOrd, Debug, Serialize, Deserialize)]
+pub struct Merger {
+    /// Minimum length of the overlap.
+    pub min_overlap: usize,
+    /// Maximum fraction of mismatches and gap columns within the overlap.
+    pub max_mismatch_rate: f64,
+    /// Maximum PHRED quality (not offset) assigned to merged bases.
+    pub max_qual: u8,
+}
+
+impl Default for Merger {
+    /// A merger requiring an overlap of at least 10 with at most 10% mismatches,
+    /// capping qualities at 41 (as in Illumina 1.8+).
+    fn default() -> Self {
+        Merger::new(10, 0.1, 41)
+    }
+}
+
+/// A builder for mergers, validating the parameters. Without further configuration, it uses
+/// the parameters of `Merger::default`. Missing fields are set to these defaults when
+/// deserializing.
+///
+/// # Example
+///
+/// ```
+/// use bio::seq_analysis::read_merging::{Error, MergerBuilder};
+///
+/// let merger = MergerBuilder::new().min_overlap(20).build().unwrap();
+/// assert_eq!(merger.min_overlap, 20);
+///
+/// let builder = MergerBuilder::new().max_mismatch_rate(1.5);
+/// assert_eq!(builder.build(), Err(Error::InvalidMismatchRate { rate: 1.5 }));
+/// ```
+#[derive(Copy, Clone, PartialEq, PartialOrd, Debug, Serialize, Deserialize)]
+#[serde(default)]
+pub struct MergerBuilder {
+    min_overlap: usize,
+    max_mismatch_rate: f64,
+    max_qual: u8,
+}
+
+impl Default for MergerBuilder {
+    fn default() -> Self {
+        let merger = Merger::default();
+        MergerBuilder {
+            min_overlap: merger.min_overlap,
+            max_mismatch_rate: merger.max_mismatch_rate,
+            max_qual: merger.max_qual,
+        }
+    }
+}
+
+impl MergerBuilder {
+    /// Create a new builder with the default configuration.
+    pub fn new() -> Self {
+        MergerBuilder::default()
+    }
+
+    /// Require an overlap of at least the given length (default: 10).
+    pub fn min_overlap(mut self, min_overlap: usize) -> Self {
+        self.min_overlap = min_overlap;
+        self
+    }
+
+    /// Allow at most the given fraction of mismatches and gap columns within the overlap
+    /// (default: 0.1).
+    pub fn max_mismatch_rate(mut self, max_mismatch_rate: f64) -> Self {
+        self.max_mismatch_rate = max_mismatch_rate;
+        self
+    }
+
+    /// Cap the qualities of merged bases at the given PHRED quality (default: 41).
+    pub fn max_qual(mut self, max_qual: u8) -> Self {
+        self.max_qual = max_qual;
+        self
+    }
+
+    /// Build the merger.
+    ///
+    /// # Errors
+    /// * `Error::ZeroMinOverlap` - the minimum overlap is zero
+    /// * `Error::InvalidMismatchRate` - the maximum mismatch rate is not within 0 and 1
+    /// * `Error::InvalidMaxQual` - the maximum quality cannot be encoded as PHRED+33
+    pub fn build(&self) -> Result<Merger> {
+        if self.min_overlap == 0 {
+            return Err(Error::ZeroMinOverlap);
+        }
+        if !(0.0..=1.0).contains(&self.max_mismatch_rate) {
+            return Err(Error::InvalidMismatchRate {
+                rate: self.max_mismatch_rate,
+            });
+        }
+        if self.max_qual > 93 {
+            return Err(Error::InvalidMaxQual {
+                qual: self.max_qual,
+            });
+        }
+        Ok(Merger::new(
+            self.min_overlap,
+            self.max_mismatch_rate,
+            self.max_qual,
+        ))
+    }
+}
+
+impl Merger {
+    /// Create a new merger.
+    ///
+    /// # Arguments
+    ///
+    /// * `min_overlap` - minimum length of the overlap
+    /// * `max_mismatch_rate` - maximum fraction of mismatches and gap columns in the overlap
+    /// * `max_qual` - maximum PHRED quality (not offset) assigned to merged bases
+    pub fn new(min_overlap: usize, max_mismatch_rate: f64, max_qual: u8) -> Self {
+        Merger {
+            min_overlap,
+            max_mismatch_rate,
+            max_qual,
+        }
+    }
+
+    /// Merge the given read pair. The second read is expected as sequenced, i.e. on the
+    /// opposite strand of the first read. Returns `None` if the reads do not overlap
+    /// sufficiently.
+    ///
+    /// # Arguments
+    ///
+    /// * `seq1`, `qual1` - sequence and qualities of the first read
+    /// * `seq2`, `qual2` - sequence and qualities of the second read
+    pub fn merge(
+        &self,
+        seq1: TextSlice<'_>,
+        qual1: &[u8],
+        seq2: TextSlice<'_>,
+        qual2: &[u8],
+    ) -> Option<MergedPair> {
+        assert_eq!(
+            seq1.len(),
+            qual1.len(),
+            "Expecting a quality for each base."
+        );
+        assert_eq!(
+            seq2.len(),
+            qual2.len(),
+            "Expecting a quality for each base."
+        );
+        let seq2 = dna::revcomp(seq2);
+        let qual2: Vec<u8> = qual2.iter().rev().copied().collect();
+
+        // free clipping of the prefix of read 1 and the suffix of read 2
+        let scoring = Scoring::from_scores(-5, -2, 1, -2)
+            .xclip_prefix(0)
+            .xclip_suffix(MIN_SCORE)
+            .yclip_prefix(MIN_SCORE)
+            .yclip_suffix(0);
+        let mut aligner = Aligner::with_capacity_and_scoring(seq1.len(), seq2.len(), scoring);
+        let alignment = aligner.custom(seq1, &seq2);
+
+        let overlap = alignment.x_aln_len().max(alignment.y_aln_len());
+        let mismatches = alignment
+            .operations
+            .iter()
+            .filter(|op| {
+                matches!(
+                    op,
+                    AlignmentOperation::Subst | AlignmentOperation::Ins | AlignmentOperation::Del
+                )
+            })
+            .count();
+        if overlap == 0
+            || overlap < self.min_overlap
+            || mismatches as f64 > self.max_mismatch_rate * overlap as f64
+        {
+            return None;
+        }
+
+        let mut seq = Vec::with_capacity(alignment.xstart + seq2.len());
+        let mut qual = Vec::with_capacity(alignment.xstart + seq2.len());
+        seq.extend_from_slice(&seq1[..alignment.xstart]);
+        qual.extend_from_slice(&qual1[..alignment.xstart]);
+
+        let (mut i, mut j) = (alignment.xstart, alignment.ystart);
+        for op in &alignment.operations {
+            match *op {
+                AlignmentOperation::Match => {
+                    seq.push(seq1[i]);
+                    qual.push(self.agreeing_qual(qual1[i], qual2[j]));
+                    i += 1;
+                    j += 1;
+                }
+                AlignmentOperation::Subst => {
+                    let (a, q) = if qual1[i] >= qual2[j] {
+                        (seq1[i], qual1[i] - qual2[j])
+                    } else {
+                        (seq2[j], qual2[j] - qual1[i])
+                    };
+                    seq.push(a);
+                    // lowest valid Illumina quality is 2
+                    qual.push(q.max(2) + 33);
+                    i += 1;
+                    j += 1;
+                }
+                AlignmentOperation::Ins => {
+                    seq.push(seq1[i]);
+                    qual.push(qual1[i]);
+                    i += 1;
+                }
+                AlignmentOperation::Del => {
+                    seq.push(seq2[j]);
+                    qual.push(qual2[j]);
+                    j += 1;
+                }
+                AlignmentOperation::Xclip(_) | AlignmentOperation::Yclip(_) => (),
+            }
+        }
+        seq.extend_from_slice(&seq2[alignment.yend..]);
+        qual.extend_from_slice(&qual2[alignment.yend..]);
+
+        Some(MergedPair {
+            seq,
+            qual,
+            overlap,
+            mismatches,
+        })
+    }
+
+    /// Merge the given FASTQ read pair (see `Merger::merge`). The merged record takes the
+    /// id and description of the first read.
+    pub fn merge_records(&self, r1: &fastq::Record, r2: &fastq::Record) -> Option<fastq::Record> {
+        self.merge(r1.seq(), r1.qual(), r2.seq(), r2.qual())
+            .map(|merged| fastq::Record::with_attrs(r1.id(), r1.desc(), &merged.seq, &merged.qual))
+    }
+
+    /// Quality of two agreeing bases (PHRED+33 encoded).
+    fn agreeing_qual(&self, q1: u8, q2: u8) -> u8 {
+        let q = (q1 - 33) as u16 + (q2 - 33) as u16;
+        q.min(self.max_qual as u16) as u8 + 33
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const FRAGMENT: &[u8] = b"ACGTTGCAAGCTAGCTTTGACCATGAGCATCGGATCCATGCAAGTGGCATTACCAGATC";
+
+    #[test]
+    fn test_merge_with_mismatch() {
+        let r1 = FRAGMENT[..40].to_vec();
+        let mut qual1 = vec![b'5'; 40];
+        let mut r2 = FRAGMENT[25..].to_vec();
+        // sequencing error in read 2 with low quality
+        r2[5] = b'A';
+        let mut qual2 = vec![b'5'; r2.len()];
+        qual2[5] = b'#';
+        let r2 = dna::revcomp(&r2);
+        qual2.reverse();
+        // low quality end of read 1
+        qual1[39] = b'&';
+
+        let merged = Merger::default().merge(&r1, &qual1, &r2, &qual2).unwrap();
+        assert_eq!(merged.seq, FRAGMENT);
+        assert_eq!(merged.overlap, 15);
+        assert_eq!(merged.mismatches, 1);
+        // disagreeing bases get the difference of the qualities
+        assert_eq!(merged.qual[30], b'5' - 2);
+        // agreeing bases get the sum, capped at max_qual
+        assert_eq!(merged.qual[29], 40 + 33);
+        let capped = Merger::new(10, 0.1, 30)
+            .merge(&r1, &qual1, &r2, &qual2)
+            .unwrap();
+        assert_eq!(capped.qual[29], 30 + 33);
+        assert_eq!(merged.qual[39], b'5' + 5);
+        assert_eq!(merged.qual[0], b'5');
+        assert_eq!(merged.qual.len(), FRAGMENT.len());
+    }
+
+    #[test]
+    fn test_no_overlap() {
+        let r1 = &FRAGMENT[..20];
+        let r2 = dna::revcomp(&FRAGMENT[30..]);
+        let merger = Merger::default();
+        assert!(merger
+            .merge(r1, &[b'5'; 20], &r2, &vec![b'5'; r2.len()])
+            .is_none());
+
+        // overlap too short
+        let r1 = &FRAGMENT[..35];
+        assert!(Merger::new(10, 0.1, 41)
+            .merge(r1, &[b'5'; 35], &r2, &vec![b'5'; r2.len()])
+            .is_none());
+        assert!(Merger::new(5, 0.1, 41)
+            .merge(r1, &[b'5'; 35], &r2, &vec![b'5'; r2.len()])
+            .is_some());
+    }
+
+    #[test]
+    fn test_builder() {
+        assert_eq!(MergerBuilder::new().build(), Ok(Merger::default()));
+        assert_eq!(
+            MergerBuilder::new().max_qual(30).build(),
+            Ok(Merger::new(10, 0.1, 30))
+        );
+        assert_eq!(
+            MergerBuilder::new().min_overlap(0).build(),
+            Err(Error::ZeroMinOverlap)
+        );
+        // NaN is not within 0 and 1 either
+        assert!(MergerBuilder::new()
+            .max_mismatch_rate(f64::NAN)
+            .build()
+            .is_err());
+        assert_eq!(
+            MergerBuilder::new().max_qual(94).build(),
+            Err(Error::InvalidMaxQual { qual: 94 })
+        );
+    }
+}
@@ -0,0 +1,219 @@
+//! Scanning sequences for known short tandem repeats, like telomeric repeats (e.g. `TTAGGG`) or
+//! user-supplied satellite motifs, e.g. for quality control of assemblies: complete
+//! chromosome-level assemblies should end in telomeric repeat tracts on both ends.
+//!
+//! Occurrences of the motifs and their reverse complements are searched with up to a given
+//! edit distance (using Myers' bit-parallel algorithm), such that diverged copies are found as
+//! well. The scan yields a mask of the bases covered by any occurrence, which can be summarized
+//! per window or as the lengths of the repeat tracts at both ends of the sequence.
+//! Complexity: O(nm) for a sequence of length n and m motifs.
+//!
+//! # Example
+//!
+//! ```
+//! use bio::seq_analysis::repeat_content::RepeatScanner;
+//!
+//! let scanner = RepeatScanner::telomeric(0);
+//! // a contig with telomeric repeats at the start (as reverse complement) and end
+//! let contig = b"CCCTAACCCTAACCCTAAGATTACAGATTACATTAGGGTTAGGGTTAGG";
+//! let repeats = scanner.scan(contig);
+//! let tracts = repeats.terminal_tracts();
+//! assert_eq!((tracts.start, tracts.end), (18, 17));
+//! assert_eq!(
+//!     repeats.window_content(16, 16),
+//!     [(0, 1.0), (16, 0.125), (32, 1.0)]
+//! );
+//! ```
+
+use std::iter;
+
+use crate::alphabets::dna;
+use crate::pattern_matching::myers::Myers;
+use crate::utils::{Text, TextSlice};
+
+/// Telomeric repeat units of vertebrates (`TTAGGG`), most plants (`TTTAGGG`), insects
+/// (`TTAGG`) and ciliates (`TTGGGG`).
+pub const TELOMERIC_REPEATS: &[&[u8]] = &[b"TTAGGG", b"TTTAGGG", b"TTAGG", b"TTGGGG"];
+
+/// A scanner for occurrences of repeat units.
+#[derive(Default, Clone, Eq, PartialEq, Hash, Debug, Serialize, Deserialize)]
+pub struct RepeatScanner {
+    motifs: Vec<Text>,
+    max_dist: u8,
+}
+
+impl RepeatScanner {
+    /// Create a new scanner for the given repeat units (e.g. satellite monomers) and their
+    /// reverse complements, allowing occurrences with up to the given edit distance.
+    ///
+    /// # Panics
+    ///
+    /// Panics if a motif is empty or longer than 64 bases, or not longer than `max_dist`.
+    pub fn new<M: AsRef<[u8]>>(motifs: &[M], max_dist: u8) -> Self {
+        let mut all = Vec::with_capacity(2 * motifs.len());
+        for motif in motifs {
+            let motif = motif.as_ref().to_ascii_uppercase();
+            assert!(
+                motif.len() > max_dist as usize && motif.len() <= 64,
+                "Expecting motifs of length in {}..=64.",
+                max_dist as usize + 1
+            );
+            let revcomp = dna::revcomp(&motif);
+            for m in iter::once(motif).chain(iter::once(revcomp)) {
+                if !all.contains(&m) {
+                    all.push(m);
+                }
+            }
+        }
+        RepeatScanner {
+            motifs: all,
+            max_dist,
+        }
+    }
+
+    /// Create a new scanner for the common telomeric repeats (see `TELOMERIC_REPEATS`).
+    pub fn telomeric(max_dist: u8) -> Self {
+        RepeatScanner::new(TELOMERIC_REPEATS, max_dist)
+    }
+
+    /// The searched motifs, including reverse complements.
+    pub fn motifs(&self) -> &[Text] {
+        &self.motifs
+    }
+
+    /// Scan the given sequence (case-insensitively) for occurrences of the motifs.
+    pub fn scan(&self, text: TextSlice<'_>) -> RepeatMask {
+        let mut covered = vec![false; text.len()];
+        for motif in &self.motifs {
+            let mut myers = Myers::<u64>::new(motif);
+            let upper = text.iter().map(|a| a.to_ascii_uppercase());
+            for (start, end, _) in myers.find_all(upper, self.max_dist) {
+                for c in &mut covered[start..end] {
+                    *c = true;
+                }
+            }
+        }
+        RepeatMask {
+            covered,
+            max_gap: self.motifs.iter().map(|m| m.len()).max().unwrap_or(0),
+        }
+    }
+}
+
+/// Lengths of the repeat tracts at the start and end of a sequence.
+#[derive(
+    Default, Copy, Clone, Eq, PartialEq, Ord, PartialOrd, Hash, Debug, Serialize, Deserialize,
+)]
+pub struct TerminalTracts {
+    pub start: usize,
+    pub end: usize,
+}
+
+/// The bases of a sequence covered by repeat occurrences (see `RepeatScanner::scan`).
+#[derive(Default, Clone, Eq, PartialEq, Hash, Debug, Serialize, Deserialize)]
+pub struct RepeatMask {
+    covered: Vec<bool>,
+    /// Longest gap between occurrences within a tract.
+    max_gap: usize,
+}
+
+impl RepeatMask {
+    /// Whether each base is covered by a repeat occurrence.
+    pub fn covered(&self) -> &[bool] {
+        &self.covered
+    }
+
+    /// Fraction of bases of the sequence covered by repeats.
+    pub fn content(&self) -> f64 {
+        self.covered.iter().filter(|&&c| c).count() as f64 / self.covered.len() as f64
+    }
+
+    /// Fraction of bases covered by repeats in each complete window of the given length,
+    /// starting every `step` bases, together with the window start.
+    ///
+    /// # Panics
+    ///
+    /// Panics if window or step are zero.
+    pub fn window_content(&self, window: usize, step: usize) -> Vec<(usize, f64)> {
+        assert!(
+            window > 0 && step > 0,
+            "Expecting positive window length and step."
+        );
+        let mut prefix = Vec::with_capacity(self.covered.len() + 1);
+        prefix.push(0);
+        for &c in &self.covered {
+            prefix.push(prefix[prefix.len() - 1] + c as usize);
+        }
+        (0..)
+            .step_by(step)
+            .take_while(|&pos| pos + window <= self.covered.len())
+            .map(|pos| {
+                (
+                    pos,
+                    (prefix[pos + window] - prefix[pos]) as f64 / window as f64,
+                )
+            })
+            .collect()
+    }
+
+    /// Lengths of the repeat tracts at both ends of the sequence. A tract extends from the
+    /// sequence end up to its last covered base, tolerating uncovered gaps of at most the
+    /// motif length (e.g. a truncated repeat unit at the sequence end or a diverged copy).
+    pub fn terminal_tracts(&self) -> TerminalTracts {
+        TerminalTracts {
+            start: self.tract(self.covered.iter()),
+            end: self.tract(self.covered.iter().rev()),
+        }
+    }
+
+    fn tract<'a, I: Iterator<Item = &'a bool>>(&self, covered: I) -> usize {
+        let (mut len, mut gap) = (0, 0);
+        for (i, &c) in covered.enumerate() {
+            if c {
+                len = i + 1;
+                gap = 0;
+            } else {
+                gap += 1;
+                if gap > self.max_gap {
+                    break;
+                }
+            }
+        }
+        len
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_approximate() {
+        // a diverged copy (TTAGCG differs from TTAGGG by one substitution)
+        let text = b"acgtacgtTTAGGGTTAGCGTTAGGGacgtacgtacgt";
+        let exact = RepeatScanner::new(&[b"TTAGGG"], 0).scan(text);
+        assert_eq!(exact.covered().iter().filter(|&&c| c).count(), 12);
+        let approx = RepeatScanner::new(&[b"TTAGGG"], 1).scan(text);
+        assert!(approx.covered()[8..26].iter().all(|&c| c));
+        assert!(approx.content() > exact.content());
+    }
+
+    #[test]
+    fn test_terminal_tracts() {
+        let scanner = RepeatScanner::new(&[b"TTAGGG"], 0);
+        // the tract starts after a truncated repeat unit
+        let tracts = scanner
+            .scan(b"AGGGTTAGGGTTAGGGACGTACGTACGTACGTACGT")
+            .terminal_tracts();
+        assert_eq!(tracts, TerminalTracts { start: 16, end: 0 });
+        let tracts = scanner.scan(b"").terminal_tracts();
+        assert_eq!(tracts, TerminalTracts { start: 0, end: 0 });
+        assert_eq!(scanner.motifs().len(), 2);
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_short_motif() {
+        RepeatScanner::new(&[b"TA"], 2);
+    }
+}
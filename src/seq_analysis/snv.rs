@@ -0,0 +1,404 @@
+//! A simple caller of single nucleotide variants (SNVs) in a single diploid sample, working
+//! on pileup columns (see `bio::alignment::pileup`).
+//!
+//! For each column, the most frequent non-reference base is considered as alternative allele.
+//! Under each genotype (homozygous reference, heterozygous, homozygous alternative) the reads
+//! are modeled as binomial draws of the alleles at fractions 0, 0.5 and 1, where each observed
+//! base is mis-called with the error probability given by its quality. Combined with a prior
+//! based on the expected heterozygosity, this yields genotype posteriors, and the variant
+//! quality is the PHRED-scaled posterior probability of the homozygous reference genotype.
+//!
+//! This is intended for simple use cases and as a demonstration; it does not model
+//! mapping errors, strand bias or other systematic artifacts.
+//!
+//! # Example
+//!
+//! ```
+//! use bio::alignment::pileup::Pileup;
+//! use bio::io::bam::{CigarOp, Reference, Record};
+//! use bio::io::vcf;
+//! use bio::seq_analysis::snv::{vcf_header, Genotype, SnvCaller};
+//!
+//! let reference = b"ACGTACGTAC";
+//! let read = |seq: &[u8], reverse| Record {
+//!     ref_id: Some(0),
+//!     pos: Some(0),
+//!     flag: if reverse { 0x10 } else { 0 },
+//!     mapq: 60,
+//!     cigar: vec![CigarOp { len: 10, op: b'M' }],
+//!     seq: seq.to_vec(),
+//!     qual: vec![30; 10],
+//!     ..Default::default()
+//! };
+//! // five reads support a G at position 4, five the reference
+//! let records: Vec<_> = (0..10)
+//!     .map(|i| read(if i % 2 == 0 { b"ACGTGCGTAC" } else { reference }, i < 5))
+//!     .collect();
+//!
+//! let caller = SnvCaller::default();
+//! let calls: Vec<_> = Pileup::new(records.into_iter().map(Ok))
+//!     .filter_map(|column| caller.call(&column.unwrap(), reference))
+//!     .collect();
+//! assert_eq!(calls.len(), 1);
+//! assert_eq!((calls[0].pos, calls[0].alt_base), (4, b'G'));
+//! assert_eq!(calls[0].genotype, Genotype::Heterozygous);
+//!
+//! let references = [Reference { name: "chr1".to_owned(), len: 10 }];
+//! let mut writer = vcf::Writer::new(vec![], &vcf_header(&references, "sample")).unwrap();
+//! writer.write(&calls[0].to_vcf_record(&references)).unwrap();
+//! ```
+
+use crate::alignment::pileup::PileupColumn;
+use crate::io::bam::Reference;
+use crate::io::vcf;
+use crate::stats::{LogProb, PHREDProb, Prob};
+use crate::utils::TextSlice;
+use bio_types::strand::ReqStrand;
+
+/// Genotype of a diploid sample at a biallelic site.
+#[derive(Copy, Clone, Eq, PartialEq, Ord, PartialOrd, Hash, Debug, Serialize, Deserialize)]
+pub enum Genotype {
+    HomozygousReference,
+    Heterozygous,
+    HomozygousAlternative,
+}
+
+impl Genotype {
+    const ALL: [Genotype; 3] = [
+        Genotype::HomozygousReference,
+        Genotype::Heterozygous,
+        Genotype::HomozygousAlternative,
+    ];
+
+    /// Fraction of the alternative allele.
+    pub fn alt_fraction(self) -> f64 {
+        match self {
+            Genotype::HomozygousReference => 0.0,
+            Genotype::Heterozygous => 0.5,
+            Genotype::HomozygousAlternative => 1.0,
+        }
+    }
+
+    /// The unphased genotype in VCF notation, e.g. `0/1`.
+    pub fn to_vcf(self) -> &'static str {
+        match self {
+            Genotype::HomozygousReference => "0/0",
+            Genotype::Heterozygous => "0/1",
+            Genotype::HomozygousAlternative => "1/1",
+        }
+    }
+}
+
+/// A called SNV.
+#[derive(Copy, Clone, PartialEq, Debug, Serialize, Deserialize)]
+pub struct SnvCall {
+    pub ref_id: usize,
+    /// 0-based reference position.
+    pub pos: usize,
+    pub ref_base: u8,
+    pub alt_base: u8,
+    /// Most likely genotype.
+    pub genotype: Genotype,
+    /// PHRED-scaled posterior probability of the homozygous reference genotype.
+    pub qual: PHREDProb,
+    /// PHRED-scaled posterior probability of any other than the called genotype.
+    pub genotype_qual: PHREDProb,
+    /// Number of reads with sufficient base quality.
+    pub depth: usize,
+    /// Number of forward and reverse reads supporting the reference base.
+    pub ref_counts: (usize, usize),
+    /// Number of forward and reverse reads supporting the alternative base.
+    pub alt_counts: (usize, usize),
+}
+
+impl SnvCall {
+    /// Convert into a VCF record, with chromosome names taken from the given references,
+    /// reporting depth (`DP`), strand-specific allele counts (`SB`, as reference forward,
+    /// reference reverse, alternative forward, alternative reverse), genotype (`GT`) and
+    /// genotype quality (`GQ`), see `vcf_header`.
+    pub fn to_vcf_record(&self, references: &[Reference]) -> vcf::Record {
+        let counts = [
+            self.ref_counts.0,
+            self.ref_counts.1,
+            self.alt_counts.0,
+            self.alt_counts.1,
+        ];
+        vcf::Record {
+            chrom: references[self.ref_id].name.clone(),
+            pos: self.pos as u64 + 1,
+            ref_allele: vec![self.ref_base],
+            alt_alleles: vec![vec![self.alt_base]],
+            qual: Some((*self.qual * 100.0).round() / 100.0),
+            filters: vec!["PASS".to_owned()],
+            info: vec![
+                ("DP".to_owned(), Some(self.depth.to_string())),
+                (
+                    "SB".to_owned(),
+                    Some(
+                        counts
+                            .iter()
+                            .map(|c| c.to_string())
+                            .collect::<Vec<_>>()
+                            .join(","),
+                    ),
+                ),
+            ],
+            format: vec!["GT".to_owned(), "GQ".to_owned()],
+            samples: vec![vec![
+                self.genotype.to_vcf().to_owned(),
+                format!("{}", (*self.genotype_qual).min(99.0).round()),
+            ]],
+            id: None,
+        }
+    }
+}
+
+/// A VCF header declaring the given references, the fields written by
+/// `SnvCall::to_vcf_record` and the given sample.
+pub fn vcf_header(references: &[Reference], sample: &str) -> vcf::Header {
+    let mut header = vcf::Header::new();
+    for reference in references {
+        header.add_contig(&reference.name, Some(reference.len));
+    }
+    header
+        .add_info(
+            "DP",
+            "1",
+            "Integer",
+            "Number of reads with sufficient base quality",
+        )
+        .add_info(
+            "SB",
+            "4",
+            "Integer",
+            "Forward and reverse reads supporting reference and alternative base",
+        )
+        .add_format("GT", "1", "String", "Genotype")
+        .add_format("GQ", "1", "Integer", "Genotype quality")
+        .add_sample(sample);
+    header
+}
+
+/// A caller of SNVs from pileup columns.
+#[derive(Copy, Clone, PartialEq, Debug, Serialize, Deserialize)]
+pub struct SnvCaller {
+    /// Bases with lower quality are ignored.
+    pub min_base_qual: u8,
+    /// Base qualities are capped at this value, to account for overestimated qualities.
+    pub max_base_qual: u8,
+    /// Minimum number of reads with sufficient base quality.
+    pub min_depth: usize,
+    /// Minimum variant quality (PHRED-scaled) of reported calls.
+    pub min_qual: f64,
+    /// Prior probability of a heterozygous site. The prior of a homozygous alternative site
+    /// is half of it.
+    pub heterozygosity: Prob,
+}
+
+impl Default for SnvCaller {
+    /// A caller ignoring bases below quality 13 and requiring a depth of 3 and a variant
+    /// quality of 20, assuming a heterozygosity of 0.001 (as in humans).
+    fn default() -> Self {
+        SnvCaller {
+            min_base_qual: 13,
+            max_base_qual: 40,
+            min_depth: 3,
+            min_qual: 20.0,
+            heterozygosity: Prob(0.001),
+        }
+    }
+}
+
+impl SnvCaller {
+    /// Call an SNV at the given column, with the reference sequence of the column's
+    /// reference id. Returns `None` if the column does not reach the minimum depth, the
+    /// reference base is not one of `ACGT`, or the most likely genotype is homozygous
+    /// reference or has a quality below the minimum.
+    pub fn call(&self, column: &PileupColumn, reference: TextSlice<'_>) -> Option<SnvCall> {
+        let ref_base = reference.get(column.pos)?.to_ascii_uppercase();
+        let index = |base: u8| b"ACGT".iter().position(|&b| b == base.to_ascii_uppercase());
+        index(ref_base)?;
+
+        // bases with sufficient quality, and their counts
+        let observations: Vec<(u8, u8)> = column
+            .entries
+            .iter()
+            .filter(|e| e.qual >= self.min_base_qual)
+            .filter_map(|e| e.base.map(|b| (b.to_ascii_uppercase(), e.qual)))
+            .filter(|&(b, _)| index(b).is_some())
+            .collect();
+        if observations.len() < self.min_depth {
+            return None;
+        }
+        let mut counts = [0; 4];
+        for &(b, _) in &observations {
+            counts[index(b).unwrap()] += 1;
+        }
+        let alt_base = b"ACGT"
+            .iter()
+            .copied()
+            .filter(|&b| b != ref_base)
+            .max_by_key(|&b| counts[index(b).unwrap()])
+            .unwrap();
+        if counts[index(alt_base).unwrap()] == 0 {
+            return None;
+        }
+
+        let priors = [
+            Prob(1.0 - 1.5 * *self.heterozygosity),
+            self.heterozygosity,
+            Prob(0.5 * *self.heterozygosity),
+        ];
+        let joint: Vec<LogProb> = Genotype::ALL
+            .iter()
+            .zip(priors.iter())
+            .map(|(&genotype, &prior)| {
+                LogProb::from(prior) + self.likelihood(&observations, ref_base, alt_base, genotype)
+            })
+            .collect();
+        let marginal = LogProb::ln_sum_exp(&joint);
+        let posteriors: Vec<LogProb> = joint.iter().map(|&p| p - marginal).collect();
+
+        let (best, _) = posteriors
+            .iter()
+            .enumerate()
+            .max_by(|(_, a), (_, b)| a.partial_cmp(b).unwrap())
+            .unwrap();
+        let genotype = Genotype::ALL[best];
+        let qual = PHREDProb::from(posteriors[0]);
+        if genotype == Genotype::HomozygousReference || *qual < self.min_qual {
+            return None;
+        }
+
+        let strand_counts = |base: u8| {
+            column.entries.iter().fold((0, 0), |(fwd, rev), e| {
+                let matches = e.qual >= self.min_base_qual
+                    && e.base.map_or(false, |b| b.eq_ignore_ascii_case(&base));
+                match (matches, e.strand) {
+                    (false, _) => (fwd, rev),
+                    (true, ReqStrand::Reverse) => (fwd, rev + 1),
+                    (true, _) => (fwd + 1, rev),
+                }
+            })
+        };
+        Some(SnvCall {
+            ref_id: column.ref_id,
+            pos: column.pos,
+            ref_base,
+            alt_base,
+            genotype,
+            qual,
+            genotype_qual: PHREDProb::from(posteriors[best].ln_one_minus_exp()),
+            depth: observations.len(),
+            ref_counts: strand_counts(ref_base),
+            alt_counts: strand_counts(alt_base),
+        })
+    }
+
+    /// Log-likelihood of the observed bases under the given genotype.
+    fn likelihood(
+        &self,
+        observations: &[(u8, u8)],
+        ref_base: u8,
+        alt_base: u8,
+        genotype: Genotype,
+    ) -> LogProb {
+        let f = genotype.alt_fraction();
+        observations
+            .iter()
+            .map(|&(base, qual)| {
+                let error = *Prob::from(PHREDProb(qual.min(self.max_base_qual) as f64));
+                // probability to observe the base given the true allele
+                let emit = |allele: u8| {
+                    if base == allele {
+                        1.0 - error
+                    } else {
+                        error / 3.0
+                    }
+                };
+                LogProb::from(Prob(f * emit(alt_base) + (1.0 - f) * emit(ref_base)))
+            })
+            .sum()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::alignment::pileup::PileupEntry;
+
+    fn column(bases: &[u8], qual: u8) -> PileupColumn {
+        PileupColumn {
+            ref_id: 0,
+            pos: 2,
+            entries: bases
+                .iter()
+                .enumerate()
+                .map(|(i, &b)| PileupEntry {
+                    base: if b == b'-' { None } else { Some(b) },
+                    qual,
+                    read_pos: 0,
+                    strand: if i % 2 == 0 {
+                        ReqStrand::Forward
+                    } else {
+                        ReqStrand::Reverse
+                    },
+                    mapq: 60,
+                })
+                .collect(),
+        }
+    }
+
+    #[test]
+    fn test_genotypes() {
+        let caller = SnvCaller::default();
+        let reference = b"ACGT";
+        assert!(caller.call(&column(b"GGGGGGGGGG", 30), reference).is_none());
+        // a single error
+        assert!(caller.call(&column(b"GGGGGGGGGA", 30), reference).is_none());
+
+        let het = caller.call(&column(b"GGGGGAAAAA", 30), reference).unwrap();
+        assert_eq!(het.genotype, Genotype::Heterozygous);
+        assert_eq!((het.ref_base, het.alt_base), (b'G', b'A'));
+        assert_eq!(het.ref_counts, (3, 2));
+        assert_eq!(het.alt_counts, (2, 3));
+        assert!(*het.qual > 100.0);
+
+        let hom = caller.call(&column(b"tttttt-", 30), reference).unwrap();
+        assert_eq!(hom.genotype, Genotype::HomozygousAlternative);
+        assert_eq!(hom.alt_base, b'T');
+        assert_eq!(hom.depth, 6);
+    }
+
+    #[test]
+    fn test_filters() {
+        let caller = SnvCaller::default();
+        let reference = b"ACGT";
+        // low base qualities are ignored
+        assert!(caller.call(&column(b"GGAAAA", 10), reference).is_none());
+        assert!(caller.call(&column(b"AA", 30), reference).is_none());
+        assert!(caller.call(&column(b"AAA", 30), b"ACNT").is_none());
+        let lenient = SnvCaller {
+            min_depth: 2,
+            ..Default::default()
+        };
+        assert!(lenient.call(&column(b"AA", 30), reference).is_some());
+    }
+
+    #[test]
+    fn test_vcf_record() {
+        let call = SnvCaller::default()
+            .call(&column(b"GGAAAA", 30), b"ACGT")
+            .unwrap();
+        let references = [Reference {
+            name: "chrM".to_owned(),
+            len: 4,
+        }];
+        let mut writer = vcf::Writer::new(vec![], &vcf_header(&references, "s1")).unwrap();
+        writer.write(&call.to_vcf_record(&references)).unwrap();
+        let output = String::from_utf8(writer.into_inner().unwrap()).unwrap();
+        let last = output.lines().last().unwrap();
+        assert!(last.starts_with("chrM\t3\t.\tG\tA\t"));
+        assert!(last.contains("\tPASS\tDP=6;SB=1,1,2,2\tGT:GQ\t0/1:"));
+    }
+}
@@ -0,0 +1,467 @@
+//! Translation of DNA into protein sequences with the genetic code tables of the NCBI.
+//!
+//! Codons are translated case-insensitively, with `U` being treated like `T`. Codons with
+//! ambiguous IUPAC symbols are translated to the amino acid that all possible codons agree on
+//! (e.g. `CTN` to `L`), and to `X` otherwise. Codons containing other symbols are translated
+//! to `X`. Stop codons are translated to `*`.
+//!
+//! # Example
+//!
+//! ```
+//! use bio::seq_analysis::orf::Finder;
+//! use bio::seq_analysis::translation::{translate, GeneticCode};
+//!
+//! assert_eq!(translate(b"ATGGCCTTTTAA"), b"MAF*");
+//! assert_eq!(translate(b"ATGGCNTTYTAR"), b"MAF*");
+//!
+//! // translate the ORFs found by the ORF finder
+//! let seq = b"CCATGAAACCCTAGGG";
+//! let finder = Finder::new(vec![b"ATG"], vec![b"TAG"], 6);
+//! let orfs = finder
+//!     .find_all(seq)
+//!     .map(|orf| translate(&seq[orf.start..orf.end]))
+//!     .collect::<Vec<_>>();
+//! assert_eq!(orfs, [b"MKP*".to_vec()]);
+//!
+//! // vertebrate mitochondria read TGA as tryptophan
+//! let mito = GeneticCode::from_id(2).unwrap();
+//! assert_eq!(mito.translate(b"ATGTGA"), b"MW");
+//! ```
+
+use thiserror::Error;
+
+use crate::alphabets::dna::iupac;
+use crate::utils::{Text, TextSlice};
+use bio_types::strand::ReqStrand;
+
+/// Errors that can occur when selecting a genetic code.
+#[derive(
+    Error, Copy, Clone, Eq, PartialEq, Ord, PartialOrd, Hash, Debug, Serialize, Deserialize,
+)]
+pub enum Error {
+    #[error("unknown genetic code table {}", id)]
+    UnknownTable { id: u8 },
+}
+
+pub type Result<T, E = Error> = std::result::Result<T, E>;
+
+/// A genetic code table, mapping the 64 codons to amino acids.
+#[derive(Copy, Clone, Eq, PartialEq, Hash, Debug)]
+pub struct GeneticCode {
+    id: u8,
+    name: &'static str,
+    /// Amino acids of the codons, in the order of the NCBI tables (bases ordered `TCAG`, first
+    /// base varying slowest).
+    amino_acids: &'static [u8; 64],
+    /// `M` for alternative start codons, in the same order.
+    starts: &'static [u8; 64],
+}
+
+macro_rules! genetic_code {
+    ($id:expr, $name:expr, $amino_acids:expr, $starts:expr) => {
+        GeneticCode {
+            id: $id,
+            name: $name,
+            amino_acids: $amino_acids,
+            starts: $starts,
+        }
+    };
+}
+
+/// All genetic code tables of the NCBI, by increasing ID.
+pub const GENETIC_CODES: &[GeneticCode] = &[
+    genetic_code!(
+        1,
+        "Standard",
+        b"FFLLSSSSYY**CC*WLLLLPPPPHHQQRRRRIIIMTTTTNNKKSSRRVVVVAAAADDEEGGGG",
+        b"---M------**--*----M---------------M----------------------------"
+    ),
+    genetic_code!(
+        2,
+        "Vertebrate Mitochondrial",
+        b"FFLLSSSSYY**CCWWLLLLPPPPHHQQRRRRIIMMTTTTNNKKSS**VVVVAAAADDEEGGGG",
+        b"----------**--------------------MMMM----------**---M------------"
+    ),
+    genetic_code!(
+        3,
+        "Yeast Mitochondrial",
+        b"FFLLSSSSYY**CCWWTTTTPPPPHHQQRRRRIIMMTTTTNNKKSSRRVVVVAAAADDEEGGGG",
+        b"----------**----------------------MM---------------M------------"
+    ),
+    genetic_code!(
+        4,
+        "Mold, Protozoan, and Coelenterate Mitochondrial and Mycoplasma/Spiroplasma",
+        b"FFLLSSSSYY**CCWWLLLLPPPPHHQQRRRRIIIMTTTTNNKKSSRRVVVVAAAADDEEGGGG",
+        b"--MM------**-------M------------MMMM---------------M------------"
+    ),
+    genetic_code!(
+        5,
+        "Invertebrate Mitochondrial",
+        b"FFLLSSSSYY**CCWWLLLLPPPPHHQQRRRRIIMMTTTTNNKKSSSSVVVVAAAADDEEGGGG",
+        b"---M------**--------------------MMMM---------------M------------"
+    ),
+    genetic_code!(
+        6,
+        "Ciliate, Dasycladacean and Hexamita Nuclear",
+        b"FFLLSSSSYYQQCC*WLLLLPPPPHHQQRRRRIIIMTTTTNNKKSSRRVVVVAAAADDEEGGGG",
+        b"--------------*--------------------M----------------------------"
+    ),
+    genetic_code!(
+        9,
+        "Echinoderm and Flatworm Mitochondrial",
+        b"FFLLSSSSYY**CCWWLLLLPPPPHHQQRRRRIIIMTTTTNNNKSSSSVVVVAAAADDEEGGGG",
+        b"----------**-----------------------M---------------M------------"
+    ),
+    genetic_code!(
+        10,
+        "Euplotid Nuclear",
+        b"FFLLSSSSYY**CCCWLLLLPPPPHHQQRRRRIIIMTTTTNNKKSSRRVVVVAAAADDEEGGGG",
+        b"----------**-----------------------M----------------------------"
+    ),
+    genetic_code!(
+        11,
+        "Bacterial, Archaeal and Plant Plastid",
+        b"FFLLSSSSYY**CC*WLLLLPPPPHHQQRRRRIIIMTTTTNNKKSSRRVVVVAAAADDEEGGGG",
+        b"---M------**--*----M------------MMMM---------------M------------"
+    ),
+    genetic_code!(
+        12,
+        "Alternative Yeast Nuclear",
+        b"FFLLSSSSYY**CC*WLLLSPPPPHHQQRRRRIIIMTTTTNNKKSSRRVVVVAAAADDEEGGGG",
+        b"----------**--*----M---------------M----------------------------"
+    ),
+    genetic_code!(
+        13,
+        "Ascidian Mitochondrial",
+        b"FFLLSSSSYY**CCWWLLLLPPPPHHQQRRRRIIMMTTTTNNKKSSGGVVVVAAAADDEEGGGG",
+        b"---M------**----------------------MM---------------M------------"
+    ),
+    genetic_code!(
+        14,
+        "Alternative Flatworm Mitochondrial",
+        b"FFLLSSSSYYY*CCWWLLLLPPPPHHQQRRRRIIIMTTTTNNNKSSSSVVVVAAAADDEEGGGG",
+        b"-----------*-----------------------M----------------------------"
+    ),
+    genetic_code!(
+        16,
+        "Chlorophycean Mitochondrial",
+        b"FFLLSSSSYY*LCC*WLLLLPPPPHHQQRRRRIIIMTTTTNNKKSSRRVVVVAAAADDEEGGGG",
+        b"----------*---*--------------------M----------------------------"
+    ),
+    genetic_code!(
+        21,
+        "Trematode Mitochondrial",
+        b"FFLLSSSSYY**CCWWLLLLPPPPHHQQRRRRIIMMTTTTNNNKSSSSVVVVAAAADDEEGGGG",
+        b"----------**-----------------------M---------------M------------"
+    ),
+    genetic_code!(
+        22,
+        "Scenedesmus obliquus Mitochondrial",
+        b"FFLLSS*SYY*LCC*WLLLLPPPPHHQQRRRRIIIMTTTTNNKKSSRRVVVVAAAADDEEGGGG",
+        b"------*---*---*--------------------M----------------------------"
+    ),
+    genetic_code!(
+        23,
+        "Thraustochytrium Mitochondrial",
+        b"FF*LSSSSYY**CC*WLLLLPPPPHHQQRRRRIIIMTTTTNNKKSSRRVVVVAAAADDEEGGGG",
+        b"--*-------**--*-----------------M--M---------------M------------"
+    ),
+    genetic_code!(
+        24,
+        "Rhabdopleuridae Mitochondrial",
+        b"FFLLSSSSYY**CCWWLLLLPPPPHHQQRRRRIIIMTTTTNNKKSSSKVVVVAAAADDEEGGGG",
+        b"---M------**-------M---------------M---------------M------------"
+    ),
+    genetic_code!(
+        25,
+        "Candidate Division SR1 and Gracilibacteria",
+        b"FFLLSSSSYY**CCGWLLLLPPPPHHQQRRRRIIIMTTTTNNKKSSRRVVVVAAAADDEEGGGG",
+        b"---M------**-----------------------M---------------M------------"
+    ),
+    genetic_code!(
+        26,
+        "Pachysolen tannophilus Nuclear",
+        b"FFLLSSSSYY**CC*WLLLAPPPPHHQQRRRRIIIMTTTTNNKKSSRRVVVVAAAADDEEGGGG",
+        b"----------**--*----M---------------M----------------------------"
+    ),
+    genetic_code!(
+        27,
+        "Karyorelict Nuclear",
+        b"FFLLSSSSYYQQCCWWLLLLPPPPHHQQRRRRIIIMTTTTNNKKSSRRVVVVAAAADDEEGGGG",
+        b"--------------*--------------------M----------------------------"
+    ),
+    genetic_code!(
+        28,
+        "Condylostoma Nuclear",
+        b"FFLLSSSSYYQQCCWWLLLLPPPPHHQQRRRRIIIMTTTTNNKKSSRRVVVVAAAADDEEGGGG",
+        b"----------**--*--------------------M----------------------------"
+    ),
+    genetic_code!(
+        29,
+        "Mesodinium Nuclear",
+        b"FFLLSSSSYYYYCC*WLLLLPPPPHHQQRRRRIIIMTTTTNNKKSSRRVVVVAAAADDEEGGGG",
+        b"--------------*--------------------M----------------------------"
+    ),
+    genetic_code!(
+        30,
+        "Peritrich Nuclear",
+        b"FFLLSSSSYYEECC*WLLLLPPPPHHQQRRRRIIIMTTTTNNKKSSRRVVVVAAAADDEEGGGG",
+        b"--------------*--------------------M----------------------------"
+    ),
+    genetic_code!(
+        31,
+        "Blastocrithidia Nuclear",
+        b"FFLLSSSSYYEECCWWLLLLPPPPHHQQRRRRIIIMTTTTNNKKSSRRVVVVAAAADDEEGGGG",
+        b"----------**-----------------------M----------------------------"
+    ),
+    genetic_code!(
+        33,
+        "Cephalodiscidae Mitochondrial UAA-Tyr",
+        b"FFLLSSSSYYY*CCWWLLLLPPPPHHQQRRRRIIIMTTTTNNKKSSSKVVVVAAAADDEEGGGG",
+        b"---M-------*-------M---------------M---------------M------------"
+    ),
+];
+
+/// Bit sets (see `iupac::bases`) of the bases in the order of the NCBI tables.
+const BASE_ORDER: [u8; 4] = [8, 2, 1, 4];
+
+impl GeneticCode {
+    /// The standard genetic code (table 1).
+    pub fn standard() -> &'static GeneticCode {
+        &GENETIC_CODES[0]
+    }
+
+    /// The genetic code table with the given NCBI ID (e.g. 11 for bacteria).
+    pub fn from_id(id: u8) -> Result<&'static GeneticCode> {
+        GENETIC_CODES
+            .iter()
+            .find(|code| code.id == id)
+            .ok_or(Error::UnknownTable { id })
+    }
+
+    /// The NCBI ID of the table.
+    pub fn id(&self) -> u8 {
+        self.id
+    }
+
+    /// The NCBI name of the table.
+    pub fn name(&self) -> &'static str {
+        self.name
+    }
+
+    /// Indices (into the tables) of all codons the given codon can stand for.
+    fn indices(codon: &[u8]) -> Vec<usize> {
+        let mut indices = vec![0];
+        for &a in codon {
+            let bases = iupac::bases(a);
+            indices = indices
+                .iter()
+                .flat_map(|&i| {
+                    BASE_ORDER
+                        .iter()
+                        .enumerate()
+                        .filter(move |&(_, &b)| bases & b != 0)
+                        .map(move |(j, _)| i * 4 + j)
+                })
+                .collect();
+        }
+        indices
+    }
+
+    /// Translate a single codon, returning `X` if it is ambiguous or invalid.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the codon does not have length 3.
+    pub fn translate_codon(&self, codon: &[u8]) -> u8 {
+        assert_eq!(codon.len(), 3, "Expecting a codon of length 3.");
+        // fast path for unambiguous codons
+        let index = codon.iter().try_fold(0, |index, &a| {
+            let j = BASE_ORDER.iter().position(|&b| b == iupac::bases(a))?;
+            Some(index * 4 + j)
+        });
+        if let Some(index) = index {
+            return self.amino_acids[index];
+        }
+        let mut amino_acids = Self::indices(codon)
+            .into_iter()
+            .map(|index| self.amino_acids[index]);
+        match amino_acids.next() {
+            Some(first) if amino_acids.all(|a| a == first) => first,
+            _ => b'X',
+        }
+    }
+
+    /// Whether the codon is a start codon (including alternative start codons). Ambiguous
+    /// codons are start codons if all codons they can stand for are.
+    pub fn is_start(&self, codon: &[u8]) -> bool {
+        let indices = Self::indices(codon);
+        !indices.is_empty() && indices.iter().all(|&index| self.starts[index] == b'M')
+    }
+
+    /// Whether the codon is a stop codon. Ambiguous codons are stop codons if all codons they
+    /// can stand for are.
+    pub fn is_stop(&self, codon: &[u8]) -> bool {
+        codon.len() == 3 && self.translate_codon(codon) == b'*'
+    }
+
+    /// The start codons of this table (uppercase).
+    pub fn start_codons(&self) -> Vec<[u8; 3]> {
+        self.codons(|index| self.starts[index] == b'M')
+    }
+
+    /// The stop codons of this table (uppercase).
+    pub fn stop_codons(&self) -> Vec<[u8; 3]> {
+        self.codons(|index| self.amino_acids[index] == b'*')
+    }
+
+    fn codons<F: Fn(usize) -> bool>(&self, filter: F) -> Vec<[u8; 3]> {
+        (0..64)
+            .filter(|&index| filter(index))
+            .map(|index| {
+                let base = |i: usize| iupac::code(BASE_ORDER[(index >> (2 * i)) & 3]);
+                [base(2), base(1), base(0)]
+            })
+            .collect()
+    }
+
+    /// Translate the given sequence in its first frame, ignoring a trailing incomplete codon.
+    /// Complexity: O(n).
+    pub fn translate(&self, seq: TextSlice<'_>) -> Text {
+        seq.chunks_exact(3)
+            .map(|codon| self.translate_codon(codon))
+            .collect()
+    }
+
+    /// Translate the given sequence in the given frame.
+    pub fn translate_frame(&self, seq: TextSlice<'_>, frame: Frame) -> Text {
+        match frame.strand {
+            ReqStrand::Reverse => {
+                let revcomp = iupac::revcomp(seq);
+                self.translate(revcomp.get(frame.offset..).unwrap_or_default())
+            }
+            _ => self.translate(seq.get(frame.offset..).unwrap_or_default()),
+        }
+    }
+
+    /// Iterate over the translations of the given sequence in all six frames, with the three
+    /// forward frames first.
+    pub fn six_frames<'a>(&'a self, seq: TextSlice<'a>) -> SixFrames<'a> {
+        SixFrames {
+            code: self,
+            seq,
+            revcomp: iupac::revcomp(seq),
+            frames: Frame::all().iter(),
+        }
+    }
+}
+
+/// A reading frame of a sequence: the offset of the first codon and the strand, where offsets
+/// on the reverse strand refer to the reverse complement.
+#[derive(Copy, Clone, Eq, PartialEq, Ord, PartialOrd, Hash, Debug, Serialize, Deserialize)]
+pub struct Frame {
+    pub offset: usize,
+    pub strand: ReqStrand,
+}
+
+impl Frame {
+    /// The six frames, forward frames first.
+    pub fn all() -> &'static [Frame; 6] {
+        const fn frame(offset: usize, strand: ReqStrand) -> Frame {
+            Frame { offset, strand }
+        }
+        const FRAMES: [Frame; 6] = [
+            frame(0, ReqStrand::Forward),
+            frame(1, ReqStrand::Forward),
+            frame(2, ReqStrand::Forward),
+            frame(0, ReqStrand::Reverse),
+            frame(1, ReqStrand::Reverse),
+            frame(2, ReqStrand::Reverse),
+        ];
+        &FRAMES
+    }
+}
+
+/// Iterator over the six-frame translation of a sequence (see `GeneticCode::six_frames`).
+#[derive(Clone, Debug)]
+pub struct SixFrames<'a> {
+    code: &'a GeneticCode,
+    seq: TextSlice<'a>,
+    revcomp: Text,
+    frames: std::slice::Iter<'static, Frame>,
+}
+
+impl Iterator for SixFrames<'_> {
+    type Item = (Frame, Text);
+
+    fn next(&mut self) -> Option<(Frame, Text)> {
+        let frame = *self.frames.next()?;
+        let seq = match frame.strand {
+            ReqStrand::Reverse => &self.revcomp[..],
+            _ => self.seq,
+        };
+        let protein = self
+            .code
+            .translate(seq.get(frame.offset..).unwrap_or_default());
+        Some((frame, protein))
+    }
+}
+
+/// Translate the given sequence with the standard genetic code (see `GeneticCode::translate`).
+pub fn translate(seq: TextSlice<'_>) -> Text {
+    GeneticCode::standard().translate(seq)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_tables() {
+        for code in GENETIC_CODES {
+            assert_eq!(GeneticCode::from_id(code.id).unwrap(), code);
+            // all tables only reassign a few codons of the standard code
+            let changes = code
+                .amino_acids
+                .iter()
+                .zip(GeneticCode::standard().amino_acids)
+                .filter(|(a, b)| a != b)
+                .count();
+            assert!(changes <= 6, "table {}", code.id);
+            assert!(code.starts.iter().all(|a| b"-M*".contains(a)));
+        }
+        assert!(GENETIC_CODES.windows(2).all(|w| w[0].id < w[1].id));
+        assert_eq!(GeneticCode::from_id(7), Err(Error::UnknownTable { id: 7 }));
+    }
+
+    #[test]
+    fn test_codons() {
+        let standard = GeneticCode::standard();
+        assert_eq!(standard.stop_codons(), [*b"TAA", *b"TAG", *b"TGA"]);
+        assert_eq!(standard.start_codons(), [*b"TTG", *b"CTG", *b"ATG"]);
+        assert!(standard.is_start(b"aug"));
+        assert!(standard.is_stop(b"TRA"));
+        assert!(!standard.is_stop(b"TNA"));
+        assert_eq!(standard.translate_codon(b"TNA"), b'X');
+        assert_eq!(standard.translate_codon(b"GGN"), b'G');
+        assert_eq!(standard.translate_codon(b"A-G"), b'X');
+
+        let bacterial = GeneticCode::from_id(11).unwrap();
+        assert_eq!(bacterial.name(), "Bacterial, Archaeal and Plant Plastid");
+        assert!(bacterial.is_start(b"GTG"));
+        assert_eq!(bacterial.translate(b"GTGAAA"), b"VK");
+    }
+
+    #[test]
+    fn test_six_frames() {
+        let seq = b"ATGAAACCCGGGTTTTAG";
+        let frames = GeneticCode::standard().six_frames(seq).collect::<Vec<_>>();
+        assert_eq!(frames.len(), 6);
+        assert_eq!(frames[0].1, b"MKPGF*");
+        assert_eq!(frames[1].1, b"*NPGF");
+        assert_eq!(frames[3].1, b"LKPGFH");
+        for (frame, protein) in frames {
+            assert_eq!(GeneticCode::standard().translate_frame(seq, frame), protein);
+        }
+    }
+}
@@ -132,16 +132,24 @@ where
                 p
             };
 
-            let posterior_probs: HashMap<PosteriorEvent, LogProb> = universe
+            let posterior_probs = universe
                 .into_iter()
                 .map(|event| {
                     let p = self.posterior.compute(&event, data, &mut joint_prob);
                     (event, p)
                 })
-                .collect();
-            let marginal = LogProb::ln_sum_exp(&posterior_probs.values().cloned().collect_vec());
-
-            (posterior_probs, marginal)
+                .collect_vec();
+            // sum in the order of the universe instead of the (randomized) hash order, such
+            // that the marginal is reproducible to the last bit
+            let marginal =
+                LogProb::ln_sum_exp(&posterior_probs.iter().map(|&(_, p)| p).collect_vec());
+
+            (
+                posterior_probs
+                    .into_iter()
+                    .collect::<HashMap<PosteriorEvent, LogProb>>(),
+                marginal,
+            )
         };
 
         ModelInstance {
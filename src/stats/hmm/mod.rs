@@ -493,6 +493,78 @@ pub fn backward<O, M: Model<O>>(hmm: &M, observations: &[O]) -> (Array2<LogProb>
     (vals, prob)
 }
 
+/// Execute the forward and the backward algorithm and return the posterior probabilities of
+/// the states as `LogProb` values and the overall probability of the observations.
+///
+/// ## Arguments
+///
+/// - `hmm` - the `Model` to compute the posterior probabilities for
+/// - `observations` - a slice of observation values to use in the algorithm
+///
+/// ## Result
+///
+/// The resulting pair (*P*, *p*) is the posterior probability table (`P[[o, s]]` is the
+/// probability of state `s` at observation `o`, given all observations) and the overall
+/// probability for `observations` (as `LogProb`).
+///
+/// ## Type Parameters
+///
+/// - `O` - the observation type
+/// - `M` - type `Model` type
+pub fn posterior<O, M: Model<O>>(hmm: &M, observations: &[O]) -> (Array2<LogProb>, LogProb) {
+    let (mut vals, prob) = forward(hmm, observations);
+    let (prob_table_b, _) = backward(hmm, observations);
+
+    // The backward matrix entries are in reverse order of the observations.
+    let n = observations.len();
+    for (i, mut row) in vals.axis_iter_mut(Axis(0)).enumerate() {
+        for (val, b) in row.iter_mut().zip(prob_table_b.row(n - 1 - i)) {
+            *val = *val + *b - prob;
+        }
+    }
+
+    (vals, prob)
+}
+
+/// Execute posterior decoding, i.e. choose the state with the largest posterior probability
+/// (see `posterior`) for each observation. Other than the result of `viterbi`, the resulting
+/// sequence of states is not necessarily a valid path of the model.
+///
+/// ## Arguments
+///
+/// - `hmm` - the `Model` to run the posterior decoding on
+/// - `observations` - a slice of observation values to use in the algorithm
+///
+/// ## Result
+///
+/// The resulting pair *(s, P)* is the `Vec<State>` of the most probable state for each
+/// observation and the posterior probability of each of these states (as `LogProb`).
+///
+/// ## Type Parameters
+///
+/// - `O` - the observation type
+/// - `M` - type `Model` type
+pub fn posterior_decoding<O, M: Model<O>>(
+    hmm: &M,
+    observations: &[O],
+) -> (Vec<State>, Vec<LogProb>) {
+    let (vals, _) = posterior(hmm, observations);
+    vals.axis_iter(Axis(0))
+        .map(|row| {
+            hmm.states().map(|s| (s, row[*s])).fold(
+                (State(0), LogProb::ln_zero()),
+                |best, (s, p)| {
+                    if p > best.1 {
+                        (s, p)
+                    } else {
+                        best
+                    }
+                },
+            )
+        })
+        .unzip()
+}
+
 /// Execute **one step** of Baum-Welch algorithm to find the maximum likelihood estimate of the parameters of a HMM given a set of observed
 /// feature vector and return the estimated initial state distribution (*π**), estimated transition matrix (*A**),
 ///  estimated emission probabilities matrix (*B**) and end probabilities vector (if the model has declared an end state beforehand).
@@ -1324,6 +1396,44 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_discrete_posterior_toy_example() {
+        // Same toy example as above, with an optional end state.
+        let transition = array![[0.5, 0.5], [0.4, 0.6]];
+        let observation = array![[0.2, 0.3, 0.3, 0.2], [0.3, 0.2, 0.2, 0.3]];
+        let initial = array![0.5, 0.5];
+        let end = array![0.1, 0.3];
+        let hmm = DiscreteEmissionHMM::with_float(&transition, &observation, &initial)
+            .expect("Dimensions should be consistent");
+        let hmm_end =
+            DiscreteEmissionHMMoptEND::with_float(&transition, &observation, &initial, Some(&end))
+                .expect("Dimensions should be consistent");
+
+        let seq = [2, 2, 1, 0, 1, 3, 2, 0, 0];
+        for (vals, prob) in [posterior(&hmm, &seq), posterior(&hmm_end, &seq)] {
+            assert_eq!(vals.dim(), (seq.len(), 2));
+            for row in vals.axis_iter(Axis(0)) {
+                let total = *Prob::from(LogProb::ln_sum_exp(row.to_slice().unwrap()));
+                assert_relative_eq!(1.0, total, epsilon = 1e-5);
+            }
+            assert!(*prob < 0.0);
+        }
+
+        // Other than the Viterbi path, posterior decoding switches between the states.
+        let (path, probs) = posterior_decoding(&hmm, &seq);
+        let expected = [0, 0, 0, 1, 0, 1, 0, 1, 1]
+            .iter()
+            .map(|i| State(*i))
+            .collect::<Vec<State>>();
+        assert_eq!(expected, path);
+        let (vals, _) = posterior(&hmm, &seq);
+        for (i, (s, p)) in path.iter().zip(&probs).enumerate() {
+            assert_eq!(vals[[i, **s]], *p);
+            assert!(*Prob::from(*p) >= 0.5);
+        }
+        assert_relative_eq!(0.61064, *Prob::from(probs[0]), epsilon = 1e-5);
+    }
+
     #[test]
     fn test_gaussian_viterbi_simple_example() {
         let transition = array![[0.5, 0.5], [0.4, 0.6]];
@@ -9,6 +9,7 @@ pub mod bayesian;
 pub mod combinatorics;
 pub mod hmm;
 pub mod pairhmm;
+pub mod popgen;
 pub mod probs;
 
 pub use crate::stats::probs::{LogProb, PHREDProb, Prob};
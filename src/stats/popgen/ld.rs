@@ -0,0 +1,251 @@
+//! Pairwise linkage disequilibrium (LD) statistics between biallelic variants.
+//!
+//! For unphased diploid genotypes, the coefficient of linkage disequilibrium `D` is
+//! estimated by the composite measure of Weir (1979), i.e. half of the covariance of
+//! the allele dosages. `r²` is the squared Pearson correlation of the dosages and
+//! `D'` is `D` normalized by its theoretical maximum given the allele frequencies.
+//! Samples with a missing call at either variant are ignored.
+//!
+//! # Example
+//!
+//! ```
+//! use bio::stats::popgen::ld::{ld, LdWindows};
+//!
+//! let a = [Some(0), Some(1), Some(2), Some(1), None];
+//! let b = [Some(0), Some(1), Some(2), Some(1), Some(2)];
+//! let stats = ld(a.iter().cloned(), b.iter().cloned()).unwrap();
+//! assert_eq!(stats.n, 4);
+//! assert!((stats.r2 - 1.0).abs() < 1e-12);
+//! assert!((stats.d_prime - 1.0).abs() < 1e-12);
+//!
+//! // stream over variants, reporting pairs at most 2 variants apart with r² >= 0.8
+//! let variants = vec![a.to_vec(), b.to_vec(), vec![Some(2), Some(1), Some(0), Some(1), None]];
+//! let pairs: Vec<_> = LdWindows::new(variants.into_iter(), 2, 0.8).collect();
+//! assert_eq!(pairs.len(), 3);
+//! assert_eq!((pairs[0].i, pairs[0].j), (0, 1));
+//! ```
+
+use std::collections::VecDeque;
+
+/// LD statistics of a pair of biallelic variants.
+#[derive(Copy, Clone, PartialEq, Debug, Serialize, Deserialize)]
+pub struct LinkageDisequilibrium {
+    /// Coefficient of linkage disequilibrium.
+    pub d: f64,
+    /// `D` normalized by its maximum possible value given the allele frequencies.
+    pub d_prime: f64,
+    /// Squared correlation coefficient.
+    pub r2: f64,
+    /// Number of samples with a call at both variants.
+    pub n: usize,
+}
+
+/// Calculate LD statistics between two variants given as iterators of allele dosages
+/// over the same samples.
+///
+/// Returns `None` if no sample has a call at both variants or if one of the
+/// variants is monomorphic among those samples.
+///
+/// Complexity: O(n), where n is the number of samples.
+pub fn ld<A, B>(a: A, b: B) -> Option<LinkageDisequilibrium>
+where
+    A: IntoIterator<Item = Option<u8>>,
+    B: IntoIterator<Item = Option<u8>>,
+{
+    let (mut n, mut sx, mut sy, mut sxx, mut syy, mut sxy) = (0usize, 0.0, 0.0, 0.0, 0.0, 0.0);
+    for (x, y) in a.into_iter().zip(b) {
+        if let (Some(x), Some(y)) = (x, y) {
+            let (x, y) = (x as f64, y as f64);
+            n += 1;
+            sx += x;
+            sy += y;
+            sxx += x * x;
+            syy += y * y;
+            sxy += x * y;
+        }
+    }
+    if n == 0 {
+        return None;
+    }
+    let nf = n as f64;
+    let (mx, my) = (sx / nf, sy / nf);
+    let var_x = sxx / nf - mx * mx;
+    let var_y = syy / nf - my * my;
+    if var_x <= 0.0 || var_y <= 0.0 {
+        return None;
+    }
+    let cov = sxy / nf - mx * my;
+    let r2 = (cov * cov / (var_x * var_y)).min(1.0);
+
+    let d = cov / 2.0;
+    let (pa, pb) = (mx / 2.0, my / 2.0);
+    let d_max = if d >= 0.0 {
+        (pa * (1.0 - pb)).min((1.0 - pa) * pb)
+    } else {
+        (pa * pb).min((1.0 - pa) * (1.0 - pb))
+    };
+    let d_prime = if d_max > 0.0 {
+        (d.abs() / d_max).min(1.0)
+    } else {
+        0.0
+    };
+
+    Some(LinkageDisequilibrium { d, d_prime, r2, n })
+}
+
+/// LD statistics of the variants with the given indices (`i < j`) in the stream.
+#[derive(Copy, Clone, PartialEq, Debug, Serialize, Deserialize)]
+pub struct LdPair {
+    pub i: usize,
+    pub j: usize,
+    pub ld: LinkageDisequilibrium,
+}
+
+/// Iterator over all pairs of variants that are at most `window` variants apart,
+/// yielding only pairs with an `r²` of at least the given threshold.
+///
+/// Variants are consumed lazily from the underlying iterator, such that at most
+/// `window + 1` variants are kept in memory at any time.
+/// Pairs are reported ordered by `j`, then by `i`.
+pub struct LdWindows<I, V>
+where
+    I: Iterator<Item = V>,
+    V: AsRef<[Option<u8>]>,
+{
+    variants: I,
+    window: usize,
+    min_r2: f64,
+    buffer: VecDeque<(usize, V)>,
+    pending: VecDeque<LdPair>,
+    next_idx: usize,
+}
+
+impl<I, V> LdWindows<I, V>
+where
+    I: Iterator<Item = V>,
+    V: AsRef<[Option<u8>]>,
+{
+    /// Create a new instance.
+    ///
+    /// # Arguments
+    ///
+    /// * `variants` - iterator over variants, each given as allele dosages per sample
+    /// * `window` - maximum distance (in number of variants) between reported pairs
+    /// * `min_r2` - minimum `r²` of reported pairs
+    pub fn new(variants: I, window: usize, min_r2: f64) -> Self {
+        LdWindows {
+            variants,
+            window,
+            min_r2,
+            buffer: VecDeque::with_capacity(window + 1),
+            pending: VecDeque::new(),
+            next_idx: 0,
+        }
+    }
+}
+
+impl<I, V> Iterator for LdWindows<I, V>
+where
+    I: Iterator<Item = V>,
+    V: AsRef<[Option<u8>]>,
+{
+    type Item = LdPair;
+
+    fn next(&mut self) -> Option<LdPair> {
+        while self.pending.is_empty() {
+            let variant = self.variants.next()?;
+            let j = self.next_idx;
+            self.next_idx += 1;
+
+            while self
+                .buffer
+                .front()
+                .map_or(false, |(i, _)| j - i > self.window)
+            {
+                self.buffer.pop_front();
+            }
+            for (i, other) in &self.buffer {
+                if let Some(stats) = ld(
+                    other.as_ref().iter().cloned(),
+                    variant.as_ref().iter().cloned(),
+                ) {
+                    if stats.r2 >= self.min_r2 {
+                        self.pending.push_back(LdPair {
+                            i: *i,
+                            j,
+                            ld: stats,
+                        });
+                    }
+                }
+            }
+            if self.window > 0 {
+                self.buffer.push_back((j, variant));
+            }
+        }
+        self.pending.pop_front()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn calls(dosages: &[u8]) -> Vec<Option<u8>> {
+        dosages.iter().map(|&d| Some(d)).collect()
+    }
+
+    #[test]
+    fn test_ld_perfect() {
+        let a = calls(&[0, 1, 2, 0, 2]);
+        let stats = ld(a.clone(), a).unwrap();
+        assert_relative_eq!(stats.r2, 1.0, epsilon = 1e-12);
+        assert_relative_eq!(stats.d_prime, 1.0, epsilon = 1e-12);
+        assert!(stats.d > 0.0);
+    }
+
+    #[test]
+    fn test_ld_negative() {
+        let a = calls(&[0, 1, 2, 0, 2]);
+        let b = calls(&[2, 1, 0, 2, 0]);
+        let stats = ld(a, b).unwrap();
+        assert_relative_eq!(stats.r2, 1.0, epsilon = 1e-12);
+        assert_relative_eq!(stats.d_prime, 1.0, epsilon = 1e-12);
+        assert!(stats.d < 0.0);
+    }
+
+    #[test]
+    fn test_ld_independent() {
+        let a = calls(&[0, 0, 2, 2]);
+        let b = calls(&[0, 2, 0, 2]);
+        let stats = ld(a, b).unwrap();
+        assert_relative_eq!(stats.r2, 0.0, epsilon = 1e-12);
+        assert_relative_eq!(stats.d, 0.0, epsilon = 1e-12);
+    }
+
+    #[test]
+    fn test_ld_missing_and_monomorphic() {
+        let a = vec![Some(0), None, Some(2), Some(1)];
+        let b = vec![Some(0), Some(1), None, Some(1)];
+        assert_eq!(ld(a.clone(), b).unwrap().n, 2);
+        assert!(ld(a, calls(&[1, 1, 1, 1])).is_none());
+        assert!(ld(vec![None, None], calls(&[0, 1])).is_none());
+    }
+
+    #[test]
+    fn test_ld_windows() {
+        let variants = vec![
+            calls(&[0, 1, 2, 0]),
+            calls(&[0, 1, 2, 0]),
+            calls(&[0, 2, 0, 2]),
+            calls(&[0, 1, 2, 0]),
+        ];
+        let pairs: Vec<_> = LdWindows::new(variants.iter(), 2, 0.99)
+            .map(|p| (p.i, p.j))
+            .collect();
+        assert_eq!(pairs, vec![(0, 1), (1, 3)]);
+
+        let all: Vec<_> = LdWindows::new(variants.iter(), 3, 0.0).collect();
+        assert_eq!(all.len(), 6);
+        assert_eq!(LdWindows::new(variants.iter(), 0, 0.0).count(), 0);
+    }
+}
@@ -0,0 +1,8 @@
+//! Population genetics statistics over biallelic genotype data.
+//!
+//! Genotypes are represented as alternative allele dosages (`0`, `1` or `2` for
+//! diploid samples), with `None` denoting a missing call.
+
+pub mod ld;
+
+pub use self::ld::{ld, LdPair, LdWindows, LinkageDisequilibrium};
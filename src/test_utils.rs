@@ -0,0 +1,220 @@
+//! Generators of random sequences, mutations and alignments with known ground truth, for
+//! property-based testing of pipelines built on rust-bio. This module is only available with
+//! the feature `test-utils`.
+//!
+//! All generators take a random number generator, such that tests can be made reproducible
+//! with a seeded generator. They can be combined with any property-testing framework, e.g. by
+//! deriving the seed from a generated integer.
+//!
+//! # Example
+//!
+//! ```
+//! use bio::alignment::distance::levenshtein;
+//! use bio::alignment::AlignmentOperation;
+//! use bio::test_utils::{random_dna, Mutator, DNA};
+//! use rand::rngs::StdRng;
+//! use rand::SeedableRng;
+//!
+//! let mut rng = StdRng::seed_from_u64(42);
+//! let reference = random_dna(&mut rng, 100);
+//! let mutator = Mutator {
+//!     substitution_rate: 0.05,
+//!     insertion_rate: 0.01,
+//!     deletion_rate: 0.01,
+//! };
+//! let (read, alignment) = mutator.mutate(&mut rng, &reference, DNA);
+//!
+//! // the alignment describes how the read was derived from the reference
+//! assert_eq!((alignment.xlen, alignment.ylen), (read.len(), reference.len()));
+//! // hence, it bounds the edit distance
+//! let edits = alignment
+//!     .operations
+//!     .iter()
+//!     .filter(|&&op| op != AlignmentOperation::Match)
+//!     .count();
+//! assert!(levenshtein(&read, &reference) as usize <= edits);
+//! ```
+
+use rand::seq::SliceRandom;
+use rand::Rng;
+
+use crate::alignment::{Alignment, AlignmentMode, AlignmentOperation};
+use crate::utils::{Text, TextSlice};
+
+/// The DNA alphabet (upper case, without `N`).
+pub const DNA: &[u8] = b"ACGT";
+/// The 20 standard amino acids (upper case).
+pub const PROTEIN: &[u8] = b"ACDEFGHIKLMNPQRSTVWY";
+
+/// Draw a sequence of the given length with symbols drawn uniformly from the alphabet.
+///
+/// # Panics
+///
+/// Panics if the alphabet is empty and the length is not zero.
+pub fn random_seq<R: Rng + ?Sized>(rng: &mut R, alphabet: &[u8], len: usize) -> Text {
+    (0..len)
+        .map(|_| *alphabet.choose(rng).expect("Expecting non-empty alphabet."))
+        .collect()
+}
+
+/// Draw a random DNA sequence over `ACGT`.
+pub fn random_dna<R: Rng + ?Sized>(rng: &mut R, len: usize) -> Text {
+    random_seq(rng, DNA, len)
+}
+
+/// Draw a random protein sequence over the 20 standard amino acids.
+pub fn random_protein<R: Rng + ?Sized>(rng: &mut R, len: usize) -> Text {
+    random_seq(rng, PROTEIN, len)
+}
+
+/// Replace the symbol at the given position with a different symbol drawn from the alphabet.
+///
+/// # Panics
+///
+/// Panics if the alphabet does not contain a symbol different from the current one.
+pub fn substitute<R: Rng + ?Sized>(rng: &mut R, seq: &mut [u8], pos: usize, alphabet: &[u8]) {
+    let other = alphabet
+        .iter()
+        .filter(|&&a| a != seq[pos])
+        .collect::<Vec<_>>();
+    seq[pos] = **other
+        .choose(rng)
+        .expect("Expecting alphabet with at least two symbols.");
+}
+
+/// Random point mutations, applied independently at each position of a sequence, e.g. to
+/// simulate sequencing reads or diverged homologs.
+#[derive(Copy, Clone, PartialEq, Debug, Serialize, Deserialize)]
+pub struct Mutator {
+    /// Probability of substituting a symbol.
+    pub substitution_rate: f64,
+    /// Probability of inserting a random symbol after each position (and before the first).
+    /// Insertions are extended with the same probability, yielding geometric lengths, hence
+    /// this has to be smaller than 1.
+    pub insertion_rate: f64,
+    /// Probability of deleting a symbol. Consecutive deletions form longer gaps.
+    pub deletion_rate: f64,
+}
+
+impl Mutator {
+    /// Mutate the given sequence (y), returning the mutated sequence (x) and the global
+    /// alignment of x against y that describes the applied mutations, i.e. the ground truth.
+    /// Note that this need not be an optimal alignment under a given scoring; its score is
+    /// left at zero.
+    pub fn mutate<R: Rng + ?Sized>(
+        &self,
+        rng: &mut R,
+        y: TextSlice<'_>,
+        alphabet: &[u8],
+    ) -> (Text, Alignment) {
+        let mut x = Vec::with_capacity(y.len());
+        let mut operations = Vec::with_capacity(y.len());
+        let insert = |rng: &mut R, x: &mut Text, operations: &mut Vec<AlignmentOperation>| {
+            while rng.gen_bool(self.insertion_rate) {
+                x.push(*alphabet.choose(rng).expect("Expecting non-empty alphabet."));
+                operations.push(AlignmentOperation::Ins);
+            }
+        };
+
+        insert(rng, &mut x, &mut operations);
+        for &a in y {
+            if rng.gen_bool(self.deletion_rate) {
+                operations.push(AlignmentOperation::Del);
+            } else {
+                x.push(a);
+                if rng.gen_bool(self.substitution_rate) {
+                    let pos = x.len() - 1;
+                    substitute(rng, &mut x, pos, alphabet);
+                    operations.push(AlignmentOperation::Subst);
+                } else {
+                    operations.push(AlignmentOperation::Match);
+                }
+            }
+            insert(rng, &mut x, &mut operations);
+        }
+
+        let alignment = Alignment {
+            score: 0,
+            xstart: 0,
+            ystart: 0,
+            xend: x.len(),
+            yend: y.len(),
+            xlen: x.len(),
+            ylen: y.len(),
+            operations,
+            mode: AlignmentMode::Global,
+        };
+        (x, alignment)
+    }
+}
+
+/// Draw a random sequence y of the given length from the alphabet and mutate it into x,
+/// returning x, y and their ground truth alignment (see `Mutator::mutate`).
+pub fn random_alignment<R: Rng + ?Sized>(
+    rng: &mut R,
+    alphabet: &[u8],
+    len: usize,
+    mutator: &Mutator,
+) -> (Text, Text, Alignment) {
+    let y = random_seq(rng, alphabet, len);
+    let (x, alignment) = mutator.mutate(rng, &y, alphabet);
+    (x, y, alignment)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::rngs::StdRng;
+    use rand::SeedableRng;
+
+    #[test]
+    fn test_random_alignment() {
+        let mut rng = StdRng::seed_from_u64(42);
+        let mutator = Mutator {
+            substitution_rate: 0.1,
+            insertion_rate: 0.1,
+            deletion_rate: 0.1,
+        };
+        for _ in 0..100 {
+            let (x, y, alignment) = random_alignment(&mut rng, PROTEIN, 50, &mutator);
+            assert!(y.iter().all(|a| PROTEIN.contains(a)));
+
+            // replay the operations
+            let (mut i, mut j) = (0, 0);
+            for op in &alignment.operations {
+                match op {
+                    AlignmentOperation::Match => assert_eq!(x[i], y[j]),
+                    AlignmentOperation::Subst => assert_ne!(x[i], y[j]),
+                    _ => (),
+                }
+                match op {
+                    AlignmentOperation::Ins => i += 1,
+                    AlignmentOperation::Del => j += 1,
+                    _ => {
+                        i += 1;
+                        j += 1;
+                    }
+                }
+            }
+            assert_eq!((i, j), (x.len(), y.len()));
+            assert_eq!((alignment.xend, alignment.yend), (x.len(), y.len()));
+        }
+    }
+
+    #[test]
+    fn test_no_mutations() {
+        let mut rng = StdRng::seed_from_u64(42);
+        let y = random_dna(&mut rng, 20);
+        let (x, alignment) = Mutator {
+            substitution_rate: 0.0,
+            insertion_rate: 0.0,
+            deletion_rate: 0.0,
+        }
+        .mutate(&mut rng, &y, DNA);
+        assert_eq!(x, y);
+        assert!(alignment
+            .operations
+            .iter()
+            .all(|&op| op == AlignmentOperation::Match));
+    }
+}
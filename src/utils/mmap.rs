@@ -0,0 +1,103 @@
+//! Read-only access to the bytes of index files, memory-mapping them on unix systems.
+
+use std::fs;
+use std::io;
+use std::io::prelude::*;
+
+/// The bytes of a file, either memory-mapped or read into memory.
+pub(crate) enum Storage {
+    Owned(Vec<u8>),
+    #[cfg(unix)]
+    Mapped(Mmap),
+}
+
+impl Storage {
+    /// Memory-map the given file on unix systems, otherwise read it entirely.
+    ///
+    /// # Safety
+    ///
+    /// The file must not be modified or truncated (by this or any other process) while the
+    /// storage is alive, since the bytes handed out by `bytes` would change underneath.
+    pub(crate) unsafe fn map(file: &mut fs::File) -> io::Result<Self> {
+        #[cfg(unix)]
+        {
+            // mapping an empty file is not allowed
+            if file.metadata()?.len() > 0 {
+                return Mmap::map(file).map(Storage::Mapped);
+            }
+        }
+        let mut data = Vec::new();
+        file.read_to_end(&mut data)?;
+        Ok(Storage::Owned(data))
+    }
+
+    pub(crate) fn bytes(&self) -> &[u8] {
+        match self {
+            Storage::Owned(data) => data,
+            #[cfg(unix)]
+            Storage::Mapped(data) => data.bytes(),
+        }
+    }
+
+    pub(crate) fn is_mapped(&self) -> bool {
+        match self {
+            Storage::Owned(_) => false,
+            #[cfg(unix)]
+            Storage::Mapped(_) => true,
+        }
+    }
+}
+
+/// A read-only memory mapping of a whole file.
+#[cfg(unix)]
+pub(crate) struct Mmap {
+    ptr: *mut libc::c_void,
+    len: usize,
+}
+
+// The mapping is read-only and owned, hence it can be shared between threads.
+#[cfg(unix)]
+unsafe impl Send for Mmap {}
+#[cfg(unix)]
+unsafe impl Sync for Mmap {}
+
+#[cfg(unix)]
+impl Mmap {
+    /// # Safety
+    ///
+    /// See `Storage::map`.
+    unsafe fn map(file: &fs::File) -> io::Result<Self> {
+        use std::os::unix::io::AsRawFd;
+
+        let len = file.metadata()?.len() as usize;
+        // the whole (non-empty) file is mapped read-only and privately, and failure is checked
+        let ptr = libc::mmap(
+            std::ptr::null_mut(),
+            len,
+            libc::PROT_READ,
+            libc::MAP_PRIVATE,
+            file.as_raw_fd(),
+            0,
+        );
+        if ptr == libc::MAP_FAILED {
+            Err(io::Error::last_os_error())
+        } else {
+            Ok(Mmap { ptr, len })
+        }
+    }
+
+    fn bytes(&self) -> &[u8] {
+        // SAFETY: the mapping is valid for len bytes until it is dropped.
+        unsafe { std::slice::from_raw_parts(self.ptr as *const u8, self.len) }
+    }
+}
+
+#[cfg(unix)]
+impl Drop for Mmap {
+    fn drop(&mut self) {
+        // SAFETY: ptr and len stem from a successful call to mmap.
+        unsafe {
+            libc::munmap(self.ptr, self.len);
+        }
+    }
+}
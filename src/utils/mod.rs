@@ -9,11 +9,18 @@ mod fastexp;
 pub use self::fastexp::FastExp;
 
 mod text;
-pub use self::text::{trim_newline, Text, TextSlice};
+pub use self::text::{trim_newline, window, Text, TextSlice, Window};
 
 mod interval;
 pub use self::interval::Interval;
 
+pub(crate) mod mmap;
+pub mod parallel;
+pub mod seq_collection;
+pub mod seq_hash;
+
+pub use bio_types::strand::{ReqStrand, Strand};
+
 /// In place implementation of scan over a slice.
 pub fn scan<T: Copy, F: Fn(T, T) -> T>(a: &mut [T], op: F) {
     let mut s = a[0];
@@ -0,0 +1,207 @@
+//! Control over the number of threads used by the parallel variants of long-running
+//! algorithms (e.g. `suffix_array::suffix_array_parallel`, `bwt::bwt_parallel`,
+//! `bwt::Occ::new_parallel` and `kmer_counting::KmerCounter::add_all_parallel`).
+//!
+//! Parallel functions take the number of threads to use as an argument, such that CPU usage is
+//! limited per call, e.g. when embedding rust-bio into a server that runs several requests at
+//! once. Passing `0` uses all available cores (see `available_threads`). There is no
+//! process-wide setting, such that concurrent callers cannot interfere with each other's
+//! limits. Threads are scoped to the respective call, i.e. no threads are kept alive in the
+//! background. All other functions of the library run on the calling thread.
+//!
+//! With the feature `wasm` (for WebAssembly targets without thread support), parallel functions
+//! never spawn threads and run on the calling thread regardless of the requested number.
+//!
+//! # Example
+//!
+//! ```
+//! use bio::alphabets::dna;
+//! use bio::data_structures::bwt::{bwt_parallel, Occ};
+//! use bio::data_structures::suffix_array::{suffix_array, suffix_array_parallel};
+//!
+//! // never use more than two threads
+//! let threads = 2;
+//!
+//! let text = b"GCCTTAACATTATTACGCCTA$";
+//! let sa = suffix_array_parallel(text, threads);
+//! assert_eq!(sa, suffix_array(text));
+//! let bwt = bwt_parallel(text, &sa, threads);
+//! let occ = Occ::new_parallel(&bwt, 3, &dna::n_alphabet(), threads);
+//! assert_eq!(occ, Occ::new(&bwt, 3, &dna::n_alphabet()));
+//! ```
+
+use std::thread;
+
+/// The number of threads used by parallel functions that are called with `threads = 0`, i.e.
+/// the available parallelism of the machine, always 1 with the feature `wasm`.
+pub fn available_threads() -> usize {
+    if cfg!(feature = "wasm") {
+        return 1;
+    }
+    thread::available_parallelism().map_or(1, |n| n.get())
+}
+
+/// Resolve the number of threads requested for a call, where `0` means all available cores.
+pub(crate) fn threads(requested: usize) -> usize {
+    if requested == 0 || cfg!(feature = "wasm") {
+        available_threads()
+    } else {
+        requested
+    }
+}
+
+/// Split the items into at most `threads` consecutive chunks, with chunk boundaries at
+/// multiples of `align`, and apply `f` to the offset and items of each chunk in parallel.
+/// Results are returned in the order of the chunks.
+pub(crate) fn map_chunks<T, R, F>(items: &[T], threads: usize, align: usize, f: F) -> Vec<R>
+where
+    T: Sync,
+    R: Send,
+    F: Fn(usize, &[T]) -> R + Sync,
+{
+    let threads = self::threads(threads);
+    // ceil(len / threads), rounded up to a multiple of align
+    let chunk_len = ((items.len() + threads - 1) / threads + align - 1) / align * align;
+    let chunk_len = chunk_len.max(align);
+    if chunk_len >= items.len() {
+        return vec![f(0, items)];
+    }
+
+    let f = &f;
+    thread::scope(|scope| {
+        let handles = items
+            .chunks(chunk_len)
+            .enumerate()
+            .map(|(i, chunk)| scope.spawn(move || f(i * chunk_len, chunk)))
+            .collect::<Vec<_>>();
+        handles
+            .into_iter()
+            .map(|handle| handle.join().expect("bug: worker thread panicked"))
+            .collect()
+    })
+}
+
+/// Split the items into at most `threads` consecutive chunks and apply `f` to the offset and
+/// items of each chunk in parallel.
+pub(crate) fn for_each_chunk_mut<T, F>(items: &mut [T], threads: usize, f: F)
+where
+    T: Send,
+    F: Fn(usize, &mut [T]) + Sync,
+{
+    let threads = self::threads(threads);
+    let chunk_len = ((items.len() + threads - 1) / threads).max(1);
+    if chunk_len >= items.len() {
+        f(0, items);
+        return;
+    }
+
+    let f = &f;
+    thread::scope(|scope| {
+        for (i, chunk) in items.chunks_mut(chunk_len).enumerate() {
+            scope.spawn(move || f(i * chunk_len, chunk));
+        }
+    });
+}
+
+/// Stably sort the items by the given key, sorting at most `threads` chunks in parallel and
+/// merging them pairwise, again in parallel.
+pub(crate) fn sort_by_key<T, K, F>(items: &mut [T], threads: usize, key: F)
+where
+    T: Copy + Send + Sync,
+    K: Ord,
+    F: Fn(&T) -> K + Sync,
+{
+    let threads = self::threads(threads);
+    let n = items.len();
+    let mut width = ((n + threads - 1) / threads).max(1);
+    if width >= n {
+        items.sort_by_key(key);
+        return;
+    }
+
+    let key = &key;
+    for_each_chunk_mut(items, threads, |_, chunk| chunk.sort_by_key(key));
+    let mut src = items.to_vec();
+    let mut dst = src.clone();
+    while width < n {
+        thread::scope(|scope| {
+            for (src, dst) in src.chunks(2 * width).zip(dst.chunks_mut(2 * width)) {
+                scope.spawn(move || {
+                    let (left, right) = src.split_at(width.min(src.len()));
+                    merge(left, right, dst, key);
+                });
+            }
+        });
+        std::mem::swap(&mut src, &mut dst);
+        width *= 2;
+    }
+    items.copy_from_slice(&src);
+}
+
+/// Merge two sorted runs into `out`, preferring the left run on ties.
+fn merge<T: Copy, K: Ord, F: Fn(&T) -> K>(left: &[T], right: &[T], out: &mut [T], key: F) {
+    let (mut i, mut j) = (0, 0);
+    for item in out.iter_mut() {
+        if j == right.len() || (i < left.len() && key(&left[i]) <= key(&right[j])) {
+            *item = left[i];
+            i += 1;
+        } else {
+            *item = right[j];
+            j += 1;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_map_chunks() {
+        let items = (0..100).collect::<Vec<usize>>();
+        for threads in 1..8 {
+            let chunks = map_chunks(&items, threads, 8, |offset, chunk| {
+                assert_eq!(offset % 8, 0);
+                assert_eq!(chunk[0], offset);
+                chunk.iter().sum::<usize>()
+            });
+            assert!(chunks.len() <= threads);
+            if cfg!(feature = "wasm") {
+                assert_eq!(chunks.len(), 1);
+            }
+            assert_eq!(chunks.iter().sum::<usize>(), 4950);
+        }
+        assert_eq!(
+            map_chunks(&[] as &[usize], 4, 1, |_, chunk| chunk.len()),
+            [0]
+        );
+    }
+
+    #[test]
+    fn test_for_each_chunk_mut() {
+        let mut items = vec![0; 100];
+        for_each_chunk_mut(&mut items, 3, |offset, chunk| {
+            for (i, item) in chunk.iter_mut().enumerate() {
+                *item = offset + i;
+            }
+        });
+        assert_eq!(items, (0..100).collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn test_sort_by_key() {
+        let items = (0..1000)
+            .map(|i: usize| (i.wrapping_mul(7919) % 97, i))
+            .collect::<Vec<_>>();
+        let mut expected = items.clone();
+        expected.sort_by_key(|&(k, _)| k);
+        for threads in 1..8 {
+            let mut sorted = items.clone();
+            sort_by_key(&mut sorted, threads, |&(k, _)| k);
+            // stable, i.e. equal keys keep their order
+            assert_eq!(sorted, expected);
+        }
+        let mut empty: [usize; 0] = [];
+        sort_by_key(&mut empty, 4, |&i| i);
+    }
+}
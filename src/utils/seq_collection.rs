@@ -0,0 +1,231 @@
+//! Digests of sequence collections following the GA4GH refget sequence collections
+//! specification, e.g. to verify that a FASTA file matches the reference expected by an
+//! index or an annotation set.
+//!
+//! A collection is described by the arrays of sequence names, lengths and GA4GH sequence
+//! digests (see `seq_hash::ga4gh_digest`). Each array is serialized as canonical JSON
+//! (RFC 8785) and digested with sha512t24u. The digest of the whole collection is computed from
+//! the digests of the names and sequences arrays. Collections that only differ in the order of
+//! their sequences share the digest of their sorted name-length pairs, which hence identifies
+//! the coordinate system.
+//!
+//! # Example
+//!
+//! ```
+//! use bio::io::fasta;
+//! use bio::utils::seq_collection::SequenceCollection;
+//!
+//! let fasta = b">chrX\nTTGGGGAA\n>chr1\nGGAA\n>chr2\nGCGC\n";
+//! let collection = SequenceCollection::from_records(fasta::Reader::new(&fasta[..]).records())
+//!     .unwrap();
+//! assert_eq!(collection.lengths, [8, 4, 4]);
+//! assert_eq!(collection.digest(), "XZlrcEGi6mlopZ2uD8ObHkQB1d0oDwKk");
+//!
+//! // the same sequences in a different order use the same coordinate system
+//! let mut reordered = SequenceCollection::new();
+//! reordered.push("chr1", b"GGAA");
+//! reordered.push("chr2", b"GCGC");
+//! reordered.push("chrX", b"TTGGGGAA");
+//! assert_ne!(reordered.digest(), collection.digest());
+//! assert!(reordered.same_coordinate_system(&collection));
+//! ```
+
+use std::io;
+
+use crate::io::fasta;
+use crate::utils::seq_hash::{normalize, sha512t24u};
+use crate::utils::TextSlice;
+
+/// Serialize a string as canonical JSON (RFC 8785).
+fn json_string(s: &str) -> String {
+    let mut json = String::with_capacity(s.len() + 2);
+    json.push('"');
+    for c in s.chars() {
+        match c {
+            '"' => json.push_str("\\\""),
+            '\\' => json.push_str("\\\\"),
+            '\u{8}' => json.push_str("\\b"),
+            '\u{c}' => json.push_str("\\f"),
+            '\n' => json.push_str("\\n"),
+            '\r' => json.push_str("\\r"),
+            '\t' => json.push_str("\\t"),
+            c if (c as u32) < 0x20 => json.push_str(&format!("\\u{:04x}", c as u32)),
+            c => json.push(c),
+        }
+    }
+    json.push('"');
+    json
+}
+
+/// Serialize already serialized JSON values as array.
+fn json_array<I: IntoIterator<Item = String>>(values: I) -> String {
+    format!("[{}]", values.into_iter().collect::<Vec<_>>().join(","))
+}
+
+/// The level 1 representation of a sequence collection, i.e. the digest of each attribute
+/// array.
+#[derive(Default, Clone, Eq, PartialEq, Ord, PartialOrd, Hash, Debug, Serialize, Deserialize)]
+pub struct Level1Digests {
+    pub names: String,
+    pub lengths: String,
+    pub sequences: String,
+    pub sorted_name_length_pairs: String,
+}
+
+/// A collection of named sequences, e.g. the records of a reference FASTA file, in their
+/// original order.
+#[derive(Default, Clone, Eq, PartialEq, Ord, PartialOrd, Hash, Debug, Serialize, Deserialize)]
+pub struct SequenceCollection {
+    pub names: Vec<String>,
+    pub lengths: Vec<usize>,
+    /// GA4GH digests of the sequences.
+    pub sequences: Vec<String>,
+}
+
+impl SequenceCollection {
+    /// Create an empty collection.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Collect the given FASTA records, e.g. from `fasta::Reader::records`.
+    pub fn from_records<I: IntoIterator<Item = io::Result<fasta::Record>>>(
+        records: I,
+    ) -> io::Result<Self> {
+        let mut collection = Self::new();
+        for record in records {
+            let record = record?;
+            collection.push(record.id(), record.seq());
+        }
+        Ok(collection)
+    }
+
+    /// Append a sequence. The length and digest refer to the normalized sequence (see
+    /// `seq_hash::normalize`).
+    pub fn push(&mut self, name: &str, seq: TextSlice<'_>) {
+        let seq = normalize(seq);
+        self.names.push(name.to_owned());
+        self.lengths.push(seq.len());
+        self.sequences.push(format!("SQ.{}", sha512t24u(&seq)));
+    }
+
+    /// Number of sequences.
+    pub fn len(&self) -> usize {
+        self.names.len()
+    }
+
+    /// Whether the collection is empty.
+    pub fn is_empty(&self) -> bool {
+        self.names.is_empty()
+    }
+
+    /// Digest of the sequence names.
+    pub fn names_digest(&self) -> String {
+        sha512t24u(json_array(self.names.iter().map(|name| json_string(name))).as_bytes())
+    }
+
+    /// Digest of the sequence lengths.
+    pub fn lengths_digest(&self) -> String {
+        sha512t24u(json_array(self.lengths.iter().map(|len| len.to_string())).as_bytes())
+    }
+
+    /// Digest of the sequence digests.
+    pub fn sequences_digest(&self) -> String {
+        sha512t24u(json_array(self.sequences.iter().map(|seq| json_string(seq))).as_bytes())
+    }
+
+    /// Digest of the name-length pairs, independent of the order of the sequences.
+    pub fn sorted_name_length_pairs_digest(&self) -> String {
+        let mut pairs = self
+            .names
+            .iter()
+            .zip(&self.lengths)
+            .map(|(name, len)| {
+                sha512t24u(
+                    format!("{{\"length\":{},\"name\":{}}}", len, json_string(name)).as_bytes(),
+                )
+            })
+            .collect::<Vec<_>>();
+        pairs.sort_unstable();
+        sha512t24u(json_array(pairs.iter().map(|pair| json_string(pair))).as_bytes())
+    }
+
+    /// The digests of all attribute arrays.
+    pub fn level1(&self) -> Level1Digests {
+        Level1Digests {
+            names: self.names_digest(),
+            lengths: self.lengths_digest(),
+            sequences: self.sequences_digest(),
+            sorted_name_length_pairs: self.sorted_name_length_pairs_digest(),
+        }
+    }
+
+    /// The top-level digest of the collection, identifying names and sequences in their
+    /// order.
+    pub fn digest(&self) -> String {
+        sha512t24u(
+            format!(
+                "{{\"names\":{},\"sequences\":{}}}",
+                json_string(&self.names_digest()),
+                json_string(&self.sequences_digest())
+            )
+            .as_bytes(),
+        )
+    }
+
+    /// Whether both collections have the same names and lengths, regardless of their order,
+    /// such that coordinates (e.g. of an annotation) are valid in both.
+    pub fn same_coordinate_system(&self, other: &SequenceCollection) -> bool {
+        self.sorted_name_length_pairs_digest() == other.sorted_name_length_pairs_digest()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn collection() -> SequenceCollection {
+        let mut collection = SequenceCollection::new();
+        collection.push("chrX", b"TTGGGGAA");
+        collection.push("chr1", b"GGAA");
+        collection.push("chr2", b"GCGC");
+        collection
+    }
+
+    #[test]
+    fn test_digests() {
+        // digests computed with an independent implementation of the specification
+        let collection = collection();
+        assert_eq!(
+            collection.sequences,
+            [
+                "SQ.iYtREV555dUFKg2_agSJW6suquUyPpMw",
+                "SQ.YBbVX0dLKG1ieEDCiMmkrTZFt_Z5Vdaj",
+                "SQ.AcLxtBuKEPk_7PGE_H4dGElwZHCujwH6"
+            ]
+        );
+        assert_eq!(
+            collection.level1(),
+            Level1Digests {
+                names: "Fw1r9eRxfOZD98KKrhlYQNEdSRHoVxAG".to_owned(),
+                lengths: "cGRMZIb3AVgkcAfNv39RN7hnT5Chk7RX".to_owned(),
+                sequences: "0uDQVLuHaOZi1u76LjV__yrVUIz9Bwhr".to_owned(),
+                sorted_name_length_pairs: "zjM1Ie9m0zFbqsAnZ6jAJSXuFpKTr40J".to_owned(),
+            }
+        );
+        assert_eq!(collection.digest(), "XZlrcEGi6mlopZ2uD8ObHkQB1d0oDwKk");
+    }
+
+    #[test]
+    fn test_mismatch() {
+        let mut other = collection();
+        other.names[0] = "X".to_owned();
+        assert_ne!(other.digest(), collection().digest());
+        assert!(!other.same_coordinate_system(&collection()));
+    }
+
+    #[test]
+    fn test_json_string() {
+        assert_eq!(json_string("a\"b\\c\n\u{1}é"), "\"a\\\"b\\\\c\\n\\u0001é\"");
+    }
+}
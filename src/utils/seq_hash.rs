@@ -0,0 +1,364 @@
+//! Stable sequence hashing, e.g. for identifying reference sequences across runs, machines and
+//! tools.
+//!
+//! Sequences are normalized before hashing as required by refget and by the `M5` tag of SAM and
+//! CRAM headers: all bytes outside of the printable ASCII range 33 to 126 (e.g. whitespace and
+//! line breaks) are removed and the remaining ones are converted to upper case. The resulting
+//! IDs only depend on the normalized sequence and are identical on all platforms.
+//! Besides MD5 digests, the GA4GH identifiers of refget (truncated SHA-512 digests) are
+//! supported, see `ga4gh_digest`.
+//!
+//! # Example
+//!
+//! ```
+//! use bio::utils::seq_hash::{ga4gh_digest, md5_of_sequence, seq_id64};
+//!
+//! assert_eq!(md5_of_sequence(b"ACGT"), "f1f8f4bf413b16ad135722aa4591043e");
+//! assert_eq!(ga4gh_digest(b"ACGT"), "SQ.aKF498dAxcJAqme6QYQ7EZ07-fiw8Kw2");
+//! // case and line breaks do not matter
+//! assert_eq!(md5_of_sequence(b"ac\ngt"), md5_of_sequence(b"ACGT"));
+//! assert_eq!(seq_id64(b"acgt"), seq_id64(b"ACGT"));
+//! ```
+
+use crate::utils::{Text, TextSlice};
+
+/// Normalize a sequence for hashing by removing all bytes outside of the printable ASCII range
+/// 33 to 126 and converting to upper case.
+pub fn normalize(seq: TextSlice<'_>) -> Text {
+    seq.iter()
+        .filter(|a| (33..=126).contains(*a))
+        .map(|a| a.to_ascii_uppercase())
+        .collect()
+}
+
+/// Per-round shift amounts of MD5.
+const SHIFTS: [u32; 64] = [
+    7, 12, 17, 22, 7, 12, 17, 22, 7, 12, 17, 22, 7, 12, 17, 22, 5, 9, 14, 20, 5, 9, 14, 20, 5, 9,
+    14, 20, 5, 9, 14, 20, 4, 11, 16, 23, 4, 11, 16, 23, 4, 11, 16, 23, 4, 11, 16, 23, 6, 10, 15,
+    21, 6, 10, 15, 21, 6, 10, 15, 21, 6, 10, 15, 21,
+];
+
+/// Binary integer parts of the sines of integers, as defined by MD5 (RFC 1321).
+const SINES: [u32; 64] = [
+    0xd76aa478, 0xe8c7b756, 0x242070db, 0xc1bdceee, 0xf57c0faf, 0x4787c62a, 0xa8304613, 0xfd469501,
+    0x698098d8, 0x8b44f7af, 0xffff5bb1, 0x895cd7be, 0x6b901122, 0xfd987193, 0xa679438e, 0x49b40821,
+    0xf61e2562, 0xc040b340, 0x265e5a51, 0xe9b6c7aa, 0xd62f105d, 0x02441453, 0xd8a1e681, 0xe7d3fbc8,
+    0x21e1cde6, 0xc33707d6, 0xf4d50d87, 0x455a14ed, 0xa9e3e905, 0xfcefa3f8, 0x676f02d9, 0x8d2a4c8a,
+    0xfffa3942, 0x8771f681, 0x6d9d6122, 0xfde5380c, 0xa4beea44, 0x4bdecfa9, 0xf6bb4b60, 0xbebfbc70,
+    0x289b7ec6, 0xeaa127fa, 0xd4ef3085, 0x04881d05, 0xd9d4d039, 0xe6db99e5, 0x1fa27cf8, 0xc4ac5665,
+    0xf4292244, 0x432aff97, 0xab9423a7, 0xfc93a039, 0x655b59c3, 0x8f0ccc92, 0xffeff47d, 0x85845dd1,
+    0x6fa87e4f, 0xfe2ce6e0, 0xa3014314, 0x4e0811a1, 0xf7537e82, 0xbd3af235, 0x2ad7d2bb, 0xeb86d391,
+];
+
+/// Compute the MD5 digest (RFC 1321) of the given bytes, without any normalization.
+/// MD5 is used here for identification only; it is not suitable for security purposes.
+pub fn md5(data: &[u8]) -> [u8; 16] {
+    let mut state: [u32; 4] = [0x67452301, 0xefcdab89, 0x98badcfe, 0x10325476];
+
+    let mut message = data.to_vec();
+    message.push(0x80);
+    while message.len() % 64 != 56 {
+        message.push(0);
+    }
+    message.extend_from_slice(&((data.len() as u64).wrapping_mul(8)).to_le_bytes());
+
+    for block in message.chunks(64) {
+        let mut words = [0u32; 16];
+        for (word, bytes) in words.iter_mut().zip(block.chunks(4)) {
+            *word = u32::from_le_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]);
+        }
+        let [mut a, mut b, mut c, mut d] = state;
+        for i in 0..64 {
+            let (f, g) = match i / 16 {
+                0 => ((b & c) | (!b & d), i),
+                1 => ((d & b) | (!d & c), (5 * i + 1) % 16),
+                2 => (b ^ c ^ d, (3 * i + 5) % 16),
+                _ => (c ^ (b | !d), (7 * i) % 16),
+            };
+            let f = f
+                .wrapping_add(a)
+                .wrapping_add(SINES[i])
+                .wrapping_add(words[g]);
+            a = d;
+            d = c;
+            c = b;
+            b = b.wrapping_add(f.rotate_left(SHIFTS[i]));
+        }
+        for (s, v) in state.iter_mut().zip([a, b, c, d].iter()) {
+            *s = s.wrapping_add(*v);
+        }
+    }
+
+    let mut digest = [0u8; 16];
+    for (bytes, s) in digest.chunks_mut(4).zip(state.iter()) {
+        bytes.copy_from_slice(&s.to_le_bytes());
+    }
+    digest
+}
+
+/// Round constants of SHA-512 (FIPS 180-4).
+const SHA512_ROUNDS: [u64; 80] = [
+    0x428a2f98d728ae22,
+    0x7137449123ef65cd,
+    0xb5c0fbcfec4d3b2f,
+    0xe9b5dba58189dbbc,
+    0x3956c25bf348b538,
+    0x59f111f1b605d019,
+    0x923f82a4af194f9b,
+    0xab1c5ed5da6d8118,
+    0xd807aa98a3030242,
+    0x12835b0145706fbe,
+    0x243185be4ee4b28c,
+    0x550c7dc3d5ffb4e2,
+    0x72be5d74f27b896f,
+    0x80deb1fe3b1696b1,
+    0x9bdc06a725c71235,
+    0xc19bf174cf692694,
+    0xe49b69c19ef14ad2,
+    0xefbe4786384f25e3,
+    0x0fc19dc68b8cd5b5,
+    0x240ca1cc77ac9c65,
+    0x2de92c6f592b0275,
+    0x4a7484aa6ea6e483,
+    0x5cb0a9dcbd41fbd4,
+    0x76f988da831153b5,
+    0x983e5152ee66dfab,
+    0xa831c66d2db43210,
+    0xb00327c898fb213f,
+    0xbf597fc7beef0ee4,
+    0xc6e00bf33da88fc2,
+    0xd5a79147930aa725,
+    0x06ca6351e003826f,
+    0x142929670a0e6e70,
+    0x27b70a8546d22ffc,
+    0x2e1b21385c26c926,
+    0x4d2c6dfc5ac42aed,
+    0x53380d139d95b3df,
+    0x650a73548baf63de,
+    0x766a0abb3c77b2a8,
+    0x81c2c92e47edaee6,
+    0x92722c851482353b,
+    0xa2bfe8a14cf10364,
+    0xa81a664bbc423001,
+    0xc24b8b70d0f89791,
+    0xc76c51a30654be30,
+    0xd192e819d6ef5218,
+    0xd69906245565a910,
+    0xf40e35855771202a,
+    0x106aa07032bbd1b8,
+    0x19a4c116b8d2d0c8,
+    0x1e376c085141ab53,
+    0x2748774cdf8eeb99,
+    0x34b0bcb5e19b48a8,
+    0x391c0cb3c5c95a63,
+    0x4ed8aa4ae3418acb,
+    0x5b9cca4f7763e373,
+    0x682e6ff3d6b2b8a3,
+    0x748f82ee5defb2fc,
+    0x78a5636f43172f60,
+    0x84c87814a1f0ab72,
+    0x8cc702081a6439ec,
+    0x90befffa23631e28,
+    0xa4506cebde82bde9,
+    0xbef9a3f7b2c67915,
+    0xc67178f2e372532b,
+    0xca273eceea26619c,
+    0xd186b8c721c0c207,
+    0xeada7dd6cde0eb1e,
+    0xf57d4f7fee6ed178,
+    0x06f067aa72176fba,
+    0x0a637dc5a2c898a6,
+    0x113f9804bef90dae,
+    0x1b710b35131c471b,
+    0x28db77f523047d84,
+    0x32caab7b40c72493,
+    0x3c9ebe0a15c9bebc,
+    0x431d67c49c100d4c,
+    0x4cc5d4becb3e42b6,
+    0x597f299cfc657e2a,
+    0x5fcb6fab3ad6faec,
+    0x6c44198c4a475817,
+];
+
+/// Compute the SHA-512 digest (FIPS 180-4) of the given bytes, without any normalization.
+pub fn sha512(data: &[u8]) -> [u8; 64] {
+    let mut state: [u64; 8] = [
+        0x6a09e667f3bcc908,
+        0xbb67ae8584caa73b,
+        0x3c6ef372fe94f82b,
+        0xa54ff53a5f1d36f1,
+        0x510e527fade682d1,
+        0x9b05688c2b3e6c1f,
+        0x1f83d9abfb41bd6b,
+        0x5be0cd19137e2179,
+    ];
+
+    let mut message = data.to_vec();
+    message.push(0x80);
+    while message.len() % 128 != 112 {
+        message.push(0);
+    }
+    message.extend_from_slice(&((data.len() as u128).wrapping_mul(8)).to_be_bytes());
+
+    for block in message.chunks(128) {
+        let mut words = [0u64; 80];
+        for (word, bytes) in words.iter_mut().zip(block.chunks(8)) {
+            let mut buf = [0u8; 8];
+            buf.copy_from_slice(bytes);
+            *word = u64::from_be_bytes(buf);
+        }
+        for t in 16..80 {
+            let s0 = words[t - 15].rotate_right(1)
+                ^ words[t - 15].rotate_right(8)
+                ^ (words[t - 15] >> 7);
+            let s1 =
+                words[t - 2].rotate_right(19) ^ words[t - 2].rotate_right(61) ^ (words[t - 2] >> 6);
+            words[t] = words[t - 16]
+                .wrapping_add(s0)
+                .wrapping_add(words[t - 7])
+                .wrapping_add(s1);
+        }
+
+        let [mut a, mut b, mut c, mut d, mut e, mut f, mut g, mut h] = state;
+        for t in 0..80 {
+            let s1 = e.rotate_right(14) ^ e.rotate_right(18) ^ e.rotate_right(41);
+            let ch = (e & f) ^ (!e & g);
+            let t1 = h
+                .wrapping_add(s1)
+                .wrapping_add(ch)
+                .wrapping_add(SHA512_ROUNDS[t])
+                .wrapping_add(words[t]);
+            let s0 = a.rotate_right(28) ^ a.rotate_right(34) ^ a.rotate_right(39);
+            let maj = (a & b) ^ (a & c) ^ (b & c);
+            let t2 = s0.wrapping_add(maj);
+            h = g;
+            g = f;
+            f = e;
+            e = d.wrapping_add(t1);
+            d = c;
+            c = b;
+            b = a;
+            a = t1.wrapping_add(t2);
+        }
+        for (s, v) in state.iter_mut().zip([a, b, c, d, e, f, g, h].iter()) {
+            *s = s.wrapping_add(*v);
+        }
+    }
+
+    let mut digest = [0u8; 64];
+    for (bytes, s) in digest.chunks_mut(8).zip(state.iter()) {
+        bytes.copy_from_slice(&s.to_be_bytes());
+    }
+    digest
+}
+
+/// Encode bytes as URL-safe base64 (RFC 4648) without padding.
+fn base64url(bytes: &[u8]) -> String {
+    const SYMBOLS: &[u8; 64] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789-_";
+    let mut encoded = String::with_capacity((bytes.len() + 2) / 3 * 4);
+    for chunk in bytes.chunks(3) {
+        let value = chunk
+            .iter()
+            .enumerate()
+            .fold(0u32, |value, (i, &b)| value | (b as u32) << (16 - 8 * i));
+        for i in 0..=chunk.len() {
+            encoded.push(SYMBOLS[(value >> (18 - 6 * i)) as usize & 0x3f] as char);
+        }
+    }
+    encoded
+}
+
+/// The sha512t24u digest of the given bytes (without normalization) as used by GA4GH
+/// specifications like refget: the first 24 bytes of the SHA-512 digest, encoded as URL-safe
+/// base64.
+pub fn sha512t24u(data: &[u8]) -> String {
+    base64url(&sha512(data)[..24])
+}
+
+/// The GA4GH identifier of the normalized sequence as used by refget, i.e. its sha512t24u
+/// digest with prefix `SQ.`.
+pub fn ga4gh_digest(seq: TextSlice<'_>) -> String {
+    format!("SQ.{}", sha512t24u(&normalize(seq)))
+}
+
+/// Format bytes as lower case hexadecimal string.
+pub(crate) fn to_hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+/// The MD5 digest of the normalized sequence as lower case hexadecimal string, as used for the
+/// `M5` tag of SAM and CRAM reference sequence headers and by refget.
+pub fn md5_of_sequence(seq: TextSlice<'_>) -> String {
+    to_hex(&md5(&normalize(seq)))
+}
+
+/// A stable 128-bit ID of the normalized sequence (its MD5 digest, read as big endian
+/// integer).
+pub fn seq_id128(seq: TextSlice<'_>) -> u128 {
+    u128::from_be_bytes(md5(&normalize(seq)))
+}
+
+/// A stable 64-bit ID of the normalized sequence (the first 8 bytes of its MD5 digest, read as
+/// big endian integer). With n distinct sequences, collisions occur with probability about
+/// n² / 2^65.
+pub fn seq_id64(seq: TextSlice<'_>) -> u64 {
+    (seq_id128(seq) >> 64) as u64
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_md5() {
+        // test suite of RFC 1321
+        let cases: [(&[u8], &str); 7] = [
+            (b"", "d41d8cd98f00b204e9800998ecf8427e"),
+            (b"a", "0cc175b9c0f1b6a831c399e269772661"),
+            (b"abc", "900150983cd24fb0d6963f7d28e17f72"),
+            (b"message digest", "f96b697d7cb7938d525a2f31aaf161d0"),
+            (
+                b"abcdefghijklmnopqrstuvwxyz",
+                "c3fcd3d76192e4007dfb496cca67e13b",
+            ),
+            (
+                b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789",
+                "d174ab98d277d9f5a5611c2c9f419d9f",
+            ),
+            (
+                b"12345678901234567890123456789012345678901234567890123456789012345678901234567890",
+                "57edf4a22be3c955ac49da2e2107b67a",
+            ),
+        ];
+        for (data, digest) in cases.iter() {
+            assert_eq!(to_hex(&md5(data)), *digest);
+        }
+    }
+
+    #[test]
+    fn test_normalized_ids() {
+        assert_eq!(normalize(b" acg\tT\r\n"), b"ACGT");
+        assert_eq!(seq_id128(b"ACGT"), 0xf1f8f4bf413b16ad135722aa4591043e);
+        assert_eq!(seq_id64(b"ACGT"), 0xf1f8f4bf413b16ad);
+        assert_ne!(seq_id64(b"ACGT"), seq_id64(b"ACGA"));
+    }
+
+    #[test]
+    fn test_sha512() {
+        assert_eq!(
+            to_hex(&sha512(b"abc")),
+            "ddaf35a193617abacc417349ae20413112e6fa4e89a97ea20a9eeee64b55d39a\
+             2192992a274fc1a836ba3c23a3feebbd454d4423643ce80e2a9ac94fa54ca49f"
+        );
+        // multiple blocks
+        let data = b"abcdefghbcdefghicdefghijdefghijkefghijklfghijklmghijklmn\
+                     hijklmnoijklmnopjklmnopqklmnopqrlmnopqrsmnopqrstnopqrstu";
+        assert_eq!(
+            to_hex(&sha512(data)),
+            "8e959b75dae313da8cf4f72814fc143f8f7779c6eb9f7fa17299aeadb6889018\
+             501d289e4900f7e4331b99dec4b5433ac7d329eeb6dd26545e96e55b874be909"
+        );
+        assert_eq!(sha512t24u(b""), "z4PhNX7vuL3xVChQ1m2AB9Yg5AULVxXc");
+        assert_eq!(base64url(b"ab"), "YWI");
+    }
+}
@@ -1,3 +1,5 @@
+use std::borrow::Cow;
+
 /// Type alias for an owned text, i.e. ``Vec<u8>``.
 pub type Text = Vec<u8>;
 /// Type alias for a text slice, i.e. ``&[u8]``.
@@ -10,6 +12,71 @@ pub fn trim_newline(s: &mut String) {
     }
 }
 
+/// A window extracted from a text, see `window`.
+#[derive(Clone, Eq, PartialEq, Hash, Debug)]
+pub struct Window<'a> {
+    /// The window sequence. Borrowed from the text unless padding was added.
+    pub seq: Cow<'a, [u8]>,
+    /// Effective start position (inclusive) of the window in the text, after clamping.
+    pub start: usize,
+    /// Effective end position (exclusive) of the window in the text, after clamping.
+    pub end: usize,
+    /// Number of padding symbols added before the text part of the window.
+    pub left_padding: usize,
+    /// Number of padding symbols added after the text part of the window.
+    pub right_padding: usize,
+}
+
+/// Extract the window `[pos - flank, pos + flank]` (both inclusive) from the given text,
+/// e.g. the context of a variant.
+/// Parts of the window that lie outside of the text are clamped, or filled with the given
+/// padding symbol, such that the window always has length `2 * flank + 1`.
+/// The effective coordinates of the window in the text are always reported.
+///
+/// # Example
+///
+/// ```
+/// use bio::utils::window;
+///
+/// let text = b"ACGTACGT";
+/// let w = window(text, 1, 2, None);
+/// assert_eq!(&w.seq[..], b"ACGT");
+/// assert_eq!((w.start, w.end), (0, 4));
+///
+/// let w = window(text, 1, 2, Some(b'N'));
+/// assert_eq!(&w.seq[..], b"NACGT");
+/// assert_eq!((w.start, w.end, w.left_padding, w.right_padding), (0, 4, 1, 0));
+/// ```
+pub fn window(text: TextSlice<'_>, pos: usize, flank: usize, padding: Option<u8>) -> Window<'_> {
+    let start = pos.saturating_sub(flank).min(text.len());
+    let end = pos.saturating_add(flank).saturating_add(1).min(text.len());
+    let left_padding = flank.saturating_sub(pos).min(2 * flank + 1);
+    let right_padding = 2 * flank + 1 - left_padding - (end - start);
+
+    match padding {
+        Some(pad) if left_padding > 0 || right_padding > 0 => {
+            let mut seq = Vec::with_capacity(2 * flank + 1);
+            seq.resize(left_padding, pad);
+            seq.extend_from_slice(&text[start..end]);
+            seq.resize(2 * flank + 1, pad);
+            Window {
+                seq: Cow::Owned(seq),
+                start,
+                end,
+                left_padding,
+                right_padding,
+            }
+        }
+        _ => Window {
+            seq: Cow::Borrowed(&text[start..end]),
+            start,
+            end,
+            left_padding: 0,
+            right_padding: 0,
+        },
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -36,6 +103,30 @@ mod tests {
         println!("{:?}", s);
     }
 
+    #[test]
+    fn test_window() {
+        let text = b"ACGTACGT";
+        let w = window(text, 4, 1, Some(b'N'));
+        assert_eq!(w.seq, Cow::Borrowed(&b"TAC"[..]));
+        assert_eq!((w.start, w.end), (3, 6));
+
+        let w = window(text, 7, 2, Some(b'N'));
+        assert_eq!(&w.seq[..], b"CGTNN");
+        assert_eq!((w.start, w.end, w.right_padding), (5, 8, 2));
+
+        let w = window(text, 1, 10, None);
+        assert_eq!(&w.seq[..], text);
+
+        // window completely outside of the text
+        let w = window(text, 20, 1, Some(b'N'));
+        assert_eq!(&w.seq[..], b"NNN");
+        assert_eq!(
+            (w.start, w.end, w.left_padding, w.right_padding),
+            (8, 8, 0, 3)
+        );
+        assert!(window(text, 20, 1, None).seq.is_empty());
+    }
+
     #[test]
     fn test_trim_newline_from_string() {
         let mut s = String::from("AGCT\n");